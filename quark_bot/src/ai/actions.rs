@@ -6,12 +6,37 @@ use teloxide::Bot;
 use teloxide::prelude::Requester;
 use teloxide::types::{ChatId, Message};
 
+use crate::ai::gecko_cache;
 use crate::dependencies::BotDependencies;
-use crate::message_history::handler::fetch;
+use crate::market_data::handler::fetch_search_pools;
+use crate::message_history::handler::{MessageHistory, fetch};
+use crate::payment::memo::validate_memo;
 use crate::pending_transactions::dto::PendingTransaction;
+use crate::table_image::{Table, send_table_image};
+
+/// Tells the model the user already has the data as an image, so it shouldn't repeat it
+const TABLE_SENT_NOTE: &str = "[A table image of these pools has already been sent to the user. Do not repeat the full list; reply with a short summary of the highlights and the most relevant links.]";
+
+/// Whether the requesting user wants dense tool output as a table image
+pub fn wants_table_image(msg: &Message, bot_deps: &BotDependencies) -> bool {
+    msg.from
+        .as_ref()
+        .and_then(|user| user.username.as_ref())
+        .map(|username| {
+            bot_deps
+                .user_model_prefs
+                .get_preferences(username)
+                .table_images
+        })
+        .unwrap_or(false)
+}
 
-/// Execute trending pools fetch from GeckoTerminal
-pub async fn execute_trending_pools(arguments: &serde_json::Value) -> String {
+/// Execute trending pools fetch from GeckoTerminal. With a table target the pools are also
+/// sent as an image, and the text goes back to the model as context only.
+pub async fn execute_trending_pools(
+    arguments: &serde_json::Value,
+    table_target: Option<(Bot, Message)>,
+) -> String {
     // Parse arguments
     let network = arguments
         .get("network")
@@ -44,10 +69,53 @@ pub async fn execute_trending_pools(arguments: &serde_json::Value) -> String {
     // Add include parameter for more data
     url.push_str("&include=base_token,quote_token,dex");
 
+    let result = match fetch_trending_pools(&url, network).await {
+        Ok(data) => {
+            let mut result = format_trending_pools_response(&data, network, limit, duration);
+            if let Some((bot, msg)) = &table_target {
+                let table = trending_pools_table(&data, network, limit, duration);
+                if table.rows.is_empty() {
+                    // Nothing to draw; the text already says so
+                } else if let Err(e) = send_table_image(bot, msg, &table).await {
+                    log::warn!("Sending trending pools as text, table image failed: {}", e);
+                } else {
+                    result = format!("{}\n\n{}", TABLE_SENT_NOTE, result);
+                }
+            }
+            // Ensure we never return an empty string to prevent Telegram error
+            if result.trim().is_empty() {
+                format!(
+                    "📊 No trending pools found for {} network. The API returned valid data but no pools matched the criteria.",
+                    network
+                )
+            } else {
+                result
+            }
+        }
+        Err(e) => e,
+    };
+
+    // Final safety check to prevent empty responses
+    if result.trim().is_empty() {
+        format!(
+            "🔧 Debug: Function completed but result was empty. Network: {}, URL attempted",
+            network
+        )
+    } else {
+        result
+    }
+}
+
+/// Query GeckoTerminal's trending pools. Errors are returned as user-facing messages.
+async fn fetch_trending_pools(url: &str, network: &str) -> Result<serde_json::Value, String> {
+    if let Some(data) = gecko_cache::get(url) {
+        return Ok(data);
+    }
+
     // Make HTTP request
     let client = reqwest::Client::new();
-    let result = match client
-        .get(&url)
+    match client
+        .get(url)
         .header("Accept", "application/json")
         .header("User-Agent", "QuarkBot/1.0")
         .send()
@@ -55,34 +123,21 @@ pub async fn execute_trending_pools(arguments: &serde_json::Value) -> String {
     {
         Ok(response) => {
             if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(data) => {
-                        let result =
-                            format_trending_pools_response(&data, network, limit, duration);
-                        // Ensure we never return an empty string to prevent Telegram error
-                        if result.trim().is_empty() {
-                            format!(
-                                "📊 No trending pools found for {} network. The API returned valid data but no pools matched the criteria.",
-                                network
-                            )
-                        } else {
-                            result
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Failed to parse trending pools API response: {}", e);
-                        format!("❌ Error parsing API response: {}", e)
-                    }
-                }
+                let data = response.json::<serde_json::Value>().await.map_err(|e| {
+                    log::error!("Failed to parse trending pools API response: {}", e);
+                    format!("❌ Error parsing API response: {}", e)
+                })?;
+                gecko_cache::insert(url, &data);
+                Ok(data)
             } else if response.status() == 404 {
                 log::error!("Network '{}' not found in trending pools API", network);
-                format!(
+                Err(format!(
                     "❌ Network '{}' not found. Please check the network name and try again.",
                     network
-                )
+                ))
             } else if response.status() == 429 {
                 log::error!("Rate limit exceeded for trending pools API");
-                "⚠️ Rate limit exceeded. GeckoTerminal allows 30 requests per minute. Please try again later.".to_string()
+                Err("⚠️ Rate limit exceeded. GeckoTerminal allows 30 requests per minute. Please try again later.".to_string())
             } else {
                 let status = response.status();
                 let error_text = response
@@ -94,10 +149,10 @@ pub async fn execute_trending_pools(arguments: &serde_json::Value) -> String {
                     status,
                     error_text
                 );
-                format!(
+                Err(format!(
                     "❌ API request failed with status: {} - {}",
                     status, error_text
-                )
+                ))
             }
         }
         Err(e) => {
@@ -105,18 +160,8 @@ pub async fn execute_trending_pools(arguments: &serde_json::Value) -> String {
                 "Network error when calling trending pools GeckoTerminal API: {}",
                 e
             );
-            format!("❌ Network error when calling GeckoTerminal API: {}", e)
+            Err(format!("❌ Network error when calling GeckoTerminal API: {}", e))
         }
-    };
-
-    // Final safety check to prevent empty responses
-    if result.trim().is_empty() {
-        format!(
-            "🔧 Debug: Function completed but result was empty. Network: {}, URL attempted",
-            network
-        )
-    } else {
-        result
     }
 }
 
@@ -412,8 +457,105 @@ fn format_trending_pools_response(
     result
 }
 
+/// One row per pool with the columns that matter at a glance
+fn trending_pools_table(
+    data: &serde_json::Value,
+    network: &str,
+    limit: u32,
+    duration: &str,
+) -> Table {
+    let dex_names: std::collections::HashMap<&str, &str> = data
+        .get("included")
+        .and_then(|d| d.as_array())
+        .map(|included| {
+            included
+                .iter()
+                .filter(|item| item.get("type").and_then(|v| v.as_str()) == Some("dex"))
+                .filter_map(|item| {
+                    Some((
+                        item.get("id")?.as_str()?,
+                        item.get("attributes")?.get("name")?.as_str()?,
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // GeckoTerminal keys price changes as m5/h1/h6/h24
+    let change_key = match duration {
+        "5m" => "m5",
+        "1h" => "h1",
+        "6h" => "h6",
+        _ => "h24",
+    };
+    let change_header = format!("Chg {}", duration);
+
+    let rows = data
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|pools| {
+            pools
+                .iter()
+                .take(limit as usize)
+                .enumerate()
+                .filter_map(|(index, pool)| {
+                    let attributes = pool.get("attributes")?;
+                    let field = |key: &str| {
+                        attributes
+                            .get(key)
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("0")
+                    };
+                    let dex = pool
+                        .get("relationships")
+                        .and_then(|r| r.get("dex"))
+                        .and_then(|r| r.get("data"))
+                        .and_then(|d| d.get("id"))
+                        .and_then(|v| v.as_str())
+                        .map(|id| dex_names.get(id).copied().unwrap_or(id))
+                        .unwrap_or("-");
+                    let change = attributes
+                        .get("price_change_percentage")
+                        .and_then(|v| v.get(change_key))
+                        .and_then(|v| v.as_str())
+                        .and_then(|v| v.parse::<f64>().ok())
+                        .map(|c| format!("{:+.2}%", c))
+                        .unwrap_or_else(|| "-".to_string());
+                    let volume = attributes
+                        .get("volume_usd")
+                        .and_then(|v| v.get("h24"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("0");
+                    Some(vec![
+                        (index + 1).to_string(),
+                        attributes
+                            .get("name")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("Unknown Pool")
+                            .to_string(),
+                        dex.to_string(),
+                        format!("${}", format_price(field("base_token_price_usd"))),
+                        change,
+                        format!("${}", format_large_number(volume)),
+                        format!("${}", format_large_number(field("reserve_in_usd"))),
+                    ])
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Table {
+        title: format!("Trending pools on {} ({})", network.to_uppercase(), duration),
+        headers: ["#", "Pool", "DEX", "Price", change_header.as_str(), "Vol 24h", "Liquidity"]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        rows,
+    }
+}
+
 /// Format large numbers with appropriate suffixes (K, M, B)
-fn format_large_number(num_str: &str) -> String {
+pub(crate) fn format_large_number(num_str: &str) -> String {
     if let Ok(num) = num_str.parse::<f64>() {
         if num >= 1_000_000_000.0 {
             format!("{:.2}B", num / 1_000_000_000.0)
@@ -430,7 +572,7 @@ fn format_large_number(num_str: &str) -> String {
 }
 
 /// Format price with appropriate decimal places
-fn format_price(price_str: &str) -> String {
+pub(crate) fn format_price(price_str: &str) -> String {
     if let Ok(price) = price_str.parse::<f64>() {
         if price >= 1.0 {
             format!("{:.4}", price)
@@ -463,75 +605,19 @@ pub async fn execute_search_pools(arguments: &serde_json::Value) -> String {
         .unwrap_or(1)
         .max(1);
 
-    // Construct GeckoTerminal API URL
-    let mut url = format!(
-        "https://api.geckoterminal.com/api/v2/search/pools?query={}&page={}",
-        urlencoding::encode(query),
-        page
-    );
-    if let Some(net) = network {
-        url.push_str(&format!("&network={}", urlencoding::encode(net)));
-    }
-    url.push_str("&include=base_token,quote_token,dex");
-
-    // Make HTTP request
-    let client = reqwest::Client::new();
-    let result = match client
-        .get(&url)
-        .header("Accept", "application/json")
-        .header("User-Agent", "QuarkBot/1.0")
-        .send()
-        .await
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(data) => {
-                        let result = format_search_pools_response(&data, query, network);
-                        if result.trim().is_empty() {
-                            format!(
-                                "🔍 No pools found for query '{}'. The API returned valid data but no pools matched the criteria.",
-                                query
-                            )
-                        } else {
-                            result
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Failed to parse search pools API response: {}", e);
-                        format!("❌ Error parsing API response: {}", e)
-                    }
-                }
-            } else if response.status() == 404 {
-                log::error!("No pools found for query '{}' (404 response)", query);
-                format!("❌ No pools found for query '{}'.", query)
-            } else if response.status() == 429 {
-                log::error!("Rate limit exceeded for search pools API");
-                "⚠️ Rate limit exceeded. GeckoTerminal allows 30 requests per minute. Please try again later.".to_string()
-            } else {
-                let status = response.status();
-                let error_text = response
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "Unknown error".to_string());
-                log::error!(
-                    "Search pools API request failed with status: {} - {}",
-                    status,
-                    error_text
-                );
+    let result = match fetch_search_pools(query, network, page).await {
+        Ok(data) => {
+            let result = format_search_pools_response(&data, query, network);
+            if result.trim().is_empty() {
                 format!(
-                    "❌ API request failed with status: {} - {}",
-                    status, error_text
+                    "🔍 No pools found for query '{}'. The API returned valid data but no pools matched the criteria.",
+                    query
                 )
+            } else {
+                result
             }
         }
-        Err(e) => {
-            log::error!(
-                "Network error when calling search pools GeckoTerminal API: {}",
-                e
-            );
-            format!("❌ Network error when calling GeckoTerminal API: {}", e)
-        }
+        Err(e) => e,
     };
     if result.trim().is_empty() {
         format!(
@@ -953,6 +1039,182 @@ fn format_new_pools_response(data: &serde_json::Value, network: &str) -> String
     result
 }
 
+/// Candle sizes GeckoTerminal accepts for each OHLCV timeframe
+fn ohlcv_aggregates(timeframe: &str) -> Option<&'static [u64]> {
+    match timeframe {
+        "day" => Some(&[1]),
+        "hour" => Some(&[1, 4, 12]),
+        "minute" => Some(&[1, 5, 15]),
+        _ => None,
+    }
+}
+
+/// Execute OHLCV (candles) fetch for one pool from GeckoTerminal
+pub async fn execute_ohlcv(arguments: &serde_json::Value) -> String {
+    let network = arguments
+        .get("network")
+        .and_then(|v| v.as_str())
+        .unwrap_or("aptos");
+    let pool = match arguments.get("pool_address").and_then(|v| v.as_str()) {
+        Some(p) if !p.trim().is_empty() => p.trim(),
+        _ => {
+            return "❌ Error: 'pool_address' is required. Use search_pools to find the pool for a token first.".to_string();
+        }
+    };
+    let timeframe = arguments
+        .get("timeframe")
+        .and_then(|v| v.as_str())
+        .unwrap_or("hour");
+    let Some(aggregates) = ohlcv_aggregates(timeframe) else {
+        return format!(
+            "❌ Error: timeframe '{}' is not supported. Use 'day', 'hour' or 'minute'.",
+            timeframe
+        );
+    };
+    let aggregate = arguments
+        .get("aggregate")
+        .and_then(|v| v.as_u64())
+        .filter(|a| aggregates.contains(a))
+        .unwrap_or(1);
+    let default_limit = match timeframe {
+        "day" => 30,
+        "hour" => 24,
+        _ => 60,
+    };
+    let limit = arguments
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(default_limit)
+        .clamp(1, 1000);
+
+    let url = format!(
+        "https://api.geckoterminal.com/api/v2/networks/{}/pools/{}/ohlcv/{}?aggregate={}&limit={}&currency=usd",
+        urlencoding::encode(network),
+        urlencoding::encode(pool),
+        timeframe,
+        aggregate,
+        limit
+    );
+
+    let data = match gecko_cache::get(&url) {
+        Some(data) => data,
+        None => {
+            let client = reqwest::Client::new();
+            let response = match client
+                .get(&url)
+                .header("Accept", "application/json")
+                .header("User-Agent", "QuarkBot/1.0")
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    log::error!("Network error when calling OHLCV GeckoTerminal API: {}", e);
+                    return format!("❌ Network error when calling GeckoTerminal API: {}", e);
+                }
+            };
+            if response.status() == 404 {
+                log::error!("Pool '{}' not found on {} (OHLCV 404)", pool, network);
+                return format!(
+                    "❌ Pool '{}' not found on {}. Use search_pools to find the pool address.",
+                    pool, network
+                );
+            } else if response.status() == 429 {
+                log::error!("Rate limit exceeded for OHLCV API");
+                return "⚠️ Rate limit exceeded. GeckoTerminal allows 30 requests per minute. Please try again later.".to_string();
+            } else if !response.status().is_success() {
+                let status = response.status();
+                log::error!("OHLCV API request failed with status: {}", status);
+                return format!("❌ API request failed with status: {}", status);
+            }
+            match response.json::<serde_json::Value>().await {
+                Ok(data) => {
+                    gecko_cache::insert(&url, &data);
+                    data
+                }
+                Err(e) => {
+                    log::error!("Failed to parse OHLCV API response: {}", e);
+                    return format!("❌ Error parsing API response: {}", e);
+                }
+            }
+        }
+    };
+
+    let label = if aggregate > 1 {
+        format!("{} × {}", aggregate, timeframe)
+    } else {
+        timeframe.to_string()
+    };
+    format_ohlcv_response(&data, &label).unwrap_or_else(|| {
+        format!(
+            "📊 No candles returned for pool {} on {} ({}).",
+            pool, network, label
+        )
+    })
+}
+
+/// Compact open/high/low/close summary of a GeckoTerminal OHLCV response
+fn format_ohlcv_response(data: &serde_json::Value, label: &str) -> Option<String> {
+    // Each candle is [timestamp, open, high, low, close, volume], newest first
+    let candles: Vec<[f64; 6]> = data
+        .pointer("/data/attributes/ohlcv_list")?
+        .as_array()?
+        .iter()
+        .filter_map(|candle| {
+            let values = candle.as_array()?;
+            let mut parsed = [0.0; 6];
+            for (slot, value) in parsed.iter_mut().zip(values) {
+                *slot = value
+                    .as_f64()
+                    .or_else(|| value.as_str().and_then(|v| v.parse().ok()))?;
+            }
+            (values.len() >= 6).then_some(parsed)
+        })
+        .collect();
+    let newest = candles.first()?;
+    let oldest = candles.last()?;
+
+    let open = oldest[1];
+    let close = newest[4];
+    let high = candles.iter().map(|c| c[2]).fold(f64::MIN, f64::max);
+    let low = candles.iter().map(|c| c[3]).fold(f64::MAX, f64::min);
+    let volume: f64 = candles.iter().map(|c| c[5]).sum();
+    let change = if open > 0.0 {
+        (close - open) / open * 100.0
+    } else {
+        0.0
+    };
+
+    let symbol = |side: &str| {
+        data.pointer(&format!("/meta/{}/symbol", side))
+            .and_then(|v| v.as_str())
+            .unwrap_or("?")
+            .to_string()
+    };
+    let time = |ts: f64| {
+        chrono::DateTime::from_timestamp(ts as i64, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default()
+    };
+
+    Some(format!(
+        "📊 **{}/{}** ({}, {} candles)\n🕒 {} → {}\n💰 Open: ${} | High: ${} | Low: ${} | Close: ${}\n{} Change: {:+.2}%\n📦 Volume: ${}",
+        symbol("base"),
+        symbol("quote"),
+        label,
+        candles.len(),
+        time(oldest[0]),
+        time(newest[0]),
+        format_price(&open.to_string()),
+        format_price(&high.to_string()),
+        format_price(&low.to_string()),
+        format_price(&close.to_string()),
+        if change >= 0.0 { "📈" } else { "📉" },
+        change,
+        format_large_number(&volume.to_string())
+    ))
+}
+
 /// Execute get time fetch from WorldTimeAPI
 pub async fn execute_get_time(arguments: &serde_json::Value) -> String {
     log::info!("Executing get time tool");
@@ -1156,6 +1418,20 @@ fn format_fear_and_greed_response(data: &serde_json::Value) -> String {
     }
 }
 
+/// Total to pay: decimal tokens as the AI and /send give it, or exact smallest units
+#[derive(Debug, Clone, Copy)]
+pub enum PayAmount {
+    Tokens(f64),
+    Units(u64),
+}
+
+/// Payment stored as pending, ready for the confirmation message
+#[derive(Debug, Clone)]
+pub struct PreparedPayment {
+    pub transaction_id: String,
+    pub summary: String,
+}
+
 pub async fn execute_pay_users(
     arguments: &serde_json::Value,
     bot: Bot,
@@ -1163,12 +1439,38 @@ pub async fn execute_pay_users(
     bot_deps: BotDependencies,
     group_id: Option<String>,
 ) -> String {
-    let mut version = CoinVersion::V1;
-
     let amount = arguments
         .get("amount")
         .and_then(|v| v.as_f64())
         .unwrap_or(0.0);
+
+    match prepare_pay_users(
+        PayAmount::Tokens(amount),
+        arguments,
+        bot,
+        msg,
+        bot_deps,
+        group_id,
+    )
+    .await
+    {
+        Ok(prepared) => prepared.summary,
+        Err(e) => e,
+    }
+}
+
+/// Validate and store a pending payment. `arguments` carries symbol, users and memo in the
+/// `get_pay_users` shape; errors are user-facing messages.
+pub async fn prepare_pay_users(
+    pay_amount: PayAmount,
+    arguments: &serde_json::Value,
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+    group_id: Option<String>,
+) -> Result<PreparedPayment, String> {
+    let mut version = CoinVersion::V1;
+
     let symbol = arguments
         .get("symbol")
         .and_then(|v| v.as_str())
@@ -1184,6 +1486,16 @@ pub async fn execute_pay_users(
         .map(|v| v.as_str().unwrap().to_string())
         .collect::<Vec<_>>();
 
+    let memo = match validate_memo(
+        arguments
+            .get("memo")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default(),
+    ) {
+        Ok(memo) => memo,
+        Err(reason) => return Err(format!("❌ {}", reason)),
+    };
+
     let (token_type, decimals) =
         if symbol.to_lowercase() == "apt" || symbol.to_lowercase() == "aptos" {
             version = CoinVersion::V1;
@@ -1193,7 +1505,7 @@ pub async fn execute_pay_users(
 
             if token.is_err() {
                 log::error!("❌ Error getting token: {}", token.as_ref().err().unwrap());
-                return format!("❌ Error getting token: {}", token.err().unwrap());
+                return Err(format!("❌ Error getting token: {}", token.err().unwrap()));
             }
 
             let token = token.unwrap();
@@ -1208,8 +1520,11 @@ pub async fn execute_pay_users(
             (token_type_result, token.decimals)
         };
 
-    // Convert amount to blockchain format using token decima
-    let blockchain_amount = (amount as f64 * 10_f64.powi(decimals as i32)) as u64;
+    // Convert amount to blockchain format using token decimals; exact units pass through
+    let (blockchain_amount, amount) = match pay_amount {
+        PayAmount::Tokens(amount) => ((amount * 10_f64.powi(decimals as i32)) as u64, amount),
+        PayAmount::Units(units) => (units, units as f64 / 10_f64.powi(decimals as i32)),
+    };
 
     let user_addresses = users
         .iter()
@@ -1229,7 +1544,7 @@ pub async fn execute_pay_users(
 
     if user_addresses.is_empty() {
         log::error!("❌ No users found");
-        return "❌ No users found".to_string();
+        return Err("❌ No users found".to_string());
     }
 
     // Calculate per-user amount for display
@@ -1240,7 +1555,7 @@ pub async fn execute_pay_users(
         user.id.0 as i64
     } else {
         log::error!("❌ Could not get user ID");
-        return "❌ Could not get user ID".to_string();
+        return Err("❌ Could not get user ID".to_string());
     };
 
     // Get JWT token and determine if it's a group transfer
@@ -1252,7 +1567,7 @@ pub async fn execute_pay_users(
                 "❌ Error getting chat administrators: {}",
                 admin_ids.err().unwrap()
             );
-            return "❌ Error getting chat administrators".to_string();
+            return Err("❌ Error getting chat administrators".to_string());
         }
 
         let admin_ids = admin_ids.unwrap();
@@ -1263,14 +1578,14 @@ pub async fn execute_pay_users(
 
         if !is_admin {
             log::error!("❌ User is not an admin");
-            return "❌ Only admins can send tokens to members".to_string();
+            return Err("❌ Only admins can send tokens to members".to_string());
         }
 
         let group_credentials = bot_deps.group.get_credentials(msg.chat.id);
 
         if group_credentials.is_none() {
             log::error!("❌ Group not found");
-            return "❌ Group not found".to_string();
+            return Err("❌ Group not found".to_string());
         }
 
         (group_credentials.unwrap().jwt, true)
@@ -1279,7 +1594,7 @@ pub async fn execute_pay_users(
 
         if user.is_none() {
             log::error!("❌ User not found");
-            return "❌ User not found".to_string();
+            return Err("❌ User not found".to_string());
         }
 
         let user = user.unwrap();
@@ -1288,7 +1603,7 @@ pub async fn execute_pay_users(
 
         if username.is_none() {
             log::error!("❌ Username not found");
-            return "❌ Username not found".to_string();
+            return Err("❌ Username not found".to_string());
         }
 
         let username = username.unwrap();
@@ -1297,12 +1612,33 @@ pub async fn execute_pay_users(
 
         if user_credentials.is_none() {
             log::error!("❌ User not found");
-            return "❌ User not found".to_string();
+            return Err("❌ User not found".to_string());
         }
 
         (user_credentials.unwrap().jwt, false)
     };
 
+    // Payments worth less than the user's auto-confirm threshold skip the accept/reject step.
+    // Without a USD price we can't tell, so fall back to asking for confirmation. The threshold
+    // is a personal setting: group treasury transfers always ask.
+    let auto_confirm_threshold = bot_deps.payment.get_auto_confirm_threshold(user_id);
+    let auto_confirm = if !is_group_transfer && auto_confirm_threshold > 0.0 {
+        match bot_deps.panora.get_token_by_symbol(symbol).await {
+            Ok(token) => token
+                .usd_price
+                .as_deref()
+                .and_then(|p| p.parse::<f64>().ok())
+                .map(|price| amount * price < auto_confirm_threshold)
+                .unwrap_or(false),
+            Err(e) => {
+                log::warn!("Could not price {} for auto-confirm check: {}", symbol, e);
+                false
+            }
+        }
+    } else {
+        false
+    };
+
     // Create pending transaction with 1 minute expiration and unique base64-encoded UUID
     let now = Utc::now().timestamp() as u64;
     let expires_at = now + 60; // 1 minute from now
@@ -1327,6 +1663,8 @@ pub async fn execute_pay_users(
         expires_at,
         chat_id: msg.chat.id.0, // Store the chat ID from the message
         message_id: 0,          // Placeholder - will be updated after message is sent
+        auto_confirm,
+        memo,
     };
 
     // Convert group_id from Option<String> to Option<i64>
@@ -1339,7 +1677,7 @@ pub async fn execute_pay_users(
         &pending_transaction,
     ) {
         log::error!("❌ Failed to store pending transaction: {}", e);
-        return "❌ Failed to prepare transaction".to_string();
+        return Err("❌ Failed to prepare transaction".to_string());
     }
 
     log::info!(
@@ -1347,14 +1685,30 @@ pub async fn execute_pay_users(
         pending_transaction.transaction_id
     );
 
-    // Return summary for AI to incorporate
-    format!(
-        "Confirm sending {:.2} {} total, split evenly among {} users ({:.2} each).",
-        amount,
-        symbol,
-        users.len(),
-        per_user_amount
-    )
+    let summary = if auto_confirm {
+        format!(
+            "Sending {:.2} {} total, split evenly among {} users ({:.2} each). This is below the user's auto-confirm threshold (${:.2}), so it is sent without a confirmation step.",
+            amount,
+            symbol,
+            users.len(),
+            per_user_amount,
+            auto_confirm_threshold
+        )
+    } else {
+        // Summary for AI to incorporate
+        format!(
+            "Confirm sending {:.2} {} total, split evenly among {} users ({:.2} each).",
+            amount,
+            symbol,
+            users.len(),
+            per_user_amount
+        )
+    };
+
+    Ok(PreparedPayment {
+        transaction_id: pending_transaction.transaction_id,
+        summary,
+    })
 }
 
 pub async fn execute_get_wallet_address(
@@ -1743,45 +2097,122 @@ pub async fn execute_fund_account(
     url
 }
 
-/// Execute prices command to display model pricing information
-pub async fn execute_prices(_arguments: &serde_json::Value) -> String {
-    "💰 <b>Model Prices</b> <i>(per 1000 tokens)</i>
+/// USD price per 1000 tokens (or per call for tools), shared by /prices and /rates
+const MODEL_PRICES_USD: [(&str, f64); 3] = [
+    ("gpt-5", 0.00410),
+    ("gpt-5-mini", 0.00082),
+    ("gpt-5-nano (sentinel)", 0.00016),
+];
+
+const TOOL_PRICES_USD: [(&str, f64); 3] = [
+    ("FileSearch", 0.0040),
+    ("ImageGeneration", 0.16),
+    ("WebSearchPreview", 0.0160),
+];
+
+/// Execute prices command to display model pricing information.
+///
+/// With a payment token context (`symbol`, USD price), prices are converted into that
+/// token so group operators can budget in the currency they actually pay with.
+pub async fn execute_prices(
+    _arguments: &serde_json::Value,
+    token_context: Option<(String, Option<f64>)>,
+) -> String {
+    let token_context = token_context.filter(|(_, price)| price.is_none_or(|p| p > 0.0));
+
+    let format_price = |usd: f64| match &token_context {
+        Some((symbol, Some(price))) => format!(
+            "<b>{:.6} {}</b> (${:.5})",
+            usd / price,
+            teloxide::utils::html::escape(symbol),
+            usd
+        ),
+        _ => format!("<b>${:.5}</b>", usd),
+    };
+
+    let models = MODEL_PRICES_USD
+        .iter()
+        .map(|(name, usd)| format!("• <code>{}</code> - {}", name, format_price(*usd)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let tools = TOOL_PRICES_USD
+        .iter()
+        .map(|(name, usd)| format!("• <code>{}</code> - {}", name, format_price(*usd)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let payment_info = match &token_context {
+        Some((symbol, Some(price))) => format!(
+            "💰 Converted at <b>1 {} = ${:.4}</b> (Panora market rate)",
+            teloxide::utils::html::escape(symbol),
+            price
+        ),
+        Some((symbol, None)) => format!(
+            "⚠️ No market price available for <b>{}</b> right now, so prices are shown in USD. You are charged the equivalent amount of {} at the dollar market rate.",
+            teloxide::utils::html::escape(symbol),
+            teloxide::utils::html::escape(symbol)
+        ),
+        None => "💰 Payment is made in <b>your selected payment token (deafult APT)</b> at the <u>dollar market rate</u>".to_string(),
+    };
+
+    format!(
+        "💰 <b>Model Prices</b> <i>(per 1000 tokens)</i>
 
 🤖 <b>AI Models:</b>
-• <code>gpt-5</code> - <b>$0.00410</b>
-• <code>gpt-5-mini</code> - <b>$0.00082</b>
-• <code>gpt-5-nano (sentinel)</code> - <b>$0.00016</b>
+{}
 
 🛠️ <b>Tools:</b>
-• <code>FileSearch</code> - <b>$0.0040</b>
-• <code>ImageGeneration</code> - <b>$0.16</b>
-• <code>WebSearchPreview</code> - <b>$0.0160</b>
+{}
 
 💳 <b>Payment Information:</b>
-💰 Payment is made in <b>your selected payment token (deafult APT)</b> at the <u>dollar market rate</u>
-⚠️ <i>All prices are subject to change based on provider rates</i>"
-        .to_string()
+{}
+⚠️ <i>All prices are subject to change based on provider rates</i>",
+        models, tools, payment_info
+    )
 }
 
-/// Fetch the recent messages from the rolling buffer (up to 30 lines)
-pub async fn execute_get_recent_messages(msg: Message, bot_deps: BotDependencies) -> String {
-    if msg.chat.is_private() {
-        return "This tool is only available in group chats.".into();
-    }
-
-    let lines = fetch(msg.chat.id, bot_deps.history_storage.clone()).await;
-    if lines.is_empty() {
+/// Render buffered history lines for the model, noting when older entries were dropped
+fn format_recent_messages(chat_id: ChatId, history: MessageHistory) -> String {
+    if history.entries.is_empty() {
         return "(No recent messages stored.)".into();
     }
 
-    lines
+    let truncated = history.dropped;
+    let count = history.entries.len();
+
+    let body = history
+        .entries
         .into_iter()
         .map(|e| match e.sender {
             Some(name) => format!("{name}: {}", e.text),
             None => e.text,
         })
         .collect::<Vec<_>>()
-        .join("\n")
+        .join("\n");
+
+    if truncated {
+        log::info!(
+            "Recent messages for chat {} truncated to the last {} entries",
+            chat_id.0,
+            count
+        );
+        format!(
+            "(Note: only the last {} messages are visible; earlier messages in this chat were dropped and are not available as context.)\n{}",
+            count, body
+        )
+    } else {
+        body
+    }
+}
+
+/// Fetch the recent messages from the rolling buffer (up to the group's history limit)
+pub async fn execute_get_recent_messages(msg: Message, bot_deps: BotDependencies) -> String {
+    if msg.chat.is_private() {
+        return "This tool is only available in group chats.".into();
+    }
+
+    let history = fetch(msg.chat.id, bot_deps.history_storage.clone()).await;
+    format_recent_messages(msg.chat.id, history)
 }
 
 /// Core helper for schedules: fetch recent messages by ChatId (no Message required)
@@ -1789,17 +2220,33 @@ pub async fn execute_get_recent_messages_for_chat(
     chat_id: ChatId,
     bot_deps: BotDependencies,
 ) -> String {
-    let lines = fetch(chat_id, bot_deps.history_storage.clone()).await;
-    if lines.is_empty() {
-        return "(No recent messages stored.)".into();
-    }
+    let history = fetch(chat_id, bot_deps.history_storage.clone()).await;
+    format_recent_messages(chat_id, history)
+}
 
-    lines
-        .into_iter()
-        .map(|e| match e.sender {
-            Some(name) => format!("{name}: {}", e.text),
-            None => e.text,
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_ohlcv_response() {
+        let data = serde_json::json!({
+            "data": { "attributes": { "ohlcv_list": [
+                [1_700_007_200, 1.1, 1.3, 1.05, 1.2, 500.0],
+                [1_700_003_600, 1.0, 1.15, 0.9, 1.1, 700.0],
+                [1_700_000_000, 0.95, 1.0, 0.92, 1.0, 800.0]
+            ] } },
+            "meta": { "base": { "symbol": "APT" }, "quote": { "symbol": "USDC" } }
+        });
+
+        let text = format_ohlcv_response(&data, "hour").unwrap();
+        assert!(text.contains("**APT/USDC** (hour, 3 candles)"));
+        assert!(text.contains("Open: $0.950000 | High: $1.3000 | Low: $0.900000 | Close: $1.2000"));
+        assert!(text.contains("📈 Change: +26.32%"));
+        assert!(text.contains("Volume: $2.00K"));
+
+        let empty = serde_json::json!({ "data": { "attributes": { "ohlcv_list": [] } } });
+        assert!(format_ohlcv_response(&empty, "day").is_none());
+        assert!(ohlcv_aggregates("week").is_none());
+    }
 }
@@ -0,0 +1,116 @@
+//! Optional disclaimer appended to AI replies, always or only after market-data tools ran.
+
+use std::env;
+use std::sync::OnceLock;
+
+use open_ai_rust_responses_by_sshift::FunctionCallInfo;
+use serde::{Deserialize, Serialize};
+use teloxide::utils::html;
+
+const DEFAULT_DISCLAIMER_TEXT: &str =
+    "Not financial advice. Market data can be delayed or wrong — do your own research.";
+
+/// Custom tools that return prices or pool/trading data
+const FINANCIAL_TOOLS: [&str; 5] = [
+    "get_trending_pools",
+    "search_pools",
+    "get_new_pools",
+    "get_ohlcv",
+    "get_fear_and_greed_index",
+];
+
+static DISCLAIMER_TEXT: OnceLock<String> = OnceLock::new();
+static DEFAULT_MODE: OnceLock<DisclaimerMode> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DisclaimerMode {
+    #[default]
+    Off,
+    /// Only replies that used a price or trading tool
+    Financial,
+    Always,
+}
+
+impl DisclaimerMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisclaimerMode::Off => "Off",
+            DisclaimerMode::Financial => "Market data replies",
+            DisclaimerMode::Always => "All replies",
+        }
+    }
+
+    /// Cycle order for the settings button
+    pub fn next(&self) -> Self {
+        match self {
+            DisclaimerMode::Off => DisclaimerMode::Financial,
+            DisclaimerMode::Financial => DisclaimerMode::Always,
+            DisclaimerMode::Always => DisclaimerMode::Off,
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Some(DisclaimerMode::Off),
+            "financial" => Some(DisclaimerMode::Financial),
+            "always" => Some(DisclaimerMode::Always),
+            _ => None,
+        }
+    }
+}
+
+/// Operator wording from AI_DISCLAIMER_TEXT
+pub fn disclaimer_text() -> &'static str {
+    DISCLAIMER_TEXT.get_or_init(|| {
+        env::var("AI_DISCLAIMER_TEXT")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_DISCLAIMER_TEXT.to_string())
+    })
+}
+
+/// Mode for chats that haven't picked one, from AI_DISCLAIMER_MODE (off unless set)
+pub fn default_mode() -> DisclaimerMode {
+    *DEFAULT_MODE.get_or_init(|| {
+        env::var("AI_DISCLAIMER_MODE")
+            .ok()
+            .and_then(|v| DisclaimerMode::parse(&v))
+            .unwrap_or_default()
+    })
+}
+
+pub fn used_financial_tools(tool_calls: Option<&[FunctionCallInfo]>) -> bool {
+    tool_calls.is_some_and(|calls| {
+        calls
+            .iter()
+            .any(|call| FINANCIAL_TOOLS.contains(&call.name.as_str()))
+    })
+}
+
+/// Append the disclaimer as its own italic paragraph, so chunking keeps it whole at the end
+pub fn apply(text: &str, mode: DisclaimerMode, used_financial_tools: bool) -> String {
+    let show = match mode {
+        DisclaimerMode::Off => false,
+        DisclaimerMode::Financial => used_financial_tools,
+        DisclaimerMode::Always => true,
+    };
+    if !show {
+        return text.to_string();
+    }
+    format!("{}\n\n<i>⚠️ {}</i>", text, html::escape(disclaimer_text()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_respects_mode() {
+        assert_eq!(apply("hi", DisclaimerMode::Off, true), "hi");
+        assert_eq!(apply("hi", DisclaimerMode::Financial, false), "hi");
+        assert!(apply("hi", DisclaimerMode::Financial, true).starts_with("hi\n\n<i>⚠️ "));
+        assert!(apply("hi", DisclaimerMode::Always, false).ends_with("</i>"));
+    }
+}
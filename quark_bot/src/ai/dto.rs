@@ -1,5 +1,16 @@
 use open_ai_rust_responses_by_sshift::types::{Response, ResponseItem};
 use open_ai_rust_responses_by_sshift::{FunctionCallInfo, Model};
+use serde_json::Value;
+
+/// Maximum number of sources appended to a single reply.
+pub const MAX_SOURCES: usize = 5;
+
+/// A web source cited by the model in its answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    pub url: String,
+    pub title: String,
+}
 
 /// Represents the AI's response, which can include text and/or an image.
 #[derive(Debug)]
@@ -37,6 +48,16 @@ impl AIResponse {
         (web_search, file_search, image_generation, code_interpreter)
     }
 
+    /// Collect unique URL citations annotated on the response output
+    pub fn extract_citations(response: &Response) -> Vec<Citation> {
+        let mut citations = Vec::new();
+        match serde_json::to_value(&response.output) {
+            Ok(value) => collect_citations(&value, &mut citations),
+            Err(e) => log::warn!("Failed to inspect response output for citations: {}", e),
+        }
+        citations
+    }
+
     /// Get raw counts for custom formatting
     pub fn get_tool_usage_counts(&self) -> (u32, u32, u32, u32) {
         (
@@ -151,6 +172,97 @@ impl
     }
 }
 
+fn collect_citations(value: &Value, out: &mut Vec<Citation>) {
+    match value {
+        Value::Object(map) => {
+            if map.get("type").and_then(Value::as_str) == Some("url_citation") {
+                if let Some(url) = map.get("url").and_then(Value::as_str) {
+                    if !url.is_empty() && !out.iter().any(|c| c.url == url) {
+                        let title = map
+                            .get("title")
+                            .and_then(Value::as_str)
+                            .filter(|t| !t.trim().is_empty())
+                            .unwrap_or(url);
+                        out.push(Citation {
+                            url: url.to_string(),
+                            title: title.trim().to_string(),
+                        });
+                    }
+                }
+            }
+            for nested in map.values() {
+                collect_citations(nested, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_citations(item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Render citations as a compact HTML "Sources" block, or `None` when there are none
+pub fn format_sources(citations: &[Citation]) -> Option<String> {
+    if citations.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = citations
+        .iter()
+        .take(MAX_SOURCES)
+        .enumerate()
+        .map(|(i, c)| {
+            format!(
+                "{}. <a href=\"{}\">{}</a>",
+                i + 1,
+                c.url.replace('"', "%22"),
+                teloxide::utils::html::escape(&c.title)
+            )
+        })
+        .collect();
+
+    Some(format!("<b>Sources:</b>\n{}", lines.join("\n")))
+}
+
+/// Result of a JSON-mode request; `value` is `None` when the output wasn't usable JSON
+#[derive(Debug)]
+pub struct StructuredResponse {
+    pub value: Option<Value>,
+    /// Raw model output, kept as the text fallback
+    pub text: String,
+    pub model: Model,
+    pub total_tokens: u32,
+}
+
+/// Parse model output as a JSON object carrying every `required` key of the schema
+pub fn parse_structured_output(text: &str, schema: &Value) -> Option<Value> {
+    let trimmed = text.trim();
+    // Tolerate a fenced ```json block even though the response format should prevent it
+    let body = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+
+    let value: Value = serde_json::from_str(body).ok()?;
+    let object = value.as_object()?;
+
+    let required = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|keys| keys.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if required.iter().all(|key| object.contains_key(*key)) {
+        Some(value)
+    } else {
+        None
+    }
+}
+
 // Backward compatibility constructor
 impl
     From<(
@@ -200,4 +312,52 @@ mod tests {
         assert_eq!(ai_response.image_generation, None);
         assert_eq!(ai_response.code_interpreter, None);
     }
+
+    #[test]
+    fn test_collect_citations_dedupes_and_formats() {
+        let output = serde_json::json!([
+            {
+                "type": "message",
+                "content": [{
+                    "type": "output_text",
+                    "text": "Answer",
+                    "annotations": [
+                        {"type": "url_citation", "url": "https://a.example", "title": "A & B"},
+                        {"type": "url_citation", "url": "https://a.example", "title": "A again"},
+                        {"type": "url_citation", "url": "https://b.example", "title": ""},
+                        {"type": "file_citation", "file_id": "file_1"}
+                    ]
+                }]
+            }
+        ]);
+
+        let mut citations = Vec::new();
+        collect_citations(&output, &mut citations);
+
+        assert_eq!(citations.len(), 2);
+        assert_eq!(citations[1].title, "https://b.example");
+
+        let sources = format_sources(&citations).unwrap();
+        assert!(sources.contains("<a href=\"https://a.example\">A &amp; B</a>"));
+        assert!(format_sources(&[]).is_none());
+    }
+
+    #[test]
+    fn test_parse_structured_output() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"amount": {"type": "number"}, "symbol": {"type": "string"}},
+            "required": ["amount", "symbol"]
+        });
+
+        let parsed = parse_structured_output(r#"{"amount": 5, "symbol": "APT"}"#, &schema).unwrap();
+        assert_eq!(parsed["symbol"], "APT");
+
+        let fenced = "```json\n{\"amount\": 1.5, \"symbol\": \"USDC\"}\n```";
+        assert!(parse_structured_output(fenced, &schema).is_some());
+
+        assert!(parse_structured_output(r#"{"amount": 5}"#, &schema).is_none());
+        assert!(parse_structured_output("Sure! Sending 5 APT.", &schema).is_none());
+        assert!(parse_structured_output("[1, 2]", &schema).is_none());
+    }
 }
@@ -0,0 +1,85 @@
+//! Short-lived cache of successful GeckoTerminal responses, keyed by request URL, so
+//! groups asking for the same pools at once share one call against the 30 requests/minute limit.
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// GeckoTerminal refreshes pool data about this often (GECKO_CACHE_TTL_SECS, 0 disables)
+const DEFAULT_GECKO_CACHE_TTL_SECS: u64 = 30;
+
+struct GeckoCache {
+    ttl: Duration,
+    entries: HashMap<String, (Instant, serde_json::Value)>,
+}
+
+impl GeckoCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&self, url: &str, now: Instant) -> Option<serde_json::Value> {
+        self.entries
+            .get(url)
+            .filter(|(stored_at, _)| now.duration_since(*stored_at) < self.ttl)
+            .map(|(_, body)| body.clone())
+    }
+
+    fn insert(&mut self, url: &str, body: serde_json::Value, now: Instant) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, (stored_at, _)| now.duration_since(*stored_at) < ttl);
+        self.entries.insert(url.to_string(), (now, body));
+    }
+}
+
+fn cache() -> &'static Mutex<GeckoCache> {
+    static CACHE: OnceLock<Mutex<GeckoCache>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        let secs = env::var("GECKO_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|secs| secs.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_GECKO_CACHE_TTL_SECS);
+        Mutex::new(GeckoCache::new(Duration::from_secs(secs)))
+    })
+}
+
+/// A fresh cached body for `url`, if there is one
+pub fn get(url: &str) -> Option<serde_json::Value> {
+    cache().lock().unwrap().get(url, Instant::now())
+}
+
+/// Store a successful (200) response body; error responses must not be stored
+pub fn insert(url: &str, body: &serde_json::Value) {
+    cache()
+        .lock()
+        .unwrap()
+        .insert(url, body.clone(), Instant::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let start = Instant::now();
+        let mut cache = GeckoCache::new(Duration::from_secs(30));
+        cache.insert("url", serde_json::json!({ "data": [] }), start);
+
+        assert!(cache.get("url", start + Duration::from_secs(29)).is_some());
+        assert!(cache.get("url", start + Duration::from_secs(30)).is_none());
+        assert!(cache.get("other", start).is_none());
+
+        let mut disabled = GeckoCache::new(Duration::ZERO);
+        disabled.insert("url", serde_json::json!({}), start);
+        assert!(disabled.get("url", start).is_none());
+    }
+}
@@ -4,6 +4,29 @@ use open_ai_rust_responses_by_sshift::files::FilePurpose;
 use open_ai_rust_responses_by_sshift::vector_stores::{
     AddFileToVectorStoreRequest, CreateVectorStoreRequest,
 };
+use std::env;
+
+/// Operator-managed knowledge base searched by every group's /g alongside its own library
+pub fn default_group_vector_store_id() -> Option<String> {
+    env::var("DEFAULT_GROUP_VECTOR_STORE_ID")
+        .ok()
+        .map(|id| id.trim().to_string())
+        .filter(|id| id.starts_with("vs_"))
+}
+
+/// Vector stores /g file search should use: the group's own library first, then the default
+pub fn group_file_search_stores(group_vector_store_id: Option<String>) -> Vec<String> {
+    let mut stores: Vec<String> = group_vector_store_id
+        .filter(|id| !id.is_empty())
+        .into_iter()
+        .collect();
+    if let Some(default_id) = default_group_vector_store_id() {
+        if !stores.contains(&default_id) {
+            stores.push(default_id);
+        }
+    }
+    stores
+}
 
 pub async fn upload_files_to_group_vector_store(
     group_id: String,
@@ -2,30 +2,51 @@ use crate::ai::actions::{
     execute_fear_and_greed_index, execute_get_recent_messages_for_chat, execute_get_time,
     execute_new_pools, execute_search_pools, execute_trending_pools,
 };
-use crate::ai::dto::AIResponse;
+use crate::ai::dto::{AIResponse, StructuredResponse, format_sources, parse_structured_output};
 use crate::ai::gcs::GcsImageUploader;
+use crate::ai::group_vector_store::group_file_search_stores;
+use crate::ai::metrics::{RequestTiming, record_response_time};
+use crate::ai::openai_client::build_openai_client;
 use crate::ai::prompt::get_prompt;
+use crate::ai::streaming::{StreamingReply, stream_response};
 use crate::ai::tools::{
-    execute_custom_tool, get_all_custom_tools, get_fear_and_greed_index_tool, get_new_pools_tool,
-    get_recent_messages_tool, get_search_pools_tool, get_time_tool, get_trending_pools_tool,
+    execute_custom_tool, filter_enabled_tools, get_enabled_custom_tools,
+    get_fear_and_greed_index_tool, get_new_pools_tool, get_recent_messages_tool,
+    get_search_pools_tool, get_time_tool, get_trending_pools_tool, is_tool_enabled,
+    run_tool_calls, tool_concurrency_for,
 };
+use crate::context_note::NoteScope;
+use crate::context_note::handler::apply_context_note;
 use crate::dependencies::BotDependencies;
 use crate::payment::dto::PaymentPrefs;
+use crate::profile;
 use crate::user_conversation::handler::UserConversations;
 use base64::{Engine as _, engine::general_purpose};
 use open_ai_rust_responses_by_sshift::types::{
-    Include, InputItem, ReasoningParams, Response, ResponseItem, Tool, ToolChoice,
+    Include, InputItem, ReasoningParams, Response, ResponseItem, TextConfig, TextFormat, Tool,
+    ToolChoice,
 };
 use open_ai_rust_responses_by_sshift::{
     Client as OAIClient, FunctionCallInfo, Model, ReasoningEffort, RecoveryPolicy, Request,
 };
 use serde_json;
+use std::time::Instant;
 use teloxide::Bot;
 use teloxide::types::{Message, User};
 
+/// Append the user's persona preset style, if any, to the model instructions
+fn apply_persona_style(instructions: String, style: Option<&str>) -> String {
+    match style {
+        Some(style) => format!("{}\n\nResponse style: {}", instructions, style),
+        None => instructions,
+    }
+}
+
 #[derive(Clone)]
 pub struct AI {
     openai_client: OAIClient,
+    openai_api_key: String,
+    http: reqwest::Client,
     system_prompt: String,
     cloud: GcsImageUploader,
 }
@@ -37,11 +58,13 @@ impl AI {
         // Use default recovery policy for API error handling
         // This provides automatic retry with 1 attempt for seamless experience
         let recovery_policy = RecoveryPolicy::default();
-        let openai_client = OAIClient::new_with_recovery(&openai_api_key, recovery_policy)
+        let openai_client = build_openai_client(&openai_api_key, Some(recovery_policy))
             .expect("Failed to create OpenAI client with recovery policy");
 
         Self {
             openai_client,
+            openai_api_key,
+            http: reqwest::Client::new(),
             system_prompt,
             cloud,
         }
@@ -51,6 +74,18 @@ impl AI {
         &self.openai_client
     }
 
+    /// Create a response, streaming its text into `stream_to` when given
+    async fn create_response(
+        &self,
+        request: Request,
+        stream_to: Option<&mut StreamingReply>,
+    ) -> Result<Response, anyhow::Error> {
+        match stream_to {
+            Some(reply) => stream_response(&self.http, &self.openai_api_key, request, reply).await,
+            None => Ok(self.openai_client.responses.create(request).await?),
+        }
+    }
+
     pub async fn upload_user_images(
         &self,
         image_paths: Vec<(String, String)>,
@@ -90,7 +125,9 @@ impl AI {
         reasoning: Option<ReasoningParams>,
         bot_deps: BotDependencies,
         group_id: Option<String>,
+        mut stream_to: Option<&mut StreamingReply>,
     ) -> Result<AIResponse, anyhow::Error> {
+        let started_at = Instant::now();
         let user: Option<User> = msg.from.clone();
 
         if user.is_none() {
@@ -208,7 +245,7 @@ impl AI {
 
         let min_deposit = (min_deposit as f64 * 10_f64.powi(token_decimals as i32)) as u64;
 
-        if user_balance < min_deposit as i64 {
+        if user_balance < min_deposit as i64 && !profile::current().test_shortcuts() {
             let min_deposit_formatted = format!(
                 "{:.2}",
                 min_deposit as f64 / 10_f64.powi(token_decimals as i32)
@@ -259,15 +296,21 @@ impl AI {
         let mut total_tokens_used = 0u32;
 
         // Strict separation: group vs user vector stores
-        let vector_store_id = if group_id.is_some() {
-            // For /g commands: ONLY use group vector store, NO fallback to user
+        let vector_store_ids = if group_id.is_some() {
+            // For /g commands: the group's library plus the operator default, NO fallback to user
             let group_id_str = group_id.as_ref().unwrap();
-            bot_deps
-                .group_docs
-                .get_group_vector_store_id(group_id_str.clone())
+            group_file_search_stores(
+                bot_deps
+                    .group_docs
+                    .get_group_vector_store_id(group_id_str.clone()),
+            )
         } else {
             // For /c commands: ONLY use user vector store
-            user_convos.get_vector_store_id(user_id)
+            user_convos
+                .get_vector_store_id(user_id)
+                .filter(|vs_id| !vs_id.is_empty())
+                .into_iter()
+                .collect::<Vec<_>>()
         };
 
         // Enhanced tools: built-in tools + custom function tools
@@ -284,15 +327,13 @@ impl AI {
         // Add web search for all models
         tools.push(Tool::web_search_preview());
 
-        if let Some(vs_id) = vector_store_id.clone() {
-            if !vs_id.is_empty() {
-                tools.push(Tool::file_search(vec![vs_id]));
-            }
+        if !vector_store_ids.is_empty() {
+            tools.push(Tool::file_search(vector_store_ids.clone()));
         }
 
         // Add custom function tools (get_balance, withdraw_funds, recent_messages, etc.)
-        // Full set is available for /c and /g
-        tools.extend(get_all_custom_tools());
+        // Full set is available for /c and /g, minus any disabled via DISABLED_TOOLS
+        tools.extend(get_enabled_custom_tools());
 
         let user = if group_id.is_some() {
             format!("group-{}", group_id.clone().unwrap())
@@ -312,9 +353,42 @@ impl AI {
             system_prompt
         };
 
+        let note_scope = if group_id.is_some() {
+            NoteScope::Group(msg.chat.id.0)
+        } else {
+            NoteScope::User(user_id)
+        };
+        let mut final_system_prompt =
+            apply_context_note(&bot_deps, note_scope, final_system_prompt);
+
+        // Opt-in: let /g answer from the group's pinned rules/FAQ
+        if let Some(gid) = &group_id {
+            if bot_deps
+                .command_settings
+                .get_command_settings(gid.clone())
+                .use_pinned_message
+            {
+                if let Some(pinned) = bot_deps.pinned_messages.get(&bot, msg.chat.id).await {
+                    final_system_prompt = format!(
+                        "{}\n\nPinned message in this group (use it when relevant, e.g. for rules or FAQs):\n{}",
+                        final_system_prompt, pinned
+                    );
+                }
+            }
+        }
+
+        let prefs = msg
+            .from
+            .as_ref()
+            .and_then(|u| u.username.as_ref())
+            .map(|username| bot_deps.user_model_prefs.get_preferences(username));
+
+        let persona_style = prefs.as_ref().and_then(|p| p.persona.instructions());
+        let final_system_prompt = apply_persona_style(final_system_prompt, persona_style);
+
         let mut request_builder = Request::builder()
             .model(model.clone())
-            .instructions(final_system_prompt)
+            .instructions(final_system_prompt.clone())
             .tools(tools.clone())
             .tool_choice(ToolChoice::auto())
             .parallel_tool_calls(true) // Enable parallel execution for efficiency
@@ -323,13 +397,11 @@ impl AI {
             .store(true);
 
         // Apply user preferences based on model family
-        if let Some(username) = msg.from.as_ref().and_then(|u| u.username.clone()) {
-            let prefs = bot_deps.user_model_prefs.get_preferences(&username);
-
+        if let Some(prefs) = prefs {
             match model {
                 Model::GPT5 | Model::GPT5Mini => {
-                    // GPT-5: apply verbosity and reasoning from user preferences
-                    let verbosity = prefs.verbosity.to_openai_verbosity();
+                    // GPT-5: apply verbosity (persona first) and reasoning from user preferences
+                    let verbosity = prefs.effective_verbosity().to_openai_verbosity();
                     request_builder = request_builder.verbosity(verbosity);
 
                     // Apply reasoning if enabled (always low effort)
@@ -372,7 +444,7 @@ impl AI {
             // No images ⇒ plain text input as before
             request_builder = request_builder.input(input);
             // With no vision payload we can safely include file-search results if user has a vector store
-            if vector_store_id.is_some() {
+            if !vector_store_ids.is_empty() {
                 request_builder = request_builder.include(vec![Include::FileSearchResults]);
             }
         }
@@ -391,9 +463,7 @@ impl AI {
 
         log::info!("About to call OpenAI API...");
         let mut current_response: Response = match self
-            .openai_client
-            .responses
-            .create(request)
+            .create_response(request, stream_to.as_deref_mut())
             .await
         {
             Ok(response) => {
@@ -454,7 +524,7 @@ impl AI {
                     }
                 }
 
-                return Err(e.into());
+                return Err(e);
             }
         };
 
@@ -480,9 +550,10 @@ impl AI {
                 log::info!("Tool call found: {} with call_id: {}", tc.name, tc.call_id);
             }
 
-            // Filter for custom function calls (get_balance, get_wallet_address, withdraw_funds, fund_account, get_trending_pools, search_pools, get_current_time, get_fear_and_greed_index, get_pay_users, get_recent_messages)
+            // Filter for custom function calls (get_balance, get_wallet_address, withdraw_funds, fund_account, get_trending_pools, search_pools, get_ohlcv, get_current_time, get_fear_and_greed_index, get_pay_users, get_recent_messages)
             let custom_tool_calls: Vec<_> = tool_calls
                 .iter()
+                .filter(|tc| is_tool_enabled(&tc.name))
                 .filter(|tc| {
                     tc.name == "get_balance"
                         || tc.name == "get_wallet_address"
@@ -491,6 +562,7 @@ impl AI {
                         || tc.name == "get_trending_pools"
                         || tc.name == "search_pools"
                         || tc.name == "get_new_pools"
+                        || tc.name == "get_ohlcv"
                         || tc.name == "get_current_time"
                         || tc.name == "get_fear_and_greed_index"
                         || tc.name == "get_pay_users"
@@ -508,58 +580,63 @@ impl AI {
             );
 
             if !custom_tool_calls.is_empty() {
-                // Handle parallel custom function calls
-                let mut function_outputs = Vec::new();
-                for tool_call in &custom_tool_calls {
-                    log::info!(
-                        "Executing custom tool: {} with call_id: {}",
-                        tool_call.name,
-                        tool_call.call_id
-                    );
-
-                    // Parse arguments as JSON Value for execute_custom_tool
-                    let args_value: serde_json::Value = serde_json::from_str(&tool_call.arguments)
-                        .unwrap_or_else(|e| {
-                            log::error!("Failed to parse tool arguments: {}", e);
-                            serde_json::json!({})
-                        });
-
-                    let result = execute_custom_tool(
-                        &tool_call.name,
-                        &args_value,
-                        bot.clone(),
-                        msg.clone(),
-                        group_id.clone(),
-                        bot_deps.clone(),
-                    )
-                    .await;
-
-                    log::info!(
-                        "Tool {} executed successfully, result length: {}",
-                        tool_call.name,
-                        result.len()
-                    );
-
-                    // Ensure result is not empty
-                    let final_result = if result.trim().is_empty() {
-                        log::warn!(
-                            "Tool {} returned empty result, providing default",
-                            tool_call.name
+                // Handle parallel custom function calls; outputs keep the order of the calls
+                let calls = custom_tool_calls
+                    .iter()
+                    .map(|tool_call| {
+                        log::info!(
+                            "Executing custom tool: {} with call_id: {}",
+                            tool_call.name,
+                            tool_call.call_id
                         );
-                        format!("Tool '{}' completed but returned no output", tool_call.name)
-                    } else {
-                        result
-                    };
 
-                    function_outputs.push((tool_call.call_id.clone(), final_result));
-                }
+                        // Parse arguments as JSON Value for execute_custom_tool
+                        let args_value: serde_json::Value =
+                            serde_json::from_str(&tool_call.arguments).unwrap_or_else(|e| {
+                                log::error!("Failed to parse tool arguments: {}", e);
+                                serde_json::json!({})
+                            });
+
+                        let name = tool_call.name.clone();
+                        let (bot, msg, group_id, bot_deps) =
+                            (bot.clone(), msg.clone(), group_id.clone(), bot_deps.clone());
+                        let call = async move {
+                            let result =
+                                execute_custom_tool(&name, &args_value, bot, msg, group_id, bot_deps)
+                                    .await;
+
+                            log::info!(
+                                "Tool {} executed successfully, result length: {}",
+                                name,
+                                result.len()
+                            );
+
+                            // Ensure result is not empty
+                            if result.trim().is_empty() {
+                                log::warn!(
+                                    "Tool {} returned empty result, providing default",
+                                    name
+                                );
+                                format!("Tool '{}' completed but returned no output", name)
+                            } else {
+                                result
+                            }
+                        };
+                        (tool_call.call_id.clone(), call)
+                    })
+                    .collect();
+                let function_outputs = run_tool_calls(
+                    calls,
+                    tool_concurrency_for(custom_tool_calls.iter().map(|tc| tc.name.as_str())),
+                )
+                .await;
 
                 // Submit tool outputs using Responses API pattern (with_function_outputs)
                 let mut continuation_builder = Request::builder()
                     .model(model.clone())
                     .with_function_outputs(current_response.id(), function_outputs)
                     .tools(tools.clone()) // Keep tools available for follow-ups
-                    .instructions(self.system_prompt.clone())
+                    .instructions(final_system_prompt.clone())
                     .parallel_tool_calls(true)
                     .max_output_tokens(max_tokens)
                     .user(&format!("user-{}", user_id))
@@ -573,9 +650,7 @@ impl AI {
 
                 log::info!("Making continuation request to OpenAI");
                 current_response = self
-                    .openai_client
-                    .responses
-                    .create(continuation_request)
+                    .create_response(continuation_request, stream_to.as_deref_mut())
                     .await?;
                 log::info!("Continuation request completed");
 
@@ -604,6 +679,24 @@ impl AI {
         let mut reply = current_response.output_text();
         let response_id = current_response.id().to_string();
 
+        // Append cited web sources unless the user has turned them off
+        let show_sources = user
+            .username
+            .as_ref()
+            .map(|username| {
+                bot_deps
+                    .user_model_prefs
+                    .get_preferences(username)
+                    .show_sources
+            })
+            .unwrap_or(true);
+        if show_sources {
+            let citations = AIResponse::extract_citations(&current_response);
+            if let Some(sources) = format_sources(&citations) {
+                reply = format!("{}\n\n{}", reply, sources);
+            }
+        }
+
         // Save response ID for future conversation context
         user_convos.set_response_id(user_id, &response_id)?;
         log::info!(
@@ -690,6 +783,13 @@ impl AI {
                             .await
                         {
                             Ok(url) => {
+                                if let Err(e) = user_convos.push_image_url(user_id, &url) {
+                                    log::warn!(
+                                        "Failed to remember image for user {}: {}",
+                                        user_id,
+                                        e
+                                    );
+                                }
                                 reply = format!(
                                     "{}\n\n<a href=\"{}\">Your image for download</a>",
                                     reply, url
@@ -728,6 +828,23 @@ impl AI {
             code_interpreter_count
         );
 
+        record_response_time(
+            started_at.elapsed(),
+            &RequestTiming {
+                source: if group_id.is_some() { "group_chat" } else { "chat" },
+                model: format!("{:?}", model),
+                tool_iterations: iteration - 1,
+                function_calls: tool_called.len(),
+                builtin_tools: (
+                    web_search_count,
+                    file_search_count,
+                    image_generation_count,
+                    code_interpreter_count,
+                ),
+                total_tokens: total_tokens_used,
+            },
+        );
+
         Ok(AIResponse::from((
             reply,
             model,
@@ -741,6 +858,79 @@ impl AI {
         )))
     }
 
+    /// One-off request whose output is constrained to `schema` via the JSON response format.
+    ///
+    /// Runs without tools or conversation state so integrations get a predictable shape.
+    /// If the output doesn't parse or misses required keys, `value` is `None` and the
+    /// caller can fall back to `text`.
+    pub async fn generate_structured_response(
+        &self,
+        instructions: &str,
+        input: &str,
+        schema_name: &str,
+        schema: serde_json::Value,
+        model: Model,
+        max_tokens: u32,
+    ) -> Result<StructuredResponse, anyhow::Error> {
+        let request = Request::builder()
+            .model(model.clone())
+            .instructions(instructions.to_string())
+            .input(input.to_string())
+            .text(TextConfig {
+                format: TextFormat::JsonSchema {
+                    name: schema_name.to_string(),
+                    schema: schema.clone(),
+                    strict: true,
+                },
+                verbosity: None,
+            })
+            .max_output_tokens(max_tokens)
+            .store(false)
+            .build();
+
+        let response = self.openai_client.responses.create(request).await?;
+        let text = response.output_text();
+        let total_tokens = response.usage.as_ref().map(|u| u.total_tokens).unwrap_or(0);
+
+        let value = parse_structured_output(&text, &schema);
+        if value.is_none() {
+            log::warn!(
+                "Structured output for '{}' did not match its schema, falling back to text",
+                schema_name
+            );
+        }
+
+        Ok(StructuredResponse {
+            value,
+            text,
+            model,
+            total_tokens,
+        })
+    }
+
+    /// One-off plain-text completion with no conversation state or tools.
+    /// Returns the text and total tokens used for billing.
+    pub async fn generate_text_response(
+        &self,
+        instructions: &str,
+        input: &str,
+        model: Model,
+        max_tokens: u32,
+    ) -> Result<(String, u32), anyhow::Error> {
+        let request = Request::builder()
+            .model(model)
+            .instructions(instructions.to_string())
+            .input(input.to_string())
+            .max_output_tokens(max_tokens)
+            .store(false)
+            .build();
+
+        let response = self.openai_client.responses.create(request).await?;
+        let total_tokens = response.usage.as_ref().map(|u| u.total_tokens).unwrap_or(0);
+
+        Ok((response.output_text(), total_tokens))
+    }
+
     /// Generate a response for a scheduled prompt in a group context, using a
     /// per-schedule conversation thread. Returns the AIResponse and the new
     /// response_id. Does not persist response_id in user_conversations.
@@ -757,6 +947,7 @@ impl AI {
         creator_user_id: i64,
         creator_username: String,
     ) -> Result<(AIResponse, String), anyhow::Error> {
+        let started_at = Instant::now();
         // Validate group credentials
         let group_chat_id_i64: i64 = group_id.parse().unwrap_or(0);
         let group_chat_id = teloxide::types::ChatId(group_chat_id_i64 as i64);
@@ -813,9 +1004,10 @@ impl AI {
             )));
         }
 
-        // Use the group's vector store (if any)
+        // Use the group's vector store (if any) plus the operator default
         let group_docs = &bot_deps.group_docs;
-        let vector_store_id = group_docs.get_group_vector_store_id(group_id.clone());
+        let vector_store_ids =
+            group_file_search_stores(group_docs.get_group_vector_store_id(group_id.clone()));
 
         // Tools setup
         let mut tools = vec![];
@@ -826,18 +1018,18 @@ impl AI {
             tools.push(Tool::image_generation());
         }
         tools.push(Tool::web_search_preview());
-        if let Some(vs_id) = vector_store_id.clone() {
-            if !vs_id.is_empty() {
-                tools.push(Tool::file_search(vec![vs_id]));
-            }
+        if !vector_store_ids.is_empty() {
+            tools.push(Tool::file_search(vector_store_ids.clone()));
         }
         // For scheduled prompts, only expose the safe subset plus recent-messages
-        tools.push(get_time_tool());
-        tools.push(get_fear_and_greed_index_tool());
-        tools.push(get_trending_pools_tool());
-        tools.push(get_search_pools_tool());
-        tools.push(get_new_pools_tool());
-        tools.push(get_recent_messages_tool());
+        tools.extend(filter_enabled_tools(vec![
+            get_time_tool(),
+            get_fear_and_greed_index_tool(),
+            get_trending_pools_tool(),
+            get_search_pools_tool(),
+            get_new_pools_tool(),
+            get_recent_messages_tool(),
+        ]));
 
         // Label for per-schedule conversation identity (Responses API max length: 64)
         // Use a compact, deterministic label based only on schedule_id
@@ -860,9 +1052,12 @@ impl AI {
             system_prompt
         };
 
+        let note_scope = NoteScope::Group(group_id.parse().unwrap_or(0));
+        let final_system_prompt = apply_context_note(&bot_deps, note_scope, final_system_prompt);
+
         let mut request_builder = Request::builder()
             .model(model.clone())
-            .instructions(final_system_prompt)
+            .instructions(final_system_prompt.clone())
             .tools(tools.clone())
             .tool_choice(ToolChoice::auto())
             .parallel_tool_calls(true)
@@ -889,7 +1084,7 @@ impl AI {
         if let Some(reasoning_params) = reasoning.clone() {
             request_builder = request_builder.reasoning(reasoning_params);
         }
-        if vector_store_id.is_some() {
+        if !vector_store_ids.is_empty() {
             request_builder = request_builder.include(vec![Include::FileSearchResults]);
         }
         if let Some(prev_id) = previous_response_id.clone() {
@@ -901,7 +1096,7 @@ impl AI {
             user_label,
             model,
             previous_response_id.is_some(),
-            vector_store_id.is_some()
+            !vector_store_ids.is_empty()
         );
 
         // tools already include the safe subset + get_recent_messages
@@ -922,6 +1117,7 @@ impl AI {
             let tool_calls = current_response.tool_calls();
             let custom_tool_calls: Vec<_> = tool_calls
                 .iter()
+                .filter(|tc| is_tool_enabled(&tc.name))
                 .filter(|tc| {
                     tc.name == "get_current_time"
                         || tc.name == "get_fear_and_greed_index"
@@ -936,32 +1132,44 @@ impl AI {
                 break;
             }
 
-            let mut function_outputs = Vec::new();
-            for tc in &custom_tool_calls {
-                let args_value: serde_json::Value =
-                    serde_json::from_str(&tc.arguments).unwrap_or_else(|_| serde_json::json!({}));
-                let result = match tc.name.as_str() {
-                    "get_current_time" => execute_get_time(&args_value).await,
-                    "get_fear_and_greed_index" => execute_fear_and_greed_index(&args_value).await,
-                    "get_trending_pools" => execute_trending_pools(&args_value).await,
-                    "search_pools" => execute_search_pools(&args_value).await,
-                    "get_new_pools" => execute_new_pools(&args_value).await,
-                    "get_recent_messages" => {
-                        // Use group chat id for schedules
-                        let chat_id_i64: i64 = group_id.parse().unwrap_or(0);
-                        let chat_id = teloxide::types::ChatId(chat_id_i64 as i64);
-                        execute_get_recent_messages_for_chat(chat_id, bot_deps.clone()).await
-                    }
-                    _ => "".to_string(),
-                };
-                function_outputs.push((tc.call_id.clone(), result));
-            }
+            // Use group chat id for schedules
+            let chat_id = teloxide::types::ChatId(group_id.parse().unwrap_or(0));
+            let calls = custom_tool_calls
+                .iter()
+                .map(|tc| {
+                    let args_value: serde_json::Value = serde_json::from_str(&tc.arguments)
+                        .unwrap_or_else(|_| serde_json::json!({}));
+                    let name = tc.name.clone();
+                    let bot_deps = bot_deps.clone();
+                    let call = async move {
+                        match name.as_str() {
+                            "get_current_time" => execute_get_time(&args_value).await,
+                            "get_fear_and_greed_index" => {
+                                execute_fear_and_greed_index(&args_value).await
+                            }
+                            "get_trending_pools" => execute_trending_pools(&args_value, None).await,
+                            "search_pools" => execute_search_pools(&args_value).await,
+                            "get_new_pools" => execute_new_pools(&args_value).await,
+                            "get_recent_messages" => {
+                                execute_get_recent_messages_for_chat(chat_id, bot_deps).await
+                            }
+                            _ => "".to_string(),
+                        }
+                    };
+                    (tc.call_id.clone(), call)
+                })
+                .collect();
+            let function_outputs = run_tool_calls(
+                calls,
+                tool_concurrency_for(custom_tool_calls.iter().map(|tc| tc.name.as_str())),
+            )
+            .await;
 
             let mut continuation_builder = Request::builder()
                 .model(model.clone())
                 .with_function_outputs(current_response.id(), function_outputs)
                 .tools(tools.clone())
-                .instructions(self.system_prompt.clone())
+                .instructions(final_system_prompt.clone())
                 .parallel_tool_calls(true)
                 .max_output_tokens(max_tokens)
                 .user(&user_label)
@@ -1016,6 +1224,23 @@ impl AI {
         let (web_search_count, file_search_count, image_generation_count, code_interpreter_count) =
             AIResponse::calculate_tool_usage(&current_response);
 
+        record_response_time(
+            started_at.elapsed(),
+            &RequestTiming {
+                source: "schedule",
+                model: format!("{:?}", model),
+                tool_iterations: iteration - 1,
+                function_calls: 0,
+                builtin_tools: (
+                    web_search_count,
+                    file_search_count,
+                    image_generation_count,
+                    code_interpreter_count,
+                ),
+                total_tokens: total_tokens_used,
+            },
+        );
+
         let ai_resp = AIResponse::from((
             reply,
             model,
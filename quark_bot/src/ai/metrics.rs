@@ -0,0 +1,157 @@
+//! Response-time metrics for AI calls, with slow requests logged as warnings.
+
+use std::env;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Requests slower than this are logged as warnings unless AI_SLOW_REQUEST_SECS says otherwise
+const DEFAULT_SLOW_REQUEST_SECS: u64 = 30;
+
+static SLOW_REQUEST_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+static METRICS: ResponseTimeMetrics = ResponseTimeMetrics::new();
+
+pub fn slow_request_threshold() -> Duration {
+    *SLOW_REQUEST_THRESHOLD.get_or_init(|| {
+        let secs = env::var("AI_SLOW_REQUEST_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_SLOW_REQUEST_SECS);
+        Duration::from_secs(secs)
+    })
+}
+
+/// What a timed request did, for the timing log line
+pub struct RequestTiming<'a> {
+    /// Which flow made the call, e.g. "chat" or "schedule"
+    pub source: &'a str,
+    pub model: String,
+    /// Rounds of custom tool calls before the final answer
+    pub tool_iterations: usize,
+    pub function_calls: usize,
+    /// web_search, file_search, image_generation, code_interpreter
+    pub builtin_tools: (u32, u32, u32, u32),
+    pub total_tokens: u32,
+}
+
+/// Lock-free counters; recording is a handful of atomic adds per request
+pub struct ResponseTimeMetrics {
+    count: AtomicU64,
+    total_ms: AtomicU64,
+    max_ms: AtomicU64,
+    slow: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricsSnapshot {
+    pub count: u64,
+    pub average_ms: u64,
+    pub max_ms: u64,
+    pub slow: u64,
+}
+
+impl ResponseTimeMetrics {
+    const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            total_ms: AtomicU64::new(0),
+            max_ms: AtomicU64::new(0),
+            slow: AtomicU64::new(0),
+        }
+    }
+
+    fn add(&self, elapsed: Duration, slow: bool) {
+        let ms = elapsed.as_millis() as u64;
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_ms.fetch_add(ms, Ordering::Relaxed);
+        self.max_ms.fetch_max(ms, Ordering::Relaxed);
+        if slow {
+            self.slow.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let count = self.count.load(Ordering::Relaxed);
+        let total_ms = self.total_ms.load(Ordering::Relaxed);
+        MetricsSnapshot {
+            count,
+            average_ms: if count == 0 { 0 } else { total_ms / count },
+            max_ms: self.max_ms.load(Ordering::Relaxed),
+            slow: self.slow.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters since the process started
+pub fn snapshot() -> MetricsSnapshot {
+    METRICS.snapshot()
+}
+
+/// Record how long an AI request took and log it; slow requests are logged as warnings
+pub fn record_response_time(elapsed: Duration, timing: &RequestTiming) {
+    let slow = elapsed >= slow_request_threshold();
+    METRICS.add(elapsed, slow);
+
+    let (web_search, file_search, image_generation, code_interpreter) = timing.builtin_tools;
+    let details = format!(
+        "source={} model={} elapsed_ms={} tool_iterations={} function_calls={} web_search={} file_search={} image_generation={} code_interpreter={} tokens={}",
+        timing.source,
+        timing.model,
+        elapsed.as_millis(),
+        timing.tool_iterations,
+        timing.function_calls,
+        web_search,
+        file_search,
+        image_generation,
+        code_interpreter,
+        timing.total_tokens
+    );
+    if slow {
+        log::warn!(
+            "Slow AI request (over {}s): {}",
+            slow_request_threshold().as_secs(),
+            details
+        );
+    } else {
+        log::info!("AI request timing: {}", details);
+    }
+}
+
+/// One-line summary for operator diagnostics
+pub fn format_snapshot(snapshot: &MetricsSnapshot) -> String {
+    if snapshot.count == 0 {
+        return "No AI requests since start".to_string();
+    }
+    format!(
+        "{} AI requests, avg {:.1}s, max {:.1}s, {} slow (≥{}s)",
+        snapshot.count,
+        snapshot.average_ms as f64 / 1000.0,
+        snapshot.max_ms as f64 / 1000.0,
+        snapshot.slow,
+        slow_request_threshold().as_secs()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_average_and_max() {
+        let metrics = ResponseTimeMetrics::new();
+        assert_eq!(metrics.snapshot().average_ms, 0);
+
+        metrics.add(Duration::from_millis(1_000), false);
+        metrics.add(Duration::from_millis(3_000), true);
+        assert_eq!(
+            metrics.snapshot(),
+            MetricsSnapshot {
+                count: 2,
+                average_ms: 2_000,
+                max_ms: 3_000,
+                slow: 1,
+            }
+        );
+    }
+}
@@ -1,12 +1,20 @@
 pub mod actions;
+pub mod disclaimer;
 pub mod dto;
 pub mod gcs;
+pub mod gecko_cache;
 pub mod group_vector_store;
 pub mod handler;
+pub mod metrics;
 pub mod moderation;
+pub mod openai_client;
+pub mod pinned;
 pub mod prompt;
+pub mod prompt_safety;
 pub mod schedule_guard;
 pub mod sentinel;
+pub mod streaming;
 pub mod summarizer;
 pub mod tools;
+pub mod upload_session;
 pub mod vector_store;
@@ -0,0 +1,209 @@
+//! "🙋 Appeal" on flag notices: a muted user can tell the admins a flag was a mistake
+//! instead of waiting for someone to notice and tap Unmute.
+
+use anyhow::Result;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{
+        CallbackQuery, InlineKeyboardButton, InlineKeyboardButtonKind, InlineKeyboardMarkup,
+        MaybeInaccessibleMessage, ParseMode, User,
+    },
+    utils::html,
+};
+
+use crate::{
+    ai::moderation::{dto::ModerationAction, flagged_log::FlaggedEvent},
+    dependencies::BotDependencies,
+};
+
+const APPEAL_PREFIX: &str = "appeal:";
+
+fn appeal_callback(event: &FlaggedEvent) -> String {
+    format!("{}{}:{}", APPEAL_PREFIX, event.flagged_at, event.message_id)
+}
+
+/// Add the appeal button to a flag notice's keyboard when the user was muted
+pub fn with_appeal_button(
+    keyboard: InlineKeyboardMarkup,
+    event: &FlaggedEvent,
+) -> InlineKeyboardMarkup {
+    if event.action != ModerationAction::Mute {
+        return keyboard;
+    }
+    keyboard.append_row(vec![InlineKeyboardButton::callback(
+        "🙋 Appeal",
+        appeal_callback(event),
+    )])
+}
+
+/// The notice's keyboard without the appeal button, so Unmute/Ban stay usable
+fn without_appeal_button(keyboard: &InlineKeyboardMarkup) -> InlineKeyboardMarkup {
+    let rows = keyboard
+        .inline_keyboard
+        .iter()
+        .map(|row| {
+            row.iter()
+                .filter(|button| {
+                    !matches!(&button.kind, InlineKeyboardButtonKind::CallbackData(data) if data.starts_with(APPEAL_PREFIX))
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<_>>();
+    InlineKeyboardMarkup::new(rows)
+}
+
+fn mention(user: &User) -> String {
+    match &user.username {
+        Some(username) => format!("@{}", username),
+        None => format!(
+            "<a href=\"tg://user?id={}\">{}</a>",
+            user.id.0,
+            html::escape(&user.first_name)
+        ),
+    }
+}
+
+/// `appeal:<flagged_at>:<message_id>` — only the flagged user may appeal, and only once
+pub async fn handle_appeal_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+    let data = query.data.clone().unwrap_or_default();
+
+    let event = data
+        .strip_prefix(APPEAL_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .and_then(|(flagged_at, message_id)| {
+            Some((
+                flagged_at.parse::<i64>().ok()?,
+                message_id.parse::<i32>().ok()?,
+            ))
+        })
+        .and_then(|(flagged_at, message_id)| {
+            bot_deps.flagged_log.get(m.chat.id, flagged_at, message_id)
+        });
+    let Some(event) = event else {
+        bot.answer_callback_query(query.id)
+            .text("❌ This flag is no longer available")
+            .await?;
+        return Ok(());
+    };
+
+    if query.from.id.0 as i64 != event.user_id {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only the muted user can appeal")
+            .await?;
+        return Ok(());
+    }
+    if event.resolution.is_some() {
+        bot.answer_callback_query(query.id)
+            .text("✅ An admin has already reviewed this")
+            .await?;
+        return Ok(());
+    }
+    if !bot_deps
+        .flagged_log
+        .record_appeal(&event, chrono::Utc::now().timestamp())?
+    {
+        bot.answer_callback_query(query.id)
+            .text("⏳ Your appeal is already pending")
+            .await?;
+        return Ok(());
+    }
+
+    log::info!(
+        "User {} appealed flag of message {} in {}",
+        event.user_id,
+        event.message_id,
+        event.chat_id
+    );
+    bot.answer_callback_query(query.id)
+        .text("🙋 Appeal sent to the admins")
+        .await?;
+
+    let notice = m
+        .html_text()
+        .unwrap_or_else(|| html::escape(m.text().unwrap_or_default()));
+    let keyboard = m
+        .reply_markup()
+        .map(without_appeal_button)
+        .unwrap_or_default();
+    if let Err(e) = bot
+        .edit_message_text(
+            m.chat.id,
+            m.id,
+            format!("{}\n\n🙋 <b>Appeal pending</b>", notice),
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await
+    {
+        log::warn!("Failed to mark flag notice {} as appealed: {}", m.id.0, e);
+    }
+
+    let admins = match bot.get_chat_administrators(m.chat.id).await {
+        Ok(admins) => admins
+            .iter()
+            .filter(|member| !member.user.is_bot)
+            .map(|member| mention(&member.user))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Err(e) => {
+            log::warn!("Failed to list admins for appeal ping: {}", e);
+            String::new()
+        }
+    };
+    bot.send_message(
+        m.chat.id,
+        format!(
+            "🙋 {} appealed this flag and says it was a mistake.\n\n{}\nPlease review and use <b>Unmute</b> or <b>Ban</b> above.",
+            mention(&query.from),
+            admins
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_to(m.id)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appeal_button_only_for_mutes_and_removable() {
+        let mut event = FlaggedEvent {
+            chat_id: -100,
+            message_id: 42,
+            user_id: 7,
+            user_display: "@alice".to_string(),
+            snippet: "gm".to_string(),
+            source: crate::ai::moderation::flagged_log::FlagSource::Sentinel,
+            action: ModerationAction::Mute,
+            status: "🔇 User has been muted".to_string(),
+            flagged_at: 1_700_000_000,
+            resolution: None,
+            appealed_at: None,
+        };
+        let base = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback("🔇 Unmute", "unmute:7"),
+            InlineKeyboardButton::callback("🚫 Ban", "ban:7"),
+        ]]);
+
+        let with_appeal = with_appeal_button(base.clone(), &event);
+        assert_eq!(with_appeal.inline_keyboard.len(), 2);
+        assert_eq!(without_appeal_button(&with_appeal), base);
+
+        event.action = ModerationAction::Ban;
+        assert_eq!(with_appeal_button(base.clone(), &event), base);
+    }
+}
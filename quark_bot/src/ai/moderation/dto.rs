@@ -10,6 +10,54 @@ pub struct ModerationResult {
 pub struct ModerationOverrides {
     pub allowed_items: Vec<String>,
     pub disallowed_items: Vec<String>,
+    /// The group's Link Allowlist; links to these domains are not suspicious on their own
+    #[serde(default)]
+    pub permitted_domains: Vec<String>,
+}
+
+/// What happens to a flagged message and its author
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ModerationAction {
+    Warn,
+    Delete,
+    #[default]
+    Mute,
+    Kick,
+    Ban,
+}
+
+impl ModerationAction {
+    pub const ALL: [ModerationAction; 5] = [
+        ModerationAction::Warn,
+        ModerationAction::Delete,
+        ModerationAction::Mute,
+        ModerationAction::Kick,
+        ModerationAction::Ban,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ModerationAction::Warn => "warn",
+            ModerationAction::Delete => "delete",
+            ModerationAction::Mute => "mute",
+            ModerationAction::Kick => "kick",
+            ModerationAction::Ban => "ban",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModerationAction::Warn => "⚠️ Warn only",
+            ModerationAction::Delete => "🗑️ Delete message",
+            ModerationAction::Mute => "🔇 Mute + delete",
+            ModerationAction::Kick => "👢 Kick + delete",
+            ModerationAction::Ban => "🚫 Ban + delete",
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -18,6 +66,11 @@ pub struct ModerationSettings {
     pub disallowed_items: Vec<String>,
     pub updated_by_user_id: i64,
     pub updated_at_unix_ms: i64,
+    #[serde(default)]
+    pub action: ModerationAction,
+    /// React to messages that pass a manual /report instead of staying silent
+    #[serde(default)]
+    pub react_on_pass: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -27,19 +80,35 @@ pub struct ModerationState {
     pub message_id: Option<i64>,
     #[serde(default)]
     pub started_by_user_id: Option<i64>,
+    /// AI rules builder: community description and the latest draft under review
+    #[serde(default)]
+    pub rules_draft: Option<RulesDraft>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RulesDraft {
+    pub description: String,
+    pub allowed_items: Vec<String>,
+    pub disallowed_items: Vec<String>,
 }
 
 impl From<(String, Option<Vec<String>>, Option<i64>)> for ModerationState {
     fn from(value: (String, Option<Vec<String>>, Option<i64>)) -> Self {
         let (step, allowed_items, message_id) = value;
-        Self { step, allowed_items, message_id, started_by_user_id: None }
+        Self { step, allowed_items, message_id, started_by_user_id: None, rules_draft: None }
     }
 }
 
 impl From<(String, Option<Vec<String>>, Option<i64>, i64)> for ModerationState {
     fn from(value: (String, Option<Vec<String>>, Option<i64>, i64)) -> Self {
         let (step, allowed_items, message_id, started_by_user_id) = value;
-        Self { step, allowed_items, message_id, started_by_user_id: Some(started_by_user_id) }
+        Self {
+            step,
+            allowed_items,
+            message_id,
+            started_by_user_id: Some(started_by_user_id),
+            rules_draft: None,
+        }
     }
 }
 
@@ -52,6 +121,8 @@ impl From<(Vec<String>, Vec<String>, i64, i64)> for ModerationSettings {
             disallowed_items,
             updated_by_user_id,
             updated_at_unix_ms,
+            action: ModerationAction::default(),
+            react_on_pass: false,
         }
     }
 }
@@ -0,0 +1,126 @@
+use teloxide::{
+    Bot,
+    prelude::*,
+    types::{ChatPermissions, InlineKeyboardButton, InlineKeyboardMarkup, MessageId, User},
+};
+
+use crate::ai::moderation::dto::ModerationAction;
+
+/// Outcome of applying a group's moderation action, used to build the flag notification
+pub struct Enforcement {
+    pub title: &'static str,
+    pub status: String,
+    pub keyboard: InlineKeyboardMarkup,
+}
+
+/// Apply the configured action to a flagged message and its author.
+///
+/// Every action except `Warn` removes the offending message. Failures are logged and
+/// reflected in the returned status so admins know the action didn't go through.
+pub async fn enforce_moderation_action(
+    bot: &Bot,
+    chat_id: ChatId,
+    flagged_user: &User,
+    flagged_message_id: MessageId,
+    action: ModerationAction,
+) -> Enforcement {
+    let user_id = flagged_user.id;
+
+    let (title, status, keyboard) = match action {
+        ModerationAction::Warn => (
+            "Content Flagged",
+            "⚠️ Warning issued, message kept".to_string(),
+            vec![ban_button(user_id)],
+        ),
+        ModerationAction::Delete => (
+            "Content Flagged & Message Removed",
+            "🗑️ Message removed".to_string(),
+            vec![ban_button(user_id)],
+        ),
+        ModerationAction::Mute => {
+            let status = match bot
+                .restrict_chat_member(chat_id, user_id, ChatPermissions::empty())
+                .await
+            {
+                Ok(_) => {
+                    log::info!("Muted user {} for flagged content", user_id);
+                    "🔇 User has been muted".to_string()
+                }
+                Err(e) => {
+                    log::error!("Failed to mute user {}: {}", user_id, e);
+                    "⚠️ Could not mute user (check bot admin rights)".to_string()
+                }
+            };
+            (
+                "Content Flagged & User Muted",
+                status,
+                vec![
+                    InlineKeyboardButton::callback("🔇 Unmute", format!("unmute:{}", user_id)),
+                    ban_button(user_id),
+                ],
+            )
+        }
+        ModerationAction::Kick => {
+            // Telegram has no kick: ban then immediately unban so the user can rejoin
+            let kicked = match bot.ban_chat_member(chat_id, user_id).await {
+                Ok(_) => bot.unban_chat_member(chat_id, user_id).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            let status = match kicked {
+                Ok(_) => {
+                    log::info!("Kicked user {} for flagged content", user_id);
+                    "👢 User has been removed from the group".to_string()
+                }
+                Err(e) => {
+                    log::error!("Failed to kick user {}: {}", user_id, e);
+                    "⚠️ Could not kick user (check bot admin rights)".to_string()
+                }
+            };
+            (
+                "Content Flagged & User Kicked",
+                status,
+                vec![ban_button(user_id)],
+            )
+        }
+        ModerationAction::Ban => {
+            let status = match bot.ban_chat_member(chat_id, user_id).await {
+                Ok(_) => {
+                    log::info!("Banned user {} for flagged content", user_id);
+                    "🚫 User has been banned".to_string()
+                }
+                Err(e) => {
+                    log::error!("Failed to ban user {}: {}", user_id, e);
+                    "⚠️ Could not ban user (check bot admin rights)".to_string()
+                }
+            };
+            (
+                "Content Flagged & User Banned",
+                status,
+                vec![InlineKeyboardButton::callback(
+                    "✅ Unban",
+                    format!("unban:{}", user_id),
+                )],
+            )
+        }
+    };
+
+    if action != ModerationAction::Warn {
+        if let Err(e) = bot.delete_message(chat_id, flagged_message_id).await {
+            log::warn!(
+                "Failed to delete offending message {}: {}",
+                flagged_message_id.0,
+                e
+            );
+        }
+    }
+
+    Enforcement {
+        title,
+        status,
+        keyboard: InlineKeyboardMarkup::new(vec![keyboard]),
+    }
+}
+
+fn ban_button(user_id: UserId) -> InlineKeyboardButton {
+    InlineKeyboardButton::callback("🚫 Ban", format!("ban:{}", user_id))
+}
@@ -0,0 +1,473 @@
+//! Persisted record of flagged messages so admins can review what moderation did with /flagged.
+
+use std::env;
+use std::sync::OnceLock;
+
+use anyhow::Result as AnyResult;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use teloxide::{
+    prelude::*,
+    types::{
+        ChatPermissions, InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage,
+        ParseMode, User,
+    },
+    utils::html,
+};
+
+use crate::{
+    ai::moderation::dto::ModerationAction,
+    dependencies::BotDependencies,
+    utils::{self, KeyboardMarkupType, format_timestamp, send_markdown_message_with_keyboard, send_message},
+};
+
+const TREE_NAME: &str = "moderation_flagged_log";
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+/// Newest events kept per group, whatever their age
+const MAX_EVENTS_PER_CHAT: usize = 200;
+const PAGE_SIZE: usize = 5;
+const SNIPPET_CHARS: usize = 120;
+
+/// Flagged events older than this are dropped, from FLAGGED_LOG_RETENTION_DAYS (default 30)
+pub fn retention_secs() -> i64 {
+    static RETENTION: OnceLock<i64> = OnceLock::new();
+    *RETENTION.get_or_init(|| {
+        let days = env::var("FLAGGED_LOG_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+        days * 24 * 60 * 60
+    })
+}
+
+/// Who flagged the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlagSource {
+    Sentinel,
+    Report,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlaggedEvent {
+    pub chat_id: i64,
+    pub message_id: i32,
+    pub user_id: i64,
+    /// @username, or first name when the user has none
+    pub user_display: String,
+    pub snippet: String,
+    pub source: FlagSource,
+    pub action: ModerationAction,
+    /// Enforcement outcome as shown in the flag notice
+    pub status: String,
+    pub flagged_at: i64,
+    /// Admin follow-up from /flagged, e.g. "Unmuted by @admin"
+    #[serde(default)]
+    pub resolution: Option<String>,
+    /// Unix seconds when the flagged user appealed from the flag notice
+    #[serde(default)]
+    pub appealed_at: Option<i64>,
+}
+
+impl FlaggedEvent {
+    pub fn new(
+        chat_id: ChatId,
+        message_id: i32,
+        user: &User,
+        text: &str,
+        source: FlagSource,
+        action: ModerationAction,
+        status: &str,
+    ) -> Self {
+        Self {
+            chat_id: chat_id.0,
+            message_id,
+            user_id: user.id.0 as i64,
+            user_display: display_name(user),
+            snippet: snippet(text),
+            source,
+            action,
+            status: status.to_string(),
+            flagged_at: chrono::Utc::now().timestamp(),
+            resolution: None,
+            appealed_at: None,
+        }
+    }
+}
+
+fn display_name(user: &User) -> String {
+    match &user.username {
+        Some(username) => format!("@{}", username),
+        None => user.first_name.clone(),
+    }
+}
+
+/// First SNIPPET_CHARS characters of the message, on one line
+fn snippet(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= SNIPPET_CHARS {
+        return flat;
+    }
+    let cut: String = flat.chars().take(SNIPPET_CHARS).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Key sorts by chat, then time, so a prefix scan in reverse gives newest first
+fn event_key(chat_id: i64, flagged_at: i64, message_id: i32) -> Vec<u8> {
+    let mut key = Vec::with_capacity(20);
+    key.extend_from_slice(&chat_id.to_be_bytes());
+    key.extend_from_slice(&flagged_at.to_be_bytes());
+    key.extend_from_slice(&message_id.to_be_bytes());
+    key
+}
+
+#[derive(Clone)]
+pub struct FlaggedLog {
+    tree: Tree,
+}
+
+impl FlaggedLog {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    /// Store a flagged event and drop the group's events past the retention window or cap
+    pub fn record(&self, event: &FlaggedEvent) -> sled::Result<()> {
+        self.tree.insert(
+            event_key(event.chat_id, event.flagged_at, event.message_id),
+            serde_json::to_vec(event).unwrap(),
+        )?;
+        self.prune(event.chat_id, event.flagged_at)
+    }
+
+    fn prune(&self, chat_id: i64, now: i64) -> sled::Result<()> {
+        let cutoff = now - retention_secs();
+        let stale: Vec<_> = self
+            .tree
+            .scan_prefix(chat_id.to_be_bytes())
+            .rev()
+            .filter_map(|kv| kv.ok())
+            .enumerate()
+            .filter(|(index, (_, v))| {
+                *index >= MAX_EVENTS_PER_CHAT
+                    || serde_json::from_slice::<FlaggedEvent>(v)
+                        .map(|e| e.flagged_at < cutoff)
+                        .unwrap_or(true)
+            })
+            .map(|(_, (k, _))| k)
+            .collect();
+        for key in stale {
+            self.tree.remove(key)?;
+        }
+        Ok(())
+    }
+
+    /// The group's events within the retention window, newest first
+    pub fn recent(&self, chat_id: ChatId) -> Vec<FlaggedEvent> {
+        let cutoff = chrono::Utc::now().timestamp() - retention_secs();
+        self.tree
+            .scan_prefix(chat_id.0.to_be_bytes())
+            .rev()
+            .filter_map(|kv| kv.ok())
+            .filter_map(|(_, v)| serde_json::from_slice::<FlaggedEvent>(&v).ok())
+            .filter(|e| e.flagged_at >= cutoff)
+            .take(MAX_EVENTS_PER_CHAT)
+            .collect()
+    }
+
+    /// Drop events past the retention window in every group; returns how many were removed
+    pub fn purge_expired(&self, now: i64) -> usize {
+        let cutoff = now - retention_secs();
+        let stale: Vec<_> = self
+            .tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(_, v)| {
+                serde_json::from_slice::<FlaggedEvent>(v)
+                    .map(|e| e.flagged_at < cutoff)
+                    .unwrap_or(true)
+            })
+            .map(|(k, _)| k)
+            .collect();
+        for key in &stale {
+            let _ = self.tree.remove(key);
+        }
+        stale.len()
+    }
+
+    pub fn get(&self, chat_id: ChatId, flagged_at: i64, message_id: i32) -> Option<FlaggedEvent> {
+        self.tree
+            .get(event_key(chat_id.0, flagged_at, message_id))
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+    }
+
+    /// Mark the event as appealed; false when an appeal was already recorded
+    pub fn record_appeal(&self, event: &FlaggedEvent, appealed_at: i64) -> sled::Result<bool> {
+        let key = event_key(event.chat_id, event.flagged_at, event.message_id);
+        let mut recorded = false;
+        self.tree.update_and_fetch(&key, |current| {
+            recorded = false;
+            let bytes = current?;
+            let Ok(mut stored) = serde_json::from_slice::<FlaggedEvent>(bytes) else {
+                return Some(bytes.to_vec());
+            };
+            recorded = stored.appealed_at.is_none();
+            stored.appealed_at.get_or_insert(appealed_at);
+            Some(serde_json::to_vec(&stored).unwrap())
+        })?;
+        Ok(recorded)
+    }
+
+    pub fn set_resolution(&self, event: &FlaggedEvent, resolution: String) -> sled::Result<()> {
+        let mut event = event.clone();
+        event.resolution = Some(resolution);
+        self.tree.insert(
+            event_key(event.chat_id, event.flagged_at, event.message_id),
+            serde_json::to_vec(&event).unwrap(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Record a flag, logging instead of failing the moderation flow when storage errors
+pub fn log_flagged_event(bot_deps: &BotDependencies, event: FlaggedEvent) {
+    if let Err(e) = bot_deps.flagged_log.record(&event) {
+        log::warn!(
+            "Failed to record flagged message {} in {}: {}",
+            event.message_id,
+            event.chat_id,
+            e
+        );
+    }
+}
+
+fn render_page(events: &[FlaggedEvent], page: usize) -> (String, InlineKeyboardMarkup) {
+    let pages = events.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+    let start = page * PAGE_SIZE;
+
+    let mut text = format!(
+        "🛡️ <b>Flagged messages</b> (last {} days)\n\nPage {}/{} · {} total",
+        retention_secs() / (24 * 60 * 60),
+        page + 1,
+        pages,
+        events.len()
+    );
+    let mut rows = Vec::new();
+
+    for (offset, event) in events.iter().skip(start).take(PAGE_SIZE).enumerate() {
+        let number = start + offset + 1;
+        text.push_str(&format!(
+            "\n\n<b>#{}</b> · {} · {}\n👤 {} (<code>{}</code>)\n{}\n💬 <i>{}</i>",
+            number,
+            format_timestamp(event.flagged_at as u64),
+            match event.source {
+                FlagSource::Sentinel => "Sentinel",
+                FlagSource::Report => "/report",
+            },
+            html::escape(&event.user_display),
+            event.user_id,
+            html::escape(&event.status),
+            html::escape(&event.snippet)
+        ));
+        if let Some(resolution) = &event.resolution {
+            text.push_str(&format!("\n✅ {}", html::escape(resolution)));
+            continue;
+        }
+        if event.appealed_at.is_some() {
+            text.push_str("\n🙋 Appeal pending");
+        }
+
+        let callback = |verb: &str| {
+            format!(
+                "flagged_{}:{}:{}:{}",
+                verb, page, event.flagged_at, event.message_id
+            )
+        };
+        match event.action {
+            ModerationAction::Mute => rows.push(vec![InlineKeyboardButton::callback(
+                format!("🔊 Unmute #{}", number),
+                callback("unmute"),
+            )]),
+            ModerationAction::Ban => rows.push(vec![InlineKeyboardButton::callback(
+                format!("✅ Unban #{}", number),
+                callback("unban"),
+            )]),
+            _ => {}
+        }
+    }
+
+    let mut nav = Vec::new();
+    if page > 0 {
+        nav.push(InlineKeyboardButton::callback(
+            "◀️ Newer",
+            format!("flagged_page:{}", page - 1),
+        ));
+    }
+    if page + 1 < pages {
+        nav.push(InlineKeyboardButton::callback(
+            "Older ▶️",
+            format!("flagged_page:{}", page + 1),
+        ));
+    }
+    if !nav.is_empty() {
+        rows.push(nav);
+    }
+
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+/// /flagged — recent flagged messages for this group with their outcome (admins only)
+pub async fn handle_flagged_command(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(user) = msg.from.clone() else {
+        return Ok(());
+    };
+    if !utils::is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group administrators can review flagged messages.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let events = bot_deps.flagged_log.recent(msg.chat.id);
+    if events.is_empty() {
+        send_message(
+            msg,
+            bot,
+            "✅ Nothing has been flagged in this group recently.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (text, keyboard) = render_page(&events, 0);
+    send_markdown_message_with_keyboard(
+        bot,
+        msg,
+        KeyboardMarkupType::InlineKeyboardType(keyboard),
+        &text,
+    )
+    .await?;
+    Ok(())
+}
+
+/// `flagged_page:<page>`, `flagged_unmute:<page>:<flagged_at>:<message_id>` and `flagged_unban:…`
+pub async fn handle_flagged_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+    let data = query.data.clone().unwrap_or_default();
+
+    if !utils::is_admin(&bot, m.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can use this action")
+            .await?;
+        return Ok(());
+    }
+
+    let (verb, rest) = data
+        .strip_prefix("flagged_")
+        .and_then(|d| d.split_once(':'))
+        .unwrap_or_default();
+    let parts: Vec<&str> = rest.split(':').collect();
+    let page = parts.first().and_then(|p| p.parse::<usize>().ok()).unwrap_or(0);
+
+    if verb == "unmute" || verb == "unban" {
+        let event = match (
+            parts.get(1).and_then(|p| p.parse::<i64>().ok()),
+            parts.get(2).and_then(|p| p.parse::<i32>().ok()),
+        ) {
+            (Some(flagged_at), Some(message_id)) => {
+                bot_deps.flagged_log.get(m.chat.id, flagged_at, message_id)
+            }
+            _ => None,
+        };
+        let Some(event) = event else {
+            bot.answer_callback_query(query.id)
+                .text("❌ This entry is no longer available")
+                .await?;
+            return Ok(());
+        };
+
+        let target = UserId(event.user_id as u64);
+        let result = if verb == "unmute" {
+            bot.restrict_chat_member(m.chat.id, target, ChatPermissions::all())
+                .await
+                .map(|_| ())
+        } else {
+            bot.unban_chat_member(m.chat.id, target)
+                .only_if_banned(true)
+                .await
+                .map(|_| ())
+        };
+        let done = if verb == "unmute" { "Unmuted" } else { "Unbanned" };
+
+        match result {
+            Ok(_) => {
+                log::info!(
+                    "Admin {} {} user {} from /flagged",
+                    query.from.id,
+                    done.to_lowercase(),
+                    event.user_id
+                );
+                bot_deps.flagged_log.set_resolution(
+                    &event,
+                    format!("{} by {}", done, display_name(&query.from)),
+                )?;
+                bot.answer_callback_query(query.id.clone())
+                    .text(format!("✅ {}", done))
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to {} user {}: {}", verb, event.user_id, e);
+                bot.answer_callback_query(query.id)
+                    .text(format!("❌ Failed to {} user", verb))
+                    .await?;
+                return Ok(());
+            }
+        }
+    } else {
+        bot.answer_callback_query(query.id.clone()).await?;
+    }
+
+    let events = bot_deps.flagged_log.recent(m.chat.id);
+    if events.is_empty() {
+        bot.edit_message_text(m.chat.id, m.id, "✅ Nothing has been flagged in this group recently.")
+            .await?;
+        return Ok(());
+    }
+    let (text, keyboard) = render_page(&events, page);
+    bot.edit_message_text(m.chat.id, m.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_flattens_and_truncates() {
+        assert_eq!(snippet("buy\n  now"), "buy now");
+        let long = "a".repeat(SNIPPET_CHARS + 10);
+        let cut = snippet(&long);
+        assert_eq!(cut.chars().count(), SNIPPET_CHARS + 1);
+        assert!(cut.ends_with('…'));
+    }
+}
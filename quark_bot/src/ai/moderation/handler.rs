@@ -7,7 +7,11 @@ use teloxide::{
 };
 
 use crate::{
-    ai::moderation::dto::ModerationSettings,
+    ai::moderation::{
+        dto::ModerationSettings,
+        link_allowlist::{STEP_AWAITING_DOMAIN, handle_link_allowlist_message},
+        rules_builder::{handle_rules_builder_message, is_builder_step},
+    },
     dependencies::BotDependencies,
     utils::{is_admin, send_html_message},
 };
@@ -49,6 +53,14 @@ pub async fn handle_message_moderation(
                 .unwrap_or("")
                 .trim()
                 .to_string();
+            if !text.is_empty() && is_builder_step(&moderation_state.step) {
+                return handle_rules_builder_message(bot, msg, bot_deps, moderation_state, text)
+                    .await;
+            }
+            if !text.is_empty() && moderation_state.step == STEP_AWAITING_DOMAIN {
+                return handle_link_allowlist_message(bot, msg, bot_deps, moderation_state, text)
+                    .await;
+            }
             if !text.is_empty() {
                 let parse_items = |s: &str| -> Vec<String> {
                     s.split(';')
@@ -93,13 +105,15 @@ pub async fn handle_message_moderation(
                 } else if moderation_state.step == "AwaitingDisallowed" {
                     let disallowed = parse_items(&text);
                     let allowed = moderation_state.allowed_items.unwrap_or_default();
-                    // Save to moderation_settings tree
-                    let settings = ModerationSettings::from((
-                        allowed.clone(),
-                        disallowed.clone(),
-                        user.id.0 as i64,
-                        chrono::Utc::now().timestamp_millis(),
-                    ));
+                    // Save to moderation_settings tree; only the custom rules change
+                    let mut settings = bot_deps
+                        .moderation
+                        .get_moderation_settings(chat_id.clone())
+                        .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+                    settings.allowed_items = allowed.clone();
+                    settings.disallowed_items = disallowed.clone();
+                    settings.updated_by_user_id = user.id.0 as i64;
+                    settings.updated_at_unix_ms = chrono::Utc::now().timestamp_millis();
                     bot_deps
                         .moderation
                         .set_or_update_moderation_settings(chat_id.clone(), settings)
@@ -0,0 +1,386 @@
+//! Per-group "Link Allowlist": domains Sentinel trusts. A message that is nothing but links
+//! to allowlisted domains (or their subdomains) skips AI moderation; any other message is
+//! moderated with the allowlist passed along as permitted domains.
+
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use regex::Regex;
+use sled::{Db, Tree};
+use teloxide::{
+    prelude::*,
+    types::{
+        CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage,
+        MessageEntityKind, MessageId, ParseMode,
+    },
+    utils::html,
+};
+
+use crate::{ai::moderation::dto::ModerationState, dependencies::BotDependencies, utils::is_admin};
+
+const TREE_NAME: &str = "moderation_link_allowlist";
+pub const STEP_AWAITING_DOMAIN: &str = "AwaitingAllowlistDomain";
+const MAX_DOMAINS: usize = 50;
+/// Keeps `mod_links_rm:<domain>` within Telegram's 64-byte callback data limit
+const MAX_DOMAIN_LEN: usize = 50;
+
+#[derive(Clone)]
+pub struct LinkAllowlist {
+    tree: Tree,
+}
+
+impl LinkAllowlist {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    /// The group's allowlisted domains, sorted
+    pub fn get(&self, chat_id: ChatId) -> Vec<String> {
+        self.tree
+            .get(chat_id.0.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default()
+    }
+
+    fn set(&self, chat_id: ChatId, domains: &[String]) -> sled::Result<()> {
+        if domains.is_empty() {
+            self.tree.remove(chat_id.0.to_be_bytes())?;
+        } else {
+            self.tree.insert(
+                chat_id.0.to_be_bytes(),
+                serde_json::to_vec(domains).unwrap(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add a normalized domain; false when it was already listed
+    pub fn add(&self, chat_id: ChatId, domain: &str) -> sled::Result<bool> {
+        let mut domains = self.get(chat_id);
+        if domains.iter().any(|d| d == domain) {
+            return Ok(false);
+        }
+        domains.push(domain.to_string());
+        domains.sort();
+        self.set(chat_id, &domains)?;
+        Ok(true)
+    }
+
+    /// Remove a domain; false when it wasn't listed
+    pub fn remove(&self, chat_id: ChatId, domain: &str) -> sled::Result<bool> {
+        let mut domains = self.get(chat_id);
+        let before = domains.len();
+        domains.retain(|d| d != domain);
+        if domains.len() == before {
+            return Ok(false);
+        }
+        self.set(chat_id, &domains)?;
+        Ok(true)
+    }
+}
+
+/// Lowercase host of a domain or URL as an admin would type it: scheme, `www.`, port and
+/// path are dropped. None when what's left doesn't look like a domain.
+pub fn normalize_domain(input: &str) -> Option<String> {
+    let input = input.trim().to_lowercase();
+    let without_scheme = input
+        .split_once("://")
+        .map_or(input.as_str(), |(_, rest)| rest);
+    let host = without_scheme
+        .split(['/', '?', '#', ':'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('.');
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid = labels.len() >= 2
+        && host.len() <= MAX_DOMAIN_LEN
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+    valid.then(|| host.to_string())
+}
+
+fn url_regex() -> &'static Regex {
+    static URL_RE: OnceLock<Regex> = OnceLock::new();
+    URL_RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:https?://)?(?:[a-z0-9-]+\.)+[a-z]{2,}(?:[:/?#][^\s<>]*)?").unwrap()
+    })
+}
+
+/// Domains linked from the text, including ones hidden behind text links
+pub fn message_domains(msg: &Message) -> Vec<String> {
+    let text = msg.text().or_else(|| msg.caption()).unwrap_or("");
+    let mut domains: Vec<String> = url_regex()
+        .find_iter(text)
+        .filter_map(|m| normalize_domain(m.as_str()))
+        .collect();
+
+    let entities = msg
+        .entities()
+        .or_else(|| msg.caption_entities())
+        .unwrap_or(&[]);
+    for entity in entities {
+        if let MessageEntityKind::TextLink { url } = &entity.kind {
+            match url.host_str().and_then(normalize_domain) {
+                Some(domain) => domains.push(domain),
+                // A hidden link we can't read must not let the message through
+                None => domains.push(url.as_str().to_string()),
+            }
+        }
+    }
+
+    domains.sort();
+    domains.dedup();
+    domains
+}
+
+/// `domain` is listed, or is a subdomain of a listed one
+fn is_allowlisted(domain: &str, allowlist: &[String]) -> bool {
+    allowlist.iter().any(|allowed| {
+        domain == allowed
+            || domain
+                .strip_suffix(allowed.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Whether the text has nothing besides links, ignoring whitespace and punctuation
+fn is_links_only(text: &str) -> bool {
+    !url_regex()
+        .replace_all(text, "")
+        .chars()
+        .any(char::is_alphanumeric)
+}
+
+/// Whether the message is nothing but links, every one of them allowlisted. Any other
+/// text, including the label of a text link, still goes through moderation.
+pub fn only_allowlisted_links(text: &str, domains: &[String], allowlist: &[String]) -> bool {
+    is_links_only(text)
+        && !domains.is_empty()
+        && domains.iter().all(|d| is_allowlisted(d, allowlist))
+}
+
+fn allowlist_menu(domains: &[String]) -> (String, InlineKeyboardMarkup) {
+    let listed = if domains.is_empty() {
+        "<i>(none)</i>".to_string()
+    } else {
+        domains
+            .iter()
+            .map(|d| format!("• <code>{}</code>", html::escape(d)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let text = format!(
+        "🔗 <b>Link Allowlist</b>\n\nMessages that are only links to these domains (or their subdomains) skip Sentinel moderation. Other messages are moderated as usual, with links to these domains treated as trusted.\n\n<b>Domains ({}/{})</b>:\n{}\n\nTap a domain to remove it.",
+        domains.len(),
+        MAX_DOMAINS,
+        listed
+    );
+
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = domains
+        .iter()
+        .map(|d| {
+            vec![InlineKeyboardButton::callback(
+                format!("🗑 {}", d),
+                format!("mod_links_rm:{}", d),
+            )]
+        })
+        .collect();
+    if domains.len() < MAX_DOMAINS {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "➕ Add Domain",
+            "mod_links_add",
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "open_moderation_settings",
+    )]);
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+/// `mod_links_menu`, `mod_links_add`, `mod_links_cancel` and `mod_links_rm:<domain>` from the
+/// moderation menu
+pub async fn handle_link_allowlist_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+    let data = query.data.clone().unwrap_or_default();
+
+    if !is_admin(&bot, m.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can manage moderation settings")
+            .await?;
+        return Ok(());
+    }
+
+    if data == "mod_links_add" {
+        let mut state = ModerationState::from((
+            STEP_AWAITING_DOMAIN.to_string(),
+            None,
+            None,
+            query.from.id.0 as i64,
+        ));
+        let sent = bot
+            .send_message(
+                m.chat.id,
+                "🔗 Send the domain to allowlist, e.g. <code>example.com</code>. Subdomains such as <code>docs.example.com</code> are covered too.",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback("❌ Cancel", "mod_links_cancel"),
+            ]]))
+            .await?;
+        state.message_id = Some(sent.id.0 as i64);
+        bot_deps
+            .moderation
+            .set_moderation_state(m.chat.id.to_string(), state)?;
+        bot.answer_callback_query(query.id)
+            .text("🔗 Send a domain")
+            .await?;
+        return Ok(());
+    }
+
+    if data == "mod_links_cancel" {
+        if let Ok(state) = bot_deps
+            .moderation
+            .get_moderation_state(m.chat.id.to_string())
+        {
+            if state.step == STEP_AWAITING_DOMAIN {
+                bot_deps
+                    .moderation
+                    .remove_moderation_state(m.chat.id.to_string())?;
+            }
+        }
+        bot.answer_callback_query(query.id)
+            .text("Cancelled")
+            .await?;
+        let _ = bot.delete_message(m.chat.id, m.id).await;
+        return Ok(());
+    }
+
+    if let Some(domain) = data.strip_prefix("mod_links_rm:") {
+        let removed = bot_deps.link_allowlist.remove(m.chat.id, domain)?;
+        bot.answer_callback_query(query.id)
+            .text(if removed {
+                format!("🗑 Removed {}", domain)
+            } else {
+                "Already removed".to_string()
+            })
+            .await?;
+    } else {
+        bot.answer_callback_query(query.id).await?;
+    }
+
+    let (text, kb) = allowlist_menu(&bot_deps.link_allowlist.get(m.chat.id));
+    bot.edit_message_text(m.chat.id, m.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(kb)
+        .await?;
+    Ok(())
+}
+
+/// The admin's reply to "Add Domain"
+pub async fn handle_link_allowlist_message(
+    bot: &Bot,
+    msg: &Message,
+    bot_deps: &BotDependencies,
+    state: ModerationState,
+    text: String,
+) -> Result<bool> {
+    let Some(domain) = normalize_domain(&text) else {
+        bot.send_message(
+            msg.chat.id,
+            "❌ That doesn't look like a domain. Send something like <code>example.com</code>.",
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        return Ok(true);
+    };
+
+    if bot_deps.link_allowlist.get(msg.chat.id).len() >= MAX_DOMAINS {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "❌ The allowlist is full ({} domains). Remove one first.",
+                MAX_DOMAINS
+            ),
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    let added = bot_deps.link_allowlist.add(msg.chat.id, &domain)?;
+    if let Some(mid) = state.message_id {
+        let _ = bot.delete_message(msg.chat.id, MessageId(mid as i32)).await;
+    }
+    bot_deps
+        .moderation
+        .remove_moderation_state(msg.chat.id.to_string())?;
+
+    let (menu, kb) = allowlist_menu(&bot_deps.link_allowlist.get(msg.chat.id));
+    let status = if added {
+        format!("✅ Added <code>{}</code>", html::escape(&domain))
+    } else {
+        format!(
+            "ℹ️ <code>{}</code> is already allowlisted",
+            html::escape(&domain)
+        )
+    };
+    bot.send_message(msg.chat.id, format!("{}\n\n{}", status, menu))
+        .parse_mode(ParseMode::Html)
+        .reply_markup(kb)
+        .await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(
+            normalize_domain("https://www.Example.com/docs?x=1"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            normalize_domain("explorer.aptoslabs.com:443"),
+            Some("explorer.aptoslabs.com".to_string())
+        );
+        assert_eq!(normalize_domain("localhost"), None);
+        assert_eq!(normalize_domain("bad_domain.com"), None);
+    }
+
+    #[test]
+    fn test_only_allowlisted_links() {
+        let allowlist = vec!["example.com".to_string()];
+        let check = |text: &str| -> bool {
+            let domains: Vec<String> = url_regex()
+                .find_iter(text)
+                .filter_map(|m| normalize_domain(m.as_str()))
+                .collect();
+            only_allowlisted_links(text, &domains, &allowlist)
+        };
+        assert!(check("https://docs.example.com/start, example.com"));
+        assert!(!check("see https://docs.example.com/start and example.com"));
+        assert!(!check("example.com https://example.com.evil.io"));
+        assert!(!check("notexample.com"));
+        assert!(!check("no links here"));
+    }
+}
@@ -1,7 +1,12 @@
+pub mod appeal;
 pub mod dto;
+pub mod enforcement;
+pub mod flagged_log;
 pub mod handler;
+pub mod link_allowlist;
 pub mod moderation_service;
 pub mod overrides;
+pub mod rules_builder;
 
-pub use dto::ModerationOverrides;
+pub use dto::{ModerationAction, ModerationOverrides};
 pub use moderation_service::ModerationService;
@@ -4,6 +4,7 @@ use teloxide::{Bot, prelude::*, types::Message};
 
 use crate::ai::moderation::dto::{ModerationOverrides, ModerationResult};
 use crate::ai::moderation::overrides::build_override_section;
+use crate::ai::openai_client::build_openai_client;
 
 #[derive(Clone)]
 pub struct ModerationService {
@@ -12,7 +13,7 @@ pub struct ModerationService {
 
 impl ModerationService {
     pub fn new(api_key: String) -> Result<Self> {
-        let client = Client::new(&api_key)?;
+        let client = build_openai_client(&api_key, None)?;
         Ok(Self { client })
     }
 
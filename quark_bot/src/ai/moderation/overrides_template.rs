@@ -20,15 +20,26 @@ pub fn build_override_section(overrides: Option<ModerationOverrides>) -> String
                 .collect::<Vec<_>>()
                 .join("\n")
         };
+        let permitted_domains = if o.permitted_domains.is_empty() {
+            "- (none)".to_string()
+        } else {
+            o.permitted_domains
+                .iter()
+                .map(|x| format!("- {}", x))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
         format!(
             concat!(
                 "[INSERT YOUR OVERRIDE RULES PROMPTING HERE]",
                 "\n\n",
                 "Disallowed: {disallowed_list}\n",
-                "Allowed: {allowed_list}\n"
+                "Allowed: {allowed_list}\n",
+                "Permitted link domains (and their subdomains): {permitted_domains}\n"
             ),
             disallowed_list = disallowed_list,
             allowed_list = allowed_list,
+            permitted_domains = permitted_domains,
         )
     } else {
         String::new()
@@ -0,0 +1,404 @@
+//! AI-assisted drafting of custom moderation rules. The admin describes the community,
+//! reviews the drafted allowed/disallowed lists, tweaks or regenerates them, then saves.
+
+use anyhow::Result;
+use open_ai_rust_responses_by_sshift::Model;
+use serde::Deserialize;
+use teloxide::{
+    prelude::*,
+    types::{CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, MessageId, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    ai::moderation::dto::{ModerationSettings, ModerationState, RulesDraft},
+    dependencies::BotDependencies,
+    utils::{create_purchase_request, is_admin},
+};
+
+pub const STEP_AWAITING_DESCRIPTION: &str = "AwaitingDescription";
+pub const STEP_REVIEWING_DRAFT: &str = "ReviewingDraft";
+
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+const MAX_ITEMS: usize = 50;
+const MAX_ITEM_CHARS: usize = 200;
+const DRAFT_MAX_TOKENS: u32 = 1500;
+
+const BUILDER_INSTRUCTIONS: &str = "You help Telegram group admins write moderation rules. \
+Given a description of their community, draft two lists for an AI moderator: ALLOWED items \
+(topics or behaviour that must not be flagged even if they look risky, e.g. discussion of the \
+group's own token) and DISALLOWED items (concrete behaviour to flag). Default rules already \
+cover scams, phishing links, wallet-approval requests, fake giveaways and DM solicitation, so \
+don't repeat those. Be specific and include short examples in each item. Use at most 10 items \
+per list, each under 150 characters, and never use the ';' character. If the admin gives \
+feedback on a previous draft, apply it and keep everything else. Reply with JSON only: \
+{\"allowed\": [\"...\"], \"disallowed\": [\"...\"]}";
+
+#[derive(Deserialize)]
+struct DraftResponse {
+    #[serde(default)]
+    allowed: Vec<String>,
+    #[serde(default)]
+    disallowed: Vec<String>,
+}
+
+pub fn is_builder_step(step: &str) -> bool {
+    step == STEP_AWAITING_DESCRIPTION || step == STEP_REVIEWING_DRAFT
+}
+
+/// Items are stored ';'-separated elsewhere, so strip separators, blanks and duplicates
+fn clean_items(items: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for item in items {
+        let item = item.replace(';', ",").trim().to_string();
+        if item.is_empty() || out.iter().any(|x| x.eq_ignore_ascii_case(&item)) {
+            continue;
+        }
+        out.push(item.chars().take(MAX_ITEM_CHARS).collect());
+        if out.len() == MAX_ITEMS {
+            break;
+        }
+    }
+    out
+}
+
+/// Pull the JSON object out of the model's reply, tolerating code fences or stray prose
+pub fn parse_draft(text: &str) -> Option<(Vec<String>, Vec<String>)> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    let parsed: DraftResponse = serde_json::from_str(&text[start..=end]).ok()?;
+    let allowed = clean_items(parsed.allowed);
+    let disallowed = clean_items(parsed.disallowed);
+    if allowed.is_empty() && disallowed.is_empty() {
+        return None;
+    }
+    Some((allowed, disallowed))
+}
+
+fn format_list(items: &[String]) -> String {
+    if items.is_empty() {
+        "<i>(none)</i>".to_string()
+    } else {
+        items
+            .iter()
+            .map(|x| format!("• {}", html::escape(x)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn format_draft(draft: &RulesDraft) -> String {
+    format!(
+        "✨ <b>Drafted moderation rules</b>\n\n<b>Allowed ({})</b>:\n{}\n\n<b>Disallowed ({})</b>:\n{}\n\n<i>Reply with changes (e.g. \"also flag price predictions\") to tweak this draft, regenerate it, or save it.</i>",
+        draft.allowed_items.len(),
+        format_list(&draft.allowed_items),
+        draft.disallowed_items.len(),
+        format_list(&draft.disallowed_items),
+    )
+}
+
+fn draft_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback("✅ Save these rules", "mod_ai_accept")],
+        vec![InlineKeyboardButton::callback("🔄 Regenerate", "mod_ai_regen")],
+        vec![InlineKeyboardButton::callback("❌ Cancel", "mod_ai_cancel")],
+    ])
+}
+
+/// Ask the AI for a draft and bill the group for it
+async fn draft_rules(
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    user_id: i64,
+    description: &str,
+    revise: Option<(&RulesDraft, &str)>,
+) -> Result<RulesDraft> {
+    let jwt = bot_deps
+        .group
+        .get_credentials(chat_id)
+        .map(|c| c.jwt)
+        .ok_or_else(|| anyhow::anyhow!("Group is not logged in"))?;
+
+    let mut input = format!("Community description:\n{}", description);
+    if let Some((previous, feedback)) = revise {
+        input.push_str(&format!(
+            "\n\nPrevious draft:\n{}\n\nAdmin feedback:\n{}",
+            serde_json::json!({
+                "allowed": previous.allowed_items,
+                "disallowed": previous.disallowed_items,
+            }),
+            feedback
+        ));
+    }
+
+    let (text, total_tokens) = bot_deps
+        .ai
+        .generate_text_response(BUILDER_INSTRUCTIONS, &input, Model::GPT5Mini, DRAFT_MAX_TOKENS)
+        .await?;
+
+    if let Err(e) = create_purchase_request(
+        0,
+        0,
+        0,
+        total_tokens,
+        Model::GPT5Mini,
+        &jwt,
+        Some(chat_id.to_string()),
+        Some(user_id.to_string()),
+        bot_deps.clone(),
+    )
+    .await
+    {
+        log::error!("Failed to bill moderation rules draft: {}", e);
+        return Err(anyhow::anyhow!("Couldn't charge the group for this draft"));
+    }
+
+    let (allowed_items, disallowed_items) =
+        parse_draft(&text).ok_or_else(|| anyhow::anyhow!("The AI returned no usable rules"))?;
+    Ok(RulesDraft {
+        description: description.to_string(),
+        allowed_items,
+        disallowed_items,
+    })
+}
+
+/// Draft (or revise) and show the result, replacing the previous wizard message
+async fn draft_and_show(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    user_id: i64,
+    mut state: ModerationState,
+    description: String,
+    feedback: Option<&str>,
+) -> Result<()> {
+    let working = bot
+        .send_message(chat_id, "⏳ Drafting moderation rules…")
+        .await?;
+
+    let previous = state.rules_draft.clone();
+    let revise = match (&previous, feedback) {
+        (Some(draft), Some(feedback)) => Some((draft, feedback)),
+        _ => None,
+    };
+    let result = draft_rules(bot_deps, chat_id, user_id, &description, revise).await;
+    let _ = bot.delete_message(chat_id, working.id).await;
+
+    let draft = match result {
+        Ok(draft) => draft,
+        Err(e) => {
+            log::error!("Moderation rules draft failed for {}: {}", chat_id, e);
+            bot.send_message(
+                chat_id,
+                format!("❌ {}. Send your description again or tap Cancel.", e),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    if let Some(mid) = state.message_id {
+        let _ = bot.delete_message(chat_id, MessageId(mid as i32)).await;
+    }
+    let sent = bot
+        .send_message(chat_id, format_draft(&draft))
+        .parse_mode(ParseMode::Html)
+        .reply_markup(draft_keyboard())
+        .await?;
+
+    state.step = STEP_REVIEWING_DRAFT.to_string();
+    state.message_id = Some(sent.id.0 as i64);
+    state.rules_draft = Some(draft);
+    bot_deps
+        .moderation
+        .set_moderation_state(chat_id.to_string(), state)?;
+    Ok(())
+}
+
+/// Text from the wizard owner while the builder is active: a description or draft feedback
+pub async fn handle_rules_builder_message(
+    bot: &Bot,
+    msg: &Message,
+    bot_deps: &BotDependencies,
+    state: ModerationState,
+    text: String,
+) -> Result<bool> {
+    let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or_default();
+
+    if text.chars().count() > MAX_DESCRIPTION_CHARS {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "❌ Please keep it under {} characters.",
+                MAX_DESCRIPTION_CHARS
+            ),
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    if state.step == STEP_AWAITING_DESCRIPTION {
+        draft_and_show(bot, bot_deps, msg.chat.id, user_id, state, text, None).await?;
+    } else {
+        let description = state
+            .rules_draft
+            .as_ref()
+            .map(|d| d.description.clone())
+            .unwrap_or_default();
+        draft_and_show(bot, bot_deps, msg.chat.id, user_id, state, description, Some(&text))
+            .await?;
+    }
+    Ok(true)
+}
+
+/// `mod_ai_*` callbacks: start, regenerate, accept or cancel the builder
+pub async fn handle_rules_builder_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let Some(teloxide::types::MaybeInaccessibleMessage::Regular(m)) = query.message.clone() else {
+        return Ok(());
+    };
+    let chat_id = m.chat.id;
+    let user_id = query.from.id.0 as i64;
+
+    if !is_admin(&bot, chat_id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can manage moderation settings")
+            .await?;
+        return Ok(());
+    }
+
+    if data == "mod_ai_start" {
+        let mut state = ModerationState::from((
+            STEP_AWAITING_DESCRIPTION.to_string(),
+            None,
+            None,
+            user_id,
+        ));
+        let sent = bot
+            .send_message(
+                chat_id,
+                "✨ <b>Draft rules with AI</b>\n\nDescribe your community in a few sentences: what it's about, what members usually discuss, and anything you want kept out.\n\n<b>Example</b>:\n<i>A community for our NFT project on Aptos. Price talk and memes are fine, but no shilling other projects, no political arguments, and keep it English only.</i>\n\nSend your description as your next message. Drafting is billed like a normal AI request.",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback("❌ Cancel", "mod_ai_cancel"),
+            ]]))
+            .await?;
+        state.message_id = Some(sent.id.0 as i64);
+        bot_deps
+            .moderation
+            .set_moderation_state(chat_id.to_string(), state)?;
+        bot.answer_callback_query(query.id)
+            .text("✨ Describe your community")
+            .await?;
+        return Ok(());
+    }
+
+    let state = match bot_deps.moderation.get_moderation_state(chat_id.to_string()) {
+        Ok(state) if is_builder_step(&state.step) => state,
+        _ => {
+            bot.answer_callback_query(query.id)
+                .text("❌ No active rules draft")
+                .await?;
+            return Ok(());
+        }
+    };
+    if state.started_by_user_id.is_some_and(|owner| owner != user_id) {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only the admin who started the draft can do this")
+            .await?;
+        return Ok(());
+    }
+
+    match data.as_str() {
+        "mod_ai_regen" => {
+            let Some(description) = state.rules_draft.as_ref().map(|d| d.description.clone())
+            else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Send a description first")
+                    .await?;
+                return Ok(());
+            };
+            bot.answer_callback_query(query.id).text("🔄 Regenerating…").await?;
+            draft_and_show(&bot, &bot_deps, chat_id, user_id, state, description, None).await?;
+        }
+        "mod_ai_accept" => {
+            let Some(draft) = state.rules_draft.clone() else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Nothing to save yet")
+                    .await?;
+                return Ok(());
+            };
+            // Keep the flag action and pass reaction; only the custom rules change
+            let mut settings = bot_deps
+                .moderation
+                .get_moderation_settings(chat_id.to_string())
+                .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+            settings.allowed_items = draft.allowed_items.clone();
+            settings.disallowed_items = draft.disallowed_items.clone();
+            settings.updated_by_user_id = user_id;
+            settings.updated_at_unix_ms = chrono::Utc::now().timestamp_millis();
+            bot_deps
+                .moderation
+                .set_or_update_moderation_settings(chat_id.to_string(), settings)?;
+            bot_deps
+                .moderation
+                .remove_moderation_state(chat_id.to_string())?;
+
+            bot.answer_callback_query(query.id).text("✅ Rules saved").await?;
+            bot.edit_message_text(
+                chat_id,
+                m.id,
+                format!(
+                    "✅ <b>Custom moderation rules saved.</b>\n\n<b>Allowed ({})</b>:\n{}\n\n<b>Disallowed ({})</b>:\n{}",
+                    draft.allowed_items.len(),
+                    format_list(&draft.allowed_items),
+                    draft.disallowed_items.len(),
+                    format_list(&draft.disallowed_items),
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        }
+        "mod_ai_cancel" => {
+            bot_deps
+                .moderation
+                .remove_moderation_state(chat_id.to_string())?;
+            bot.answer_callback_query(query.id).text("Draft discarded").await?;
+            let _ = bot.delete_message(chat_id, m.id).await;
+        }
+        _ => {
+            bot.answer_callback_query(query.id)
+                .text("❌ Unknown action")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_draft_from_fenced_reply() {
+        let reply = "```json\n{\"allowed\": [\"memes about APT\"], \"disallowed\": [\"shilling; other projects\", \"  \", \"SHILLING, other projects\"]}\n```";
+        let (allowed, disallowed) = parse_draft(reply).unwrap();
+        assert_eq!(allowed, vec!["memes about APT"]);
+        assert_eq!(disallowed, vec!["shilling, other projects"]);
+    }
+
+    #[test]
+    fn test_parse_draft_rejects_empty_or_invalid() {
+        assert_eq!(parse_draft("{\"allowed\": [], \"disallowed\": []}"), None);
+        assert_eq!(parse_draft("no json here"), None);
+    }
+}
@@ -0,0 +1,63 @@
+use std::env;
+
+use anyhow::Result;
+use open_ai_rust_responses_by_sshift::{Client as OAIClient, Config, RecoveryPolicy};
+use reqwest::Url;
+
+/// The SDK's own endpoint, used when OPENAI_BASE_URL is unset
+const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Optional override for the OpenAI endpoint (proxy, Azure OpenAI, regional routing).
+/// When unset the SDK's default `https://api.openai.com/v1` is used.
+pub fn get_openai_base_url() -> Option<String> {
+    env::var("OPENAI_BASE_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Endpoint for requests made outside the SDK client, so they follow OPENAI_BASE_URL too
+pub fn openai_base_url() -> String {
+    get_openai_base_url().unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string())
+}
+
+/// Validate OPENAI_BASE_URL at startup so a typo fails fast instead of on the first AI call
+pub fn validate_openai_base_url() -> Result<()> {
+    if let Some(base_url) = get_openai_base_url() {
+        let url = Url::parse(&base_url)
+            .map_err(|e| anyhow::anyhow!("OPENAI_BASE_URL is not a valid URL: {}", e))?;
+
+        if url.scheme() != "https" && url.scheme() != "http" {
+            return Err(anyhow::anyhow!(
+                "OPENAI_BASE_URL must use http or https, got {}",
+                url.scheme()
+            ));
+        }
+
+        log::info!("Using custom OpenAI base URL: {}", base_url);
+    }
+
+    Ok(())
+}
+
+/// Build an OpenAI client that honors OPENAI_BASE_URL. Every client construction
+/// goes through here so the main AI, summarizer, moderation and schedule guard
+/// all talk to the same endpoint.
+pub fn build_openai_client(
+    api_key: &str,
+    recovery_policy: Option<RecoveryPolicy>,
+) -> Result<OAIClient> {
+    let client = match (get_openai_base_url(), recovery_policy) {
+        (Some(base_url), Some(policy)) => OAIClient::new_with_config_and_recovery(
+            Config::new(api_key).with_base_url(base_url),
+            policy,
+        )?,
+        (Some(base_url), None) => {
+            OAIClient::new_with_config(Config::new(api_key).with_base_url(base_url))?
+        }
+        (None, Some(policy)) => OAIClient::new_with_recovery(api_key, policy)?,
+        (None, None) => OAIClient::new(api_key)?,
+    };
+
+    Ok(client)
+}
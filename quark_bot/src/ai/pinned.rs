@@ -0,0 +1,65 @@
+//! Pinned-message context for group AI replies, with a short in-memory cache.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use teloxide::{prelude::*, types::ChatId};
+
+/// How long a fetched pinned message is reused before asking Telegram again
+const CACHE_TTL: Duration = Duration::from_secs(300);
+/// Keep the extra instructions small; pinned rules/FAQs are rarely longer
+const MAX_PINNED_CHARS: usize = 1500;
+
+#[derive(Clone, Default)]
+pub struct PinnedMessageCache {
+    entries: Arc<DashMap<ChatId, (Instant, Option<String>)>>,
+}
+
+impl PinnedMessageCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Text of the chat's pinned message, or `None` when nothing (textual) is pinned
+    pub async fn get(&self, bot: &Bot, chat_id: ChatId) -> Option<String> {
+        if let Some(entry) = self.entries.get(&chat_id) {
+            let (fetched_at, text) = entry.value();
+            if fetched_at.elapsed() < CACHE_TTL {
+                return text.clone();
+            }
+        }
+
+        let text = match bot.get_chat(chat_id).await {
+            Ok(chat) => chat.pinned_message.and_then(|pinned| {
+                pinned
+                    .text()
+                    .or_else(|| pinned.caption())
+                    .map(|text| truncate(text.trim(), MAX_PINNED_CHARS))
+                    .filter(|text| !text.is_empty())
+            }),
+            Err(e) => {
+                // Don't cache failures so the next request can retry
+                log::warn!("Failed to fetch pinned message for chat {}: {}", chat_id, e);
+                return None;
+            }
+        };
+
+        self.entries.insert(chat_id, (Instant::now(), text.clone()));
+        text
+    }
+
+    /// Forget the cached copy, e.g. after the pin changed
+    pub fn invalidate(&self, chat_id: ChatId) {
+        self.entries.remove(&chat_id);
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(max).collect();
+        format!("{}…", cut)
+    }
+}
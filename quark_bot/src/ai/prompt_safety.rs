@@ -0,0 +1,142 @@
+//! Optional check of /c prompts against OpenAI's moderation endpoint, run before anything is
+//! generated or billed. Separate from sentinel, which moderates group messages.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::ai::openai_client::openai_base_url;
+
+const MODERATION_MODEL: &str = "omni-moderation-latest";
+
+static MODE: OnceLock<PromptSafetyMode> = OnceLock::new();
+
+/// Operator switch from PROMPT_SAFETY_CHECK
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptSafetyMode {
+    /// Never check (default)
+    Off,
+    /// Only groups that turned the check on in Command Settings
+    OptIn,
+    /// Every /c prompt, in groups and DMs
+    Always,
+}
+
+impl PromptSafetyMode {
+    fn parse(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "opt_in" | "optin" | "groups" => PromptSafetyMode::OptIn,
+            "always" | "all" => PromptSafetyMode::Always,
+            _ => PromptSafetyMode::Off,
+        }
+    }
+}
+
+pub fn mode() -> PromptSafetyMode {
+    *MODE.get_or_init(|| {
+        env::var("PROMPT_SAFETY_CHECK")
+            .map(|v| PromptSafetyMode::parse(&v))
+            .unwrap_or(PromptSafetyMode::Off)
+    })
+}
+
+#[derive(Deserialize)]
+struct ModerationResponse {
+    results: Vec<ModerationVerdict>,
+}
+
+#[derive(Deserialize)]
+struct ModerationVerdict {
+    flagged: bool,
+    #[serde(default)]
+    categories: BTreeMap<String, bool>,
+}
+
+/// Readable names of the categories a flagged verdict hit; empty when the prompt is allowed
+fn flagged_categories(response: ModerationResponse) -> Vec<String> {
+    response
+        .results
+        .into_iter()
+        .filter(|verdict| verdict.flagged)
+        .flat_map(|verdict| {
+            let hits: Vec<String> = verdict
+                .categories
+                .into_iter()
+                .filter(|(_, hit)| *hit)
+                .map(|(name, _)| name.replace(['/', '-'], " "))
+                .collect();
+            // A flagged verdict always reports something, even without a named category
+            if hits.is_empty() {
+                vec!["disallowed content".to_string()]
+            } else {
+                hits
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct PromptSafety {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl PromptSafety {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key,
+        }
+    }
+
+    /// Whether a prompt in this chat must be checked, given the group's own opt-in
+    pub fn should_check(&self, is_group: bool, group_opted_in: bool) -> bool {
+        match mode() {
+            PromptSafetyMode::Off => false,
+            PromptSafetyMode::OptIn => is_group && group_opted_in,
+            PromptSafetyMode::Always => true,
+        }
+    }
+
+    /// Categories the prompt was flagged for; empty when it may be answered
+    pub async fn check(&self, prompt: &str) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .post(format!("{}/moderations", openai_base_url()))
+            .bearer_auth(&self.api_key)
+            .json(&json!({ "model": MODERATION_MODEL, "input": prompt }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ModerationResponse>()
+            .await?;
+        Ok(flagged_categories(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flagged_categories() {
+        let response: ModerationResponse = serde_json::from_value(json!({
+            "results": [{
+                "flagged": true,
+                "categories": { "harassment": true, "self-harm/intent": true, "violence": false }
+            }]
+        }))
+        .unwrap();
+        assert_eq!(flagged_categories(response), vec!["harassment", "self harm intent"]);
+
+        let allowed: ModerationResponse = serde_json::from_value(json!({
+            "results": [{ "flagged": false, "categories": { "harassment": false } }]
+        }))
+        .unwrap();
+        assert!(flagged_categories(allowed).is_empty());
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::Result;
 use open_ai_rust_responses_by_sshift::{Client, Model, Request, ReasoningEffort, Verbosity};
 
 use super::dto::ScheduleGuardResult;
+use crate::ai::openai_client::build_openai_client;
 
 #[derive(Clone)]
 pub struct ScheduleGuardService {
@@ -10,7 +11,7 @@ pub struct ScheduleGuardService {
 
 impl ScheduleGuardService {
 	pub fn new(api_key: String) -> Result<Self> {
-		let client = Client::new(&api_key)?;
+		let client = build_openai_client(&api_key, None)?;
 		Ok(Self { client })
 	}
 
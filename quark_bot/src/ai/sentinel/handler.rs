@@ -1,17 +1,121 @@
 use anyhow::Result as AnyResult;
 use open_ai_rust_responses_by_sshift::Model;
-use teloxide::{prelude::*, sugar::request::RequestReplyExt, types::{InlineKeyboardButton, InlineKeyboardMarkup, Message, ParseMode}};
+use teloxide::{prelude::*, sugar::request::RequestReplyExt, types::{InputFile, Message, ParseMode}};
+
+use crate::{ai::moderation::{appeal::with_appeal_button, dto::{ModerationAction, ModerationOverrides}, enforcement::enforce_moderation_action, flagged_log::{FlagSource, FlaggedEvent, log_flagged_event}, link_allowlist::{STEP_AWAITING_DOMAIN, message_domains, only_allowlisted_links}, rules_builder::is_builder_step}, dependencies::BotDependencies, group::dto::GroupCredentials, group_budget::handler::group_ai_allowed, payment::dto::PaymentPrefs, utils::{create_purchase_request, send_scheduled_message, topic_thread_id, wallet_qr_png}};
+
+/// Ask the group to top up, attaching the address as a QR so admins can scan it from a
+/// wallet app. Falls back to the plain text message if the QR can't be rendered or sent.
+async fn send_funding_prompt(bot: &Bot, msg: &Message, address: &str, caption: String) -> AnyResult<()> {
+    let thread_id = topic_thread_id(msg);
+
+    match wallet_qr_png(address) {
+        Ok(png) => {
+            let request = bot
+                .send_photo(msg.chat.id, InputFile::memory(png).file_name("wallet.png"))
+                .caption(caption.clone())
+                .parse_mode(ParseMode::Html);
+            let sent = if let Some(thread_id) = thread_id {
+                request.message_thread_id(thread_id).await
+            } else {
+                request.await
+            };
+            match sent {
+                Ok(_) => return Ok(()),
+                Err(e) => log::warn!("Failed to send wallet QR, falling back to text: {}", e),
+            }
+        }
+        Err(e) => log::warn!("Failed to render wallet QR for {}: {}", address, e),
+    }
+
+    let request = bot.send_message(msg.chat.id, caption);
+    if let Some(thread_id) = thread_id {
+        request.message_thread_id(thread_id).parse_mode(ParseMode::Html).await?;
+    } else {
+        request.parse_mode(ParseMode::Html).await?;
+    }
+    Ok(())
+}
+
+/// A group's balance next to the minimum the sentinel needs before it stops moderating
+pub struct GroupBalanceStatus {
+    pub balance: i64,
+    pub min_deposit: u64,
+    pub decimals: u8,
+    pub symbol: String,
+}
+
+impl GroupBalanceStatus {
+    pub fn below_minimum(&self) -> bool {
+        self.balance < self.min_deposit as i64
+    }
+
+    pub fn format_amount(&self, amount: i64) -> String {
+        format!("{:.2}", amount as f64 / 10_f64.powi(self.decimals as i32))
+    }
+}
+
+/// Why the balance couldn't be priced against the minimum deposit
+#[derive(Debug)]
+pub enum BalanceStatusError {
+    TokenNotFound,
+    PriceNotFound,
+}
+
+impl BalanceStatusError {
+    pub fn user_message(&self) -> &'static str {
+        match self {
+            BalanceStatusError::TokenNotFound => "❌ Token not found, please contact support",
+            BalanceStatusError::PriceNotFound => "❌ Token price not found, please contact support",
+        }
+    }
+}
+
+/// Fetch the group's balance in its payment token and the minimum deposit at the current price
+pub async fn group_balance_status(
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+    address: &str,
+) -> AnyResult<Result<GroupBalanceStatus, BalanceStatusError>> {
+    let default_payment_prefs = bot_deps.default_payment_prefs.clone();
 
-use crate::{ai::moderation::dto::ModerationOverrides, dependencies::BotDependencies, payment::dto::PaymentPrefs, utils::{create_purchase_request, send_scheduled_message}};
+    let coin = bot_deps.payment.get_payment_token(chat_id.to_string(), bot_deps).await.unwrap_or(PaymentPrefs::from((default_payment_prefs.label, default_payment_prefs.currency, default_payment_prefs.version)));
+
+    let balance = bot_deps
+        .panora
+        .aptos
+        .get_account_balance(address, &coin.currency)
+        .await?;
+
+    let Ok(token) = bot_deps.panora.get_token_by_symbol(&coin.label).await else {
+        return Ok(Err(BalanceStatusError::TokenNotFound));
+    };
+
+    let Some(token_price) = token.usd_price.as_deref().and_then(|p| p.parse::<f64>().ok()) else {
+        return Ok(Err(BalanceStatusError::PriceNotFound));
+    };
+
+    let min_deposit = (bot_deps.panora.min_deposit / 10_f64) / token_price;
+
+    let min_deposit = (min_deposit * 10_f64.powi(token.decimals as i32)) as u64;
+
+    Ok(Ok(GroupBalanceStatus {
+        balance,
+        min_deposit,
+        decimals: token.decimals,
+        symbol: token.symbol,
+    }))
+}
 
 pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDependencies, chat_id: String) -> AnyResult<bool> {
-    let thread_id = msg.thread_id;
+    // The flagged message is usually deleted, so notices go to its topic rather than replying to it
+    let thread_id = topic_thread_id(&msg);
     let sentinel_on = bot_deps.sentinel.get_sentinel(chat_id.clone());
     if sentinel_on {
         // Skip moderation if there's an active moderation settings wizard
         if let Some(_) = &msg.from {
             if let Ok(moderation_state) = bot_deps.moderation.get_moderation_state(chat_id.clone()) {
-                if moderation_state.step == "AwaitingAllowed" || moderation_state.step == "AwaitingDisallowed" {
+                if moderation_state.step == "AwaitingAllowed" || moderation_state.step == "AwaitingDisallowed" || moderation_state.step == STEP_AWAITING_DOMAIN || is_builder_step(&moderation_state.step) {
                     log::info!("Sentinel moderation state is {}, skipping moderation", moderation_state.step);
                     return Ok(true);
                 }
@@ -39,6 +143,15 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
                 return Ok(true);
             }
         } 
+
+        // A bare link the group trusts (its site, docs, explorer) skips the model, and its cost
+        let message_text = msg.text().or_else(|| msg.caption()).unwrap_or("");
+        let domains = message_domains(&msg);
+        let link_allowlist = bot_deps.link_allowlist.get(msg.chat.id);
+        if only_allowlisted_links(message_text, &domains, &link_allowlist) {
+            log::info!("Sentinel skipped message {} in {}: only allowlisted links {:?}", msg.id.0, msg.chat.id, domains);
+            return Ok(true);
+        }
         
         let group_credentials = bot_deps.group.get_credentials(msg.chat.id);
 
@@ -48,85 +161,50 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
 
         let group_credentials = group_credentials.unwrap();
 
-        let address = group_credentials.resource_account_address;
-
-        let default_payment_prefs = bot_deps.default_payment_prefs.clone();
-
-        let coin = bot_deps.payment.get_payment_token(msg.chat.id.to_string(), &bot_deps).await.unwrap_or(PaymentPrefs::from((default_payment_prefs.label, default_payment_prefs.currency, default_payment_prefs.version)));
-
-        let group_balance = bot_deps
-        .panora
-        .aptos
-        .get_account_balance(&address, &coin.currency)
-            .await?;
-
-        let token = bot_deps.panora.get_token_by_symbol(&coin.label).await;
-
-        if token.is_err() {
-            send_scheduled_message(&bot, msg.chat.id,"❌ Token not found, please contact support", if let Some(thread_id) = thread_id { Some(thread_id.0.0) } else { None })
-                .await?;
-            return Ok(true);
-        }
-
-        let token = token.unwrap();
+        // Billing fails on an expired group JWT, so re-issue it before spending on moderation
+        let group_credentials = if bot_deps
+            .group
+            .jwt_manager
+            .is_group_token_valid(&group_credentials.jwt)
+        {
+            group_credentials
+        } else {
+            match refresh_group_credentials(&bot, &bot_deps, msg.chat.id).await {
+                Some(credentials) => credentials,
+                None => return Ok(true),
+            }
+        };
 
-        let token_price = token.usd_price;
+        let address = group_credentials.resource_account_address;
 
-        if token_price.is_none() {
-            send_scheduled_message(&bot, msg.chat.id,"❌ Token price not found, please contact support", if let Some(thread_id) = thread_id { Some(thread_id.0.0) } else { None })
-                .await?;
-            return Ok(true);
-        }
+        let status = match group_balance_status(&bot_deps, msg.chat.id, &address).await? {
+            Ok(status) => status,
+            Err(e) => {
+                send_scheduled_message(&bot, msg.chat.id, e.user_message(), if let Some(thread_id) = thread_id { Some(thread_id.0.0) } else { None })
+                    .await?;
+                return Ok(true);
+            }
+        };
 
-        let token_price = token_price.unwrap();
+        if status.below_minimum() {
+            let caption = format!(
+                "User balance is less than the minimum deposit. Please fund your account transfering {} to <code>{}</code> address. Minimum deposit: {} {} (Your balance: {} {})",
+                status.symbol,
+                address,
+                status.format_amount(status.min_deposit as i64),
+                status.symbol,
+                status.format_amount(status.balance),
+                status.symbol
+            );
 
-        let token_price = token_price.parse::<f64>();
+            send_funding_prompt(&bot, &msg, &address, caption).await?;
 
-        if token_price.is_err() {
-            send_scheduled_message(&bot, msg.chat.id,"❌ Token price not found, please contact support", if let Some(thread_id) = thread_id { Some(thread_id.0.0) } else { None })
-                .await?;
             return Ok(true);
         }
 
-        let token_price = token_price.unwrap();
-
-        let token_decimals = token.decimals;
-
-        let min_deposit = (bot_deps.panora.min_deposit / 10_f64) / token_price;
-
-        let min_deposit = (min_deposit as f64 * 10_f64.powi(token_decimals as i32)) as u64;
-
-        if group_balance < min_deposit as i64 {
-            let min_deposit_formatted = format!(
-                "{:.2}",
-                min_deposit as f64 / 10_f64.powi(token_decimals as i32)
-            );
-
-            let group_balance_formatted = format!(
-                "{:.2}",
-                group_balance as f64 / 10_f64.powi(token_decimals as i32)
-            );
-
-            let request= bot.send_message(
-                msg.chat.id,
-                format!(
-                    "User balance is less than the minimum deposit. Please fund your account transfering {} to <code>{}</code> address. Minimum deposit: {} {} (Your balance: {} {})",
-                    token.symbol, 
-                    address,
-                    min_deposit_formatted,
-                    token.symbol,
-                    group_balance_formatted,
-                    token.symbol
-                )
-            );
-
-            if let Some(thread_id) = thread_id {
-                request.reply_to(thread_id.0).parse_mode(ParseMode::Html).await?;
-            } else {
-                request.parse_mode(ParseMode::Html).await?;
-            }
-            
-            return Ok(true);
+        // Over-budget groups keep their messages, just without AI moderation
+        if !group_ai_allowed(&bot, &msg, &bot_deps, false).await? {
+            return Ok(false);
         }
 
         // Use the same moderation logic as /mod, via injected dependency
@@ -134,18 +212,21 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
         // Load overrides
         let overrides = bot_deps.moderation.get_moderation_settings(chat_id);
 
-        let overrides = match overrides {
-            Ok(overrides) => Some(ModerationOverrides {
-                allowed_items: overrides.allowed_items,
-                disallowed_items: overrides.disallowed_items,
-            }),
+        let (overrides, moderation_action) = match overrides {
+            Ok(overrides) => (
+                Some(ModerationOverrides {
+                    allowed_items: overrides.allowed_items,
+                    disallowed_items: overrides.disallowed_items,
+                    permitted_domains: link_allowlist,
+                }),
+                overrides.action,
+            ),
             Err(e) => {
                 log::error!("Failed to get moderation settings: {}", e);
-                None
+                (None, ModerationAction::default())
             }
         };
 
-        let message_text = msg.text().or_else(|| msg.caption()).unwrap_or("");
         match moderation_service
             .moderate_message(message_text, &bot, &msg, &msg, overrides)
             .await
@@ -167,7 +248,7 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
                     &group_credentials.jwt,
                     Some(msg.chat.id.0.to_string()),
                     None,
-                    bot_deps,
+                    bot_deps.clone(),
                 )
                 .await;
 
@@ -177,41 +258,27 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
                 }
                 
                 if result.verdict == "F" {
-                    // Mute the user
                     if let Some(flagged_user) = &msg.from {
-                        let restricted_permissions = teloxide::types::ChatPermissions::empty();
-
-                        // Check if the user is already muted
-                        if let Err(mute_error) = bot
-                            .restrict_chat_member(
-                                msg.chat.id,
-                                flagged_user.id,
-                                restricted_permissions,
-                            )
-                            .await
-                        {
-                            log::error!(
-                                "Failed to mute user {}: {}",
-                                flagged_user.id,
-                                mute_error
-                            );
-                        } else {
-                            log::info!(
-                                "Successfully muted user {} for flagged content (sentinel)",
-                                flagged_user.id
-                            );
-                        }
-                        // Add admin buttons
-                        let keyboard = InlineKeyboardMarkup::new(vec![vec![
-                            InlineKeyboardButton::callback(
-                                "🔇 Unmute",
-                                format!("unmute:{}", flagged_user.id),
-                            ),
-                            InlineKeyboardButton::callback(
-                                "🚫 Ban",
-                                format!("ban:{}:{}", flagged_user.id, msg.id.0),
-                            ),
-                        ]]);
+                        let enforcement = enforce_moderation_action(
+                            &bot,
+                            msg.chat.id,
+                            flagged_user,
+                            msg.id,
+                            moderation_action,
+                        )
+                        .await;
+                        let event = FlaggedEvent::new(
+                            msg.chat.id,
+                            msg.id.0,
+                            flagged_user,
+                            message_text,
+                            FlagSource::Sentinel,
+                            moderation_action,
+                            &enforcement.status,
+                        );
+                        let keyboard = with_appeal_button(enforcement.keyboard, &event);
+                        log_flagged_event(&bot_deps, event);
+
                         // Build a visible user mention (prefer @username, else clickable name)
                         let user_mention = if let Some(username) = &flagged_user.username {
                             format!("@{}", username)
@@ -226,8 +293,10 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
                         let request= bot.send_message(
                             msg.chat.id,
                             format!(
-                                "🛡️ <b>Content Flagged & User Muted</b>\n\n📝 Message ID: <code>{}</code>\n\n❌ Status: <b>FLAGGED</b> 🔴\n🔇 User has been muted\n👤 <b>User:</b> {}\n\n💬 <i>Flagged message:</i>\n<blockquote><span class=\"tg-spoiler\">{}</span></blockquote>",
+                                "🛡️ <b>{}</b>\n\n📝 Message ID: <code>{}</code>\n\n❌ Status: <b>FLAGGED</b> 🔴\n{}\n👤 <b>User:</b> {}\n\n💬 <i>Flagged message:</i>\n<blockquote><span class=\"tg-spoiler\">{}</span></blockquote>",
+                                enforcement.title,
                                 msg.id,
+                                enforcement.status,
                                 user_mention,
                                 teloxide::utils::html::escape(message_text)
                             )
@@ -236,18 +305,18 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
                         .reply_markup(keyboard);
 
                         if let Some(thread_id) = thread_id {
-                            request.reply_to(thread_id.0).parse_mode(ParseMode::Html).await?;
+                            request.message_thread_id(thread_id).await?;
                         } else {
                             request.parse_mode(ParseMode::Html).await?;
                         }
-                        // Immediately remove the offending message from the chat
-                    }
-                    if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
-                        log::warn!(
-                            "Failed to delete offending message {}: {}",
-                            msg.id.0,
-                            e
-                        );
+                    } else if moderation_action != ModerationAction::Warn {
+                        if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+                            log::warn!(
+                                "Failed to delete offending message {}: {}",
+                                msg.id.0,
+                                e
+                            );
+                        }
                     }
                 }
             }
@@ -259,4 +328,64 @@ pub async fn handle_message_sentinel(bot: Bot, msg: Message, bot_deps: BotDepend
     }
 
     Ok(false)
-}
\ No newline at end of file
+}
+
+/// Re-issue an expired group JWT. Attempts are rate limited per chat so a real outage
+/// doesn't trigger a re-issue on every message; admins are told when it fails.
+async fn refresh_group_credentials(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    chat_id: ChatId,
+) -> Option<GroupCredentials> {
+    if !bot_deps.sentinel.try_begin_jwt_refresh(chat_id) {
+        log::debug!("Sentinel JWT refresh for {} is cooling down", chat_id);
+        return None;
+    }
+
+    log::info!("Group JWT expired for {}, re-issuing for sentinel", chat_id);
+
+    match bot_deps
+        .group
+        .refresh_credentials(chat_id, bot_deps.panora.clone())
+        .await
+    {
+        Ok(credentials) => {
+            log::info!("Sentinel refreshed group credentials for {}", chat_id);
+            return Some(credentials);
+        }
+        Err(e) => log::error!("Failed to refresh group credentials for {}: {}", chat_id, e),
+    }
+
+    let admins = match bot.get_chat_administrators(chat_id).await {
+        Ok(admins) => admins,
+        Err(e) => {
+            log::warn!(
+                "Failed to load admins of {} for the refresh notice: {}",
+                chat_id,
+                e
+            );
+            return None;
+        }
+    };
+    let title = match bot.get_chat(chat_id).await {
+        Ok(chat) => chat.title().unwrap_or("your group").to_string(),
+        Err(_) => "your group".to_string(),
+    };
+    let text = format!(
+        "⚠️ Sentinel couldn't renew the credentials of \"{}\", so moderation is paused there. Run /logingroup in the group to restore it.",
+        title
+    );
+
+    // Admins who never started the bot can't be messaged; skip them
+    for admin in admins.iter().filter(|admin| !admin.user.is_bot) {
+        if let Err(e) = bot.send_message(admin.user.id, text.clone()).await {
+            log::debug!(
+                "Couldn't notify admin {} about the refresh failure: {}",
+                admin.user.id,
+                e
+            );
+        }
+    }
+
+    None
+}
@@ -0,0 +1,212 @@
+//! Early warning for sentinel groups whose balance is approaching the hard minimum.
+
+use anyhow::Result as AnyResult;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+};
+
+use crate::{dependencies::BotDependencies, utils};
+
+const TREE_NAME: &str = "low_balance_alerts";
+/// Alert when the balance drops below this many times the minimum deposit
+pub const DEFAULT_MULTIPLIER: u32 = 3;
+pub const MULTIPLIER_CHOICES: [u32; 4] = [2, 3, 5, 10];
+/// Minimum gap between two alerts for the same group while it stays low
+pub const ALERT_COOLDOWN_SECS: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LowBalanceSettings {
+    /// Threshold as a multiple of the sentinel minimum deposit; 0 turns alerts off
+    pub multiplier: u32,
+    #[serde(default)]
+    pub last_alerted_at: Option<i64>,
+}
+
+impl Default for LowBalanceSettings {
+    fn default() -> Self {
+        Self {
+            multiplier: DEFAULT_MULTIPLIER,
+            last_alerted_at: None,
+        }
+    }
+}
+
+impl LowBalanceSettings {
+    pub fn enabled(&self) -> bool {
+        self.multiplier > 0
+    }
+
+    pub fn threshold(&self, min_deposit: u64) -> u64 {
+        min_deposit.saturating_mul(self.multiplier as u64)
+    }
+
+    /// True when the group is under the threshold and hasn't been pinged recently
+    pub fn should_alert(&self, balance: i64, min_deposit: u64, now: i64) -> bool {
+        self.enabled()
+            && balance < self.threshold(min_deposit) as i64
+            && self
+                .last_alerted_at
+                .is_none_or(|at| now - at >= ALERT_COOLDOWN_SECS)
+    }
+
+    pub fn label(&self) -> String {
+        if self.enabled() {
+            format!("{}× minimum", self.multiplier)
+        } else {
+            "OFF".to_string()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LowBalanceAlerts {
+    tree: Tree,
+}
+
+impl LowBalanceAlerts {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn get(&self, chat_id: ChatId) -> LowBalanceSettings {
+        self.tree
+            .get(chat_id.0.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&self, chat_id: ChatId, settings: &LowBalanceSettings) -> sled::Result<()> {
+        self.tree.insert(
+            chat_id.0.to_be_bytes(),
+            serde_json::to_vec(settings).unwrap(),
+        )?;
+        Ok(())
+    }
+
+    pub fn set_multiplier(&self, chat_id: ChatId, multiplier: u32) -> sled::Result<()> {
+        let mut settings = self.get(chat_id);
+        settings.multiplier = multiplier;
+        settings.last_alerted_at = None;
+        self.set(chat_id, &settings)
+    }
+
+    pub fn mark_alerted(&self, chat_id: ChatId, now: i64) -> sled::Result<()> {
+        let mut settings = self.get(chat_id);
+        settings.last_alerted_at = Some(now);
+        self.set(chat_id, &settings)
+    }
+
+    /// Forget the last alert once the group is topped up, so the next dip alerts right away
+    pub fn clear_alerted(&self, chat_id: ChatId) -> sled::Result<()> {
+        let mut settings = self.get(chat_id);
+        if settings.last_alerted_at.take().is_some() {
+            self.set(chat_id, &settings)?;
+        }
+        Ok(())
+    }
+}
+
+/// `mod_lowbal_menu` and `mod_lowbal_set:<multiplier>` from the moderation menu
+pub async fn handle_low_balance_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+    let data = query.data.clone().unwrap_or_default();
+
+    if !utils::is_admin(&bot, m.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can manage moderation settings")
+            .await?;
+        return Ok(());
+    }
+
+    if let Some(value) = data.strip_prefix("mod_lowbal_set:") {
+        let Ok(multiplier) = value.parse::<u32>() else {
+            bot.answer_callback_query(query.id)
+                .text("❌ Unknown threshold")
+                .await?;
+            return Ok(());
+        };
+        bot_deps
+            .low_balance_alerts
+            .set_multiplier(m.chat.id, multiplier)?;
+        let text = if multiplier == 0 {
+            "🔕 Low balance alerts turned off".to_string()
+        } else {
+            format!("✅ Alerting below {}× the minimum", multiplier)
+        };
+        bot.answer_callback_query(query.id.clone()).text(text).await?;
+    } else {
+        bot.answer_callback_query(query.id.clone()).await?;
+    }
+
+    let current = bot_deps.low_balance_alerts.get(m.chat.id).multiplier;
+    let mark = |value: u32, label: String| {
+        if value == current {
+            format!("✅ {}", label)
+        } else {
+            label
+        }
+    };
+
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = MULTIPLIER_CHOICES
+        .iter()
+        .map(|value| {
+            vec![InlineKeyboardButton::callback(
+                mark(*value, format!("{}× minimum deposit", value)),
+                format!("mod_lowbal_set:{}", value),
+            )]
+        })
+        .collect();
+    rows.push(vec![InlineKeyboardButton::callback(
+        mark(0, "🔕 Off".to_string()),
+        "mod_lowbal_set:0",
+    )]);
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "open_moderation_settings",
+    )]);
+
+    bot.edit_message_text(
+        m.chat.id,
+        m.id,
+        format!(
+            "🔔 <b>Low Balance Alert</b>\n\nSentinel stops moderating once the group balance falls below the minimum deposit. Get a heads-up in this chat before that happens.\n\nAlert when the balance drops below a multiple of the minimum. Alerts repeat at most once every {} hours while the balance stays low.",
+            ALERT_COOLDOWN_SECS / 3600
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(InlineKeyboardMarkup::new(rows))
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_alert_debounces() {
+        let mut settings = LowBalanceSettings::default();
+        assert!(settings.should_alert(250, 100, 0));
+        assert!(!settings.should_alert(300, 100, 0));
+
+        settings.last_alerted_at = Some(1_000);
+        assert!(!settings.should_alert(50, 100, 1_000 + ALERT_COOLDOWN_SECS - 1));
+        assert!(settings.should_alert(50, 100, 1_000 + ALERT_COOLDOWN_SECS));
+
+        settings.multiplier = 0;
+        assert!(!settings.should_alert(0, 100, i64::MAX));
+    }
+}
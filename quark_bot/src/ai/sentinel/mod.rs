@@ -1,2 +1,4 @@
 pub mod handler;
+pub mod low_balance;
 pub mod sentinel;
+pub mod snooze;
@@ -1,11 +1,21 @@
-use std::env;
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use sled::{Db, Tree};
+use teloxide::types::ChatId;
+
+/// Minimum time between group JWT re-issue attempts for the same chat
+const JWT_REFRESH_COOLDOWN: Duration = Duration::from_secs(10 * 60);
 
 #[derive(Clone)]
 pub struct SentinelService {
     pub(crate) db: Tree,
     pub(crate) account_seed: String,
+    jwt_refresh_attempts: Arc<Mutex<HashMap<ChatId, Instant>>>,
 }
 
 impl SentinelService {
@@ -17,6 +27,7 @@ impl SentinelService {
         Self {
             db: tree,
             account_seed,
+            jwt_refresh_attempts: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -47,4 +58,32 @@ impl SentinelService {
             .insert(key.as_bytes(), value.to_string().as_bytes())
             .unwrap();
     }
+
+    /// Chats that currently have sentinel switched on
+    pub fn enabled_chat_ids(&self) -> Vec<ChatId> {
+        let suffix = format!("_{}", self.account_seed);
+        self.db
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(_, v)| serde_json::from_slice::<bool>(v).unwrap_or(false))
+            .filter_map(|(k, _)| {
+                let key = String::from_utf8(k.to_vec()).ok()?;
+                key.strip_suffix(&suffix)?.parse::<i64>().ok().map(ChatId)
+            })
+            .collect()
+    }
+
+    /// Returns true (and records the attempt) when a JWT re-issue may be tried for this chat
+    pub fn try_begin_jwt_refresh(&self, chat_id: ChatId) -> bool {
+        let mut attempts = self.jwt_refresh_attempts.lock().unwrap();
+        let now = Instant::now();
+
+        match attempts.get(&chat_id) {
+            Some(last) if now.duration_since(*last) < JWT_REFRESH_COOLDOWN => false,
+            _ => {
+                attempts.insert(chat_id, now);
+                true
+            }
+        }
+    }
 }
@@ -0,0 +1,232 @@
+//! Temporarily switch sentinel off (e.g. during an AMA) and switch it back on automatically.
+
+use anyhow::Result as AnyResult;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use teloxide::prelude::*;
+
+use crate::{
+    blocklist::blocklist::parse_duration,
+    dependencies::BotDependencies,
+    utils::{self, format_timestamp, send_html_message, send_message, send_scheduled_message},
+};
+
+const TREE_NAME: &str = "sentinel_snoozes";
+/// Longest snooze an admin can set in one go
+pub const MAX_SNOOZE_SECS: i64 = 7 * 24 * 60 * 60;
+const SNOOZE_USAGE: &str = "Usage: /snoozesentinel <duration like 30m, 2h or 1d>\n/snoozesentinel off resumes sentinel now.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentinelSnooze {
+    pub chat_id: i64,
+    /// Unix seconds when sentinel is switched back on
+    pub until: i64,
+    pub snoozed_by: i64,
+}
+
+/// Persisted snooze end times, so sentinel comes back even if the bot restarts mid-snooze
+#[derive(Clone)]
+pub struct SentinelSnoozes {
+    tree: Tree,
+}
+
+impl SentinelSnoozes {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn get(&self, chat_id: ChatId) -> Option<SentinelSnooze> {
+        self.tree
+            .get(chat_id.0.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+    }
+
+    pub fn set(&self, snooze: &SentinelSnooze) -> sled::Result<()> {
+        self.tree.insert(
+            snooze.chat_id.to_be_bytes(),
+            serde_json::to_vec(snooze).unwrap(),
+        )?;
+        Ok(())
+    }
+
+    /// Returns true when a snooze was removed
+    pub fn clear(&self, chat_id: ChatId) -> sled::Result<bool> {
+        Ok(self.tree.remove(chat_id.0.to_be_bytes())?.is_some())
+    }
+
+    /// Snoozes whose end time has passed
+    pub fn due(&self, now: i64) -> Vec<SentinelSnooze> {
+        self.tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter_map(|(_, v)| serde_json::from_slice::<SentinelSnooze>(&v).ok())
+            .filter(|s| s.until <= now)
+            .collect()
+    }
+}
+
+/// "1h 30m" style remaining time, rounded up to the minute
+pub fn format_remaining(secs: i64) -> String {
+    let minutes = (secs.max(0) + 59) / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+    let parts: Vec<String> = [(days, "d"), (hours, "h"), (minutes, "m")]
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect();
+    if parts.is_empty() {
+        "less than a minute".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// /snoozesentinel — pause sentinel for a while, show the remaining snooze, or end it early
+pub async fn handle_snooze_sentinel_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(user) = msg.from.clone() else {
+        return Ok(());
+    };
+    if !utils::is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group administrators can snooze sentinel.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let current = bot_deps.sentinel_snoozes.get(msg.chat.id);
+    let args = args.trim();
+
+    if args.is_empty() {
+        let text = match current {
+            Some(snooze) => format!(
+                "😴 Sentinel is snoozed for another {} (back on {}).\n\n{}",
+                format_remaining(snooze.until - now),
+                format_timestamp(snooze.until as u64),
+                SNOOZE_USAGE
+            ),
+            None => SNOOZE_USAGE.to_string(),
+        };
+        send_message(msg, bot, text).await?;
+        return Ok(());
+    }
+
+    if args.eq_ignore_ascii_case("off") {
+        if bot_deps.sentinel_snoozes.clear(msg.chat.id)? {
+            bot_deps.sentinel.set_sentinel(msg.chat.id.to_string(), true);
+            send_message(msg, bot, "🛡️ Snooze ended early. Sentinel is back ON.".to_string())
+                .await?;
+        } else {
+            send_message(msg, bot, "ℹ️ Sentinel isn't snoozed.".to_string()).await?;
+        }
+        return Ok(());
+    }
+
+    let Some(duration) = parse_duration(args) else {
+        send_message(msg, bot, SNOOZE_USAGE.to_string()).await?;
+        return Ok(());
+    };
+    if duration > MAX_SNOOZE_SECS {
+        send_message(
+            msg,
+            bot,
+            format!(
+                "❌ Sentinel can be snoozed for at most {}.",
+                format_remaining(MAX_SNOOZE_SECS)
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Only a running sentinel can be snoozed; an existing snooze is simply replaced
+    if current.is_none() && !bot_deps.sentinel.get_sentinel(msg.chat.id.to_string()) {
+        send_message(
+            msg,
+            bot,
+            "ℹ️ Sentinel is already OFF. Turn it on in Group Settings → Moderation.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let snooze = SentinelSnooze {
+        chat_id: msg.chat.id.0,
+        until: now + duration,
+        snoozed_by: user.id.0 as i64,
+    };
+    bot_deps.sentinel_snoozes.set(&snooze)?;
+    bot_deps.sentinel.set_sentinel(msg.chat.id.to_string(), false);
+    log::info!(
+        "Sentinel snoozed in {} by {} until {}",
+        snooze.chat_id,
+        snooze.snoozed_by,
+        snooze.until
+    );
+
+    send_html_message(
+        msg,
+        bot,
+        format!(
+            "😴 <b>Sentinel snoozed</b> for {}.\n\nModeration is paused and turns back on automatically {}. Use <code>/snoozesentinel off</code> to resume sooner.",
+            format_remaining(duration),
+            format_timestamp(snooze.until as u64)
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Switch sentinel back on for every snooze that has run out; called by the snooze job
+pub async fn resume_due_snoozes(bot: &Bot, bot_deps: &BotDependencies) {
+    let now = chrono::Utc::now().timestamp();
+    for snooze in bot_deps.sentinel_snoozes.due(now) {
+        let chat_id = ChatId(snooze.chat_id);
+        match bot_deps.sentinel_snoozes.clear(chat_id) {
+            Ok(true) => {}
+            // Cleared by an admin in the meantime
+            Ok(false) => continue,
+            Err(e) => {
+                log::error!("Failed to clear sentinel snooze for {}: {}", chat_id, e);
+                continue;
+            }
+        }
+        bot_deps.sentinel.set_sentinel(chat_id.to_string(), true);
+        log::info!("Sentinel snooze ended for {}", chat_id);
+
+        if let Err(e) = send_scheduled_message(
+            bot,
+            chat_id,
+            "🛡️ <b>Sentinel is back ON</b>\n\nThe snooze has ended and moderation has resumed.",
+            None,
+        )
+        .await
+        {
+            log::warn!("Failed to announce end of sentinel snooze in {}: {}", chat_id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(30 * 60), "30m");
+        assert_eq!(format_remaining(90 * 60 + 1), "1h 31m");
+        assert_eq!(format_remaining(MAX_SNOOZE_SECS), "7d");
+        assert_eq!(format_remaining(0), "less than a minute");
+    }
+}
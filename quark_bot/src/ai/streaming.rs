@@ -0,0 +1,304 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use open_ai_rust_responses_by_sshift::Request;
+use open_ai_rust_responses_by_sshift::types::Response;
+use teloxide::{
+    prelude::*,
+    types::{MessageId, ParseMode},
+};
+
+use crate::ai::openai_client::openai_base_url;
+use crate::utils;
+
+/// Minimum gap between preview edits, to stay clear of Telegram's edit rate limits
+const EDIT_INTERVAL: Duration = Duration::from_millis(1500);
+const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
+
+/// One parsed server-sent event from the Responses API stream
+#[derive(Debug)]
+enum StreamEvent {
+    TextDelta(String),
+    Completed(Box<Response>),
+    Failed(String),
+    Other,
+}
+
+fn parse_event(data: &str) -> StreamEvent {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+        return StreamEvent::Other;
+    };
+    match value.get("type").and_then(|t| t.as_str()) {
+        Some("response.output_text.delta") => value
+            .get("delta")
+            .and_then(|d| d.as_str())
+            .map(|d| StreamEvent::TextDelta(d.to_string()))
+            .unwrap_or(StreamEvent::Other),
+        Some("response.completed") | Some("response.incomplete") => {
+            match value
+                .get("response")
+                .cloned()
+                .map(serde_json::from_value::<Response>)
+            {
+                Some(Ok(response)) => StreamEvent::Completed(Box::new(response)),
+                Some(Err(e)) => StreamEvent::Failed(format!("Unreadable streamed response: {}", e)),
+                None => StreamEvent::Failed("Streamed response missing payload".to_string()),
+            }
+        }
+        Some("response.failed") | Some("error") => {
+            let message = value
+                .pointer("/response/error/message")
+                .or_else(|| value.pointer("/error/message"))
+                .or_else(|| value.get("message"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error");
+            StreamEvent::Failed(message.to_string())
+        }
+        _ => StreamEvent::Other,
+    }
+}
+
+/// Pop complete events (terminated by a blank line) off the front of `buffer`,
+/// returning the joined `data:` payload of each.
+///
+/// Works on bytes so a multibyte character split across network chunks is only
+/// decoded once its event is complete.
+fn drain_events(buffer: &mut Vec<u8>) -> Vec<String> {
+    let mut events = Vec::new();
+    while let Some(end) = buffer.windows(2).position(|pair| pair == b"\n\n") {
+        let raw: Vec<u8> = buffer.drain(..end + 2).collect();
+        let data = String::from_utf8_lossy(&raw)
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim_start)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !data.is_empty() && data != "[DONE]" {
+            events.push(data);
+        }
+    }
+    events
+}
+
+/// Run `request` as a streamed Responses API call, feeding text deltas to `reply`.
+///
+/// Returns the same `Response` a non-streamed `create` would, taken from the
+/// `response.completed` event, so tool calls, usage and images are handled as before.
+pub async fn stream_response(
+    http: &reqwest::Client,
+    api_key: &str,
+    request: Request,
+    reply: &mut StreamingReply,
+) -> Result<Response> {
+    let mut body = serde_json::to_value(&request)?;
+    body["stream"] = serde_json::Value::Bool(true);
+
+    let mut response = http
+        .post(format!("{}/responses", openai_base_url()))
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(anyhow::anyhow!(
+            "OpenAI streaming request failed ({}): {}",
+            status,
+            text
+        ));
+    }
+
+    // Each call is a new turn; text from a turn that ended in tool calls is not the answer
+    reply.reset_text();
+
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        // SSE allows CRLF line endings; JSON payloads never carry a raw CR
+        buffer.extend(chunk.iter().filter(|&&byte| byte != b'\r'));
+        for data in drain_events(&mut buffer) {
+            match parse_event(&data) {
+                StreamEvent::TextDelta(delta) => reply.push(&delta).await,
+                StreamEvent::Completed(response) => return Ok(*response),
+                StreamEvent::Failed(message) => return Err(anyhow::anyhow!(message)),
+                StreamEvent::Other => {}
+            }
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "OpenAI stream ended before the response completed"
+    ))
+}
+
+/// A single Telegram message that is edited as the answer streams in.
+///
+/// The preview is plain text (partial markdown would not parse as HTML); `finish`
+/// replaces it with the formatted reply.
+pub struct StreamingReply {
+    bot: Bot,
+    msg: Message,
+    message_id: Option<MessageId>,
+    text: String,
+    shown: String,
+    last_edit: Instant,
+}
+
+impl StreamingReply {
+    pub fn new(bot: Bot, msg: Message) -> Self {
+        Self {
+            bot,
+            msg,
+            message_id: None,
+            text: String::new(),
+            shown: String::new(),
+            last_edit: Instant::now(),
+        }
+    }
+
+    /// Whether a preview message has been posted
+    pub fn is_started(&self) -> bool {
+        self.message_id.is_some()
+    }
+
+    fn reset_text(&mut self) {
+        self.text.clear();
+    }
+
+    /// First `limit` bytes of the text, cut on a char boundary
+    fn preview(text: &str, limit: usize) -> String {
+        if text.len() <= limit {
+            return text.to_string();
+        }
+        let mut end = limit.saturating_sub(2);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}…", &text[..end])
+    }
+
+    async fn push(&mut self, delta: &str) {
+        self.text.push_str(delta);
+        let due = self.shown.is_empty() || self.last_edit.elapsed() >= EDIT_INTERVAL;
+        if !due || self.text.trim().is_empty() {
+            return;
+        }
+
+        // The preview shows the first message's worth; the rest arrives with `finish`
+        let preview = Self::preview(&self.text, TELEGRAM_MESSAGE_LIMIT);
+        if preview == self.shown {
+            return;
+        }
+        self.last_edit = Instant::now();
+
+        match self.message_id {
+            None => {
+                let mut request = self.bot.send_message(self.msg.chat.id, preview.clone());
+                if let Some(thread_id) = utils::topic_thread_id(&self.msg) {
+                    request = request.message_thread_id(thread_id);
+                }
+                match request.await {
+                    Ok(sent) => self.message_id = Some(sent.id),
+                    Err(e) => log::warn!("Failed to post streaming preview: {}", e),
+                }
+            }
+            Some(message_id) => {
+                if let Err(e) = self
+                    .bot
+                    .edit_message_text(self.msg.chat.id, message_id, preview.clone())
+                    .await
+                {
+                    log::debug!("Skipped streaming preview edit: {}", e);
+                }
+            }
+        }
+        self.shown = preview;
+    }
+
+    /// Replace the preview with the final, formatted reply. `chunks` are Telegram HTML
+    /// already split to the message limit; the first goes into the preview message and
+    /// the rest are sent after it. Returns false if the preview could not be edited
+    /// (it is removed then), so the caller can send the reply the usual way.
+    pub async fn finish(mut self, chunks: Vec<String>, link_previews: bool) -> Result<bool> {
+        let Some(message_id) = self.message_id.take() else {
+            return Ok(false);
+        };
+        let mut chunks = chunks.into_iter();
+        let first = chunks.next().unwrap_or_default();
+
+        let mut request = self
+            .bot
+            .edit_message_text(self.msg.chat.id, message_id, first)
+            .parse_mode(ParseMode::Html);
+        if !link_previews {
+            request = request.link_preview_options(utils::disabled_link_preview());
+        }
+        if let Err(e) = request.await {
+            log::warn!("Failed to finalize streamed reply, resending: {}", e);
+            let _ = self.bot.delete_message(self.msg.chat.id, message_id).await;
+            return Ok(false);
+        }
+
+        for chunk in chunks {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            utils::send_html_message_with_previews(
+                self.msg.clone(),
+                self.bot.clone(),
+                chunk,
+                link_previews,
+            )
+            .await?;
+        }
+        Ok(true)
+    }
+
+    /// Remove the preview, for replies delivered another way (images, wallet buttons, errors)
+    pub async fn discard(mut self) {
+        if let Some(message_id) = self.message_id.take() {
+            if let Err(e) = self.bot.delete_message(self.msg.chat.id, message_id).await {
+                log::warn!("Failed to remove streaming preview: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_events_keeps_partial_tail() {
+        let mut buffer = b"event: response.output_text.delta\ndata: {\"type\":\"response.output_text.delta\",\"delta\":\"Hel\"}\n\ndata: {\"type\":\"resp".to_vec();
+        let events = drain_events(&mut buffer);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(parse_event(&events[0]), StreamEvent::TextDelta(d) if d == "Hel"));
+        assert_eq!(buffer, b"data: {\"type\":\"resp");
+    }
+
+    #[test]
+    fn test_drain_events_multibyte_split_across_chunks() {
+        let event =
+            "data: {\"type\":\"response.output_text.delta\",\"delta\":\"héllo\"}\n\n".as_bytes();
+        let split = event.iter().position(|&byte| byte == 0xC3).unwrap() + 1;
+
+        let mut buffer = event[..split].to_vec();
+        assert!(drain_events(&mut buffer).is_empty());
+        buffer.extend_from_slice(&event[split..]);
+        let events = drain_events(&mut buffer);
+        assert!(matches!(parse_event(&events[0]), StreamEvent::TextDelta(d) if d == "héllo"));
+    }
+
+    #[test]
+    fn test_parse_failed_event() {
+        let event =
+            parse_event(r#"{"type":"response.failed","response":{"error":{"message":"boom"}}}"#);
+        assert!(matches!(event, StreamEvent::Failed(m) if m == "boom"));
+    }
+
+    #[test]
+    fn test_preview_cuts_on_char_boundary() {
+        assert_eq!(StreamingReply::preview("héllo", 10), "héllo");
+        assert_eq!(StreamingReply::preview("héllo", 5), "hé…");
+    }
+}
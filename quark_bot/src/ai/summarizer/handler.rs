@@ -211,6 +211,23 @@ impl SummarizerService {
         Ok(())
     }
 
+    /// For /forget: a summary written on the latest turn contains it, so it is dropped.
+    /// Returns whether an older summary remains to seed the next thread.
+    pub fn forget_latest_turn(
+        &self,
+        user_id: &str,
+        group_id: Option<String>,
+    ) -> sled::Result<bool> {
+        match self.get_state(user_id, group_id.clone()) {
+            Some(state) if state.pending_thread_clear => {
+                self.clear_summary(user_id, group_id)?;
+                Ok(false)
+            }
+            Some(state) => Ok(state.summary.is_some()),
+            None => Ok(false),
+        }
+    }
+
     pub fn check_and_clear_pending_thread(
         &self,
         user_id: &str,
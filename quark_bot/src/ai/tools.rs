@@ -1,15 +1,20 @@
 use super::actions::{
     execute_fear_and_greed_index, execute_get_recent_messages, execute_get_time,
-    execute_get_wallet_address, execute_new_pools, execute_pay_users, execute_search_pools,
-    execute_trending_pools,
+    execute_get_wallet_address, execute_new_pools, execute_ohlcv, execute_pay_users,
+    execute_search_pools, execute_trending_pools, wants_table_image,
 };
 use crate::{
     ai::actions::{execute_fund_account, execute_get_balance, execute_withdraw_funds},
     dao::handler::execute_create_proposal,
     dependencies::BotDependencies,
 };
+use futures::stream::{self, StreamExt};
 use open_ai_rust_responses_by_sshift::types::Tool;
 use serde_json::json;
+use std::collections::HashSet;
+use std::env;
+use std::future::Future;
+use std::sync::OnceLock;
 use teloxide::{Bot, types::Message};
 
 /// Get account balance tool - returns a Tool for checking user balance
@@ -181,6 +186,47 @@ pub fn get_new_pools_tool() -> Tool {
     )
 }
 
+/// OHLCV tool - returns a Tool for fetching a pool's price candles from GeckoTerminal
+pub fn get_ohlcv_tool() -> Tool {
+    Tool::function(
+        "get_ohlcv",
+        "Get historical price candles (open/high/low/close/volume) for one DEX pool from GeckoTerminal, summarized over the period. Use it for questions like how a token moved over the last day. It needs a pool address: use search_pools first to find the token's most liquid pool. Report the change and range briefly; do not dump raw numbers.",
+        json!({
+            "type": "object",
+            "properties": {
+                "network": {
+                    "type": "string",
+                    "description": "Blockchain network identifier (e.g., 'aptos' for Aptos, 'eth' for Ethereum).",
+                    "enum": ["aptos", "sui", "eth", "bsc", "polygon_pos", "avax", "ftm", "cro", "arbitrum", "base", "solana"]
+                },
+                "pool_address": {
+                    "type": "string",
+                    "description": "Pool address on that network, as returned by search_pools."
+                },
+                "timeframe": {
+                    "type": "string",
+                    "description": "Candle timeframe. 'hour' for the last day, 'day' for weeks or months, 'minute' for the last hour.",
+                    "enum": ["day", "hour", "minute"],
+                    "default": "hour"
+                },
+                "aggregate": {
+                    "type": "integer",
+                    "description": "(Optional) Candle size in timeframe units: day 1; hour 1, 4 or 12; minute 1, 5 or 15.",
+                    "default": 1
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "(Optional) Number of candles (default 30 daily, 24 hourly, 60 by minute).",
+                    "minimum": 1,
+                    "maximum": 1000
+                }
+            },
+            "required": ["network", "pool_address"],
+            "additionalProperties": false
+        }),
+    )
+}
+
 /// Get current time tool - returns a Tool for fetching the current time for a specific timezone
 pub fn get_time_tool() -> Tool {
     Tool::function(
@@ -261,6 +307,10 @@ pub fn get_pay_users_tool() -> Tool {
                     "items": {
                         "type": "string"
                     }
+                },
+                "memo": {
+                    "type": "string",
+                    "description": "Optional short note the user wants attached to the payment (max 140 characters), e.g. 'March rent'. It is shown on the confirmation automatically; omit when none was given"
                 }
             },
             "required": ["amount", "symbol", "users"],
@@ -302,6 +352,18 @@ pub fn create_proposal() -> Tool {
                 "symbol": {
                     "type": "string",
                     "description": "The symbol of the currency of the proposal. Optional - if not provided, will use the saved DAO token preference for this group."
+                },
+                "discussion_message": {
+                    "type": "string",
+                    "description": "Optional link (https://t.me/...) or message id of an existing group message where the proposal is discussed. If the user replies to a message while creating the proposal and asks to link it, leave this empty and set use_replied_message to true."
+                },
+                "use_replied_message": {
+                    "type": "boolean",
+                    "description": "Optional. Link the message the user replied to as the discussion thread."
+                },
+                "create_discussion": {
+                    "type": "boolean",
+                    "description": "Optional. Post a discussion prompt in the group and link it to the proposal. Use when the user asks for a discussion thread and did not reference an existing message."
                 }
             },
             "required": ["name", "description", "start_date", "end_date", "options"],
@@ -339,9 +401,14 @@ pub async fn execute_custom_tool(
         "get_wallet_address" => execute_get_wallet_address(msg, bot_deps.clone(), group_id).await,
         "withdraw_funds" => execute_withdraw_funds(arguments, msg, bot_deps.clone()).await,
         "fund_account" => execute_fund_account(arguments, msg, bot_deps.clone()).await,
-        "get_trending_pools" => execute_trending_pools(arguments).await,
+        "get_trending_pools" => {
+            let table_target =
+                wants_table_image(&msg, &bot_deps).then(|| (bot.clone(), msg.clone()));
+            execute_trending_pools(arguments, table_target).await
+        }
         "search_pools" => execute_search_pools(arguments).await,
         "get_new_pools" => execute_new_pools(arguments).await,
+        "get_ohlcv" => execute_ohlcv(arguments).await,
         "get_current_time" => execute_get_time(arguments).await,
         "get_fear_and_greed_index" => execute_fear_and_greed_index(arguments).await,
         "get_pay_users" => execute_pay_users(arguments, bot, msg, bot_deps.clone(), group_id).await,
@@ -381,6 +448,7 @@ pub fn get_all_custom_tools() -> Vec<Tool> {
         get_trending_pools_tool(),
         get_search_pools_tool(),
         get_new_pools_tool(),
+        get_ohlcv_tool(),
         get_time_tool(),
         get_fear_and_greed_index_tool(),
         get_pay_users_tool(),
@@ -388,3 +456,163 @@ pub fn get_all_custom_tools() -> Vec<Tool> {
         get_recent_messages_tool(),
     ]
 }
+
+/// Custom tool names switched off via the comma-separated `DISABLED_TOOLS` env var
+pub fn disabled_tools() -> &'static HashSet<String> {
+    static DISABLED: OnceLock<HashSet<String>> = OnceLock::new();
+    DISABLED.get_or_init(|| {
+        env::var("DISABLED_TOOLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    })
+}
+
+/// Tools that start payments, withdrawals or proposals; these share per-user pending state
+const STATEFUL_TOOLS: [&str; 4] = [
+    "withdraw_funds",
+    "fund_account",
+    "get_pay_users",
+    "create_proposal",
+];
+
+const DEFAULT_TOOL_CONCURRENCY: usize = 4;
+
+/// How many custom tool calls from one response may run at once, from `AI_TOOL_CONCURRENCY`
+pub fn tool_concurrency() -> usize {
+    static CONCURRENCY: OnceLock<usize> = OnceLock::new();
+    *CONCURRENCY.get_or_init(|| {
+        env::var("AI_TOOL_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_TOOL_CONCURRENCY)
+    })
+}
+
+/// Concurrency for a batch of calls: read-only batches use the configured limit, while a
+/// batch with a stateful tool runs one call at a time
+pub fn tool_concurrency_for<'a>(names: impl IntoIterator<Item = &'a str>) -> usize {
+    if names.into_iter().any(|name| STATEFUL_TOOLS.contains(&name)) {
+        1
+    } else {
+        tool_concurrency()
+    }
+}
+
+/// Run tool calls with at most `limit` in flight, returning `(call_id, output)` in call order
+pub async fn run_tool_calls<Fut>(calls: Vec<(String, Fut)>, limit: usize) -> Vec<(String, String)>
+where
+    Fut: Future<Output = String>,
+{
+    stream::iter(
+        calls
+            .into_iter()
+            .map(|(call_id, call)| async move { (call_id, call.await) }),
+    )
+    .buffered(limit.max(1))
+    .collect()
+    .await
+}
+
+pub fn is_tool_enabled(name: &str) -> bool {
+    !disabled_tools().contains(name)
+}
+
+fn tool_name(tool: &Tool) -> Option<String> {
+    serde_json::to_value(tool)
+        .ok()?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Drop function tools the operator has disabled; built-in tools have no name and are kept
+pub fn filter_enabled_tools(tools: Vec<Tool>) -> Vec<Tool> {
+    tools
+        .into_iter()
+        .filter(|tool| tool_name(tool).map_or(true, |name| is_tool_enabled(&name)))
+        .collect()
+}
+
+/// Custom tools offered to the model after applying `DISABLED_TOOLS`
+pub fn get_enabled_custom_tools() -> Vec<Tool> {
+    filter_enabled_tools(get_all_custom_tools())
+}
+
+/// Names of the custom tools the model is offered
+pub fn active_tool_names() -> Vec<String> {
+    get_enabled_custom_tools().iter().filter_map(tool_name).collect()
+}
+
+/// Log the custom tool surface once at startup
+pub fn log_active_tools() {
+    log::info!("Active custom tools: {}", active_tool_names().join(", "));
+
+    let known: HashSet<String> = get_all_custom_tools().iter().filter_map(tool_name).collect();
+    let mut disabled: Vec<&String> = disabled_tools().iter().collect();
+    disabled.sort();
+    for name in disabled {
+        if known.contains(name) {
+            log::info!("Custom tool disabled by config: {}", name);
+        } else {
+            log::warn!("DISABLED_TOOLS lists unknown tool '{}'", name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_tool_calls_concurrent_and_ordered() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let call = |output: &'static str, delay_ms: u64| {
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                output.to_string()
+            }
+        };
+
+        // The slower call is first, so a naive completion-order collect would swap them
+        let outputs = run_tool_calls(
+            vec![
+                ("call_a".to_string(), call("trending", 50)),
+                ("call_b".to_string(), call("fear_greed", 10)),
+            ],
+            2,
+        )
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            outputs,
+            vec![
+                ("call_a".to_string(), "trending".to_string()),
+                ("call_b".to_string(), "fear_greed".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stateful_batches_run_one_at_a_time() {
+        assert_eq!(tool_concurrency_for(["get_trending_pools", "get_pay_users"]), 1);
+        assert_eq!(
+            tool_concurrency_for(["get_trending_pools", "get_fear_and_greed_index"]),
+            tool_concurrency()
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use std::collections::HashMap;
+
+const TREE_NAME: &str = "upload_sessions";
+/// Temp files don't outlive a day, so neither should the session pointing at them
+const SESSION_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// Progress of a multi-file vector store upload, kept so a failed batch can resume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadSession {
+    pub file_paths: Vec<String>,
+    /// Local path -> OpenAI file id for files that finished uploading
+    pub uploaded: HashMap<String, String>,
+    /// File ids already attached to the user's existing vector store
+    #[serde(default)]
+    pub attached: Vec<String>,
+    pub started_at: i64,
+}
+
+impl UploadSession {
+    pub fn new(file_paths: Vec<String>) -> Self {
+        Self {
+            file_paths,
+            uploaded: HashMap::new(),
+            attached: Vec::new(),
+            started_at: Utc::now().timestamp(),
+        }
+    }
+
+    pub fn is_uploaded(&self, path: &str) -> bool {
+        self.uploaded.contains_key(path)
+    }
+
+    pub fn remaining(&self) -> Vec<&String> {
+        self.file_paths
+            .iter()
+            .filter(|p| !self.is_uploaded(p))
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+pub struct UploadSessions {
+    tree: Tree,
+}
+
+impl UploadSessions {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn get(&self, user_id: i64) -> Option<UploadSession> {
+        let session: UploadSession = self
+            .tree
+            .get(user_id.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())?;
+
+        if Utc::now().timestamp() - session.started_at > SESSION_TTL_SECS {
+            let _ = self.clear(user_id);
+            return None;
+        }
+        Some(session)
+    }
+
+    /// Resume the stored session if it covers the same files, otherwise start fresh
+    pub fn resume_or_start(&self, user_id: i64, file_paths: &[String]) -> UploadSession {
+        match self.get(user_id) {
+            Some(session) if session.file_paths == file_paths => session,
+            _ => UploadSession::new(file_paths.to_vec()),
+        }
+    }
+
+    pub fn save(&self, user_id: i64, session: &UploadSession) -> sled::Result<()> {
+        let bytes = serde_json::to_vec(session).unwrap();
+        self.tree.insert(user_id.to_be_bytes(), bytes)?;
+        Ok(())
+    }
+
+    /// Drop sessions older than the TTL; returns how many were removed
+    pub fn purge_expired(&self, now: i64) -> usize {
+        let stale: Vec<_> = self
+            .tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(_, v)| {
+                serde_json::from_slice::<UploadSession>(v)
+                    .map(|s| now - s.started_at > SESSION_TTL_SECS)
+                    .unwrap_or(true)
+            })
+            .map(|(k, _)| k)
+            .collect();
+        for key in &stale {
+            let _ = self.tree.remove(key);
+        }
+        stale.len()
+    }
+
+    pub fn clear(&self, user_id: i64) -> sled::Result<()> {
+        self.tree.remove(user_id.to_be_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_skips_uploaded_files() {
+        let mut session = UploadSession::new(vec![
+            "/tmp/1_a.pdf".to_string(),
+            "/tmp/1_b.pdf".to_string(),
+            "/tmp/1_c.pdf".to_string(),
+        ]);
+        session
+            .uploaded
+            .insert("/tmp/1_a.pdf".to_string(), "file-a".to_string());
+
+        assert_eq!(session.remaining(), vec!["/tmp/1_b.pdf", "/tmp/1_c.pdf"]);
+    }
+
+    #[test]
+    fn test_purge_expired_keeps_fresh_sessions() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let sessions = UploadSessions::new(&db).unwrap();
+        let fresh = UploadSession::new(vec!["/tmp/1_a.pdf".to_string()]);
+        let mut stale = fresh.clone();
+        stale.started_at -= SESSION_TTL_SECS + 1;
+        sessions.save(1, &fresh).unwrap();
+        sessions.save(2, &stale).unwrap();
+
+        assert_eq!(sessions.purge_expired(fresh.started_at), 1);
+        assert!(sessions.get(1).is_some());
+        assert!(sessions.get(2).is_none());
+    }
+}
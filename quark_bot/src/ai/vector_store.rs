@@ -1,34 +1,62 @@
+use crate::ai::upload_session::UploadSessions;
 use crate::dependencies::BotDependencies;
-use crate::user_conversation::{dto::FileInfo, handler::UserConversations};
+use crate::user_conversation::{dto::LibraryFile, handler::UserConversations};
+use futures::future::join_all;
 use open_ai_rust_responses_by_sshift::files::FilePurpose;
 use open_ai_rust_responses_by_sshift::vector_stores::{
     AddFileToVectorStoreRequest, CreateVectorStoreRequest,
 };
 
+/// OpenAI-side name of the store backing the user's active knowledge base
+fn vector_store_name(user_id: i64, user_convos: &UserConversations) -> String {
+    format!(
+        "user_{}_{}_vector_store",
+        user_id,
+        user_convos.active_knowledge_base_name(user_id)
+    )
+}
+
 pub async fn upload_files_to_vector_store(
     user_id: i64,
     bot_deps: BotDependencies,
     file_paths: Vec<String>,
 ) -> Result<String, anyhow::Error> {
     let user_convos = UserConversations::new(&bot_deps.db)?;
+    let sessions = UploadSessions::new(&bot_deps.db)?;
+    let mut session = sessions.resume_or_start(user_id, &file_paths);
     let mut file_ids = Vec::new();
 
-    // Check if user has invalid vector store ID and clear stale data upfront
-    if let Some(existing_vs_id) = user_convos.get_vector_store_id(user_id) {
-        if existing_vs_id.is_empty() || !existing_vs_id.starts_with("vs_") {
-            // Clear stale file tracking before adding new files
-            user_convos.clear_files(user_id)?;
+    // Check if user has invalid vector store ID and clear stale data upfront.
+    // Skipped when resuming, since files from the failed attempt are already tracked.
+    if session.uploaded.is_empty() {
+        if let Some(existing_vs_id) = user_convos.get_vector_store_id(user_id) {
+            if existing_vs_id.is_empty() || !existing_vs_id.starts_with("vs_") {
+                // Clear stale file tracking before adding new files
+                user_convos.clear_files(user_id)?;
+            }
         }
     }
 
     let client = bot_deps.ai.get_client();
 
-    // Upload each file to OpenAI
+    // Upload each file to OpenAI, skipping the ones a previous attempt already finished
     for path in &file_paths {
-        let file = client
+        if let Some(file_id) = session.uploaded.get(path) {
+            file_ids.push(file_id.clone());
+            continue;
+        }
+
+        let file = match client
             .files
             .upload_file(path, FilePurpose::Assistants, None)
-            .await?;
+            .await
+        {
+            Ok(file) => file,
+            Err(e) => {
+                sessions.save(user_id, &session)?;
+                return Err(e.into());
+            }
+        };
         file_ids.push(file.id.clone());
         // Store file ID and name in user's local database for reliable tracking
         let filename = std::path::Path::new(path)
@@ -37,15 +65,22 @@ pub async fn upload_files_to_vector_store(
             .unwrap_or("unknown_file")
             .to_string();
         user_convos.add_file(user_id, &file.id, &filename)?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            user_convos.set_file_size(&file.id, metadata.len())?;
+        }
+        session.uploaded.insert(path.clone(), file.id.clone());
     }
 
+    // Persist before touching the vector store so a failure there can resume too
+    sessions.save(user_id, &session)?;
+
     // Check if user already has a vector store
     let vector_store_id = if let Some(existing_vs_id) = user_convos.get_vector_store_id(user_id) {
         // Check if the vector store ID is valid (not empty and starts with 'vs_')
         if existing_vs_id.is_empty() || !existing_vs_id.starts_with("vs_") {
             // Invalid vector store ID, create a new one
             let vs_request = CreateVectorStoreRequest {
-                name: format!("user_{}_vector_store", user_id),
+                name: vector_store_name(user_id, &user_convos),
                 file_ids: file_ids.clone(),
             };
             let vector_store = client.vector_stores.create(vs_request).await?;
@@ -58,6 +93,9 @@ pub async fn upload_files_to_vector_store(
         } else {
             // User has existing valid vector store, add files to it
             for file_id in &file_ids {
+                if session.attached.contains(file_id) {
+                    continue;
+                }
                 let add_file_request = AddFileToVectorStoreRequest {
                     file_id: file_id.clone(),
                     attributes: None,
@@ -73,6 +111,7 @@ pub async fn upload_files_to_vector_store(
                             file_id,
                             existing_vs_id
                         );
+                        session.attached.push(file_id.clone());
                     }
                     Err(e) => {
                         let error_msg = e.to_string();
@@ -90,7 +129,7 @@ pub async fn upload_files_to_vector_store(
 
                             // Create a new vector store with all files
                             let vs_request = CreateVectorStoreRequest {
-                                name: format!("user_{}_vector_store", user_id),
+                                name: vector_store_name(user_id, &user_convos),
                                 file_ids: file_ids.clone(),
                             };
                             let vector_store = client.vector_stores.create(vs_request).await?;
@@ -98,10 +137,12 @@ pub async fn upload_files_to_vector_store(
 
                             // Store the new vector_store_id in the user's db record
                             user_convos.set_vector_store_id(user_id, &new_vs_id)?;
+                            sessions.clear(user_id)?;
 
                             return Ok(new_vs_id);
                         } else {
-                            // Re-throw other errors
+                            // Keep progress so a retry only attaches what's left
+                            sessions.save(user_id, &session)?;
                             return Err(e.into());
                         }
                     }
@@ -112,7 +153,7 @@ pub async fn upload_files_to_vector_store(
     } else {
         // User doesn't have a vector store, create a new one
         let vs_request = CreateVectorStoreRequest {
-            name: format!("user_{}_vector_store", user_id),
+            name: vector_store_name(user_id, &user_convos),
             file_ids: file_ids.clone(),
         };
         let vector_store = client.vector_stores.create(vs_request).await?;
@@ -124,18 +165,47 @@ pub async fn upload_files_to_vector_store(
         new_vs_id
     };
 
+    sessions.clear(user_id)?;
+
     Ok(vector_store_id)
 }
 
-/// List files with names from user's local database (reliable, immediate)
+/// List files with names and upload sizes from user's local database (reliable, immediate)
 /// This bypasses the unreliable OpenAI vector store file listing API
-pub fn list_user_files_with_names(
+pub async fn list_user_files_with_names(
     user_id: i64,
     bot_deps: BotDependencies,
-) -> Result<Vec<FileInfo>, anyhow::Error> {
+) -> Result<Vec<LibraryFile>, anyhow::Error> {
     let user_convos = UserConversations::new(&bot_deps.db)?;
-    let files = user_convos.get_files(user_id);
-    Ok(files)
+    let client = bot_deps.ai.get_client();
+    let lookups = user_convos.get_files(user_id).into_iter().map(|file| {
+        let user_convos = &user_convos;
+        async move {
+            // Files uploaded before sizes were recorded: ask OpenAI once and keep the answer,
+            // including a failed lookup, so listing the library stays fast
+            let bytes = match user_convos.get_file_size(&file.id) {
+                Some(bytes) => bytes,
+                None => match client.files.retrieve(&file.id).await {
+                    Ok(remote) => {
+                        let bytes = remote.bytes as u64;
+                        user_convos.set_file_size(&file.id, bytes)?;
+                        Some(bytes)
+                    }
+                    Err(e) => {
+                        log::warn!("Could not get size of file {}: {}", file.id, e);
+                        user_convos.set_file_size_unknown(&file.id)?;
+                        None
+                    }
+                },
+            };
+            Ok::<_, anyhow::Error>(LibraryFile {
+                id: file.id,
+                name: file.name,
+                bytes,
+            })
+        }
+    });
+    join_all(lookups).await.into_iter().collect()
 }
 
 /// Delete a specific file from a vector store
@@ -31,9 +31,23 @@ impl AnnouncerAuth {
         Ok(Self { authorized_usernames })
     }
 
+    /// Load the operator list from `config/authorized_announcers.ron` in the working directory
+    pub fn load_default() -> Result<Self> {
+        let config_path = std::env::current_dir()
+            .unwrap_or_else(|_| std::path::PathBuf::from("."))
+            .join("config/authorized_announcers.ron");
+
+        Self::new(config_path)
+    }
+
     pub fn is_authorized(&self, username: &str) -> bool {
         self.authorized_usernames.contains(username)
     }
-}
-
 
+    /// Operators from the config file, sorted; they act as super-admins for announcement rights
+    pub fn usernames(&self) -> Vec<String> {
+        let mut usernames: Vec<String> = self.authorized_usernames.iter().cloned().collect();
+        usernames.sort();
+        usernames
+    }
+}
@@ -1,8 +1,17 @@
 use serde::{Deserialize, Serialize};
+use teloxide::types::UserId;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthorizedAnnouncersConfig {
     pub usernames: Vec<String>,
 }
 
-
+/// Someone allowed to send global announcements by a super-admin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnouncerGrant {
+    pub user_id: UserId,
+    /// Username at grant time, for display only
+    pub username: String,
+    pub granted_by: String,
+    pub granted_at: i64,
+}
@@ -0,0 +1,63 @@
+//! Announcement rights granted from chat, on top of the operators in the config file.
+//! Keyed by Telegram user id, so a grant survives username changes and can't be picked up
+//! by whoever takes a released username.
+
+use sled::{Db, Tree};
+use teloxide::types::UserId;
+
+use super::dto::AnnouncerGrant;
+
+const TREE_NAME: &str = "announcer_grants";
+
+/// Telegram usernames are case-insensitive; compare them without "@" in lowercase
+pub fn normalize_username(username: &str) -> String {
+    username.trim().trim_start_matches('@').to_lowercase()
+}
+
+#[derive(Clone)]
+pub struct AnnouncerGrants {
+    tree: Tree,
+}
+
+impl AnnouncerGrants {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn grant(&self, grant: &AnnouncerGrant) -> sled::Result<()> {
+        self.tree.insert(
+            grant.user_id.0.to_be_bytes(),
+            serde_json::to_vec(grant).unwrap(),
+        )?;
+        Ok(())
+    }
+
+    /// Returns true when the user had been granted rights
+    pub fn revoke(&self, user_id: UserId) -> sled::Result<bool> {
+        Ok(self.tree.remove(user_id.0.to_be_bytes())?.is_some())
+    }
+
+    pub fn is_granted(&self, user_id: UserId) -> bool {
+        matches!(self.tree.contains_key(user_id.0.to_be_bytes()), Ok(true))
+    }
+
+    /// Grant recorded under `username`, for revoking someone who has since logged out
+    pub fn find_by_username(&self, username: &str) -> Option<AnnouncerGrant> {
+        let username = normalize_username(username);
+        self.list()
+            .into_iter()
+            .find(|grant| normalize_username(&grant.username) == username)
+    }
+
+    pub fn list(&self) -> Vec<AnnouncerGrant> {
+        let mut grants: Vec<AnnouncerGrant> = self
+            .tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter_map(|(_, v)| serde_json::from_slice(&v).ok())
+            .collect();
+        grants.sort_by(|a, b| a.username.cmp(&b.username));
+        grants
+    }
+}
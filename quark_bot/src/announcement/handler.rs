@@ -6,11 +6,16 @@ use teloxide::{
     Bot,
     prelude::*,
     types::{Message, ParseMode, UserId},
+    utils::html,
 };
 
 use crate::dependencies::BotDependencies;
+use crate::rate_limiter::SendLimiter;
+use crate::utils::format_timestamp;
 
 use super::announcement::AnnouncerAuth;
+use super::dto::AnnouncerGrant;
+use super::grants::normalize_username;
 
 pub async fn handle_announcement(
     bot: Bot,
@@ -40,26 +45,26 @@ pub async fn handle_announcement(
         }
     };
 
-    // Create announcer auth instance
-    let config_path = std::env::current_dir()
-        .unwrap_or_else(|_| std::path::PathBuf::from("."))
-        .join("config/authorized_announcers.ron");
-
-    let announcer_auth = match AnnouncerAuth::new(&config_path) {
-        Ok(auth) => auth,
+    // Super-admins come from the config file; others may have been granted rights in chat
+    let is_granted = bot_deps.announcer_grants.is_granted(sender.id);
+    let is_super_admin = match AnnouncerAuth::load_default() {
+        Ok(auth) => auth.is_authorized(username),
         Err(e) => {
             log::error!("Failed to load announcer auth: {}", e);
-            bot.send_message(
-                msg.chat.id,
-                "❌ Configuration error. Please contact an administrator.",
-            )
-            .await?;
-            return Ok(());
+            if !is_granted {
+                bot.send_message(
+                    msg.chat.id,
+                    "❌ Configuration error. Please contact an administrator.",
+                )
+                .await?;
+                return Ok(());
+            }
+            false
         }
     };
 
     // Check authorization
-    if !announcer_auth.is_authorized(username) {
+    if !is_super_admin && !is_granted {
         bot.send_message(
             msg.chat.id,
             "❌ You are not authorized to send global announcements.",
@@ -99,7 +104,14 @@ pub async fn handle_announcement(
         }
     };
 
-    log::info!("Sending announcement to {} recipients", recipients.len());
+    log::info!(
+        "Global announcement from @{} (user {}, {}) to {} recipients: {:?}",
+        username,
+        sender.id,
+        if is_super_admin { "super-admin" } else { "granted" },
+        recipients.len(),
+        text.chars().take(200).collect::<String>()
+    );
 
     // Confirm sending
     bot.send_message(
@@ -120,9 +132,6 @@ pub async fn handle_announcement(
             let announcement_text = announcement_text.clone();
 
             async move {
-                // Small delay per task to respect API limits
-                tokio::time::sleep(tokio::time::Duration::from_millis(75)).await;
-
                 match send_announcement_to_user(bot, user_id, &announcement_text).await {
                     Ok(_) => {
                         log::debug!("Successfully sent announcement to user {}", user_id);
@@ -145,6 +154,142 @@ pub async fn handle_announcement(
     Ok(())
 }
 
+/// /announcers [grant|revoke @username] — super-admins manage who may send global announcements
+pub async fn handle_announcers_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(username) = msg.from.as_ref().and_then(|u| u.username.clone()) else {
+        bot.send_message(msg.chat.id, "❌ Username required to manage announcers.")
+            .await?;
+        return Ok(());
+    };
+
+    let auth = match AnnouncerAuth::load_default() {
+        Ok(auth) => auth,
+        Err(e) => {
+            log::error!("Failed to load announcer auth: {}", e);
+            bot.send_message(
+                msg.chat.id,
+                "❌ Configuration error. Please contact an administrator.",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    if !auth.is_authorized(&username) {
+        bot.send_message(
+            msg.chat.id,
+            "❌ Only super-admins can manage announcement rights.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut words = args.split_whitespace();
+    let action = words.next().map(|w| w.to_lowercase());
+    let target = words.next().map(normalize_username).filter(|t| !t.is_empty());
+    let is_super_admin =
+        |target: &str| auth.usernames().iter().any(|u| normalize_username(u) == target);
+
+    let text = match (action.as_deref(), target) {
+        (None, _) | (Some("list"), _) => {
+            let super_admins = auth
+                .usernames()
+                .iter()
+                .map(|u| format!("• @{}", html::escape(u)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let grants = bot_deps.announcer_grants.list();
+            let granted = if grants.is_empty() {
+                "• none".to_string()
+            } else {
+                grants
+                    .iter()
+                    .map(|g| {
+                        format!(
+                            "• @{} — by @{} on {}",
+                            html::escape(&g.username),
+                            html::escape(&g.granted_by),
+                            format_timestamp(g.granted_at as u64)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            format!(
+                "📢 <b>Announcement rights</b>\n\n<b>Super-admins</b> (config file):\n{}\n\n<b>Granted</b>:\n{}\n\nUse <code>/announcers grant @user</code> or <code>/announcers revoke @user</code>.",
+                super_admins, granted
+            )
+        }
+        (Some("grant"), Some(target)) => {
+            if is_super_admin(&target) {
+                format!("ℹ️ @{} is already a super-admin.", html::escape(&target))
+            } else if let Some(user_id) = bot_deps.auth.find_user_id(&target) {
+                bot_deps.announcer_grants.grant(&AnnouncerGrant {
+                    user_id,
+                    username: target.clone(),
+                    granted_by: username.clone(),
+                    granted_at: chrono::Utc::now().timestamp(),
+                })?;
+                log::info!("Announcement rights granted to @{} by @{}", target, username);
+                format!(
+                    "✅ @{} can now send global announcements.",
+                    html::escape(&target)
+                )
+            } else {
+                format!(
+                    "❌ @{} isn't logged in to Quark yet. They need to /loginuser before rights can be granted.",
+                    html::escape(&target)
+                )
+            }
+        }
+        (Some("revoke"), Some(target)) => {
+            if is_super_admin(&target) {
+                format!(
+                    "❌ @{} is a super-admin; remove them from the config file instead.",
+                    html::escape(&target)
+                )
+            } else if granted_user_id(&bot_deps, &target)
+                .map(|user_id| bot_deps.announcer_grants.revoke(user_id))
+                .transpose()?
+                .unwrap_or(false)
+            {
+                log::info!("Announcement rights revoked from @{} by @{}", target, username);
+                format!(
+                    "✅ @{} can no longer send global announcements.",
+                    html::escape(&target)
+                )
+            } else {
+                format!("ℹ️ @{} had no announcement rights.", html::escape(&target))
+            }
+        }
+        _ => "Usage: /announcers, /announcers grant @user or /announcers revoke @user"
+            .to_string(),
+    };
+
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+/// Id holding a grant for `username`: the logged-in account, else the name recorded at grant time
+fn granted_user_id(bot_deps: &BotDependencies, username: &str) -> Option<UserId> {
+    bot_deps
+        .auth
+        .find_user_id(username)
+        .filter(|id| bot_deps.announcer_grants.is_granted(*id))
+        .or_else(|| {
+            bot_deps
+                .announcer_grants
+                .find_by_username(username)
+                .map(|grant| grant.user_id)
+        })
+}
+
 async fn gather_recipients(bot_deps: &BotDependencies) -> Result<HashSet<UserId>> {
     let mut recipients = HashSet::new();
 
@@ -165,16 +310,21 @@ async fn send_announcement_to_user(bot: Bot, user_id: UserId, text: &str) -> Res
     // Handle long messages by splitting them
     const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
 
-    if text.len() > TELEGRAM_MESSAGE_LIMIT {
-        let chunks = split_text(text, TELEGRAM_MESSAGE_LIMIT);
-        for chunk in chunks {
-            bot.send_message(user_id, chunk)
-                .parse_mode(ParseMode::Html)
-                .await?;
-        }
+    let limiter = SendLimiter::global();
+
+    let chunks = if text.len() > TELEGRAM_MESSAGE_LIMIT {
+        split_text(text, TELEGRAM_MESSAGE_LIMIT)
     } else {
-        bot.send_message(user_id, text)
-            .parse_mode(ParseMode::Html)
+        vec![text.to_string()]
+    };
+
+    for chunk in chunks {
+        limiter
+            .send(|| {
+                bot.send_message(user_id, chunk.clone())
+                    .parse_mode(ParseMode::Html)
+                    .send()
+            })
             .await?;
     }
 
@@ -3,8 +3,9 @@
 pub mod handler;
 pub mod dto;
 pub mod announcement;
+pub mod grants;
 
 // Re-export the public handler so existing call sites can import from crate root.
-pub use handler::handle_announcement;
+pub use handler::{handle_announcement, handle_announcers_command};
 
 
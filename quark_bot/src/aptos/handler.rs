@@ -140,4 +140,19 @@ impl Aptos {
 
         Ok(balance.unwrap())
     }
+
+    /// A committed transaction by hash; `Ok(None)` when the node doesn't know it
+    pub async fn get_transaction_by_hash(&self, hash: &str) -> Result<Option<serde_json::Value>> {
+        match self.node.get_transaction_by_hash(hash.to_string()).await {
+            Ok(response) => Ok(Some(response.into_inner())),
+            Err(e) => {
+                let error_msg = e.to_string();
+                if error_msg.contains("404") || error_msg.contains("transaction_not_found") {
+                    Ok(None)
+                } else {
+                    Err(e.into())
+                }
+            }
+        }
+    }
 }
@@ -1,33 +1,137 @@
 use crate::dependencies::BotDependencies;
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
 use std::sync::Arc;
 use std::time::Duration;
 use teloxide::prelude::*;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 
-use crate::bot::handler::handle_chat;
+use crate::bot::handler::handle_chat_with_model;
+use crate::user_model_preferences::dto::ChatModel;
+
+/// How long a persisted pending command may still pick up a late photo (e.g. across a restart)
+const PENDING_TTL_SECS: i64 = 120;
+
+/// Pending command state mirrored to sled so a restart doesn't drop it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PersistedPendingCmd {
+    pub first_msg: Message,
+    pub extra_photos: Vec<Message>,
+    pub group_id: Option<String>,
+    pub expires_at: i64,
+    /// Model picked with `/c:alias`; absent in commands persisted before it existed
+    #[serde(default)]
+    pub model: Option<ChatModel>,
+}
+
+#[derive(Clone)]
+pub struct PendingCommandStore {
+    tree: Tree,
+}
+
+impl PendingCommandStore {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree("pending_image_commands")?;
+        Ok(Self { tree })
+    }
+
+    fn key(key: (ChatId, i64)) -> String {
+        format!("{}:{}", key.0.0, key.1)
+    }
+
+    pub fn save(&self, key: (ChatId, i64), pending: &PersistedPendingCmd) {
+        match serde_json::to_vec(pending) {
+            Ok(bytes) => {
+                if let Err(e) = self.tree.insert(Self::key(key), bytes) {
+                    log::warn!("Failed to persist pending command: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to serialize pending command: {}", e),
+        }
+    }
+
+    /// Load a pending command, dropping it if it has expired
+    pub fn load(&self, key: (ChatId, i64), now: i64) -> Option<PersistedPendingCmd> {
+        let bytes = self.tree.get(Self::key(key)).ok().flatten()?;
+        let pending = serde_json::from_slice::<PersistedPendingCmd>(&bytes).ok();
+
+        match pending {
+            Some(pending) if pending.expires_at > now => Some(pending),
+            _ => {
+                self.remove(key);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&self, key: (ChatId, i64)) {
+        if let Err(e) = self.tree.remove(Self::key(key)) {
+            log::warn!("Failed to remove pending command: {}", e);
+        }
+    }
+
+    /// Remove every expired or unreadable entry; returns how many were dropped
+    pub fn purge_expired(&self, now: i64) -> usize {
+        let mut removed = 0;
+        for (k, v) in self.tree.iter().flatten() {
+            let expired = serde_json::from_slice::<PersistedPendingCmd>(&v)
+                .map(|p| p.expires_at <= now)
+                .unwrap_or(true);
+            if expired && self.tree.remove(k).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
 
 /// Holds an in-flight `/c` command and any trailing photo-only messages
 struct PendingCmd {
     first_msg: Message,
     extra_photos: Vec<Message>,
+    model: Option<ChatModel>,
     timer: Option<JoinHandle<()>>, // debounce task
 }
 
 pub struct CommandImageCollector {
     // Keyed by (chat_id, user_id)
     pendings: DashMap<(ChatId, i64), PendingCmd>,
+    store: PendingCommandStore,
     bot: Bot,
     debounce_ms: u64,
 }
 
 impl CommandImageCollector {
-    pub fn new(bot: Bot) -> Self {
-        Self {
+    pub fn new(bot: Bot, db: &Db) -> sled::Result<Self> {
+        let store = PendingCommandStore::new(db)?;
+
+        let purged = store.purge_expired(chrono::Utc::now().timestamp());
+        if purged > 0 {
+            log::info!("Dropped {} stale pending image commands", purged);
+        }
+
+        Ok(Self {
             pendings: DashMap::new(),
+            store,
             bot,
             debounce_ms: 1000, // 1 second default
+        })
+    }
+
+    fn persist(&self, key: (ChatId, i64), group_id: &Option<String>) {
+        if let Some(entry) = self.pendings.get(&key) {
+            self.store.save(
+                key,
+                &PersistedPendingCmd {
+                    first_msg: entry.first_msg.clone(),
+                    extra_photos: entry.extra_photos.clone(),
+                    group_id: group_id.clone(),
+                    expires_at: chrono::Utc::now().timestamp() + PENDING_TTL_SECS,
+                    model: entry.model.clone(),
+                },
+            );
         }
     }
 
@@ -37,6 +141,7 @@ impl CommandImageCollector {
         msg: Message,
         bot_deps: BotDependencies,
         group_id: Option<String>,
+        model: Option<ChatModel>,
     ) {
         // Cancel any existing pending command for this user/chat
         let key = (
@@ -55,9 +160,11 @@ impl CommandImageCollector {
             PendingCmd {
                 first_msg: msg.clone(),
                 extra_photos: Vec::new(),
+                model,
                 timer: None,
             },
         );
+        self.persist(key, &group_id);
 
         self.reset_timer(msg, key, bot_deps, group_id);
     }
@@ -71,11 +178,48 @@ impl CommandImageCollector {
     ) {
         let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
         let key = (msg.chat.id, user_id);
+
+        let mut group_id = group_id;
+        if self.attach_photo(key, &msg, &mut group_id) {
+            self.persist(key, &group_id);
+            // restart debounce
+            self.reset_timer(msg, key, bot_deps, group_id);
+        }
+    }
+
+    /// Add the photo to the user's pending command, if there is one. `group_id` takes the
+    /// command's own group when it is restored from storage.
+    fn attach_photo(
+        &self,
+        key: (ChatId, i64),
+        msg: &Message,
+        group_id: &mut Option<String>,
+    ) -> bool {
+        // After a restart the command only survives in sled; bring it back into memory
+        if !self.pendings.contains_key(&key) {
+            if let Some(persisted) = self.store.load(key, chrono::Utc::now().timestamp()) {
+                log::info!("Restored pending command for {:?} from storage", key);
+                if persisted.group_id.is_some() {
+                    *group_id = persisted.group_id;
+                }
+                self.pendings.insert(
+                    key,
+                    PendingCmd {
+                        first_msg: persisted.first_msg,
+                        extra_photos: persisted.extra_photos,
+                        model: persisted.model,
+                        timer: None,
+                    },
+                );
+            }
+        }
+
         if let Some(mut entry) = self.pendings.get_mut(&key) {
             // Attach photo
             entry.extra_photos.push(msg.clone());
-            // restart debounce
-            self.reset_timer(msg, key, bot_deps, group_id);
+            true
+        } else {
+            false
         }
     }
 
@@ -112,6 +256,7 @@ impl CommandImageCollector {
         bot_deps: BotDependencies,
     ) {
         if let Some((_k, pending)) = self.pendings.remove(&key) {
+            self.store.remove(key);
             let mut all_msgs = Vec::new();
             all_msgs.push(pending.first_msg.clone());
             all_msgs.extend(pending.extra_photos);
@@ -122,12 +267,13 @@ impl CommandImageCollector {
                 // Single message path
                 let msg = all_msgs.pop().unwrap();
                 // Unified handler for both /c and legacy /r
-                if let Err(e) = handle_chat(
+                if let Err(e) = handle_chat_with_model(
                     self.bot.clone(),
                     msg,
                     text.to_string(),
                     group_id,
                     false,
+                    pending.model,
                     bot_deps.clone(),
                 )
                 .await
@@ -140,3 +286,107 @@ impl CommandImageCollector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(chat_id: i64, user_id: u64, text: &str) -> Message {
+        serde_json::from_value(serde_json::json!({
+            "message_id": 1,
+            "date": 1_700_000_000,
+            "chat": { "id": chat_id, "type": "private", "first_name": "Test" },
+            "from": { "id": user_id, "is_bot": false, "first_name": "Test" },
+            "text": text,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn pending_command_survives_restart() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let key = (ChatId(42), 7);
+        let now = 1_700_000_000;
+
+        {
+            let store = PendingCommandStore::new(&db).unwrap();
+            store.save(
+                key,
+                &PersistedPendingCmd {
+                    first_msg: message(42, 7, "/c describe this"),
+                    extra_photos: vec![],
+                    group_id: None,
+                    expires_at: now + PENDING_TTL_SECS,
+                    model: None,
+                },
+            );
+        }
+
+        // A fresh store over the same db stands in for the restarted bot
+        let store = PendingCommandStore::new(&db).unwrap();
+        assert_eq!(store.purge_expired(now), 0);
+
+        let restored = store.load(key, now + 1).expect("pending command restored");
+        assert_eq!(restored.first_msg.text(), Some("/c describe this"));
+
+        store.remove(key);
+        assert!(store.load(key, now + 1).is_none());
+    }
+
+    #[test]
+    fn photo_after_restart_attaches_to_stored_command() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let key = (ChatId(42), 7);
+
+        {
+            let collector = CommandImageCollector::new(Bot::new("0:test"), &db).unwrap();
+            collector.pendings.insert(
+                key,
+                PendingCmd {
+                    first_msg: message(42, 7, "/c describe this"),
+                    extra_photos: Vec::new(),
+                    model: Some(ChatModel::GPT5Mini),
+                    timer: None,
+                },
+            );
+            collector.persist(key, &Some("-100123".to_string()));
+        }
+
+        // The restarted bot only has what was persisted
+        let collector = CommandImageCollector::new(Bot::new("0:test"), &db).unwrap();
+        let mut group_id = None;
+        assert!(collector.attach_photo(key, &message(42, 7, ""), &mut group_id));
+        assert_eq!(group_id.as_deref(), Some("-100123"));
+
+        let pending = collector.pendings.get(&key).unwrap();
+        assert_eq!(pending.first_msg.text(), Some("/c describe this"));
+        assert_eq!(pending.extra_photos.len(), 1);
+        assert_eq!(pending.model, Some(ChatModel::GPT5Mini));
+
+        // Nothing pending for another user, so their photo is left alone
+        let mut group_id = None;
+        assert!(!collector.attach_photo((ChatId(42), 8), &message(42, 8, ""), &mut group_id));
+    }
+
+    #[test]
+    fn expired_pending_command_is_dropped() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let store = PendingCommandStore::new(&db).unwrap();
+        let key = (ChatId(42), 7);
+        let now = 1_700_000_000;
+
+        store.save(
+            key,
+            &PersistedPendingCmd {
+                first_msg: message(42, 7, "/c too late"),
+                extra_photos: vec![],
+                group_id: None,
+                expires_at: now,
+                model: None,
+            },
+        );
+
+        assert!(store.load(key, now + 1).is_none());
+        assert_eq!(store.purge_expired(now + 1), 0);
+    }
+}
@@ -3,14 +3,19 @@
 use crate::ai::group_vector_store::{
     list_group_files_with_names, upload_files_to_group_vector_store,
 };
+use crate::ai::upload_session::UploadSessions;
 use crate::ai::vector_store::{list_user_files_with_names, upload_files_to_vector_store};
 use crate::dependencies::BotDependencies;
+use crate::user_conversation::dto::LibraryFile;
 use crate::utils::{self, KeyboardMarkupType, send_markdown_message_with_keyboard, send_message};
 use anyhow::Result as AnyResult;
 use std::time::Duration;
 use teloxide::net::Download;
 use teloxide::prelude::*;
-use teloxide::types::{ChatAction, InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
+use teloxide::types::{
+    ChatAction, InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode,
+};
+use teloxide::utils::html;
 use tokio::time::sleep;
 
 pub async fn handle_file_upload(
@@ -83,7 +88,7 @@ pub async fn handle_file_upload(
                 show_user_document_library(bot, chat_id, user_id, bot_deps).await?;
             }
             Err(e) => {
-                send_message(msg, bot, format!("[Upload error]: {}", e).to_string()).await?;
+                send_upload_failure(&bot, chat_id, user_id, &e, &bot_deps).await?;
             }
         }
     } else {
@@ -92,6 +97,194 @@ pub async fn handle_file_upload(
     Ok(())
 }
 
+/// Report a failed upload, listing what's left and offering to resume if progress was kept
+async fn send_upload_failure(
+    bot: &Bot,
+    chat_id: ChatId,
+    user_id: i64,
+    error: &anyhow::Error,
+    bot_deps: &BotDependencies,
+) -> AnyResult<()> {
+    let session = UploadSessions::new(&bot_deps.db)?.get(user_id);
+
+    let Some(session) = session else {
+        bot.send_message(chat_id, format!("[Upload error]: {}", error))
+            .await?;
+        return Ok(());
+    };
+
+    let remaining = session.remaining();
+    let remaining_text = if remaining.is_empty() {
+        "All files were uploaded; only adding them to your library failed.".to_string()
+    } else {
+        let names = remaining
+            .iter()
+            .map(|p| format!("• {}", utils::clean_filename(&file_name(p))))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("Files still to upload:\n{}", names)
+    };
+
+    let kb = InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("🔁 Resume Upload", "upload_resume"),
+        InlineKeyboardButton::callback("✖️ Discard", "upload_discard"),
+    ]]);
+
+    bot.send_message(
+        chat_id,
+        format!(
+            "⚠️ Upload interrupted ({} of {} files done): {}\n\n{}",
+            session.uploaded.len(),
+            session.file_paths.len(),
+            error,
+            remaining_text
+        ),
+    )
+    .reply_markup(kb)
+    .await?;
+    Ok(())
+}
+
+fn file_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// Resume or discard an interrupted upload session
+pub async fn handle_upload_session_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let data = query.data.as_deref().unwrap_or_default();
+    let user_id = query.from.id.0 as i64;
+    let sessions = UploadSessions::new(&bot_deps.db)?;
+
+    let Some(MaybeInaccessibleMessage::Regular(message)) = &query.message else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+    let chat_id = message.chat.id;
+
+    if data == "upload_discard" {
+        sessions.clear(user_id)?;
+        bot.answer_callback_query(query.id)
+            .text("Upload discarded")
+            .await?;
+        bot.edit_message_text(chat_id, message.id, "✖️ Upload discarded.")
+            .await?;
+        return Ok(());
+    }
+
+    let Some(session) = sessions.get(user_id) else {
+        bot.answer_callback_query(query.id)
+            .text("❌ Nothing to resume — please upload the files again.")
+            .await?;
+        return Ok(());
+    };
+
+    // The local copies are needed for anything not yet uploaded
+    let mut missing = Vec::new();
+    for path in session.remaining() {
+        if tokio::fs::metadata(path).await.is_err() {
+            missing.push(utils::clean_filename(&file_name(path)));
+        }
+    }
+    if !missing.is_empty() {
+        sessions.clear(user_id)?;
+        bot.answer_callback_query(query.id).await?;
+        bot.edit_message_text(
+            chat_id,
+            message.id,
+            format!(
+                "❌ These files are no longer available to resume, please send them again:\n{}",
+                missing
+                    .iter()
+                    .map(|n| format!("• {}", n))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    bot.answer_callback_query(query.id)
+        .text("🔁 Resuming upload...")
+        .await?;
+    bot.edit_message_text(chat_id, message.id, "🔁 Resuming upload...")
+        .await?;
+
+    let file_count = session.file_paths.len();
+    match upload_files_to_vector_store(user_id, bot_deps.clone(), session.file_paths).await {
+        Ok(vector_store_id) => {
+            bot.send_message(
+                chat_id,
+                format!(
+                    "✅ Upload resumed and completed! {} {} indexed. Your vector store ID: {}",
+                    file_count,
+                    if file_count == 1 { "file" } else { "files" },
+                    vector_store_id
+                ),
+            )
+            .await?;
+            show_user_document_library(bot, chat_id, user_id, bot_deps).await?;
+        }
+        Err(e) => {
+            send_upload_failure(&bot, chat_id, user_id, &e, &bot_deps).await?;
+        }
+    }
+    Ok(())
+}
+
+/// One line per file in the user's Document Library, with its size when known
+pub fn user_library_file_list(files: &[LibraryFile]) -> String {
+    files
+        .iter()
+        .map(|file| {
+            let icon = utils::get_file_icon(&file.name);
+            let clean_name = utils::clean_filename(&file.name);
+            match file.bytes {
+                Some(bytes) => format!(
+                    "{}  <b>{}</b> · {}",
+                    icon,
+                    clean_name,
+                    utils::format_file_size(bytes)
+                ),
+                None => format!("{}  <b>{}</b>", icon, clean_name),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A delete button per file, plus Clear All when there is more than one.
+/// Both ask for confirmation before anything is removed.
+pub fn user_library_file_buttons(files: &[LibraryFile]) -> Vec<Vec<InlineKeyboardButton>> {
+    let mut keyboard_rows = Vec::new();
+    for file in files {
+        let clean_name = utils::clean_filename(&file.name);
+        let button_text = if clean_name.chars().count() > 25 {
+            let cut: String = clean_name.chars().take(22).collect();
+            format!("🗑️ {}", cut.trim_end())
+        } else {
+            format!("🗑️ {}", clean_name)
+        };
+        let delete_button =
+            InlineKeyboardButton::callback(button_text, format!("delete_file:{}", file.id));
+        keyboard_rows.push(vec![delete_button]);
+    }
+    if files.len() > 1 {
+        let clear_all_button =
+            InlineKeyboardButton::callback("🗑️ Clear All Files", "clear_all_files");
+        keyboard_rows.push(vec![clear_all_button]);
+    }
+    keyboard_rows
+}
+
 /// Display the user document library interface as a new message
 pub async fn show_user_document_library(
     bot: Bot,
@@ -99,7 +292,8 @@ pub async fn show_user_document_library(
     user_id: i64,
     bot_deps: BotDependencies,
 ) -> AnyResult<()> {
-    match list_user_files_with_names(user_id, bot_deps) {
+    let kb_name = html::escape(&bot_deps.user_convos.active_knowledge_base_name(user_id));
+    match list_user_files_with_names(user_id, bot_deps).await {
         Ok(files) => {
             let (text, keyboard) = if files.is_empty() {
                 let kb = InlineKeyboardMarkup::new(vec![
@@ -113,43 +307,21 @@ pub async fn show_user_document_library(
                     )],
                 ]);
                 (
-                    "📁 <b>Your Document Library</b>\n\n<i>No files uploaded yet</i>\n\n💡 Use the button below to upload your first documents.".to_string(),
+                    format!(
+                        "📁 <b>Your Document Library</b> · 📚 {}\n\n<i>No files uploaded yet</i>\n\n💡 Use the button below to upload your first documents. Switch knowledge bases with /usekb.",
+                        kb_name
+                    ),
                     kb,
                 )
             } else {
-                let file_list = files
-                    .iter()
-                    .map(|file| {
-                        let icon = utils::get_file_icon(&file.name);
-                        let clean_name = utils::clean_filename(&file.name);
-                        format!("{}  <b>{}</b>", icon, clean_name)
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
+                let file_list = user_library_file_list(&files);
                 let response = format!(
-                    "🗂️ <b>Your Document Library</b> ({} files)\n\n{}\n\n💡 <i>Tap any button below to manage your files</i>",
+                    "🗂️ <b>Your Document Library</b> · 📚 {} ({} files)\n\n{}\n\n💡 <i>Tap any button below to manage your files</i>",
+                    kb_name,
                     files.len(),
                     file_list
                 );
-                let mut keyboard_rows = Vec::new();
-                for file in &files {
-                    let clean_name = utils::clean_filename(&file.name);
-                    let button_text = if clean_name.len() > 25 {
-                        format!("🗑️ {}", &clean_name[..22].trim_end())
-                    } else {
-                        format!("🗑️ {}", clean_name)
-                    };
-                    let delete_button = InlineKeyboardButton::callback(
-                        button_text,
-                        format!("delete_file:{}", file.id),
-                    );
-                    keyboard_rows.push(vec![delete_button]);
-                }
-                if files.len() > 1 {
-                    let clear_all_button =
-                        InlineKeyboardButton::callback("🗑️ Clear All Files", "clear_all_files");
-                    keyboard_rows.push(vec![clear_all_button]);
-                }
+                let mut keyboard_rows = user_library_file_buttons(&files);
                 // Upload + Back controls
                 keyboard_rows.push(vec![InlineKeyboardButton::callback(
                     "📎 Upload Files",
@@ -343,8 +515,9 @@ pub async fn show_group_document_library(
                 let mut keyboard_rows = Vec::new();
                 for file in &files {
                     let clean_name = utils::clean_filename(&file.name);
-                    let button_text = if clean_name.len() > 25 {
-                        format!("🗑️ {}", &clean_name[..22].trim_end())
+                    let button_text = if clean_name.chars().count() > 25 {
+                        let cut: String = clean_name.chars().take(22).collect();
+                        format!("🗑️ {}", cut.trim_end())
                     } else {
                         format!("🗑️ {}", clean_name)
                     };
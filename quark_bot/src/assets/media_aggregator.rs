@@ -1,9 +1,11 @@
 use crate::ai::handler::AI;
+use crate::bot::handler_tree::parse_command_text;
 use crate::credentials::handler::Auth;
 use crate::dependencies::BotDependencies;
 use crate::user_model_preferences::handler::UserModelPreferences;
 use dashmap::DashMap;
 use open_ai_rust_responses_by_sshift::types::ReasoningParams;
+use quark_core::helpers::bot_commands::Command;
 
 use std::sync::Arc;
 use std::time::Duration;
@@ -81,7 +83,18 @@ impl MediaGroupAggregator {
         if let Some(cmd_msg) = command_msg {
             // Determine prompt & command type
             let text = cmd_msg.caption().unwrap_or("");
-            let is_group_command = text.trim_start().starts_with("/g ");
+            // Same parsing as commands, so a custom prefix like `!g` bills the group too
+            let bot_name = self
+                .bot
+                .get_me()
+                .await
+                .ok()
+                .and_then(|me| me.user.username.clone())
+                .unwrap_or_default();
+            let is_group_command = matches!(
+                parse_command_text(text, cmd_msg, &bot_name, &bot_deps),
+                Some(Command::G(_))
+            );
             let group_id = if is_group_command && !cmd_msg.chat.is_private() {
                 Some(cmd_msg.chat.id.to_string())
             } else {
@@ -141,9 +154,39 @@ impl MediaGroupAggregator {
             let model = prefs.chat_model.to_openai_model();
             let reasoning_params: Option<ReasoningParams> = None;
 
+            // --- Cap the album size to keep vision cost in check ---
+            let max_images = bot_deps
+                .command_settings
+                .max_images_per_request(chat_id.to_string());
+            let photo_count = messages.iter().filter(|m| m.photo().is_some()).count();
+            if photo_count > max_images {
+                log::info!(
+                    "Image cap applied in chat {}: album of {} images, using first {}",
+                    chat_id,
+                    photo_count,
+                    max_images
+                );
+                if let Err(e) = self
+                    .bot
+                    .send_message(
+                        chat_id,
+                        format!(
+                            "⚠️ Only the first {} image(s) will be used for this request.",
+                            max_images
+                        ),
+                    )
+                    .await {
+                    log::warn!("Failed to send image cap warning: {}", e);
+                }
+            }
+
             // --- Gather photos: take largest variant from each message ---
             let mut image_paths: Vec<(String, String)> = Vec::new();
-            for m in &messages {
+            for m in messages
+                .iter()
+                .filter(|m| m.photo().is_some())
+                .take(max_images)
+            {
                 if let Some(photos) = m.photo() {
                     if let Some(photo) = photos.last() {
                         let file_id = &photo.file.id;
@@ -206,6 +249,7 @@ impl MediaGroupAggregator {
                     reasoning_params,
                     bot_deps.clone(),
                     group_id.clone(),
+                    None,
                 )
                 .await;
 
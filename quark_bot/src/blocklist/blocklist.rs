@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+
+const TREE_NAME: &str = "blocklist";
+pub const MAX_REASON_CHARS: usize = 200;
+
+/// Where a block applies: everywhere (operators) or a single group (group admins)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlockScope {
+    Global,
+    Group(i64),
+}
+
+impl BlockScope {
+    fn prefix(&self) -> String {
+        match self {
+            BlockScope::Global => "global:".to_string(),
+            BlockScope::Group(chat_id) => format!("group:{}:", chat_id),
+        }
+    }
+
+    fn key(&self, user_id: i64) -> String {
+        format!("{}{}", self.prefix(), user_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockEntry {
+    pub user_id: i64,
+    pub scope: BlockScope,
+    pub reason: Option<String>,
+    pub blocked_by: i64,
+    pub created_at: i64,
+    /// Unix seconds; `None` blocks until removed
+    pub expires_at: Option<i64>,
+}
+
+impl BlockEntry {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// Parse "30m", "12h", "7d" or "2w" into seconds
+pub fn parse_duration(input: &str) -> Option<i64> {
+    let input = input.trim().to_lowercase();
+    let unit = input.chars().last()?;
+    let value = &input[..input.len() - unit.len_utf8()];
+    let value: i64 = value.parse().ok().filter(|v| *v > 0)?;
+    let unit_secs = match unit {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    value.checked_mul(unit_secs)
+}
+
+/// Users the bot ignores entirely, globally or per group
+#[derive(Clone)]
+pub struct Blocklist {
+    tree: Tree,
+}
+
+impl Blocklist {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn block(&self, entry: &BlockEntry) -> sled::Result<()> {
+        self.tree.insert(
+            entry.scope.key(entry.user_id),
+            serde_json::to_vec(entry).unwrap(),
+        )?;
+        Ok(())
+    }
+
+    /// Returns true when an entry was removed
+    pub fn unblock(&self, scope: BlockScope, user_id: i64) -> sled::Result<bool> {
+        Ok(self.tree.remove(scope.key(user_id))?.is_some())
+    }
+
+    fn active_entry(&self, scope: BlockScope, user_id: i64, now: i64) -> Option<BlockEntry> {
+        let key = scope.key(user_id);
+        let entry: BlockEntry = self
+            .tree
+            .get(&key)
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())?;
+        if entry.is_expired(now) {
+            let _ = self.tree.remove(&key);
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Global blocks win over group ones; `chat_id` is `None` for DMs
+    pub fn find(&self, chat_id: Option<i64>, user_id: i64, now: i64) -> Option<BlockEntry> {
+        if self.tree.is_empty() {
+            return None;
+        }
+        self.active_entry(BlockScope::Global, user_id, now).or_else(|| {
+            chat_id.and_then(|chat_id| self.active_entry(BlockScope::Group(chat_id), user_id, now))
+        })
+    }
+
+    pub fn is_blocked(&self, chat_id: Option<i64>, user_id: i64) -> bool {
+        self.find(chat_id, user_id, chrono::Utc::now().timestamp())
+            .is_some()
+    }
+
+    /// Active entries for a scope, newest first
+    pub fn list(&self, scope: BlockScope, now: i64) -> Vec<BlockEntry> {
+        let mut entries: Vec<BlockEntry> = self
+            .tree
+            .scan_prefix(scope.prefix())
+            .filter_map(|kv| kv.ok())
+            .filter_map(|(_, v)| serde_json::from_slice::<BlockEntry>(&v).ok())
+            .filter(|e| !e.is_expired(now))
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(1_800));
+        assert_eq!(parse_duration("12H"), Some(43_200));
+        assert_eq!(parse_duration("7d"), Some(604_800));
+        assert_eq!(parse_duration("1w"), Some(604_800));
+        assert_eq!(parse_duration("0d"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("spam"), None);
+    }
+
+    #[test]
+    fn test_group_keys_do_not_overlap() {
+        // "group:-1001:" must not prefix-match keys of "group:-10012:"
+        assert_eq!(BlockScope::Group(-1001).key(5), "group:-1001:5");
+        assert!(!BlockScope::Group(-10012).key(5).starts_with(&BlockScope::Group(-1001).prefix()));
+    }
+}
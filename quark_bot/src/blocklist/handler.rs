@@ -0,0 +1,298 @@
+use anyhow::Result;
+use teloxide::{prelude::*, types::UserId, utils::html};
+
+use super::blocklist::{BlockEntry, BlockScope, MAX_REASON_CHARS, parse_duration};
+use crate::{
+    announcement::announcement::AnnouncerAuth,
+    dependencies::BotDependencies,
+    utils::{self, format_timestamp, send_html_message, send_message},
+};
+
+const BLOCK_USAGE: &str = "Usage: /block [global] <user id> [duration like 12h or 7d] [reason]\nOr reply to a user's message with /block [duration] [reason].";
+const UNBLOCK_USAGE: &str = "Usage: /unblock [global] <user id>, or reply to a user's message with /unblock.";
+
+#[derive(Debug, PartialEq)]
+pub struct BlockArgs {
+    pub global: bool,
+    pub user_id: i64,
+    pub duration_secs: Option<i64>,
+    pub reason: Option<String>,
+}
+
+/// Parse `/block` arguments; the target comes from the reply when there is one
+pub fn parse_block_args(args: &str, replied_user: Option<i64>) -> Result<BlockArgs, String> {
+    let mut words = args.split_whitespace().peekable();
+    let global = words.next_if(|w| w.eq_ignore_ascii_case("global")).is_some();
+
+    let user_id = match replied_user {
+        Some(user_id) => user_id,
+        None => words
+            .next()
+            .and_then(|w| w.parse::<i64>().ok())
+            .ok_or_else(|| BLOCK_USAGE.to_string())?,
+    };
+
+    let duration_secs = words.peek().and_then(|w| parse_duration(w));
+    if duration_secs.is_some() {
+        words.next();
+    }
+
+    let reason = words.collect::<Vec<_>>().join(" ");
+    if reason.chars().count() > MAX_REASON_CHARS {
+        return Err(format!("Reasons can be at most {} characters.", MAX_REASON_CHARS));
+    }
+
+    Ok(BlockArgs {
+        global,
+        user_id,
+        duration_secs,
+        reason: (!reason.is_empty()).then_some(reason),
+    })
+}
+
+fn is_operator(msg: &Message) -> bool {
+    let username = msg.from.as_ref().and_then(|u| u.username.clone());
+    match (username, AnnouncerAuth::load_default()) {
+        (Some(username), Ok(auth)) => auth.is_authorized(&username),
+        (_, Err(e)) => {
+            log::error!("Failed to load authorized operators: {}", e);
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Resolve the scope and check the caller may manage it; sends the refusal itself
+async fn authorized_scope(bot: &Bot, msg: &Message, global: bool) -> Result<Option<BlockScope>> {
+    if global {
+        if !is_operator(msg) {
+            send_message(
+                msg.clone(),
+                bot.clone(),
+                "❌ Only bot operators can manage the global blocklist.".to_string(),
+            )
+            .await?;
+            return Ok(None);
+        }
+        return Ok(Some(BlockScope::Global));
+    }
+
+    if msg.chat.is_private() {
+        send_message(
+            msg.clone(),
+            bot.clone(),
+            "❌ Use this in a group to manage its blocklist, or add \"global\" if you're an operator."
+                .to_string(),
+        )
+        .await?;
+        return Ok(None);
+    }
+
+    let Some(user) = &msg.from else {
+        return Ok(None);
+    };
+    if !utils::is_admin(bot, msg.chat.id, user.id).await {
+        send_message(
+            msg.clone(),
+            bot.clone(),
+            "❌ Only group administrators can manage the blocklist.".to_string(),
+        )
+        .await?;
+        return Ok(None);
+    }
+    Ok(Some(BlockScope::Group(msg.chat.id.0)))
+}
+
+fn replied_user_id(msg: &Message) -> Option<i64> {
+    msg.reply_to_message()
+        .and_then(|reply| reply.from.as_ref())
+        .filter(|u| !u.is_bot)
+        .map(|u| u.id.0 as i64)
+}
+
+fn scope_label(scope: BlockScope) -> &'static str {
+    match scope {
+        BlockScope::Global => "everywhere",
+        BlockScope::Group(_) => "in this group",
+    }
+}
+
+/// /block — make the bot ignore a user's messages and commands
+pub async fn handle_block_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let parsed = match parse_block_args(&args, replied_user_id(&msg)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            send_message(msg, bot, e).await?;
+            return Ok(());
+        }
+    };
+    let Some(scope) = authorized_scope(&bot, &msg, parsed.global).await? else {
+        return Ok(());
+    };
+
+    let blocked_by = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or_default();
+    if parsed.user_id == blocked_by {
+        send_message(msg, bot, "❌ You can't block yourself.".to_string()).await?;
+        return Ok(());
+    }
+    if let BlockScope::Group(_) = scope {
+        if utils::is_admin(&bot, msg.chat.id, UserId(parsed.user_id as u64)).await {
+            send_message(msg, bot, "❌ Group administrators can't be blocked.".to_string())
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let entry = BlockEntry {
+        user_id: parsed.user_id,
+        scope,
+        reason: parsed.reason,
+        blocked_by,
+        created_at: now,
+        expires_at: parsed.duration_secs.map(|secs| now + secs),
+    };
+    bot_deps.blocklist.block(&entry)?;
+    log::info!(
+        "User {} blocked {:?} by {} until {:?}",
+        entry.user_id,
+        entry.scope,
+        blocked_by,
+        entry.expires_at
+    );
+
+    send_html_message(
+        msg,
+        bot,
+        format!(
+            "🚫 User <code>{}</code> is now ignored {}{}.{}",
+            entry.user_id,
+            scope_label(scope),
+            entry
+                .expires_at
+                .map(|at| format!(" until {}", format_timestamp(at as u64)))
+                .unwrap_or_default(),
+            entry
+                .reason
+                .as_ref()
+                .map(|r| format!("\n📝 {}", html::escape(r)))
+                .unwrap_or_default()
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// /unblock — remove a user from the blocklist
+pub async fn handle_unblock_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let mut words = args.split_whitespace().peekable();
+    let global = words.next_if(|w| w.eq_ignore_ascii_case("global")).is_some();
+    let user_id = match replied_user_id(&msg) {
+        Some(user_id) => Some(user_id),
+        None => words.next().and_then(|w| w.parse::<i64>().ok()),
+    };
+    let Some(user_id) = user_id else {
+        send_message(msg, bot, UNBLOCK_USAGE.to_string()).await?;
+        return Ok(());
+    };
+    let Some(scope) = authorized_scope(&bot, &msg, global).await? else {
+        return Ok(());
+    };
+
+    let text = if bot_deps.blocklist.unblock(scope, user_id)? {
+        format!("✅ User {} is no longer blocked {}.", user_id, scope_label(scope))
+    } else {
+        format!("ℹ️ User {} isn't blocked {}.", user_id, scope_label(scope))
+    };
+    send_message(msg, bot, text).await?;
+    Ok(())
+}
+
+/// /blocklist — show active blocks for this group, or the global list for operators
+pub async fn handle_blocklist_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let global = args.trim().eq_ignore_ascii_case("global");
+    let Some(scope) = authorized_scope(&bot, &msg, global).await? else {
+        return Ok(());
+    };
+
+    let entries = bot_deps
+        .blocklist
+        .list(scope, chrono::Utc::now().timestamp());
+    if entries.is_empty() {
+        send_message(msg, bot, format!("✅ Nobody is blocked {}.", scope_label(scope))).await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|e| {
+            format!(
+                "• <code>{}</code> — {}{}",
+                e.user_id,
+                e.expires_at
+                    .map(|at| format!("until {}", format_timestamp(at as u64)))
+                    .unwrap_or_else(|| "permanent".to_string()),
+                e.reason
+                    .as_ref()
+                    .map(|r| format!(" · {}", html::escape(r)))
+                    .unwrap_or_default()
+            )
+        })
+        .collect();
+    send_html_message(
+        msg,
+        bot,
+        format!(
+            "🚫 <b>Blocked users {}</b> ({})\n\n{}",
+            scope_label(scope),
+            entries.len(),
+            lines.join("\n")
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block_args() {
+        assert_eq!(
+            parse_block_args("global 42 7d spamming links", None),
+            Ok(BlockArgs {
+                global: true,
+                user_id: 42,
+                duration_secs: Some(7 * 24 * 60 * 60),
+                reason: Some("spamming links".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_block_args("abusive", Some(7)),
+            Ok(BlockArgs {
+                global: false,
+                user_id: 7,
+                duration_secs: None,
+                reason: Some("abusive".to_string()),
+            })
+        );
+        assert!(parse_block_args("", None).is_err());
+        assert!(parse_block_args("@someone", None).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod blocklist;
+pub mod handler;
+
+pub use blocklist::Blocklist;
@@ -7,24 +7,52 @@ use teloxide::{
 };
 
 use super::handler::{
-    handle_chat, handle_help, handle_login_group, handle_login_user, handle_mod, handle_new_chat,
-    handle_prices, handle_rules,
+    handle_chat, handle_chat_with_model, handle_debug, handle_forget, handle_help, handle_last_image, handle_login_group, handle_login_user, handle_mod, handle_new_chat, handle_test_mod,
+    handle_prices, handle_rates, handle_refresh_group, handle_refresh_tokens, handle_rules,
 };
 use crate::utils::{self, KeyboardMarkupType, send_markdown_message_with_keyboard};
 use crate::yield_ai::handler as yield_ai_handler;
-use crate::{announcement::handle_announcement, utils::send_message};
+use crate::{
+    announcement::{handle_announcement, handle_announcers_command},
+    utils::send_message,
+};
 
 use crate::bot::handler::{
     handle_aptos_connect, handle_balance, handle_group_balance, handle_group_wallet_address,
     handle_wallet_address,
 };
+use crate::ai::sentinel::snooze::handle_snooze_sentinel_command;
+use crate::ai::moderation::flagged_log::handle_flagged_command;
+use crate::command_settings::handler::handle_command_prefix_command;
+use crate::blocklist::handler::{
+    handle_block_command, handle_blocklist_command, handle_unblock_command,
+};
+use crate::bot::balances::{handle_balances, handle_group_balances};
+use crate::bot::pools::handle_pools;
+use crate::bot::price::handle_price;
+use crate::bot::diagnostics::{handle_config, handle_uptime, handle_version};
+use crate::dao::handler::handle_my_votes;
 use crate::dependencies::BotDependencies;
+use crate::explain_tx::handler::handle_explain_tx_command;
+use crate::notification_prefs::handler::handle_notifications_command;
+use crate::group::members::handle_members;
+use crate::payment::handler::{handle_send_command, handle_send_max_command};
+use crate::pending_transactions::command::handle_pending_command;
+use crate::repeat_guard::handler::hold_repeated_prompt;
 use crate::scheduled_payments::handler::{
-    handle_listscheduledpayments_command, handle_schedulepayment_command,
+    handle_export_scheduled_payments_command, handle_listscheduledpayments_command,
+    handle_schedulepayment_command,
 };
 use crate::scheduled_prompts::handler::{
     handle_listscheduled_command, handle_scheduleprompt_command,
 };
+use crate::usage_stats::handler::handle_stats_command;
+use crate::user_conversation::knowledge_base::{handle_list_kb, handle_new_kb, handle_use_kb};
+use crate::group_budget::handler::handle_ai_budget_command;
+use crate::user_model_preferences::aliases::{
+    global_aliases, handle_model_alias_command, parse_model_prefix, resolve_model,
+};
+use crate::user_model_preferences::dto::ChatModel;
 
 pub async fn answers(
     bot: Bot,
@@ -43,40 +71,30 @@ pub async fn answers(
                 handle_balance(bot, msg, &symbol, bot_deps.clone()).await?
             }
         }
-        Command::Prices => handle_prices(bot, msg).await?,
+        Command::Balances => handle_balances(bot, msg, bot_deps.clone()).await?,
+        Command::Price(symbol) => handle_price(bot, msg, &symbol, bot_deps.clone()).await?,
+        Command::Pools(args) => handle_pools(bot, msg, &args, bot_deps.clone()).await?,
+        Command::Prices => handle_prices(bot, msg, bot_deps.clone()).await?,
+        Command::Rates => handle_rates(bot, msg, bot_deps.clone()).await?,
+        Command::Send(instruction) => {
+            handle_send_command(bot, msg, instruction, bot_deps.clone()).await?
+        }
+        Command::SendMax(args) => {
+            handle_send_max_command(bot, msg, args, bot_deps.clone()).await?
+        }
+        Command::Pending => handle_pending_command(bot, msg, bot_deps.clone()).await?,
+        Command::ExplainTx(hash) => {
+            handle_explain_tx_command(bot, msg, hash, bot_deps.clone()).await?
+        }
         Command::LoginUser => handle_login_user(bot, msg).await?,
         Command::LoginGroup => handle_login_group(bot, msg, bot_deps.clone()).await?,
+        Command::RefreshGroup => handle_refresh_group(bot, msg, bot_deps.clone()).await?,
         Command::NewChat => handle_new_chat(bot, msg, bot_deps.clone()).await?,
-        Command::C(prompt) => {
-            // Check if chat commands are enabled for this group (skip check for private chats)
-            if !msg.chat.is_private() {
-                let group_id = msg.chat.id.to_string();
-                if !bot_deps.command_settings.is_chat_commands_enabled(group_id) {
-                    send_message(
-                        msg,
-                        bot,
-                        "❌ Chat commands (/c, /chat) are disabled in this group. Contact an administrator to enable them.".to_string(),
-                    )
-                    .await?;
-                    return Ok(());
-                }
-            }
-
-            let cmd_collector = bot_deps.cmd_collector.clone();
-
-            if prompt.trim().is_empty() && msg.photo().is_some() {
-                cmd_collector.add_command(msg, bot_deps.clone(), None).await;
-            } else if prompt.trim().is_empty() {
-                send_message(
-                    msg,
-                    bot,
-                    "Please include a message after /c, e.g. /c What is the weather today?"
-                        .to_string(),
-                )
-                .await?;
-            } else {
-                handle_chat(bot, msg, prompt, None, false, bot_deps).await?;
-            }
+        Command::Forget => handle_forget(bot, msg, bot_deps.clone()).await?,
+        Command::LastImage => handle_last_image(bot, msg, bot_deps.clone()).await?,
+        Command::C(prompt) => handle_c_command(bot, msg, prompt, None, bot_deps).await?,
+        Command::ModelAlias(args) => {
+            handle_model_alias_command(bot, msg, args, bot_deps.clone()).await?
         }
         Command::G(prompt) => {
             let cmd_collector = bot_deps.cmd_collector.clone();
@@ -133,7 +151,7 @@ pub async fn answers(
 
             if prompt.trim().is_empty() && multimedia_message.photo().is_some() {
                 cmd_collector
-                    .add_command(multimedia_message, bot_deps.clone(), Some(group_id))
+                    .add_command(multimedia_message, bot_deps.clone(), Some(group_id), None)
                     .await;
             } else {
                 handle_chat(
@@ -151,6 +169,21 @@ pub async fn answers(
         Command::Report => {
             handle_mod(bot, msg, bot_deps.clone()).await?;
         }
+        Command::TestMod(text) => {
+            handle_test_mod(bot, msg, text, bot_deps.clone()).await?;
+        }
+        Command::CommandPrefix(args) => {
+            handle_command_prefix_command(bot, msg, args, bot_deps.clone()).await?;
+        }
+        Command::SnoozeSentinel(args) => {
+            handle_snooze_sentinel_command(bot, msg, args, bot_deps.clone()).await?;
+        }
+        Command::Flagged => {
+            handle_flagged_command(bot, msg, bot_deps.clone()).await?;
+        }
+        Command::AiBudget(args) => {
+            handle_ai_budget_command(bot, msg, args, bot_deps.clone()).await?;
+        }
         Command::Rules => {
             handle_rules(bot, msg, bot_deps.clone()).await?;
         }
@@ -180,6 +213,10 @@ pub async fn answers(
                         "💳 Payment Settings",
                         "open_payment_settings",
                     )],
+                    vec![InlineKeyboardButton::callback(
+                        "🔔 Notifications",
+                        "open_notifications",
+                    )],
                     vec![InlineKeyboardButton::callback(
                         "📁 Document Library",
                         "open_document_library",
@@ -192,6 +229,10 @@ pub async fn answers(
                         "🧾 Summarization Settings",
                         "open_summarization_settings",
                     )],
+                    vec![InlineKeyboardButton::callback(
+                        "🖼️ Max Images per Request",
+                        "open_user_max_images",
+                    )],
                     vec![InlineKeyboardButton::callback(
                         "↩️ Close",
                         "user_settings_close",
@@ -206,6 +247,14 @@ pub async fn answers(
                 .await?;
             }
         }
+        Command::MyVotes => handle_my_votes(bot, msg, bot_deps.clone()).await?,
+        Command::Stats => handle_stats_command(bot, msg, bot_deps.clone()).await?,
+        Command::Notifications => {
+            handle_notifications_command(bot, msg, bot_deps.clone()).await?
+        }
+        Command::NewKb(name) => handle_new_kb(bot, msg, &name, bot_deps.clone()).await?,
+        Command::ListKb => handle_list_kb(bot, msg, bot_deps.clone()).await?,
+        Command::UseKb(name) => handle_use_kb(bot, msg, &name, bot_deps.clone()).await?,
         Command::GroupWalletAddress => {
             handle_group_wallet_address(bot, msg, bot_deps.clone()).await?;
         }
@@ -216,9 +265,14 @@ pub async fn answers(
                 handle_group_balance(bot, msg, bot_deps.clone(), &symbol).await?
             }
         }
+        Command::GroupBalances => handle_group_balances(bot, msg, bot_deps.clone()).await?,
         Command::Announcement(text) => {
             handle_announcement(bot, msg, text, bot_deps.clone()).await?;
         }
+        Command::Announcers(args) => {
+            handle_announcers_command(bot, msg, args, bot_deps.clone()).await?;
+        }
+        Command::RefreshTokens => handle_refresh_tokens(bot, msg, bot_deps.clone()).await?,
         Command::Groupsettings => {
             if msg.chat.is_private() {
                 send_message(
@@ -280,6 +334,10 @@ pub async fn answers(
                             "📋 Summarization Settings",
                             "open_group_summarization_settings",
                         )],
+                        vec![InlineKeyboardButton::callback(
+                            "🧠 History Settings",
+                            "open_history_settings",
+                        )],
                         vec![InlineKeyboardButton::callback(
                             "🔄 Migrate Group ID",
                             "open_migrate_group_id",
@@ -311,6 +369,96 @@ pub async fn answers(
         Command::ListScheduledPayments => {
             handle_listscheduledpayments_command(bot, msg, bot_deps.clone()).await?;
         }
+        Command::ExportScheduledPayments => {
+            handle_export_scheduled_payments_command(bot, msg, bot_deps.clone()).await?;
+        }
+        Command::Debug => {
+            handle_debug(bot, msg, bot_deps.clone()).await?;
+        }
+        Command::Members => {
+            handle_members(bot, msg, bot_deps.clone()).await?;
+        }
+        Command::Uptime => handle_uptime(bot, msg).await?,
+        Command::Version => handle_version(bot, msg).await?,
+        Command::Config => handle_config(bot, msg, bot_deps).await?,
+        Command::Block(args) => handle_block_command(bot, msg, args, bot_deps.clone()).await?,
+        Command::Unblock(args) => {
+            handle_unblock_command(bot, msg, args, bot_deps.clone()).await?
+        }
+        Command::Blocklist(args) => {
+            handle_blocklist_command(bot, msg, args, bot_deps.clone()).await?
+        }
     };
     Ok(())
 }
+
+/// /c, optionally with a model picked for this prompt only
+async fn handle_c_command(
+    bot: Bot,
+    msg: Message,
+    prompt: String,
+    model: Option<ChatModel>,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    // Check if chat commands are enabled for this group (skip check for private chats)
+    if !msg.chat.is_private() {
+        let group_id = msg.chat.id.to_string();
+        if !bot_deps.command_settings.is_chat_commands_enabled(group_id) {
+            send_message(
+                msg,
+                bot,
+                "❌ Chat commands (/c, /chat) are disabled in this group. Contact an administrator to enable them.".to_string(),
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let cmd_collector = bot_deps.cmd_collector.clone();
+
+    if prompt.trim().is_empty() && msg.photo().is_some() {
+        cmd_collector.add_command(msg, bot_deps.clone(), None, model).await;
+    } else if prompt.trim().is_empty() {
+        send_message(
+            msg,
+            bot,
+            "Please include a message after /c, e.g. /c What is the weather today?"
+                .to_string(),
+        )
+        .await?;
+    } else if !hold_repeated_prompt(&bot, &msg, &prompt, model.clone(), &bot_deps).await? {
+        handle_chat_with_model(bot, msg, prompt, None, false, model, bot_deps).await?;
+    }
+    Ok(())
+}
+
+/// `/c:alias prompt` — resolve the alias against the user's and global aliases, then run /c
+pub async fn handle_c_with_alias(bot: Bot, msg: Message, bot_deps: BotDependencies) -> Result<()> {
+    let text = msg.text().or_else(|| msg.caption()).unwrap_or_default();
+    let Some((alias, prompt)) = parse_model_prefix(text) else {
+        return Ok(());
+    };
+
+    let user_aliases = msg
+        .from
+        .as_ref()
+        .and_then(|u| u.username.as_ref())
+        .map(|username| bot_deps.user_model_prefs.get_preferences(username).model_aliases)
+        .unwrap_or_default();
+
+    match resolve_model(&alias, &user_aliases, global_aliases()) {
+        Some(model) => handle_c_command(bot, msg, prompt, Some(model), bot_deps).await,
+        None => {
+            send_message(
+                msg,
+                bot,
+                format!(
+                    "❌ Unknown model or alias '{}'. Send /modelalias to see the available aliases.",
+                    alias
+                ),
+            )
+            .await?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! /balances and /groupbalances: every non-zero token balance of an account in one message.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use anyhow::Result;
+use futures::{StreamExt, stream};
+use teloxide::{
+    prelude::*,
+    types::{ChatAction, Message},
+    utils::html,
+};
+
+use crate::{
+    bot::handler::send_long_message, dependencies::BotDependencies, panora::dto::Token,
+    utils::send_message,
+};
+
+/// Holdings listed before the rest are summarized as hidden
+const MAX_LISTED_HOLDINGS: usize = 30;
+/// Parallel node lookups while walking the token list
+const BALANCE_LOOKUP_CONCURRENCY: usize = 8;
+
+struct Holding {
+    symbol: String,
+    amount: f64,
+    usd_value: Option<f64>,
+}
+
+/// Coin type or FA address used for the balance lookup, same as /balance
+fn token_type(token: &Token) -> String {
+    token
+        .token_address
+        .clone()
+        .unwrap_or_else(|| token.fa_address.clone())
+}
+
+/// Highest USD value first; tokens without a price go last, largest amount first
+fn sort_holdings(holdings: &mut [Holding]) {
+    holdings.sort_by(|a, b| match (a.usd_value, b.usd_value) {
+        (Some(a_usd), Some(b_usd)) => b_usd.partial_cmp(&a_usd).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => b.amount.partial_cmp(&a.amount).unwrap_or(Ordering::Equal),
+    });
+}
+
+/// `single_command` is the per-token command suggested when holdings are hidden
+fn format_holdings(title: &str, single_command: &str, holdings: &[Holding]) -> String {
+    if holdings.is_empty() {
+        return format!("💰 <b>{}</b>\n\nNo token balances found.", title);
+    }
+
+    let shown = &holdings[..holdings.len().min(MAX_LISTED_HOLDINGS)];
+    let lines = shown
+        .iter()
+        .map(|h| match h.usd_value {
+            Some(usd) => format!(
+                "• <b>{}</b>: {:.4} (${:.2})",
+                html::escape(&h.symbol),
+                h.amount,
+                usd
+            ),
+            None => format!("• <b>{}</b>: {:.4}", html::escape(&h.symbol), h.amount),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let total_usd: f64 = holdings.iter().filter_map(|h| h.usd_value).sum();
+    let mut text = format!(
+        "💰 <b>{}</b>\n\n{}\n\n💵 Total: <b>${:.2}</b>",
+        title, lines, total_usd
+    );
+
+    let hidden = holdings.len() - shown.len();
+    if hidden > 0 {
+        text.push_str(&format!(
+            "\n\n<i>{} smaller holding(s) hidden. Use /{} &lt;symbol&gt; for a specific token.</i>",
+            hidden, single_command
+        ));
+    }
+    text
+}
+
+/// Non-zero balances of `address` across the Panora token list
+async fn fetch_holdings(address: &str, bot_deps: &BotDependencies) -> Result<Vec<Holding>> {
+    let tokens = bot_deps.panora.get_panora_token_list().await?;
+
+    // The list can carry the same asset more than once (e.g. bridged aliases)
+    let mut seen = HashSet::new();
+    let tokens: Vec<Token> = tokens
+        .into_iter()
+        .filter(|token| seen.insert(token_type(token)))
+        .collect();
+
+    let mut holdings: Vec<Holding> = stream::iter(tokens)
+        .map(|token| async move {
+            let raw = bot_deps
+                .panora
+                .aptos
+                .get_account_balance(address, &token_type(&token))
+                .await
+                .ok()
+                .filter(|raw| *raw > 0)?;
+            let amount = raw as f64 / 10_f64.powi(token.decimals as i32);
+            let usd_value = token
+                .usd_price
+                .as_deref()
+                .and_then(|price| price.parse::<f64>().ok())
+                .map(|price| price * amount);
+            Some(Holding {
+                symbol: token.symbol,
+                amount,
+                usd_value,
+            })
+        })
+        .buffer_unordered(BALANCE_LOOKUP_CONCURRENCY)
+        .filter_map(|holding| async move { holding })
+        .collect()
+        .await;
+
+    sort_holdings(&mut holdings);
+    Ok(holdings)
+}
+
+async fn send_balances(
+    bot: Bot,
+    msg: Message,
+    title: &str,
+    single_command: &str,
+    address: &str,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let _ = bot.send_chat_action(msg.chat.id, ChatAction::Typing).await;
+
+    let holdings = match fetch_holdings(address, &bot_deps).await {
+        Ok(holdings) => holdings,
+        Err(e) => {
+            log::error!("❌ Error getting balances: {}", e);
+            send_message(msg, bot, "❌ Error getting balances".to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let link_previews = !bot_deps
+        .command_settings
+        .link_previews_disabled(msg.chat.id.to_string());
+    send_long_message(
+        msg,
+        &bot,
+        &format_holdings(title, single_command, &holdings),
+        link_previews,
+    )
+    .await
+}
+
+/// /balances — all of the user's non-zero token balances
+pub async fn handle_balances(bot: Bot, msg: Message, bot_deps: BotDependencies) -> Result<()> {
+    let Some(username) = msg.from.as_ref().and_then(|u| u.username.clone()) else {
+        send_message(msg, bot, "❌ Username not found".to_string()).await?;
+        return Ok(());
+    };
+
+    let Some(credentials) = bot_deps.auth.get_credentials(&username) else {
+        send_message(msg, bot, "❌ User not found".to_string()).await?;
+        return Ok(());
+    };
+
+    send_balances(
+        bot,
+        msg,
+        "Your Balances",
+        "balance",
+        &credentials.resource_account_address,
+        bot_deps,
+    )
+    .await
+}
+
+/// /groupbalances — all of the group's non-zero token balances
+pub async fn handle_group_balances(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    if !msg.chat.is_group() && !msg.chat.is_supergroup() {
+        send_message(
+            msg,
+            bot,
+            "❌ This command can only be used in a group".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(credentials) = bot_deps.group.get_credentials(msg.chat.id) else {
+        send_message(msg, bot, "❌ Group not found".to_string()).await?;
+        return Ok(());
+    };
+
+    send_balances(
+        bot,
+        msg,
+        "Group Balances",
+        "groupbalance",
+        &credentials.resource_account_address,
+        bot_deps,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(symbol: &str, amount: f64, usd_value: Option<f64>) -> Holding {
+        Holding {
+            symbol: symbol.to_string(),
+            amount,
+            usd_value,
+        }
+    }
+
+    #[test]
+    fn test_sort_and_cap_holdings() {
+        let mut holdings = vec![
+            holding("MEME", 1_000_000.0, None),
+            holding("APT", 2.0, Some(10.0)),
+            holding("USDC", 50.0, Some(50.0)),
+        ];
+        sort_holdings(&mut holdings);
+        let order: Vec<_> = holdings.iter().map(|h| h.symbol.as_str()).collect();
+        assert_eq!(order, ["USDC", "APT", "MEME"]);
+
+        let many: Vec<_> = (0..MAX_LISTED_HOLDINGS + 3)
+            .map(|i| holding(&format!("T{}", i), 1.0, Some(1.0)))
+            .collect();
+        let text = format_holdings("Your Balances", "balance", &many);
+        assert!(text.contains("3 smaller holding(s) hidden"));
+        assert!(!text.contains(&format!("T{}", MAX_LISTED_HOLDINGS)));
+    }
+}
@@ -0,0 +1,192 @@
+//! Read-only /uptime, /version and /config diagnostics for coordinating deploys and spotting
+//! AI latency problems.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use teloxide::{
+    prelude::*,
+    types::Message,
+    utils::html,
+};
+
+use crate::{
+    ai::{
+        metrics,
+        openai_client::get_openai_base_url,
+        prompt_safety,
+        tools::{active_tool_names, disabled_tools, tool_concurrency},
+    },
+    announcement::announcement::AnnouncerAuth,
+    dependencies::BotDependencies,
+    profile,
+    utils::{send_html_message, send_message},
+};
+
+static STARTED_AT: OnceLock<Instant> = OnceLock::new();
+
+/// Commit baked in at build time (`GIT_COMMIT=$(git rev-parse --short HEAD) cargo build`)
+const BUILD_COMMIT: Option<&str> = option_env!("GIT_COMMIT");
+
+/// Non-secret settings read from the environment at startup, shown by /config.
+/// API keys, credentials and tokens never go in here.
+#[derive(Debug, Clone)]
+pub struct DeploymentConfig {
+    pub network: String,
+    pub contract_address: String,
+    pub default_symbol: String,
+    pub min_deposit: f64,
+}
+
+/// Record process start; called once from main before the dispatcher runs
+pub fn mark_started() {
+    let _ = STARTED_AT.set(Instant::now());
+}
+
+fn uptime() -> Duration {
+    STARTED_AT.get().map(Instant::elapsed).unwrap_or_default()
+}
+
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, (secs % 86_400) / 3600, (secs % 3600) / 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m {}s", minutes, secs % 60)
+    }
+}
+
+pub async fn handle_uptime(bot: Bot, msg: Message) -> Result<()> {
+    send_message(msg, bot, format!("⏱️ Up for {}", format_uptime(uptime()))).await?;
+    Ok(())
+}
+
+fn is_authorized_operator(msg: &Message) -> bool {
+    let username = msg.from.as_ref().and_then(|u| u.username.clone());
+    match (&username, AnnouncerAuth::load_default()) {
+        (Some(username), Ok(auth)) => auth.is_authorized(username),
+        (_, Err(e)) => {
+            log::error!("Failed to load authorized operators: {}", e);
+            false
+        }
+        _ => false,
+    }
+}
+
+/// /version — build version and commit, for authorized operators only
+pub async fn handle_version(bot: Bot, msg: Message) -> Result<()> {
+    if !is_authorized_operator(&msg) {
+        send_message(msg, bot, "❌ You are not authorized to view build info.".to_string())
+            .await?;
+        return Ok(());
+    }
+
+    send_message(
+        msg,
+        bot,
+        format!(
+            "🏷️ quark_bot v{} ({})\n⏱️ {}",
+            env!("CARGO_PKG_VERSION"),
+            BUILD_COMMIT.unwrap_or("unknown commit"),
+            metrics::format_snapshot(&metrics::snapshot())
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// An env-driven setting as configured, or its default when unset
+fn env_or(var: &str, default: &str) -> String {
+    std::env::var(var)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| format!("{} (default)", default))
+}
+
+fn format_config(config: &DeploymentConfig) -> String {
+    let mut disabled: Vec<&String> = disabled_tools().iter().collect();
+    disabled.sort();
+    let disabled = if disabled.is_empty() {
+        "none".to_string()
+    } else {
+        disabled.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+    };
+
+    format!(
+        "⚙️ <b>Configuration</b>\n\n\
+         🌐 Network: <code>{}</code>\n\
+         📜 Contract: <code>{}</code>\n\
+         💳 Default token: <code>{}</code>\n\
+         💰 Min deposit: <code>{}</code>\n\
+         🏷️ Profile: {}\n\
+         🤖 OpenAI endpoint: {}\n\n\
+         <b>Features</b>\n\
+         🧰 Tools: {}\n\
+         🚫 Disabled tools: {}\n\
+         ⚡ Tool concurrency: {}\n\
+         🛡️ Prompt safety: {:?}\n\
+         🧾 Reconciliation: <code>{}</code>\n\
+         🔔 Low balance alerts: <code>{}</code>\n\
+         🧹 Store maintenance: <code>{}</code>",
+        html::escape(&config.network),
+        html::escape(&config.contract_address),
+        html::escape(&config.default_symbol),
+        config.min_deposit,
+        profile::current().summary(),
+        // Only whether it is overridden; a custom URL may embed credentials
+        if get_openai_base_url().is_some() { "custom" } else { "default" },
+        html::escape(&active_tool_names().join(", ")),
+        html::escape(&disabled),
+        tool_concurrency(),
+        prompt_safety::mode(),
+        html::escape(&env_or("RECONCILIATION_CRON", "0 0 * * * *")),
+        html::escape(&env_or("LOW_BALANCE_ALERT_CRON", "0 30 * * * *")),
+        html::escape(&env_or("MAINTENANCE_CRON", "0 15 4 * * *")),
+    )
+}
+
+/// /config — effective non-secret configuration, for authorized operators only
+pub async fn handle_config(bot: Bot, msg: Message, bot_deps: BotDependencies) -> Result<()> {
+    if !is_authorized_operator(&msg) {
+        send_message(msg, bot, "❌ You are not authorized to view the configuration.".to_string())
+            .await?;
+        return Ok(());
+    }
+
+    let text = format_config(&bot_deps.deployment_config);
+    send_html_message(msg, bot, text).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_secs(42)), "0m 42s");
+        assert_eq!(format_uptime(Duration::from_secs(2 * 3600 + 5 * 60)), "2h 5m");
+        assert_eq!(
+            format_uptime(Duration::from_secs(3 * 86_400 + 4 * 3600 + 60)),
+            "3d 4h 1m"
+        );
+    }
+
+    #[test]
+    fn test_format_config_escapes_values() {
+        let config = DeploymentConfig {
+            network: "testnet".to_string(),
+            contract_address: "0x1<2>".to_string(),
+            default_symbol: "APT".to_string(),
+            min_deposit: 10.0,
+        };
+        let text = format_config(&config);
+        assert!(text.contains("<code>testnet</code>"));
+        assert!(text.contains("0x1&lt;2&gt;"));
+    }
+}
@@ -1,22 +1,30 @@
 //! Command handlers for quark_bot Telegram bot.
 use crate::{
     ai::{
-        moderation::handler::handle_message_moderation, sentinel::handler::handle_message_sentinel,
+        disclaimer, moderation::handler::handle_message_moderation,
+        sentinel::handler::handle_message_sentinel, streaming::StreamingReply,
     },
+    announcement::announcement::AnnouncerAuth,
     assets::handler::{handle_file_upload, handle_group_file_upload},
     bot::hooks::{fund_account_hook, pay_users_hook, withdraw_funds_hook},
+    context_note::handler::handle_context_note_input,
     credentials::dto::CredentialsPayload,
     dao::handler::handle_message_dao,
     dependencies::BotDependencies,
+    dm_onboarding::handler::handle_plain_dm,
     filters::handler::{handle_message_filters, process_message_for_filters},
     group::dto::GroupCredentials,
+    group_budget::handler::group_ai_allowed,
+    panora::dto::Token,
+    payment::dto::PaymentPrefs,
     scheduled_payments::handler::handle_message_scheduled_payments,
     scheduled_prompts::handler::handle_message_scheduled_prompts,
+    spam_guard::handler::enforce_spam_limit,
     sponsor::handler::handle_sponsor_message,
-    user_model_preferences::dto::ModelPreferences,
+    user_model_preferences::dto::{ChatModel, ModelPreferences},
     utils::{
         self, KeyboardMarkupType, create_purchase_request, send_html_message,
-        send_markdown_message_with_keyboard, send_message,
+        send_html_message_with_previews, send_markdown_message_with_keyboard, send_message,
     },
     welcome::handler::handle_welcome_message,
 };
@@ -25,21 +33,30 @@ use aptos_rust_sdk_types::api_types::view::ViewRequest;
 use serde_json::value;
 
 use crate::{
-    ai::moderation::ModerationOverrides,
+    ai::moderation::{
+        ModerationAction, ModerationOverrides,
+        appeal::with_appeal_button,
+        enforcement::enforce_moderation_action,
+        flagged_log::{FlagSource, FlaggedEvent, log_flagged_event},
+    },
     user_model_preferences::handler::initialize_user_preferences,
 };
 
 use open_ai_rust_responses_by_sshift::Model;
-use quark_core::helpers::{bot_commands::Command, dto::CreateGroupRequest};
+use quark_core::helpers::{
+    bot_commands::{Command, CommandContext},
+    dto::CreateGroupRequest,
+};
 use regex;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::time::Duration;
 use teloxide::types::{
-    ChatAction, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, WebAppInfo,
+    ChatAction, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, ThreadId, WebAppInfo,
 };
-use teloxide::types::{KeyboardMarkup, ParseMode};
+use teloxide::sugar::request::RequestReplyExt;
+use teloxide::types::{KeyboardMarkup, ParseMode, ReactionType};
 use teloxide::{net::Download, utils::command::BotCommands};
 use teloxide::{
     prelude::*,
@@ -52,7 +69,27 @@ const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
 
 /// Split a Telegram-HTML message into chunks without cutting inside tags/entities.
 fn split_message(text: &str) -> Vec<String> {
-    if text.len() <= TELEGRAM_MESSAGE_LIMIT {
+    split_message_with_limit(text, TELEGRAM_MESSAGE_LIMIT)
+}
+
+/// Keep only the first `max_len` characters of a reply, cut at a tag-safe boundary.
+///
+/// Returns the text unchanged when it already fits.
+fn truncate_reply(text: &str, max_len: usize) -> String {
+    let html_text = utils::markdown_to_html(text);
+    // The splitter works in bytes, so cut at the byte offset of the `max_len`-th char
+    let Some((byte_limit, _)) = html_text.char_indices().nth(max_len) else {
+        return text.to_string();
+    };
+    let first = split_message_with_limit(&html_text, byte_limit)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    format!("{}\n\n<i>…[truncated]</i>", first)
+}
+
+fn split_message_with_limit(text: &str, limit: usize) -> Vec<String> {
+    if text.len() <= limit {
         return vec![text.to_string()];
     }
 
@@ -165,7 +202,7 @@ fn split_message(text: &str) -> Vec<String> {
             }
         }
 
-        if buf.len() >= TELEGRAM_MESSAGE_LIMIT {
+        if buf.len() >= limit {
             if let Some(idx) = last_safe_break {
                 let remainder = buf.split_off(idx);
                 let chunk = buf.trim().to_string();
@@ -237,7 +274,21 @@ fn split_off_pre_blocks(text: &str) -> (String, Vec<String>) {
 }
 
 /// Send a long <pre> block safely by chunking and wrapping each chunk in <pre> tags
-async fn send_pre_block(bot: &Bot, chat_id: ChatId, title: &str, content: &str) -> AnyResult<()> {
+async fn send_pre_block(
+    bot: &Bot,
+    chat_id: ChatId,
+    thread_id: Option<ThreadId>,
+    title: &str,
+    content: &str,
+) -> AnyResult<()> {
+    // Forum groups need the topic on every chunk, or they land in General
+    let send = |text: String| {
+        let mut request = bot.send_message(chat_id, text);
+        if let Some(thread_id) = thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        request
+    };
     // Escape HTML special chars inside the <pre> block
     let escaped = teloxide::utils::html::escape(content);
     let prefix = format!("{}\n<pre>", title);
@@ -248,11 +299,7 @@ async fn send_pre_block(bot: &Bot, chat_id: ChatId, title: &str, content: &str)
     for ch in escaped.chars() {
         if current.chars().count() + 1 > max_payload {
             let msg = format!("{}{}{}", prefix, current, suffix);
-            match bot
-                .send_message(chat_id, msg)
-                .parse_mode(ParseMode::Html)
-                .await
-            {
+            match send(msg).parse_mode(ParseMode::Html).await {
                 Ok(_) => {}
                 Err(e) => {
                     let err_text = e.to_string();
@@ -260,12 +307,11 @@ async fn send_pre_block(bot: &Bot, chat_id: ChatId, title: &str, content: &str)
                     if err_text.contains("can't parse entities")
                         || err_text.contains("Unsupported start tag")
                     {
-                        let _ = bot
-                            .send_message(
-                                chat_id,
-                                "Sorry — I made an error in my output. Please try again or start a /newchat.",
-                            )
-                            .await;
+                        let _ = send(
+                            "Sorry — I made an error in my output. Please try again or start a /newchat."
+                                .to_string(),
+                        )
+                        .await;
                         return Ok(());
                     }
                     return Err(e.into());
@@ -277,11 +323,7 @@ async fn send_pre_block(bot: &Bot, chat_id: ChatId, title: &str, content: &str)
     }
     if !current.is_empty() {
         let msg = format!("{}{}{}", prefix, current, suffix);
-        match bot
-            .send_message(chat_id, msg)
-            .parse_mode(ParseMode::Html)
-            .await
-        {
+        match send(msg).parse_mode(ParseMode::Html).await {
             Ok(_) => {}
             Err(e) => {
                 let err_text = e.to_string();
@@ -289,12 +331,11 @@ async fn send_pre_block(bot: &Bot, chat_id: ChatId, title: &str, content: &str)
                 if err_text.contains("can't parse entities")
                     || err_text.contains("Unsupported start tag")
                 {
-                    let _ = bot
-                        .send_message(
-                            chat_id,
-                            "Sorry — I made an error in my output. Please try again or start a /newchat.",
-                        )
-                        .await;
+                    let _ = send(
+                        "Sorry — I made an error in my output. Please try again or start a /newchat."
+                            .to_string(),
+                    )
+                    .await;
                     return Ok(());
                 }
                 return Err(e.into());
@@ -305,12 +346,27 @@ async fn send_pre_block(bot: &Bot, chat_id: ChatId, title: &str, content: &str)
 }
 
 /// Send a potentially long message, splitting it into multiple messages if necessary
-async fn send_long_message(msg: Message, bot: &Bot, text: &str) -> AnyResult<()> {
+pub(crate) async fn send_long_message(
+    msg: Message,
+    bot: &Bot,
+    text: &str,
+    link_previews: bool,
+) -> AnyResult<()> {
     // Convert markdown (including ``` code fences) to Telegram-compatible HTML
     let html_text = utils::markdown_to_html(text);
     // Normalize image anchor to point to the public GCS URL when present
     let html_text = utils::normalize_image_url_anchor(&html_text);
-    let chunks = split_message(&html_text);
+    send_long_html(msg, bot, &html_text, link_previews).await
+}
+
+/// Send text that is already Telegram HTML, splitting it into multiple messages if necessary
+pub(crate) async fn send_long_html(
+    msg: Message,
+    bot: &Bot,
+    html_text: &str,
+    link_previews: bool,
+) -> AnyResult<()> {
+    let chunks = split_message(html_text);
 
     for (i, chunk) in chunks.iter().enumerate() {
         if i > 0 {
@@ -318,7 +374,9 @@ async fn send_long_message(msg: Message, bot: &Bot, text: &str) -> AnyResult<()>
             sleep(Duration::from_millis(100)).await;
         }
 
-        match send_html_message(msg.clone(), bot.clone(), chunk.to_string()).await {
+        match send_html_message_with_previews(msg.clone(), bot.clone(), chunk.to_string(), link_previews)
+            .await
+        {
             Ok(_) => {}
             Err(e) => {
                 let err_text = e.to_string();
@@ -444,8 +502,6 @@ pub async fn handle_login_group(
 
     let group_id_formatted = format!("{}-{}", msg.chat.id, account_seed);
 
-    let payload: GroupCredentials;
-
     if let Some(uid) = requester_id {
         let is_admin = admins.iter().any(|member| member.user.id == uid);
         if !is_admin {
@@ -468,64 +524,445 @@ pub async fn handle_login_group(
         return Ok(());
     }
 
-    let group_exists = bot_deps
+    // The guard is held through saving credentials so two admins logging in at once
+    // can't both create the group
+    let login = bot_deps
         .group
-        .group_exists(group_id, bot_deps.panora.clone())
+        .login(
+            group_id,
+            || {
+                bot_deps
+                    .group
+                    .group_exists(group_id, bot_deps.panora.clone())
+            },
+            || {
+                bot_deps.service.create_group(CreateGroupRequest {
+                    group_id: group_id_formatted.clone(),
+                })
+            },
+        )
         .await;
 
-    if !group_exists {
-        let group_result = bot_deps
-            .service
-            .create_group(CreateGroupRequest {
-                group_id: group_id_formatted.clone(),
-            })
-            .await;
-
-        if group_result.is_err() {
-            send_message(msg, bot, "❌ Unable to create group.".to_string()).await?;
+    let (payload, _login_guard) = match login {
+        Ok(login) => login,
+        Err(e) => {
+            send_message(msg, bot, format!("❌ {}.", e)).await?;
             return Ok(());
         }
+    };
+
+    let updated_credentials =
+        check_group_resource_account_address(&bot, payload, msg.clone(), &bot_deps).await;
+
+    if updated_credentials.is_err() {
+        send_message(msg, bot, "❌ Unable to save credentials.".to_string()).await?;
+        return Ok(());
+    }
+
+    send_html_message(msg, bot, format!("🔑 <b>Group Login Successful!</b>\n\n<i>You can now use the group's Nova account to interact with the bot.</i>\n\n💡 <i>Use /groupwalletaddress to get the group's wallet address and /groupbalance to get the group's balance of a token.</i>").to_string()).await?;
+    Ok(())
+}
+
+/// /refreshgroup — re-issue the group JWT and re-check the resource account without the
+/// full /logingroup flow
+pub async fn handle_refresh_group(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    if msg.chat.is_private() {
+        send_message(
+            msg,
+            bot,
+            "❌ This command must be used in a group chat.".to_string(),
+        )
+        .await?;
+        return Ok(());
     }
 
-    let jwt = bot_deps.group.generate_new_jwt(group_id);
+    let Some(user) = msg.from.as_ref() else {
+        send_message(msg, bot, "❌ Unable to verify permissions.".to_string()).await?;
+        return Ok(());
+    };
+    if !utils::is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group administrators can use this command.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let group_id = msg.chat.id;
+    // Same lock as /logingroup, so a refresh can't interleave with a login
+    let _login_guard = bot_deps.group.login_locks.lock(group_id).await;
 
-    if !jwt {
-        send_message(msg, bot, "❌ Unable to generate JWT.".to_string()).await?;
+    if !bot_deps
+        .group
+        .group_exists(group_id, bot_deps.panora.clone())
+        .await
+    {
+        send_message(
+            msg,
+            bot,
+            "❌ This group isn't registered yet. An admin should run /logingroup first.".to_string(),
+        )
+        .await?;
         return Ok(());
     }
 
-    let payload_response = bot_deps.group.get_credentials(group_id);
+    let previous_address = bot_deps
+        .group
+        .get_credentials(group_id)
+        .map(|c| c.resource_account_address)
+        .filter(|address| !address.is_empty());
 
-    if payload_response.is_none() {
+    if !bot_deps.group.generate_new_jwt(group_id) {
+        send_message(
+            msg,
+            bot,
+            "❌ Couldn't issue new group credentials. Please try again, or use /logingroup.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(credentials) = bot_deps.group.get_credentials(group_id) else {
         send_message(msg, bot, "❌ Unable to get credentials.".to_string()).await?;
         return Ok(());
+    };
+
+    // Reports its own failure to the chat
+    let Ok(credentials) =
+        check_group_resource_account_address(&bot, credentials, msg.clone(), &bot_deps).await
+    else {
+        return Ok(());
+    };
+
+    let address_note = match previous_address {
+        Some(previous) if previous != credentials.resource_account_address => format!(
+            "\n\n⚠️ The group wallet address changed from <code>{}</code>.",
+            previous
+        ),
+        _ => String::new(),
+    };
+
+    send_html_message(
+        msg,
+        bot,
+        format!(
+            "🔄 <b>Group credentials refreshed</b>\n\nGroup wallet: <code>{}</code>{}",
+            credentials.resource_account_address, address_note
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Command list filtered to the ones usable in this chat type, with a button for the full list
+pub async fn handle_help(bot: Bot, msg: Message) -> AnyResult<()> {
+    let is_private = msg.chat.is_private();
+
+    let (applicable, hidden): (Vec<_>, Vec<_>) = Command::bot_commands()
+        .into_iter()
+        .partition(|c| CommandContext::of(&c.command).applies_to(is_private));
+
+    let mut text = format!(
+        "These commands are supported {}:\n\n{}",
+        if is_private { "in DMs" } else { "in groups" },
+        applicable
+            .iter()
+            .map(|c| format!("/{} — {}", c.command.trim_start_matches('/'), c.description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+
+    if !hidden.is_empty() {
+        text.push_str(&format!(
+            "\n\nℹ️ {} more command(s) only work {}.",
+            hidden.len(),
+            if is_private { "in groups" } else { "in DMs with the bot" }
+        ));
     }
 
-    payload = payload_response.unwrap();
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "📋 Show all commands",
+        "help_show_all",
+    )]]);
 
-    let updated_credentials =
-        check_group_resource_account_address(&bot, payload, msg.clone(), &bot_deps).await;
+    let mut request = bot.send_message(msg.chat.id, text).reply_markup(keyboard);
+    if let Some(thread_id) = utils::topic_thread_id(&msg) {
+        request = request.message_thread_id(thread_id);
+    }
+    request.await?;
+    Ok(())
+}
 
-    if updated_credentials.is_err() {
-        send_message(msg, bot, "❌ Unable to save credentials.".to_string()).await?;
+pub async fn handle_debug(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    if msg.chat.is_private() {
+        send_message(
+            msg,
+            bot,
+            "❌ This command must be used in a group chat.".to_string(),
+        )
+        .await?;
         return Ok(());
     }
 
-    send_html_message(msg, bot, format!("🔑 <b>Group Login Successful!</b>\n\n<i>You can now use the group's Nova account to interact with the bot.</i>\n\n💡 <i>Use /groupwalletaddress to get the group's wallet address and /groupbalance to get the group's balance of a token.</i>").to_string()).await?;
+    let user = msg.from.as_ref();
+
+    if user.is_none() {
+        send_message(msg, bot, "❌ Unable to verify permissions.".to_string()).await?;
+        return Ok(());
+    }
+
+    let is_admin = utils::is_admin(&bot, msg.chat.id, user.unwrap().id).await;
+
+    if !is_admin {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group administrators can use this command.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let chat_id = msg.chat.id;
+    let group_id = chat_id.to_string();
+
+    let yes_no = |value: bool| if value { "✅ Yes" } else { "❌ No" };
+    let on_off = |value: bool| if value { "🟢 ON" } else { "🔴 OFF" };
+
+    let group_credentials = bot_deps.group.get_credentials(chat_id);
+    let (has_credentials, has_wallet, known_users) = match &group_credentials {
+        Some(credentials) => (
+            true,
+            !credentials.resource_account_address.is_empty(),
+            credentials.users.len(),
+        ),
+        None => (false, false, 0),
+    };
+
+    let sentinel_on = bot_deps.sentinel.get_sentinel(group_id.clone());
+
+    let payment_token = bot_deps
+        .payment
+        .get_payment_token(group_id.clone(), &bot_deps)
+        .await
+        .map(|prefs| format!("{} (group setting)", prefs.label))
+        .unwrap_or_else(|| {
+            format!(
+                "{} (default)",
+                bot_deps.default_payment_prefs.label.clone()
+            )
+        });
+
+    let scheduled_prompts = bot_deps
+        .scheduled_storage
+        .list_schedules_for_group(chat_id.0)
+        .len();
+    let scheduled_payments = bot_deps
+        .scheduled_payments
+        .list_schedules_for_group(chat_id.0)
+        .len();
+
+    let (allowed_rules, disallowed_rules) = bot_deps
+        .moderation
+        .get_moderation_settings(group_id.clone())
+        .map(|settings| (settings.allowed_items.len(), settings.disallowed_items.len()))
+        .unwrap_or((0, 0));
+
+    let bot_is_admin = match bot.get_me().await {
+        Ok(me) => match bot.get_chat_member(chat_id, me.id).await {
+            Ok(member) => yes_no(member.is_privileged()).to_string(),
+            Err(e) => {
+                log::warn!("Failed to get bot membership in chat {}: {}", chat_id, e);
+                "⚠️ Unknown".to_string()
+            }
+        },
+        Err(e) => {
+            log::warn!("Failed to get bot identity: {}", e);
+            "⚠️ Unknown".to_string()
+        }
+    };
+
+    let text = format!(
+        "🛠️ <b>Debug Info</b>\n\n🆔 <b>Chat ID:</b> <code>{}</code>\n\n🔑 <b>Group credentials:</b> {}\n💰 <b>Group wallet:</b> {}\n👥 <b>Known members:</b> {}\n🛡️ <b>Sentinel:</b> {}\n💳 <b>Payment token:</b> {}\n⏰ <b>Active scheduled prompts:</b> {}\n💸 <b>Active scheduled payments:</b> {}\n📋 <b>Moderation rules:</b> {} allowed / {} disallowed\n🤖 <b>Bot has admin rights:</b> {}",
+        chat_id.0,
+        yes_no(has_credentials),
+        yes_no(has_wallet),
+        known_users,
+        on_off(sentinel_on),
+        teloxide::utils::html::escape(&payment_token),
+        scheduled_prompts,
+        scheduled_payments,
+        allowed_rules,
+        disallowed_rules,
+        bot_is_admin
+    );
+
+    send_html_message(msg, bot, text).await?;
     Ok(())
 }
 
-pub async fn handle_help(bot: Bot, msg: Message) -> AnyResult<()> {
-    send_message(msg, bot, Command::descriptions().to_string()).await?;
+/// Cache key for /prices output shown in USD
+const USD_PRICES_CACHE_KEY: &str = "usd";
+
+pub async fn handle_prices(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    let pricing_info = match bot_deps.panora.cached_prices(USD_PRICES_CACHE_KEY) {
+        Some(text) => text,
+        None => {
+            let text = crate::ai::actions::execute_prices(&serde_json::json!({}), None).await;
+            bot_deps.panora.cache_prices(USD_PRICES_CACHE_KEY, &text);
+            text
+        }
+    };
+    send_html_message(msg, bot, pricing_info).await?;
     Ok(())
 }
 
-pub async fn handle_prices(bot: Bot, msg: Message) -> AnyResult<()> {
-    let pricing_info = crate::ai::actions::execute_prices(&serde_json::json!({})).await;
+/// Show model pricing converted into the chat's payment token (group token in groups,
+/// the user's own token in DMs)
+pub async fn handle_rates(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    let payment_id = if msg.chat.is_private() {
+        match &msg.from {
+            Some(user) => user.id.to_string(),
+            None => msg.chat.id.to_string(),
+        }
+    } else {
+        msg.chat.id.to_string()
+    };
+
+    let default_payment_prefs = bot_deps.default_payment_prefs.clone();
+    let coin = bot_deps
+        .payment
+        .get_payment_token(payment_id, &bot_deps)
+        .await
+        .unwrap_or(PaymentPrefs::from((
+            default_payment_prefs.label,
+            default_payment_prefs.currency,
+            default_payment_prefs.version,
+        )));
+
+    let cache_key = format!("token:{}", coin.label);
+    if let Some(text) = bot_deps.panora.cached_prices(&cache_key) {
+        send_html_message(msg, bot, text).await?;
+        return Ok(());
+    }
+
+    let usd_price = match bot_deps.panora.get_token_by_symbol(&coin.label).await {
+        Ok(token) => token.usd_price.and_then(|p| p.parse::<f64>().ok()),
+        Err(e) => {
+            log::warn!("Failed to fetch price for {}: {}", coin.label, e);
+            None
+        }
+    };
+
+    let pricing_info =
+        crate::ai::actions::execute_prices(&serde_json::json!({}), Some((coin.label, usd_price)))
+            .await;
+    // Keep retrying the price lookup instead of serving the USD fallback for the whole TTL
+    if usd_price.is_some() {
+        bot_deps.panora.cache_prices(&cache_key, &pricing_info);
+    }
     send_html_message(msg, bot, pricing_info).await?;
     Ok(())
 }
 
+/// Re-run the startup token list and AI fee updates so new Panora tokens show up without a restart
+pub async fn handle_refresh_tokens(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let username = msg.from.as_ref().and_then(|u| u.username.clone());
+
+    let authorized = match (&username, AnnouncerAuth::load_default()) {
+        (Some(username), Ok(auth)) => auth.is_authorized(username),
+        (_, Err(e)) => {
+            log::error!("Failed to load authorized operators: {}", e);
+            false
+        }
+        _ => false,
+    };
+
+    if !authorized {
+        send_message(
+            msg,
+            bot,
+            "❌ You are not authorized to refresh token data.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (before_full, before_non_bonding) = bot_deps.panora.token_list_counts().await;
+
+    let mut succeeded = 0;
+    let mut failures = Vec::new();
+
+    match bot_deps.panora.set_panora_token_list().await {
+        Ok(_) => succeeded += 1,
+        Err(e) => {
+            log::error!("Failed to refresh Panora token list: {}", e);
+            failures.push(format!("Token list: {}", e));
+        }
+    }
+
+    match bot_deps.panora.aptos.get_token_address().await {
+        Ok(token_address) => match bot_deps.panora.set_token_ai_fees(&token_address).await {
+            Ok(_) => succeeded += 1,
+            Err(e) => {
+                log::error!("Failed to refresh token AI fees: {}", e);
+                failures.push(format!("AI fees: {}", e));
+            }
+        },
+        Err(e) => {
+            log::error!("Failed to get token address: {}", e);
+            failures.push(format!("AI fees: {}", e));
+        }
+    }
+
+    let (after_full, after_non_bonding) = bot_deps.panora.token_list_counts().await;
+
+    log::info!(
+        "Token refresh by @{}: tokens {} -> {}, non-bonding {} -> {} ({} ok, {} failed)",
+        username.unwrap_or_default(),
+        before_full,
+        after_full,
+        before_non_bonding,
+        after_non_bonding,
+        succeeded,
+        failures.len()
+    );
+
+    let mut text = format!(
+        "🔄 <b>Token data refreshed</b>\n\n✅ Succeeded: <b>{}</b>\n❌ Failed: <b>{}</b>\n\n🪙 Tokens: {} → <b>{}</b>\n🪙 Non-bonding tokens: {} → <b>{}</b>",
+        succeeded,
+        failures.len(),
+        before_full,
+        after_full,
+        before_non_bonding,
+        after_non_bonding
+    );
+
+    if !failures.is_empty() {
+        text.push_str("\n\n<b>Errors:</b>\n");
+        text.push_str(
+            &failures
+                .iter()
+                .map(|f| format!("• {}", teloxide::utils::html::escape(f)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    send_html_message(msg, bot, text).await?;
+    Ok(())
+}
+
 pub async fn handle_chat(
     bot: Bot,
     msg: Message,
@@ -533,18 +970,30 @@ pub async fn handle_chat(
     group_id: Option<String>,
     is_sponsor: bool,
     bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    handle_chat_with_model(bot, msg, prompt, group_id, is_sponsor, None, bot_deps).await
+}
+
+/// Same as `handle_chat`, optionally overriding the user's model for this one prompt (/c:alias)
+pub async fn handle_chat_with_model(
+    bot: Bot,
+    msg: Message,
+    prompt: String,
+    group_id: Option<String>,
+    is_sponsor: bool,
+    model_override: Option<ChatModel>,
+    bot_deps: BotDependencies,
 ) -> AnyResult<()> {
     // Store group_id for later use to avoid move issues
     let group_id_for_hook = group_id.clone();
 
     // --- Start Typing Indicator Immediately ---
     let bot_clone = bot.clone();
-    let profile = env::var("PROFILE").unwrap_or("prod".to_string());
     let typing_indicator_handle = tokio::spawn(async move {
         loop {
             let typing = bot_clone.send_chat_action(msg.chat.id, ChatAction::Typing);
 
-            let type_result = if let Some(thread_id) = msg.thread_id {
+            let type_result = if let Some(thread_id) = utils::topic_thread_id(&msg) {
                 typing.message_thread_id(thread_id).await
             } else {
                 typing.await
@@ -570,6 +1019,7 @@ pub async fn handle_chat(
     }
 
     let user_id = user.unwrap().id.to_string();
+    let user_id_i64 = user.unwrap().id.0 as i64;
     let username = user.unwrap().username.as_ref();
 
     if username.is_none() {
@@ -589,6 +1039,47 @@ pub async fn handle_chat(
 
     let group_credentials = bot_deps.group.get_credentials(msg.chat.id);
 
+    // Opt-in safety gate: refuse disallowed prompts before downloading, generating or billing
+    let safety_opted_in = bot_deps
+        .command_settings
+        .get_command_settings(msg.chat.id.to_string())
+        .prompt_safety_check;
+    if bot_deps
+        .prompt_safety
+        .should_check(!msg.chat.is_private(), safety_opted_in)
+    {
+        match bot_deps.prompt_safety.check(&prompt).await {
+            Ok(categories) if !categories.is_empty() => {
+                typing_indicator_handle.abort();
+                log::info!(
+                    "Prompt from user {} in chat {} refused by safety check: {}",
+                    user_id,
+                    msg.chat.id,
+                    categories.join(", ")
+                );
+                send_message(
+                    msg,
+                    bot,
+                    format!(
+                        "🚫 I can't help with this request because it appears to involve {}. Please rephrase it.",
+                        categories.join(", ")
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+            Ok(_) => {}
+            // Fail open: an outage of the moderation endpoint shouldn't block every prompt
+            Err(e) => log::warn!("Prompt safety check failed, continuing: {}", e),
+        }
+    }
+
+    // Groups over their monthly AI budget don't run /g until it resets or is raised
+    if group_id.is_some() && !group_ai_allowed(&bot, &msg, &bot_deps, true).await? {
+        typing_indicator_handle.abort();
+        return Ok(());
+    }
+
     // Load user's chat model preferences
     let preferences = if group_id.is_some() {
         ModelPreferences::default()
@@ -596,7 +1087,9 @@ pub async fn handle_chat(
         bot_deps.user_model_prefs.get_preferences(username)
     };
 
-    let chat_model = preferences.chat_model.to_openai_model();
+    let chat_model = model_override
+        .unwrap_or(preferences.chat_model.clone())
+        .to_openai_model();
 
     let _temperature: Option<f32> = None;
 
@@ -731,6 +1224,28 @@ pub async fn handle_chat(
     let mut all_image_urls = user_uploaded_image_urls;
     all_image_urls.extend(replied_message_image_urls);
 
+    let max_images = bot_deps
+        .command_settings
+        .max_images_per_request(msg.chat.id.to_string());
+    if all_image_urls.len() > max_images {
+        log::info!(
+            "Image cap applied in chat {}: {} images, using first {}",
+            msg.chat.id,
+            all_image_urls.len(),
+            max_images
+        );
+        all_image_urls.truncate(max_images);
+        send_message(
+            msg.clone(),
+            bot.clone(),
+            format!(
+                "⚠️ Only the first {} image(s) will be used for this request.",
+                max_images
+            ),
+        )
+        .await?;
+    }
+
     // Prepare the final prompt with context if available
     let final_prompt = if let Some(context) = replied_message_context {
         format!("{}\n\nUser asks: {}", context, prompt)
@@ -738,6 +1253,11 @@ pub async fn handle_chat(
         prompt
     };
 
+    // Streaming preview for /c, when the user has it on (groups use default preferences)
+    let mut streaming_reply = preferences
+        .streaming
+        .then(|| StreamingReply::new(bot.clone(), msg.clone()));
+
     // Asynchronously generate the response
     let response_result = bot_deps
         .ai
@@ -752,14 +1272,62 @@ pub async fn handle_chat(
             None,
             bot_deps.clone(),
             group_id.clone(),
+            streaming_reply.as_mut(),
         )
         .await;
 
     typing_indicator_handle.abort();
 
     match response_result {
-        Ok(ai_response) => {
+        Ok(mut ai_response) => {
             let (web_search, file_search, image_gen, _) = ai_response.get_tool_usage_counts();
+            let link_previews = !bot_deps
+                .command_settings
+                .link_previews_disabled(msg.chat.id.to_string());
+
+            if let Err(e) = bot_deps.usage_stats.record(
+                user_id_i64,
+                ai_response.total_tokens,
+                image_gen,
+                web_search,
+            ) {
+                log::error!("Failed to record usage stats for user {}: {}", user_id, e);
+            }
+
+            if let Some(max_len) = group_id
+                .as_ref()
+                .and_then(|_| bot_deps.command_settings.max_reply_chars(msg.chat.id.to_string()))
+            {
+                ai_response.text = truncate_reply(&ai_response.text, max_len);
+            }
+
+            if is_sponsor {
+                if let Some(notice) = group_id
+                    .clone()
+                    .and_then(|gid| bot_deps.sponsor.remaining_requests_notice(gid))
+                {
+                    ai_response.text = format!("{}\n\n🎁 {}", ai_response.text, notice);
+                }
+            }
+
+            // Wallet hook replies are sent as plain text with buttons, so they stay as they are
+            let is_hook_reply = ai_response.tool_calls.as_ref().is_some_and(|calls| {
+                calls.iter().any(|call| {
+                    matches!(
+                        call.name.as_str(),
+                        "withdraw_funds" | "fund_account" | "get_pay_users"
+                    )
+                })
+            });
+            if !is_hook_reply {
+                ai_response.text = disclaimer::apply(
+                    &ai_response.text,
+                    bot_deps
+                        .command_settings
+                        .disclaimer_mode(msg.chat.id.to_string()),
+                    disclaimer::used_financial_tools(ai_response.tool_calls.as_deref()),
+                );
+            }
 
             let jwt = if group_id.is_some() {
                 let group_credentials = group_credentials;
@@ -773,50 +1341,68 @@ pub async fn handle_chat(
                 credentials.unwrap().jwt
             };
 
-            if profile != "dev" {
-                let response = create_purchase_request(
-                    file_search,
-                    web_search,
-                    image_gen,
-                    ai_response.total_tokens,
-                    ai_response.model,
-                    &jwt,
-                    group_id,
-                    Some(user_id),
-                    bot_deps.clone(),
-                )
-                .await;
+            let response = create_purchase_request(
+                file_search,
+                web_search,
+                image_gen,
+                ai_response.total_tokens,
+                ai_response.model,
+                &jwt,
+                group_id,
+                Some(user_id),
+                bot_deps.clone(),
+            )
+            .await;
 
-                if response.is_err() {
-                    log::error!(
-                        "Error purchasing tokens: {}",
-                        response.as_ref().err().unwrap()
-                    );
+            if response.is_err() {
+                log::error!(
+                    "Error purchasing tokens: {}",
+                    response.as_ref().err().unwrap()
+                );
 
-                    if response.as_ref().err().unwrap().to_string().contains("401")
-                        || response.as_ref().err().unwrap().to_string().contains("403")
-                    {
-                        send_message(
-                            msg,
-                            bot,
-                            "Your login has expired. Please login again.".to_string(),
-                        )
-                        .await?;
-                    } else {
-                        send_message(
-                            msg,
-                            bot,
-                            "Sorry, I encountered an error while processing your chat request."
-                                .to_string(),
-                        )
-                        .await?;
-                    }
+                if let Some(reply) = streaming_reply.take() {
+                    reply.discard().await;
+                }
 
-                    return Ok(());
+                if response.as_ref().err().unwrap().to_string().contains("401")
+                    || response.as_ref().err().unwrap().to_string().contains("403")
+                {
+                    send_message(
+                        msg,
+                        bot,
+                        "Your login has expired. Please login again.".to_string(),
+                    )
+                    .await?;
+                } else {
+                    send_message(
+                        msg,
+                        bot,
+                        "Sorry, I encountered an error while processing your chat request."
+                            .to_string(),
+                    )
+                    .await?;
+                }
+
+                return Ok(());
+            }
+
+            // Plain replies are finalized in the streamed message; images and wallet hooks
+            // are sent fresh, so their preview is removed
+            let mut streamed = false;
+            if let Some(reply) = streaming_reply.take() {
+                if ai_response.image_data.is_some() || is_hook_reply {
+                    reply.discard().await;
+                } else if reply.is_started() {
+                    let html_text = utils::normalize_image_url_anchor(&utils::markdown_to_html(
+                        &ai_response.text,
+                    ));
+                    streamed = reply.finish(split_message(&html_text), link_previews).await?;
                 }
             }
 
-            if let Some(image_data) = ai_response.image_data {
+            if streamed {
+                // Already delivered in the streamed message
+            } else if let Some(image_data) = ai_response.image_data {
                 let photo = InputFile::memory(image_data);
                 // Strip <pre> blocks from caption to avoid unbalanced HTML when truncated
                 let (text_without_pre, pre_blocks) = split_off_pre_blocks(&ai_response.text);
@@ -825,17 +1411,21 @@ pub async fn handle_chat(
                 } else {
                     &text_without_pre
                 };
-                bot.send_photo(msg.chat.id, photo)
+                let mut request = bot
+                    .send_photo(msg.chat.id, photo)
                     .caption(caption)
-                    .parse_mode(ParseMode::Html)
-                    .await?;
+                    .parse_mode(ParseMode::Html);
+                if let Some(thread_id) = utils::topic_thread_id(&msg) {
+                    request = request.message_thread_id(thread_id);
+                }
+                request.await?;
                 // Send any extracted <pre> blocks safely in full
                 for pre in pre_blocks {
-                    send_pre_block(&bot, msg.chat.id, "", &pre).await?;
+                    send_pre_block(&bot, msg.chat.id, utils::topic_thread_id(&msg), "", &pre).await?;
                 }
                 // If the text_without_pre is longer than 1024, send the remainder
                 if text_without_pre.len() > 1024 {
-                    send_long_message(msg, &bot, &text_without_pre[1024..]).await?;
+                    send_long_message(msg, &bot, &text_without_pre[1024..], link_previews).await?;
                 }
             } else if let Some(ref tool_calls) = ai_response.tool_calls {
                 if tool_calls
@@ -857,7 +1447,7 @@ pub async fn handle_chat(
                         user.id.0 as i64
                     } else {
                         log::warn!("Unable to get user ID for pay_users_hook");
-                        send_long_message(msg.clone(), &bot, &ai_response.text).await?;
+                        send_long_message(msg.clone(), &bot, &ai_response.text, link_previews).await?;
                         return Ok(());
                     };
 
@@ -884,13 +1474,13 @@ pub async fn handle_chat(
                             user_id,
                             group_id_i64
                         );
-                        send_long_message(msg.clone(), &bot, &ai_response.text).await?;
+                        send_long_message(msg.clone(), &bot, &ai_response.text, link_previews).await?;
                     }
                 } else {
-                    send_long_message(msg.clone(), &bot, &ai_response.text).await?;
+                    send_long_message(msg.clone(), &bot, &ai_response.text, link_previews).await?;
                 }
             } else {
-                send_long_message(msg, &bot, &ai_response.text).await?;
+                send_long_message(msg, &bot, &ai_response.text, link_previews).await?;
             }
 
             // Log tool calls if any
@@ -901,6 +1491,9 @@ pub async fn handle_chat(
             }
         }
         Err(e) => {
+            if let Some(reply) = streaming_reply.take() {
+                reply.discard().await;
+            }
             send_html_message(
                 msg,
                 bot,
@@ -954,6 +1547,88 @@ pub async fn handle_new_chat(bot: Bot, msg: Message, bot_deps: BotDependencies)
     Ok(())
 }
 
+/// /forget — drop the last /c exchange from the bot's context without a full /newchat.
+///
+/// Threads are chained server-side through `previous_response_id`, so the chain can't be
+/// cut at a given turn; the next /c starts a fresh thread instead. A conversation summary
+/// from before the forgotten turn is kept and seeds that thread; one written on the turn
+/// itself is dropped. Responses OpenAI has stored stay there until they expire.
+pub async fn handle_forget(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    let user_id = msg.from.as_ref().map(|u| u.id.0).unwrap_or(0) as i64;
+
+    if let Err(e) = bot_deps.user_convos.forget_last_turn(user_id) {
+        log::error!("Failed to forget last turn for user {}: {}", user_id, e);
+        send_message(
+            msg,
+            bot,
+            "❌ Error forgetting your last message".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let summary_kept = bot_deps
+        .summarizer
+        .forget_latest_turn(&user_id.to_string(), None)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to update summary for user {}: {}", user_id, e);
+            false
+        });
+    let seed = if summary_kept {
+        "Your next /c starts a fresh thread, seeded with the summary of your earlier conversation."
+    } else {
+        "Your next /c starts a fresh thread. Earlier messages are no longer sent as context."
+    };
+
+    send_html_message(
+        msg,
+        bot,
+        format!(
+            "🧹 <b>Last message forgotten</b>\n\n{}\n\n💡 <i>Cached image links were cleared; your files and settings are unchanged.</i>\n\n⚠️ <i>OpenAI may keep stored responses on its side until they expire. /forget only stops the bot from using them.</i>",
+            seed
+        ),
+    )
+    .await
+}
+
+/// /lastimage — resend the caller's most recent generated image from its stored link
+pub async fn handle_last_image(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    let user_id = msg.from.as_ref().map(|u| u.id.0).unwrap_or(0) as i64;
+    let Some(url) = bot_deps.user_convos.last_image_url(user_id) else {
+        send_message(
+            msg,
+            bot,
+            "🖼️ No recent image found. Ask /c to create one first.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    };
+    let Ok(url) = Url::parse(&url) else {
+        log::warn!("Stored image link for user {} is not a URL: {}", user_id, url);
+        send_message(msg, bot, "🖼️ No recent image found.".to_string()).await?;
+        return Ok(());
+    };
+
+    let mut request = bot.send_photo(msg.chat.id, InputFile::url(url.clone()));
+    if let Some(thread_id) = utils::topic_thread_id(&msg) {
+        request = request.message_thread_id(thread_id);
+    }
+    if !msg.chat.is_private() {
+        request = request.reply_to(msg.id);
+    }
+    // Links can stop working once storage clean-up removes the file
+    if let Err(e) = request.await {
+        log::warn!("Failed to resend last image for user {}: {}", user_id, e);
+        send_message(
+            msg,
+            bot,
+            "🖼️ Your last image is no longer available. Ask /c to create it again.".to_string(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
 pub async fn handle_web_app_data(
     bot: Bot,
     msg: Message,
@@ -1013,12 +1688,22 @@ pub async fn handle_web_app_data(
 }
 
 pub async fn handle_message(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    // A new pin replaces whatever the AI had cached for this group
+    if msg.pinned_message().is_some() {
+        bot_deps.pinned_messages.invalidate(msg.chat.id);
+    }
+
     // Sentinel: moderate every message in group if sentinel is on
     if !msg.chat.is_private() {
         let group_id = msg.chat.id.to_string();
         let chat_id = msg.chat.id;
         let user = msg.from.clone();
 
+        // Emoji/sticker spam is deleted outright, so don't pay to moderate it first
+        if enforce_spam_limit(&bot, &msg, &bot_deps).await? {
+            return Ok(());
+        }
+
         let sentinel_executed =
             handle_message_sentinel(bot.clone(), msg.clone(), bot_deps.clone(), group_id.clone())
                 .await?;
@@ -1111,6 +1796,10 @@ pub async fn handle_message(bot: Bot, msg: Message, bot_deps: BotDependencies) -
             return Ok(());
         }
 
+        if handle_context_note_input(&bot, &msg, &bot_deps).await? {
+            return Ok(());
+        }
+
         let scheduled_payments_executed = handle_message_scheduled_payments(
             bot.clone(),
             msg.clone(),
@@ -1186,6 +1875,15 @@ pub async fn handle_message(bot: Bot, msg: Message, bot_deps: BotDependencies) -
     // Handle private user file uploads (documents only)
     if msg.caption().is_none() && msg.chat.is_private() && msg.document().is_some() {
         handle_file_upload(bot, msg, bot_deps.clone()).await?;
+        return Ok(());
+    }
+
+    // Plain DM text with no command: context note input, onboarding note or opt-in AI chat
+    if msg.chat.is_private() {
+        if handle_context_note_input(&bot, &msg, &bot_deps).await? {
+            return Ok(());
+        }
+        handle_plain_dm(bot, msg, bot_deps).await?;
     }
     Ok(())
 }
@@ -1288,24 +1986,35 @@ pub async fn handle_mod(bot: Bot, msg: Message, bot_deps: BotDependencies) -> An
         // Load overrides
         let formatted_group_id = format!("{}-{}", msg.chat.id.0, bot_deps.group.account_seed);
         let settings_tree = bot_deps.db.open_tree("moderation_settings").unwrap();
-        let overrides = if let Ok(Some(raw)) = settings_tree.get(formatted_group_id.as_bytes()) {
+        let (overrides, moderation_action, react_on_pass) = if let Ok(Some(raw)) =
+            settings_tree.get(formatted_group_id.as_bytes())
+        {
             #[derive(Serialize, Deserialize)]
             struct ModerationSettings {
                 allowed_items: Vec<String>,
                 disallowed_items: Vec<String>,
                 updated_by_user_id: i64,
                 updated_at_unix_ms: i64,
+                #[serde(default)]
+                action: ModerationAction,
+                #[serde(default)]
+                react_on_pass: bool,
             }
             if let Ok(ms) = serde_json::from_slice::<ModerationSettings>(&raw) {
-                Some(ModerationOverrides {
-                    allowed_items: ms.allowed_items,
-                    disallowed_items: ms.disallowed_items,
-                })
+                (
+                    Some(ModerationOverrides {
+                        allowed_items: ms.allowed_items,
+                        disallowed_items: ms.disallowed_items,
+                        permitted_domains: bot_deps.link_allowlist.get(msg.chat.id),
+                    }),
+                    ms.action,
+                    ms.react_on_pass,
+                )
             } else {
-                None
+                (None, ModerationAction::default(), false)
             }
         } else {
-            None
+            (None, ModerationAction::default(), false)
         };
         match moderation_service
             .moderate_message(message_text, &bot, &msg, &reply_to_msg, overrides)
@@ -1328,7 +2037,7 @@ pub async fn handle_mod(bot: Bot, msg: Message, bot_deps: BotDependencies) -> An
                     &group_credentials.unwrap().jwt,
                     Some(msg.chat.id.0.to_string()),
                     None,
-                    bot_deps,
+                    bot_deps.clone(),
                 )
                 .await;
 
@@ -1342,39 +2051,26 @@ pub async fn handle_mod(bot: Bot, msg: Message, bot_deps: BotDependencies) -> An
 
                 // Only respond if the message is flagged
                 if result.verdict == "F" {
-                    // First, mute the user who sent the flagged message
                     if let Some(flagged_user) = &reply_to_msg.from {
-                        // Create restricted permissions (muted)
-                        let restricted_permissions = teloxide::types::ChatPermissions::empty();
-
-                        // Mute the user indefinitely
-                        if let Err(mute_error) = bot
-                            .restrict_chat_member(
-                                msg.chat.id,
-                                flagged_user.id,
-                                restricted_permissions,
-                            )
-                            .await
-                        {
-                            log::error!("Failed to mute user {}: {}", flagged_user.id, mute_error);
-                        } else {
-                            log::info!(
-                                "Successfully muted user {} for flagged content",
-                                flagged_user.id
-                            );
-                        }
-
-                        // Create keyboard with admin controls
-                        let keyboard = InlineKeyboardMarkup::new(vec![vec![
-                            InlineKeyboardButton::callback(
-                                "🔇 Unmute",
-                                format!("unmute:{}", flagged_user.id),
-                            ),
-                            InlineKeyboardButton::callback(
-                                "🚫 Ban",
-                                format!("ban:{}:{}", flagged_user.id, reply_to_msg.id.0),
-                            ),
-                        ]]);
+                        let enforcement = enforce_moderation_action(
+                            &bot,
+                            msg.chat.id,
+                            flagged_user,
+                            reply_to_msg.id,
+                            moderation_action,
+                        )
+                        .await;
+                        let event = FlaggedEvent::new(
+                            msg.chat.id,
+                            reply_to_msg.id.0,
+                            flagged_user,
+                            message_text,
+                            FlagSource::Report,
+                            moderation_action,
+                            &enforcement.status,
+                        );
+                        let keyboard = with_appeal_button(enforcement.keyboard, &event);
+                        log_flagged_event(&bot_deps, event);
 
                         // Build a visible user mention (prefer @username, else clickable name)
                         let user_mention = if let Some(username) = &flagged_user.username {
@@ -1393,34 +2089,32 @@ pub async fn handle_mod(bot: Bot, msg: Message, bot_deps: BotDependencies) -> An
                             msg.clone(),
                             KeyboardMarkupType::InlineKeyboardType(keyboard),
                             &format!(
-                                "🛡️ <b>Content Flagged & User Muted</b>\n\n📝 Message ID: <code>{}</code>\n\n❌ Status: <b>FLAGGED</b> 🔴\n🔇 User has been muted\n👤 <b>User:</b> {}\n\n💬 <i>Flagged message:</i>\n<blockquote><span class=\"tg-spoiler\">{}</span></blockquote>",
+                                "🛡️ <b>{}</b>\n\n📝 Message ID: <code>{}</code>\n\n❌ Status: <b>FLAGGED</b> 🔴\n{}\n👤 <b>User:</b> {}\n\n💬 <i>Flagged message:</i>\n<blockquote><span class=\"tg-spoiler\">{}</span></blockquote>",
+                                enforcement.title,
                                 reply_to_msg.id,
+                                enforcement.status,
                                 user_mention,
                                 teloxide::utils::html::escape(message_text)
                             ),
                         ).await?;
-                        // Immediately remove the offending message from the chat
-                        if let Err(e) = bot.delete_message(msg.chat.id, reply_to_msg.id).await {
-                            log::warn!(
-                                "Failed to delete offending replied message {}: {}",
-                                reply_to_msg.id.0,
-                                e
-                            );
-                        }
                     } else {
                         // Fallback if no user found in the replied message
-                        send_html_message(msg.clone(), bot.clone(), format!("🛡️ <b>Content Flagged</b>\n\n📝 Message ID: <code>{}</code>\n\n❌ Status: <b>FLAGGED</b> 🔴\n⚠️ Could not identify user to mute\n\n💬 <i>Flagged message:</i>\n<blockquote><span class=\"tg-spoiler\">{}</span></blockquote>", reply_to_msg.id, teloxide::utils::html::escape(message_text)).to_string()).await?;
-                        // Remove the offending message regardless
-                        if let Err(e) = bot.delete_message(msg.chat.id, reply_to_msg.id).await {
-                            log::warn!(
-                                "Failed to delete offending replied message {}: {}",
-                                reply_to_msg.id.0,
-                                e
-                            );
+                        send_html_message(msg.clone(), bot.clone(), format!("🛡️ <b>Content Flagged</b>\n\n📝 Message ID: <code>{}</code>\n\n❌ Status: <b>FLAGGED</b> 🔴\n⚠️ Could not identify user to act on\n\n💬 <i>Flagged message:</i>\n<blockquote><span class=\"tg-spoiler\">{}</span></blockquote>", reply_to_msg.id, teloxide::utils::html::escape(message_text)).to_string()).await?;
+                        // Remove the offending message unless the group only warns
+                        if moderation_action != ModerationAction::Warn {
+                            if let Err(e) = bot.delete_message(msg.chat.id, reply_to_msg.id).await {
+                                log::warn!(
+                                    "Failed to delete offending replied message {}: {}",
+                                    reply_to_msg.id.0,
+                                    e
+                                );
+                            }
                         }
                     }
+                } else if react_on_pass {
+                    react_to_passed_message(&bot, &msg, &reply_to_msg).await;
                 }
-                // Silent when passed (P) - no response
+                // Otherwise silent when passed (P) - no response
             }
             Err(e) => {
                 log::error!("Moderation failed: {}", e);
@@ -1434,6 +2128,220 @@ pub async fn handle_mod(bot: Bot, msg: Message, bot_deps: BotDependencies) -> An
     Ok(())
 }
 
+/// /testmod — run moderation on some text with the group's rules, without enforcing anything
+pub async fn handle_test_mod(
+    bot: Bot,
+    msg: Message,
+    text: String,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(user) = msg.from.clone() else {
+        return Ok(());
+    };
+    if !utils::is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group administrators can use /testmod.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(group_credentials) = bot_deps.group.get_credentials(msg.chat.id) else {
+        send_message(msg, bot, "❌ Group not found".to_string()).await?;
+        return Ok(());
+    };
+
+    let reply_to_msg = msg.reply_to_message().cloned();
+    let (message_text, author_is_admin) = if !text.trim().is_empty() {
+        (text.trim().to_string(), false)
+    } else if let Some(reply) = &reply_to_msg {
+        let reply_text = reply.text().or_else(|| reply.caption()).unwrap_or_default();
+        let author_is_admin = match &reply.from {
+            Some(author) => utils::is_admin(&bot, msg.chat.id, author.id).await,
+            None => false,
+        };
+        (reply_text.to_string(), author_is_admin)
+    } else {
+        send_html_message(msg, bot, "🧪 <b>Moderation Dry Run</b>\n\nUsage:\n• <code>/testmod your text</code>\n• or reply to a message with <code>/testmod</code>\n\nShows the verdict your current rules would give. Nothing is deleted and nobody is muted.".to_string()).await?;
+        return Ok(());
+    };
+
+    if message_text.is_empty() {
+        send_message(
+            msg,
+            bot,
+            "❌ There is no text to test in that message.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (overrides, moderation_action) = match bot_deps
+        .moderation
+        .get_moderation_settings(msg.chat.id.to_string())
+    {
+        Ok(settings) => (
+            Some(ModerationOverrides {
+                allowed_items: settings.allowed_items,
+                disallowed_items: settings.disallowed_items,
+                permitted_domains: bot_deps.link_allowlist.get(msg.chat.id),
+            }),
+            settings.action,
+        ),
+        Err(e) => {
+            log::error!("Failed to get moderation settings: {}", e);
+            (None, ModerationAction::default())
+        }
+    };
+    let rules_summary = match &overrides {
+        Some(o) => format!(
+            "Default rules + {} allowed / {} disallowed group items",
+            o.allowed_items.len(),
+            o.disallowed_items.len()
+        ),
+        None => "Default rules only".to_string(),
+    };
+
+    let (flagged, reason) = if author_is_admin {
+        // Mirrors moderate_message, which passes admins without an API call
+        (false, "Messages from group administrators always pass.".to_string())
+    } else {
+        // Drop the sender so moderate_message doesn't exempt the admin running the test
+        let mut probe = msg.clone();
+        probe.from = None;
+        let result = match bot_deps
+            .moderation
+            .moderate_message(&message_text, &bot, &msg, &probe, overrides)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                log::error!("Test moderation failed: {}", e);
+                send_message(
+                    msg,
+                    bot,
+                    "❌ Failed to analyze the text. Please try again later.".to_string(),
+                )
+                .await?;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = create_purchase_request(
+            0,
+            0,
+            0,
+            result.total_tokens,
+            Model::GPT5Nano,
+            &group_credentials.jwt,
+            Some(msg.chat.id.0.to_string()),
+            None,
+            bot_deps.clone(),
+        )
+        .await
+        {
+            log::error!("Failed to purchase ai for test moderation: {}", e);
+        }
+
+        if result.verdict == "F" {
+            (
+                true,
+                "Violates the default scam rules or this group's disallowed items.".to_string(),
+            )
+        } else {
+            (
+                false,
+                "No violation of the default rules or this group's disallowed items.".to_string(),
+            )
+        }
+    };
+
+    let verdict = if flagged {
+        format!(
+            "❌ Verdict: <b>FLAGGED</b> 🔴\n📝 {}\n⚙️ Would apply: <b>{}</b>",
+            reason,
+            moderation_action.label()
+        )
+    } else {
+        format!("✅ Verdict: <b>PASSED</b> 🟢\n📝 {}", reason)
+    };
+
+    send_html_message(
+        msg,
+        bot,
+        format!(
+            "🧪 <b>Moderation Dry Run</b>\n\n{}\n📚 Rules: {}\n\n💬 <i>Tested text:</i>\n<blockquote>{}</blockquote>\n\nℹ️ <i>Dry run only — no message was deleted and nobody was muted.</i>",
+            verdict,
+            rules_summary,
+            teloxide::utils::html::escape(&message_text)
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Telegram only accepts reactions from a fixed emoji set, which has no ✅
+const PASS_REACTION_EMOJI: &str = "👌";
+
+/// Mark a message that passed /report, falling back to a short reply where reactions are unavailable
+async fn react_to_passed_message(bot: &Bot, msg: &Message, passed_msg: &Message) {
+    let reaction = bot
+        .set_message_reaction(msg.chat.id, passed_msg.id)
+        .reaction(vec![ReactionType::Emoji {
+            emoji: PASS_REACTION_EMOJI.to_string(),
+        }])
+        .await;
+
+    if let Err(e) = reaction {
+        // Old clients, disabled reactions or a restricted emoji list all end up here
+        log::warn!(
+            "Could not react to passed message {} in chat {}: {}",
+            passed_msg.id.0,
+            msg.chat.id.0,
+            e
+        );
+        if let Err(e) = bot
+            .send_message(msg.chat.id, "✅ No violations found")
+            .reply_to(passed_msg.id)
+            .await
+        {
+            log::warn!("Failed to send moderation pass notice: {}", e);
+        }
+    }
+}
+
+/// Parsed Panora USD price; `None` for unpriced tokens
+fn token_usd_price(token: &Token) -> Option<f64> {
+    token.usd_price.as_deref()?.parse::<f64>().ok()
+}
+
+/// APT skips the token lookup for its balance, so its price is fetched separately
+async fn apt_usd_price(bot_deps: &BotDependencies) -> Option<f64> {
+    match bot_deps.panora.get_token_by_symbol("APT").await {
+        Ok(token) => token_usd_price(&token),
+        Err(e) => {
+            log::warn!("Failed to look up APT price: {}", e);
+            None
+        }
+    }
+}
+
+/// Balance line, with its USD value when the token is priced
+fn format_balance(human_balance: f64, symbol: &str, usd_price: Option<f64>) -> String {
+    match usd_price {
+        Some(price) => format!(
+            "💰 <b>Balance</b>: {:.2} {} (≈ ${:.2})",
+            human_balance,
+            symbol,
+            human_balance * price
+        ),
+        None => format!("💰 <b>Balance</b>: {:.2} {}", human_balance, symbol),
+    }
+}
+
 pub async fn handle_balance(
     bot: Bot,
     msg: Message,
@@ -1467,12 +2375,13 @@ pub async fn handle_balance(
         return Ok(());
     }
 
-    let (token_type, decimals, token_symbol) =
+    let (token_type, decimals, token_symbol, usd_price) =
         if symbol.to_lowercase() == "apt" || symbol.to_lowercase() == "aptos" {
             (
                 "0x1::aptos_coin::AptosCoin".to_string(),
                 8u8,
                 "APT".to_string(),
+                apt_usd_price(&bot_deps).await,
             )
         } else {
             let token = bot_deps.panora.get_token_by_symbol(symbol).await;
@@ -1491,7 +2400,12 @@ pub async fn handle_balance(
                 token.fa_address.clone()
             };
 
-            (token_type, token.decimals, token.symbol.clone())
+            (
+                token_type,
+                token.decimals,
+                token.symbol.clone(),
+                token_usd_price(&token),
+            )
         };
 
     let user_credentials = user_credentials.unwrap();
@@ -1538,7 +2452,7 @@ pub async fn handle_balance(
     send_html_message(
         msg,
         bot,
-        format!("💰 <b>Balance</b>: {:.2} {}", human_balance, token_symbol).to_string(),
+        format_balance(human_balance, &token_symbol, usd_price),
     )
     .await?;
 
@@ -1570,12 +2484,13 @@ pub async fn handle_group_balance(
 
     let group_credentials = group_credentials.unwrap();
 
-    let (token_type, decimals, token_symbol) =
+    let (token_type, decimals, token_symbol, usd_price) =
         if symbol.to_lowercase() == "apt" || symbol.to_lowercase() == "aptos" {
             (
                 "0x1::aptos_coin::AptosCoin".to_string(),
                 8u8,
                 "APT".to_string(),
+                apt_usd_price(&bot_deps).await,
             )
         } else {
             let tokens = bot_deps.panora.get_token_by_symbol(symbol).await;
@@ -1594,7 +2509,12 @@ pub async fn handle_group_balance(
                 token.fa_address.clone()
             };
 
-            (token_type, token.decimals, token.symbol.clone())
+            (
+                token_type,
+                token.decimals,
+                token.symbol.clone(),
+                token_usd_price(&token),
+            )
         };
 
     let balance = bot_deps
@@ -1634,7 +2554,7 @@ pub async fn handle_group_balance(
     send_html_message(
         msg,
         bot,
-        format!("💰 <b>Balance</b>: {:.2} {}", human_balance, token_symbol).to_string(),
+        format_balance(human_balance, &token_symbol, usd_price),
     )
     .await?;
 
@@ -1,17 +1,24 @@
-use crate::{dependencies::BotDependencies, utils::send_message};
+use crate::{dependencies::BotDependencies, utils::{send_message, topic_thread_id}};
 use anyhow::Result;
 use quark_core::helpers::bot_commands::{Command, QuarkState};
 use teloxide::{
     Bot,
     dispatching::{DpHandlerDescription, HandlerExt, UpdateFilterExt, dialogue::InMemStorage},
     dptree::{self, Handler},
-    types::{Message, Update, ChatMemberUpdated},
+    types::{Me, Message, Update, ChatMemberUpdated},
+    utils::command::BotCommands,
 };
 
 use crate::{
-    bot::{answers::answers, handler::handle_message, handler::handle_web_app_data},
+    ai::sentinel::handler::handle_message_sentinel,
+    bot::{
+        answers::{answers, handle_c_with_alias},
+        handler::handle_message,
+        handler::handle_web_app_data,
+    },
     callbacks::handle_callback_query,
     message_history::handler::{store_message, MessageEntry},
+    user_model_preferences::aliases::parse_model_prefix,
 };
 
 async fn handle_unauthenticated(bot: Bot, msg: Message) -> Result<()> {
@@ -47,7 +54,7 @@ async fn handle_chat_member_update(
                 let first_name = user.first_name.clone();
                 
                 if let Err(e) = welcome_service
-                    .handle_new_member(&bot, update.chat.id, user.id, username, first_name)
+                    .handle_new_member(&bot, update.chat.id, user.id, username, first_name, None)
                     .await
                 {
                     log::error!("Failed to handle new member: {}", e);
@@ -59,11 +66,67 @@ async fn handle_chat_member_update(
     Ok(())
 }
 
+fn is_blocked_sender(msg: &Message, bot_deps: &BotDependencies) -> bool {
+    let Some(user) = &msg.from else {
+        return false;
+    };
+    let chat_id = (!msg.chat.is_private()).then_some(msg.chat.id.0);
+    bot_deps.blocklist.is_blocked(chat_id, user.id.0 as i64)
+}
+
+/// Parse a bot command. Groups with a custom prefix (e.g. `!`) also get `!c` treated as `/c`;
+/// standard `/` commands always work.
+fn parse_command(msg: Message, me: Me, bot_deps: BotDependencies) -> Option<Command> {
+    let text = msg.text().or_else(|| msg.caption())?;
+    let bot_name = me.user.username.clone().unwrap_or_default();
+    parse_command_text(text, &msg, &bot_name, &bot_deps)
+}
+
+/// `parse_command` for text taken from elsewhere, e.g. an album caption
+pub(crate) fn parse_command_text(
+    text: &str,
+    msg: &Message,
+    bot_name: &str,
+    bot_deps: &BotDependencies,
+) -> Option<Command> {
+    if let Ok(cmd) = Command::parse(text, bot_name) {
+        return Some(cmd);
+    }
+
+    // Cheap check first so ordinary chatter doesn't hit the settings store
+    let first = text.chars().next()?;
+    if msg.chat.is_private() || !first.is_ascii_punctuation() || first == '/' {
+        return None;
+    }
+    let prefix = bot_deps
+        .command_settings
+        .command_prefix(msg.chat.id.to_string())?;
+    let rest = text.strip_prefix(prefix.as_str())?;
+    if rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Command::parse(&format!("/{}", rest), bot_name).ok()
+}
+
 pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
     dptree::entry()
         .branch(
             Update::filter_message()
                 .enter_dialogue::<Message, InMemStorage<QuarkState>, QuarkState>()
+                // Blocked users get no AI or commands, but the group's sentinel still moderates
+                // them so a block can't be used to slip past moderation
+                .branch(
+                    dptree::entry()
+                        .filter(|msg: Message, bot_deps: BotDependencies| is_blocked_sender(&msg, &bot_deps))
+                        .endpoint(|bot: Bot, msg: Message, bot_deps: BotDependencies| async move {
+                            if !msg.chat.is_private() {
+                                let chat_id = msg.chat.id.to_string();
+                                handle_message_sentinel(bot, msg.clone(), bot_deps, chat_id).await?;
+                            }
+                            log::debug!("Ignoring message from blocked user in chat {}", msg.chat.id);
+                            Ok(())
+                        }),
+                )
                 // Record messages with text to message history buffer (groups only, passthrough)
                 .inspect_async(|bot_deps: BotDependencies, msg: Message| async move {
                     if let Some(text) = msg.text() {
@@ -74,7 +137,8 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                                 sender: sender_name,
                                 text: text.to_string(),
                             };
-                            store_message(msg.chat.id, entry, bot_deps.history_storage.clone()).await;
+                            let settings = bot_deps.group.get_history_settings(msg.chat.id);
+                            store_message(msg.chat.id, entry, bot_deps.history_storage.clone(), settings).await;
                         }
                     }
                 })
@@ -93,7 +157,7 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                                         let username = user.username.clone();
                                         let first_name = user.first_name.clone();
                                         if let Err(e) = welcome_service
-                                            .handle_new_member(&bot, msg.chat.id, user.id, username, first_name)
+                                            .handle_new_member(&bot, msg.chat.id, user.id, username, first_name, topic_thread_id(&msg))
                                             .await
                                         {
                                             log::error!("Failed to handle new member (message event): {}", e);
@@ -135,15 +199,27 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                 .branch(
                     // 2. Branch for public commands for new users
                     dptree::entry()
-                        .filter_command::<Command>()
+                        .filter_map(parse_command)
                         .filter(|cmd| {
                             matches!(
                                 cmd,
                                 Command::Help
                                     | Command::LoginUser
                                     | Command::LoginGroup
+                                    | Command::RefreshGroup
                                     | Command::AptosConnect
+                                    | Command::Price(_)
+                                    | Command::Pools(_)
                                     | Command::Prices
+                                    | Command::Rates
+                                    | Command::Debug
+                                    | Command::Uptime
+                                    | Command::Version
+                                    | Command::Config
+                                    | Command::Block(_)
+                                    | Command::Unblock(_)
+                                    | Command::Blocklist(_)
+                                    | Command::Announcers(_)
                             )
                         })
                         .endpoint(answers),
@@ -151,16 +227,25 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                 .branch(
                     // 1. Branch for authenticated users
                     dptree::entry()
-                        .filter_command::<Command>()
+                        .filter_map(parse_command)
                         .filter(|cmd| {
                             matches!(
                                 cmd,
                                 Command::C(_)
                                     | Command::WalletAddress
                                     | Command::Balance(_)
+                                    | Command::Balances
+                                    | Command::Send(_)
+                                    | Command::SendMax(_)
+                                    | Command::Pending
+                                    | Command::ExplainTx(_)
+                                    | Command::ModelAlias(_)
                                     | Command::NewChat
+                                    | Command::Forget
+                                    | Command::LastImage
                                     | Command::PromptExamples
                                     | Command::Announcement(_)
+                                    | Command::RefreshTokens
                             )
                         })
                         .filter_async(|msg: Message, bot_deps: BotDependencies| async move {
@@ -168,14 +253,28 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                         })
                         .endpoint(answers),
                 )
+                .branch(
+                    // /c:alias <prompt> — /c with a per-prompt model, not parseable as a Command
+                    dptree::entry()
+                        .filter(|msg: Message| {
+                            msg.text()
+                                .or_else(|| msg.caption())
+                                .and_then(parse_model_prefix)
+                                .is_some()
+                        })
+                        .filter_async(|msg: Message, bot_deps: BotDependencies| async move {
+                            bot_deps.auth.verify(msg).await
+                        })
+                        .endpoint(handle_c_with_alias),
+                )
                 .branch(
                     dptree::entry()
-                        .filter_command::<Command>()
+                        .filter_map(parse_command)
                         .filter(|cmd| {
                             matches!(
                                 cmd,
                                 Command::G(_) | Command::Groupsettings
-                                    | Command::Report | Command::GroupBalance(_) | Command::GroupWalletAddress | Command::Rules | Command::SchedulePrompt | Command::ListScheduled | Command::SchedulePayment | Command::ListScheduledPayments
+                                    | Command::Report | Command::TestMod(_) | Command::SnoozeSentinel(_) | Command::Flagged | Command::AiBudget(_) | Command::CommandPrefix(_) | Command::GroupBalance(_) | Command::GroupBalances | Command::GroupWalletAddress | Command::Members | Command::Rules | Command::SchedulePrompt | Command::ListScheduled | Command::SchedulePayment | Command::ListScheduledPayments | Command::ExportScheduledPayments
                             )
                         })
                         .filter_async(|msg: Message, bot_deps: BotDependencies| async move {
@@ -186,8 +285,8 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                 .branch(
                     // DM-only authenticated commands
                     dptree::entry()
-                        .filter_command::<Command>()
-                        .filter(|cmd| { matches!(cmd, Command::Usersettings) })
+                        .filter_map(parse_command)
+                        .filter(|cmd| { matches!(cmd, Command::Usersettings | Command::MyVotes | Command::Stats | Command::Notifications | Command::NewKb(_) | Command::ListKb | Command::UseKb(_)) })
                         .filter(|msg: Message| msg.chat.is_private())
                         .filter_async(|msg: Message, bot_deps: BotDependencies| async move {
                             bot_deps.auth.verify(msg).await
@@ -197,8 +296,8 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                 .branch(
                     // Handle DM-only commands when used in groups - direct to DMs
                     dptree::entry()
-                        .filter_command::<Command>()
-                        .filter(|cmd| { matches!(cmd, Command::Usersettings) })
+                        .filter_map(parse_command)
+                        .filter(|cmd| { matches!(cmd, Command::Usersettings | Command::MyVotes | Command::Stats | Command::Notifications | Command::NewKb(_) | Command::ListKb | Command::UseKb(_)) })
                         .filter(|msg: Message| !msg.chat.is_private())
                         .endpoint(|bot: Bot, msg: Message| async move {
                             send_message(
@@ -218,10 +317,21 @@ pub fn handler_tree() -> Handler<'static, Result<()>, DpHandlerDescription> {
                         )
                 .branch(
                     dptree::entry()
-                        .filter_command::<Command>()
+                        .filter_map(parse_command)
                         .endpoint(handle_unauthenticated),
                 ),
         )
+        .branch(
+            Update::filter_callback_query()
+                .filter(|query: teloxide::types::CallbackQuery, bot_deps: BotDependencies| {
+                    let chat_id = query.message.as_ref().map(|m| m.chat().id);
+                    bot_deps.blocklist.is_blocked(
+                        chat_id.filter(|id| !id.is_user()).map(|id| id.0),
+                        query.from.id.0 as i64,
+                    )
+                })
+                .endpoint(|| async { Ok(()) }),
+        )
         .branch(Update::filter_callback_query().endpoint(
             |bot: Bot,
              query: teloxide::types::CallbackQuery,
@@ -1,13 +1,22 @@
-use crate::utils::{
-    KeyboardMarkupType, send_markdown_message_with_keyboard,
-    send_markdown_message_with_keyboard_with_reply, send_message,
+use crate::{
+    payment::{
+        ledger::record_sent_payment,
+        memo::memo_html_line,
+        notifications::{notify_payment_recipients, payment_source_label},
+    },
+    pending_transactions::{dto::PendingTransaction, handler::PendingTransactions},
+    utils::{
+        KeyboardMarkupType, send_markdown_message_with_keyboard,
+        send_markdown_message_with_keyboard_with_reply, send_message,
+    },
 };
 use anyhow::Result;
 use quark_core::helpers::utils::extract_url_from_markdown;
 use reqwest::Url;
 use teloxide::{
     Bot,
-    types::{InlineKeyboardButton, InlineKeyboardMarkup, Message, WebAppInfo},
+    prelude::*,
+    types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, Message, ParseMode, WebAppInfo},
 };
 
 pub async fn withdraw_funds_hook(bot: Bot, msg: Message, text: String) -> Result<()> {
@@ -95,6 +104,27 @@ pub async fn pay_users_hook(
         .and_then(|gid| gid.parse::<i64>().ok())
         .unwrap_or(0);
 
+    let group_id_opt = if group_id_i64 == 0 {
+        None
+    } else {
+        Some(group_id_i64)
+    };
+
+    let mut text = text;
+    if let Some(transaction) = bot_deps
+        .pending_transactions
+        .get_pending_transaction(user_id, group_id_opt)
+    {
+        if transaction.transaction_id == transaction_id && transaction.auto_confirm {
+            return auto_send_payment(bot, msg, user_id, group_id_opt, transaction, bot_deps).await;
+        }
+        if transaction.transaction_id == transaction_id {
+            if let Some(memo) = &transaction.memo {
+                text.push_str(&format!("\n\n📝 Memo: {}", memo));
+            }
+        }
+    }
+
     let accept_btn = InlineKeyboardButton::callback(
         "✅ Accept",
         format!("pay_accept:{}:{}:{}", user_id, group_id_i64, transaction_id),
@@ -116,11 +146,6 @@ pub async fn pay_users_hook(
     .await?;
 
     // Update the pending transaction with the message ID
-    let group_id_opt = if group_id_i64 == 0 {
-        None
-    } else {
-        Some(group_id_i64)
-    };
     if let Err(e) = bot_deps.pending_transactions.update_transaction_message_id(
         user_id,
         group_id_opt,
@@ -161,3 +186,119 @@ pub async fn pay_users_hook(
 
     Ok(())
 }
+
+/// Execute a payment that is below the requester's auto-confirm threshold without
+/// showing the accept/reject buttons.
+async fn auto_send_payment(
+    bot: Bot,
+    msg: Message,
+    user_id: i64,
+    group_id: Option<i64>,
+    transaction: PendingTransaction,
+    bot_deps: crate::dependencies::BotDependencies,
+) -> Result<()> {
+    if let Err(e) = bot_deps
+        .pending_transactions
+        .delete_pending_transaction(user_id, group_id)
+    {
+        log::warn!("Failed to delete auto-confirmed pending transaction: {}", e);
+    }
+
+    let pay_request = PendingTransactions::to_pay_users_request(&transaction);
+    let result = if transaction.is_group_transfer {
+        bot_deps
+            .service
+            .pay_members(transaction.jwt_token.clone(), pay_request)
+            .await
+    } else {
+        bot_deps
+            .service
+            .pay_users(transaction.jwt_token.clone(), pay_request)
+            .await
+    };
+
+    let total = transaction.per_user_amount * transaction.original_usernames.len() as f64;
+    let recipients_text = transaction
+        .original_usernames
+        .iter()
+        .map(|username| format!("@{}", username))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match result {
+        Ok(response) => {
+            log::info!(
+                "Auto-confirmed payment {} by user {}: {:.2} {} to {} (tx {})",
+                transaction.transaction_id,
+                user_id,
+                total,
+                transaction.symbol,
+                recipients_text,
+                response.hash
+            );
+
+            let network = std::env::var("APTOS_NETWORK")
+                .unwrap_or("mainnet".to_string())
+                .to_lowercase();
+
+            if let Some(memo) = &transaction.memo {
+                if let Err(e) = bot_deps.payment_memos.set(&response.hash, memo) {
+                    log::error!("Failed to store memo for tx {}: {}", response.hash, e);
+                }
+            }
+            record_sent_payment(
+                &bot_deps,
+                &transaction,
+                &response.hash,
+                "auto",
+                msg.from.as_ref().and_then(|u| u.username.as_deref()),
+            );
+
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "⚡ <b>Auto-sent</b>\n\n💰 {:.2} {} sent to {} ({:.2} each){}\n\n🔗 <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">View transaction</a>\n\n<i>Below your auto-confirm threshold. Change it in /usersettings → Payment Settings.</i>",
+                    total,
+                    transaction.symbol,
+                    recipients_text,
+                    transaction.per_user_amount,
+                    memo_html_line(transaction.memo.as_deref()),
+                    response.hash,
+                    network
+                ),
+            )
+            .reply_to(msg.id)
+            .parse_mode(ParseMode::Html)
+            .await?;
+
+            let source_chat = group_id.map(ChatId).unwrap_or(ChatId(user_id));
+            let sender = msg.from.as_ref().and_then(|u| u.username.clone());
+            let source =
+                payment_source_label(&bot, source_chat, sender.as_deref()).await;
+            notify_payment_recipients(
+                &bot,
+                &bot_deps,
+                &transaction.original_usernames,
+                transaction.per_user_amount,
+                &transaction.symbol,
+                &source,
+                &response.hash,
+                transaction.memo.as_deref(),
+            )
+            .await;
+        }
+        Err(e) => {
+            log::error!(
+                "Auto-confirmed payment {} failed: {}",
+                transaction.transaction_id,
+                e
+            );
+            bot.send_message(msg.chat.id, format!("❌ <b>Payment failed</b>\n\n{}", e))
+                .reply_to(msg.id)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
 pub mod answers;
+pub mod balances;
+pub mod diagnostics;
 pub mod handler;
 pub mod handler_tree;
 pub mod hooks;
+pub mod pools;
+pub mod price;
@@ -0,0 +1,148 @@
+//! /pools: GeckoTerminal pool search without going through the AI.
+
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use regex::Regex;
+use teloxide::{
+    prelude::*,
+    types::{ChatAction, Message},
+    utils::html,
+};
+
+use crate::{
+    ai::actions::execute_search_pools,
+    bot::handler::send_long_html,
+    dependencies::BotDependencies,
+    utils::{self, send_html_message},
+};
+
+/// GeckoTerminal network ids suggested when none is given
+const SUGGESTED_NETWORKS: &str = "aptos, eth, solana, base, bsc";
+
+/// GeckoTerminal network ids accepted as the last word, same as the pool tools' schema
+const KNOWN_NETWORKS: [&str; 11] = [
+    "aptos",
+    "sui",
+    "eth",
+    "bsc",
+    "polygon_pos",
+    "avax",
+    "ftm",
+    "cro",
+    "arbitrum",
+    "base",
+    "solana",
+];
+
+/// `<query> [network]`: the last word is the network only when it is a known network id,
+/// so multi-word queries like "pepe coin" stay whole
+fn parse_pools_args(args: &str) -> Option<(String, Option<String>)> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    match words.as_slice() {
+        [] => None,
+        [query @ .., network]
+            if !query.is_empty() && KNOWN_NETWORKS.contains(&network.to_lowercase().as_str()) =>
+        {
+            Some((query.join(" "), Some(network.to_lowercase())))
+        }
+        words => Some((words.join(" "), None)),
+    }
+}
+
+fn bold_regex() -> &'static Regex {
+    static BOLD_RE: OnceLock<Regex> = OnceLock::new();
+    BOLD_RE.get_or_init(|| Regex::new(r"\*\*(.+?)\*\*").unwrap())
+}
+
+fn code_regex() -> &'static Regex {
+    static CODE_RE: OnceLock<Regex> = OnceLock::new();
+    CODE_RE.get_or_init(|| Regex::new(r"`([^`]+)`").unwrap())
+}
+
+/// The search output is plain markdown written for the model, not HTML. Escape it and turn
+/// its bold and code spans into tags, which `utils::markdown_to_html` leaves alone, then
+/// let the shared converter handle the links.
+fn pools_markdown_to_html(text: &str) -> String {
+    let escaped = html::escape(text);
+    let text = bold_regex().replace_all(&escaped, "<b>$1</b>");
+    let text = code_regex().replace_all(&text, "<code>$1</code>");
+    utils::markdown_to_html(&text)
+}
+
+/// /pools <query> [network] — top pools matching a token symbol, name or address
+pub async fn handle_pools(
+    bot: Bot,
+    msg: Message,
+    args: &str,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some((query, network)) = parse_pools_args(args) else {
+        send_html_message(
+            msg,
+            bot,
+            format!(
+                "Usage: /pools &lt;query&gt; &lt;network&gt;, e.g. /pools APT aptos\n\nNetworks: {}",
+                SUGGESTED_NETWORKS
+            ),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let Some(network) = network else {
+        send_html_message(
+            msg,
+            bot,
+            format!(
+                "🌐 Which network should I search? Add it after the query, e.g. <code>/pools {} aptos</code>\n\nNetworks: {}",
+                html::escape(&query),
+                SUGGESTED_NETWORKS
+            ),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let _ = bot.send_chat_action(msg.chat.id, ChatAction::Typing).await;
+
+    let result = execute_search_pools(&serde_json::json!({
+        "query": query,
+        "network": network,
+    }))
+    .await;
+
+    let link_previews = !bot_deps
+        .command_settings
+        .link_previews_disabled(msg.chat.id.to_string());
+    send_long_html(msg, &bot, &pools_markdown_to_html(&result), link_previews).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pools_args() {
+        assert_eq!(parse_pools_args("  "), None);
+        assert_eq!(parse_pools_args("APT"), Some(("APT".to_string(), None)));
+        assert_eq!(
+            parse_pools_args("wrapped eth Solana"),
+            Some(("wrapped eth".to_string(), Some("solana".to_string())))
+        );
+        assert_eq!(
+            parse_pools_args("pepe coin"),
+            Some(("pepe coin".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn test_pools_markdown_to_html() {
+        assert_eq!(
+            pools_markdown_to_html(
+                "**1. <APT>** `0x1` [Pool](https://www.geckoterminal.com/aptos)"
+            ),
+            "<b>1. &lt;APT&gt;</b> <code>0x1</code> <a href=\"https://www.geckoterminal.com/aptos\">Pool</a>\n"
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! /price: a token's USD price straight from the token list, without an AI round-trip.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use teloxide::{
+    prelude::*,
+    types::{ChatAction, Message},
+    utils::html,
+};
+
+use crate::{
+    ai::actions::{format_large_number, format_price},
+    dependencies::BotDependencies,
+    market_data::handler::fetch_search_pools,
+    panora::dto::Token,
+    utils::{send_html_message, send_message},
+};
+
+/// APT is looked up as the native coin, not whichever token list entry matches "APT" first
+const APT_COIN_TYPE: &str = "0x1::aptos_coin::AptosCoin";
+
+#[derive(Debug, PartialEq)]
+struct DayContext {
+    change_pct: f64,
+    volume_usd: f64,
+}
+
+fn clean_symbol(symbol: &str) -> String {
+    symbol.replace('\u{fe0f}', "").to_lowercase()
+}
+
+fn is_apt(symbol: &str) -> bool {
+    matches!(symbol.to_lowercase().as_str(), "apt" | "aptos")
+}
+
+/// Distinct tradable tokens using `symbol`, across both Panora lists
+async fn symbol_matches(symbol: &str, bot_deps: &BotDependencies) -> usize {
+    let wanted = clean_symbol(symbol);
+    let mut lists = bot_deps
+        .panora
+        .get_panora_token_list()
+        .await
+        .unwrap_or_default();
+    lists.extend(
+        bot_deps
+            .panora
+            .get_panora_token_list_non_bonding()
+            .await
+            .unwrap_or_default(),
+    );
+
+    lists
+        .iter()
+        .filter(|t| !t.is_banned && clean_symbol(&t.panora_symbol) == wanted)
+        .map(|t| t.fa_address.to_lowercase())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// 24h change and volume from the deepest GeckoTerminal pool with the token as its base
+fn day_context(data: &serde_json::Value, addresses: &[&str]) -> Option<DayContext> {
+    let pools = data.get("data").and_then(|d| d.as_array())?;
+    let number = |value: Option<&serde_json::Value>| {
+        value
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<f64>().ok())
+    };
+
+    pools
+        .iter()
+        .filter_map(|pool| {
+            let base_id = pool
+                .pointer("/relationships/base_token/data/id")
+                .and_then(|v| v.as_str())?;
+            // GeckoTerminal token ids are "<network>_<address>"
+            let base_address = base_id.split_once('_').map(|(_, a)| a).unwrap_or(base_id);
+            if !addresses
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(base_address))
+            {
+                return None;
+            }
+
+            let attributes = pool.get("attributes")?;
+            let change_pct = number(attributes.pointer("/price_change_percentage/h24"))?;
+            let volume_usd = number(attributes.pointer("/volume_usd/h24")).unwrap_or(0.0);
+            let reserve = number(attributes.get("reserve_in_usd")).unwrap_or(0.0);
+            Some((
+                DayContext {
+                    change_pct,
+                    volume_usd,
+                },
+                reserve,
+            ))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(context, _)| context)
+}
+
+async fn fetch_day_context(token: &Token, address: &str) -> Option<DayContext> {
+    let mut addresses = vec![token.fa_address.as_str(), address];
+    if let Some(token_address) = token.token_address.as_deref() {
+        addresses.push(token_address);
+    }
+
+    match fetch_search_pools(address, Some("aptos"), 1).await {
+        Ok(data) => day_context(&data, &addresses),
+        Err(e) => {
+            log::warn!("24h context for {} unavailable: {}", token.symbol, e);
+            None
+        }
+    }
+}
+
+fn format_price_reply(
+    token: &Token,
+    address: &str,
+    usd_price: f64,
+    day: Option<&DayContext>,
+    matches: usize,
+) -> String {
+    let mut text = format!(
+        "💲 <b>{}</b> ({})\n\nPrice: <b>${}</b>",
+        html::escape(&token.symbol),
+        html::escape(&token.name),
+        format_price(&usd_price.to_string())
+    );
+
+    if let Some(day) = day {
+        let arrow = if day.change_pct >= 0.0 {
+            "📈"
+        } else {
+            "📉"
+        };
+        text.push_str(&format!(
+            "\n24h: {} {:+.2}% · Volume ${}",
+            arrow,
+            day.change_pct,
+            format_large_number(&day.volume_usd.to_string())
+        ));
+    }
+
+    text.push_str(&format!("\n\n<code>{}</code>", html::escape(address)));
+
+    if matches > 1 {
+        text.push_str(&format!(
+            "\n\n⚠️ {} tokens use the symbol {}; this is the one listed first. Compare them with /pools {} aptos",
+            matches,
+            html::escape(&token.symbol),
+            html::escape(&token.symbol)
+        ));
+    }
+    text
+}
+
+/// /price <symbol> — USD price of a token, with 24h change when a pool reports it
+pub async fn handle_price(
+    bot: Bot,
+    msg: Message,
+    symbol: &str,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let symbol = symbol.trim().trim_start_matches('$');
+    if symbol.is_empty() {
+        send_message(
+            msg,
+            bot,
+            "Usage: /price <symbol>, e.g. /price APT".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let _ = bot.send_chat_action(msg.chat.id, ChatAction::Typing).await;
+
+    let lookup = if is_apt(symbol) { "APT" } else { symbol };
+    let token = match bot_deps.panora.get_token_by_symbol(lookup).await {
+        Ok(token) => token,
+        Err(e) => {
+            log::info!("/price lookup for {} failed: {}", symbol, e);
+            send_html_message(
+                msg,
+                bot,
+                format!(
+                    "❌ No token with the symbol <b>{}</b> is listed. Try /pools {} aptos to search DEX pools.",
+                    html::escape(symbol),
+                    html::escape(symbol)
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let address = if is_apt(symbol) {
+        APT_COIN_TYPE.to_string()
+    } else {
+        token
+            .token_address
+            .clone()
+            .unwrap_or_else(|| token.fa_address.clone())
+    };
+
+    let Some(usd_price) = token
+        .usd_price
+        .as_deref()
+        .and_then(|price| price.parse::<f64>().ok())
+    else {
+        send_html_message(
+            msg,
+            bot,
+            format!(
+                "⚠️ <b>{}</b> has no USD price right now. Try /pools {} aptos for on-chain pool prices.",
+                html::escape(&token.symbol),
+                html::escape(symbol)
+            ),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let matches = if is_apt(symbol) {
+        1
+    } else {
+        symbol_matches(symbol, &bot_deps).await
+    };
+    let day = fetch_day_context(&token, &address).await;
+
+    send_html_message(
+        msg,
+        bot,
+        format_price_reply(&token, &address, usd_price, day.as_ref(), matches),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_context_uses_deepest_base_pool() {
+        let data = serde_json::json!({
+            "data": [
+                {
+                    "attributes": {
+                        "reserve_in_usd": "1000",
+                        "price_change_percentage": { "h24": "5.0" },
+                        "volume_usd": { "h24": "10" }
+                    },
+                    "relationships": { "base_token": { "data": { "id": "aptos_0xabc" } } }
+                },
+                {
+                    "attributes": {
+                        "reserve_in_usd": "90000",
+                        "price_change_percentage": { "h24": "-2.5" },
+                        "volume_usd": { "h24": "12345" }
+                    },
+                    "relationships": { "base_token": { "data": { "id": "aptos_0xABC" } } }
+                },
+                {
+                    "attributes": {
+                        "reserve_in_usd": "999999",
+                        "price_change_percentage": { "h24": "40" },
+                        "volume_usd": { "h24": "1" }
+                    },
+                    "relationships": { "base_token": { "data": { "id": "aptos_0xother" } } }
+                }
+            ]
+        });
+
+        assert_eq!(
+            day_context(&data, &["0xabc"]),
+            Some(DayContext {
+                change_pct: -2.5,
+                volume_usd: 12345.0
+            })
+        );
+        assert_eq!(day_context(&data, &["0xnone"]), None);
+    }
+}
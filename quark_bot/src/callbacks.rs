@@ -3,13 +3,23 @@
 use crate::ai::group_vector_store::{
     delete_file_from_group_vector_store, delete_group_vector_store, list_group_files_with_names,
 };
-use crate::ai::moderation::dto::{ModerationSettings, ModerationState};
+use crate::ai::moderation::dto::{ModerationAction, ModerationSettings, ModerationState};
 use crate::ai::vector_store::{
     delete_file_from_vector_store, delete_vector_store, list_user_files_with_names,
 };
-use crate::dao::handler::{handle_dao_preference_callback, handle_disable_notifications_callback};
+use crate::assets::handler::{
+    handle_upload_session_callback, user_library_file_buttons, user_library_file_list,
+};
+use crate::context_note::handler::handle_context_note_callback;
+use crate::dao::handler::{
+    handle_dao_preference_callback, handle_disable_notifications_callback,
+    handle_my_votes_callback,
+};
+use crate::context_note::NoteScope;
 use crate::dependencies::BotDependencies;
+use crate::group::members::handle_members_callback;
 use crate::filters::handler::handle_filters_callback;
+use crate::repeat_guard::handler::handle_repeat_callback;
 use crate::scheduled_payments::callbacks::handle_scheduled_payments_callback;
 use crate::scheduled_prompts::callbacks::handle_scheduled_prompts_callback;
 use crate::sponsor::handler::handle_sponsor_settings_callback;
@@ -18,9 +28,11 @@ use crate::utils::{self, send_html_message};
 use crate::welcome::handler::handle_welcome_settings_callback;
 use anyhow::Result;
 use teloxide::sugar::request::RequestReplyExt;
+use teloxide::utils::command::BotCommands;
 use teloxide::{
     prelude::*,
     types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+    utils::html,
 };
 
 pub async fn handle_callback_query(
@@ -37,9 +49,65 @@ pub async fn handle_callback_query(
     if let Some(data) = &query.data {
         let user_id = query.from.id.0 as i64;
 
-        if data.starts_with("delete_file:") {
-            let file_id = data.strip_prefix("delete_file:").unwrap();
-
+        if let Some(file_id) = data.strip_prefix("delete_file:") {
+            // First tap only asks; the file is removed on `confirm_delete_file:<id>`
+            let Some(file) = list_user_files_with_names(user_id, bot_deps.clone()).await?
+                .into_iter()
+                .find(|f| f.id == file_id)
+            else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ File not found. Please reopen the Document Library.")
+                    .await?;
+                return Ok(());
+            };
+            bot.answer_callback_query(query.id).await?;
+            if let Some(MaybeInaccessibleMessage::Regular(message)) = &query.message {
+                let size = file
+                    .bytes
+                    .map(|bytes| format!(" ({})", utils::format_file_size(bytes)))
+                    .unwrap_or_default();
+                let kb = InlineKeyboardMarkup::new(vec![vec![
+                    InlineKeyboardButton::callback(
+                        "🗑️ Delete",
+                        format!("confirm_delete_file:{}", file.id),
+                    ),
+                    InlineKeyboardButton::callback("↩️ Cancel", "open_document_library"),
+                ]]);
+                bot.edit_message_text(
+                    message.chat.id,
+                    message.id,
+                    format!(
+                        "⚠️ <b>Delete this file?</b>\n\n{}  <b>{}</b>{}\n\n<i>It is removed from your document library permanently.</i>",
+                        utils::get_file_icon(&file.name),
+                        utils::clean_filename(&file.name),
+                        size
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(kb)
+                .await?;
+            }
+        } else if data == "clear_all_files" {
+            bot.answer_callback_query(query.id).await?;
+            if let Some(MaybeInaccessibleMessage::Regular(message)) = &query.message {
+                let count = bot_deps.user_convos.get_files(user_id).len();
+                let kb = InlineKeyboardMarkup::new(vec![vec![
+                    InlineKeyboardButton::callback("🗑️ Clear All", "confirm_clear_all_files"),
+                    InlineKeyboardButton::callback("↩️ Cancel", "open_document_library"),
+                ]]);
+                bot.edit_message_text(
+                    message.chat.id,
+                    message.id,
+                    format!(
+                        "⚠️ <b>Clear all {} files?</b>\n\n<i>Your whole document library is deleted permanently.</i>",
+                        count
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(kb)
+                .await?;
+            }
+        } else if let Some(file_id) = data.strip_prefix("confirm_delete_file:") {
             if let Some(vector_store_id) = bot_deps.user_convos.get_vector_store_id(user_id) {
                 match delete_file_from_vector_store(
                     user_id,
@@ -51,8 +119,10 @@ pub async fn handle_callback_query(
                 {
                     Ok(_) => {
                         bot.answer_callback_query(query.id.clone()).await?;
+                        let kb_name =
+                            html::escape(&bot_deps.user_convos.active_knowledge_base_name(user_id));
 
-                        match list_user_files_with_names(user_id, bot_deps.clone()) {
+                        match list_user_files_with_names(user_id, bot_deps.clone()).await {
                             Ok(files) => {
                                 if files.is_empty() {
                                     if let Some(MaybeInaccessibleMessage::Regular(message)) =
@@ -74,42 +144,16 @@ pub async fn handle_callback_query(
                                             .await?;
                                     }
                                 } else {
-                                    let file_list = files
-                                        .iter()
-                                        .map(|file| {
-                                            let icon = utils::get_file_icon(&file.name);
-                                            let clean_name = utils::clean_filename(&file.name);
-                                            format!("{}  <b>{}</b>", icon, clean_name)
-                                        })
-                                        .collect::<Vec<_>>()
-                                        .join("\n");
+                                    let file_list = user_library_file_list(&files);
                                     let response = format!(
-                                        "🗂️ <b>Your Document Library</b> ({} files)\n\n{}\n\n💡 <i>Tap any button below to manage your files</i>",
+                                        "🗂️ <b>Your Document Library</b> · 📚 {} ({} files)\n\n{}\n\n💡 <i>Tap any button below to manage your files</i>",
+                                        kb_name,
                                         files.len(),
                                         file_list
                                     );
-                                    let mut keyboard_rows = Vec::new();
-                                    for file in &files {
-                                        let clean_name = utils::clean_filename(&file.name);
-                                        let button_text = if clean_name.len() > 25 {
-                                            format!("🗑️ {}", &clean_name[..22].trim_end())
-                                        } else {
-                                            format!("🗑️ {}", clean_name)
-                                        };
-                                        let delete_button = InlineKeyboardButton::callback(
-                                            button_text,
-                                            format!("delete_file:{}", file.id),
-                                        );
-                                        keyboard_rows.push(vec![delete_button]);
-                                    }
-                                    if files.len() > 1 {
-                                        let clear_all_button = InlineKeyboardButton::callback(
-                                            "🗑️ Clear All Files",
-                                            "clear_all_files",
-                                        );
-                                        keyboard_rows.push(vec![clear_all_button]);
-                                    }
-                                    let keyboard = InlineKeyboardMarkup::new(keyboard_rows);
+                                    let keyboard = InlineKeyboardMarkup::new(
+                                        user_library_file_buttons(&files),
+                                    );
 
                                     if let Some(MaybeInaccessibleMessage::Regular(message)) =
                                         &query.message
@@ -154,7 +198,7 @@ pub async fn handle_callback_query(
                     .text("❌ No document library found. Please reopen the Document Library.")
                     .await?;
             }
-        } else if data == "clear_all_files" {
+        } else if data == "confirm_clear_all_files" {
             match delete_vector_store(user_id, bot_deps.clone()).await {
                 Ok(_) => {
                     bot.answer_callback_query(query.id).await?;
@@ -182,6 +226,8 @@ pub async fn handle_callback_query(
                         .await?;
                 }
             }
+        } else if data.starts_with("appeal:") {
+            crate::ai::moderation::appeal::handle_appeal_callback(bot, query, bot_deps).await?;
         } else if data.starts_with("unmute:") {
             // Handle unmute callback - admin only
             let user_id_str = data.strip_prefix("unmute:").unwrap();
@@ -287,6 +333,65 @@ pub async fn handle_callback_query(
                     }
                 }
             }
+        } else if data == "help_show_all" {
+            // Expand the context-aware /help listing to every command
+            if let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message {
+                bot.edit_message_text(
+                    m.chat.id,
+                    m.id,
+                    quark_core::helpers::bot_commands::Command::descriptions().to_string(),
+                )
+                .await?;
+            }
+            bot.answer_callback_query(query.id).await?;
+        } else if data.starts_with("unban:") {
+            // Handle unban callback - admin only
+            let target_user_id: i64 = data
+                .strip_prefix("unban:")
+                .and_then(|id| id.parse().ok())
+                .unwrap_or(0);
+
+            if let Some(MaybeInaccessibleMessage::Regular(message)) = &query.message {
+                let is_admin = utils::is_admin(&bot, message.chat.id, query.from.id).await;
+
+                if !is_admin {
+                    bot.answer_callback_query(query.id)
+                        .text("❌ Only administrators can use this action")
+                        .await?;
+                    return Ok(());
+                }
+
+                match bot
+                    .unban_chat_member(
+                        message.chat.id,
+                        teloxide::types::UserId(target_user_id as u64),
+                    )
+                    .only_if_banned(true)
+                    .await
+                {
+                    Ok(_) => {
+                        if let Err(e) = bot.delete_message(message.chat.id, message.id).await {
+                            log::warn!("Failed to delete moderation notification: {}", e);
+                        }
+
+                        bot.answer_callback_query(query.id)
+                            .text("✅ User unbanned successfully")
+                            .await?;
+
+                        log::info!(
+                            "Admin {} unbanned user {}",
+                            query.from.id,
+                            target_user_id
+                        );
+                    }
+                    Err(e) => {
+                        log::error!("Failed to unban user {}: {}", target_user_id, e);
+                        bot.answer_callback_query(query.id)
+                            .text("❌ Failed to unban user")
+                            .await?;
+                    }
+                }
+            }
         } else if data.starts_with("select_chat_model:")
             || data.starts_with("set_temperature:")
             || data.starts_with("set_gpt5_mode:")
@@ -294,6 +399,8 @@ pub async fn handle_callback_query(
             || data.starts_with("set_gpt5_verbosity:")
             || data.starts_with("set_reasoning:")
             || data.starts_with("set_verbosity:")
+            || data.starts_with("set_persona:")
+            || data == "open_persona"
             || data == "continue_to_verbosity"
             || data == "back_to_model_selection"
             || data == "back_to_reasoning"
@@ -329,7 +436,14 @@ pub async fn handle_callback_query(
                     .await?;
                 }
             }
-        } else if data == "open_my_settings" {
+        } else if data == "open_my_settings"
+            || data == "toggle_show_sources"
+            || data == "toggle_table_images"
+            || data == "toggle_streaming"
+            || data == "toggle_dm_plain_text"
+            || data == "toggle_repeat_check"
+            || data == "toggle_user_link_previews"
+        {
             // Render user's current settings using existing logic
             if let Some(message) = &query.message {
                 if let MaybeInaccessibleMessage::Regular(m) = message {
@@ -337,7 +451,68 @@ pub async fn handle_callback_query(
                     let user = query.from.username.clone();
                     let id = query.from.id;
                     if let Some(username) = user {
-                        let prefs = bot_deps.user_model_prefs.get_preferences(&username);
+                        let mut prefs = bot_deps.user_model_prefs.get_preferences(&username);
+                        if data == "toggle_show_sources"
+                            || data == "toggle_table_images"
+                            || data == "toggle_streaming"
+                        {
+                            if data == "toggle_show_sources" {
+                                prefs.show_sources = !prefs.show_sources;
+                            } else if data == "toggle_table_images" {
+                                prefs.table_images = !prefs.table_images;
+                            } else {
+                                prefs.streaming = !prefs.streaming;
+                            }
+                            if let Err(e) = bot_deps
+                                .user_model_prefs
+                                .set_preferences(&username, &prefs)
+                            {
+                                log::error!("Failed to save display preference: {}", e);
+                                bot.answer_callback_query(query.id)
+                                    .text("❌ Failed to update preference")
+                                    .await?;
+                                return Ok(());
+                            }
+                        }
+                        let dm_user_id = id.0 as i64;
+                        if data == "toggle_dm_plain_text" {
+                            let enabled = !bot_deps
+                                .dm_onboarding
+                                .is_plain_text_prompt_enabled(dm_user_id);
+                            if let Err(e) = bot_deps
+                                .dm_onboarding
+                                .set_plain_text_prompt(dm_user_id, enabled)
+                            {
+                                log::error!("Failed to save plain DM preference: {}", e);
+                            }
+                        }
+                        let plain_dm_enabled = bot_deps
+                            .dm_onboarding
+                            .is_plain_text_prompt_enabled(dm_user_id);
+                        if data == "toggle_repeat_check" {
+                            let enabled = !bot_deps.repeat_guard.is_enabled_for(dm_user_id);
+                            if let Err(e) =
+                                bot_deps.repeat_guard.set_enabled_for(dm_user_id, enabled)
+                            {
+                                log::error!("Failed to save repeat check preference: {}", e);
+                            }
+                        }
+                        let repeat_check_enabled = bot_deps.repeat_guard.is_enabled_for(dm_user_id);
+                        if data == "toggle_user_link_previews" {
+                            if let Err(e) = bot_deps
+                                .command_settings
+                                .toggle_link_previews(dm_user_id.to_string())
+                            {
+                                log::error!("Failed to save link preview preference: {}", e);
+                            }
+                        }
+                        let link_previews_disabled = bot_deps
+                            .command_settings
+                            .link_previews_disabled(dm_user_id.to_string());
+                        let has_context_note = bot_deps
+                            .context_notes
+                            .get(NoteScope::User(dm_user_id))
+                            .is_some();
                         // Resolve selected token from user prefs; fall back to default
                         let token_label = if let Some(token) = bot_deps
                             .payment
@@ -367,22 +542,92 @@ pub async fn handle_callback_query(
                             .summarization_settings
                             .get_effective_prefs(&user_id_str, group_id);
                         let sum_status = if sum_prefs.enabled { "On" } else { "Off" };
+                        let sources_text = if prefs.show_sources { "On" } else { "Off" };
+                        let plain_dm_text = if plain_dm_enabled { "On" } else { "Off" };
+                        let repeat_check_text = if repeat_check_enabled { "On" } else { "Off" };
 
                         let text = format!(
-                            "⚙️ <b>Your Settings</b>\n\n🤖 Model: {}\n🧠 Reasoning: {}\n🗣️ Verbosity: {}\n💳 Token: <code>{}</code>\n🧾 Summarizer: {}\n📏 Threshold: {} tokens",
+                            "⚙️ <b>Your Settings</b>\n\n🤖 Model: {}\n🧠 Reasoning: {}\n🗣️ Verbosity: {}\n🎭 Persona: {}\n📚 Web Sources: {}\n📊 Table Images: {}\n⚡ Streaming: {}\n💬 Plain DM → AI: {}\n🔁 Repeat Check: {}\n🔗 Link Previews: {}\n📝 Context Note: {}\n💳 Token: <code>{}</code>\n🧾 Summarizer: {}\n📏 Threshold: {} tokens",
                             prefs.chat_model.to_display_string(),
                             reasoning_text,
                             verbosity_text,
+                            prefs.persona.to_display_string(),
+                            sources_text,
+                            if prefs.table_images { "On" } else { "Off" },
+                            if prefs.streaming { "On" } else { "Off" },
+                            plain_dm_text,
+                            repeat_check_text,
+                            if link_previews_disabled { "Off" } else { "On" },
+                            if has_context_note { "Set" } else { "None" },
                             token_label,
                             sum_status,
                             sum_prefs.token_limit
                         );
 
-                        let keyboard =
-                            InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+                        let sources_button = if prefs.show_sources {
+                            "📚 Hide Web Sources"
+                        } else {
+                            "📚 Show Web Sources"
+                        };
+
+                        let keyboard = InlineKeyboardMarkup::new(vec![
+                            vec![InlineKeyboardButton::callback(
+                                "🎭 Persona",
+                                "open_persona",
+                            )],
+                            vec![InlineKeyboardButton::callback(
+                                sources_button,
+                                "toggle_show_sources",
+                            )],
+                            vec![InlineKeyboardButton::callback(
+                                if prefs.table_images {
+                                    "📊 Table Images: Turn Off"
+                                } else {
+                                    "📊 Table Images: Turn On"
+                                },
+                                "toggle_table_images",
+                            )],
+                            vec![InlineKeyboardButton::callback(
+                                if prefs.streaming {
+                                    "⚡ Streaming: Turn Off"
+                                } else {
+                                    "⚡ Streaming: Turn On"
+                                },
+                                "toggle_streaming",
+                            )],
+                            vec![InlineKeyboardButton::callback(
+                                if plain_dm_enabled {
+                                    "💬 Plain DM → AI: Turn Off"
+                                } else {
+                                    "💬 Plain DM → AI: Turn On"
+                                },
+                                "toggle_dm_plain_text",
+                            )],
+                            vec![InlineKeyboardButton::callback(
+                                if repeat_check_enabled {
+                                    "🔁 Repeat Check: Turn Off"
+                                } else {
+                                    "🔁 Repeat Check: Turn On"
+                                },
+                                "toggle_repeat_check",
+                            )],
+                            vec![InlineKeyboardButton::callback(
+                                if link_previews_disabled {
+                                    "🔗 Link Previews: Turn On"
+                                } else {
+                                    "🔗 Link Previews: Turn Off"
+                                },
+                                "toggle_user_link_previews",
+                            )],
+                            vec![InlineKeyboardButton::callback(
+                                "📝 Context Note",
+                                "ctxnote_open:user",
+                            )],
+                            vec![InlineKeyboardButton::callback(
                                 "↩️ Back to Settings",
                                 "back_to_user_settings",
-                            )]]);
+                            )],
+                        ]);
 
                         bot.edit_message_text(m.chat.id, m.id, text)
                             .parse_mode(ParseMode::Html)
@@ -437,8 +682,9 @@ pub async fn handle_callback_query(
         } else if data == "open_document_library" {
             // Open the user's Document Library within /usersettings (DM context)
             let user_id = query.from.id.0 as i64;
+            let kb_name = html::escape(&bot_deps.user_convos.active_knowledge_base_name(user_id));
 
-            match list_user_files_with_names(user_id, bot_deps.clone()) {
+            match list_user_files_with_names(user_id, bot_deps.clone()).await {
                 Ok(files) => {
                     use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 
@@ -454,45 +700,21 @@ pub async fn handle_callback_query(
                             )],
                         ]);
                         (
-                            "📁 <b>Your Document Library</b>\n\n<i>No files uploaded yet</i>\n\n💡 Use the button below to upload your first documents.".to_string(),
+                            format!(
+                                "📁 <b>Your Document Library</b> · 📚 {}\n\n<i>No files uploaded yet</i>\n\n💡 Use the button below to upload your first documents. Switch knowledge bases with /usekb.",
+                                kb_name
+                            ),
                             kb,
                         )
                     } else {
-                        let file_list = files
-                            .iter()
-                            .map(|file| {
-                                let icon = utils::get_file_icon(&file.name);
-                                let clean_name = utils::clean_filename(&file.name);
-                                format!("{}  <b>{}</b>", icon, clean_name)
-                            })
-                            .collect::<Vec<_>>()
-                            .join("\n");
+                        let file_list = user_library_file_list(&files);
                         let response = format!(
-                            "🗂️ <b>Your Document Library</b> ({} files)\n\n{}\n\n💡 <i>Tap any button below to manage your files</i>",
+                            "🗂️ <b>Your Document Library</b> · 📚 {} ({} files)\n\n{}\n\n💡 <i>Tap any button below to manage your files</i>",
+                            kb_name,
                             files.len(),
                             file_list
                         );
-                        let mut keyboard_rows = Vec::new();
-                        for file in &files {
-                            let clean_name = utils::clean_filename(&file.name);
-                            let button_text = if clean_name.len() > 25 {
-                                format!("🗑️ {}", &clean_name[..22].trim_end())
-                            } else {
-                                format!("🗑️ {}", clean_name)
-                            };
-                            let delete_button = InlineKeyboardButton::callback(
-                                button_text,
-                                format!("delete_file:{}", file.id),
-                            );
-                            keyboard_rows.push(vec![delete_button]);
-                        }
-                        if files.len() > 1 {
-                            let clear_all_button = InlineKeyboardButton::callback(
-                                "🗑️ Clear All Files",
-                                "clear_all_files",
-                            );
-                            keyboard_rows.push(vec![clear_all_button]);
-                        }
+                        let mut keyboard_rows = user_library_file_buttons(&files);
                         // Upload + Back controls
                         keyboard_rows.push(vec![InlineKeyboardButton::callback(
                             "📎 Upload Files",
@@ -546,6 +768,10 @@ pub async fn handle_callback_query(
                             "💳 Payment Settings",
                             "open_payment_settings",
                         )],
+                        vec![InlineKeyboardButton::callback(
+                            "🔔 Notifications",
+                            "open_notifications",
+                        )],
                         vec![InlineKeyboardButton::callback(
                             "📁 Document Library",
                             "open_document_library",
@@ -558,6 +784,10 @@ pub async fn handle_callback_query(
                             "🧾 Summarization Settings",
                             "open_summarization_settings",
                         )],
+                        vec![InlineKeyboardButton::callback(
+                            "🖼️ Max Images per Request",
+                            "open_user_max_images",
+                        )],
                         vec![InlineKeyboardButton::callback(
                             "↩️ Close",
                             "user_settings_close",
@@ -569,6 +799,27 @@ pub async fn handle_callback_query(
                         .await?;
                 }
             }
+        } else if data == "open_history_settings"
+            || data.starts_with("history_limit:")
+            || data.starts_with("history_chars:")
+        {
+            crate::message_history::settings::handle_history_settings_callback(
+                bot, query, bot_deps,
+            )
+            .await?;
+        } else if data.starts_with("flagged_") {
+            crate::ai::moderation::flagged_log::handle_flagged_callback(bot, query, bot_deps)
+                .await?;
+        } else if data == "open_notifications" || data.starts_with("notif_toggle:") {
+            crate::notification_prefs::handler::handle_notifications_callback(
+                bot, query, bot_deps,
+            )
+            .await?;
+        } else if data == "open_user_max_images" || data.starts_with("user_max_images:") {
+            crate::command_settings::handler::handle_user_max_images_callback(
+                bot, query, bot_deps,
+            )
+            .await?;
         } else if data == "user_settings_close" {
             if let Some(message) = &query.message {
                 if let MaybeInaccessibleMessage::Regular(m) = message {
@@ -628,8 +879,9 @@ pub async fn handle_callback_query(
                                 let mut keyboard_rows = Vec::new();
                                 for file in &files {
                                     let clean_name = utils::clean_filename(&file.name);
-                                    let button_text = if clean_name.len() > 25 {
-                                        format!("🗑️ {}", &clean_name[..22].trim_end())
+                                    let button_text = if clean_name.chars().count() > 25 {
+                                        let cut: String = clean_name.chars().take(22).collect();
+                                        format!("🗑️ {}", cut.trim_end())
                                     } else {
                                         format!("🗑️ {}", clean_name)
                                     };
@@ -740,8 +992,9 @@ pub async fn handle_callback_query(
                                             let mut keyboard_rows = Vec::new();
                                             for file in &files {
                                                 let clean_name = utils::clean_filename(&file.name);
-                                                let button_text = if clean_name.len() > 25 {
-                                                    format!("🗑️ {}", &clean_name[..22].trim_end())
+                                                let button_text = if clean_name.chars().count() > 25 {
+                                                    let cut: String = clean_name.chars().take(22).collect();
+                                                    format!("🗑️ {}", cut.trim_end())
                                                 } else {
                                                     format!("🗑️ {}", clean_name)
                                                 };
@@ -1192,6 +1445,10 @@ pub async fn handle_callback_query(
                             "📋 Summarization Settings",
                             "open_group_summarization_settings",
                         )],
+                        vec![InlineKeyboardButton::callback(
+                            "🧠 History Settings",
+                            "open_history_settings",
+                        )],
                         vec![InlineKeyboardButton::callback(
                             "🔄 Migrate Group ID",
                             "open_migrate_group_id",
@@ -1266,13 +1523,20 @@ pub async fn handle_callback_query(
             || data.starts_with("sponsor_cooldown_")
             || data == "sponsor_enable"
             || data == "sponsor_disable"
+            || data == "sponsor_toggle_remaining"
             || data == "sponsor_cancel_input"
         {
             // Handle sponsor settings callbacks
             handle_sponsor_settings_callback(bot, query, bot_deps).await?;
         } else if data == "open_command_settings"
             || data == "toggle_chat_commands"
+            || data == "toggle_pinned_context"
+            || data == "toggle_link_previews"
+            || data == "cycle_disclaimer"
+            || data == "toggle_prompt_safety"
             || data == "command_settings_back"
+            || data.starts_with("cmd_max_images:")
+            || data.starts_with("cmd_reply_cap:")
         {
             crate::command_settings::handler::handle_command_settings_callback(
                 bot, query, bot_deps,
@@ -1398,57 +1662,7 @@ pub async fn handle_callback_query(
                         .get_moderation_settings(m.chat.id.to_string())
                         .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
 
-                    let text = format!(
-                        concat!(
-                            "🛡️ <b>Moderation Settings</b>\n\n",
-                            "Sentinel: <b>{sentinel}</b>\n",
-                            "Custom Rules: <b>{allowed}</b> allowed, <b>{disallowed}</b> disallowed\n",
-                            "Updated: <i>{updated}</i>\n\n",
-                            "Choose an action below:"
-                        ),
-                        sentinel = if sentinel_on { "ON" } else { "OFF" },
-                        allowed = settings.allowed_items.len(),
-                        disallowed = settings.disallowed_items.len(),
-                        updated = settings.updated_at_unix_ms.to_string(),
-                    );
-
-                    let toggle_label = if sentinel_on {
-                        "🔕 Turn OFF Sentinel"
-                    } else {
-                        "🛡️ Turn ON Sentinel"
-                    };
-                    let toggle_cb = if sentinel_on {
-                        "mod_toggle_sentinel_off"
-                    } else {
-                        "mod_toggle_sentinel_on"
-                    };
-                    let kb = InlineKeyboardMarkup::new(vec![
-                        vec![InlineKeyboardButton::callback(toggle_label, toggle_cb)],
-                        vec![InlineKeyboardButton::callback(
-                            "📝 Start Moderation Wizard",
-                            "mod_settings_start",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "🧹 Reset Custom Rules",
-                            "mod_reset",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "✅ Show Allowed Rules",
-                            "mod_show_allowed",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "⛔ Show Disallowed Rules",
-                            "mod_show_disallowed",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "📜 Show Default Rules",
-                            "mod_show_defaults",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "↩️ Back",
-                            "back_to_group_settings",
-                        )],
-                    ]);
+                    let (text, kb) = moderation_settings_menu(sentinel_on, &settings);
                     bot.edit_message_text(m.chat.id, m.id, text)
                         .parse_mode(teloxide::types::ParseMode::Html)
                         .reply_markup(kb)
@@ -1466,6 +1680,10 @@ pub async fn handle_callback_query(
                             .await?;
                         return Ok(());
                     }
+                    // A manual toggle overrides any running snooze
+                    if let Err(e) = bot_deps.sentinel_snoozes.clear(m.chat.id) {
+                        log::error!("Failed to clear sentinel snooze: {}", e);
+                    }
                     if data == "mod_toggle_sentinel_on" {
                         bot_deps.sentinel.set_sentinel(m.chat.id.to_string(), true);
                         bot.answer_callback_query(query.id)
@@ -1478,64 +1696,13 @@ pub async fn handle_callback_query(
                             .await?;
                     }
                     // Refresh submenu
-                    // Reuse the same rendering path by simulating the branch
-                    // (duplicate minimal logic for clarity)
                     let sentinel_on = bot_deps.sentinel.get_sentinel(m.chat.id.to_string());
                     let settings = bot_deps
                         .moderation
                         .get_moderation_settings(m.chat.id.to_string())
                         .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
 
-                    let text = format!(
-                        concat!(
-                            "🛡️ <b>Moderation Settings</b>\n\n",
-                            "Sentinel: <b>{sentinel}</b>\n",
-                            "Custom Rules: <b>{allowed}</b> allowed, <b>{disallowed}</b> disallowed\n",
-                            "Updated: <i>{updated}</i>\n\n",
-                            "Choose an action below:"
-                        ),
-                        sentinel = if sentinel_on { "ON" } else { "OFF" },
-                        allowed = settings.allowed_items.len(),
-                        disallowed = settings.disallowed_items.len(),
-                        updated = settings.updated_at_unix_ms.to_string(),
-                    );
-                    let toggle_label = if sentinel_on {
-                        "🔕 Turn OFF Sentinel"
-                    } else {
-                        "🛡️ Turn ON Sentinel"
-                    };
-                    let toggle_cb = if sentinel_on {
-                        "mod_toggle_sentinel_off"
-                    } else {
-                        "mod_toggle_sentinel_on"
-                    };
-                    let kb = InlineKeyboardMarkup::new(vec![
-                        vec![InlineKeyboardButton::callback(toggle_label, toggle_cb)],
-                        vec![InlineKeyboardButton::callback(
-                            "📝 Start Moderation Wizard",
-                            "mod_settings_start",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "🧹 Reset Custom Rules",
-                            "mod_reset",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "✅ Show Allowed Rules",
-                            "mod_show_allowed",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "⛔ Show Disallowed Rules",
-                            "mod_show_disallowed",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "📜 Show Default Rules",
-                            "mod_show_defaults",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "↩️ Back",
-                            "back_to_group_settings",
-                        )],
-                    ]);
+                    let (text, kb) = moderation_settings_menu(sentinel_on, &settings);
                     bot.edit_message_text(m.chat.id, m.id, text)
                         .parse_mode(ParseMode::Html)
                         .reply_markup(kb)
@@ -1585,6 +1752,34 @@ pub async fn handle_callback_query(
                         .await?;
                 }
             }
+        } else if data.starts_with("mod_spamlim_") {
+            crate::spam_guard::handler::handle_spam_limit_callback(
+                bot.clone(),
+                query.clone(),
+                bot_deps.clone(),
+            )
+            .await?;
+        } else if data.starts_with("mod_lowbal_") {
+            crate::ai::sentinel::low_balance::handle_low_balance_callback(
+                bot.clone(),
+                query.clone(),
+                bot_deps.clone(),
+            )
+            .await?;
+        } else if data.starts_with("mod_links_") {
+            crate::ai::moderation::link_allowlist::handle_link_allowlist_callback(
+                bot.clone(),
+                query.clone(),
+                bot_deps.clone(),
+            )
+            .await?;
+        } else if data.starts_with("mod_ai_") {
+            crate::ai::moderation::rules_builder::handle_rules_builder_callback(
+                bot.clone(),
+                query.clone(),
+                bot_deps.clone(),
+            )
+            .await?;
         } else if data == "mod_skip_allowed" {
             // Skip Step 1 (Allowed) and move to Step 2
             if let Some(message) = &query.message {
@@ -1685,12 +1880,15 @@ pub async fn handle_callback_query(
                         let allowed = state.allowed_items.unwrap_or_default();
                         let disallowed: Vec<String> = vec![];
 
-                        let settings = ModerationSettings::from((
-                            allowed.clone(),
-                            disallowed.clone(),
-                            query.from.id.0 as i64,
-                            chrono::Utc::now().timestamp_millis(),
-                        ));
+                        // Only the custom rules change; action and pass reaction are kept
+                        let mut settings = bot_deps
+                            .moderation
+                            .get_moderation_settings(m.chat.id.to_string())
+                            .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+                        settings.allowed_items = allowed.clone();
+                        settings.disallowed_items = disallowed.clone();
+                        settings.updated_by_user_id = query.from.id.0 as i64;
+                        settings.updated_at_unix_ms = chrono::Utc::now().timestamp_millis();
                         bot_deps
                             .moderation
                             .set_or_update_moderation_settings(m.chat.id.to_string(), settings)?;
@@ -1744,66 +1942,28 @@ pub async fn handle_callback_query(
                     bot_deps
                         .moderation
                         .remove_moderation_state(m.chat.id.to_string())?;
-                    bot_deps.moderation.set_or_update_moderation_settings(
-                        m.chat.id.to_string(),
-                        ModerationSettings::from((vec![], vec![], 0, 0)),
-                    )?;
+                    // Keep the flag action and pass reaction; only the custom rules are reset
+                    let mut settings = bot_deps
+                        .moderation
+                        .get_moderation_settings(m.chat.id.to_string())
+                        .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+                    settings.allowed_items.clear();
+                    settings.disallowed_items.clear();
+                    settings.updated_by_user_id = query.from.id.0 as i64;
+                    settings.updated_at_unix_ms = chrono::Utc::now().timestamp_millis();
+                    bot_deps
+                        .moderation
+                        .set_or_update_moderation_settings(m.chat.id.to_string(), settings)?;
                     bot.answer_callback_query(query.id)
                         .text("🧹 Custom rules reset")
                         .await?;
                     // Re-open moderation settings view
                     let sentinel_on = bot_deps.sentinel.get_sentinel(m.chat.id.to_string());
-                    let text = format!(
-                        concat!(
-                            "🛡️ <b>Moderation Settings</b>\n\n",
-                            "Sentinel: <b>{sentinel}</b>\n",
-                            "Custom Rules: <b>0</b> allowed, <b>0</b> disallowed\n",
-                            "Updated: <i>(none)</i>\n\n",
-                            "Choose an action below:"
-                        ),
-                        sentinel = if sentinel_on { "ON" } else { "OFF" },
-                    );
-                    let toggle_label = if sentinel_on {
-                        "🔕 Turn OFF Sentinel"
-                    } else {
-                        "🛡️ Turn ON Sentinel"
-                    };
-                    let toggle_cb = if sentinel_on {
-                        "mod_toggle_sentinel_off"
-                    } else {
-                        "mod_toggle_sentinel_on"
-                    };
-                    let kb = InlineKeyboardMarkup::new(vec![
-                        vec![InlineKeyboardButton::callback(toggle_label, toggle_cb)],
-                        vec![InlineKeyboardButton::callback(
-                            "📝 Start Moderation Wizard",
-                            "mod_settings_start",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "🧹 Reset Custom Rules",
-                            "mod_reset",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "✅ Show Allowed Rules",
-                            "mod_show_allowed",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "⛔ Show Disallowed Rules",
-                            "mod_show_disallowed",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "📜 Show Default Rules",
-                            "mod_show_defaults",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "🎯 Sponsor Settings",
-                            "open_sponsor_settings",
-                        )],
-                        vec![InlineKeyboardButton::callback(
-                            "↩️ Back",
-                            "back_to_group_settings",
-                        )],
-                    ]);
+                    let settings = bot_deps
+                        .moderation
+                        .get_moderation_settings(m.chat.id.to_string())
+                        .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+                    let (text, kb) = moderation_settings_menu(sentinel_on, &settings);
                     bot.edit_message_text(m.chat.id, m.id, text)
                         .parse_mode(ParseMode::Html)
                         .reply_markup(kb)
@@ -1848,6 +2008,118 @@ If you have questions, ask an admin before posting.
                         .await?;
                 }
             }
+        } else if data == "mod_toggle_pass_reaction" {
+            // React to messages that pass /report instead of staying silent
+            if let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message {
+                let is_admin = utils::is_admin(&bot, m.chat.id, query.from.id).await;
+                if !is_admin {
+                    bot.answer_callback_query(query.id)
+                        .text("❌ Only administrators can manage moderation settings")
+                        .await?;
+                    return Ok(());
+                }
+
+                let mut settings = bot_deps
+                    .moderation
+                    .get_moderation_settings(m.chat.id.to_string())
+                    .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+                settings.react_on_pass = !settings.react_on_pass;
+                settings.updated_by_user_id = query.from.id.0 as i64;
+                settings.updated_at_unix_ms = chrono::Utc::now().timestamp_millis();
+                let enabled = settings.react_on_pass;
+                bot_deps
+                    .moderation
+                    .set_or_update_moderation_settings(m.chat.id.to_string(), settings)?;
+
+                bot.answer_callback_query(query.id)
+                    .text(if enabled {
+                        "👌 Clean /report checks will now get a reaction"
+                    } else {
+                        "🔕 Clean /report checks will stay silent"
+                    })
+                    .await?;
+
+                let sentinel_on = bot_deps.sentinel.get_sentinel(m.chat.id.to_string());
+                let settings = bot_deps
+                    .moderation
+                    .get_moderation_settings(m.chat.id.to_string())
+                    .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+                let (text, kb) = moderation_settings_menu(sentinel_on, &settings);
+                bot.edit_message_text(m.chat.id, m.id, text)
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(kb)
+                    .await?;
+            }
+        } else if data == "mod_action_menu" || data.starts_with("mod_action_set:") {
+            // Choose what happens to flagged messages in this group
+            if let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message {
+                let is_admin = utils::is_admin(&bot, m.chat.id, query.from.id).await;
+                if !is_admin {
+                    bot.answer_callback_query(query.id)
+                        .text("❌ Only administrators can manage moderation settings")
+                        .await?;
+                    return Ok(());
+                }
+
+                let mut settings = bot_deps
+                    .moderation
+                    .get_moderation_settings(m.chat.id.to_string())
+                    .unwrap_or(ModerationSettings::from((vec![], vec![], 0, 0)));
+
+                if let Some(key) = data.strip_prefix("mod_action_set:") {
+                    let Some(action) = ModerationAction::from_key(key) else {
+                        bot.answer_callback_query(query.id)
+                            .text("❌ Unknown action")
+                            .await?;
+                        return Ok(());
+                    };
+                    settings.action = action;
+                    settings.updated_by_user_id = query.from.id.0 as i64;
+                    settings.updated_at_unix_ms = chrono::Utc::now().timestamp_millis();
+                    bot_deps
+                        .moderation
+                        .set_or_update_moderation_settings(m.chat.id.to_string(), settings)?;
+                    bot.answer_callback_query(query.id.clone())
+                        .text(format!("✅ Flag action set to {}", action.label()))
+                        .await?;
+                } else {
+                    bot.answer_callback_query(query.id.clone()).await?;
+                }
+
+                let current = bot_deps
+                    .moderation
+                    .get_moderation_settings(m.chat.id.to_string())
+                    .map(|s| s.action)
+                    .unwrap_or_default();
+
+                let mut rows: Vec<Vec<InlineKeyboardButton>> = ModerationAction::ALL
+                    .iter()
+                    .map(|a| {
+                        let label = if *a == current {
+                            format!("✅ {}", a.label())
+                        } else {
+                            a.label().to_string()
+                        };
+                        vec![InlineKeyboardButton::callback(
+                            label,
+                            format!("mod_action_set:{}", a.key()),
+                        )]
+                    })
+                    .collect();
+                rows.push(vec![InlineKeyboardButton::callback(
+                    "↩️ Back",
+                    "open_moderation_settings",
+                )]);
+
+                bot.edit_message_text(
+                    m.chat.id,
+                    m.id,
+                    "⚖️ <b>Flag Action</b>\n\nChoose what happens when Sentinel or /report flags a message:\n\n• <b>Warn only</b> — post a notice, keep the message\n• <b>Delete</b> — remove the message\n• <b>Mute</b> — remove the message and mute the author (default)\n• <b>Kick</b> — remove the message and the author (they can rejoin)\n• <b>Ban</b> — remove the message and ban the author",
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(InlineKeyboardMarkup::new(rows))
+                .await?;
+            }
         } else if data == "mod_show_allowed" || data == "mod_show_disallowed" {
             // Show current Allowed or Disallowed custom rules
             if let Some(message) = &query.message {
@@ -1882,6 +2154,16 @@ If you have questions, ask an admin before posting.
                     bot.answer_callback_query(query.id).text("✅ Sent").await?;
                 }
             }
+        } else if data.starts_with("ctxnote_") {
+            handle_context_note_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("repeat_run:") || data.starts_with("repeat_cancel:") {
+            handle_repeat_callback(bot, query, bot_deps).await?;
+        } else if data == "upload_resume" || data == "upload_discard" {
+            handle_upload_session_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("members_page:") {
+            handle_members_callback(bot, query, bot_deps).await?;
+        } else if data.starts_with("myvotes_page:") {
+            handle_my_votes_callback(bot, query, bot_deps).await?;
         } else if data == "disable_notifications" {
             // Handle disable notifications callback
             handle_disable_notifications_callback(bot, query, bot_deps).await?;
@@ -1905,6 +2187,9 @@ If you have questions, ask an admin before posting.
             handle_filters_callback(bot, query, bot_deps).await?;
         } else if data == "open_payment_settings"
             || data == "open_group_payment_settings"
+            || data == "pay_notify_toggle"
+            || data == "pay_autoconf"
+            || data.starts_with("pay_autoconf_set:")
             || data == "payment_selected"
             || data.starts_with("pay_tokpage:")
             || data.starts_with("pay_selid-")
@@ -2062,12 +2347,12 @@ pub async fn handle_payment_callback(
             let result = if pending_transaction.is_group_transfer {
                 bot_deps
                     .service
-                    .pay_members(pending_transaction.jwt_token, pay_request)
+                    .pay_members(pending_transaction.jwt_token.clone(), pay_request)
                     .await
             } else {
                 bot_deps
                     .service
-                    .pay_users(pending_transaction.jwt_token, pay_request)
+                    .pay_users(pending_transaction.jwt_token.clone(), pay_request)
                     .await
             };
 
@@ -2096,13 +2381,27 @@ pub async fn handle_payment_callback(
                             .join(", ")
                     };
 
+                    if let Some(memo) = &pending_transaction.memo {
+                        if let Err(e) = bot_deps.payment_memos.set(&response.hash, memo) {
+                            log::error!("Failed to store memo for tx {}: {}", response.hash, e);
+                        }
+                    }
+                    crate::payment::ledger::record_sent_payment(
+                        &bot_deps,
+                        &pending_transaction,
+                        &response.hash,
+                        "send",
+                        query.from.username.as_deref(),
+                    );
+
                     let success_message = format!(
-                        "✅ <b>Payment sent successfully!</b>\n\n💰 {:.2} {} sent to {} ({:.2} each)\n\n🔗 <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">View transaction</a>",
+                        "✅ <b>Payment sent successfully!</b>\n\n💰 {:.2} {} sent to {} ({:.2} each){}\n\n🔗 <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">View transaction</a>",
                         pending_transaction.per_user_amount
                             * pending_transaction.original_usernames.len() as f64,
                         pending_transaction.symbol,
                         recipients_text,
                         pending_transaction.per_user_amount,
+                        crate::payment::memo::memo_html_line(pending_transaction.memo.as_deref()),
                         response.hash,
                         network
                     );
@@ -2119,6 +2418,29 @@ pub async fn handle_payment_callback(
                     bot.answer_callback_query(query.id)
                         .text("✅ Payment executed successfully!")
                         .await?;
+
+                    let source_chat = if group_id_i64 == 0 {
+                        ChatId(user_id)
+                    } else {
+                        ChatId(group_id_i64)
+                    };
+                    let source = crate::payment::notifications::payment_source_label(
+                        &bot,
+                        source_chat,
+                        query.from.username.as_deref(),
+                    )
+                    .await;
+                    crate::payment::notifications::notify_payment_recipients(
+                        &bot,
+                        &bot_deps,
+                        &pending_transaction.original_usernames,
+                        pending_transaction.per_user_amount,
+                        &pending_transaction.symbol,
+                        &source,
+                        &response.hash,
+                        pending_transaction.memo.as_deref(),
+                    )
+                    .await;
                 }
                 Err(e) => {
                     let error_message = format!("❌ <b>Payment failed</b>\n\n{}", e);
@@ -2166,6 +2488,27 @@ pub async fn handle_payment_callback(
                 recipients_text
             );
 
+            // Cancelled from /pending: the confirmation message still has its buttons
+            let from_other_message = match &query.message {
+                Some(MaybeInaccessibleMessage::Regular(msg)) => {
+                    msg.id.0 != pending_transaction.message_id
+                }
+                _ => true,
+            };
+            if from_other_message && pending_transaction.message_id != 0 {
+                if let Err(e) = bot
+                    .edit_message_text(
+                        ChatId(pending_transaction.chat_id),
+                        teloxide::types::MessageId(pending_transaction.message_id),
+                        cancel_message.clone(),
+                    )
+                    .parse_mode(ParseMode::Html)
+                    .await
+                {
+                    log::warn!("Failed to update cancelled transaction message: {}", e);
+                }
+            }
+
             // Edit the original message
             if let Some(message) = &query.message {
                 if let MaybeInaccessibleMessage::Regular(msg) = message {
@@ -2188,3 +2531,91 @@ pub async fn handle_payment_callback(
 
     Ok(())
 }
+
+/// Text and keyboard for the Moderation submenu of /groupsettings
+fn moderation_settings_menu(
+    sentinel_on: bool,
+    settings: &ModerationSettings,
+) -> (String, InlineKeyboardMarkup) {
+    let text = format!(
+        concat!(
+            "🛡️ <b>Moderation Settings</b>\n\n",
+            "Sentinel: <b>{sentinel}</b>\n",
+            "Custom Rules: <b>{allowed}</b> allowed, <b>{disallowed}</b> disallowed\n",
+            "Flag Action: <b>{action}</b>\n",
+            "Pass Reaction: <b>{reaction}</b>\n",
+            "Updated: <i>{updated}</i>\n\n",
+            "Choose an action below:"
+        ),
+        sentinel = if sentinel_on { "ON" } else { "OFF" },
+        allowed = settings.allowed_items.len(),
+        disallowed = settings.disallowed_items.len(),
+        action = settings.action.label(),
+        reaction = if settings.react_on_pass { "ON" } else { "OFF" },
+        updated = settings.updated_at_unix_ms.to_string(),
+    );
+
+    let toggle_label = if sentinel_on {
+        "🔕 Turn OFF Sentinel"
+    } else {
+        "🛡️ Turn ON Sentinel"
+    };
+    let toggle_cb = if sentinel_on {
+        "mod_toggle_sentinel_off"
+    } else {
+        "mod_toggle_sentinel_on"
+    };
+    let kb = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(toggle_label, toggle_cb)],
+        vec![InlineKeyboardButton::callback(
+            "📝 Start Moderation Wizard",
+            "mod_settings_start",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "✨ Draft Rules with AI",
+            "mod_ai_start",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "⚖️ Flag Action",
+            "mod_action_menu",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🔔 Low Balance Alert",
+            "mod_lowbal_menu",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🧸 Emoji/Sticker Limit",
+            "mod_spamlim_menu",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🔗 Link Allowlist",
+            "mod_links_menu",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "👌 Toggle Pass Reaction",
+            "mod_toggle_pass_reaction",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "🧹 Reset Custom Rules",
+            "mod_reset",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "✅ Show Allowed Rules",
+            "mod_show_allowed",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "⛔ Show Disallowed Rules",
+            "mod_show_disallowed",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "📜 Show Default Rules",
+            "mod_show_defaults",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "back_to_group_settings",
+        )],
+    ]);
+
+    (text, kb)
+}
@@ -3,6 +3,7 @@ use std::env;
 use anyhow::Result;
 use sled::{Db, Tree};
 
+use crate::ai::disclaimer::{self, DisclaimerMode};
 use crate::command_settings::dto::CommandSettings;
 
 #[derive(Clone)]
@@ -63,4 +64,84 @@ impl CommandSettingsManager {
         let settings = self.get_command_settings(group_id);
         settings.chat_commands_enabled
     }
+
+    /// Cap on images sent with one AI request. Keyed by group id, or by user id in DMs.
+    pub fn max_images_per_request(&self, chat_id: String) -> usize {
+        self.get_command_settings(chat_id).max_images_per_request.max(1)
+    }
+
+    /// Reply length cap for /g in a group, if one is configured
+    pub fn max_reply_chars(&self, group_id: String) -> Option<usize> {
+        self.get_command_settings(group_id)
+            .max_reply_chars
+            .filter(|cap| *cap > 0)
+    }
+
+    pub fn set_max_reply_chars(&self, group_id: String, max_chars: Option<usize>) -> Result<()> {
+        let mut settings = self.get_command_settings(group_id.clone());
+        settings.group_id = group_id.clone();
+        settings.max_reply_chars = max_chars.filter(|cap| *cap > 0);
+        self.set_command_settings(group_id, settings)
+    }
+
+    /// Whether AI replies skip link previews. Keyed by group id, or by user id in DMs.
+    pub fn link_previews_disabled(&self, chat_id: String) -> bool {
+        self.get_command_settings(chat_id).disable_link_previews
+    }
+
+    /// Flip the link preview setting and return the new "disabled" value
+    pub fn toggle_link_previews(&self, chat_id: String) -> Result<bool> {
+        let mut settings = self.get_command_settings(chat_id.clone());
+        settings.group_id = chat_id.clone();
+        settings.disable_link_previews = !settings.disable_link_previews;
+        let disabled = settings.disable_link_previews;
+        self.set_command_settings(chat_id, settings)?;
+        Ok(disabled)
+    }
+
+    /// Custom command prefix for a group, if one is configured
+    pub fn command_prefix(&self, group_id: String) -> Option<String> {
+        self.get_command_settings(group_id).command_prefix
+    }
+
+    pub fn set_command_prefix(&self, group_id: String, prefix: Option<String>) -> Result<()> {
+        let mut settings = self.get_command_settings(group_id.clone());
+        settings.group_id = group_id.clone();
+        settings.command_prefix = prefix;
+        self.set_command_settings(group_id, settings)
+    }
+
+    /// Flip the group's prompt safety opt-in and return the new value
+    pub fn toggle_prompt_safety_check(&self, chat_id: String) -> Result<bool> {
+        let mut settings = self.get_command_settings(chat_id.clone());
+        settings.group_id = chat_id.clone();
+        settings.prompt_safety_check = !settings.prompt_safety_check;
+        let enabled = settings.prompt_safety_check;
+        self.set_command_settings(chat_id, settings)?;
+        Ok(enabled)
+    }
+
+    /// Disclaimer mode for a chat, falling back to the operator default
+    pub fn disclaimer_mode(&self, chat_id: String) -> DisclaimerMode {
+        self.get_command_settings(chat_id)
+            .disclaimer
+            .unwrap_or_else(disclaimer::default_mode)
+    }
+
+    /// Move to the next disclaimer mode and return it
+    pub fn cycle_disclaimer_mode(&self, chat_id: String) -> Result<DisclaimerMode> {
+        let mode = self.disclaimer_mode(chat_id.clone()).next();
+        let mut settings = self.get_command_settings(chat_id.clone());
+        settings.group_id = chat_id.clone();
+        settings.disclaimer = Some(mode);
+        self.set_command_settings(chat_id, settings)?;
+        Ok(mode)
+    }
+
+    pub fn set_max_images_per_request(&self, chat_id: String, max_images: usize) -> Result<()> {
+        let mut settings = self.get_command_settings(chat_id.clone());
+        settings.group_id = chat_id.clone();
+        settings.max_images_per_request = max_images;
+        self.set_command_settings(chat_id, settings)
+    }
 }
@@ -1,9 +1,69 @@
 use serde::{Deserialize, Serialize};
 
+use crate::ai::disclaimer::DisclaimerMode;
+
+/// Images attached to a single AI request when no cap has been configured
+pub const DEFAULT_MAX_IMAGES_PER_REQUEST: usize = 5;
+
+/// Choices offered in the settings menus for the per-request image cap
+pub const MAX_IMAGES_OPTIONS: [usize; 4] = [1, 3, 5, 10];
+
+/// Choices for the group reply length cap, in characters; 0 means no cap
+pub const MAX_REPLY_CHARS_OPTIONS: [usize; 5] = [0, 1000, 2000, 4000, 8000];
+
+/// Longest custom command prefix a group can set
+pub const MAX_COMMAND_PREFIX_CHARS: usize = 3;
+
+/// Characters Telegram already gives a meaning to (commands, mentions, hashtags, cashtags)
+/// or that clash with HTML replies
+const RESERVED_PREFIX_CHARS: [char; 7] = ['/', '@', '#', '$', '<', '>', '&'];
+
+/// Check a custom command prefix such as `!` or `..`; returns the trimmed prefix
+pub fn validate_command_prefix(prefix: &str) -> Result<String, String> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() || prefix.chars().count() > MAX_COMMAND_PREFIX_CHARS {
+        return Err(format!(
+            "The prefix must be 1 to {} characters long.",
+            MAX_COMMAND_PREFIX_CHARS
+        ));
+    }
+    if !prefix.chars().all(|c| c.is_ascii_punctuation()) {
+        return Err("The prefix may only contain symbols such as ! . ? ~ -".to_string());
+    }
+    if let Some(c) = prefix.chars().find(|c| RESERVED_PREFIX_CHARS.contains(c)) {
+        return Err(format!("\"{}\" is reserved by Telegram and can't be used.", c));
+    }
+    Ok(prefix.to_string())
+}
+
+fn default_max_images_per_request() -> usize {
+    DEFAULT_MAX_IMAGES_PER_REQUEST
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CommandSettings {
     pub group_id: String,
     pub chat_commands_enabled: bool,
+    #[serde(default = "default_max_images_per_request")]
+    pub max_images_per_request: usize,
+    /// Longest /g reply sent in the group before truncation; `None` sends everything
+    #[serde(default)]
+    pub max_reply_chars: Option<usize>,
+    /// Give /g the group's pinned message as extra context
+    #[serde(default)]
+    pub use_pinned_message: bool,
+    /// Send AI replies without Telegram's link preview card
+    #[serde(default)]
+    pub disable_link_previews: bool,
+    /// Extra command prefix (e.g. `!` for `!c`) accepted alongside `/`
+    #[serde(default)]
+    pub command_prefix: Option<String>,
+    /// When to append the operator disclaimer to AI replies; `None` follows AI_DISCLAIMER_MODE
+    #[serde(default)]
+    pub disclaimer: Option<DisclaimerMode>,
+    /// Check /c prompts for disallowed content first; only used when PROMPT_SAFETY_CHECK=opt_in
+    #[serde(default)]
+    pub prompt_safety_check: bool,
 }
 
 impl Default for CommandSettings {
@@ -11,6 +71,13 @@ impl Default for CommandSettings {
         Self {
             group_id: String::new(),
             chat_commands_enabled: true, // Default to enabled
+            max_images_per_request: DEFAULT_MAX_IMAGES_PER_REQUEST,
+            max_reply_chars: None,
+            use_pinned_message: false,
+            disable_link_previews: false,
+            command_prefix: None,
+            disclaimer: None,
+            prompt_safety_check: false,
         }
     }
 }
@@ -20,6 +87,29 @@ impl From<String> for CommandSettings {
         Self {
             group_id,
             chat_commands_enabled: true,
+            max_images_per_request: DEFAULT_MAX_IMAGES_PER_REQUEST,
+            max_reply_chars: None,
+            use_pinned_message: false,
+            disable_link_previews: false,
+            command_prefix: None,
+            disclaimer: None,
+            prompt_safety_check: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_command_prefix() {
+        assert_eq!(validate_command_prefix(" ! "), Ok("!".to_string()));
+        assert_eq!(validate_command_prefix(".."), Ok("..".to_string()));
+        assert!(validate_command_prefix("").is_err());
+        assert!(validate_command_prefix("!!!!").is_err());
+        assert!(validate_command_prefix("q").is_err());
+        assert!(validate_command_prefix("/").is_err());
+        assert!(validate_command_prefix("!@").is_err());
+    }
+}
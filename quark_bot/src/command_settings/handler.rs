@@ -4,6 +4,10 @@ use teloxide::{
     types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
 };
 
+use crate::ai::prompt_safety::{self, PromptSafetyMode};
+use crate::command_settings::dto::{
+    MAX_IMAGES_OPTIONS, MAX_REPLY_CHARS_OPTIONS, validate_command_prefix,
+};
 use crate::dependencies::BotDependencies;
 use crate::utils;
 
@@ -33,6 +37,77 @@ pub async fn handle_command_settings_callback(
                     "toggle_chat_commands" => {
                         toggle_chat_commands(&bot, &query, &bot_deps, m.chat.id).await?;
                     }
+                    "toggle_pinned_context" => {
+                        let group_id = m.chat.id.to_string();
+                        let mut settings =
+                            bot_deps.command_settings.get_command_settings(group_id.clone());
+                        settings.use_pinned_message = !settings.use_pinned_message;
+                        settings.group_id = group_id.clone();
+                        bot_deps
+                            .command_settings
+                            .set_command_settings(group_id, settings)?;
+                        bot_deps.pinned_messages.invalidate(m.chat.id);
+                        show_command_settings_menu(&bot, &query, &bot_deps, m.chat.id).await?;
+                    }
+                    "toggle_link_previews" => {
+                        bot_deps
+                            .command_settings
+                            .toggle_link_previews(m.chat.id.to_string())?;
+                        show_command_settings_menu(&bot, &query, &bot_deps, m.chat.id).await?;
+                    }
+                    "toggle_prompt_safety" => {
+                        bot_deps
+                            .command_settings
+                            .toggle_prompt_safety_check(m.chat.id.to_string())?;
+                        show_command_settings_menu(&bot, &query, &bot_deps, m.chat.id).await?;
+                    }
+                    "cycle_disclaimer" => {
+                        bot_deps
+                            .command_settings
+                            .cycle_disclaimer_mode(m.chat.id.to_string())?;
+                        show_command_settings_menu(&bot, &query, &bot_deps, m.chat.id).await?;
+                    }
+                    data if data.starts_with("cmd_max_images:") => {
+                        let value = data
+                            .strip_prefix("cmd_max_images:")
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .filter(|v| MAX_IMAGES_OPTIONS.contains(v));
+                        match value {
+                            Some(value) => {
+                                bot_deps.command_settings.set_max_images_per_request(
+                                    m.chat.id.to_string(),
+                                    value,
+                                )?;
+                                show_command_settings_menu(&bot, &query, &bot_deps, m.chat.id)
+                                    .await?;
+                            }
+                            None => {
+                                bot.answer_callback_query(query.id)
+                                    .text("❌ Invalid image limit")
+                                    .await?;
+                            }
+                        }
+                    }
+                    data if data.starts_with("cmd_reply_cap:") => {
+                        let value = data
+                            .strip_prefix("cmd_reply_cap:")
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .filter(|v| MAX_REPLY_CHARS_OPTIONS.contains(v));
+                        match value {
+                            Some(value) => {
+                                bot_deps
+                                    .command_settings
+                                    .set_max_reply_chars(m.chat.id.to_string(), Some(value))?;
+                                show_command_settings_menu(&bot, &query, &bot_deps, m.chat.id)
+                                    .await?;
+                            }
+                            None => {
+                                bot.answer_callback_query(query.id)
+                                    .text("❌ Invalid reply length")
+                                    .await?;
+                            }
+                        }
+                    }
                     "command_settings_back" => {
                         show_group_settings_menu(&bot, &query, m.chat.id).await?;
                     }
@@ -56,7 +131,8 @@ async fn show_command_settings_menu(
     chat_id: teloxide::types::ChatId,
 ) -> Result<()> {
     let group_id = chat_id.to_string();
-    let settings = bot_deps.command_settings.get_command_settings(group_id);
+    let settings = bot_deps.command_settings.get_command_settings(group_id.clone());
+    let disclaimer_mode = bot_deps.command_settings.disclaimer_mode(group_id);
 
     let chat_status = if settings.chat_commands_enabled {
         "✅ Enabled"
@@ -70,20 +146,82 @@ async fn show_command_settings_menu(
         "✅ Enable Chat Commands"
     };
 
-    let keyboard = InlineKeyboardMarkup::new(vec![
+    let prompt_safety_mode = prompt_safety::mode();
+    let prompt_safety = match prompt_safety_mode {
+        PromptSafetyMode::Off => "Not available",
+        PromptSafetyMode::Always => "On (set by operator)",
+        PromptSafetyMode::OptIn if settings.prompt_safety_check => "On",
+        PromptSafetyMode::OptIn => "Off",
+    };
+
+    let mut rows = vec![
         vec![InlineKeyboardButton::callback(
             chat_action,
             "toggle_chat_commands",
         )],
+        max_images_row(settings.max_images_per_request, "cmd_max_images"),
+        reply_cap_row(settings.max_reply_chars.unwrap_or(0)),
+        vec![InlineKeyboardButton::callback(
+            if settings.use_pinned_message {
+                "📌 Pinned Message Context: Turn Off"
+            } else {
+                "📌 Pinned Message Context: Turn On"
+            },
+            "toggle_pinned_context",
+        )],
         vec![InlineKeyboardButton::callback(
-            "↩️ Back to Settings",
-            "command_settings_back",
+            if settings.disable_link_previews {
+                "🔗 Link Previews: Turn On"
+            } else {
+                "🔗 Link Previews: Turn Off"
+            },
+            "toggle_link_previews",
         )],
-    ]);
+        vec![InlineKeyboardButton::callback(
+            format!("⚠️ Disclaimer: {}", disclaimer_mode.label()),
+            "cycle_disclaimer",
+        )],
+    ];
+    // Only offered when the operator lets groups decide
+    if prompt_safety_mode == PromptSafetyMode::OptIn {
+        rows.push(vec![InlineKeyboardButton::callback(
+            if settings.prompt_safety_check {
+                "🛡️ Prompt Safety Check: Turn Off"
+            } else {
+                "🛡️ Prompt Safety Check: Turn On"
+            },
+            "toggle_prompt_safety",
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "📝 Context Note",
+        "ctxnote_open:group",
+    )]);
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back to Settings",
+        "command_settings_back",
+    )]);
+    let keyboard = InlineKeyboardMarkup::new(rows);
+
+    let reply_cap = match settings.max_reply_chars.filter(|cap| *cap > 0) {
+        Some(cap) => format!("{} characters", cap),
+        None => "No limit".to_string(),
+    };
 
     let text = format!(
-        "⚙️ <b>Command Settings</b>\n\nManage which commands are available in this group.\n\n<b>Chat Commands (/c, /chat):</b> {}\n\n💡 <i>When disabled, the /c and /chat commands will not work in this group.</i>",
-        chat_status
+        "⚙️ <b>Command Settings</b>\n\nManage which commands are available in this group.\n\n<b>Chat Commands (/c, /chat):</b> {}\n<b>Max images per request:</b> {}\n<b>Max /g reply length:</b> {}\n<b>Pinned message context:</b> {}\n<b>Link previews in AI replies:</b> {}\n<b>Custom command prefix:</b> {}\n<b>Reply disclaimer:</b> {}\n<b>Prompt safety check:</b> {}\n\n💡 <i>When disabled, the /c and /chat commands will not work in this group. Extra images beyond the limit are ignored. Longer /g replies are cut off with a \"…[truncated]\" note. Pinned message context lets /g answer from the pinned rules/FAQ, at some extra token cost. Turning link previews off hides the preview cards for links in AI replies. Set a custom prefix with /commandprefix. The disclaimer can be added to every AI reply or only to replies built from price and pool data. The prompt safety check refuses /c prompts asking for disallowed content before anything is generated or charged.</i>",
+        chat_status,
+        settings.max_images_per_request,
+        reply_cap,
+        if settings.use_pinned_message { "On" } else { "Off" },
+        if settings.disable_link_previews { "Off" } else { "On" },
+        settings
+            .command_prefix
+            .as_deref()
+            .map(|p| format!("<code>{}</code>", teloxide::utils::html::escape(p)))
+            .unwrap_or_else(|| "None".to_string()),
+        disclaimer_mode.label(),
+        prompt_safety
     );
 
     if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(message)) = &query.message {
@@ -177,6 +315,10 @@ async fn show_group_settings_menu(
             "📋 Summarization Settings",
             "open_group_summarization_settings",
         )],
+        vec![InlineKeyboardButton::callback(
+            "🧠 History Settings",
+            "open_history_settings",
+        )],
         vec![InlineKeyboardButton::callback(
             "🔄 Migrate Group ID",
             "open_migrate_group_id",
@@ -199,3 +341,162 @@ async fn show_group_settings_menu(
     bot.answer_callback_query(query.id.clone()).await?;
     Ok(())
 }
+
+/// Row of buttons to pick the per-request image cap; the current value is ticked
+fn max_images_row(current: usize, callback_prefix: &str) -> Vec<InlineKeyboardButton> {
+    MAX_IMAGES_OPTIONS
+        .iter()
+        .map(|n| {
+            let label = if *n == current {
+                format!("✅ {} 🖼️", n)
+            } else {
+                format!("{} 🖼️", n)
+            };
+            InlineKeyboardButton::callback(label, format!("{}:{}", callback_prefix, n))
+        })
+        .collect()
+}
+
+/// Row of buttons to pick the /g reply length cap; 0 is shown as "∞"
+fn reply_cap_row(current: usize) -> Vec<InlineKeyboardButton> {
+    MAX_REPLY_CHARS_OPTIONS
+        .iter()
+        .map(|n| {
+            let value = if *n == 0 {
+                "∞".to_string()
+            } else {
+                format!("{}k", n / 1000)
+            };
+            let label = if *n == current {
+                format!("✅ {} 📏", value)
+            } else {
+                format!("{} 📏", value)
+            };
+            InlineKeyboardButton::callback(label, format!("cmd_reply_cap:{}", n))
+        })
+        .collect()
+}
+
+/// Per-user image cap for DMs, opened from /usersettings
+pub async fn handle_user_max_images_callback(
+    bot: Bot,
+    query: teloxide::types::CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let user_key = query.from.id.to_string();
+
+    if let Some(value) = query
+        .data
+        .as_deref()
+        .and_then(|d| d.strip_prefix("user_max_images:"))
+    {
+        match value.parse::<usize>().ok().filter(|v| MAX_IMAGES_OPTIONS.contains(v)) {
+            Some(value) => {
+                bot_deps
+                    .command_settings
+                    .set_max_images_per_request(user_key.clone(), value)?;
+            }
+            None => {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Invalid image limit")
+                    .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let current = bot_deps.command_settings.max_images_per_request(user_key);
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        max_images_row(current, "user_max_images"),
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "back_to_user_settings",
+        )],
+    ]);
+
+    if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(message)) = &query.message {
+        bot.edit_message_text(
+            message.chat.id,
+            message.id,
+            format!(
+                "🖼️ <b>Max Images per Request</b>\n\nCurrent limit: <b>{}</b>\n\nWhen you send more images than this with /c, only the first ones are used. Lower limits keep vision costs down.",
+                current
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    }
+
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+/// /commandprefix — show, set or clear the group's extra command prefix (e.g. `!` for `!c`)
+pub async fn handle_command_prefix_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(user) = msg.from.clone() else {
+        return Ok(());
+    };
+    if !utils::is_admin(&bot, msg.chat.id, user.id).await {
+        utils::send_message(
+            msg,
+            bot,
+            "❌ Only group administrators can change the command prefix.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let group_id = msg.chat.id.to_string();
+    let args = args.trim();
+
+    if args.is_empty() {
+        let text = match bot_deps.command_settings.command_prefix(group_id) {
+            Some(prefix) => format!(
+                "🔤 Commands also work with <code>{0}</code> here, e.g. <code>{0}g hello</code>. Standard / commands always work.\n\nChange it with <code>/commandprefix &lt;prefix&gt;</code> or remove it with <code>/commandprefix off</code>.",
+                teloxide::utils::html::escape(&prefix)
+            ),
+            None => "🔤 No custom prefix is set, so only / commands work here.\n\nSet one with <code>/commandprefix !</code> to also accept commands like <code>!g hello</code>, which helps when several bots share the group.".to_string(),
+        };
+        utils::send_html_message(msg, bot, text).await?;
+        return Ok(());
+    }
+
+    if args.eq_ignore_ascii_case("off") {
+        bot_deps.command_settings.set_command_prefix(group_id, None)?;
+        utils::send_message(
+            msg,
+            bot,
+            "✅ Custom prefix removed. Only / commands work now.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    match validate_command_prefix(args) {
+        Ok(prefix) => {
+            bot_deps
+                .command_settings
+                .set_command_prefix(group_id, Some(prefix.clone()))?;
+            utils::send_html_message(
+                msg,
+                bot,
+                format!(
+                    "✅ Commands now also work with <code>{0}</code>, e.g. <code>{0}g hello</code>. Standard / commands keep working.",
+                    teloxide::utils::html::escape(&prefix)
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            utils::send_message(msg, bot, format!("❌ {}", e)).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+
+const TREE_NAME: &str = "context_notes";
+
+/// Longest standing instruction a chat can keep; the full system prompt is the place for more
+pub const MAX_CONTEXT_NOTE_CHARS: usize = 300;
+
+/// Input prompts older than this are ignored
+const INPUT_TTL_SECS: i64 = 600;
+
+/// Who a context note belongs to: a group (for /g) or a single user (for DMs and /c)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoteScope {
+    Group(i64),
+    User(i64),
+}
+
+impl NoteScope {
+    fn key(&self) -> String {
+        match self {
+            NoteScope::Group(chat_id) => format!("note:group:{}", chat_id),
+            NoteScope::User(user_id) => format!("note:user:{}", user_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AwaitingNote {
+    scope: NoteScope,
+    user_id: i64,
+    started_at: i64,
+}
+
+fn awaiting_key(chat_id: i64) -> String {
+    format!("awaiting:{}", chat_id)
+}
+
+/// Check a note before saving; the error is shown to the user as-is
+pub fn validate_context_note(text: &str) -> Result<String, String> {
+    let note = text.trim();
+    if note.is_empty() {
+        return Err("The note is empty.".to_string());
+    }
+    let len = note.chars().count();
+    if len > MAX_CONTEXT_NOTE_CHARS {
+        return Err(format!(
+            "The note is {} characters; the limit is {}.",
+            len, MAX_CONTEXT_NOTE_CHARS
+        ));
+    }
+    if note.contains('<') || note.contains('>') {
+        return Err("Please leave out HTML tags and the < > characters.".to_string());
+    }
+    Ok(note.to_string())
+}
+
+/// Short standing instructions added to every AI request of a chat
+#[derive(Clone)]
+pub struct ContextNotes {
+    tree: Tree,
+}
+
+impl ContextNotes {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        Ok(Self {
+            tree: db.open_tree(TREE_NAME)?,
+        })
+    }
+
+    pub fn get(&self, scope: NoteScope) -> Option<String> {
+        match self.tree.get(scope.key()) {
+            Ok(Some(raw)) => String::from_utf8(raw.to_vec()).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn set(&self, scope: NoteScope, note: &str) -> sled::Result<()> {
+        self.tree.insert(scope.key(), note.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn clear(&self, scope: NoteScope) -> sled::Result<()> {
+        self.tree.remove(scope.key())?;
+        Ok(())
+    }
+
+    /// Remember that the next message from `user_id` in `chat_id` is the new note
+    pub fn start_input(&self, chat_id: i64, user_id: i64, scope: NoteScope) -> anyhow::Result<()> {
+        let state = AwaitingNote {
+            scope,
+            user_id,
+            started_at: chrono::Utc::now().timestamp(),
+        };
+        self.tree
+            .insert(awaiting_key(chat_id), serde_json::to_vec(&state)?)?;
+        Ok(())
+    }
+
+    /// Scope awaiting input from this user in this chat, if any and not expired
+    pub fn awaiting_input(&self, chat_id: i64, user_id: i64) -> Option<NoteScope> {
+        let raw = self.tree.get(awaiting_key(chat_id)).ok()??;
+        let state: AwaitingNote = serde_json::from_slice(&raw).ok()?;
+        let fresh = chrono::Utc::now().timestamp() - state.started_at <= INPUT_TTL_SECS;
+        (state.user_id == user_id && fresh).then_some(state.scope)
+    }
+
+    pub fn finish_input(&self, chat_id: i64) -> sled::Result<()> {
+        self.tree.remove(awaiting_key(chat_id))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_context_note() {
+        assert_eq!(
+            validate_context_note("  This is a gaming guild, keep it casual. ").unwrap(),
+            "This is a gaming guild, keep it casual."
+        );
+        assert!(validate_context_note("   ").is_err());
+        assert!(validate_context_note("<b>shout</b>").is_err());
+        assert!(validate_context_note(&"a".repeat(MAX_CONTEXT_NOTE_CHARS + 1)).is_err());
+        assert!(validate_context_note(&"ä".repeat(MAX_CONTEXT_NOTE_CHARS)).is_ok());
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::Result;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+    utils::html,
+};
+
+use crate::context_note::NoteScope;
+use crate::context_note::context_note::{MAX_CONTEXT_NOTE_CHARS, validate_context_note};
+use crate::dependencies::BotDependencies;
+use crate::utils::{self, send_html_message, send_message};
+
+/// Prepend the chat's context note to the model instructions, if one is set
+pub fn apply_context_note(bot_deps: &BotDependencies, scope: NoteScope, instructions: String) -> String {
+    match bot_deps.context_notes.get(scope) {
+        Some(note) => format!(
+            "Standing context for this chat (follow it unless it conflicts with the rules below): {}\n\n{}",
+            note, instructions
+        ),
+        None => instructions,
+    }
+}
+
+fn menu_for(scope: NoteScope, note: Option<&str>) -> (String, InlineKeyboardMarkup) {
+    let (kind, back) = match scope {
+        NoteScope::Group(_) => ("group", "open_command_settings"),
+        NoteScope::User(_) => ("user", "open_my_settings"),
+    };
+    let applies_to = match scope {
+        NoteScope::Group(_) => "every /g reply in this group",
+        NoteScope::User(_) => "every /c reply and AI chat in your DMs",
+    };
+
+    let current = match note {
+        Some(note) => format!("<blockquote>{}</blockquote>", html::escape(note)),
+        None => "<i>(none)</i>".to_string(),
+    };
+
+    let text = format!(
+        "📝 <b>Context Note</b>\n\nA short standing instruction added to {}, e.g. <i>\"This is a gaming guild, keep it casual.\"</i>\n\n<b>Current note:</b>\n{}\n\n💡 Up to {} characters, plain text only.",
+        applies_to, current, MAX_CONTEXT_NOTE_CHARS
+    );
+
+    let mut rows = vec![vec![InlineKeyboardButton::callback(
+        "✏️ Set Note",
+        format!("ctxnote_set:{}", kind),
+    )]];
+    if note.is_some() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "🗑️ Clear Note",
+            format!("ctxnote_clear:{}", kind),
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback("↩️ Back", back)]);
+
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+/// Handle `ctxnote_open:*`, `ctxnote_set:*` and `ctxnote_clear:*` callbacks
+pub async fn handle_context_note_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+
+    let (action, kind) = data
+        .strip_prefix("ctxnote_")
+        .and_then(|rest| rest.split_once(':'))
+        .unwrap_or(("", ""));
+
+    let scope = match kind {
+        "group" => {
+            if !utils::is_admin(&bot, m.chat.id, query.from.id).await {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Only administrators can change the context note")
+                    .await?;
+                return Ok(());
+            }
+            NoteScope::Group(m.chat.id.0)
+        }
+        "user" => NoteScope::User(query.from.id.0 as i64),
+        _ => {
+            bot.answer_callback_query(query.id).await?;
+            return Ok(());
+        }
+    };
+
+    match action {
+        "set" => {
+            bot_deps
+                .context_notes
+                .start_input(m.chat.id.0, query.from.id.0 as i64, scope)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(
+                m.chat.id,
+                m.id,
+                format!(
+                    "✏️ <b>Send the new context note</b> as your next message (up to {} characters, plain text).\n\nSend /cancel to keep the current note.",
+                    MAX_CONTEXT_NOTE_CHARS
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(vec![vec![
+                InlineKeyboardButton::callback("↩️ Back", format!("ctxnote_open:{}", kind)),
+            ]]))
+            .await?;
+            return Ok(());
+        }
+        "clear" => {
+            bot_deps.context_notes.clear(scope)?;
+            bot.answer_callback_query(query.id.clone())
+                .text("🗑️ Context note cleared")
+                .await?;
+        }
+        _ => {
+            // Opening (or going back to) the menu drops any half-finished input
+            bot_deps.context_notes.finish_input(m.chat.id.0)?;
+            bot.answer_callback_query(query.id.clone()).await?;
+        }
+    }
+
+    let note = bot_deps.context_notes.get(scope);
+    let (text, keyboard) = menu_for(scope, note.as_deref());
+    bot.edit_message_text(m.chat.id, m.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+
+    Ok(())
+}
+
+/// Save the next message as the context note when an input prompt is open. Returns `true` when handled.
+pub async fn handle_context_note_input(
+    bot: &Bot,
+    msg: &Message,
+    bot_deps: &BotDependencies,
+) -> Result<bool> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(false);
+    };
+    let Some(scope) = bot_deps
+        .context_notes
+        .awaiting_input(msg.chat.id.0, user.id.0 as i64)
+    else {
+        return Ok(false);
+    };
+    let Some(text) = msg.text() else {
+        return Ok(false);
+    };
+
+    if text.trim() == "/cancel" {
+        bot_deps.context_notes.finish_input(msg.chat.id.0)?;
+        send_message(msg.clone(), bot.clone(), "❌ Context note unchanged.".to_string()).await?;
+        return Ok(true);
+    }
+
+    match validate_context_note(text) {
+        Ok(note) => {
+            bot_deps.context_notes.set(scope, &note)?;
+            bot_deps.context_notes.finish_input(msg.chat.id.0)?;
+            send_html_message(
+                msg.clone(),
+                bot.clone(),
+                format!(
+                    "✅ <b>Context note saved.</b>\n\n<blockquote>{}</blockquote>",
+                    html::escape(&note)
+                ),
+            )
+            .await?;
+        }
+        Err(reason) => {
+            send_message(
+                msg.clone(),
+                bot.clone(),
+                format!("❌ {} Send another note or /cancel.", reason),
+            )
+            .await?;
+        }
+    }
+
+    Ok(true)
+}
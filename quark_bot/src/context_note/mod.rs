@@ -0,0 +1,4 @@
+pub mod context_note;
+pub mod handler;
+
+pub use context_note::{ContextNotes, NoteScope};
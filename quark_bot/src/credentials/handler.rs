@@ -29,6 +29,22 @@ impl Auth {
         }
     }
 
+    /// Telegram id of a logged-in user, matching the username case-insensitively
+    pub fn find_user_id(&self, username: &str) -> Option<UserId> {
+        let username = username.trim_start_matches('@');
+        self.get_credentials(username)
+            .or_else(|| {
+                self.db
+                    .iter()
+                    .filter_map(|kv| kv.ok())
+                    .find(|(key, _)| {
+                        std::str::from_utf8(key).is_ok_and(|k| k.eq_ignore_ascii_case(username))
+                    })
+                    .and_then(|(_, value)| serde_json::from_slice::<Credentials>(&value).ok())
+            })
+            .map(|credentials| credentials.user_id)
+    }
+
     pub fn save_credentials(&self, username: &str, credentials: Credentials) -> Result<()> {
         let bytes = serde_json::to_vec(&credentials).unwrap();
         self.db
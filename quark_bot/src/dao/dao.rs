@@ -358,6 +358,37 @@ impl Dao {
         Ok(())
     }
 
+    pub fn set_discussion_message(
+        &self,
+        proposal_id: String,
+        message_id: Option<i32>,
+    ) -> Result<()> {
+        self.db.fetch_and_update("daos", |entries| {
+            if let Some(daos) = entries {
+                let daos_result: Result<Vec<ProposalEntry>, serde_json::Error> =
+                    serde_json::from_slice(daos);
+
+                if daos_result.is_err() {
+                    return None;
+                }
+
+                let mut daos = daos_result.unwrap();
+
+                let dao = daos.iter_mut().find(|dao| dao.proposal_id == proposal_id);
+
+                if let Some(dao) = dao {
+                    dao.discussion_message_id = message_id;
+                }
+
+                Some(serde_json::to_vec(&daos).unwrap())
+            } else {
+                None
+            }
+        })?;
+
+        Ok(())
+    }
+
     pub fn update_last_result_notification(&self, proposal_id: String) -> Result<()> {
         let now = Utc::now().timestamp() as u64;
 
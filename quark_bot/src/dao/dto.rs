@@ -1,5 +1,6 @@
 use chrono::Utc;
 use quark_core::helpers::dto::{CoinVersion, CreateProposalRequest};
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -35,6 +36,30 @@ pub struct ProposalEntry {
     pub last_result_notification: u64,
     pub disabled_notifications: bool,
     pub thread_id: Option<i32>,
+    /// Message in the group where the proposal is discussed
+    #[serde(default)]
+    pub discussion_message_id: Option<i32>,
+}
+
+impl ProposalEntry {
+    /// Mini-app link that casts a vote for the option at `choice_index`
+    pub fn vote_url(&self, app_url: &str, choice_index: usize) -> Option<Url> {
+        Url::parse(&format!(
+            "{}/dao?group_id={}&proposal_id={}&choice_id={}&coin_type={}&coin_version={}&dao_name={}&dao_description={}",
+            app_url,
+            self.group_id,
+            self.proposal_id,
+            choice_index,
+            self.coin_type,
+            match self.version {
+                CoinVersion::V1 => "V1",
+                CoinVersion::V2 => "V2",
+            },
+            self.name,
+            self.description
+        ))
+        .ok()
+    }
 }
 
 impl From<(&CreateProposalRequest, String)> for ProposalEntry {
@@ -56,6 +81,7 @@ impl From<(&CreateProposalRequest, String)> for ProposalEntry {
             last_result_notification: now,
             disabled_notifications: false,
             thread_id: request.thread_id,
+            discussion_message_id: None,
         }
     }
 }
@@ -1,7 +1,6 @@
 use anyhow::Result as AnyResult;
 use chrono::Utc;
 use quark_core::helpers::dto::{CoinVersion, CreateProposalRequest};
-use reqwest::Url;
 use teloxide::{
     prelude::*,
     types::{
@@ -15,7 +14,10 @@ use uuid::Uuid;
 use crate::{
     dao::dto::ProposalEntry,
     dependencies::BotDependencies,
-    utils::{format_time_duration, send_html_message, send_message},
+    utils::{
+        format_time_duration, format_timestamp, group_message_link, parse_message_reference,
+        send_html_message, send_message, send_scheduled_message,
+    },
 };
 
 pub async fn execute_create_proposal(
@@ -229,7 +231,7 @@ pub async fn execute_create_proposal(
 
     log::info!("Creating proposal with request: {:?}", request);
 
-    let proposal_entry = ProposalEntry::from((&request, group_id_formatted));
+    let mut proposal_entry = ProposalEntry::from((&request, group_id_formatted));
 
     let response = bot_deps.service.create_proposal(auth.jwt, request).await;
 
@@ -237,6 +239,9 @@ pub async fn execute_create_proposal(
         return "❌ Error creating proposal".to_string();
     }
 
+    proposal_entry.discussion_message_id =
+        resolve_discussion_message(arguments, &bot, &msg, &proposal_entry).await;
+
     let proposal_result = bot_deps.dao.create_dao(proposal_entry);
 
     if proposal_result.is_err() {
@@ -246,6 +251,48 @@ pub async fn execute_create_proposal(
     return format!("Proposal created successfully: {}", response.unwrap().hash);
 }
 
+/// Work out which group message, if any, hosts the discussion for a new proposal
+async fn resolve_discussion_message(
+    arguments: &serde_json::Value,
+    bot: &Bot,
+    msg: &Message,
+    proposal_entry: &ProposalEntry,
+) -> Option<i32> {
+    if let Some(reference) = arguments["discussion_message"].as_str() {
+        if let Some(message_id) = parse_message_reference(reference) {
+            return Some(message_id);
+        }
+    }
+
+    if arguments["use_replied_message"].as_bool().unwrap_or(false) {
+        if let Some(reply) = msg.reply_to_message() {
+            return Some(reply.id.0);
+        }
+    }
+
+    if !arguments["create_discussion"].as_bool().unwrap_or(false) {
+        return None;
+    }
+
+    let prompt = format!(
+        "💬 <b>Discussion: {}</b>\n\n{}\n\nReply to this message to share your thoughts before voting opens.",
+        teloxide::utils::html::escape(&proposal_entry.name),
+        teloxide::utils::html::escape(&proposal_entry.description)
+    );
+
+    match send_scheduled_message(bot, msg.chat.id, &prompt, proposal_entry.thread_id).await {
+        Ok(sent) => Some(sent.id.0),
+        Err(e) => {
+            log::error!(
+                "Failed to post discussion prompt for proposal {}: {}",
+                proposal_entry.proposal_id,
+                e
+            );
+            None
+        }
+    }
+}
+
 pub async fn handle_dao_preference_callback(
     bot: Bot,
     query: CallbackQuery,
@@ -1430,29 +1477,12 @@ pub async fn handle_disable_notifications_callback(
 
             // Recreate the voting options
             for (index, option) in proposal.options.iter().enumerate() {
-                let base_url = format!(
-                    "{}/dao?group_id={}&proposal_id={}&choice_id={}&coin_type={}&coin_version={}&dao_name={}&dao_description={}",
-                    app_url,
-                    proposal.group_id,
-                    proposal.proposal_id,
-                    index,
-                    proposal.coin_type,
-                    match proposal.version {
-                        CoinVersion::V1 => "V1",
-                        CoinVersion::V2 => "V2",
-                    },
-                    proposal.name,
-                    proposal.description
-                );
-
-                // Parse URL with error handling
-                let parsed_url = match Url::parse(&base_url) {
-                    Ok(url) => url,
-                    Err(e) => {
+                let parsed_url = match proposal.vote_url(&app_url, index) {
+                    Some(url) => url,
+                    None => {
                         log::error!(
-                            "Failed to parse URL for proposal {}: {}",
-                            proposal.proposal_id,
-                            e
+                            "Failed to build vote URL for proposal {}",
+                            proposal.proposal_id
                         );
                         continue;
                     }
@@ -1464,6 +1494,13 @@ pub async fn handle_disable_notifications_callback(
                 )]);
             }
 
+            if let Some(link) = proposal
+                .discussion_message_id
+                .and_then(|id| group_message_link(msg.chat.id, id))
+            {
+                keyboard_buttons.push(vec![InlineKeyboardButton::url("💬 Discuss", link)]);
+            }
+
             // Add voting help button
             keyboard_buttons.push(vec![InlineKeyboardButton::callback(
                 "ℹ️ How to Vote",
@@ -1491,6 +1528,164 @@ pub async fn handle_disable_notifications_callback(
     Ok(())
 }
 
+const MY_VOTES_PAGE_SIZE: usize = 5;
+
+/// Build one page of the `/myvotes` listing for `username`
+async fn build_my_votes_page(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    username: &str,
+    page: usize,
+) -> AnyResult<(String, InlineKeyboardMarkup)> {
+    let app_url =
+        std::env::var("APP_URL").map_err(|e| anyhow::anyhow!("APP_URL is not set: {}", e))?;
+
+    let group_ids = bot_deps.group.group_ids_for_user(username);
+
+    let mut proposals: Vec<ProposalEntry> = bot_deps
+        .dao
+        .get_active_daos()?
+        .into_iter()
+        .filter(|proposal| group_ids.contains(&proposal.group_id))
+        .collect();
+    proposals.sort_by_key(|proposal| proposal.end_date);
+
+    if proposals.is_empty() {
+        return Ok((
+            "🗳️ <b>My Votes</b>\n\nThere are no open proposals in your groups right now.".to_string(),
+            InlineKeyboardMarkup::new(Vec::<Vec<InlineKeyboardButton>>::new()),
+        ));
+    }
+
+    let total_pages = proposals.len().div_ceil(MY_VOTES_PAGE_SIZE);
+    let page = page.min(total_pages - 1);
+    let seed_suffix = format!("-{}", bot_deps.group.account_seed);
+
+    let mut text = format!(
+        "🗳️ <b>My Votes</b> — {} open proposal{} (page {}/{})\n",
+        proposals.len(),
+        if proposals.len() == 1 { "" } else { "s" },
+        page + 1,
+        total_pages
+    );
+    let mut keyboard_buttons = Vec::new();
+
+    for (offset, proposal) in proposals
+        .iter()
+        .skip(page * MY_VOTES_PAGE_SIZE)
+        .take(MY_VOTES_PAGE_SIZE)
+        .enumerate()
+    {
+        let number = page * MY_VOTES_PAGE_SIZE + offset + 1;
+        let chat_id = proposal
+            .group_id
+            .trim_end_matches(seed_suffix.as_str())
+            .parse::<i64>()
+            .map(ChatId);
+        let group_title = match chat_id {
+            Ok(chat_id) => bot
+                .get_chat(chat_id)
+                .await
+                .ok()
+                .and_then(|chat| chat.title().map(|t| t.to_string()))
+                .unwrap_or_else(|| "Unknown group".to_string()),
+            Err(_) => "Unknown group".to_string(),
+        };
+
+        text.push_str(&format!(
+            "\n<b>{}. {}</b>\n👥 {}\n⏰ Ends {}\n",
+            number,
+            teloxide::utils::html::escape(&proposal.name),
+            teloxide::utils::html::escape(&group_title),
+            format_timestamp(proposal.end_date)
+        ));
+
+        for (index, option) in proposal.options.iter().enumerate() {
+            if let Some(url) = proposal.vote_url(&app_url, index) {
+                keyboard_buttons.push(vec![InlineKeyboardButton::url(
+                    format!("{}. 🗳️ {}", number, option),
+                    url,
+                )]);
+            }
+        }
+    }
+
+    let mut nav_row = Vec::new();
+    if page > 0 {
+        nav_row.push(InlineKeyboardButton::callback(
+            "⬅️ Prev",
+            format!("myvotes_page:{}", page - 1),
+        ));
+    }
+    if page + 1 < total_pages {
+        nav_row.push(InlineKeyboardButton::callback(
+            "Next ➡️",
+            format!("myvotes_page:{}", page + 1),
+        ));
+    }
+    if !nav_row.is_empty() {
+        keyboard_buttons.push(nav_row);
+    }
+
+    Ok((text, InlineKeyboardMarkup::new(keyboard_buttons)))
+}
+
+pub async fn handle_my_votes(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    let username = match msg.from.as_ref().and_then(|user| user.username.clone()) {
+        Some(username) => username,
+        None => {
+            send_message(msg, bot, "❌ A Telegram username is required.".to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let (text, keyboard) = build_my_votes_page(&bot, &bot_deps, &username, 0).await?;
+
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn handle_my_votes_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let data = query.data.as_deref().unwrap_or("");
+    let page = data
+        .strip_prefix("myvotes_page:")
+        .and_then(|p| p.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let msg = match &query.message {
+        Some(MaybeInaccessibleMessage::Regular(message)) => message,
+        _ => return Ok(()),
+    };
+
+    let username = match query.from.username.clone() {
+        Some(username) => username,
+        None => {
+            bot.answer_callback_query(query.id)
+                .text("❌ Username required")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let (text, keyboard) = build_my_votes_page(&bot, &bot_deps, &username, page).await?;
+
+    bot.edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    bot.answer_callback_query(query.id).await?;
+
+    Ok(())
+}
+
 pub async fn handle_message_dao(
     bot: Bot,
     msg: Message,
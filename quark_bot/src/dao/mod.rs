@@ -1,3 +1,4 @@
 pub mod dao;
 pub mod dto;
 pub mod handler;
+pub mod results;
@@ -0,0 +1,144 @@
+use teloxide::utils::html;
+
+/// Width of the text bars, in blocks
+const BAR_WIDTH: usize = 10;
+/// Keep well under Telegram's 4096 character message limit
+const MAX_DESCRIPTION_CHARS: usize = 600;
+const MAX_CHOICE_CHARS: usize = 64;
+
+/// Tally for a single proposal option
+#[derive(Debug, Clone)]
+pub struct ChoiceTally {
+    pub name: String,
+    /// Token-weighted votes, already scaled by decimals
+    pub weight: f64,
+    /// Number of wallets that picked this option
+    pub voters: usize,
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(max.saturating_sub(1)).collect();
+        format!("{}…", cut)
+    }
+}
+
+fn percentage(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        part / total * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Render a proportional bar such as `██████░░░░`
+pub fn render_bar(percent: f64) -> String {
+    let filled = ((percent / 100.0) * BAR_WIDTH as f64).round() as usize;
+    let filled = filled.min(BAR_WIDTH);
+    format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+}
+
+/// Build the HTML results notification for a concluded proposal
+pub fn format_dao_results(
+    name: &str,
+    description: &str,
+    symbol: &str,
+    tallies: &[ChoiceTally],
+    eligible_members: Option<u32>,
+) -> String {
+    let total_weight: f64 = tallies.iter().map(|t| t.weight).sum();
+    let total_voters: usize = tallies.iter().map(|t| t.voters).sum();
+    let symbol = html::escape(symbol);
+
+    let mut text = format!(
+        "🏆 <b>DAO VOTING RESULTS</b>\n\n🏛️ <b>{}</b>\n📝 {}\n",
+        html::escape(&truncate(name, MAX_CHOICE_CHARS * 2)),
+        html::escape(&truncate(description, MAX_DESCRIPTION_CHARS))
+    );
+
+    if total_weight <= 0.0 {
+        text.push_str("\n❌ No votes were cast for this DAO.");
+        return text;
+    }
+
+    let max_weight = tallies.iter().map(|t| t.weight).fold(0.0f64, f64::max);
+    let leaders: Vec<&ChoiceTally> = tallies.iter().filter(|t| t.weight == max_weight).collect();
+
+    text.push_str(&format!("\n📊 <b>Weighted results ({})</b>\n", symbol));
+    for tally in tallies {
+        let share = percentage(tally.weight, total_weight);
+        let emoji = if tally.weight == max_weight { "🥇" } else { "▫️" };
+        text.push_str(&format!(
+            "{} <b>{}</b>\n<code>{}</code> {:.2}% · {:.2} {}\n",
+            emoji,
+            html::escape(&truncate(&tally.name, MAX_CHOICE_CHARS)),
+            render_bar(share),
+            share,
+            tally.weight,
+            symbol
+        ));
+    }
+
+    text.push_str("\n🗳️ <b>Votes by wallet</b>\n");
+    for tally in tallies {
+        let share = percentage(tally.voters as f64, total_voters as f64);
+        text.push_str(&format!(
+            "<code>{}</code> {} · {} ({:.1}%)\n",
+            render_bar(share),
+            html::escape(&truncate(&tally.name, MAX_CHOICE_CHARS)),
+            tally.voters,
+            share
+        ));
+    }
+
+    match eligible_members {
+        Some(members) if members > 0 => {
+            let rate = percentage(total_voters as f64, members as f64).min(100.0);
+            text.push_str(&format!(
+                "\n👥 Participation: {} of {} members ({:.1}%)\n<code>{}</code>\n",
+                total_voters,
+                members,
+                rate,
+                render_bar(rate)
+            ));
+        }
+        _ => {
+            text.push_str(&format!("\n👥 Participation: {} voters\n", total_voters));
+        }
+    }
+
+    text.push_str(&format!(
+        "📈 Total votes cast: {:.2} {}\n\n",
+        total_weight, symbol
+    ));
+
+    if leaders.len() > 1 {
+        let names: Vec<String> = leaders
+            .iter()
+            .map(|t| html::escape(&truncate(&t.name, MAX_CHOICE_CHARS)))
+            .collect();
+        text.push_str(&format!(
+            "⚖️ <b>Tie</b> between {} — no option reached a majority.",
+            names.join(", ")
+        ));
+    } else {
+        let winner = leaders[0];
+        let share = percentage(winner.weight, total_weight);
+        let winner_name = html::escape(&truncate(&winner.name, MAX_CHOICE_CHARS));
+        if share > 50.0 {
+            text.push_str(&format!(
+                "✅ <b>Winner: {}</b> with a majority of {:.2}% (threshold 50% met)",
+                winner_name, share
+            ));
+        } else {
+            text.push_str(&format!(
+                "⚠️ <b>Winner: {}</b> with {:.2}% — plurality only, 50% majority threshold not met",
+                winner_name, share
+            ));
+        }
+    }
+
+    text
+}
@@ -2,28 +2,46 @@ use std::sync::Arc;
 
 use crate::{
     ai::{
-        handler::AI, moderation::ModerationService,
+        handler::AI,
+        moderation::{ModerationService, flagged_log::FlaggedLog, link_allowlist::LinkAllowlist},
+        pinned::PinnedMessageCache,
+        prompt_safety::PromptSafety,
         schedule_guard::schedule_guard_service::ScheduleGuardService,
-        sentinel::sentinel::SentinelService, summarizer::handler::SummarizerService,
+        sentinel::{
+            low_balance::LowBalanceAlerts, sentinel::SentinelService, snooze::SentinelSnoozes,
+        },
+        summarizer::handler::SummarizerService,
     },
+    announcement::grants::AnnouncerGrants,
     assets::{
         group_file_upload_state::GroupFileUploadState, media_aggregator::MediaGroupAggregator,
     },
+    bot::diagnostics::DeploymentConfig,
     command_settings::CommandSettingsManager,
+    context_note::ContextNotes,
+    blocklist::Blocklist,
     credentials::handler::Auth,
     dao::dao::Dao,
+    dm_onboarding::DmOnboarding,
+    explain_tx::TxExplanations,
     filters::filters::Filters,
     group::{document_library::GroupDocuments, handler::Group},
+    group_budget::GroupBudgets,
     message_history::handler::HistoryStorage,
+    notification_prefs::NotificationPrefs,
     panora::handler::Panora,
     payment::dto::PaymentPrefs,
+    payment::{ledger::PaymentLedger, memo::PaymentMemos},
     payment::payment::Payment,
     pending_transactions::handler::PendingTransactions,
+    repeat_guard::RepeatGuard,
     scheduled_payments::storage::ScheduledPaymentsStorage,
     scheduled_prompts::storage::ScheduledStorage,
     services::handler::Services,
+    spam_guard::SpamGuard,
     sponsor::sponsor::Sponsor,
     summarization_settings::SummarizationSettings,
+    usage_stats::UsageStats,
     user_conversation::handler::UserConversations,
     welcome::welcome_service::WelcomeService,
     yield_ai::yield_ai::YieldAI,
@@ -64,4 +82,23 @@ pub struct BotDependencies {
     pub summarization_settings: SummarizationSettings,
     pub welcome_service: WelcomeService,
     pub summarizer: SummarizerService,
+    pub dm_onboarding: DmOnboarding,
+    pub repeat_guard: RepeatGuard,
+    pub context_notes: ContextNotes,
+    pub pinned_messages: PinnedMessageCache,
+    pub tx_explanations: TxExplanations,
+    pub payment_memos: PaymentMemos,
+    pub payment_ledger: PaymentLedger,
+    pub low_balance_alerts: LowBalanceAlerts,
+    pub sentinel_snoozes: SentinelSnoozes,
+    pub blocklist: Blocklist,
+    pub spam_guard: SpamGuard,
+    pub announcer_grants: AnnouncerGrants,
+    pub usage_stats: UsageStats,
+    pub notification_prefs: NotificationPrefs,
+    pub flagged_log: FlaggedLog,
+    pub link_allowlist: LinkAllowlist,
+    pub prompt_safety: PromptSafety,
+    pub deployment_config: DeploymentConfig,
+    pub group_budgets: GroupBudgets,
 }
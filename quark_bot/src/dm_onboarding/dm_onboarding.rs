@@ -0,0 +1,55 @@
+use sled::{Db, Tree};
+use std::env;
+
+const TREE_NAME: &str = "dm_onboarding";
+
+fn greeted_key(user_id: i64) -> String {
+    format!("greeted:{}", user_id)
+}
+
+fn plain_text_key(user_id: i64) -> String {
+    format!("plain_text_prompt:{}", user_id)
+}
+
+/// Tracks the one-time DM greeting and whether plain DM text is sent to the AI
+#[derive(Clone)]
+pub struct DmOnboarding {
+    tree: Tree,
+    plain_text_default: bool,
+}
+
+impl DmOnboarding {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        // Deployment-wide default for users who never touched the toggle
+        let plain_text_default = env::var("DM_PLAIN_TEXT_AS_PROMPT")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        Ok(Self {
+            tree,
+            plain_text_default,
+        })
+    }
+
+    pub fn has_been_greeted(&self, user_id: i64) -> bool {
+        matches!(self.tree.get(greeted_key(user_id)), Ok(Some(_)))
+    }
+
+    pub fn mark_greeted(&self, user_id: i64) -> sled::Result<()> {
+        self.tree.insert(greeted_key(user_id), vec![1u8])?;
+        Ok(())
+    }
+
+    pub fn is_plain_text_prompt_enabled(&self, user_id: i64) -> bool {
+        match self.tree.get(plain_text_key(user_id)) {
+            Ok(Some(bytes)) => bytes.first().copied() == Some(1),
+            _ => self.plain_text_default,
+        }
+    }
+
+    pub fn set_plain_text_prompt(&self, user_id: i64, enabled: bool) -> sled::Result<()> {
+        self.tree
+            .insert(plain_text_key(user_id), vec![enabled as u8])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::Result as AnyResult;
+use teloxide::{prelude::*, types::ParseMode};
+
+use crate::{
+    bot::handler::handle_chat, dependencies::BotDependencies,
+    repeat_guard::handler::hold_repeated_prompt,
+};
+
+/// Handle a plain-text DM that matched no command or input flow.
+///
+/// Logged-in users who opted in get the text answered like `/c`; everyone else
+/// receives a single onboarding note the first time. Returns `true` when handled.
+pub async fn handle_plain_dm(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> AnyResult<bool> {
+    let text = match msg.text() {
+        Some(text) if !text.trim().is_empty() && !text.starts_with('/') => text.to_string(),
+        _ => return Ok(false),
+    };
+
+    let user = match msg.from.as_ref() {
+        Some(user) if !user.is_bot => user.clone(),
+        _ => return Ok(false),
+    };
+    let user_id = user.id.0 as i64;
+
+    let logged_in = user
+        .username
+        .as_ref()
+        .map(|username| bot_deps.auth.get_credentials(username).is_some())
+        .unwrap_or(false);
+
+    if logged_in && bot_deps.dm_onboarding.is_plain_text_prompt_enabled(user_id) {
+        if !hold_repeated_prompt(&bot, &msg, &text, None, &bot_deps).await? {
+            handle_chat(bot, msg, text, None, false, bot_deps).await?;
+        }
+        return Ok(true);
+    }
+
+    if bot_deps.dm_onboarding.has_been_greeted(user_id) {
+        return Ok(false);
+    }
+
+    let greeting = if logged_in {
+        "👋 <b>Hi there!</b>\n\nTo chat with me, start your message with <code>/c</code>, e.g. <code>/c What's the price of APT?</code>\n\n💡 Prefer plain messages? Turn on <b>Plain DM → AI</b> in /usersettings → View My Settings.\n\nSee /help for everything else I can do."
+    } else {
+        "👋 <b>Welcome to Quark!</b>\n\nI only respond to commands. To get started:\n\n1️⃣ Use /loginuser to connect your wallet\n2️⃣ Then chat with me using <code>/c your question</code>\n\nSee /help for the full list of commands."
+    };
+
+    bot.send_message(msg.chat.id, greeting)
+        .parse_mode(ParseMode::Html)
+        .await?;
+
+    if let Err(e) = bot_deps.dm_onboarding.mark_greeted(user_id) {
+        log::error!("Failed to record DM greeting for user {}: {}", user_id, e);
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,4 @@
+pub mod dm_onboarding;
+pub mod handler;
+
+pub use dm_onboarding::DmOnboarding;
@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sled::{Db, Tree};
+
+const TREE_NAME: &str = "explained_transactions";
+/// Transactions never change, but keep only recent explanations around
+const CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// Minimum gap between two billed /explaintx calls from the same user
+pub const COOLDOWN_SECS: i64 = 30;
+const MAX_EVENTS: usize = 12;
+const MAX_FIELD_CHARS: usize = 300;
+
+fn cache_key(hash: &str) -> String {
+    format!("tx:{}", hash)
+}
+
+fn cooldown_key(user_id: i64) -> String {
+    format!("cooldown:{}", user_id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedExplanation {
+    text: String,
+    at: i64,
+}
+
+/// Cache of /explaintx answers plus the per-user cooldown
+#[derive(Clone)]
+pub struct TxExplanations {
+    tree: Tree,
+}
+
+impl TxExplanations {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn get_cached(&self, hash: &str, now: i64) -> Option<String> {
+        let cached: CachedExplanation = self
+            .tree
+            .get(cache_key(hash))
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())?;
+
+        if now - cached.at > CACHE_TTL_SECS {
+            let _ = self.tree.remove(cache_key(hash));
+            return None;
+        }
+        Some(cached.text)
+    }
+
+    pub fn cache(&self, hash: &str, text: &str, now: i64) -> sled::Result<()> {
+        let entry = CachedExplanation {
+            text: text.to_string(),
+            at: now,
+        };
+        self.tree
+            .insert(cache_key(hash), serde_json::to_vec(&entry).unwrap())?;
+        Ok(())
+    }
+
+    /// Drop cached explanations past the TTL and cooldowns that have run out
+    pub fn purge_expired(&self, now: i64) -> usize {
+        let stale: Vec<_> = self
+            .tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(k, v)| {
+                if k.starts_with(b"tx:") {
+                    serde_json::from_slice::<CachedExplanation>(v)
+                        .map(|c| now - c.at > CACHE_TTL_SECS)
+                        .unwrap_or(true)
+                } else {
+                    serde_json::from_slice::<i64>(v)
+                        .map(|last| now - last >= COOLDOWN_SECS)
+                        .unwrap_or(true)
+                }
+            })
+            .map(|(k, _)| k)
+            .collect();
+        for key in &stale {
+            let _ = self.tree.remove(key);
+        }
+        stale.len()
+    }
+
+    /// Seconds left before the user may request another explanation, if any
+    pub fn cooldown_remaining(&self, user_id: i64, now: i64) -> Option<i64> {
+        let last = self
+            .tree
+            .get(cooldown_key(user_id))
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice::<i64>(&v).ok())?;
+        let remaining = COOLDOWN_SECS - (now - last);
+        (remaining > 0).then_some(remaining)
+    }
+
+    pub fn start_cooldown(&self, user_id: i64, now: i64) -> sled::Result<()> {
+        self.tree
+            .insert(cooldown_key(user_id), serde_json::to_vec(&now).unwrap())?;
+        Ok(())
+    }
+}
+
+/// Normalize a pasted hash to `0x` + 64 lowercase hex chars
+pub fn normalize_tx_hash(input: &str) -> Option<String> {
+    let hex = input.trim();
+    let hex = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!("0x{}", hex.to_lowercase()))
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(max.saturating_sub(1)).collect();
+        format!("{}…", cut)
+    }
+}
+
+fn is_transfer_event(event_type: &str) -> bool {
+    ["Withdraw", "Deposit", "Transfer"]
+        .iter()
+        .any(|kind| event_type.contains(kind))
+}
+
+/// Compact, model-friendly digest of a transaction: header, payload, and transfer events first
+pub fn transaction_digest(tx: &Value) -> String {
+    let field = |name: &str| {
+        tx.get(name)
+            .map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+
+    let mut lines = vec![
+        format!("type: {}", field("type")),
+        format!("success: {}", field("success")),
+        format!("vm_status: {}", field("vm_status")),
+        format!("sender: {}", field("sender")),
+        format!("gas_used: {} (gas_unit_price {})", field("gas_used"), field("gas_unit_price")),
+        format!("timestamp_us: {}", field("timestamp")),
+    ];
+
+    if let Some(payload) = tx.get("payload") {
+        lines.push(format!(
+            "function: {}",
+            payload
+                .get("function")
+                .and_then(Value::as_str)
+                .unwrap_or("none")
+        ));
+        if let Some(type_args) = payload.get("type_arguments") {
+            lines.push(format!(
+                "type_arguments: {}",
+                truncate(&type_args.to_string(), MAX_FIELD_CHARS)
+            ));
+        }
+        if let Some(args) = payload.get("arguments") {
+            lines.push(format!(
+                "arguments: {}",
+                truncate(&args.to_string(), MAX_FIELD_CHARS)
+            ));
+        }
+    }
+
+    let events = tx
+        .get("events")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let (mut key_events, other_events): (Vec<_>, Vec<_>) = events.iter().partition(|e| {
+        e.get("type")
+            .and_then(Value::as_str)
+            .is_some_and(is_transfer_event)
+    });
+    key_events.extend(other_events);
+
+    lines.push(format!("events ({} total, transfers first):", events.len()));
+    for event in key_events.iter().take(MAX_EVENTS) {
+        lines.push(format!(
+            "- {} {}",
+            event.get("type").and_then(Value::as_str).unwrap_or("unknown"),
+            truncate(
+                &event.get("data").map(|d| d.to_string()).unwrap_or_default(),
+                MAX_FIELD_CHARS
+            )
+        ));
+    }
+    if events.len() > MAX_EVENTS {
+        lines.push(format!("- … {} more events omitted", events.len() - MAX_EVENTS));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_tx_hash() {
+        let hex = "AB".repeat(32);
+        assert_eq!(normalize_tx_hash(&hex), Some(format!("0x{}", "ab".repeat(32))));
+        assert_eq!(
+            normalize_tx_hash(&format!(" 0x{} ", hex)),
+            Some(format!("0x{}", "ab".repeat(32)))
+        );
+        assert_eq!(normalize_tx_hash("0x1234"), None);
+        assert_eq!(normalize_tx_hash(&"zz".repeat(32)), None);
+    }
+
+    #[test]
+    fn test_digest_lists_transfers_first() {
+        let tx = json!({
+            "type": "user_transaction",
+            "success": true,
+            "sender": "0xabc",
+            "payload": {"function": "0x1::aptos_account::transfer", "arguments": ["0xdef", "100"]},
+            "events": [
+                {"type": "0x1::transaction_fee::FeeStatement", "data": {"total_charge_gas_units": "9"}},
+                {"type": "0x1::fungible_asset::Withdraw", "data": {"amount": "100"}},
+            ]
+        });
+
+        let digest = transaction_digest(&tx);
+        let withdraw = digest.find("fungible_asset::Withdraw").unwrap();
+        let fee = digest.find("FeeStatement").unwrap();
+        assert!(withdraw < fee);
+        assert!(digest.contains("function: 0x1::aptos_account::transfer"));
+    }
+}
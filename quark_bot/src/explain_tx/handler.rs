@@ -0,0 +1,202 @@
+use anyhow::Result as AnyResult;
+use open_ai_rust_responses_by_sshift::Model;
+use teloxide::{prelude::*, types::Message, utils::html};
+
+use super::explain_tx::{normalize_tx_hash, transaction_digest};
+use crate::{
+    dependencies::BotDependencies,
+    payment::memo::memo_html_line,
+    utils::{create_purchase_request, is_admin, send_html_message, send_message},
+};
+
+const EXPLAIN_MAX_TOKENS: u32 = 800;
+
+const EXPLAIN_INSTRUCTIONS: &str = "You explain Aptos blockchain transactions to non-technical users. \
+Given a transaction digest, say in plain English what happened: who sent what to whom, which \
+contract function ran, whether it succeeded, and roughly what it cost in gas. Focus on the key \
+token transfers; group repetitive events instead of listing each one. Amounts are raw on-chain \
+integers, so don't guess decimals unless the token is obvious. Keep it under 150 words and don't \
+use markdown.";
+
+fn explorer_link(hash: &str) -> String {
+    let network = std::env::var("APTOS_NETWORK")
+        .unwrap_or_else(|_| "mainnet".to_string())
+        .to_lowercase();
+    format!(
+        "https://explorer.aptoslabs.com/txn/{}?network={}",
+        hash, network
+    )
+}
+
+fn format_explanation(hash: &str, explanation: &str, cached: bool, memo: Option<&str>) -> String {
+    format!(
+        "🔍 <b>Transaction explained</b>{}\n\n{}{}\n\n🔗 <a href=\"{}\">View on explorer</a>",
+        if cached { " <i>(cached)</i>" } else { "" },
+        html::escape(explanation.trim()),
+        memo_html_line(memo),
+        explorer_link(hash)
+    )
+}
+
+/// /explaintx <hash> — plain-English summary of an on-chain transaction, billed like /c
+pub async fn handle_explain_tx_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(hash) = normalize_tx_hash(&args) else {
+        send_message(
+            msg,
+            bot,
+            "Usage: /explaintx <transaction hash>, e.g. /explaintx 0x3f9c…(64 hex characters)"
+                .to_string(),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ User not found".to_string()).await?;
+        return Ok(());
+    };
+    let user_id = user.id.0 as i64;
+    let now = chrono::Utc::now().timestamp();
+    let explanations = &bot_deps.tx_explanations;
+    // Memos sent through the bot are stored off-chain, so show them alongside
+    let memo = bot_deps.payment_memos.get(&hash);
+
+    // Explanations are cached per hash, so repeats are free and skip the cooldown
+    if let Some(text) = explanations.get_cached(&hash, now) {
+        send_html_message(msg, bot, format_explanation(&hash, &text, true, memo.as_deref())).await?;
+        return Ok(());
+    }
+
+    // In groups the group is billed, so only admins may request new explanations
+    if !msg.chat.is_private() && !is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only group admins can explain new transactions here, since the group pays. Use /explaintx in a DM with the bot instead.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if let Some(wait) = explanations.cooldown_remaining(user_id, now) {
+        send_message(
+            msg,
+            bot,
+            format!("⏳ Please wait {}s before explaining another transaction.", wait),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let group_id = if msg.chat.is_private() {
+        None
+    } else {
+        Some(msg.chat.id.to_string())
+    };
+    let jwt = if group_id.is_some() {
+        bot_deps.group.get_credentials(msg.chat.id).map(|c| c.jwt)
+    } else {
+        user.username
+            .as_ref()
+            .and_then(|username| bot_deps.auth.get_credentials(username))
+            .map(|c| c.jwt)
+    };
+    let Some(jwt) = jwt else {
+        let text = if group_id.is_some() {
+            "❌ This group isn't logged in yet. An admin can use /logingroup."
+        } else {
+            "❌ Please log in with /loginuser first."
+        };
+        send_message(msg, bot, text.to_string()).await?;
+        return Ok(());
+    };
+
+    let tx = match bot_deps.panora.aptos.get_transaction_by_hash(&hash).await {
+        Ok(Some(tx)) => tx,
+        Ok(None) => {
+            send_message(
+                msg,
+                bot,
+                "❌ Transaction not found. Check the hash and the network.".to_string(),
+            )
+            .await?;
+            return Ok(());
+        }
+        Err(e) => {
+            log::error!("Failed to fetch transaction {}: {}", hash, e);
+            send_message(
+                msg,
+                bot,
+                "❌ Couldn't reach the Aptos node right now. Please try again.".to_string(),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    explanations.start_cooldown(user_id, now)?;
+
+    let digest = transaction_digest(&tx);
+    let (explanation, total_tokens) = match bot_deps
+        .ai
+        .generate_text_response(
+            EXPLAIN_INSTRUCTIONS,
+            &digest,
+            Model::GPT5Mini,
+            EXPLAIN_MAX_TOKENS,
+        )
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!("Failed to explain transaction {}: {}", hash, e);
+            send_message(
+                msg,
+                bot,
+                "❌ Couldn't explain this transaction right now. Please try again.".to_string(),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(e) = create_purchase_request(
+        0,
+        0,
+        0,
+        total_tokens,
+        Model::GPT5Mini,
+        &jwt,
+        group_id,
+        Some(user_id.to_string()),
+        bot_deps.clone(),
+    )
+    .await
+    {
+        log::error!("Error purchasing tokens for /explaintx: {}", e);
+        send_message(
+            msg,
+            bot,
+            "❌ Couldn't charge for this request. Check your balance and try again.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if explanation.trim().is_empty() {
+        send_message(msg, bot, "❌ The AI returned an empty explanation.".to_string()).await?;
+        return Ok(());
+    }
+
+    if let Err(e) = explanations.cache(&hash, &explanation, now) {
+        log::error!("Failed to cache explanation for {}: {}", hash, e);
+    }
+
+    send_html_message(msg, bot, format_explanation(&hash, &explanation, false, memo.as_deref())).await?;
+    Ok(())
+}
@@ -0,0 +1,4 @@
+pub mod explain_tx;
+pub mod handler;
+
+pub use explain_tx::TxExplanations;
@@ -497,6 +497,10 @@ async fn show_group_settings_menu(
             "📋 Summarization Settings",
             "open_group_summarization_settings",
         )],
+        vec![InlineKeyboardButton::callback(
+            "🧠 History Settings",
+            "open_history_settings",
+        )],
         vec![InlineKeyboardButton::callback(
             "🔄 Migrate Group ID",
             "open_migrate_group_id",
@@ -1,4 +1,4 @@
-use std::env;
+use std::{collections::HashSet, env, future::Future};
 
 use anyhow::Result;
 use aptos_rust_sdk_types::api_types::view::ViewRequest;
@@ -6,14 +6,20 @@ use quark_core::helpers::jwt::JwtManager;
 use serde_json::Value;
 use sled::Tree;
 use teloxide::types::{ChatId, Message};
+use tokio::sync::OwnedMutexGuard;
 
-use crate::{group::dto::GroupCredentials, panora::handler::Panora};
+use crate::{
+    group::{dto::GroupCredentials, login_lock::GroupLocks},
+    message_history::handler::HistorySettings,
+    panora::handler::Panora,
+};
 
 #[derive(Clone)]
 pub struct Group {
     pub jwt_manager: JwtManager,
     pub db: Tree,
     pub account_seed: String,
+    pub login_locks: GroupLocks,
 }
 
 impl Group {
@@ -27,6 +33,7 @@ impl Group {
             jwt_manager,
             db,
             account_seed,
+            login_locks: GroupLocks::new(),
         }
     }
 
@@ -59,14 +66,18 @@ impl Group {
         Ok(())
     }
 
-    pub fn generate_new_jwt(&self, group_id: ChatId) -> bool {
-        let group_id = format!("{}-{}", group_id, self.account_seed);
+    pub fn generate_new_jwt(&self, chat_id: ChatId) -> bool {
+        let group_id = format!("{}-{}", chat_id, self.account_seed);
 
         match self.jwt_manager.generate_group_token(group_id.clone()) {
             Ok(token) => {
                 let jwt = token;
 
-                let users: Vec<String> = vec![];
+                // Saving replaces the member list, so carry the registered users over
+                let users: Vec<String> = self
+                    .get_credentials(chat_id)
+                    .map(|credentials| credentials.users)
+                    .unwrap_or_default();
 
                 let credentials =
                     GroupCredentials::from((jwt, group_id.clone(), "".to_string(), users));
@@ -88,6 +99,98 @@ impl Group {
         }
     }
 
+    /// /logingroup: create the group on-chain unless `exists` says it already is, then issue
+    /// a fresh JWT. The group's login lock is taken before the existence check, so concurrent
+    /// logins create the group once; the guard is returned so the caller can finish its own
+    /// credential writes under it.
+    pub async fn login<E, EFut, C, CFut>(
+        &self,
+        chat_id: ChatId,
+        exists: E,
+        create: C,
+    ) -> Result<(GroupCredentials, OwnedMutexGuard<()>)>
+    where
+        E: FnOnce() -> EFut,
+        EFut: Future<Output = bool>,
+        C: FnOnce() -> CFut,
+        CFut: Future<Output = Result<()>>,
+    {
+        let guard = self.login_locks.lock(chat_id).await;
+
+        if !exists().await {
+            create().await.map_err(|e| {
+                log::error!("Failed to create group {}: {}", chat_id, e);
+                anyhow::anyhow!("Unable to create group")
+            })?;
+        }
+
+        if !self.generate_new_jwt(chat_id) {
+            return Err(anyhow::anyhow!("Unable to generate JWT"));
+        }
+
+        let credentials = self
+            .get_credentials(chat_id)
+            .ok_or_else(|| anyhow::anyhow!("Unable to get credentials"))?;
+
+        Ok((credentials, guard))
+    }
+
+    /// Re-issue the group JWT outside /logingroup, e.g. when sentinel finds it expired.
+    /// Holds the login lock and looks the resource account address up again when none is
+    /// stored, so callers never get credentials without a wallet.
+    pub async fn refresh_credentials(
+        &self,
+        chat_id: ChatId,
+        panora: Panora,
+    ) -> Result<GroupCredentials> {
+        let _guard = self.login_locks.lock(chat_id).await;
+
+        if !self.generate_new_jwt(chat_id) {
+            return Err(anyhow::anyhow!("Unable to generate JWT"));
+        }
+
+        let mut credentials = self
+            .get_credentials(chat_id)
+            .ok_or_else(|| anyhow::anyhow!("Unable to get credentials"))?;
+
+        if credentials.resource_account_address.is_empty() {
+            credentials.resource_account_address =
+                self.fetch_resource_account_address(chat_id, panora).await?;
+            self.save_credentials(credentials.clone())?;
+        }
+
+        Ok(credentials)
+    }
+
+    /// The group's on-chain resource account (its wallet)
+    pub async fn fetch_resource_account_address(
+        &self,
+        chat_id: ChatId,
+        panora: Panora,
+    ) -> Result<String> {
+        let group_id = format!("{}-{}", chat_id, self.account_seed);
+
+        let response = panora
+            .aptos
+            .node
+            .view_function(ViewRequest {
+                function: format!(
+                    "{}::group::get_group_account",
+                    panora.aptos.contract_address
+                ),
+                type_arguments: vec![],
+                arguments: vec![Value::String(group_id)],
+            })
+            .await?
+            .into_inner();
+
+        serde_json::from_value::<Vec<String>>(response)?
+            .into_iter()
+            .next()
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Resource account address not found"))
+    }
+
     pub fn get_credentials(&self, group_id: ChatId) -> Option<GroupCredentials> {
         let group_id = format!("{}-{}", group_id, self.account_seed);
 
@@ -101,6 +204,44 @@ impl Group {
         }
     }
 
+    /// Message history buffer size for the group; defaults until an admin changes it
+    pub fn get_history_settings(&self, chat_id: ChatId) -> HistorySettings {
+        self.db
+            .get(format!("history_settings:{}", chat_id))
+            .ok()
+            .flatten()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_history_settings(&self, chat_id: ChatId, settings: &HistorySettings) -> Result<()> {
+        self.db.insert(
+            format!("history_settings:{}", chat_id),
+            serde_json::to_vec(settings)?,
+        )?;
+        Ok(())
+    }
+
+    /// Formatted ids (`<chat_id>-<seed>`) of every logged-in group the user belongs to
+    pub fn group_ids_for_user(&self, username: &str) -> HashSet<String> {
+        self.db
+            .iter()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|(key, bytes)| {
+                let credentials: GroupCredentials = serde_json::from_slice(&bytes).ok()?;
+                let is_member = credentials
+                    .users
+                    .iter()
+                    .any(|user| user.eq_ignore_ascii_case(username));
+                if is_member {
+                    String::from_utf8(key.to_vec()).ok()
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
     pub async fn group_exists(&self, group_id: ChatId, panora: Panora) -> bool {
         let group_id = format!("{}-{}", group_id, self.account_seed);
 
@@ -169,6 +310,9 @@ impl Group {
     }
 
     pub async fn add_user_to_group(&self, group_id: ChatId, username: String) -> Result<()> {
+        // Serialize with logins so a concurrent credential save can't drop the new user
+        let _guard = self.login_locks.lock(group_id).await;
+
         let credentials = self.get_credentials(group_id);
 
         if let Some(credentials) = credentials {
@@ -193,3 +337,61 @@ impl Group {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_logins_create_group_once() {
+        unsafe {
+            env::set_var("SECRET", "test-secret");
+            env::set_var("ACCOUNT_SEED", "test-seed");
+        }
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let group = Group::new(db.open_tree("group").unwrap());
+        let chat_id = ChatId(-100);
+        // Stands in for the on-chain registry
+        let on_chain = Arc::new(AtomicBool::new(false));
+        let creations = Arc::new(AtomicU32::new(0));
+
+        let logins: Vec<_> = (0..5)
+            .map(|_| {
+                let group = group.clone();
+                let on_chain = on_chain.clone();
+                let creations = creations.clone();
+                tokio::spawn(async move {
+                    let exists = on_chain.clone();
+                    group
+                        .login(
+                            chat_id,
+                            || async move { exists.load(Ordering::SeqCst) },
+                            || async move {
+                                // The round trip to the chain between check and create
+                                tokio::time::sleep(Duration::from_millis(10)).await;
+                                creations.fetch_add(1, Ordering::SeqCst);
+                                on_chain.store(true, Ordering::SeqCst);
+                                Ok(())
+                            },
+                        )
+                        .await
+                        .map(|(credentials, _guard)| credentials)
+                })
+            })
+            .collect();
+
+        for login in logins {
+            let credentials = login.await.unwrap().unwrap();
+            assert_eq!(credentials.group_id, "-100-test-seed");
+            assert!(!credentials.jwt.is_empty());
+        }
+
+        assert_eq!(creations.load(Ordering::SeqCst), 1);
+        assert!(group.get_credentials(chat_id).is_some());
+    }
+}
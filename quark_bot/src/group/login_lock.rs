@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use teloxide::types::ChatId;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Per-group async locks so concurrent logins and credential writes for the
+/// same group run one at a time, while different groups stay independent.
+#[derive(Clone, Default)]
+pub struct GroupLocks {
+    locks: Arc<DashMap<ChatId, Arc<Mutex<()>>>>,
+}
+
+impl GroupLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for exclusive access to the group; released when the guard is dropped
+    pub async fn lock(&self, group_id: ChatId) -> OwnedMutexGuard<()> {
+        let lock = self
+            .locks
+            .entry(group_id)
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone();
+
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_different_groups_do_not_block_each_other() {
+        let locks = GroupLocks::new();
+        let _first = locks.lock(ChatId(-1)).await;
+
+        let second = tokio::time::timeout(Duration::from_millis(100), locks.lock(ChatId(-2))).await;
+
+        assert!(second.is_ok());
+    }
+}
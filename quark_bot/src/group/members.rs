@@ -0,0 +1,154 @@
+//! `/members`: which group members are registered with Quark and can receive payments.
+
+use anyhow::Result as AnyResult;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    dependencies::BotDependencies,
+    utils::{self, send_message},
+};
+
+const MEMBERS_PAGE_SIZE: usize = 25;
+
+/// Only usernames the bot has seen posting in this group are listed, so nothing private leaks
+fn build_members_page(bot_deps: &BotDependencies, chat_id: ChatId, page: usize) -> (String, InlineKeyboardMarkup) {
+    let Some(credentials) = bot_deps.group.get_credentials(chat_id) else {
+        return (
+            "❌ Group not found, please login again".to_string(),
+            InlineKeyboardMarkup::new(Vec::<Vec<InlineKeyboardButton>>::new()),
+        );
+    };
+
+    let mut members: Vec<(String, bool)> = credentials
+        .users
+        .iter()
+        .map(|username| {
+            let registered = bot_deps.auth.get_credentials(username).is_some();
+            (username.clone(), registered)
+        })
+        .collect();
+    // Registered first, then alphabetical
+    members.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase())));
+    members.dedup_by(|a, b| a.0.eq_ignore_ascii_case(&b.0));
+
+    let registered = members.iter().filter(|(_, r)| *r).count();
+    let unregistered = members.len() - registered;
+
+    let mut text = format!(
+        "👥 <b>Group Members on Quark</b>\n\n✅ Registered: <b>{}</b>\n❌ Not registered: <b>{}</b>\n",
+        registered, unregistered
+    );
+
+    if members.is_empty() {
+        text.push_str("\n<i>No members seen yet. Members appear here after they post in the group.</i>");
+        return (
+            text,
+            InlineKeyboardMarkup::new(Vec::<Vec<InlineKeyboardButton>>::new()),
+        );
+    }
+
+    let total_pages = members.len().div_ceil(MEMBERS_PAGE_SIZE);
+    let page = page.min(total_pages - 1);
+
+    if total_pages > 1 {
+        text.push_str(&format!("\n<b>Page {}/{}</b>\n", page + 1, total_pages));
+    } else {
+        text.push('\n');
+    }
+
+    for (username, registered) in members
+        .iter()
+        .skip(page * MEMBERS_PAGE_SIZE)
+        .take(MEMBERS_PAGE_SIZE)
+    {
+        text.push_str(&format!(
+            "{} @{}\n",
+            if *registered { "✅" } else { "❌" },
+            html::escape(username)
+        ));
+    }
+
+    if unregistered > 0 {
+        text.push_str("\n💡 <i>Members marked ❌ can't receive payments or scheduled payments until they log in with /loginuser in a DM with the bot.</i>");
+    }
+
+    let mut nav_row = Vec::new();
+    if page > 0 {
+        nav_row.push(InlineKeyboardButton::callback(
+            "⬅️ Prev",
+            format!("members_page:{}", page - 1),
+        ));
+    }
+    if page + 1 < total_pages {
+        nav_row.push(InlineKeyboardButton::callback(
+            "Next ➡️",
+            format!("members_page:{}", page + 1),
+        ));
+    }
+    let rows = if nav_row.is_empty() { vec![] } else { vec![nav_row] };
+
+    (text, InlineKeyboardMarkup::new(rows))
+}
+
+pub async fn handle_members(bot: Bot, msg: Message, bot_deps: BotDependencies) -> AnyResult<()> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(());
+    };
+
+    if !utils::is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only administrators can list group members.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (text, keyboard) = build_members_page(&bot_deps, msg.chat.id, 0);
+
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn handle_members_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let page = query
+        .data
+        .as_deref()
+        .and_then(|data| data.strip_prefix("members_page:"))
+        .and_then(|p| p.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+
+    if !utils::is_admin(&bot, m.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can list group members")
+            .await?;
+        return Ok(());
+    }
+
+    let (text, keyboard) = build_members_page(&bot_deps, m.chat.id, page);
+
+    bot.edit_message_text(m.chat.id, m.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await?;
+    bot.answer_callback_query(query.id).await?;
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
 pub mod document_library;
 pub mod dto;
 pub mod handler;
+pub mod login_lock;
+pub mod members;
@@ -0,0 +1,158 @@
+//! Per-group monthly cap on AI spend. Spend is the USD estimate of each group purchase;
+//! periods are calendar months in UTC and reset on the first of the month.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use teloxide::types::ChatId;
+
+const TREE_NAME: &str = "group_ai_budgets";
+/// Share of the budget at which admins get a heads-up
+pub const WARN_FRACTION: f64 = 0.8;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupBudget {
+    /// Monthly cap in USD; None means unlimited
+    pub monthly_limit_usd: Option<f64>,
+    /// Calendar month the running total belongs to, e.g. "2026-10"
+    pub period: String,
+    pub spent_usd: f64,
+    pub warning_sent: bool,
+    pub pause_notice_sent: bool,
+}
+
+impl GroupBudget {
+    /// Start a fresh total when the stored one belongs to an earlier month
+    fn roll_to(&mut self, period: &str) {
+        if self.period != period {
+            self.period = period.to_string();
+            self.spent_usd = 0.0;
+            self.warning_sent = false;
+            self.pause_notice_sent = false;
+        }
+    }
+
+    pub fn used_fraction(&self) -> Option<f64> {
+        self.monthly_limit_usd
+            .filter(|limit| *limit > 0.0)
+            .map(|limit| self.spent_usd / limit)
+    }
+
+    /// AI features pause once the month's spend reaches the cap
+    pub fn is_exhausted(&self) -> bool {
+        self.monthly_limit_usd
+            .is_some_and(|limit| self.spent_usd >= limit)
+    }
+
+    pub fn should_warn(&self) -> bool {
+        !self.warning_sent
+            && !self.is_exhausted()
+            && self.used_fraction().is_some_and(|used| used >= WARN_FRACTION)
+    }
+}
+
+pub fn current_period(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+/// First day of the month after `now`, when the running total resets
+pub fn next_reset(now: DateTime<Utc>) -> NaiveDate {
+    let (year, month) = if now.month() == 12 {
+        (now.year() + 1, 1)
+    } else {
+        (now.year(), now.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).expect("first of month is a valid date")
+}
+
+#[derive(Clone)]
+pub struct GroupBudgets {
+    tree: Tree,
+}
+
+impl GroupBudgets {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    /// The group's budget for the current month
+    pub fn get(&self, chat_id: ChatId) -> GroupBudget {
+        let mut budget: GroupBudget = self
+            .tree
+            .get(chat_id.0.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default();
+        budget.roll_to(&current_period(Utc::now()));
+        budget
+    }
+
+    fn set(&self, chat_id: ChatId, budget: &GroupBudget) -> sled::Result<()> {
+        self.tree
+            .insert(chat_id.0.to_be_bytes(), serde_json::to_vec(budget).unwrap())?;
+        Ok(())
+    }
+
+    /// Change the cap; raising it above the month's spend resumes AI features right away
+    pub fn set_limit(&self, chat_id: ChatId, limit_usd: Option<f64>) -> sled::Result<GroupBudget> {
+        let mut budget = self.get(chat_id);
+        budget.monthly_limit_usd = limit_usd;
+        if !budget.is_exhausted() {
+            budget.pause_notice_sent = false;
+        }
+        if budget.used_fraction().is_none_or(|used| used < WARN_FRACTION) {
+            budget.warning_sent = false;
+        }
+        self.set(chat_id, &budget)?;
+        Ok(budget)
+    }
+
+    pub fn record_spend(&self, chat_id: ChatId, usd: f64) -> sled::Result<()> {
+        let mut budget = self.get(chat_id);
+        budget.spent_usd += usd.max(0.0);
+        self.set(chat_id, &budget)
+    }
+
+    pub fn mark_warning_sent(&self, chat_id: ChatId) -> sled::Result<()> {
+        let mut budget = self.get(chat_id);
+        budget.warning_sent = true;
+        self.set(chat_id, &budget)
+    }
+
+    pub fn mark_pause_notice_sent(&self, chat_id: ChatId) -> sled::Result<()> {
+        let mut budget = self.get(chat_id);
+        budget.pause_notice_sent = true;
+        self.set(chat_id, &budget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_budget_thresholds_and_rollover() {
+        let mut budget = GroupBudget {
+            monthly_limit_usd: Some(10.0),
+            period: "2026-09".to_string(),
+            spent_usd: 8.5,
+            ..Default::default()
+        };
+        assert!(budget.should_warn());
+        assert!(!budget.is_exhausted());
+
+        budget.spent_usd = 10.0;
+        assert!(budget.is_exhausted());
+        assert!(!budget.should_warn());
+
+        budget.roll_to("2026-10");
+        assert_eq!(budget.spent_usd, 0.0);
+        assert!(!budget.is_exhausted());
+
+        let december = Utc.with_ymd_and_hms(2026, 12, 15, 0, 0, 0).unwrap();
+        assert_eq!(next_reset(december), NaiveDate::from_ymd_opt(2027, 1, 1).unwrap());
+    }
+}
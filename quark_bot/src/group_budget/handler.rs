@@ -0,0 +1,163 @@
+use anyhow::Result as AnyResult;
+use chrono::Utc;
+use teloxide::{prelude::*, types::ParseMode};
+
+use crate::{
+    dependencies::BotDependencies,
+    group_budget::{
+        group_budget::{GroupBudget, next_reset},
+        pricing::prices_loaded,
+    },
+    utils::{self, send_html_message, send_message},
+};
+
+fn parse_limit(args: &str) -> Result<Option<f64>, String> {
+    let value = args.trim().trim_start_matches('$');
+    if value.eq_ignore_ascii_case("off") || value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    match value.parse::<f64>() {
+        Ok(limit) if limit > 0.0 && limit.is_finite() => Ok(Some(limit)),
+        _ => Err("❌ Usage: /aibudget <monthly USD amount>, /aibudget off, or /aibudget to see the current budget.".to_string()),
+    }
+}
+
+fn format_status(budget: &GroupBudget) -> String {
+    let reset = next_reset(Utc::now()).format("%-d %b %Y");
+    match budget.monthly_limit_usd {
+        None => format!(
+            "💸 <b>AI Budget</b>\n\nNo monthly limit. This month's AI spend: ${:.2}\n\nSet one with /aibudget &lt;USD&gt;.",
+            budget.spent_usd
+        ),
+        Some(limit) => {
+            let status = if budget.is_exhausted() {
+                "⏸️ Paused: /g and sentinel AI moderation are off until the budget resets or is raised."
+            } else {
+                "✅ Active"
+            };
+            format!(
+                "💸 <b>AI Budget</b>\n\n${:.2} of ${:.2} used this month ({:.0}%)\n{}\n\nResets on {} (UTC, calendar month).",
+                budget.spent_usd,
+                limit,
+                budget.used_fraction().unwrap_or(0.0) * 100.0,
+                status,
+                reset
+            )
+        }
+    }
+}
+
+/// /aibudget [USD|off] — show or change the group's monthly AI budget (admins only)
+pub async fn handle_ai_budget_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(user) = &msg.from else {
+        return Ok(());
+    };
+    if !utils::is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(msg, bot, "❌ Only administrators can manage the AI budget.".to_string())
+            .await?;
+        return Ok(());
+    }
+
+    let budget = if args.trim().is_empty() {
+        bot_deps.group_budgets.get(msg.chat.id)
+    } else {
+        match parse_limit(&args) {
+            Ok(Some(_)) if !prices_loaded() => {
+                send_message(
+                    msg,
+                    bot,
+                    "❌ AI prices aren't available, so spend can't be tracked. A budget can't be set until they load."
+                        .to_string(),
+                )
+                .await?;
+                return Ok(());
+            }
+            Ok(limit) => bot_deps.group_budgets.set_limit(msg.chat.id, limit)?,
+            Err(usage) => {
+                send_message(msg, bot, usage).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    send_html_message(msg, bot, format_status(&budget)).await?;
+    Ok(())
+}
+
+async fn notify(bot: &Bot, msg: &Message, text: String) -> AnyResult<()> {
+    let mut request = bot
+        .send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html);
+    if let Some(thread_id) = utils::topic_thread_id(msg) {
+        request = request.message_thread_id(thread_id);
+    }
+    request.await?;
+    Ok(())
+}
+
+/// Gate for group AI features. Returns false while the month's budget is used up. The 80%
+/// warning is posted once per period; the pause notice once, or on every refusal when
+/// `repeat_pause_notice` is set (for commands a user is waiting on).
+pub async fn group_ai_allowed(
+    bot: &Bot,
+    msg: &Message,
+    bot_deps: &BotDependencies,
+    repeat_pause_notice: bool,
+) -> AnyResult<bool> {
+    let budget = bot_deps.group_budgets.get(msg.chat.id);
+    let reset = next_reset(Utc::now()).format("%-d %b");
+
+    if budget.is_exhausted() {
+        if repeat_pause_notice || !budget.pause_notice_sent {
+            bot_deps.group_budgets.mark_pause_notice_sent(msg.chat.id)?;
+            notify(
+                bot,
+                msg,
+                format!(
+                    "⏸️ <b>AI budget reached</b>\n\nThis group has spent ${:.2} of its ${:.2} monthly AI budget, so /g and sentinel AI moderation are paused until {}. Admins can raise the budget with /aibudget.",
+                    budget.spent_usd,
+                    budget.monthly_limit_usd.unwrap_or_default(),
+                    reset
+                ),
+            )
+            .await?;
+        }
+        return Ok(false);
+    }
+
+    if budget.should_warn() {
+        bot_deps.group_budgets.mark_warning_sent(msg.chat.id)?;
+        notify(
+            bot,
+            msg,
+            format!(
+                "⚠️ <b>AI budget at {:.0}%</b>\n\nThis group has spent ${:.2} of its ${:.2} monthly AI budget. AI features pause when it runs out; it resets on {}. Admins can adjust it with /aibudget.",
+                budget.used_fraction().unwrap_or(0.0) * 100.0,
+                budget.spent_usd,
+                budget.monthly_limit_usd.unwrap_or_default(),
+                reset
+            ),
+        )
+        .await?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limit() {
+        assert_eq!(parse_limit("25"), Ok(Some(25.0)));
+        assert_eq!(parse_limit("$7.5"), Ok(Some(7.5)));
+        assert_eq!(parse_limit("off"), Ok(None));
+        assert!(parse_limit("-3").is_err());
+        assert!(parse_limit("lots").is_err());
+    }
+}
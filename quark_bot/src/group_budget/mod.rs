@@ -0,0 +1,5 @@
+pub mod group_budget;
+pub mod handler;
+pub mod pricing;
+
+pub use group_budget::GroupBudgets;
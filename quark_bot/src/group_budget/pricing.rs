@@ -0,0 +1,97 @@
+//! USD estimate of an AI purchase, read from the same price table the consumer bills with.
+//! The consumer converts to the payment token on-chain; budgets only need the USD side.
+
+use std::env;
+use std::sync::OnceLock;
+
+use open_ai_rust_responses_by_sshift::Model;
+use quark_core::helpers::dto::ToolUsage;
+use quark_core::helpers::prices::{Price, ToolName};
+
+/// The consumer's table, built in so the bot prices spend without shipping assets
+const BUNDLED_PRICES: &str = include_str!("../../../quark_consumer/assets/prices.ron");
+
+/// Price table from AI_PRICES_PATH if set, else the bundled one; loaded once, None when unreadable
+fn prices() -> Option<&'static Price> {
+    static PRICES: OnceLock<Option<Price>> = OnceLock::new();
+    PRICES
+        .get_or_init(|| {
+            let loaded = match env::var("AI_PRICES_PATH") {
+                Ok(path) => std::fs::read_to_string(&path)
+                    .map_err(|e| format!("{}: {}", path, e))
+                    .and_then(|content| {
+                        ron::from_str::<Price>(&content).map_err(|e| format!("{}: {}", path, e))
+                    }),
+                Err(_) => ron::from_str::<Price>(BUNDLED_PRICES)
+                    .map_err(|e| format!("bundled table: {}", e)),
+            };
+            match loaded {
+                Ok(table) => Some(table),
+                Err(e) => {
+                    log::error!(
+                        "Failed to load AI prices, group budgets won't track spend: {}",
+                        e
+                    );
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Whether spend can be priced; budgets can't be enforced without it
+pub fn prices_loaded() -> bool {
+    prices().is_some()
+}
+
+fn estimate_with(table: &Price, model: &str, tokens: u32, tools: &[ToolUsage]) -> Option<f64> {
+    let per_1k = table
+        .model
+        .iter()
+        .find(|m| m.name.to_string() == model)?
+        .price;
+    let tools_usd: f64 = tools
+        .iter()
+        .filter_map(|usage| {
+            let name = ToolName::from(&usage.tool).to_string();
+            table
+                .tool
+                .iter()
+                .find(|t| t.name.to_string() == name)
+                .map(|t| t.price * usage.calls as f64)
+        })
+        .sum();
+    Some(per_1k * tokens as f64 / 1000.0 + tools_usd)
+}
+
+/// USD cost of a purchase, or None for an unpriced model or a missing price table
+pub fn estimate_usd(model: &Model, tokens: u32, tools: &[ToolUsage]) -> Option<f64> {
+    estimate_with(prices()?, &model.to_string(), tokens, tools)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quark_core::helpers::dto::AITool;
+
+    #[test]
+    fn test_estimate_with_tokens_and_tools() {
+        let table: Price = ron::from_str(
+            "Price(model: [(name: GPT5Mini, price: 0.002)], tool: [(name: WebSearchPreview, price: 0.01)])",
+        )
+        .unwrap();
+        let tools = vec![ToolUsage {
+            tool: AITool::WebSearchPreview,
+            calls: 2,
+        }];
+
+        let usd = estimate_with(&table, "gpt-5-mini", 1500, &tools).unwrap();
+        assert!((usd - 0.023).abs() < 1e-9);
+        assert!(estimate_with(&table, "gpt-5", 1500, &tools).is_none());
+    }
+
+    #[test]
+    fn test_bundled_prices_parse() {
+        assert!(ron::from_str::<Price>(BUNDLED_PRICES).is_ok());
+    }
+}
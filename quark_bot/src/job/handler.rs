@@ -1,15 +1,32 @@
 use std::env;
 
 use chrono::Utc;
-use reqwest::Url;
-use teloxide::{Bot, types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup}};
+use sled::Db;
+use teloxide::{ApiError, Bot, RequestError, prelude::Requester, types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup}, utils::html};
 use tokio_cron_scheduler::Job;
 use aptos_rust_sdk_types::api_types::view::ViewRequest;
 
 use crate::{
-    dao::{dao::Dao, dto::ProposalEntry},
+    ai::{
+        moderation::flagged_log::FlaggedLog,
+        sentinel::{handler::group_balance_status, snooze::resume_due_snoozes},
+        upload_session::UploadSessions,
+    },
+    aptos::handler::Aptos,
+    assets::command_image_collector::PendingCommandStore,
+    dao::{
+        dao::Dao,
+        dto::ProposalEntry,
+        results::{ChoiceTally, format_dao_results},
+    },
+    dependencies::BotDependencies,
+    explain_tx::explain_tx::TxExplanations,
     panora::handler::Panora,
-    utils::{format_timestamp, send_scheduled_message, send_scheduled_message_with_keyboard},
+    payment::ledger::{LedgerEntry, PaymentLedger, ReconcileStatus, reconcile},
+    pending_transactions::handler::PendingTransactions,
+    scheduled_payments::storage::ScheduledPaymentsStorage,
+    scheduled_prompts::storage::ScheduledStorage,
+    utils::{format_timestamp, group_message_link, send_scheduled_message, send_scheduled_message_with_keyboard},
     welcome::welcome_service::WelcomeService,
 };
 use quark_core::helpers::dto::CoinVersion;
@@ -17,16 +34,6 @@ use quark_core::helpers::dto::CoinVersion;
 // Retry function for handling rate limits with exponential backoff
 
 
-// Helper function to escape special characters for MarkdownV2
-fn escape_markdown_v2(text: &str) -> String {
-    text.chars()
-        .map(|c| match c {
-            '_' | '*' | '[' | ']' | '(' | ')' | '~' | '`' | '>' | '#' | '+' | '-' | '=' | '|' | '{' | '}' | '.' | '!' => format!("\\{}", c),
-            _ => c.to_string(),
-        })
-        .collect()
-}
-
 pub fn job_token_list(panora: Panora) -> Job {
     Job::new_async("0 0 * * * *", move |_uuid, _l| {
         let panora = panora.clone();
@@ -63,6 +70,95 @@ pub fn job_welcome_service_cleanup(welcome_service: WelcomeService, bot: Bot) ->
     .expect("Failed to create cron job")
 }
 
+/// Clears /scheduleprompt wizards that were abandoned part-way
+pub fn job_scheduled_prompt_wizard_cleanup(scheduled_storage: ScheduledStorage) -> Job {
+    Job::new_async("0 */5 * * * *", move |_uuid, _l| {
+        let scheduled_storage = scheduled_storage.clone();
+        Box::pin(async move {
+            let removed = scheduled_storage.sweep_stale_pending();
+            if removed > 0 {
+                log::info!("Cleared {} abandoned scheduled prompt wizards", removed);
+            }
+        })
+    })
+    .expect("Failed to create cron job")
+}
+
+/// Expired pending transactions stay around this long so late button presses still explain
+/// themselves; overridden by PENDING_TX_RETENTION_HOURS
+const DEFAULT_PENDING_TX_RETENTION_HOURS: u64 = 24;
+
+/// Low-frequency sweep of sled trees that otherwise only shrink when a user comes back.
+/// Runs on MAINTENANCE_CRON (daily by default, "off" disables).
+pub fn job_store_maintenance(db: Db, ledger: PaymentLedger) -> Option<Job> {
+    let schedule = env::var("MAINTENANCE_CRON").unwrap_or_else(|_| "0 15 4 * * *".to_string());
+    if schedule.trim().eq_ignore_ascii_case("off") {
+        log::info!("Store maintenance job disabled");
+        return None;
+    }
+    let pending_retention_secs = env::var("PENDING_TX_RETENTION_HOURS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_PENDING_TX_RETENTION_HOURS)
+        * 60
+        * 60;
+
+    let pending_transactions =
+        PendingTransactions::new(&db).expect("Failed to open pending transactions tree");
+    let upload_sessions = UploadSessions::new(&db).expect("Failed to open upload sessions tree");
+    let tx_explanations = TxExplanations::new(&db).expect("Failed to open tx explanations tree");
+    let flagged_log = FlaggedLog::new(&db).expect("Failed to open flagged log tree");
+    let scheduled_payments =
+        ScheduledPaymentsStorage::new(&db).expect("Failed to open scheduled payments trees");
+    let pending_image_commands =
+        PendingCommandStore::new(&db).expect("Failed to open pending image commands tree");
+
+    let job = Job::new_async(schedule.as_str(), move |_uuid, _l| {
+        let pending_transactions = pending_transactions.clone();
+        let upload_sessions = upload_sessions.clone();
+        let tx_explanations = tx_explanations.clone();
+        let flagged_log = flagged_log.clone();
+        let ledger = ledger.clone();
+        let scheduled_payments = scheduled_payments.clone();
+        let pending_image_commands = pending_image_commands.clone();
+        Box::pin(async move {
+            let now = Utc::now().timestamp();
+            let pending_cutoff = (now as u64).saturating_sub(pending_retention_secs);
+
+            let pending = pending_transactions.purge_expired_before(pending_cutoff);
+            let uploads = upload_sessions.purge_expired(now);
+            let explanations = tx_explanations.purge_expired(now);
+            let flagged = flagged_log.purge_expired(now);
+            let payments = ledger.prune(now);
+            // Wizard and input states abandoned mid-way
+            let payment_wizards = scheduled_payments.sweep_stale_pending();
+            let image_commands = pending_image_commands.purge_expired(now);
+
+            log::info!(
+                "Store maintenance: removed {} pending transactions, {} upload sessions, {} tx explanations, {} flagged events, {} ledger entries, {} payment wizards, {} pending image commands",
+                pending,
+                uploads,
+                explanations,
+                flagged,
+                payments,
+                payment_wizards,
+                image_commands
+            );
+        })
+    });
+    match job {
+        Ok(job) => Some(job),
+        Err(e) => {
+            log::error!(
+                "Invalid MAINTENANCE_CRON \"{}\", store maintenance disabled: {}",
+                schedule,
+                e
+            );
+            None
+        }
+    }
+}
+
 pub fn job_token_ai_fees(panora: Panora) -> Job {
     // Run every 15 minutes instead of every minute to avoid rate limits
     Job::new_async("0 */15 * * * *", move |_uuid, _l| {
@@ -160,26 +256,10 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
                     let mut keyboard_buttons = Vec::new();
                     
                     for (index, option) in proposal_entry.options.iter().enumerate() {
-                        let base_url = format!(
-                            "{}/dao?group_id={}&proposal_id={}&choice_id={}&coin_type={}&coin_version={}&dao_name={}&dao_description={}",
-                            base_url,
-                            group_id,
-                            proposal_entry.proposal_id,
-                            index,
-                            proposal_entry.coin_type,
-                            match proposal_entry.version {
-                                CoinVersion::V1 => "V1",
-                                CoinVersion::V2 => "V2",
-                            },
-                            proposal_entry.name,
-                            proposal_entry.description
-                        );
-                        
-                        // Parse URL with error handling
-                        let parsed_url = match Url::parse(&base_url) {
-                            Ok(url) => url,
-                            Err(e) => {
-                                log::error!("Failed to parse URL for DAO {}: {}", proposal_entry.proposal_id, e);
+                        let parsed_url = match proposal_entry.vote_url(&base_url, index) {
+                            Some(url) => url,
+                            None => {
+                                log::error!("Failed to build vote URL for DAO {}", proposal_entry.proposal_id);
                                 continue;
                             }
                         };
@@ -196,6 +276,15 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
                         keyboard_buttons.push(option_row);
                     }
 
+                    // Link the discussion thread; a deleted discussion message surfaces when sending
+                    let discussion = proposal_entry
+                        .discussion_message_id
+                        .and_then(|id| group_message_link(chat_group_id, id).map(|link| (id, link)));
+                    let discussion_id = discussion.as_ref().map(|(id, _)| *id);
+                    if let Some((_, link)) = discussion {
+                        keyboard_buttons.push(vec![InlineKeyboardButton::url(DISCUSS_BUTTON, link)]);
+                    }
+
                     // Add a separator row with voting instructions
                     keyboard_buttons.push(vec![
                         InlineKeyboardButton::callback(
@@ -225,14 +314,37 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
 
                     log::info!("Sending active proposals notification for: {}", proposal_entry.proposal_id);
 
-                    // Send message with error handling
-                    match send_scheduled_message_with_keyboard(
+                    // With a discussion linked, the reminder replies to it: no probe call, and a
+                    // deleted discussion message fails the send, so the button is dropped and it's resent
+                    let mut sent = send_scheduled_message_with_keyboard(
                         &bot,
                         chat_group_id,
                         &message_text,
-                        proposal_entry.thread_id,
-                        keyboard,
-                    ).await {
+                        discussion_id.or(proposal_entry.thread_id),
+                        keyboard.clone(),
+                    ).await;
+                    if discussion_id.is_some()
+                        && matches!(sent, Err(RequestError::Api(ApiError::MessageToReplyNotFound)))
+                    {
+                        log::info!("Discussion message for proposal {} was deleted", proposal_entry.proposal_id);
+                        if let Err(e) = dao.set_discussion_message(proposal_entry.proposal_id.clone(), None) {
+                            log::error!("Failed to clear discussion message for proposal {}: {}", proposal_entry.proposal_id, e);
+                        }
+                        let mut keyboard = keyboard;
+                        keyboard
+                            .inline_keyboard
+                            .retain(|row| !row.iter().any(|button| button.text == DISCUSS_BUTTON));
+                        sent = send_scheduled_message_with_keyboard(
+                            &bot,
+                            chat_group_id,
+                            &message_text,
+                            proposal_entry.thread_id,
+                            keyboard,
+                        ).await;
+                    }
+
+                    // Send message with error handling
+                    match sent {
                         Ok(_) => {
                             log::info!("Successfully sent active proposals notification for: {}", proposal_entry.proposal_id);
                         }
@@ -260,6 +372,9 @@ pub fn job_active_daos(dao: Dao, bot: Bot) -> Job {
     .expect("Failed to create cron job")
 }
 
+/// Label of the proposal reminder's link to its discussion message
+const DISCUSS_BUTTON: &str = "💬 Discuss";
+
 pub fn job_daos_results(panora: Panora, bot: Bot, dao: Dao) -> Job {
     Job::new_async("0 */2 * * * *", move |_uuid, _l| {
         let panora = panora.clone();
@@ -405,55 +520,52 @@ async fn fetch_and_send_dao_results(
             
             let decimals = coin.decimals;
         
-            // Find the winning option
-            let mut max_votes = 0.0f64;
-            let mut winning_index = 0;
-            let mut total_votes = 0.0f64;
-            
-            for (index, weight) in choices_weights.iter().enumerate() {
-                let votes = weight.as_str().unwrap_or("0").parse::<u64>().unwrap_or(0);
-                let votes = votes as f64 / 10_f64.powi(decimals as i32);
-                total_votes += votes;
-                if votes > max_votes {
-                    max_votes = votes;
-                    winning_index = index;
+            let mut tallies: Vec<ChoiceTally> = choices
+                .iter()
+                .enumerate()
+                .map(|(index, choice)| {
+                    let weight = choices_weights
+                        .get(index)
+                        .and_then(|w| w.as_str())
+                        .unwrap_or("0")
+                        .parse::<u64>()
+                        .unwrap_or(0);
+                    ChoiceTally {
+                        name: choice.as_str().unwrap_or("Unknown").to_string(),
+                        weight: weight as f64 / 10_f64.powi(decimals as i32),
+                        voters: 0,
+                    }
+                })
+                .collect();
+
+            let user_choices = dao_info["user_choices"].as_array().unwrap_or(&empty_vec);
+            for user_choice in user_choices {
+                let choice_id = user_choice["choice_id"]
+                    .as_str()
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .or_else(|| user_choice["choice_id"].as_u64().map(|id| id as usize));
+                if let Some(tally) = choice_id.and_then(|id| tallies.get_mut(id)) {
+                    tally.voters += 1;
                 }
             }
-            
-            // Create results message (using MarkdownV2 formatting)
-            let mut results_text = format!(
-                "🏆 *DAO VOTING RESULTS*\n\n🏛️ *{}*\n📝 {}\n\n📊 *Results:*\n",
-                escape_markdown_v2(&proposal_entry.name),
-                escape_markdown_v2(&proposal_entry.description)
+
+            // Exclude the bot itself from the eligible member count
+            let eligible_members = match bot.get_chat_member_count(chat_group_id).await {
+                Ok(count) => Some(count.saturating_sub(1)),
+                Err(e) => {
+                    log::warn!("Failed to get member count for group {}: {}", group_id, e);
+                    None
+                }
+            };
+
+            let results_text = format_dao_results(
+                &proposal_entry.name,
+                &proposal_entry.description,
+                &coin.symbol,
+                &tallies,
+                eligible_members,
             );
-            
-            for (index, choice) in choices.iter().enumerate() {
-                let choice_name = choice.as_str().unwrap_or("Unknown");
-                let votes = choices_weights[index].as_str().unwrap_or("0").parse::<u64>().unwrap_or(0);
-                let votes = votes as f64 / 10_f64.powi(decimals as i32);
-                let percentage = if total_votes > 0.0f64 {
-                    (votes as f64 / total_votes as f64 * 100.0) as f64
-                } else {
-                    0.0f64
-                };
-                
-                let emoji = if index == winning_index { "🥇" } else { "📊" };
-                results_text.push_str(&format!(
-                    "{} *{}*: {} {:.2} votes \\({:.2}%\\)\n",
-                    emoji, escape_markdown_v2(choice_name), coin.symbol, votes, percentage
-                ).replace(".", "\\."));
-            }
-            
-            if total_votes > 0.0f64 {
-                let winning_choice = choices[winning_index].as_str().unwrap_or("Unknown");
-                results_text.push_str(&format!(
-                    "\n🎉 *Winner: {}* with {:.2} {} votes\\!\n📈 Total votes cast: {:.2} {}",
-                    escape_markdown_v2(winning_choice), max_votes, coin.symbol, total_votes, coin.symbol
-                ).replace(".", "\\."));
-            } else {
-                results_text.push_str("\n❌ No votes were cast for this DAO\\.");
-            }
-        
+
             // Send the results message with error handling
             match send_scheduled_message(
                 bot,
@@ -518,3 +630,192 @@ pub fn job_dao_results_cleanup(dao: Dao) -> Job {
     .expect("Failed to create cron job")
 }
 
+
+/// Entries younger than this may not be indexed by the node yet
+const RECONCILE_MIN_AGE_SECS: i64 = 5 * 60;
+const RECONCILE_BATCH: usize = 50;
+
+/// Read-only check of recorded payments against the chain. Frequency comes from
+/// RECONCILIATION_CRON; discrepancies go to RECONCILIATION_REPORT_CHAT_ID when set.
+pub fn job_payment_reconciliation(ledger: PaymentLedger, aptos: Aptos, bot: Bot) -> Option<Job> {
+    let schedule = env::var("RECONCILIATION_CRON").unwrap_or_else(|_| "0 0 * * * *".to_string());
+    if schedule.trim().eq_ignore_ascii_case("off") {
+        log::info!("Payment reconciliation job disabled");
+        return None;
+    }
+    let report_chat = env::var("RECONCILIATION_REPORT_CHAT_ID")
+        .ok()
+        .and_then(|id| id.trim().parse::<i64>().ok())
+        .map(ChatId);
+
+    let job = Job::new_async(schedule.as_str(), move |_uuid, _l| {
+        let ledger = ledger.clone();
+        let aptos = aptos.clone();
+        let bot = bot.clone();
+        Box::pin(async move {
+            let now = Utc::now().timestamp();
+            let entries = ledger.pending_before(now - RECONCILE_MIN_AGE_SECS, RECONCILE_BATCH);
+            log::info!("Reconciling {} recorded payments", entries.len());
+
+            let mut discrepancies = Vec::new();
+            let mut matched = 0;
+            for entry in entries {
+                // A node error says nothing about the payment, so retry next run
+                let tx = match aptos.get_transaction_by_hash(&entry.tx_hash).await {
+                    Ok(tx) => tx,
+                    Err(e) => {
+                        log::error!("Failed to fetch transaction {}: {}", entry.tx_hash, e);
+                        continue;
+                    }
+                };
+
+                let status = reconcile(&entry, tx.as_ref());
+                match &status {
+                    ReconcileStatus::Matched => matched += 1,
+                    ReconcileStatus::Discrepancy(reason) => {
+                        log::warn!(
+                            "Payment {} ({} in chat {}) does not reconcile: {}",
+                            entry.tx_hash,
+                            entry.source,
+                            entry.chat_id,
+                            reason
+                        );
+                        discrepancies.push(format_discrepancy(&entry, reason));
+                    }
+                    ReconcileStatus::Pending => {}
+                }
+                if let Err(e) = ledger.set_status(&entry, status, now) {
+                    log::error!("Failed to update ledger entry {}: {}", entry.tx_hash, e);
+                }
+            }
+
+            let pruned = ledger.prune(now);
+            log::info!(
+                "Payment reconciliation finished: {} matched, {} discrepancies, {} pruned",
+                matched,
+                discrepancies.len(),
+                pruned
+            );
+
+            if let (Some(chat_id), false) = (report_chat, discrepancies.is_empty()) {
+                let report = format!(
+                    "⚠️ <b>Payment reconciliation</b>\n{} of {} checked payments don't match the chain:\n\n{}",
+                    discrepancies.len(),
+                    matched + discrepancies.len(),
+                    discrepancies.join("\n\n")
+                );
+                if let Err(e) = send_scheduled_message(&bot, chat_id, &report, None).await {
+                    log::error!("Failed to send reconciliation report: {}", e);
+                }
+            }
+        })
+    })
+    .expect("Failed to create cron job");
+    Some(job)
+}
+
+fn format_discrepancy(entry: &LedgerEntry, reason: &str) -> String {
+    format!(
+        "• <code>{}</code>\n  {} {} from {} {} to {} recipient(s)\n  ❗ {}",
+        entry.tx_hash,
+        entry.amount,
+        html::escape(&entry.symbol),
+        if entry.is_group { "group" } else { "user" },
+        entry.chat_id,
+        entry.recipient_addresses.len(),
+        html::escape(reason)
+    )
+}
+
+/// Warn sentinel groups whose balance is close to the minimum before moderation stops
+pub fn job_low_balance_alerts(bot: Bot, bot_deps: BotDependencies) -> Option<Job> {
+    let schedule = env::var("LOW_BALANCE_ALERT_CRON").unwrap_or_else(|_| "0 30 * * * *".to_string());
+    if schedule.trim().eq_ignore_ascii_case("off") {
+        log::info!("Low balance alert job disabled");
+        return None;
+    }
+
+    let job = Job::new_async(schedule.as_str(), move |_uuid, _l| {
+        let bot = bot.clone();
+        let bot_deps = bot_deps.clone();
+        Box::pin(async move {
+            let now = Utc::now().timestamp();
+            let chat_ids = bot_deps.sentinel.enabled_chat_ids();
+            log::info!("Checking low balance for {} sentinel groups", chat_ids.len());
+
+            for chat_id in chat_ids {
+                let settings = bot_deps.low_balance_alerts.get(chat_id);
+                if !settings.enabled() {
+                    continue;
+                }
+                let Some(credentials) = bot_deps.group.get_credentials(chat_id) else {
+                    continue;
+                };
+                let address = credentials.resource_account_address;
+
+                let status = match group_balance_status(&bot_deps, chat_id, &address).await {
+                    Ok(Ok(status)) => status,
+                    Ok(Err(e)) => {
+                        log::warn!("Skipping low balance check for {}: {:?}", chat_id, e);
+                        continue;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to fetch balance for group {}: {}", chat_id, e);
+                        continue;
+                    }
+                };
+
+                let threshold = settings.threshold(status.min_deposit);
+                if status.balance >= threshold as i64 {
+                    if let Err(e) = bot_deps.low_balance_alerts.clear_alerted(chat_id) {
+                        log::error!("Failed to reset low balance alert for {}: {}", chat_id, e);
+                    }
+                    continue;
+                }
+                if !settings.should_alert(status.balance, status.min_deposit, now) {
+                    continue;
+                }
+
+                let text = format!(
+                    "🔔 <b>Low balance</b>\n\nThis group's balance is <b>{} {}</b>. Sentinel moderation and AI features stop below <b>{} {}</b>.\n\nAdmins, please top up by sending {} to <code>{}</code>.\n\n<i>Change or turn off this alert in Group Settings → Moderation.</i>",
+                    status.format_amount(status.balance),
+                    html::escape(&status.symbol),
+                    status.format_amount(status.min_deposit as i64),
+                    html::escape(&status.symbol),
+                    html::escape(&status.symbol),
+                    address
+                );
+                match send_scheduled_message(&bot, chat_id, &text, None).await {
+                    Ok(_) => {
+                        if let Err(e) = bot_deps.low_balance_alerts.mark_alerted(chat_id, now) {
+                            log::error!("Failed to record low balance alert for {}: {}", chat_id, e);
+                        }
+                    }
+                    Err(e) => log::error!("Failed to send low balance alert to {}: {}", chat_id, e),
+                }
+            }
+        })
+    });
+    match job {
+        Ok(job) => Some(job),
+        Err(e) => {
+            log::error!(
+                "Invalid LOW_BALANCE_ALERT_CRON \"{}\", low balance alerts disabled: {}",
+                schedule,
+                e
+            );
+            None
+        }
+    }
+}
+
+pub fn job_sentinel_snoozes(bot: Bot, bot_deps: BotDependencies) -> Job {
+    Job::new_async("0 * * * * *", move |_uuid, _l| {
+        let bot = bot.clone();
+        let bot_deps = bot_deps.clone();
+        Box::pin(async move {
+            resume_due_snoozes(&bot, &bot_deps).await;
+        })
+    })
+    .expect("Failed to create cron job")
+}
@@ -1,14 +1,19 @@
 use crate::dao::dao::Dao;
+use crate::dependencies::BotDependencies;
 use crate::job::handler::{
-    job_active_daos, job_dao_results_cleanup, job_daos_results, job_token_ai_fees, job_token_list, job_welcome_service_cleanup,
+    job_active_daos, job_dao_results_cleanup, job_daos_results, job_low_balance_alerts, job_payment_reconciliation, job_scheduled_prompt_wizard_cleanup, job_sentinel_snoozes, job_store_maintenance, job_token_ai_fees, job_token_list,
+    job_welcome_service_cleanup,
 };
 use crate::panora::handler::Panora;
+use crate::payment::ledger::PaymentLedger;
+use crate::scheduled_prompts::storage::ScheduledStorage;
 
 use anyhow::Result;
+use sled::Db;
 use teloxide::Bot;
 use tokio_cron_scheduler::JobScheduler;
 
-pub async fn schedule_jobs(panora: Panora, bot: Bot, dao: Dao, welcome_service: crate::welcome::welcome_service::WelcomeService) -> Result<()> {
+pub async fn schedule_jobs(panora: Panora, bot: Bot, dao: Dao, welcome_service: crate::welcome::welcome_service::WelcomeService, payment_ledger: PaymentLedger, scheduled_storage: ScheduledStorage, db: Db) -> Result<()> {
     log::info!("Initializing job scheduler...");
 
     let scheduler = match JobScheduler::new().await {
@@ -26,6 +31,9 @@ pub async fn schedule_jobs(panora: Panora, bot: Bot, dao: Dao, welcome_service:
     let job_active_daos = job_active_daos(dao.clone(), bot.clone());
     let job_dao_results_cleanup = job_dao_results_cleanup(dao.clone());
     let job_welcome_service_cleanup = job_welcome_service_cleanup(welcome_service.clone(), bot.clone());
+    let job_payment_reconciliation = job_payment_reconciliation(payment_ledger.clone(), panora.aptos.clone(), bot.clone());
+    let job_store_maintenance = job_store_maintenance(db, payment_ledger);
+    let job_scheduled_prompt_wizard_cleanup = job_scheduled_prompt_wizard_cleanup(scheduled_storage);
 
     // Add jobs to scheduler with error handling
     if let Err(e) = scheduler.add(job_token_list).await {
@@ -67,6 +75,47 @@ pub async fn schedule_jobs(panora: Panora, bot: Bot, dao: Dao, welcome_service:
         return Err(anyhow::anyhow!("Failed to add welcome service cleanup job: {}", e));
     }
 
+    if let Err(e) = scheduler.add(job_scheduled_prompt_wizard_cleanup).await {
+        log::error!("Failed to add scheduled prompt wizard cleanup job to scheduler: {}", e);
+        return Err(anyhow::anyhow!("Failed to add scheduled prompt wizard cleanup job: {}", e));
+    }
+
+    if let Some(job) = job_payment_reconciliation {
+        if let Err(e) = scheduler.add(job).await {
+            log::error!("Failed to add payment reconciliation job to scheduler: {}", e);
+            return Err(anyhow::anyhow!("Failed to add payment reconciliation job: {}", e));
+        }
+    }
+
+    if let Some(job) = job_store_maintenance {
+        if let Err(e) = scheduler.add(job).await {
+            log::error!("Failed to add store maintenance job to scheduler: {}", e);
+            return Err(anyhow::anyhow!("Failed to add store maintenance job: {}", e));
+        }
+    }
+
     log::info!("All jobs scheduled successfully");
     Ok(())
 }
+
+/// Jobs that need the full bot dependencies run on the shared scheduler once they exist
+pub async fn schedule_low_balance_alerts(bot: Bot, bot_deps: BotDependencies) -> Result<()> {
+    let Some(job) = job_low_balance_alerts(bot, bot_deps.clone()) else {
+        return Ok(());
+    };
+    if let Err(e) = bot_deps.scheduler.add(job).await {
+        log::error!("Failed to add low balance alert job to scheduler: {}", e);
+        return Err(anyhow::anyhow!("Failed to add low balance alert job: {}", e));
+    }
+    Ok(())
+}
+
+/// Turns sentinel back on when a snooze ends; overdue snoozes resume on the first tick after a restart
+pub async fn schedule_sentinel_snoozes(bot: Bot, bot_deps: BotDependencies) -> Result<()> {
+    let job = job_sentinel_snoozes(bot, bot_deps.clone());
+    if let Err(e) = bot_deps.scheduler.add(job).await {
+        log::error!("Failed to add sentinel snooze job to scheduler: {}", e);
+        return Err(anyhow::anyhow!("Failed to add sentinel snooze job: {}", e));
+    }
+    Ok(())
+}
@@ -2,24 +2,36 @@ mod ai;
 mod announcement;
 mod aptos;
 mod assets;
+mod blocklist;
 mod bot;
 mod callbacks;
 mod command_settings;
+mod context_note;
 mod credentials;
 mod dao;
 mod db;
+mod explain_tx;
 mod filters;
 mod group;
+mod group_budget;
 mod job;
+mod market_data;
 mod message_history;
+mod notification_prefs;
 mod panora;
 mod payment;
 mod pending_transactions;
+mod profile;
+mod rate_limiter;
+mod repeat_guard;
 mod scheduled_payments;
 mod scheduled_prompts;
 mod services;
+mod spam_guard;
 mod sponsor;
 mod summarization_settings;
+mod table_image;
+mod usage_stats;
 mod user_conversation;
 mod user_model_preferences;
 mod utils;
@@ -27,24 +39,26 @@ mod welcome;
 mod yield_ai;
 
 mod dependencies;
+mod dm_onboarding;
 
 use crate::{
     ai::{
         gcs::GcsImageUploader, handler::AI, moderation::ModerationService,
+        openai_client::validate_openai_base_url,
         schedule_guard::schedule_guard_service::ScheduleGuardService,
         sentinel::sentinel::SentinelService, summarizer::handler::SummarizerService,
     },
     aptos::handler::Aptos,
     assets::{command_image_collector, media_aggregator},
-    bot::handler_tree::handler_tree,
+    bot::{diagnostics::DeploymentConfig, handler_tree::handler_tree},
     command_settings::CommandSettingsManager,
     credentials::handler::Auth,
     dao::dao::Dao,
     dependencies::BotDependencies,
     filters::filters::Filters,
     group::{document_library::GroupDocuments, handler::Group},
-    job::job_scheduler::schedule_jobs,
-    message_history::handler::MessageHistory,
+    job::job_scheduler::{schedule_jobs, schedule_low_balance_alerts, schedule_sentinel_snoozes},
+    message_history::handler::MessageHistoryStore,
     panora::handler::Panora,
     payment::{dto::PaymentPrefs, payment::Payment},
     pending_transactions::handler::PendingTransactions,
@@ -71,8 +85,14 @@ use tokio_cron_scheduler::JobScheduler;
 #[tokio::main(flavor = "multi_thread", worker_threads = 4)]
 async fn main() {
     dotenvy::dotenv().ok();
-    tracing_subscriber::fmt::init();
+    let active_profile = profile::current();
+    tracing_subscriber::fmt()
+        .with_max_level(active_profile.log_level())
+        .init();
     log::info!("Starting quark_bot...");
+    log::info!("Active profile: {}", active_profile.summary());
+    bot::diagnostics::mark_started();
+    ai::tools::log_active_tools();
 
     let bot = Bot::from_env();
     let db = db::init_tree();
@@ -80,6 +100,7 @@ async fn main() {
     let group_db = db.open_tree("group").expect("Failed to open group tree");
 
     let openai_api_key = env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY not set");
+    validate_openai_base_url().expect("Invalid OPENAI_BASE_URL");
     let gcs_creds = env::var("STORAGE_CREDENTIALS").expect("STORAGE_CREDENTIALS not set");
     let bucket_name = env::var("GCS_BUCKET_NAME").expect("GCS_BUCKET_NAME not set");
     let aptos_network = env::var("APTOS_NETWORK").expect("APTOS_NETWORK not set");
@@ -91,13 +112,20 @@ async fn main() {
         .await
         .expect("Failed to create GCS image uploader");
 
-    let aptos = Aptos::new(aptos_network, contract_address, aptos_api_key);
-
     let min_deposit = env::var("MIN_DEPOSIT")
         .expect("MIN_DEPOSIT not set")
         .parse::<f64>()
         .expect("MIN_DEPOSIT must be a number");
 
+    let deployment_config = DeploymentConfig {
+        network: aptos_network.clone(),
+        contract_address: contract_address.clone(),
+        default_symbol: default_symbol.clone(),
+        min_deposit,
+    };
+
+    let aptos = Aptos::new(aptos_network, contract_address, aptos_api_key);
+
     let panora = Panora::new(&db, aptos, min_deposit).expect("Failed to create Panora");
 
     // Create clone for dispatcher early to avoid move issues
@@ -153,12 +181,46 @@ async fn main() {
     let summarization_settings = summarization_settings::SummarizationSettings::new(&db)
         .expect("Failed to create SummarizationSettings");
     let command_settings = CommandSettingsManager::new(db.clone());
+    let dm_onboarding =
+        dm_onboarding::DmOnboarding::new(&db).expect("Failed to create DmOnboarding");
+    let pinned_messages = ai::pinned::PinnedMessageCache::new();
+    let context_notes =
+        context_note::ContextNotes::new(&db).expect("Failed to create ContextNotes");
+    let repeat_guard =
+        repeat_guard::RepeatGuard::new(&db).expect("Failed to create RepeatGuard");
+    let tx_explanations =
+        explain_tx::TxExplanations::new(&db).expect("Failed to create TxExplanations");
+    let payment_memos =
+        payment::memo::PaymentMemos::new(&db).expect("Failed to create PaymentMemos");
+    let payment_ledger =
+        payment::ledger::PaymentLedger::new(&db).expect("Failed to create PaymentLedger");
+    let blocklist = blocklist::Blocklist::new(&db).expect("Failed to create Blocklist");
+    let low_balance_alerts = ai::sentinel::low_balance::LowBalanceAlerts::new(&db)
+        .expect("Failed to create LowBalanceAlerts");
+    let sentinel_snoozes = ai::sentinel::snooze::SentinelSnoozes::new(&db)
+        .expect("Failed to create SentinelSnoozes");
+    let spam_guard = spam_guard::SpamGuard::new(&db).expect("Failed to create SpamGuard");
+    let announcer_grants = announcement::grants::AnnouncerGrants::new(&db)
+        .expect("Failed to create AnnouncerGrants");
+    let usage_stats = usage_stats::UsageStats::new(&db).expect("Failed to create UsageStats");
+    let notification_prefs = notification_prefs::NotificationPrefs::new(&db)
+        .expect("Failed to create NotificationPrefs");
+    let flagged_log = ai::moderation::flagged_log::FlaggedLog::new(&db)
+        .expect("Failed to create FlaggedLog");
+    let link_allowlist = ai::moderation::link_allowlist::LinkAllowlist::new(&db)
+        .expect("Failed to create LinkAllowlist");
+    let group_budgets =
+        group_budget::GroupBudgets::new(&db).expect("Failed to create GroupBudgets");
+    let prompt_safety = ai::prompt_safety::PromptSafety::new(openai_api_key.clone());
 
     schedule_jobs(
         panora.clone(),
         bot.clone(),
         dao.clone(),
         welcome_service.clone(),
+        payment_ledger.clone(),
+        scheduled_storage.clone(),
+        db.clone(),
     )
     .await
     .expect("Failed to schedule jobs");
@@ -175,9 +237,10 @@ async fn main() {
 
     let service = Services::new();
 
-    let cmd_collector = Arc::new(command_image_collector::CommandImageCollector::new(
-        bot.clone(),
-    ));
+    let cmd_collector = Arc::new(
+        command_image_collector::CommandImageCollector::new(bot.clone(), &db)
+            .expect("Failed to open pending image command storage"),
+    );
 
     let media_aggregator = Arc::new(media_aggregator::MediaGroupAggregator::new(
         bot.clone(),
@@ -191,8 +254,18 @@ async fn main() {
         BotCommand::new("help", "Display this text."),
         BotCommand::new("loginuser", "Log in as a user (DM only)."),
         BotCommand::new("logingroup", "Group login (under development)."),
+        BotCommand::new(
+            "refreshgroup",
+            "Re-issue this group's credentials (admins only).",
+        ),
         BotCommand::new("newchat", "Start a new conversation thread."),
+        BotCommand::new("forget", "Drop your last /c message from the bot's context."),
+        BotCommand::new("lastimage", "Resend your most recent AI-generated image."),
         BotCommand::new("c", "prompt to chat AI with the bot."),
+        BotCommand::new(
+            "modelalias",
+            "List or edit your model aliases for /c:alias.",
+        ),
         BotCommand::new(
             "g",
             "prompt to chat AI with the bot in a group. (only admins can use this command)",
@@ -213,27 +286,90 @@ async fn main() {
             "listscheduledpayments",
             "List scheduled token payments (admins only).",
         ),
+        BotCommand::new(
+            "exportpayments",
+            "Export scheduled payments as CSV (admins only).",
+        ),
         BotCommand::new("walletaddress", "Get your wallet address."),
         // Removed selectreasoningmodel (unified under selectmodel)
         // selectmodel and mysettings entries merged under /usersettings
         BotCommand::new("usersettings", "Open user settings menu (DM only)."),
+        BotCommand::new("myvotes", "List open DAO proposals across your groups (DM only)."),
+        BotCommand::new("stats", "Show your personal AI usage (DM only)."),
+        BotCommand::new("notifications", "Choose which notifications you get (DM only)."),
+        BotCommand::new("newkb", "Create a knowledge base and switch to it (DM only)."),
+        BotCommand::new("listkb", "List your knowledge bases (DM only)."),
+        BotCommand::new("usekb", "Switch your active knowledge base (DM only)."),
         BotCommand::new(
             "report",
             "Moderate content (reply to message) and send a report to the admin if content is found to be inappropriate, muting the user in this case.",
         ),
+        BotCommand::new(
+            "testmod",
+            "Preview how moderation would judge some text, without acting on it (admins only).",
+        ),
+        BotCommand::new(
+            "snoozesentinel",
+            "Pause sentinel for a while, e.g. /snoozesentinel 30m (admins only).",
+        ),
+        BotCommand::new(
+            "flagged",
+            "Review recently flagged messages and undo mutes or bans (admins only).",
+        ),
+        BotCommand::new(
+            "aibudget",
+            "Show or set this group's monthly AI budget (admins only).",
+        ),
         BotCommand::new("rules", "Show core and custom rules for this group."),
         BotCommand::new("balance", "Get your balance of a token."),
+        BotCommand::new("balances", "List all of your non-zero token balances."),
+        BotCommand::new("send", "Send tokens described in plain words."),
+        BotCommand::new("sendmax", "Send your entire balance of a token."),
+        BotCommand::new("pending", "Show or cancel your pending payment."),
+        BotCommand::new("explaintx", "Explain a transaction in plain English."),
         BotCommand::new("groupwalletaddress", "Get the group's wallet address."),
         BotCommand::new("groupbalance", "Get the group's balance of a token."),
+        BotCommand::new(
+            "groupbalances",
+            "List all of the group's non-zero token balances.",
+        ),
+        BotCommand::new(
+            "members",
+            "Show which group members are registered with Quark (admins only).",
+        ),
+        BotCommand::new("price", "Get a token's USD price."),
+        BotCommand::new("pools", "Search DEX pools for a token on a network."),
         BotCommand::new("prices", "Display model pricing information."),
+        BotCommand::new("rates", "Display model pricing in this chat's payment token."),
         BotCommand::new(
             "globalannouncement",
             "Send a global announcement (authorized only).",
         ),
+        BotCommand::new(
+            "announcers",
+            "List, grant or revoke global announcement rights (super-admins only).",
+        ),
+        BotCommand::new(
+            "refreshtokens",
+            "Re-fetch the Panora token list and AI fees (authorized only).",
+        ),
+        BotCommand::new(
+            "commandprefix",
+            "Show or set an extra command prefix for this group (admins only).",
+        ),
         BotCommand::new("groupsettings", "Open group settings menu (admins only)."),
+        BotCommand::new("debug", "Inspect bot state for this group (admins only)."),
+        BotCommand::new("block", "Make the bot ignore a user (admins/operators only)."),
+        BotCommand::new("unblock", "Remove a user from the blocklist (admins/operators only)."),
+        BotCommand::new("blocklist", "List blocked users (admins/operators only)."),
+        BotCommand::new("uptime", "Show how long the bot has been running."),
+        BotCommand::new("version", "Show the bot's build version (authorized only)."),
+        BotCommand::new("config", "Show the bot's non-secret configuration (authorized only)."),
     ];
 
-    let history_storage = InMemStorage::<MessageHistory>::new();
+    let history_storage = Arc::new(
+        MessageHistoryStore::new(&db).expect("Failed to open message history storage"),
+    );
 
     bot.set_my_commands(commands).await.unwrap();
 
@@ -278,6 +414,25 @@ async fn main() {
         summarization_settings,
         welcome_service,
         summarizer,
+        dm_onboarding,
+        repeat_guard,
+        context_notes,
+        pinned_messages,
+        tx_explanations,
+        payment_memos,
+        payment_ledger,
+        low_balance_alerts,
+        sentinel_snoozes,
+        blocklist,
+        spam_guard,
+        announcer_grants,
+        usage_stats,
+        notification_prefs,
+        flagged_log,
+        link_allowlist,
+        prompt_safety,
+        deployment_config,
+        group_budgets,
     };
 
     // Bootstrap user-defined schedules (load and register)
@@ -287,6 +442,12 @@ async fn main() {
     if let Err(e) = bootstrap_scheduled_payments(bot.clone(), bot_deps.clone()).await {
         log::error!("Failed to bootstrap scheduled payments: {}", e);
     }
+    if let Err(e) = schedule_low_balance_alerts(bot.clone(), bot_deps.clone()).await {
+        log::error!("Failed to schedule low balance alerts: {}", e);
+    }
+    if let Err(e) = schedule_sentinel_snoozes(bot.clone(), bot_deps.clone()).await {
+        log::error!("Failed to schedule sentinel snooze job: {}", e);
+    }
 
     Dispatcher::builder(bot.clone(), handler_tree())
         .dependencies(dptree::deps![InMemStorage::<QuarkState>::new(), bot_deps])
@@ -0,0 +1,126 @@
+use crate::ai::gecko_cache;
+
+/// Query GeckoTerminal's pool search. Errors are returned as user-facing messages.
+pub async fn fetch_search_pools(
+    query: &str,
+    network: Option<&str>,
+    page: u64,
+) -> Result<serde_json::Value, String> {
+    // Construct GeckoTerminal API URL
+    let mut url = format!(
+        "https://api.geckoterminal.com/api/v2/search/pools?query={}&page={}",
+        urlencoding::encode(query),
+        page
+    );
+    if let Some(net) = network {
+        url.push_str(&format!("&network={}", urlencoding::encode(net)));
+    }
+    url.push_str("&include=base_token,quote_token,dex");
+
+    if let Some(data) = gecko_cache::get(&url) {
+        return Ok(data);
+    }
+
+    // Make HTTP request
+    let client = reqwest::Client::new();
+    match client
+        .get(&url)
+        .header("Accept", "application/json")
+        .header("User-Agent", "QuarkBot/1.0")
+        .send()
+        .await
+    {
+        Ok(response) => {
+            if response.status().is_success() {
+                let data = response.json::<serde_json::Value>().await.map_err(|e| {
+                    log::error!("Failed to parse search pools API response: {}", e);
+                    format!("❌ Error parsing API response: {}", e)
+                })?;
+                gecko_cache::insert(&url, &data);
+                Ok(data)
+            } else if response.status() == 404 {
+                log::error!("No pools found for query '{}' (404 response)", query);
+                Err(format!("❌ No pools found for query '{}'.", query))
+            } else if response.status() == 429 {
+                log::error!("Rate limit exceeded for search pools API");
+                Err("⚠️ Rate limit exceeded. GeckoTerminal allows 30 requests per minute. Please try again later.".to_string())
+            } else {
+                let status = response.status();
+                let error_text = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "Unknown error".to_string());
+                log::error!(
+                    "Search pools API request failed with status: {} - {}",
+                    status,
+                    error_text
+                );
+                Err(format!(
+                    "❌ API request failed with status: {} - {}",
+                    status, error_text
+                ))
+            }
+        }
+        Err(e) => {
+            log::error!(
+                "Network error when calling search pools GeckoTerminal API: {}",
+                e
+            );
+            Err(format!(
+                "❌ Network error when calling GeckoTerminal API: {}",
+                e
+            ))
+        }
+    }
+}
+
+/// USD price of a token from the deepest pool it appears in, matched by address
+pub fn pool_price_for_token(data: &serde_json::Value, addresses: &[&str]) -> Option<f64> {
+    let matches = |id: Option<&str>| {
+        id.map(|id| {
+            // GeckoTerminal token ids are "<network>_<address>"
+            let address = id.split_once('_').map(|(_, a)| a).unwrap_or(id);
+            addresses.iter().any(|a| a.eq_ignore_ascii_case(address))
+        })
+        .unwrap_or(false)
+    };
+
+    let pools = data.get("data").and_then(|d| d.as_array())?;
+
+    pools
+        .iter()
+        .filter_map(|pool| {
+            let attributes = pool.get("attributes")?;
+            let relationships = pool.get("relationships")?;
+            let token_id = |side: &str| {
+                relationships
+                    .get(side)
+                    .and_then(|r| r.get("data"))
+                    .and_then(|d| d.get("id"))
+                    .and_then(|v| v.as_str())
+            };
+
+            let price_field = if matches(token_id("base_token")) {
+                "base_token_price_usd"
+            } else if matches(token_id("quote_token")) {
+                "quote_token_price_usd"
+            } else {
+                return None;
+            };
+
+            let price = attributes
+                .get(price_field)
+                .and_then(|v| v.as_str())
+                .and_then(|p| p.parse::<f64>().ok())
+                .filter(|p| *p > 0.0)?;
+            let reserve = attributes
+                .get("reserve_in_usd")
+                .and_then(|v| v.as_str())
+                .and_then(|r| r.parse::<f64>().ok())
+                .unwrap_or(0.0);
+
+            Some((price, reserve))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(price, _)| price)
+}
@@ -0,0 +1,3 @@
+//! GeckoTerminal pool lookups shared by the AI tools, /price and Panora's price fallback.
+
+pub mod handler;
@@ -1,8 +1,12 @@
 use serde::{Deserialize, Serialize};
-use teloxide::{
-    dispatching::dialogue::{InMemStorage, Storage},
-    types::ChatId,
-};
+use sled::{Db, Tree};
+use teloxide::types::ChatId;
+
+/// Tree holding persisted per-chat history buffers, keyed by chat id (big-endian i64)
+const HISTORY_TREE: &str = "message_history";
+
+/// Current on-disk format of a history buffer
+const HISTORY_FORMAT_VERSION: u32 = 2;
 
 /// One stored line.
 #[derive(Clone, Serialize, Deserialize)]
@@ -11,51 +15,216 @@ pub struct MessageEntry {
     pub text: String,
 }
 
-/// Per-chat buffer (max 30).
+/// Default number of entries kept per chat; older ones are dropped.
+pub const MAX_HISTORY_ENTRIES: usize = 30;
+/// Default characters kept per entry; longer messages are cut.
+pub const DEFAULT_HISTORY_CHAR_LIMIT: usize = 500;
+pub const HISTORY_LIMIT_CHOICES: [usize; 4] = [10, 20, 50, 100];
+pub const HISTORY_CHAR_LIMIT_CHOICES: [usize; 3] = [200, 500, 1000];
+
+/// Per-group buffer size, chosen in /groupsettings. DMs always use the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistorySettings {
+    pub history_limit: usize,
+    pub history_char_limit: usize,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            history_limit: MAX_HISTORY_ENTRIES,
+            history_char_limit: DEFAULT_HISTORY_CHAR_LIMIT,
+        }
+    }
+}
+
+/// First `limit` characters of the text, marked when cut
+fn truncate_chars(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(limit).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Per-chat buffer (max the group's `history_limit`).
 #[derive(Clone, Default, Serialize, Deserialize)]
-pub struct MessageHistory(pub Vec<MessageEntry>);
+pub struct MessageHistory {
+    pub entries: Vec<MessageEntry>,
+    /// Whether older entries were evicted to stay within the limit
+    pub dropped: bool,
+}
+
+/// Versioned envelope for a chat's history. Version 1 was a bare JSON array of entries.
+#[derive(Serialize, Deserialize)]
+struct StoredHistory {
+    version: u32,
+    entries: Vec<MessageEntry>,
+    /// Set once older entries have been evicted; absent in buffers written before it existed
+    #[serde(default)]
+    dropped: bool,
+}
+
+/// Per-chat buffers persisted in sled, so recent context survives restarts.
+pub struct MessageHistoryStore {
+    tree: Tree,
+}
+
+impl MessageHistoryStore {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(HISTORY_TREE)?;
+        Ok(Self { tree })
+    }
+
+    /// Current-format envelope, or a bare entry array written before versioning
+    fn decode(value: &[u8]) -> Option<MessageHistory> {
+        serde_json::from_slice::<StoredHistory>(value)
+            .map(|stored| MessageHistory {
+                entries: stored.entries,
+                dropped: stored.dropped,
+            })
+            .or_else(|_| {
+                serde_json::from_slice::<Vec<MessageEntry>>(value).map(|entries| MessageHistory {
+                    entries,
+                    dropped: false,
+                })
+            })
+            .ok()
+    }
+
+    fn encode(history: MessageHistory) -> Vec<u8> {
+        serde_json::to_vec(&StoredHistory {
+            version: HISTORY_FORMAT_VERSION,
+            entries: history.entries,
+            dropped: history.dropped,
+        })
+        .unwrap()
+    }
+
+    /// The chat's buffer; empty for unknown chats or unreadable records
+    pub fn get(&self, chat_id: ChatId) -> MessageHistory {
+        match self.tree.get(chat_id.0.to_be_bytes()) {
+            Ok(Some(value)) => Self::decode(&value).unwrap_or_else(|| {
+                log::warn!("Discarding unreadable message history for chat {}", chat_id);
+                MessageHistory::default()
+            }),
+            Ok(None) => MessageHistory::default(),
+            Err(e) => {
+                log::error!("Failed to read message history for chat {}: {}", chat_id, e);
+                MessageHistory::default()
+            }
+        }
+    }
+
+    /// Append an entry and evict the oldest beyond `limit`, atomically per chat. A lowered
+    /// limit trims the existing buffer here, on the next write.
+    pub fn push(&self, chat_id: ChatId, entry: MessageEntry, limit: usize) -> sled::Result<()> {
+        self.tree
+            .update_and_fetch(chat_id.0.to_be_bytes(), |current| {
+                let mut history = current.and_then(Self::decode).unwrap_or_default();
+                history.entries.push(entry.clone());
+                if history.entries.len() > limit {
+                    let excess = history.entries.len() - limit;
+                    history.entries.drain(0..excess);
+                    history.dropped = true;
+                }
+                Some(Self::encode(history))
+            })?;
+        Ok(())
+    }
+}
 
 /// Handy alias used everywhere else.
-pub type HistoryStorage = std::sync::Arc<InMemStorage<MessageHistory>>;
+pub type HistoryStorage = std::sync::Arc<MessageHistoryStore>;
 
 /// Fetch the buffer (may be empty).
-#[allow(dead_code)]
-pub async fn fetch(chat_id: ChatId, storage: HistoryStorage) -> Vec<MessageEntry> {
-    storage
-        .get_dialogue(chat_id)
-        .await
-        .unwrap_or_default()
-        .unwrap_or_default()
-        .0
+pub async fn fetch(chat_id: ChatId, storage: HistoryStorage) -> MessageHistory {
+    storage.get(chat_id)
 }
 
-/// Store a new message entry in the rolling buffer (max 30 messages).
+/// Store a new message entry in the rolling buffer, sized by the chat's settings.
 pub async fn store_message(
     chat_id: ChatId,
-    entry: MessageEntry,
+    mut entry: MessageEntry,
     storage: HistoryStorage,
+    settings: HistorySettings,
 ) {
-    // Clone storage so we can use it twice
-    let storage_clone = storage.clone();
-    
-    let current_history = storage
-        .get_dialogue(chat_id)
-        .await
-        .unwrap_or_default()
-        .unwrap_or_default();
-        
-    let mut messages = current_history.0;
-    messages.push(entry);
-    
-    // Keep only the most recent 30 entries.
-    if messages.len() > 30 {
-        let excess = messages.len() - 30;
-        messages.drain(0..excess);
-    }
-    
-    let new_history = MessageHistory(messages);
-    storage_clone
-        .update_dialogue(chat_id, new_history)
-        .await
-        .expect("Failed to update message history");
+    entry.text = truncate_chars(&entry.text, settings.history_char_limit);
+    if let Err(e) = storage.push(chat_id, entry, settings.history_limit) {
+        log::error!("Failed to store message history for chat {}: {}", chat_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str) -> MessageEntry {
+        MessageEntry {
+            sender: Some("alice".to_string()),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_push_persists_and_evicts_oldest() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let store = MessageHistoryStore::new(&db).unwrap();
+        let chat = ChatId(-100123);
+
+        assert!(store.get(chat).entries.is_empty());
+        for i in 0..MAX_HISTORY_ENTRIES + 5 {
+            store.push(chat, entry(&i.to_string()), MAX_HISTORY_ENTRIES).unwrap();
+        }
+
+        // A fresh handle on the same tree sees the persisted buffer
+        let reopened = MessageHistoryStore::new(&db).unwrap();
+        let history = reopened.get(chat);
+        assert!(history.dropped);
+        let entries = history.entries;
+        assert_eq!(entries.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(entries[0].text, "5");
+        assert_eq!(entries.last().unwrap().text, (MAX_HISTORY_ENTRIES + 4).to_string());
+
+        // Lowering the limit trims the stored buffer on the next write
+        store.push(chat, entry("new"), 10).unwrap();
+        let entries = store.get(chat).entries;
+        assert_eq!(entries.len(), 10);
+        assert_eq!(entries.last().unwrap().text, "new");
+    }
+
+    #[test]
+    fn test_full_buffer_without_eviction_is_not_dropped() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let store = MessageHistoryStore::new(&db).unwrap();
+        let chat = ChatId(-100789);
+
+        for i in 0..10 {
+            store.push(chat, entry(&i.to_string()), 10).unwrap();
+        }
+        let history = store.get(chat);
+        assert_eq!(history.entries.len(), 10);
+        assert!(!history.dropped);
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("short", 10), "short");
+        assert_eq!(truncate_chars("héllo world", 5), "héllo…");
+    }
+
+    #[test]
+    fn test_reads_legacy_array() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let store = MessageHistoryStore::new(&db).unwrap();
+        let chat = ChatId(-100456);
+        store
+            .tree
+            .insert(chat.0.to_be_bytes(), serde_json::to_vec(&vec![entry("old")]).unwrap())
+            .unwrap();
+
+        let history = store.get(chat);
+        assert_eq!(history.entries[0].text, "old");
+        assert!(!history.dropped);
+    }
 }
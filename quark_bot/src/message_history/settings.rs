@@ -0,0 +1,105 @@
+//! "History Settings" in /groupsettings: how much recent chat the AI sees as context.
+
+use anyhow::Result as AnyResult;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+};
+
+use crate::{
+    dependencies::BotDependencies,
+    message_history::handler::{HISTORY_CHAR_LIMIT_CHOICES, HISTORY_LIMIT_CHOICES},
+    utils,
+};
+
+/// `open_history_settings`, `history_limit:<n>` and `history_chars:<n>`
+pub async fn handle_history_settings_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+    let data = query.data.clone().unwrap_or_default();
+
+    if !utils::is_admin(&bot, m.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can change group settings")
+            .await?;
+        return Ok(());
+    }
+
+    let mut settings = bot_deps.group.get_history_settings(m.chat.id);
+    let limit = data
+        .strip_prefix("history_limit:")
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| HISTORY_LIMIT_CHOICES.contains(v));
+    let chars = data
+        .strip_prefix("history_chars:")
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| HISTORY_CHAR_LIMIT_CHOICES.contains(v));
+
+    if limit.is_some() || chars.is_some() {
+        if let Some(limit) = limit {
+            settings.history_limit = limit;
+        }
+        if let Some(chars) = chars {
+            settings.history_char_limit = chars;
+        }
+        bot_deps.group.set_history_settings(m.chat.id, &settings)?;
+        bot.answer_callback_query(query.id.clone())
+            .text("✅ History settings updated")
+            .await?;
+    } else {
+        bot.answer_callback_query(query.id.clone()).await?;
+    }
+
+    let mark = |selected: bool, label: String| {
+        if selected {
+            format!("✅ {}", label)
+        } else {
+            label
+        }
+    };
+    let limit_row = HISTORY_LIMIT_CHOICES
+        .iter()
+        .map(|value| {
+            InlineKeyboardButton::callback(
+                mark(*value == settings.history_limit, value.to_string()),
+                format!("history_limit:{}", value),
+            )
+        })
+        .collect();
+    let chars_row = HISTORY_CHAR_LIMIT_CHOICES
+        .iter()
+        .map(|value| {
+            InlineKeyboardButton::callback(
+                mark(*value == settings.history_char_limit, format!("{} chars", value)),
+                format!("history_chars:{}", value),
+            )
+        })
+        .collect();
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        limit_row,
+        chars_row,
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "back_to_group_settings",
+        )],
+    ]);
+
+    bot.edit_message_text(
+        m.chat.id,
+        m.id,
+        format!(
+            "🧠 <b>History Settings</b>\n\nThe AI sees this group's recent messages as context.\n\n📜 Messages kept: <b>{}</b>\n✂️ Characters per message: <b>{}</b>\n\nBusy groups may want deeper context; a lower limit takes effect on the next message.",
+            settings.history_limit, settings.history_char_limit
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(keyboard)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anyhow::Result as AnyResult;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+};
+
+use super::{NotificationCategory, notification_prefs::NotificationSettings};
+use crate::{
+    dependencies::BotDependencies,
+    utils::{KeyboardMarkupType, send_markdown_message_with_keyboard, send_message},
+};
+
+const MENU_TEXT: &str = "🔔 <b>Notifications</b>\n\nChoose which messages the bot may DM you. Payment-received DMs are off until you turn them on.\n\n<i>Failed scheduled payments are always reported, since they need your attention.</i>";
+
+fn notifications_keyboard(settings: &NotificationSettings) -> InlineKeyboardMarkup {
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = NotificationCategory::ALL
+        .iter()
+        .map(|category| {
+            vec![InlineKeyboardButton::callback(
+                format!(
+                    "{}: {}",
+                    category.label(),
+                    if settings.is_enabled(*category) { "ON" } else { "OFF" }
+                ),
+                format!("notif_toggle:{}", category.key()),
+            )]
+        })
+        .collect();
+    rows.push(vec![InlineKeyboardButton::callback(
+        "↩️ Back",
+        "back_to_user_settings",
+    )]);
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// /notifications — per-user notification toggles (DM only)
+pub async fn handle_notifications_command(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ Unable to identify user.".to_string()).await?;
+        return Ok(());
+    };
+    let settings = bot_deps.notification_prefs.get(user.id.0 as i64);
+    send_markdown_message_with_keyboard(
+        bot,
+        msg,
+        KeyboardMarkupType::InlineKeyboardType(notifications_keyboard(&settings)),
+        MENU_TEXT,
+    )
+    .await?;
+    Ok(())
+}
+
+/// `open_notifications` from /usersettings and `notif_toggle:<category>`
+pub async fn handle_notifications_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+    let user_id = query.from.id.0 as i64;
+    let data = query.data.clone().unwrap_or_default();
+
+    if let Some(key) = data.strip_prefix("notif_toggle:") {
+        let Some(category) = NotificationCategory::from_key(key) else {
+            bot.answer_callback_query(query.id)
+                .text("❌ Unknown notification type")
+                .await?;
+            return Ok(());
+        };
+        let enabled = bot_deps.notification_prefs.toggle(user_id, category)?;
+        bot.answer_callback_query(query.id.clone())
+            .text(format!(
+                "{} {}",
+                if enabled { "🔔" } else { "🔕" },
+                if enabled { "Turned on" } else { "Turned off" }
+            ))
+            .await?;
+    } else {
+        bot.answer_callback_query(query.id.clone()).await?;
+    }
+
+    let settings = bot_deps.notification_prefs.get(user_id);
+    bot.edit_message_text(m.chat.id, m.id, MENU_TEXT)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(notifications_keyboard(&settings))
+        .await?;
+    Ok(())
+}
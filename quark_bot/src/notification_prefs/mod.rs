@@ -0,0 +1,4 @@
+pub mod handler;
+pub mod notification_prefs;
+
+pub use notification_prefs::{NotificationCategory, NotificationPrefs};
@@ -0,0 +1,151 @@
+//! Per-user toggles for the DMs the bot sends on its own initiative.
+
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+
+const TREE_NAME: &str = "notification_prefs";
+/// Payment-received toggles from before this store existed
+const LEGACY_PAYMENT_TREE: &str = "payment_recipient_notifications";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationCategory {
+    /// DMs to payment recipients; opt-in
+    PaymentsReceived,
+    /// Receipts for scheduled payments the user created
+    PaymentUpdates,
+}
+
+impl NotificationCategory {
+    pub const ALL: [NotificationCategory; 2] = [
+        NotificationCategory::PaymentsReceived,
+        NotificationCategory::PaymentUpdates,
+    ];
+
+    /// Stable id used in callback data
+    pub fn key(&self) -> &'static str {
+        match self {
+            NotificationCategory::PaymentsReceived => "payments_received",
+            NotificationCategory::PaymentUpdates => "payment_updates",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationCategory::PaymentsReceived => "💸 Payments received",
+            NotificationCategory::PaymentUpdates => "🧾 Scheduled payment receipts",
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Payment-received DMs are opt-in; everything else is on until the user turns it off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    #[serde(default)]
+    pub payments_received: bool,
+    #[serde(default = "enabled")]
+    pub payment_updates: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            payments_received: false,
+            payment_updates: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    fn field(&mut self, category: NotificationCategory) -> &mut bool {
+        match category {
+            NotificationCategory::PaymentsReceived => &mut self.payments_received,
+            NotificationCategory::PaymentUpdates => &mut self.payment_updates,
+        }
+    }
+
+    pub fn is_enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::PaymentsReceived => self.payments_received,
+            NotificationCategory::PaymentUpdates => self.payment_updates,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NotificationPrefs {
+    tree: Tree,
+    legacy_payment_tree: Tree,
+}
+
+impl NotificationPrefs {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        let legacy_payment_tree = db.open_tree(LEGACY_PAYMENT_TREE)?;
+        Ok(Self {
+            tree,
+            legacy_payment_tree,
+        })
+    }
+
+    pub fn get(&self, user_id: i64) -> NotificationSettings {
+        if let Some(settings) = self
+            .tree
+            .get(user_id.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+        {
+            return settings;
+        }
+        NotificationSettings {
+            payments_received: self
+                .legacy_payment_tree
+                .get(user_id.to_be_bytes())
+                .ok()
+                .flatten()
+                .and_then(|v| serde_json::from_slice::<bool>(&v).ok())
+                .unwrap_or(false),
+            ..NotificationSettings::default()
+        }
+    }
+
+    /// Whether a DM of this kind may be sent to the user
+    pub fn is_enabled(&self, user_id: i64, category: NotificationCategory) -> bool {
+        self.get(user_id).is_enabled(category)
+    }
+
+    /// Flip one category and return its new state
+    pub fn toggle(&self, user_id: i64, category: NotificationCategory) -> sled::Result<bool> {
+        let mut settings = self.get(user_id);
+        let field = settings.field(category);
+        *field = !*field;
+        let enabled = *field;
+        self.tree
+            .insert(user_id.to_be_bytes(), serde_json::to_vec(&settings).unwrap())?;
+        Ok(enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_categories_use_defaults() {
+        let settings: NotificationSettings = serde_json::from_str("{}").unwrap();
+        assert!(!settings.is_enabled(NotificationCategory::PaymentsReceived));
+        assert!(settings.is_enabled(NotificationCategory::PaymentUpdates));
+        assert_eq!(
+            NotificationCategory::from_key("payment_updates"),
+            Some(NotificationCategory::PaymentUpdates)
+        );
+    }
+}
@@ -2,13 +2,26 @@ use anyhow::Result;
 use quark_core::helpers::dto::PriceCoin;
 use reqwest::Client;
 use sled::{Db, Tree};
-use std::env;
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use crate::{
     aptos::handler::Aptos,
+    market_data::handler::{fetch_search_pools, pool_price_for_token},
     panora::dto::{PanoraResponse, Token},
 };
 
+/// How long a GeckoTerminal fallback price is reused before querying again
+const FALLBACK_PRICE_TTL: Duration = Duration::from_secs(5 * 60);
+/// How long a failed fallback lookup is remembered, so unpriced tokens don't hit the rate limit
+const FALLBACK_MISS_TTL: Duration = Duration::from_secs(60);
+/// Default lifetime of the rendered /prices and /rates output (PRICES_CACHE_TTL_SECS)
+const DEFAULT_PRICES_CACHE_TTL_SECS: u64 = 5 * 60;
+
 #[derive(Clone)]
 pub struct Panora {
     client: Client,
@@ -17,6 +30,9 @@ pub struct Panora {
     panora_api_key: String,
     pub aptos: Aptos,
     pub min_deposit: f64,
+    fallback_prices: Arc<Mutex<HashMap<String, (Option<f64>, Instant)>>>,
+    prices_cache: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+    prices_cache_ttl: Duration,
 }
 
 impl Panora {
@@ -26,6 +42,10 @@ impl Panora {
 
         let panora_url = env::var("PANORA_URL").expect("PANORA_URL must be set");
         let panora_api_key = env::var("PANORA_API_KEY").expect("PANORA_API_KEY must be set");
+        let prices_cache_ttl = env::var("PRICES_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|secs| secs.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_PRICES_CACHE_TTL_SECS);
 
         Ok(Self {
             client,
@@ -34,16 +54,46 @@ impl Panora {
             panora_api_key,
             aptos,
             min_deposit,
+            fallback_prices: Arc::new(Mutex::new(HashMap::new())),
+            prices_cache: Arc::new(Mutex::new(HashMap::new())),
+            prices_cache_ttl: Duration::from_secs(prices_cache_ttl),
         })
     }
 
+    /// Rendered pricing text for `key` if it is still fresh
+    pub fn cached_prices(&self, key: &str) -> Option<String> {
+        let cache = self.prices_cache.lock().unwrap();
+        cache
+            .get(key)
+            .filter(|(_, at)| at.elapsed() < self.prices_cache_ttl)
+            .map(|(text, _)| text.clone())
+    }
+
+    pub fn cache_prices(&self, key: &str, text: &str) {
+        if self.prices_cache_ttl.is_zero() {
+            return;
+        }
+        self.prices_cache
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (text.to_string(), Instant::now()));
+    }
+
+    /// Drop rendered pricing once token prices or AI fees change
+    pub fn invalidate_prices_cache(&self) {
+        self.prices_cache.lock().unwrap().clear();
+    }
+
     pub async fn set_panora_token_list(&self) -> Result<()> {
         const MAX_RETRIES: u32 = 3;
         const BASE_DELAY_MS: u64 = 2000; // 2 seconds base delay
 
         for attempt in 1..=MAX_RETRIES {
             match self.set_panora_token_list_internal().await {
-                Ok(_) => return Ok(()),
+                Ok(_) => {
+                    self.invalidate_prices_cache();
+                    return Ok(());
+                }
                 Err(e) => {
                     let error_msg = e.to_string();
                     if error_msg.contains("429") && attempt < MAX_RETRIES {
@@ -137,7 +187,10 @@ impl Panora {
 
         for attempt in 1..=MAX_RETRIES {
             match self.set_token_ai_fees_internal(token_address).await {
-                Ok(_) => return Ok(()),
+                Ok(_) => {
+                    self.invalidate_prices_cache();
+                    return Ok(());
+                }
                 Err(e) => {
                     let error_msg = e.to_string();
                     if error_msg.contains("429") && attempt < MAX_RETRIES {
@@ -228,6 +281,22 @@ impl Panora {
         Ok(list.unwrap())
     }
 
+    /// Number of cached tokens in the (full, non-bonding) lists; 0 when a list is missing
+    pub async fn token_list_counts(&self) -> (usize, usize) {
+        let full = self
+            .get_panora_token_list()
+            .await
+            .map(|l| l.len())
+            .unwrap_or(0);
+        let non_bonding = self
+            .get_panora_token_list_non_bonding()
+            .await
+            .map(|l| l.len())
+            .unwrap_or(0);
+
+        (full, non_bonding)
+    }
+
     pub async fn get_token_by_symbol(&self, symbol: &str) -> Result<Token> {
         let list = self.get_panora_token_list().await?;
 
@@ -259,8 +328,60 @@ impl Panora {
             }
         }
 
-        let token = token.unwrap();
+        let mut token = token.unwrap();
+
+        // Only the price is backfilled; all other metadata stays Panora's
+        if token.usd_price.is_none() {
+            token.usd_price = self.fallback_usd_price(&token).await.map(|p| p.to_string());
+        }
+
+        Ok(token)
+    }
+
+    /// Price a token Panora hasn't priced from GeckoTerminal pools, cached briefly. Misses
+    /// are cached for a shorter time.
+    async fn fallback_usd_price(&self, token: &Token) -> Option<f64> {
+        let cache_key = token.fa_address.clone();
+
+        if let Some((price, fetched_at)) = self.fallback_prices.lock().unwrap().get(&cache_key) {
+            let ttl = if price.is_some() {
+                FALLBACK_PRICE_TTL
+            } else {
+                FALLBACK_MISS_TTL
+            };
+            if fetched_at.elapsed() < ttl {
+                return *price;
+            }
+        }
+
+        let mut addresses = vec![token.fa_address.as_str()];
+        if let Some(token_address) = token.token_address.as_deref() {
+            addresses.push(token_address);
+        }
+
+        let query = token.token_address.as_deref().unwrap_or(&token.fa_address);
+
+        let price = match fetch_search_pools(query, Some("aptos"), 1).await {
+            Ok(data) => pool_price_for_token(&data, &addresses),
+            Err(e) => {
+                log::warn!("Fallback price lookup for {} failed: {}", token.symbol, e);
+                None
+            }
+        };
+
+        if let Some(price) = price {
+            log::info!(
+                "Using GeckoTerminal fallback price for {}: ${}",
+                token.symbol,
+                price
+            );
+        }
+
+        self.fallback_prices
+            .lock()
+            .unwrap()
+            .insert(cache_key, (price, Instant::now()));
 
-        Ok(token.clone())
+        price
     }
 }
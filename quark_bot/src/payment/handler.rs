@@ -1,6 +1,12 @@
+use crate::ai::actions::{PayAmount, prepare_pay_users};
+use crate::bot::hooks::pay_users_hook;
 use crate::dependencies::BotDependencies;
+use crate::notification_prefs::NotificationCategory;
 use crate::payment::dto::PaymentPrefs;
+use crate::payment::intent::{IntentParse, parse_payment_intent};
+use crate::utils::{create_purchase_request, is_admin, send_html_message, send_message};
 use anyhow::Result;
+use open_ai_rust_responses_by_sshift::Model;
 use quark_core::helpers::dto::CoinVersion;
 use teloxide::{
     Bot,
@@ -21,6 +27,11 @@ pub async fn handle_payment(
                 handle_open_group_payment_settings(bot, query, bot_deps).await?
             }
             "payment_selected" => handle_payment_selected(bot, query, bot_deps).await?,
+            "pay_notify_toggle" => handle_recipient_notifications_toggle(bot, query, bot_deps).await?,
+            "pay_autoconf" => handle_open_auto_confirm(bot, query, bot_deps).await?,
+            data if data.starts_with("pay_autoconf_set:") => {
+                handle_set_auto_confirm(bot, query, bot_deps).await?
+            }
             data if data.starts_with("pay_selid-") => {
                 handle_payment_selection(bot, query, bot_deps).await?
             }
@@ -46,11 +57,34 @@ async fn handle_open_payment_settings(
                 Ok(addr) => addr,
                 Err(_) => "0x1::aptos_coin::AptosCoin".to_string(),
             };
+            let notifications_enabled = bot_deps.notification_prefs.is_enabled(
+                query.from.id.0 as i64,
+                NotificationCategory::PaymentsReceived,
+            );
+            let auto_confirm_threshold = bot_deps
+                .payment
+                .get_auto_confirm_threshold(query.from.id.0 as i64);
             let kb = InlineKeyboardMarkup::new(vec![
                 vec![InlineKeyboardButton::callback(
                     "💳 Choose Payment Token",
                     "payment_selected",
                 )],
+                vec![InlineKeyboardButton::callback(
+                    if notifications_enabled {
+                        "🔔 Payment Received DMs: ON"
+                    } else {
+                        "🔕 Payment Received DMs: OFF"
+                    },
+                    "pay_notify_toggle",
+                )],
+                vec![InlineKeyboardButton::callback(
+                    if auto_confirm_threshold > 0.0 {
+                        format!("⚡ Auto-confirm under ${}", auto_confirm_threshold)
+                    } else {
+                        "⚡ Auto-confirm: OFF".to_string()
+                    },
+                    "pay_autoconf",
+                )],
                 vec![InlineKeyboardButton::callback(
                     "↩️ Back",
                     "back_to_user_settings",
@@ -72,6 +106,120 @@ async fn handle_open_payment_settings(
     Ok(())
 }
 
+/// Toggle the DM sent to the user when they receive a payment
+async fn handle_recipient_notifications_toggle(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let user_id = query.from.id.0 as i64;
+    let enabled = bot_deps
+        .notification_prefs
+        .toggle(user_id, NotificationCategory::PaymentsReceived)?;
+
+    bot.answer_callback_query(query.id.clone())
+        .text(if enabled {
+            "🔔 Payment notifications enabled"
+        } else {
+            "🔕 Payment notifications disabled"
+        })
+        .await?;
+
+    handle_open_payment_settings(bot, query, bot_deps).await
+}
+
+const AUTO_CONFIRM_PRESETS: [f64; 5] = [1.0, 5.0, 10.0, 25.0, 50.0];
+
+/// Show the auto-confirm threshold picker
+async fn handle_open_auto_confirm(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    if let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message {
+        let current = bot_deps
+            .payment
+            .get_auto_confirm_threshold(query.from.id.0 as i64);
+
+        let label = |value: f64| {
+            let text = if value > 0.0 {
+                format!("${}", value)
+            } else {
+                "Off".to_string()
+            };
+            if value == current {
+                format!("✅ {}", text)
+            } else {
+                text
+            }
+        };
+
+        let mut rows: Vec<Vec<InlineKeyboardButton>> = vec![vec![InlineKeyboardButton::callback(
+            label(0.0),
+            "pay_autoconf_set:0",
+        )]];
+        rows.push(
+            AUTO_CONFIRM_PRESETS
+                .iter()
+                .map(|v| {
+                    InlineKeyboardButton::callback(label(*v), format!("pay_autoconf_set:{}", v))
+                })
+                .collect(),
+        );
+        rows.push(vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "open_payment_settings",
+        )]);
+
+        bot.edit_message_text(
+            m.chat.id,
+            m.id,
+            "⚡ <b>Auto-confirm Payments</b>\n\nPayments worth less than this amount (in USD) are sent right away without the Accept/Reject step. Larger payments always ask for confirmation.\n\n<i>Off means every payment asks for confirmation.</i>",
+        )
+        .parse_mode(teloxide::types::ParseMode::Html)
+        .reply_markup(InlineKeyboardMarkup::new(rows))
+        .await?;
+    }
+
+    bot.answer_callback_query(query.id).await?;
+    Ok(())
+}
+
+/// Store the auto-confirm threshold picked by the user
+async fn handle_set_auto_confirm(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let value = query
+        .data
+        .as_deref()
+        .and_then(|d| d.strip_prefix("pay_autoconf_set:"))
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| *v == 0.0 || AUTO_CONFIRM_PRESETS.contains(v));
+
+    let Some(value) = value else {
+        bot.answer_callback_query(query.id)
+            .text("❌ Invalid threshold")
+            .await?;
+        return Ok(());
+    };
+
+    bot_deps
+        .payment
+        .set_auto_confirm_threshold(query.from.id.0 as i64, value);
+
+    bot.answer_callback_query(query.id.clone())
+        .text(if value > 0.0 {
+            format!("⚡ Payments under ${} are now auto-confirmed", value)
+        } else {
+            "✅ All payments now require confirmation".to_string()
+        })
+        .await?;
+
+    handle_open_payment_settings(bot, query, bot_deps).await
+}
+
 /// Handle opening group payment settings
 async fn handle_open_group_payment_settings(
     bot: Bot,
@@ -306,6 +454,10 @@ async fn handle_payment_selection(
                         "🧾 Summarization Settings",
                         "open_summarization_settings",
                     )],
+                    vec![InlineKeyboardButton::callback(
+                        "🖼️ Max Images per Request",
+                        "open_user_max_images",
+                    )],
                     vec![InlineKeyboardButton::callback(
                         "↩️ Close",
                         "user_settings_close",
@@ -373,6 +525,10 @@ async fn handle_payment_selection(
                         "📋 Summarization Settings",
                         "open_group_summarization_settings",
                     )],
+                    vec![InlineKeyboardButton::callback(
+                        "🧠 History Settings",
+                        "open_history_settings",
+                    )],
                     vec![InlineKeyboardButton::callback(
                         "🔄 Migrate Group ID",
                         "open_migrate_group_id",
@@ -396,3 +552,336 @@ async fn handle_payment_selection(
     }
     Ok(())
 }
+
+/// `/send <instruction>`: parse a plain-language payment and hand it to the usual confirmation
+pub async fn handle_send_command(
+    bot: Bot,
+    msg: Message,
+    instruction: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    if instruction.trim().is_empty() {
+        send_html_message(
+            msg,
+            bot,
+            "💸 <b>Send tokens in plain words</b>\n\nExamples:\n• <code>/send 5 APT to @alice</code>\n• <code>/send pay alice and bob 10 usdc each</code>\n• <code>/send 20 USDC to @alice for March rent</code> (adds a memo)".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ User not found".to_string()).await?;
+        return Ok(());
+    };
+    let user_id = user.id.0 as i64;
+
+    let group_id = if msg.chat.is_private() {
+        None
+    } else {
+        Some(msg.chat.id.to_string())
+    };
+
+    // Checked before parsing, which bills the group, rather than left to execute_pay_users
+    if group_id.is_some() && !is_admin(&bot, msg.chat.id, user.id).await {
+        send_message(
+            msg,
+            bot,
+            "❌ Only admins can send tokens to members".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let jwt = if group_id.is_some() {
+        bot_deps.group.get_credentials(msg.chat.id).map(|c| c.jwt)
+    } else {
+        user.username
+            .as_ref()
+            .and_then(|username| bot_deps.auth.get_credentials(username))
+            .map(|c| c.jwt)
+    };
+    let Some(jwt) = jwt else {
+        let text = if group_id.is_some() {
+            "❌ This group isn't logged in yet. An admin can use /logingroup."
+        } else {
+            "❌ Please log in with /loginuser first."
+        };
+        send_message(msg, bot, text.to_string()).await?;
+        return Ok(());
+    };
+
+    let (parse, total_tokens) = match parse_payment_intent(&bot_deps.ai, &instruction).await {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!("Failed to parse payment instruction: {}", e);
+            send_message(
+                msg,
+                bot,
+                "❌ Couldn't read that payment right now. Please try again.".to_string(),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(e) = create_purchase_request(
+        0,
+        0,
+        0,
+        total_tokens,
+        Model::GPT5Nano,
+        &jwt,
+        group_id.clone(),
+        Some(user_id.to_string()),
+        bot_deps.clone(),
+    )
+    .await
+    {
+        log::error!("Error purchasing tokens for /send: {}", e);
+        send_message(
+            msg,
+            bot,
+            "❌ Couldn't charge for this request. Check your balance and try again.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let intent = match parse {
+        IntentParse::Ready(intent) => intent,
+        IntentParse::Clarify(question) => {
+            send_html_message(
+                msg,
+                bot,
+                format!(
+                    "🤔 {}\n\n<i>Reply with a new /send including the missing details.</i>",
+                    teloxide::utils::html::escape(&question)
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let unknown: Vec<String> = intent
+        .recipients
+        .iter()
+        .filter(|username| bot_deps.auth.get_credentials(username).is_none())
+        .map(|username| format!("@{}", username))
+        .collect();
+    if !unknown.is_empty() {
+        send_message(
+            msg,
+            bot,
+            format!(
+                "❌ {} {} not registered with Quark yet. They need to /loginuser first.",
+                unknown.join(", "),
+                if unknown.len() == 1 { "is" } else { "are" }
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let symbol = intent.symbol.to_lowercase();
+    if symbol != "apt" && symbol != "aptos" {
+        if bot_deps.panora.get_token_by_symbol(&intent.symbol).await.is_err() {
+            send_message(
+                msg,
+                bot,
+                format!("❌ Unknown token: {}", intent.symbol),
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let prepared = match prepare_pay_users(
+        PayAmount::Tokens(intent.total_amount),
+        &intent.to_pay_users_arguments(),
+        bot.clone(),
+        msg.clone(),
+        bot_deps.clone(),
+        group_id.clone(),
+    )
+    .await
+    {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            send_message(msg, bot, e).await?;
+            return Ok(());
+        }
+    };
+
+    let recipients: Vec<String> = intent
+        .recipients
+        .iter()
+        .map(|username| format!("@{}", username))
+        .collect();
+    let text = format!(
+        "{}\n\nRecipients: {}",
+        prepared.summary,
+        recipients.join(", ")
+    );
+
+    pay_users_hook(bot, msg, text, group_id, prepared.transaction_id, bot_deps).await
+}
+
+/// Octas kept back when sending the whole APT balance, so the transfer can pay its gas
+pub const GAS_RESERVE_OCTAS: u64 = 1_000_000;
+
+/// Units that can be sent from `balance`; the gas token keeps a reserve for fees
+pub fn sendable_amount(balance: i64, is_gas_token: bool) -> Option<u64> {
+    let balance = u64::try_from(balance).ok()?;
+    let reserve = if is_gas_token { GAS_RESERVE_OCTAS } else { 0 };
+    balance.checked_sub(reserve).filter(|amount| *amount > 0)
+}
+
+/// `/sendmax @user SYMBOL`: send the entire available balance of a token through the usual confirmation
+pub async fn handle_send_max_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let mut words = args.split_whitespace();
+    let (Some(recipient), Some(symbol), None) = (words.next(), words.next(), words.next()) else {
+        send_html_message(
+            msg,
+            bot,
+            "💸 <b>Send your full balance</b>\n\nUsage: <code>/sendmax @alice USDC</code>\n\nFor APT a small amount is kept back to pay the network fee.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    };
+    let recipient = recipient.trim_start_matches('@').to_string();
+
+    if bot_deps.auth.get_credentials(&recipient).is_none() {
+        send_message(
+            msg,
+            bot,
+            format!("❌ @{} is not registered with Quark yet. They need to /loginuser first.", recipient),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let group_id = if msg.chat.is_private() {
+        None
+    } else {
+        Some(msg.chat.id.to_string())
+    };
+
+    let payer_address = if group_id.is_some() {
+        bot_deps
+            .group
+            .get_credentials(msg.chat.id)
+            .map(|c| c.resource_account_address)
+    } else {
+        msg.from
+            .as_ref()
+            .and_then(|u| u.username.as_ref())
+            .and_then(|username| bot_deps.auth.get_credentials(username))
+            .map(|c| c.resource_account_address)
+    };
+    let Some(payer_address) = payer_address else {
+        let text = if group_id.is_some() {
+            "❌ This group isn't logged in yet. An admin can use /logingroup."
+        } else {
+            "❌ Please log in with /loginuser first."
+        };
+        send_message(msg, bot, text.to_string()).await?;
+        return Ok(());
+    };
+
+    let is_gas_token = matches!(symbol.to_lowercase().as_str(), "apt" | "aptos");
+    let (token_type, decimals, token_symbol) = if is_gas_token {
+        ("0x1::aptos_coin::AptosCoin".to_string(), 8u8, "APT".to_string())
+    } else {
+        match bot_deps.panora.get_token_by_symbol(symbol).await {
+            Ok(token) => (
+                token
+                    .token_address
+                    .clone()
+                    .unwrap_or_else(|| token.fa_address.clone()),
+                token.decimals,
+                token.symbol.clone(),
+            ),
+            Err(_) => {
+                send_message(msg, bot, format!("❌ Unknown token: {}", symbol)).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let balance = match bot_deps
+        .panora
+        .aptos
+        .node
+        .get_account_balance(payer_address, token_type)
+        .await
+    {
+        Ok(balance) => balance.into_inner().as_i64().unwrap_or(0),
+        Err(e) => {
+            log::error!("❌ Error getting balance for /sendmax: {}", e);
+            send_message(msg, bot, "❌ Error getting balance".to_string()).await?;
+            return Ok(());
+        }
+    };
+
+    let Some(units) = sendable_amount(balance, is_gas_token) else {
+        let text = if balance > 0 && is_gas_token {
+            "❌ Your APT balance only covers the network fee, so there's nothing left to send."
+                .to_string()
+        } else {
+            format!("❌ You have no {} to send.", token_symbol)
+        };
+        send_message(msg, bot, text).await?;
+        return Ok(());
+    };
+    let exact_amount = format!(
+        "{:.*}",
+        decimals as usize,
+        units as f64 / 10_f64.powi(decimals as i32)
+    )
+    .trim_end_matches('0')
+    .trim_end_matches('.')
+    .to_string();
+
+    // Units go through as-is, so the whole balance is sent without float rounding
+    let prepared = match prepare_pay_users(
+        PayAmount::Units(units),
+        &serde_json::json!({
+            "symbol": token_symbol,
+            "users": [recipient],
+        }),
+        bot.clone(),
+        msg.clone(),
+        bot_deps.clone(),
+        group_id.clone(),
+    )
+    .await
+    {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            send_message(msg, bot, e).await?;
+            return Ok(());
+        }
+    };
+
+    let reserve_note = if is_gas_token {
+        format!(
+            " ({} APT kept for the network fee)",
+            GAS_RESERVE_OCTAS as f64 / 1e8
+        )
+    } else {
+        String::new()
+    };
+    let text = format!(
+        "Full balance: {} {}{}\n\n{}\n\nRecipients: @{}",
+        exact_amount, token_symbol, reserve_note, prepared.summary, recipient
+    );
+
+    pay_users_hook(bot, msg, text, group_id, prepared.transaction_id, bot_deps).await
+}
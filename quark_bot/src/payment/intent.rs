@@ -0,0 +1,290 @@
+//! Turn a free-text send instruction into an explicit, validated payment intent.
+
+use serde_json::{Value, json};
+
+use crate::ai::handler::AI;
+use crate::payment::memo::validate_memo;
+use open_ai_rust_responses_by_sshift::Model;
+
+const MAX_RECIPIENTS: usize = 20;
+const MAX_SYMBOL_LEN: usize = 20;
+
+const INTENT_INSTRUCTIONS: &str = "Extract a token payment from the user's instruction. \
+Recipients are Telegram usernames without the leading @. \
+Set amount_per_recipient to true when the amount applies to each recipient (\"10 each\"), \
+false when it is a total to split. \
+Put any note the user wants attached (\"for pizza\", \"memo: March rent\") in `memo`, else an empty string. \
+If the recipients, the amount or the token are missing or could mean more than one thing, \
+leave the unclear fields empty or zero and put a short clarifying question in `clarification`; \
+otherwise set `clarification` to an empty string. Never guess.";
+
+/// A send instruction the `get_pay_users` flow can act on
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentIntent {
+    pub recipients: Vec<String>,
+    /// Total amount, split evenly among the recipients
+    pub total_amount: f64,
+    pub symbol: String,
+    pub memo: Option<String>,
+}
+
+impl PaymentIntent {
+    pub fn per_recipient_amount(&self) -> f64 {
+        self.total_amount / self.recipients.len() as f64
+    }
+
+    /// Arguments in the shape the `get_pay_users` tool expects
+    pub fn to_pay_users_arguments(&self) -> Value {
+        let mut arguments = json!({
+            "amount": self.total_amount,
+            "symbol": self.symbol,
+            "users": self.recipients,
+        });
+        if let Some(memo) = &self.memo {
+            arguments["memo"] = json!(memo);
+        }
+        arguments
+    }
+}
+
+/// Outcome of parsing: either ready to confirm, or a question back to the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntentParse {
+    Ready(PaymentIntent),
+    Clarify(String),
+}
+
+/// JSON schema used for the structured AI request
+pub fn payment_intent_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "recipients": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Telegram usernames without @"
+            },
+            "amount": {
+                "type": "number",
+                "description": "Amount from the instruction, 0 when missing"
+            },
+            "amount_per_recipient": {
+                "type": "boolean",
+                "description": "True when the amount is for each recipient rather than a total"
+            },
+            "symbol": {
+                "type": "string",
+                "description": "Token symbol, e.g. APT or USDC; empty when missing"
+            },
+            "memo": {
+                "type": "string",
+                "description": "Note to attach to the payment, else empty"
+            },
+            "clarification": {
+                "type": "string",
+                "description": "Clarifying question when the instruction is ambiguous, else empty"
+            }
+        },
+        "required": ["recipients", "amount", "amount_per_recipient", "symbol", "memo", "clarification"],
+        "additionalProperties": false
+    })
+}
+
+/// Telegram usernames are 5-32 characters of letters, digits and underscores
+fn normalize_username(raw: &str) -> Option<String> {
+    let name = raw.trim().trim_start_matches('@');
+    let valid = (5..=32).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| name.to_string())
+}
+
+/// Check the extracted fields and compute the total; `Clarify` carries the question to ask
+pub fn validate_payment_intent(value: &Value) -> IntentParse {
+    let clarification = value
+        .get("clarification")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim();
+    if !clarification.is_empty() {
+        return IntentParse::Clarify(clarification.to_string());
+    }
+
+    let raw_recipients: Vec<&str> = value
+        .get("recipients")
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    if raw_recipients.is_empty() {
+        return IntentParse::Clarify("Who should receive the payment?".to_string());
+    }
+
+    let mut recipients: Vec<String> = Vec::new();
+    for raw in raw_recipients {
+        let Some(name) = normalize_username(raw) else {
+            return IntentParse::Clarify(format!(
+                "\"{}\" doesn't look like a Telegram username. Who did you mean?",
+                raw.trim()
+            ));
+        };
+        if !recipients
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&name))
+        {
+            recipients.push(name);
+        }
+    }
+    if recipients.len() > MAX_RECIPIENTS {
+        return IntentParse::Clarify(format!(
+            "That's {} recipients; please send to at most {} at a time.",
+            recipients.len(),
+            MAX_RECIPIENTS
+        ));
+    }
+
+    let amount = value.get("amount").and_then(Value::as_f64).unwrap_or(0.0);
+    if !amount.is_finite() || amount <= 0.0 {
+        return IntentParse::Clarify("How much should be sent?".to_string());
+    }
+
+    let symbol = value
+        .get("symbol")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .trim_start_matches('$');
+    if symbol.is_empty()
+        || symbol.chars().count() > MAX_SYMBOL_LEN
+        || symbol.chars().any(char::is_whitespace)
+    {
+        return IntentParse::Clarify("Which token should be sent?".to_string());
+    }
+
+    let per_recipient = value
+        .get("amount_per_recipient")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let total_amount = if per_recipient {
+        amount * recipients.len() as f64
+    } else {
+        amount
+    };
+
+    let memo = match validate_memo(
+        value
+            .get("memo")
+            .and_then(Value::as_str)
+            .unwrap_or_default(),
+    ) {
+        Ok(memo) => memo,
+        Err(reason) => return IntentParse::Clarify(format!("{} Please shorten the memo.", reason)),
+    };
+
+    IntentParse::Ready(PaymentIntent {
+        recipients,
+        total_amount,
+        symbol: symbol.to_string(),
+        memo,
+    })
+}
+
+/// Ask the model for a structured payment intent and validate it.
+///
+/// Returns the parse outcome and the tokens used so the caller can bill them.
+pub async fn parse_payment_intent(ai: &AI, instruction: &str) -> anyhow::Result<(IntentParse, u32)> {
+    let response = ai
+        .generate_structured_response(
+            INTENT_INSTRUCTIONS,
+            instruction,
+            "payment_intent",
+            payment_intent_schema(),
+            Model::GPT5Nano,
+            300,
+        )
+        .await?;
+
+    let parse = match &response.value {
+        Some(value) => validate_payment_intent(value),
+        None => IntentParse::Clarify(
+            "I couldn't work out that payment. Try something like \"10 USDC to @alice\".".to_string(),
+        ),
+    };
+
+    Ok((parse, response.total_tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_amount_is_multiplied() {
+        let value = json!({
+            "recipients": ["@alice_01", "bobby_02"],
+            "amount": 10.0,
+            "amount_per_recipient": true,
+            "symbol": "usdc",
+            "clarification": ""
+        });
+
+        let IntentParse::Ready(intent) = validate_payment_intent(&value) else {
+            panic!("expected a ready intent");
+        };
+        assert_eq!(intent.recipients, vec!["alice_01", "bobby_02"]);
+        assert_eq!(intent.total_amount, 20.0);
+        assert_eq!(intent.per_recipient_amount(), 10.0);
+        assert_eq!(intent.to_pay_users_arguments()["users"][1], "bobby_02");
+        assert!(intent.memo.is_none());
+        assert!(intent.to_pay_users_arguments().get("memo").is_none());
+    }
+
+    #[test]
+    fn test_memo_is_carried_to_arguments() {
+        let value = json!({
+            "recipients": ["alice_01"],
+            "amount": 5.0,
+            "amount_per_recipient": false,
+            "symbol": "APT",
+            "memo": " March rent ",
+            "clarification": ""
+        });
+
+        let IntentParse::Ready(intent) = validate_payment_intent(&value) else {
+            panic!("expected a ready intent");
+        };
+        assert_eq!(intent.memo.as_deref(), Some("March rent"));
+        assert_eq!(intent.to_pay_users_arguments()["memo"], "March rent");
+    }
+
+    #[test]
+    fn test_ambiguous_parses_ask_for_clarification() {
+        let ambiguous = json!({
+            "recipients": ["alice_01"],
+            "amount": 0.0,
+            "amount_per_recipient": false,
+            "symbol": "",
+            "clarification": "How much APT should alice_01 get?"
+        });
+        assert_eq!(
+            validate_payment_intent(&ambiguous),
+            IntentParse::Clarify("How much APT should alice_01 get?".to_string())
+        );
+
+        let bad_user = json!({
+            "recipients": ["the team"],
+            "amount": 5.0,
+            "amount_per_recipient": false,
+            "symbol": "APT",
+            "clarification": ""
+        });
+        assert!(matches!(validate_payment_intent(&bad_user), IntentParse::Clarify(_)));
+
+        let no_token = json!({
+            "recipients": ["alice_01"],
+            "amount": 5.0,
+            "amount_per_recipient": false,
+            "symbol": "",
+            "clarification": ""
+        });
+        assert!(matches!(validate_payment_intent(&no_token), IntentParse::Clarify(_)));
+    }
+}
@@ -0,0 +1,346 @@
+//! Record of payments the bot has sent, reconciled against the chain by a periodic job.
+
+use std::env;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sled::{Db, Tree};
+use teloxide::types::ChatId;
+
+use crate::{dependencies::BotDependencies, pending_transactions::dto::PendingTransaction};
+
+const TREE_NAME: &str = "payment_ledger";
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+/// Settled entries are dropped after this long to keep the tree small, from
+/// PAYMENT_LEDGER_RETENTION_DAYS (default 30)
+fn retention_secs() -> i64 {
+    static RETENTION: OnceLock<i64> = OnceLock::new();
+    *RETENTION.get_or_init(|| {
+        let days = env::var("PAYMENT_LEDGER_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+        days * 24 * 60 * 60
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReconcileStatus {
+    Pending,
+    Matched,
+    Discrepancy(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub tx_hash: String,
+    pub recorded_at: i64,
+    /// Where the payment came from, e.g. "send" or "scheduled"
+    pub source: String,
+    pub chat_id: i64,
+    pub is_group: bool,
+    /// Resource account the funds should have left from, when known
+    pub payer_address: Option<String>,
+    pub recipient_addresses: Vec<String>,
+    /// Total sent across all recipients, in smallest units
+    pub amount: u64,
+    pub coin_type: String,
+    pub symbol: String,
+    pub status: ReconcileStatus,
+    #[serde(default)]
+    pub checked_at: Option<i64>,
+}
+
+#[derive(Clone)]
+pub struct PaymentLedger {
+    tree: Tree,
+}
+
+impl PaymentLedger {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn record(&self, entry: &LedgerEntry) -> sled::Result<()> {
+        self.tree.insert(
+            entry.tx_hash.to_lowercase(),
+            serde_json::to_vec(entry).unwrap(),
+        )?;
+        Ok(())
+    }
+
+    /// Pending entries recorded before `cutoff`, oldest first
+    pub fn pending_before(&self, cutoff: i64, limit: usize) -> Vec<LedgerEntry> {
+        let mut entries: Vec<LedgerEntry> = self
+            .tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter_map(|(_, v)| serde_json::from_slice::<LedgerEntry>(&v).ok())
+            .filter(|e| e.status == ReconcileStatus::Pending && e.recorded_at <= cutoff)
+            .collect();
+        entries.sort_by_key(|e| e.recorded_at);
+        entries.truncate(limit);
+        entries
+    }
+
+    pub fn set_status(
+        &self,
+        entry: &LedgerEntry,
+        status: ReconcileStatus,
+        now: i64,
+    ) -> sled::Result<()> {
+        let mut updated = entry.clone();
+        updated.status = status;
+        updated.checked_at = Some(now);
+        self.record(&updated)
+    }
+
+    /// Drop checked entries older than the retention window
+    pub fn prune(&self, now: i64) -> usize {
+        let stale: Vec<_> = self
+            .tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(_, v)| {
+                serde_json::from_slice::<LedgerEntry>(v)
+                    .map(|e| {
+                        e.status != ReconcileStatus::Pending
+                            && now - e.recorded_at > retention_secs()
+                    })
+                    .unwrap_or(true)
+            })
+            .map(|(k, _)| k)
+            .collect();
+        for key in &stale {
+            let _ = self.tree.remove(key);
+        }
+        stale.len()
+    }
+}
+
+/// Record a confirmed /send payment so the reconciliation job can check it later
+pub fn record_sent_payment(
+    bot_deps: &BotDependencies,
+    transaction: &PendingTransaction,
+    tx_hash: &str,
+    source: &str,
+    username: Option<&str>,
+) {
+    let payer_address = if transaction.is_group_transfer {
+        bot_deps
+            .group
+            .get_credentials(ChatId(transaction.chat_id))
+            .map(|c| c.resource_account_address)
+    } else {
+        username
+            .and_then(|u| bot_deps.auth.get_credentials(u))
+            .map(|c| c.resource_account_address)
+    };
+
+    let entry = LedgerEntry {
+        tx_hash: tx_hash.to_string(),
+        recorded_at: chrono::Utc::now().timestamp(),
+        source: source.to_string(),
+        chat_id: transaction.chat_id,
+        is_group: transaction.is_group_transfer,
+        payer_address,
+        recipient_addresses: transaction.user_addresses.clone(),
+        amount: transaction.amount,
+        coin_type: transaction.coin_type.clone(),
+        symbol: transaction.symbol.clone(),
+        status: ReconcileStatus::Pending,
+        checked_at: None,
+    };
+    if let Err(e) = bot_deps.payment_ledger.record(&entry) {
+        log::error!("Failed to record payment {} in ledger: {}", tx_hash, e);
+    }
+}
+
+/// Addresses show up both long (0x000…abc) and short (0xabc); compare without padding
+fn normalize_address(address: &str) -> String {
+    let hex = address.trim().trim_start_matches("0x").trim_start_matches('0');
+    hex.to_lowercase()
+}
+
+fn collect_strings(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) => out.push(s.clone()),
+        Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_strings(v, out)),
+        Value::Number(n) => out.push(n.to_string()),
+        _ => {}
+    }
+}
+
+fn mentions_address(strings: &[String], address: &str) -> bool {
+    let wanted = normalize_address(address);
+    !wanted.is_empty()
+        && strings
+            .iter()
+            .any(|s| s.starts_with("0x") && normalize_address(s) == wanted)
+}
+
+/// Compare a ledger entry with the on-chain transaction (read-only)
+pub fn reconcile(entry: &LedgerEntry, tx: Option<&Value>) -> ReconcileStatus {
+    let Some(tx) = tx else {
+        return ReconcileStatus::Discrepancy("transaction not found on-chain".to_string());
+    };
+
+    if tx.get("success").and_then(Value::as_bool) != Some(true) {
+        let vm_status = tx
+            .get("vm_status")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown status");
+        return ReconcileStatus::Discrepancy(format!("transaction failed: {}", vm_status));
+    }
+
+    let mut strings = Vec::new();
+    if let Some(payload) = tx.get("payload") {
+        collect_strings(payload, &mut strings);
+    }
+    let events = tx
+        .get("events")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for event in &events {
+        collect_strings(event, &mut strings);
+    }
+    if let Some(sender) = tx.get("sender") {
+        collect_strings(sender, &mut strings);
+    }
+
+    let missing: Vec<&String> = entry
+        .recipient_addresses
+        .iter()
+        .filter(|addr| !mentions_address(&strings, addr))
+        .collect();
+    if !missing.is_empty() {
+        return ReconcileStatus::Discrepancy(format!(
+            "no transfer found to {}",
+            missing
+                .iter()
+                .map(|a| a.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if let Some(payer) = &entry.payer_address {
+        if !mentions_address(&strings, payer) {
+            return ReconcileStatus::Discrepancy(format!(
+                "payer account {} not involved in the transaction",
+                payer
+            ));
+        }
+    }
+
+    // `amount` is the total across all recipients, as recorded from the pending transaction
+    let expected = entry.amount;
+    let deposited: u64 = events
+        .iter()
+        .filter(|e| {
+            e.get("type")
+                .and_then(Value::as_str)
+                .is_some_and(|t| t.contains("Deposit"))
+        })
+        .filter_map(|e| e.get("data")?.get("amount")?.as_str()?.parse::<u64>().ok())
+        .sum();
+    // Fungible-asset transfers don't always emit amount-bearing deposit events,
+    // so fall back to the amount passed to the entry function
+    let amount_in_payload = tx
+        .get("payload")
+        .map(|payload| {
+            let mut args = Vec::new();
+            collect_strings(payload, &mut args);
+            args.contains(&entry.amount.to_string())
+        })
+        .unwrap_or(false);
+    if deposited != expected && !(deposited == 0 && amount_in_payload) {
+        return ReconcileStatus::Discrepancy(format!(
+            "expected {} units of {} deposited, saw {}",
+            expected, entry.symbol, deposited
+        ));
+    }
+
+    ReconcileStatus::Matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry() -> LedgerEntry {
+        LedgerEntry {
+            tx_hash: "0xabc".to_string(),
+            recorded_at: 0,
+            source: "send".to_string(),
+            chat_id: -100,
+            is_group: true,
+            payer_address: Some("0x0000aa".to_string()),
+            recipient_addresses: vec!["0xbb".to_string(), "0xcc".to_string()],
+            amount: 100,
+            coin_type: "0x1::aptos_coin::AptosCoin".to_string(),
+            symbol: "APT".to_string(),
+            status: ReconcileStatus::Pending,
+            checked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_matching_transaction() {
+        let tx = json!({
+            "success": true,
+            "payload": {"arguments": ["0xaa", ["0x00bb", "0xcc"]]},
+            "events": [
+                {"type": "0x1::coin::DepositEvent", "data": {"amount": "50", "account": "0xbb"}},
+                {"type": "0x1::coin::DepositEvent", "data": {"amount": "50", "account": "0xcc"}}
+            ]
+        });
+        assert_eq!(reconcile(&entry(), Some(&tx)), ReconcileStatus::Matched);
+
+        let without_deposit_amounts = json!({
+            "success": true,
+            "sender": "0xaa",
+            "payload": {"arguments": [["0xbb", "0xcc"], "100"]},
+            "events": []
+        });
+        assert_eq!(
+            reconcile(&entry(), Some(&without_deposit_amounts)),
+            ReconcileStatus::Matched
+        );
+    }
+
+    #[test]
+    fn test_discrepancies() {
+        assert!(matches!(reconcile(&entry(), None), ReconcileStatus::Discrepancy(_)));
+
+        let failed = json!({"success": false, "vm_status": "OUT_OF_GAS"});
+        assert_eq!(
+            reconcile(&entry(), Some(&failed)),
+            ReconcileStatus::Discrepancy("transaction failed: OUT_OF_GAS".to_string())
+        );
+
+        let short = json!({
+            "success": true,
+            "payload": {"arguments": ["0xaa", ["0xbb", "0xcc"]]},
+            "events": [{"type": "0x1::coin::DepositEvent", "data": {"amount": "40"}}]
+        });
+        assert!(matches!(reconcile(&entry(), Some(&short)), ReconcileStatus::Discrepancy(_)));
+
+        let missing_recipient = json!({
+            "success": true,
+            "payload": {"arguments": ["0xaa", ["0xbb"], "100"]},
+            "events": []
+        });
+        assert!(matches!(
+            reconcile(&entry(), Some(&missing_recipient)),
+            ReconcileStatus::Discrepancy(_)
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+//! Optional memos on payments. The contract has no memo field, so they are kept
+//! off-chain keyed by transaction hash.
+
+use sled::{Db, Tree};
+use teloxide::utils::html;
+
+const TREE_NAME: &str = "payment_memos";
+pub const MAX_MEMO_CHARS: usize = 140;
+
+/// Trim and check a memo; empty input means no memo
+pub fn validate_memo(raw: &str) -> Result<Option<String>, String> {
+    let memo = raw.trim();
+    if memo.is_empty() {
+        return Ok(None);
+    }
+    if memo.chars().count() > MAX_MEMO_CHARS {
+        return Err(format!(
+            "Memos can be at most {} characters.",
+            MAX_MEMO_CHARS
+        ));
+    }
+    if memo.chars().any(|c| c.is_control()) {
+        return Err("Memos must fit on a single line.".to_string());
+    }
+    Ok(Some(memo.to_string()))
+}
+
+/// HTML line for success messages; empty when there's no memo
+pub fn memo_html_line(memo: Option<&str>) -> String {
+    memo.map(|m| format!("\n📝 <i>{}</i>", html::escape(m)))
+        .unwrap_or_default()
+}
+
+#[derive(Clone)]
+pub struct PaymentMemos {
+    tree: Tree,
+}
+
+impl PaymentMemos {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn set(&self, tx_hash: &str, memo: &str) -> sled::Result<()> {
+        self.tree.insert(tx_hash.to_lowercase(), memo.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn get(&self, tx_hash: &str) -> Option<String> {
+        self.tree
+            .get(tx_hash.to_lowercase())
+            .ok()
+            .flatten()
+            .and_then(|v| String::from_utf8(v.to_vec()).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_memo() {
+        assert_eq!(validate_memo("   "), Ok(None));
+        assert_eq!(validate_memo(" lunch "), Ok(Some("lunch".to_string())));
+        assert!(validate_memo(&"x".repeat(MAX_MEMO_CHARS + 1)).is_err());
+        assert!(validate_memo("two\nlines").is_err());
+    }
+
+    #[test]
+    fn test_memo_line_is_escaped() {
+        assert_eq!(memo_html_line(Some("<b>")), "\n📝 <i>&lt;b&gt;</i>");
+        assert_eq!(memo_html_line(None), "");
+    }
+}
@@ -1,3 +1,7 @@
 pub mod dto;
 pub mod handler;
+pub mod intent;
+pub mod ledger;
+pub mod memo;
+pub mod notifications;
 pub mod payment;
@@ -0,0 +1,93 @@
+use teloxide::{
+    Bot,
+    prelude::*,
+    types::{ChatId, ParseMode},
+};
+
+use crate::{
+    dependencies::BotDependencies, notification_prefs::NotificationCategory,
+    rate_limiter::SendLimiter,
+};
+
+/// DM each recipient that a payment landed in their wallet.
+///
+/// Recipients that haven't opted in, have no linked account, or never started the bot
+/// (Telegram refuses the DM) are skipped silently.
+pub async fn notify_payment_recipients(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    recipient_usernames: &[String],
+    amount_each: f64,
+    symbol: &str,
+    source: &str,
+    tx_hash: &str,
+    note: Option<&str>,
+) {
+    let network = std::env::var("APTOS_NETWORK")
+        .unwrap_or_else(|_| "mainnet".to_string())
+        .to_lowercase();
+
+    let note_line = note
+        .map(|n| format!("\n📝 <i>{}</i>", teloxide::utils::html::escape(n)))
+        .unwrap_or_default();
+
+    let text = format!(
+        "💸 <b>You received a payment!</b>\n\n💰 {:.4} {} from {}{}\n\n🔗 <a href=\"https://explorer.aptoslabs.com/txn/{}?network={}\">View transaction</a>\n\n<i>Turn these messages off with /notifications.</i>",
+        amount_each,
+        teloxide::utils::html::escape(symbol),
+        teloxide::utils::html::escape(source),
+        note_line,
+        tx_hash,
+        network
+    );
+
+    for username in recipient_usernames {
+        let username = username.trim_start_matches('@');
+
+        let credentials = match bot_deps.auth.get_credentials(username) {
+            Some(c) => c,
+            None => continue,
+        };
+
+        let recipient_id = credentials.user_id.0 as i64;
+
+        if !bot_deps
+            .notification_prefs
+            .is_enabled(recipient_id, NotificationCategory::PaymentsReceived)
+        {
+            continue;
+        }
+
+        if let Err(e) = SendLimiter::global()
+            .send(|| {
+                bot.send_message(ChatId(recipient_id), text.clone())
+                    .parse_mode(ParseMode::Html)
+                    .send()
+            })
+            .await
+        {
+            log::debug!(
+                "Skipping payment notification for @{} (DM not possible): {}",
+                username,
+                e
+            );
+        }
+    }
+}
+
+/// Human-readable payment source: the group's title, or the sender for DM transfers
+pub async fn payment_source_label(bot: &Bot, chat_id: ChatId, sender: Option<&str>) -> String {
+    if chat_id.is_user() {
+        return sender
+            .map(|s| format!("@{}", s))
+            .unwrap_or_else(|| "a Quark user".to_string());
+    }
+
+    match bot.get_chat(chat_id).await {
+        Ok(chat) => chat
+            .title()
+            .map(|t| format!("group \"{}\"", t))
+            .unwrap_or_else(|| "a group".to_string()),
+        Err(_) => "a group".to_string(),
+    }
+}
@@ -4,12 +4,17 @@ use sled::{Db, Tree};
 #[derive(Clone)]
 pub struct Payment {
     db: Tree,
+    auto_confirm: Tree,
 }
 
 impl Payment {
     pub fn new(db: &Db) -> sled::Result<Self> {
         let tree = db.open_tree("payment")?;
-        Ok(Self { db: tree })
+        let auto_confirm = db.open_tree("payment_auto_confirm")?;
+        Ok(Self {
+            db: tree,
+            auto_confirm,
+        })
     }
 
     pub async fn get_payment_token(
@@ -51,4 +56,23 @@ impl Payment {
             .insert(id, serde_json::to_vec(&value).unwrap())
             .unwrap();
     }
+
+    /// USD value under which the user's payments skip the accept/reject step (0 = always confirm)
+    pub fn get_auto_confirm_threshold(&self, user_id: i64) -> f64 {
+        self.auto_confirm
+            .get(user_id.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice::<f64>(&v).ok())
+            .unwrap_or(0.0)
+    }
+
+    pub fn set_auto_confirm_threshold(&self, user_id: i64, threshold_usd: f64) {
+        self.auto_confirm
+            .insert(
+                user_id.to_be_bytes(),
+                serde_json::to_vec(&threshold_usd).unwrap(),
+            )
+            .unwrap();
+    }
 }
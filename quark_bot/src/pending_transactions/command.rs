@@ -0,0 +1,142 @@
+//! /pending — inspect and cancel a payment that is waiting for confirmation.
+
+use anyhow::Result;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId},
+    utils::html,
+};
+
+use super::{dto::PendingTransaction, handler::PendingTransactions};
+use crate::{
+    dependencies::BotDependencies,
+    utils::{KeyboardMarkupType, format_timestamp, send_markdown_message_with_keyboard, send_message},
+};
+
+fn recipients_text(transaction: &PendingTransaction) -> String {
+    transaction
+        .original_usernames
+        .iter()
+        .map(|username| format!("@{}", html::escape(username)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe(transaction: &PendingTransaction, group_id: Option<i64>, now: u64) -> String {
+    let mut text = format!(
+        "⏳ <b>Pending payment</b>\n\n💰 {:.2} {} to {}\n📍 {}\n🕒 Created {}\n⌛ Expires in {}s\n🆔 <code>{}</code>",
+        transaction.per_user_amount * transaction.original_usernames.len() as f64,
+        html::escape(&transaction.symbol),
+        recipients_text(transaction),
+        match group_id {
+            Some(group_id) => format!("Group <code>{}</code>", group_id),
+            None => "Direct messages".to_string(),
+        },
+        format_timestamp(transaction.created_at),
+        transaction.expires_at.saturating_sub(now),
+        transaction.transaction_id
+    );
+    if let Some(memo) = &transaction.memo {
+        text.push_str(&format!("\n📝 {}", html::escape(memo)));
+    }
+    text
+}
+
+/// Remove an expired transaction and strip the buttons from its confirmation message
+async fn clean_up_expired(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    user_id: i64,
+    group_id: Option<i64>,
+    transaction: &PendingTransaction,
+) {
+    if let Err(e) = bot_deps
+        .pending_transactions
+        .delete_pending_transaction(user_id, group_id)
+    {
+        log::warn!(
+            "Failed to delete expired pending transaction {}: {}",
+            transaction.transaction_id,
+            e
+        );
+        return;
+    }
+    log::info!(
+        "Removed expired transaction {} on /pending",
+        transaction.transaction_id
+    );
+    if transaction.message_id != 0 {
+        if let Err(e) = bot
+            .edit_message_reply_markup(ChatId(transaction.chat_id), MessageId(transaction.message_id))
+            .await
+        {
+            log::debug!("Failed to clear buttons of expired transaction message: {}", e);
+        }
+    }
+}
+
+/// Show the caller's pending transactions with a Cancel button each; in a group only that
+/// group's one is shown, in DMs every chat's
+pub async fn handle_pending_command(bot: Bot, msg: Message, bot_deps: BotDependencies) -> Result<()> {
+    let Some(user) = msg.from.clone() else {
+        return Ok(());
+    };
+    let user_id = user.id.0 as i64;
+
+    let transactions = if msg.chat.is_private() {
+        bot_deps.pending_transactions.list_for_user(user_id)
+    } else {
+        let group_id = Some(msg.chat.id.0);
+        bot_deps
+            .pending_transactions
+            .get_pending_transaction(user_id, group_id)
+            .map(|transaction| vec![(group_id, transaction)])
+            .unwrap_or_default()
+    };
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let mut expired = 0;
+    let mut active = Vec::new();
+    for (group_id, transaction) in transactions {
+        if PendingTransactions::is_expired(&transaction) {
+            clean_up_expired(&bot, &bot_deps, user_id, group_id, &transaction).await;
+            expired += 1;
+        } else {
+            active.push((group_id, transaction));
+        }
+    }
+
+    if active.is_empty() {
+        let mut text = "✅ You have no pending transactions.".to_string();
+        if expired > 0 {
+            text.push_str(&format!(
+                "\n\n🧹 Cleared {} expired transaction{}.",
+                expired,
+                if expired == 1 { "" } else { "s" }
+            ));
+        }
+        send_message(msg, bot, text).await?;
+        return Ok(());
+    }
+
+    for (group_id, transaction) in active {
+        // Same callback as the Reject button, so the owner and expiry checks stay in one place
+        let keyboard = InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+            "❌ Cancel",
+            format!(
+                "pay_reject:{}:{}:{}",
+                user_id,
+                group_id.unwrap_or(0),
+                transaction.transaction_id
+            ),
+        )]]);
+        send_markdown_message_with_keyboard(
+            bot.clone(),
+            msg.clone(),
+            KeyboardMarkupType::InlineKeyboardType(keyboard),
+            &describe(&transaction, group_id, now),
+        )
+        .await?;
+    }
+    Ok(())
+}
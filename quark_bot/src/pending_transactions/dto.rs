@@ -18,4 +18,8 @@ pub struct PendingTransaction {
     pub expires_at: u64,                // Timestamp when transaction expires
     pub chat_id: i64,                   // Telegram chat ID where the message was sent
     pub message_id: i32,                // Telegram message ID of the transaction message
+    #[serde(default)]
+    pub auto_confirm: bool,             // Below the user's auto-confirm threshold, sent without confirmation
+    #[serde(default)]
+    pub memo: Option<String>,           // Optional note, stored off-chain by tx hash once sent
 }
\ No newline at end of file
@@ -81,6 +81,39 @@ impl PendingTransactions {
         Ok(())
     }
 
+    /// Every pending transaction a user has open, with the group it belongs to (`None` for DMs)
+    pub fn list_for_user(&self, user_id: i64) -> Vec<(Option<i64>, PendingTransaction)> {
+        self.tree
+            .scan_prefix(format!("{}:", user_id).as_bytes())
+            .filter_map(|kv| kv.ok())
+            .filter_map(|(key, value)| {
+                let key = String::from_utf8(key.to_vec()).ok()?;
+                let group_id = key.split_once(':')?.1.parse::<i64>().ok()?;
+                let transaction = serde_json::from_slice(&value).ok()?;
+                Some(((group_id != 0).then_some(group_id), transaction))
+            })
+            .collect()
+    }
+
+    /// Remove transactions that expired before `cutoff` (unix seconds); returns how many
+    pub fn purge_expired_before(&self, cutoff: u64) -> usize {
+        let stale: Vec<IVec> = self
+            .tree
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(_, v)| {
+                serde_json::from_slice::<PendingTransaction>(v)
+                    .map(|t| t.expires_at < cutoff)
+                    .unwrap_or(true)
+            })
+            .map(|(k, _)| k)
+            .collect();
+        for key in &stale {
+            let _ = self.tree.remove(key);
+        }
+        stale.len()
+    }
+
     /// Check if a transaction has expired
     pub fn is_expired(transaction: &PendingTransaction) -> bool {
         let now = std::time::SystemTime::now()
@@ -0,0 +1,97 @@
+//! Deployment profile from the `PROFILE` env var, read once and shared by every check.
+
+use std::env;
+use std::sync::OnceLock;
+
+use tracing::Level;
+
+static PROFILE: OnceLock<Profile> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Dev,
+    Staging,
+    Prod,
+}
+
+impl Profile {
+    /// Unknown or missing values fall back to Prod so nothing is accidentally free
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "dev" | "development" => Some(Profile::Dev),
+            "staging" | "stage" => Some(Profile::Staging),
+            "prod" | "production" => Some(Profile::Prod),
+            _ => None,
+        }
+    }
+
+    /// Whether AI usage is charged through the purchase endpoint
+    pub fn billing_enabled(&self) -> bool {
+        !matches!(self, Profile::Dev)
+    }
+
+    pub fn verbose_logging(&self) -> bool {
+        !matches!(self, Profile::Prod)
+    }
+
+    /// Skip the minimum-balance gate so flows can be tried with an unfunded account
+    pub fn test_shortcuts(&self) -> bool {
+        matches!(self, Profile::Dev)
+    }
+
+    pub fn log_level(&self) -> Level {
+        if self.verbose_logging() {
+            Level::DEBUG
+        } else {
+            Level::INFO
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Profile::Dev => "dev",
+            Profile::Staging => "staging",
+            Profile::Prod => "prod",
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        format!(
+            "{} (billing: {}, verbose logging: {}, test shortcuts: {})",
+            self.as_str(),
+            on_off(self.billing_enabled()),
+            on_off(self.verbose_logging()),
+            on_off(self.test_shortcuts())
+        )
+    }
+}
+
+/// The active profile; the env var is only read on first use
+pub fn current() -> Profile {
+    *PROFILE.get_or_init(|| {
+        let value = env::var("PROFILE").unwrap_or_default();
+        Profile::parse(&value).unwrap_or_else(|| {
+            if !value.is_empty() {
+                log::warn!("Unknown PROFILE {:?}, falling back to prod", value);
+            }
+            Profile::Prod
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(Profile::parse("dev"), Some(Profile::Dev));
+        assert_eq!(Profile::parse(" Staging "), Some(Profile::Staging));
+        assert_eq!(Profile::parse("production"), Some(Profile::Prod));
+        assert_eq!(Profile::parse("qa"), None);
+        assert!(!Profile::Dev.billing_enabled());
+        assert!(Profile::Staging.billing_enabled() && Profile::Staging.verbose_logging());
+        assert!(!Profile::Prod.verbose_logging() && !Profile::Prod.test_shortcuts());
+    }
+}
@@ -0,0 +1,131 @@
+//! Shared pacing for bulk Telegram sends (broadcasts, multi-recipient notifications).
+
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use teloxide::RequestError;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Telegram allows roughly 30 messages per second across all chats
+const GLOBAL_MESSAGES_PER_SECOND: u32 = 25;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+#[derive(Clone)]
+pub struct SendLimiter {
+    next_slot: Arc<Mutex<Instant>>,
+    interval: Duration,
+    max_retries: u32,
+}
+
+impl SendLimiter {
+    pub fn new(messages_per_second: u32, max_retries: u32) -> Self {
+        Self {
+            next_slot: Arc::new(Mutex::new(Instant::now())),
+            interval: Duration::from_secs(1) / messages_per_second.max(1),
+            max_retries,
+        }
+    }
+
+    /// Process-wide limiter so every bulk sender shares the same budget
+    pub fn global() -> &'static SendLimiter {
+        static GLOBAL: OnceLock<SendLimiter> = OnceLock::new();
+        GLOBAL.get_or_init(|| SendLimiter::new(GLOBAL_MESSAGES_PER_SECOND, DEFAULT_MAX_RETRIES))
+    }
+
+    /// Wait until this caller may send the next message
+    pub async fn wait_turn(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let now = Instant::now();
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Hold back every sender sharing this limiter, e.g. after a 429
+    async fn pause_for(&self, delay: Duration) {
+        let mut next_slot = self.next_slot.lock().await;
+        let resume_at = Instant::now() + delay;
+        if *next_slot < resume_at {
+            *next_slot = resume_at;
+        }
+    }
+
+    /// Run a Telegram request in turn, retrying on 429 after the server's Retry-After
+    pub async fn send<T, F, Fut>(&self, mut request: F) -> Result<T, RequestError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, RequestError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.wait_turn().await;
+
+            match request().await {
+                Err(RequestError::RetryAfter(retry_after)) if attempt < self.max_retries => {
+                    attempt += 1;
+                    let delay = retry_after.duration();
+                    log::warn!(
+                        "Telegram rate limit hit, retrying in {:?} (attempt {}/{})",
+                        delay,
+                        attempt,
+                        self.max_retries
+                    );
+                    self.pause_for(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use teloxide::types::Seconds;
+
+    #[tokio::test]
+    async fn test_retries_after_429_with_retry_after() {
+        let limiter = SendLimiter::new(100, 2);
+        let calls = AtomicU32::new(0);
+        let started = Instant::now();
+
+        let result = limiter
+            .send(|| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if call == 0 {
+                        Err(RequestError::RetryAfter(Seconds::from_seconds(1)))
+                    } else {
+                        Ok("sent")
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), "sent");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let limiter = SendLimiter::new(100, 0);
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), RequestError> = limiter
+            .send(|| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { Err(RequestError::RetryAfter(Seconds::from_seconds(1))) }
+            })
+            .await;
+
+        assert!(matches!(result, Err(RequestError::RetryAfter(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
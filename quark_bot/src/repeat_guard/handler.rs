@@ -0,0 +1,128 @@
+use anyhow::Result as AnyResult;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+};
+
+use crate::{
+    bot::handler::handle_chat_with_model, dependencies::BotDependencies,
+    repeat_guard::repeat_guard::PendingPrompt, user_model_preferences::dto::ChatModel,
+};
+
+/// Ask for confirmation instead of running a prompt the user just sent.
+///
+/// Records the prompt otherwise. Returns `true` when the prompt was held back; a confirmed
+/// rerun goes to `model` when one was picked.
+pub async fn hold_repeated_prompt(
+    bot: &Bot,
+    msg: &Message,
+    prompt: &str,
+    model: Option<ChatModel>,
+    bot_deps: &BotDependencies,
+) -> AnyResult<bool> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(false);
+    };
+    let user_id = user.id.0 as i64;
+    let now = chrono::Utc::now().timestamp();
+    let guard = &bot_deps.repeat_guard;
+
+    if !guard.is_repeat(user_id, msg.chat.id.0, prompt, now) {
+        if let Err(e) = guard.record(user_id, msg.chat.id.0, prompt, now) {
+            log::error!("Failed to record prompt for user {}: {}", user_id, e);
+        }
+        return Ok(false);
+    }
+
+    guard.set_pending(
+        user_id,
+        &PendingPrompt {
+            prompt: prompt.to_string(),
+            model,
+        },
+    )?;
+
+    let keyboard = InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("🔁 Run again", format!("repeat_run:{}", user_id)),
+        InlineKeyboardButton::callback("✖️ Cancel", format!("repeat_cancel:{}", user_id)),
+    ]]);
+
+    bot.send_message(
+        msg.chat.id,
+        "🔁 <b>You just asked this</b> — run it again?\n\n<i>Repeats are billed like any other request.</i>",
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_to(msg.id)
+    .reply_markup(keyboard)
+    .await?;
+
+    Ok(true)
+}
+
+/// Handle the "Run again" / "Cancel" buttons of the repeat confirmation
+pub async fn handle_repeat_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let data = query.data.clone().unwrap_or_default();
+    let (run, owner) = if let Some(id) = data.strip_prefix("repeat_run:") {
+        (true, id.parse::<i64>().ok())
+    } else {
+        (false, data.strip_prefix("repeat_cancel:").and_then(|id| id.parse::<i64>().ok()))
+    };
+
+    let Some(owner) = owner else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+
+    if query.from.id.0 as i64 != owner {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only the person who asked can confirm this")
+            .await?;
+        return Ok(());
+    }
+
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        bot.answer_callback_query(query.id).await?;
+        return Ok(());
+    };
+
+    let pending = bot_deps.repeat_guard.take_pending(owner);
+    let _ = bot.delete_message(m.chat.id, m.id).await;
+
+    if !run {
+        bot.answer_callback_query(query.id).text("Cancelled").await?;
+        return Ok(());
+    }
+
+    let (Some(pending), Some(original)) = (pending, m.reply_to_message()) else {
+        bot.answer_callback_query(query.id)
+            .text("❌ This request is no longer available")
+            .await?;
+        return Ok(());
+    };
+
+    bot.answer_callback_query(query.id).await?;
+
+    if let Err(e) =
+        bot_deps
+            .repeat_guard
+            .record(owner, original.chat.id.0, &pending.prompt, chrono::Utc::now().timestamp())
+    {
+        log::error!("Failed to record prompt for user {}: {}", owner, e);
+    }
+
+    handle_chat_with_model(
+        bot,
+        original.clone(),
+        pending.prompt,
+        None,
+        false,
+        pending.model,
+        bot_deps,
+    )
+    .await
+}
@@ -0,0 +1,4 @@
+pub mod handler;
+pub mod repeat_guard;
+
+pub use repeat_guard::RepeatGuard;
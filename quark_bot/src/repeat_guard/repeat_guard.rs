@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::hash::{Hash, Hasher};
+
+use crate::user_model_preferences::dto::ChatModel;
+
+const TREE_NAME: &str = "repeat_guard";
+const DEFAULT_WINDOW_SECS: i64 = 60;
+
+fn last_key(user_id: i64) -> String {
+    format!("last:{}", user_id)
+}
+
+fn pending_key(user_id: i64) -> String {
+    format!("pending:{}", user_id)
+}
+
+fn disabled_key(user_id: i64) -> String {
+    format!("disabled:{}", user_id)
+}
+
+/// Hash of the prompt with case and whitespace differences ignored
+pub fn prompt_hash(prompt: &str) -> u64 {
+    let normalized = prompt
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastPrompt {
+    chat_id: i64,
+    hash: u64,
+    at: i64,
+}
+
+/// A held-back prompt, with the model it was sent to when one was picked (`/c:alias`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingPrompt {
+    pub prompt: String,
+    #[serde(default)]
+    pub model: Option<ChatModel>,
+}
+
+/// Catches the same /c prompt sent twice in a short window so it isn't billed twice
+#[derive(Clone)]
+pub struct RepeatGuard {
+    tree: Tree,
+    window_secs: i64,
+}
+
+impl RepeatGuard {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        let window_secs = env::var("REPEAT_PROMPT_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_WINDOW_SECS)
+            .max(0);
+        Ok(Self { tree, window_secs })
+    }
+
+    pub fn is_enabled_for(&self, user_id: i64) -> bool {
+        self.window_secs > 0 && !matches!(self.tree.get(disabled_key(user_id)), Ok(Some(_)))
+    }
+
+    pub fn set_enabled_for(&self, user_id: i64, enabled: bool) -> sled::Result<()> {
+        if enabled {
+            self.tree.remove(disabled_key(user_id))?;
+        } else {
+            self.tree.insert(disabled_key(user_id), vec![1u8])?;
+        }
+        Ok(())
+    }
+
+    /// Whether this prompt matches the user's previous one in the same chat within the window
+    pub fn is_repeat(&self, user_id: i64, chat_id: i64, prompt: &str, now: i64) -> bool {
+        if !self.is_enabled_for(user_id) {
+            return false;
+        }
+        let Ok(Some(raw)) = self.tree.get(last_key(user_id)) else {
+            return false;
+        };
+        match serde_json::from_slice::<LastPrompt>(&raw) {
+            Ok(last) => {
+                last.chat_id == chat_id
+                    && last.hash == prompt_hash(prompt)
+                    && now - last.at <= self.window_secs
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn record(&self, user_id: i64, chat_id: i64, prompt: &str, now: i64) -> anyhow::Result<()> {
+        let last = LastPrompt {
+            chat_id,
+            hash: prompt_hash(prompt),
+            at: now,
+        };
+        self.tree
+            .insert(last_key(user_id), serde_json::to_vec(&last)?)?;
+        Ok(())
+    }
+
+    /// Keep the repeated prompt until the user confirms it
+    pub fn set_pending(&self, user_id: i64, pending: &PendingPrompt) -> anyhow::Result<()> {
+        self.tree
+            .insert(pending_key(user_id), serde_json::to_vec(pending)?)?;
+        Ok(())
+    }
+
+    /// The held-back prompt; entries stored as plain text before models were kept still load
+    pub fn take_pending(&self, user_id: i64) -> Option<PendingPrompt> {
+        match self.tree.remove(pending_key(user_id)) {
+            Ok(Some(raw)) => serde_json::from_slice::<PendingPrompt>(&raw).ok().or_else(|| {
+                String::from_utf8(raw.to_vec())
+                    .ok()
+                    .map(|prompt| PendingPrompt { prompt, model: None })
+            }),
+            _ => None,
+        }
+    }
+}
@@ -4,7 +4,10 @@ use teloxide::{prelude::*, types::InlineKeyboardMarkup};
 
 use crate::dependencies::BotDependencies;
 use crate::scheduled_payments::dto::PendingPaymentStep;
-use crate::scheduled_payments::helpers::{build_repeat_keyboard_payments, summarize};
+use crate::scheduled_payments::helpers::{
+    build_repeat_keyboard_payments, schedule_list_entry, summarize, summarize_schedule,
+};
+use crate::scheduled_payments::runner::{PaymentRunOutcome, execute_payment};
 use crate::scheduled_prompts::dto::RepeatPolicy;
 
 pub async fn handle_scheduled_payments_callback(
@@ -24,6 +27,11 @@ pub async fn handle_scheduled_payments_callback(
         }
     };
 
+    // Failure notices usually land in the creator's DM, where there are no admins to check
+    if data.starts_with("schedpay_retry:") {
+        return handle_retry_callback(&bot, &query, message, &bot_deps).await;
+    }
+
     // Admin-only actions
     let admins = bot.get_chat_administrators(message.chat.id).await?;
     if !admins.iter().any(|m| m.user.id == user.id) {
@@ -63,6 +71,7 @@ pub async fn handle_scheduled_payments_callback(
             "1w" => (RepeatPolicy::Weekly, Some(1)),
             "2w" => (RepeatPolicy::Weekly, Some(2)),
             "4w" => (RepeatPolicy::Weekly, Some(4)),
+            "1mo" => (RepeatPolicy::Monthly, None),
             _ => (RepeatPolicy::Weekly, Some(1)),
         };
         if let Some(mut st) = bot_deps.scheduled_payments.get_pending(key) {
@@ -159,7 +168,7 @@ pub async fn handle_scheduled_payments_callback(
         }
     } else if data.starts_with("schedpay_delete:") {
         let id = data.split(':').nth(1).unwrap_or("");
-        if let Some(mut rec) = bot_deps.scheduled_payments.get_schedule(id) {
+        if let Some(rec) = bot_deps.scheduled_payments.get_schedule(id) {
             // Only the creator can delete their own scheduled payment
             if rec.creator_user_id != user.id.0 as i64 {
                 bot.answer_callback_query(query.id)
@@ -167,8 +176,60 @@ pub async fn handle_scheduled_payments_callback(
                     .await?;
                 return Ok(());
             }
-            rec.active = false;
-            let _ = bot_deps.scheduled_payments.put_schedule(&rec);
+            // Echo the whole schedule first so a mis-tap doesn't delete the wrong one
+            let kb = InlineKeyboardMarkup::new(vec![vec![
+                teloxide::types::InlineKeyboardButton::callback(
+                    "🗑 Yes, delete",
+                    format!("schedpay_confirm_delete:{}", rec.id),
+                ),
+                teloxide::types::InlineKeyboardButton::callback(
+                    "↩️ No, keep",
+                    format!("schedpay_keep:{}", rec.id),
+                ),
+            ]]);
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format!(
+                    "⚠️ Delete this scheduled payment permanently?\n\n{}",
+                    summarize_schedule(&rec)
+                ),
+            )
+            .reply_markup(kb)
+            .await?;
+        } else {
+            // Schedule not found - still respond to prevent UI hang
+            bot.answer_callback_query(query.id)
+                .text("ℹ️ Scheduled payment not found")
+                .await?;
+        }
+    } else if data.starts_with("schedpay_keep:") {
+        let id = data.split(':').nth(1).unwrap_or("");
+        if let Some(rec) = bot_deps.scheduled_payments.get_schedule(id) {
+            let (title, kb) = schedule_list_entry(&rec);
+            bot.answer_callback_query(query.id).text("Kept").await?;
+            let _ = bot
+                .edit_message_text(message.chat.id, message.id, title)
+                .reply_markup(kb)
+                .await;
+        } else {
+            bot.answer_callback_query(query.id)
+                .text("ℹ️ Scheduled payment not found")
+                .await?;
+            let _ = bot.delete_message(message.chat.id, message.id).await;
+        }
+    } else if data.starts_with("schedpay_confirm_delete:") {
+        let id = data.split(':').nth(1).unwrap_or("");
+        if let Some(rec) = bot_deps.scheduled_payments.get_schedule(id) {
+            if rec.creator_user_id != user.id.0 as i64 {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Only the creator can delete this payment")
+                    .await?;
+                return Ok(());
+            }
+            // Remove outright so deleted schedules aren't confused with paused ones
+            let _ = bot_deps.scheduled_payments.delete_schedule(&rec.id);
             bot.answer_callback_query(query.id)
                 .text("🗑 Deleted")
                 .await?;
@@ -176,10 +237,17 @@ pub async fn handle_scheduled_payments_callback(
                 let _ = bot.delete_message(m.chat.id, m.id).await;
             }
         } else {
-            // Schedule not found - still respond to prevent UI hang
+            // Removed in the meantime (e.g. a one-shot that already ran): retire the prompt
             bot.answer_callback_query(query.id)
-                .text("ℹ️ Scheduled payment not found")
+                .text("ℹ️ Already removed")
                 .await?;
+            let _ = bot
+                .edit_message_text(
+                    message.chat.id,
+                    message.id,
+                    "ℹ️ This scheduled payment no longer exists.",
+                )
+                .await;
         }
     } else if data.starts_with("schedpay_edit:") {
         // Creator-only edit: present submenu and open scoped wizard
@@ -204,7 +272,9 @@ pub async fn handle_scheduled_payments_callback(
                 decimals: rec.decimals,
                 amount_display: rec
                     .amount_smallest_units
+                    .filter(|_| rec.usd_amount.is_none())
                     .and_then(|v| rec.decimals.map(|d| v as f64 / 10f64.powi(d as i32))),
+                usd_amount: rec.usd_amount,
                 date: rec.start_timestamp_utc.and_then(|ts| {
                     chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)
                         .map(|dt| dt.format("%Y-%m-%d").to_string())
@@ -218,6 +288,7 @@ pub async fn handle_scheduled_payments_callback(
                 }),
                 repeat: Some(rec.repeat.clone()),
                 weekly_weeks: rec.weekly_weeks,
+                updated_at: Utc::now().timestamp(),
             };
             bot_deps
                 .scheduled_payments
@@ -281,7 +352,7 @@ pub async fn handle_scheduled_payments_callback(
                 bot_deps.scheduled_payments.put_pending(key, &st)?;
             }
             if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(m)) = &query.message {
-                bot.edit_message_text(m.chat.id, m.id, "💰 Send new amount")
+                bot.edit_message_text(m.chat.id, m.id, "💰 Send new amount, or a USD value like $25")
                     .await?;
             }
             bot.answer_callback_query(query.id).await?;
@@ -337,6 +408,51 @@ pub async fn handle_scheduled_payments_callback(
                 .text("ℹ️ Scheduled payment not found")
                 .await?;
         }
+    } else if data.starts_with("schedpay_runnote:") {
+        let id = data.split(':').nth(1).unwrap_or("");
+        if let Some(rec) = bot_deps.scheduled_payments.get_schedule(id) {
+            // Only the creator can run their own scheduled payment immediately
+            if rec.creator_user_id != user.id.0 as i64 {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Only the creator can run this payment")
+                    .await?;
+                return Ok(());
+            }
+            let st = crate::scheduled_payments::dto::PendingPaymentWizardState {
+                group_id: rec.group_id,
+                creator_user_id: rec.creator_user_id,
+                creator_username: rec.creator_username.clone(),
+                step: PendingPaymentStep::AwaitingRunNote,
+                schedule_id: Some(rec.id.clone()),
+                recipient_username: None,
+                recipient_address: None,
+                symbol: None,
+                token_type: None,
+                decimals: None,
+                amount_display: None,
+                usd_amount: None,
+                date: None,
+                hour_utc: None,
+                minute_utc: None,
+                repeat: None,
+                weekly_weeks: None,
+                updated_at: Utc::now().timestamp(),
+            };
+            bot_deps
+                .scheduled_payments
+                .put_pending((&st.group_id, &st.creator_user_id), &st)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.send_message(
+                message.chat.id,
+                "📝 Send a note for this payment (e.g. \"Bonus payout: Q3 results\"), or send 'skip' to run without one.",
+            )
+            .await?;
+        } else {
+            // Schedule not found - still respond to prevent UI hang
+            bot.answer_callback_query(query.id)
+                .text("ℹ️ Scheduled payment not found")
+                .await?;
+        }
     } else if data.starts_with("schedpay_close:") {
         let id = data.split(':').nth(1).unwrap_or("");
         if !id.is_empty() {
@@ -371,3 +487,77 @@ pub async fn handle_scheduled_payments_callback(
 
     Ok(())
 }
+
+/// `schedpay_retry:<id>:<failed_at>` — re-run a failed execution now, unless it has been
+/// dealt with since the failure notice was sent
+async fn handle_retry_callback(
+    bot: &Bot,
+    query: &teloxide::types::CallbackQuery,
+    message: &Message,
+    bot_deps: &BotDependencies,
+) -> Result<()> {
+    let data = query.data.as_deref().unwrap_or("");
+    let mut parts = data.split(':').skip(1);
+    let id = parts.next().unwrap_or("");
+    let failed_at = parts.next().and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+
+    let Some(mut rec) = bot_deps.scheduled_payments.get_schedule(id) else {
+        bot.answer_callback_query(query.id.clone())
+            .text("ℹ️ Scheduled payment not found")
+            .await?;
+        return Ok(());
+    };
+    if rec.creator_user_id != query.from.id.0 as i64 {
+        bot.answer_callback_query(query.id.clone())
+            .text("❌ Only the creator can retry this payment")
+            .await?;
+        return Ok(());
+    }
+
+    let now_ts = Utc::now().timestamp();
+    if rec.locked_until.is_some_and(|lock| now_ts < lock) {
+        bot.answer_callback_query(query.id.clone())
+            .text("⏳ This payment is running right now")
+            .await?;
+        return Ok(());
+    }
+    // The runner keeps retrying a failed run every minute, so it may have gone through already
+    let succeeded_since = rec.last_run_at.is_some_and(|at| at >= failed_at);
+    if succeeded_since || rec.last_attempt_status.as_deref() != Some("failure") {
+        bot.answer_callback_query(query.id.clone())
+            .text("✅ Nothing to retry — this payment has run since")
+            .await?;
+        let _ = bot
+            .edit_message_reply_markup(message.chat.id, message.id)
+            .await;
+        return Ok(());
+    }
+    if !rec.active {
+        bot.answer_callback_query(query.id.clone())
+            .text("⏸ This payment is paused; resume it first")
+            .await?;
+        return Ok(());
+    }
+
+    rec.locked_until = Some(now_ts + 120);
+    bot_deps.scheduled_payments.put_schedule(&rec)?;
+    bot.answer_callback_query(query.id.clone())
+        .text("🔁 Retrying…")
+        .await?;
+
+    let outcome = match execute_payment(bot, bot_deps, rec).await {
+        PaymentRunOutcome::Sent { hash } => {
+            format!("✅ Retry succeeded\nSchedule: {}\nTx: {}", id, hash)
+        }
+        PaymentRunOutcome::Skipped => format!(
+            "⚠️ Retry skipped: no price to convert the USD amount\nSchedule: {}",
+            id
+        ),
+        PaymentRunOutcome::Failed(e) => {
+            format!("❌ Retry failed: {}\nSchedule: {}", e, id)
+        }
+    };
+    bot.edit_message_text(message.chat.id, message.id, outcome)
+        .await?;
+    Ok(())
+}
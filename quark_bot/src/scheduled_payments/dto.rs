@@ -12,6 +12,7 @@ pub enum PendingPaymentStep {
     AwaitingMinute,
     AwaitingRepeat,
     AwaitingConfirm,
+    AwaitingRunNote,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, bincode::Encode, bincode::Decode)]
@@ -41,6 +42,136 @@ pub struct ScheduledPaymentRecord {
     pub last_attempt_status: Option<String>,
     pub notify_on_success: bool,
     pub notify_on_failure: bool,
+    // Optional note attached to a manual "Run now"; consumed by the next successful run
+    pub pending_run_note: Option<String>,
+    // USD-pegged schedules: target value per run, converted to the token at the live price.
+    // `amount_smallest_units` then holds the amount computed for the latest run.
+    pub usd_amount: Option<f64>,
+}
+
+/// Record layout before USD-pegged amounts were added
+#[derive(Clone, Debug, bincode::Decode)]
+pub struct NotedScheduledPaymentRecord {
+    pub id: String,
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub recipient_username: Option<String>,
+    pub recipient_address: Option<String>,
+    pub symbol: Option<String>,
+    pub token_type: Option<String>,
+    pub decimals: Option<u8>,
+    pub amount_smallest_units: Option<u64>,
+    pub start_timestamp_utc: Option<i64>,
+    pub repeat: RepeatPolicy,
+    pub weekly_weeks: Option<u8>,
+    pub active: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub run_count: u64,
+    pub locked_until: Option<i64>,
+    pub scheduler_job_id: Option<String>,
+    pub last_error: Option<String>,
+    pub last_attempt_status: Option<String>,
+    pub notify_on_success: bool,
+    pub notify_on_failure: bool,
+    pub pending_run_note: Option<String>,
+}
+
+impl From<NotedScheduledPaymentRecord> for ScheduledPaymentRecord {
+    fn from(legacy: NotedScheduledPaymentRecord) -> Self {
+        Self {
+            id: legacy.id,
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            recipient_username: legacy.recipient_username,
+            recipient_address: legacy.recipient_address,
+            symbol: legacy.symbol,
+            token_type: legacy.token_type,
+            decimals: legacy.decimals,
+            amount_smallest_units: legacy.amount_smallest_units,
+            start_timestamp_utc: legacy.start_timestamp_utc,
+            repeat: legacy.repeat,
+            weekly_weeks: legacy.weekly_weeks,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            last_run_at: legacy.last_run_at,
+            next_run_at: legacy.next_run_at,
+            run_count: legacy.run_count,
+            locked_until: legacy.locked_until,
+            scheduler_job_id: legacy.scheduler_job_id,
+            last_error: legacy.last_error,
+            last_attempt_status: legacy.last_attempt_status,
+            notify_on_success: legacy.notify_on_success,
+            notify_on_failure: legacy.notify_on_failure,
+            pending_run_note: legacy.pending_run_note,
+            usd_amount: None,
+        }
+    }
+}
+
+/// Record layout before run notes were added
+#[derive(Clone, Debug, bincode::Decode)]
+pub struct LegacyScheduledPaymentRecord {
+    pub id: String,
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub recipient_username: Option<String>,
+    pub recipient_address: Option<String>,
+    pub symbol: Option<String>,
+    pub token_type: Option<String>,
+    pub decimals: Option<u8>,
+    pub amount_smallest_units: Option<u64>,
+    pub start_timestamp_utc: Option<i64>,
+    pub repeat: RepeatPolicy,
+    pub weekly_weeks: Option<u8>,
+    pub active: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub run_count: u64,
+    pub locked_until: Option<i64>,
+    pub scheduler_job_id: Option<String>,
+    pub last_error: Option<String>,
+    pub last_attempt_status: Option<String>,
+    pub notify_on_success: bool,
+    pub notify_on_failure: bool,
+}
+
+impl From<LegacyScheduledPaymentRecord> for ScheduledPaymentRecord {
+    fn from(legacy: LegacyScheduledPaymentRecord) -> Self {
+        Self {
+            id: legacy.id,
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            recipient_username: legacy.recipient_username,
+            recipient_address: legacy.recipient_address,
+            symbol: legacy.symbol,
+            token_type: legacy.token_type,
+            decimals: legacy.decimals,
+            amount_smallest_units: legacy.amount_smallest_units,
+            start_timestamp_utc: legacy.start_timestamp_utc,
+            repeat: legacy.repeat,
+            weekly_weeks: legacy.weekly_weeks,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            last_run_at: legacy.last_run_at,
+            next_run_at: legacy.next_run_at,
+            run_count: legacy.run_count,
+            locked_until: legacy.locked_until,
+            scheduler_job_id: legacy.scheduler_job_id,
+            last_error: legacy.last_error,
+            last_attempt_status: legacy.last_attempt_status,
+            notify_on_success: legacy.notify_on_success,
+            notify_on_failure: legacy.notify_on_failure,
+            pending_run_note: None,
+            usd_amount: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, bincode::Encode, bincode::Decode)]
@@ -56,6 +187,33 @@ pub struct PendingPaymentWizardState {
     pub token_type: Option<String>,
     pub decimals: Option<u8>,
     pub amount_display: Option<f64>,
+    // Set instead of `amount_display` for USD-pegged schedules
+    pub usd_amount: Option<f64>,
+    pub date: Option<String>,
+    pub hour_utc: Option<u8>,
+    pub minute_utc: Option<u8>,
+    pub repeat: Option<RepeatPolicy>,
+    pub weekly_weeks: Option<u8>,
+    /// Last time the wizard was saved, so abandoned ones can be cleared
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+/// Wizard state layout before `updated_at` was added; decoded as a fallback
+#[derive(Clone, Debug, bincode::Decode)]
+pub struct LegacyPendingPaymentWizardState {
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub step: PendingPaymentStep,
+    pub schedule_id: Option<String>,
+    pub recipient_username: Option<String>,
+    pub recipient_address: Option<String>,
+    pub symbol: Option<String>,
+    pub token_type: Option<String>,
+    pub decimals: Option<u8>,
+    pub amount_display: Option<f64>,
+    pub usd_amount: Option<f64>,
     pub date: Option<String>,
     pub hour_utc: Option<u8>,
     pub minute_utc: Option<u8>,
@@ -63,4 +221,29 @@ pub struct PendingPaymentWizardState {
     pub weekly_weeks: Option<u8>,
 }
 
+impl From<LegacyPendingPaymentWizardState> for PendingPaymentWizardState {
+    fn from(legacy: LegacyPendingPaymentWizardState) -> Self {
+        Self {
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            step: legacy.step,
+            schedule_id: legacy.schedule_id,
+            recipient_username: legacy.recipient_username,
+            recipient_address: legacy.recipient_address,
+            symbol: legacy.symbol,
+            token_type: legacy.token_type,
+            decimals: legacy.decimals,
+            amount_display: legacy.amount_display,
+            usd_amount: legacy.usd_amount,
+            date: legacy.date,
+            hour_utc: legacy.hour_utc,
+            minute_utc: legacy.minute_utc,
+            repeat: legacy.repeat,
+            weekly_weeks: legacy.weekly_weeks,
+            updated_at: 0,
+        }
+    }
+}
+
 
@@ -5,9 +5,12 @@ use crate::dependencies::BotDependencies;
 use crate::scheduled_payments::dto::{
     PendingPaymentStep, PendingPaymentWizardState, ScheduledPaymentRecord,
 };
+use crate::scheduled_payments::helpers::{
+    PaymentAmount, parse_payment_amount, schedule_list_entry, schedules_to_csv,
+};
 use crate::utils::{KeyboardMarkupType, send_markdown_message_with_keyboard, send_message};
 use chrono::Utc;
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, User};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, InputFile, User};
 use uuid::Uuid;
 
 pub async fn handle_schedulepayment_command(
@@ -65,11 +68,13 @@ pub async fn handle_schedulepayment_command(
         token_type: None,
         decimals: None,
         amount_display: None,
+        usd_amount: None,
         date: None,
         hour_utc: None,
         minute_utc: None,
         repeat: None,
         weekly_weeks: None,
+        updated_at: Utc::now().timestamp(),
     };
 
     bot_deps
@@ -122,39 +127,7 @@ pub async fn handle_listscheduledpayments_command(
     }
 
     for rec in list {
-        let smallest = rec.amount_smallest_units.unwrap_or(0);
-        let decimals = rec.decimals.unwrap_or(8);
-        let human = (smallest as f64) / 10f64.powi(decimals as i32);
-        let title = format!(
-            "⏰ {:>11} — @{} — {:.4} {}",
-            rec.next_run_at
-                .map(|v| chrono::DateTime::<chrono::Utc>::from_timestamp(v, 0)
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
-                    .unwrap_or_else(|| v.to_string()))
-                .unwrap_or_else(|| "n/a".to_string()),
-            rec.recipient_username.clone().unwrap_or_default(),
-            human,
-            rec.symbol.clone().unwrap_or_default(),
-        );
-        let toggle_label = if rec.active {
-            "⏸ Pause"
-        } else {
-            "▶️ Resume"
-        };
-        let kb = InlineKeyboardMarkup::new(vec![
-            vec![
-                InlineKeyboardButton::callback("✏️ Edit", format!("schedpay_edit:{}", rec.id)),
-                InlineKeyboardButton::callback(toggle_label, format!("schedpay_toggle:{}", rec.id)),
-            ],
-            vec![
-                InlineKeyboardButton::callback("⚡ Run now", format!("schedpay_runnow:{}", rec.id)),
-                InlineKeyboardButton::callback("🗑 Delete", format!("schedpay_delete:{}", rec.id)),
-            ],
-            vec![InlineKeyboardButton::callback(
-                "↩️ Close",
-                format!("schedpay_close:{}", rec.id),
-            )],
-        ]);
+        let (title, kb) = schedule_list_entry(&rec);
         send_markdown_message_with_keyboard(
             bot.clone(),
             msg.clone(),
@@ -167,6 +140,61 @@ pub async fn handle_listscheduledpayments_command(
     Ok(())
 }
 
+/// Send the group's scheduled payments (active and paused) as a CSV file
+pub async fn handle_export_scheduled_payments_command(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let admins = bot.get_chat_administrators(msg.chat.id).await?;
+    let user = match msg.from.clone() {
+        Some(u) => u,
+        None => return Ok(()),
+    };
+    if !admins.iter().any(|m| m.user.id == user.id) {
+        send_message(
+            msg,
+            bot,
+            "❌ Only administrators can use this command.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let records = bot_deps
+        .scheduled_payments
+        .list_all_schedules_for_group(msg.chat.id.0);
+    if records.is_empty() {
+        send_message(
+            msg,
+            bot,
+            "📭 This group has no scheduled payments to export.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let csv = schedules_to_csv(&records);
+    let file_name = format!(
+        "scheduled_payments_{}.csv",
+        Utc::now().format("%Y-%m-%d")
+    );
+    let active = records.iter().filter(|r| r.active).count();
+    bot.send_document(
+        msg.chat.id,
+        InputFile::memory(csv.into_bytes()).file_name(file_name),
+    )
+    .caption(format!(
+        "📄 {} scheduled payments ({} active, {} paused)",
+        records.len(),
+        active,
+        records.len() - active
+    ))
+    .await?;
+
+    Ok(())
+}
+
 pub async fn finalize_and_register_payment(
     msg: Message,
     bot: Bot,
@@ -199,10 +227,15 @@ pub async fn finalize_and_register_payment(
         })
         .unwrap_or(Utc::now().timestamp());
 
-    // Convert display amount to smallest units using decimals
-    let amount_smallest_units = state
-        .amount_display
-        .and_then(|amt| state.decimals.map(|d| (amt * 10f64.powi(d as i32)) as u64));
+    // Convert display amount to smallest units using decimals; USD-pegged schedules
+    // get theirs computed from the live price at each run
+    let amount_smallest_units = if state.usd_amount.is_some() {
+        None
+    } else {
+        state
+            .amount_display
+            .and_then(|amt| state.decimals.map(|d| (amt * 10f64.powi(d as i32)) as u64))
+    };
 
     // Upsert: if editing an existing schedule, reuse its id and preserve job id if present
     let id = state
@@ -237,6 +270,8 @@ pub async fn finalize_and_register_payment(
         last_attempt_status: None,
         notify_on_success: true,
         notify_on_failure: true,
+        pending_run_note: None,
+        usd_amount: state.usd_amount,
     };
 
     bot_deps.scheduled_payments.put_schedule(&rec)?;
@@ -343,14 +378,29 @@ pub async fn handle_message_scheduled_payments(
                 st.decimals = Some(decimals);
                 st.step = crate::scheduled_payments::dto::PendingPaymentStep::AwaitingAmount;
                 bot_deps.scheduled_payments.put_pending(pay_key, &st)?;
-                send_message(msg, bot, "💰 Send amount (decimal)".to_string()).await?;
+                send_message(
+                    msg,
+                    bot,
+                    "💰 Send amount (decimal), or a USD value like $25 to convert at each run's price"
+                        .to_string(),
+                )
+                .await?;
                 return Ok(true);
             }
             crate::scheduled_payments::dto::PendingPaymentStep::AwaitingAmount => {
                 let parsed = text_raw.replace('_', "").replace(',', "");
-                match parsed.parse::<f64>() {
-                    Ok(v) if v > 0.0 => {
-                        st.amount_display = Some(v);
+                match parse_payment_amount(&parsed) {
+                    Some(amount) => {
+                        match amount {
+                            PaymentAmount::Token(v) => {
+                                st.amount_display = Some(v);
+                                st.usd_amount = None;
+                            }
+                            PaymentAmount::Usd(v) => {
+                                st.usd_amount = Some(v);
+                                st.amount_display = None;
+                            }
+                        }
                         st.step = crate::scheduled_payments::dto::PendingPaymentStep::AwaitingDate;
                         bot_deps.scheduled_payments.put_pending(pay_key, &st)?;
                         send_message(
@@ -360,11 +410,12 @@ pub async fn handle_message_scheduled_payments(
                         )
                         .await?;
                     }
-                    _ => {
+                    None => {
                         send_message(
                             msg,
                             bot,
-                            "❌ Invalid amount. Please send a positive number.".to_string(),
+                            "❌ Invalid amount. Please send a positive number, or a USD value like $25."
+                                .to_string(),
                         )
                         .await?;
                     }
@@ -389,6 +440,39 @@ pub async fn handle_message_scheduled_payments(
                 }
                 return Ok(true);
             }
+            crate::scheduled_payments::dto::PendingPaymentStep::AwaitingRunNote => {
+                let schedule_id = st.schedule_id.clone().unwrap_or_default();
+                bot_deps.scheduled_payments.delete_pending(pay_key)?;
+                let mut rec = match bot_deps.scheduled_payments.get_schedule(&schedule_id) {
+                    Some(rec) => rec,
+                    None => {
+                        send_message(
+                            msg,
+                            bot,
+                            "ℹ️ Scheduled payment not found".to_string(),
+                        )
+                        .await?;
+                        return Ok(true);
+                    }
+                };
+                let note = if text_raw.eq_ignore_ascii_case("skip") {
+                    None
+                } else {
+                    Some(text_raw.chars().take(200).collect::<String>())
+                };
+                log::info!(
+                    "Scheduled payment {} queued to run now by user {} with note: {}",
+                    rec.id,
+                    user.id.0,
+                    note.as_deref().unwrap_or("(none)")
+                );
+                rec.pending_run_note = note;
+                // Set due now and let runner pick it up on next tick
+                rec.next_run_at = Some(Utc::now().timestamp());
+                bot_deps.scheduled_payments.put_schedule(&rec)?;
+                send_message(msg, bot, "⚡ Queued to run".to_string()).await?;
+                return Ok(true);
+            }
             crate::scheduled_payments::dto::PendingPaymentStep::AwaitingConfirm => {
                 // Support 'skip' to keep existing values during edit flow
                 if text_raw.eq_ignore_ascii_case("skip") {
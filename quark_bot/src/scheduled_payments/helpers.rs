@@ -1,6 +1,6 @@
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 
-use crate::scheduled_payments::dto::PendingPaymentWizardState;
+use crate::scheduled_payments::dto::{PendingPaymentWizardState, ScheduledPaymentRecord};
 use crate::scheduled_prompts::dto::RepeatPolicy;
 
 pub fn build_repeat_keyboard_payments() -> InlineKeyboardMarkup {
@@ -23,6 +23,10 @@ pub fn build_repeat_keyboard_payments() -> InlineKeyboardMarkup {
                 "schedpay_repeat:4w".to_string(),
             ),
         ],
+        vec![InlineKeyboardButton::callback(
+            "Monthly".to_string(),
+            "schedpay_repeat:1mo".to_string(),
+        )],
     ];
     InlineKeyboardMarkup::new(rows)
 }
@@ -73,10 +77,11 @@ pub fn summarize(state: &PendingPaymentWizardState) -> String {
         .map(|u| format!("@{}", u))
         .unwrap_or("(recipient not set)".to_string());
     let symbol = state.symbol.as_deref().unwrap_or("(symbol not set)");
-    let amount = state
-        .amount_display
-        .map(|v| format!("{:.4}", v))
-        .unwrap_or("(amount not set)".to_string());
+    let amount = match (state.usd_amount, state.amount_display) {
+        (Some(usd), _) => format!("${:.2} worth of", usd),
+        (None, Some(v)) => format!("{:.4}", v),
+        (None, None) => "(amount not set)".to_string(),
+    };
     let date = state.date.clone().unwrap_or("(date not set)".to_string());
     let hour = state
         .hour_utc
@@ -93,11 +98,268 @@ pub fn summarize(state: &PendingPaymentWizardState) -> String {
         (Some(RepeatPolicy::Weekly), Some(4)) => "4-Weekly / 4w".to_string(),
         (Some(RepeatPolicy::Weekly), Some(w)) => format!("Every {}w", w),
         (Some(RepeatPolicy::Weekly), None) => "Weekly".to_string(),
+        (Some(RepeatPolicy::Monthly), _) => "Monthly (same day each month)".to_string(),
         (Some(_), _) => "(unsupported)".to_string(),
         (None, _) => "(not set)".to_string(),
     };
+    let pegged_note = if state.usd_amount.is_some() {
+        "\n(converted at the live price on every run)"
+    } else {
+        ""
+    };
     format!(
-        "💸 Payment schedule (UTC)\nRecipient: {}\nAmount: {} {}\nFirst run: {} {}:{}\nRepeat: {}",
-        recipient, amount, symbol, date, hour, minute, repeat
+        "💸 Payment schedule (UTC)\nRecipient: {}\nAmount: {} {}{}\nFirst run: {} {}:{}\nRepeat: {}",
+        recipient, amount, symbol, pegged_note, date, hour, minute, repeat
     )
 }
+
+#[derive(Debug, PartialEq)]
+pub enum PaymentAmount {
+    Token(f64),
+    Usd(f64),
+}
+
+/// Parse the wizard's amount reply: "1.5" is a token amount, "$25" or "25 usd" pegs to USD
+pub fn parse_payment_amount(input: &str) -> Option<PaymentAmount> {
+    let text = input.trim().to_lowercase();
+    let (number, usd) = if let Some(rest) = text.strip_prefix('$') {
+        (rest, true)
+    } else if let Some(rest) = text.strip_suffix("usd") {
+        (rest, true)
+    } else {
+        (text.as_str(), false)
+    };
+    let value = number.trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v > 0.0)?;
+    Some(if usd {
+        PaymentAmount::Usd(value)
+    } else {
+        PaymentAmount::Token(value)
+    })
+}
+
+/// Token amount in smallest units worth `usd` at `price`; `None` when the price is unusable
+pub fn usd_to_smallest_units(usd: f64, price: f64, decimals: u8) -> Option<u64> {
+    if !price.is_finite() || price <= 0.0 {
+        return None;
+    }
+    let units = (usd / price * 10f64.powi(decimals as i32)).floor();
+    (units >= 1.0 && units < u64::MAX as f64).then_some(units as u64)
+}
+
+fn repeat_label(repeat: &RepeatPolicy, weekly_weeks: Option<u8>) -> String {
+    match (repeat, weekly_weeks) {
+        (RepeatPolicy::Daily, _) => "Daily".to_string(),
+        (RepeatPolicy::Weekly, Some(1) | None) => "Weekly".to_string(),
+        (RepeatPolicy::Weekly, Some(w)) => format!("Every {} weeks", w),
+        (RepeatPolicy::Monthly, _) => "Monthly".to_string(),
+        (other, _) => format!("{:?}", other),
+    }
+}
+
+/// Amount as stored on the schedule, in the token's own decimals
+fn format_schedule_amount(rec: &ScheduledPaymentRecord) -> String {
+    let symbol = rec.symbol.as_deref().unwrap_or("");
+    match rec.usd_amount {
+        Some(usd) => format!("${:.2} worth of {} (converted at the live price)", usd, symbol),
+        None => {
+            let smallest = rec.amount_smallest_units.unwrap_or(0);
+            let decimals = rec.decimals.unwrap_or(8) as i32;
+            format!("{} {}", smallest as f64 / 10f64.powi(decimals), symbol)
+        }
+    }
+}
+
+/// Full description of a saved schedule, shown before it is deleted
+pub fn summarize_schedule(rec: &ScheduledPaymentRecord) -> String {
+    let recipient = match (&rec.recipient_username, &rec.recipient_address) {
+        (Some(username), _) => format!("@{}", username),
+        (None, Some(address)) => address.clone(),
+        (None, None) => "(unknown)".to_string(),
+    };
+    let next_run = if rec.active {
+        csv_timestamp(rec.next_run_at)
+    } else {
+        "paused".to_string()
+    };
+    format!(
+        "👤 Recipient: {}\n💳 Token: {}\n💰 Amount: {}\n⏰ Next run: {}\n🔁 Repeat: {}\n🔢 Executions so far: {}",
+        recipient,
+        rec.symbol.as_deref().unwrap_or("(unknown)"),
+        format_schedule_amount(rec),
+        if next_run.is_empty() { "n/a".to_string() } else { next_run },
+        repeat_label(&rec.repeat, rec.weekly_weeks),
+        rec.run_count
+    )
+}
+
+/// Title and management buttons for one schedule in /listscheduledpayments
+pub fn schedule_list_entry(rec: &ScheduledPaymentRecord) -> (String, InlineKeyboardMarkup) {
+    let amount = match rec.usd_amount {
+        Some(usd) => format!("${:.2} in", usd),
+        None => {
+            let smallest = rec.amount_smallest_units.unwrap_or(0);
+            let decimals = rec.decimals.unwrap_or(8);
+            format!("{:.4}", (smallest as f64) / 10f64.powi(decimals as i32))
+        }
+    };
+    let title = format!(
+        "⏰ {:>11} — @{} — {} {}",
+        rec.next_run_at
+            .map(|v| chrono::DateTime::<chrono::Utc>::from_timestamp(v, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| v.to_string()))
+            .unwrap_or_else(|| "n/a".to_string()),
+        rec.recipient_username.clone().unwrap_or_default(),
+        amount,
+        rec.symbol.clone().unwrap_or_default(),
+    );
+    let toggle_label = if rec.active {
+        "⏸ Pause"
+    } else {
+        "▶️ Resume"
+    };
+    let kb = InlineKeyboardMarkup::new(vec![
+        vec![
+            InlineKeyboardButton::callback("✏️ Edit", format!("schedpay_edit:{}", rec.id)),
+            InlineKeyboardButton::callback(toggle_label, format!("schedpay_toggle:{}", rec.id)),
+        ],
+        vec![
+            InlineKeyboardButton::callback("⚡ Run now", format!("schedpay_runnow:{}", rec.id)),
+            InlineKeyboardButton::callback(
+                "📝 Run with note",
+                format!("schedpay_runnote:{}", rec.id),
+            ),
+        ],
+        vec![InlineKeyboardButton::callback(
+            "🗑 Delete",
+            format!("schedpay_delete:{}", rec.id),
+        )],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Close",
+            format!("schedpay_close:{}", rec.id),
+        )],
+    ]);
+    (title, kb)
+}
+
+/// Quote a CSV cell when needed, and neutralise leading characters spreadsheets treat as formulas
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn csv_timestamp(ts: Option<i64>) -> String {
+    ts.and_then(|v| chrono::DateTime::<chrono::Utc>::from_timestamp(v, 0))
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+/// CSV export of a group's scheduled payments for accounting
+pub fn schedules_to_csv(records: &[ScheduledPaymentRecord]) -> String {
+    let mut out = String::from(
+        "id,status,recipient_username,recipient_address,symbol,token_type,amount,amount_smallest_units,schedule,first_run,next_run,last_run,executions,last_attempt,created_by,usd_target\n",
+    );
+    for rec in records {
+        let decimals = rec.decimals.unwrap_or(8) as i32;
+        let amount = rec
+            .amount_smallest_units
+            .map(|v| format!("{}", v as f64 / 10f64.powi(decimals)))
+            .unwrap_or_default();
+        let row = [
+            rec.id.clone(),
+            if rec.active { "active" } else { "paused" }.to_string(),
+            rec.recipient_username.clone().unwrap_or_default(),
+            rec.recipient_address.clone().unwrap_or_default(),
+            rec.symbol.clone().unwrap_or_default(),
+            rec.token_type.clone().unwrap_or_default(),
+            amount,
+            rec.amount_smallest_units.map(|v| v.to_string()).unwrap_or_default(),
+            repeat_label(&rec.repeat, rec.weekly_weeks),
+            csv_timestamp(rec.start_timestamp_utc),
+            if rec.active { csv_timestamp(rec.next_run_at) } else { String::new() },
+            csv_timestamp(rec.last_run_at),
+            rec.run_count.to_string(),
+            rec.last_attempt_status.clone().unwrap_or_default(),
+            rec.creator_username.clone(),
+            rec.usd_amount.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+        ];
+        out.push_str(
+            &row.iter()
+                .map(|v| csv_field(v))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("APT"), "APT");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=HYPERLINK(1)"), "'=HYPERLINK(1)");
+        assert_eq!(csv_field("@alice"), "'@alice");
+    }
+
+    #[test]
+    fn test_parse_payment_amount() {
+        assert_eq!(parse_payment_amount("1.5"), Some(PaymentAmount::Token(1.5)));
+        assert_eq!(parse_payment_amount("$25"), Some(PaymentAmount::Usd(25.0)));
+        assert_eq!(parse_payment_amount("25 USD"), Some(PaymentAmount::Usd(25.0)));
+        assert_eq!(parse_payment_amount("0"), None);
+        assert_eq!(parse_payment_amount("$abc"), None);
+        assert_eq!(usd_to_smallest_units(25.0, 5.0, 8), Some(500_000_000));
+        assert_eq!(usd_to_smallest_units(25.0, 0.0, 8), None);
+    }
+
+    #[test]
+    fn test_summarize_schedule_uses_stored_decimals() {
+        let rec = ScheduledPaymentRecord {
+            id: "sched-1".to_string(),
+            group_id: -100,
+            creator_user_id: 1,
+            creator_username: "alice".to_string(),
+            recipient_username: Some("bob".to_string()),
+            recipient_address: None,
+            symbol: Some("USDC".to_string()),
+            token_type: None,
+            decimals: Some(6),
+            amount_smallest_units: Some(1_500_000),
+            start_timestamp_utc: Some(0),
+            repeat: RepeatPolicy::Monthly,
+            weekly_weeks: None,
+            active: true,
+            created_at: 0,
+            last_run_at: None,
+            next_run_at: Some(1_700_000_000),
+            run_count: 2,
+            locked_until: None,
+            scheduler_job_id: None,
+            last_error: None,
+            last_attempt_status: None,
+            notify_on_success: false,
+            notify_on_failure: false,
+            pending_run_note: None,
+            usd_amount: None,
+        };
+        let summary = summarize_schedule(&rec);
+        assert!(summary.contains("@bob"));
+        assert!(summary.contains("1.5 USDC"));
+        assert!(summary.contains("2023-11-14 22:13 UTC"));
+        assert!(summary.contains("Monthly"));
+    }
+}
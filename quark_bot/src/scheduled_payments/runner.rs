@@ -1,9 +1,13 @@
-use chrono::Utc;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use teloxide::{prelude::*, types::ChatId};
 use tokio_cron_scheduler::Job;
 
 use crate::dependencies::BotDependencies;
+use crate::notification_prefs::NotificationCategory;
+use crate::payment::ledger::{LedgerEntry, ReconcileStatus};
+use crate::payment::notifications::{notify_payment_recipients, payment_source_label};
 use crate::scheduled_payments::dto::ScheduledPaymentRecord;
+use crate::scheduled_payments::helpers::usd_to_smallest_units;
 use crate::scheduled_payments::storage::ScheduledPaymentsStorage;
 use crate::scheduled_prompts::dto::RepeatPolicy;
 
@@ -12,14 +16,352 @@ fn next_week_cadence(now_ts: i64, weeks: u8) -> i64 {
     now_ts + days * 24 * 3600
 }
 
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(28)
+}
+
+/// First run after `now_ts` on the start's day of month and time of day. Shorter months
+/// use their last day, so a schedule started on the 31st runs Jan 31 → Feb 28/29 → Mar 31.
+fn next_month_cadence(anchor_ts: i64, now_ts: i64) -> i64 {
+    let (Some(anchor), Some(from)) = (
+        DateTime::<Utc>::from_timestamp(anchor_ts, 0),
+        DateTime::<Utc>::from_timestamp(now_ts.max(anchor_ts), 0),
+    ) else {
+        return now_ts + 30 * 24 * 3600;
+    };
+
+    let (mut year, mut month) = (from.year(), from.month());
+    loop {
+        let day = anchor.day().min(days_in_month(year, month));
+        if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
+            let candidate = date.and_time(anchor.time()).and_utc().timestamp();
+            if candidate > now_ts && candidate >= anchor_ts {
+                return candidate;
+            }
+        }
+        (year, month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+    }
+}
+
+fn next_occurrence(rec: &ScheduledPaymentRecord, now_ts: i64) -> i64 {
+    let weeks = rec.weekly_weeks.unwrap_or(1);
+    match rec.repeat {
+        RepeatPolicy::Daily => now_ts + 24 * 3600,
+        RepeatPolicy::Weekly => next_week_cadence(now_ts, weeks),
+        RepeatPolicy::Monthly => {
+            next_month_cadence(rec.start_timestamp_utc.unwrap_or(now_ts), now_ts)
+        }
+        _ => next_week_cadence(now_ts, weeks),
+    }
+}
+
+/// Token amount for a USD-pegged run at the current Panora price, with the price used
+async fn usd_pegged_amount(
+    bot_deps: &BotDependencies,
+    rec: &ScheduledPaymentRecord,
+    usd: f64,
+) -> Option<(u64, f64)> {
+    let symbol = rec.symbol.as_deref()?;
+    let token = bot_deps.panora.get_token_by_symbol(symbol).await.ok()?;
+    let price = token.usd_price.as_deref()?.parse::<f64>().ok()?;
+    let units = usd_to_smallest_units(usd, price, rec.decimals.unwrap_or(token.decimals))?;
+    Some((units, price))
+}
+
+/// How a single execution of a schedule ended
+pub enum PaymentRunOutcome {
+    Sent { hash: String },
+    /// USD-pegged run without a price; nothing was sent
+    Skipped,
+    Failed(String),
+}
+
+/// Pay, record and notify for one run of a schedule the caller has already locked
+pub async fn execute_payment(
+    bot: &Bot,
+    bot_deps: &BotDependencies,
+    mut rec: ScheduledPaymentRecord,
+) -> PaymentRunOutcome {
+    let storage = bot_deps.scheduled_payments.clone();
+    let now_ts = Utc::now().timestamp();
+    let group_chat_id = ChatId(rec.group_id);
+
+    // USD-pegged schedules convert their target at the current price; without
+    // a price this run is skipped rather than sending a stale amount
+    let mut pegged_price = None;
+    if let Some(usd) = rec.usd_amount {
+        match usd_pegged_amount(bot_deps, &rec, usd).await {
+            Some((units, price)) => {
+                rec.amount_smallest_units = Some(units);
+                pegged_price = Some(price);
+            }
+            None => {
+                let symbol = rec.symbol.clone().unwrap_or_default();
+                rec.last_attempt_status = Some("skipped".to_string());
+                rec.last_error = Some(format!("{} price unavailable", symbol));
+                rec.next_run_at = Some(next_occurrence(&rec, now_ts));
+                rec.locked_until = None;
+                let _ = storage.put_schedule(&rec);
+                let next_run = rec
+                    .next_run_at
+                    .and_then(|v| chrono::DateTime::<Utc>::from_timestamp(v, 0))
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+                    .unwrap_or_default();
+                let text = format!(
+                    "⚠️ Scheduled payment skipped\nCouldn't get a {} price to convert ${:.2}, so nothing was sent.\nTo: @{}\nNext run: {}\nSchedule: {}",
+                    symbol,
+                    usd,
+                    rec.recipient_username.as_deref().unwrap_or("Unknown"),
+                    next_run,
+                    rec.id
+                );
+                let wants_updates = bot_deps
+                    .notification_prefs
+                    .is_enabled(rec.creator_user_id, NotificationCategory::PaymentUpdates);
+                if wants_updates {
+                    if let Err(e) = bot
+                        .send_message(ChatId(rec.creator_user_id), text.clone())
+                        .await
+                    {
+                        let _ = bot
+                            .send_message(
+                                group_chat_id,
+                                format!("{}\n(tag: @{})", text, rec.creator_username),
+                            )
+                            .await;
+                        log::warn!("Failed to DM creator: {}", e);
+                    }
+                }
+                return PaymentRunOutcome::Skipped;
+            }
+        }
+    }
+
+    // Execute payment via service.pay_members
+    let result = (|| async {
+        let group_credentials = match bot_deps.group.get_credentials(group_chat_id) {
+            Some(c) => c,
+            None => return Err(anyhow::anyhow!("Group credentials not found")),
+        };
+
+        // Validate critical payment data before proceeding
+        let amount = match rec.amount_smallest_units {
+            Some(amt) => {
+                if amt > 0 {
+                    amt
+                } else {
+                    return Err(anyhow::anyhow!("Scheduled payment amount cannot be zero"));
+                }
+            }
+            None => return Err(anyhow::anyhow!("Scheduled payment amount is missing")),
+        };
+
+        let coin_type = match &rec.token_type {
+            Some(token) if !token.is_empty() => token.clone(),
+            Some(_) => {
+                return Err(anyhow::anyhow!("Scheduled payment token type is empty"));
+            }
+            None => return Err(anyhow::anyhow!("Scheduled payment token type is missing")),
+        };
+
+        let recipient_address = match &rec.recipient_address {
+            Some(addr) if !addr.is_empty() => addr.clone(),
+            Some(_) => {
+                return Err(anyhow::anyhow!(
+                    "Scheduled payment recipient address is empty"
+                ));
+            }
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Scheduled payment recipient address is missing"
+                ));
+            }
+        };
+
+        let token = group_credentials.jwt;
+        let version = if coin_type.contains("::") {
+            quark_core::helpers::dto::CoinVersion::V1
+        } else {
+            quark_core::helpers::dto::CoinVersion::V2
+        };
+        let users = vec![recipient_address];
+        let payload = quark_core::helpers::dto::PayUsersRequest {
+            amount,
+            users,
+            coin_type,
+            version,
+        };
+        bot_deps.service.pay_members(token, payload).await
+    })()
+    .await;
+
+    match result {
+        Ok(resp) => {
+            let run_note = rec.pending_run_note.take();
+            if let Some(note) = &run_note {
+                log::info!(
+                    "Scheduled payment {} executed with note: {}",
+                    rec.id,
+                    note
+                );
+            }
+            rec.last_attempt_status = Some("success".to_string());
+            rec.last_error = None;
+            rec.last_run_at = Some(now_ts);
+            rec.run_count += 1;
+            rec.next_run_at = Some(next_occurrence(&rec, now_ts));
+            rec.locked_until = None;
+            let _ = storage.put_schedule(&rec);
+            let entry = LedgerEntry {
+                tx_hash: resp.hash.clone(),
+                recorded_at: now_ts,
+                source: "scheduled".to_string(),
+                chat_id: rec.group_id,
+                is_group: true,
+                payer_address: bot_deps
+                    .group
+                    .get_credentials(group_chat_id)
+                    .map(|c| c.resource_account_address),
+                recipient_addresses: rec.recipient_address.iter().cloned().collect(),
+                amount: rec.amount_smallest_units.unwrap_or(0),
+                coin_type: rec.token_type.clone().unwrap_or_default(),
+                symbol: rec.symbol.clone().unwrap_or_default(),
+                status: ReconcileStatus::Pending,
+                checked_at: None,
+            };
+            if let Err(e) = bot_deps.payment_ledger.record(&entry) {
+                log::error!("Failed to record scheduled payment {} in ledger: {}", rec.id, e);
+            }
+            if let Some(recipient) = rec.recipient_username.clone() {
+                let decimals = rec.decimals.unwrap_or(8) as i32;
+                let human_amount =
+                    (rec.amount_smallest_units.unwrap_or(0) as f64) / 10f64.powi(decimals);
+                let source = payment_source_label(bot, group_chat_id, None).await;
+                notify_payment_recipients(
+                    bot,
+                    bot_deps,
+                    &[recipient],
+                    human_amount,
+                    rec.symbol.as_deref().unwrap_or("Unknown"),
+                    &source,
+                    &resp.hash,
+                    run_note.as_deref(),
+                )
+                .await;
+            }
+            if rec.notify_on_success
+                && bot_deps
+                    .notification_prefs
+                    .is_enabled(rec.creator_user_id, NotificationCategory::PaymentUpdates)
+            {
+                let network = std::env::var("APTOS_NETWORK")
+                    .unwrap_or_else(|_| "mainnet".to_string())
+                    .to_lowercase();
+                let hash = resp.hash.clone();
+                let amount_smallest = rec.amount_smallest_units.unwrap_or(0);
+                let decimals = rec.decimals.unwrap_or(8) as i32;
+                let human_amount = (amount_smallest as f64) / 10f64.powi(decimals);
+                let symbol = rec.symbol.as_deref().unwrap_or("Unknown");
+                let recipient_username =
+                    rec.recipient_username.as_deref().unwrap_or("Unknown");
+                let note_line = run_note
+                    .as_deref()
+                    .map(|n| format!("\nNote: {}", n))
+                    .unwrap_or_default();
+                let usd_line = match (rec.usd_amount, pegged_price) {
+                    (Some(usd), Some(price)) => {
+                        format!(" (${:.2} target at ${:.4})", usd, price)
+                    }
+                    _ => String::new(),
+                };
+                let text = format!(
+                    "✅ Payment sent\nAmount: {:.4} {}{}\nTo: @{}{}\nSchedule: {}\n🔗 Explorer: https://explorer.aptoslabs.com/txn/{}?network={}",
+                    human_amount,
+                    symbol,
+                    usd_line,
+                    recipient_username,
+                    note_line,
+                    rec.id,
+                    hash,
+                    network
+                );
+                if let Err(e) = bot
+                    .send_message(ChatId(rec.creator_user_id), text.clone())
+                    .await
+                {
+                    // DM failed -> optional group fallback
+                    let _ = bot
+                        .send_message(
+                            group_chat_id,
+                            format!("{}\n(tag: @{})", text, rec.creator_username),
+                        )
+                        .await;
+                    log::warn!("Failed to DM creator: {}", e);
+                }
+            }
+            PaymentRunOutcome::Sent { hash: resp.hash }
+        }
+        Err(e) => {
+            rec.last_attempt_status = Some("failure".to_string());
+            rec.last_error = Some(e.to_string());
+            rec.locked_until = None;
+            let _ = storage.put_schedule(&rec);
+            if rec.notify_on_failure {
+                use teloxide::types::InlineKeyboardButton as Btn;
+                use teloxide::types::InlineKeyboardMarkup as Kb;
+                let kb = Kb::new(vec![
+                    vec![Btn::callback(
+                        "🔁 Retry now",
+                        format!("schedpay_retry:{}:{}", rec.id, now_ts),
+                    )],
+                    vec![Btn::callback(
+                        "⏸ Pause",
+                        format!("schedpay_toggle:{}", rec.id),
+                    )],
+                ]);
+                if let Err(err) = bot
+                    .send_message(
+                        ChatId(rec.creator_user_id),
+                        format!("❌ Payment failed: {}", e),
+                    )
+                    .reply_markup(kb.clone())
+                    .await
+                {
+                    let _ = bot
+                        .send_message(
+                            group_chat_id,
+                            format!(
+                                "❌ Scheduled payment failed: {}\n(tag: @{})",
+                                e, rec.creator_username
+                            ),
+                        )
+                        .reply_markup(kb)
+                        .await;
+                    log::warn!("Failed to DM creator: {}", err);
+                }
+            }
+            PaymentRunOutcome::Failed(e.to_string())
+        }
+    }
+}
+
 pub async fn register_all_schedules(bot: Bot, bot_deps: BotDependencies) -> anyhow::Result<()> {
     let storage = ScheduledPaymentsStorage::new(&bot_deps.db)?;
     for item in storage.scheduled.iter() {
         if let Ok((_, ivec)) = item {
-            if let Ok((mut rec, _)) = bincode::decode_from_slice::<ScheduledPaymentRecord, _>(
-                &ivec,
-                bincode::config::standard(),
-            ) {
+            if let Some(mut rec) = ScheduledPaymentsStorage::decode_schedule(&ivec) {
                 if rec.active {
                     if let Err(e) = register_schedule(bot.clone(), bot_deps.clone(), &mut rec).await
                     {
@@ -46,7 +388,6 @@ pub async fn register_schedule(
 ) -> anyhow::Result<()> {
     let scheduler = bot_deps.scheduler.clone();
     let schedule_id = record.id.clone();
-    let group_chat_id = ChatId(record.group_id as i64);
 
     if record.next_run_at.is_none() {
         record.next_run_at = record.start_timestamp_utc;
@@ -56,7 +397,6 @@ pub async fn register_schedule(
         let bot = bot.clone();
         let bot_deps = bot_deps.clone();
         let schedule_id = schedule_id.clone();
-        let group_chat_id = group_chat_id;
         Box::pin(async move {
             let storage = bot_deps.scheduled_payments.clone();
             let mut rec = match storage.get_schedule(&schedule_id) {
@@ -84,143 +424,7 @@ pub async fn register_schedule(
             rec.locked_until = Some(now_ts + 120);
             let _ = storage.put_schedule(&rec);
 
-            // Execute payment via service.pay_members
-            let result = (|| async {
-                let group_credentials = match bot_deps.group.get_credentials(group_chat_id) {
-                    Some(c) => c,
-                    None => return Err(anyhow::anyhow!("Group credentials not found")),
-                };
-
-                // Validate critical payment data before proceeding
-                let amount = match rec.amount_smallest_units {
-                    Some(amt) => {
-                        if amt > 0 {
-                            amt
-                        } else {
-                            return Err(anyhow::anyhow!("Scheduled payment amount cannot be zero"));
-                        }
-                    }
-                    None => return Err(anyhow::anyhow!("Scheduled payment amount is missing")),
-                };
-
-                let coin_type = match &rec.token_type {
-                    Some(token) if !token.is_empty() => token.clone(),
-                    Some(_) => {
-                        return Err(anyhow::anyhow!("Scheduled payment token type is empty"));
-                    }
-                    None => return Err(anyhow::anyhow!("Scheduled payment token type is missing")),
-                };
-
-                let recipient_address = match &rec.recipient_address {
-                    Some(addr) if !addr.is_empty() => addr.clone(),
-                    Some(_) => {
-                        return Err(anyhow::anyhow!(
-                            "Scheduled payment recipient address is empty"
-                        ));
-                    }
-                    None => {
-                        return Err(anyhow::anyhow!(
-                            "Scheduled payment recipient address is missing"
-                        ));
-                    }
-                };
-
-                let token = group_credentials.jwt;
-                let version = if coin_type.contains("::") {
-                    quark_core::helpers::dto::CoinVersion::V1
-                } else {
-                    quark_core::helpers::dto::CoinVersion::V2
-                };
-                let users = vec![recipient_address];
-                let payload = quark_core::helpers::dto::PayUsersRequest {
-                    amount,
-                    users,
-                    coin_type,
-                    version,
-                };
-                bot_deps.service.pay_members(token, payload).await
-            })()
-            .await;
-
-            match result {
-                Ok(resp) => {
-                    rec.last_attempt_status = Some("success".to_string());
-                    rec.last_error = None;
-                    rec.last_run_at = Some(now_ts);
-                    rec.run_count += 1;
-                    // Compute next occurrence
-                    let weeks = rec.weekly_weeks.unwrap_or(1);
-                    rec.next_run_at = match rec.repeat {
-                        RepeatPolicy::Daily => Some(now_ts + 24 * 3600),
-                        RepeatPolicy::Weekly => Some(next_week_cadence(now_ts, weeks)),
-                        _ => Some(next_week_cadence(now_ts, weeks)),
-                    };
-                    rec.locked_until = None;
-                    let _ = storage.put_schedule(&rec);
-                    if rec.notify_on_success {
-                        let network = std::env::var("APTOS_NETWORK")
-                            .unwrap_or_else(|_| "mainnet".to_string())
-                            .to_lowercase();
-                        let hash = resp.hash;
-                        let amount_smallest = rec.amount_smallest_units.unwrap_or(0);
-                        let decimals = rec.decimals.unwrap_or(8) as i32;
-                        let human_amount = (amount_smallest as f64) / 10f64.powi(decimals);
-                        let symbol = rec.symbol.as_deref().unwrap_or("Unknown");
-                        let recipient_username =
-                            rec.recipient_username.as_deref().unwrap_or("Unknown");
-                        let text = format!(
-                            "✅ Payment sent\nAmount: {:.4} {}\nTo: @{}\nSchedule: {}\n🔗 Explorer: https://explorer.aptoslabs.com/txn/{}?network={}",
-                            human_amount, symbol, recipient_username, rec.id, hash, network
-                        );
-                        if let Err(e) = bot
-                            .send_message(ChatId(rec.creator_user_id), text.clone())
-                            .await
-                        {
-                            // DM failed -> optional group fallback
-                            let _ = bot
-                                .send_message(
-                                    group_chat_id,
-                                    format!("{}\n(tag: @{})", text, rec.creator_username),
-                                )
-                                .await;
-                            log::warn!("Failed to DM creator: {}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    rec.last_attempt_status = Some("failure".to_string());
-                    rec.last_error = Some(e.to_string());
-                    rec.locked_until = None;
-                    let _ = storage.put_schedule(&rec);
-                    if rec.notify_on_failure {
-                        use teloxide::types::InlineKeyboardButton as Btn;
-                        use teloxide::types::InlineKeyboardMarkup as Kb;
-                        let kb = Kb::new(vec![
-                            vec![Btn::callback(
-                                "🔁 Retry now",
-                                format!("schedpay_runnow:{}", rec.id),
-                            )],
-                            vec![Btn::callback(
-                                "⏸ Pause",
-                                format!("schedpay_toggle:{}", rec.id),
-                            )],
-                        ]);
-                        if let Err(err) = bot
-                            .send_message(
-                                ChatId(rec.creator_user_id),
-                                format!("❌ Payment failed: {}", e),
-                            )
-                            .reply_markup(kb.clone())
-                            .await
-                        {
-                            let _ = bot
-                                .send_message(group_chat_id, "❌ Scheduled payment failed (unable to DM). Use /listscheduledpayments for actions.")
-                                .await;
-                            log::warn!("Failed to DM creator: {}", err);
-                        }
-                    }
-                }
-            }
+            execute_payment(&bot, &bot_deps, rec).await;
         })
     })?;
 
@@ -228,3 +432,38 @@ pub async fn register_schedule(
     record.scheduler_job_id = Some(id.to_string());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(y: i32, m: u32, d: u32) -> i64 {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    #[test]
+    fn test_monthly_rollover_from_the_31st() {
+        let anchor = ts(2024, 1, 31);
+        // Runs fire a little after they're due, as the per-minute runner does
+        let feb = next_month_cadence(anchor, anchor + 60);
+        assert_eq!(feb, ts(2024, 2, 29));
+        let mar = next_month_cadence(anchor, feb + 60);
+        assert_eq!(mar, ts(2024, 3, 31));
+        assert_eq!(next_month_cadence(anchor, mar + 60), ts(2024, 4, 30));
+
+        // Non-leap year clamps to the 28th
+        let anchor = ts(2025, 1, 31);
+        assert_eq!(next_month_cadence(anchor, anchor + 60), ts(2025, 2, 28));
+    }
+
+    #[test]
+    fn test_monthly_rolls_over_the_year() {
+        let anchor = ts(2024, 12, 15);
+        assert_eq!(next_month_cadence(anchor, anchor + 60), ts(2025, 1, 15));
+    }
+}
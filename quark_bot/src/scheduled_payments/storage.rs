@@ -1,8 +1,14 @@
-use crate::scheduled_payments::dto::{PendingPaymentWizardState, ScheduledPaymentRecord};
+use crate::scheduled_payments::dto::{
+    LegacyPendingPaymentWizardState, LegacyScheduledPaymentRecord, NotedScheduledPaymentRecord,
+    PendingPaymentWizardState, ScheduledPaymentRecord,
+};
+use chrono::Utc;
 use sled::{Db, IVec, Tree};
 
 const SCHEDULED_PAYMENTS_TREE: &str = "scheduled_payments";
 const SCHEDULED_PAYMENT_PENDING_TREE: &str = "scheduled_payment_pending";
+/// A payment wizard nobody has answered for this long is dropped
+pub const PENDING_PAYMENT_WIZARD_TTL_SECS: i64 = 30 * 60;
 
 #[derive(Clone)]
 pub struct ScheduledPaymentsStorage {
@@ -24,29 +30,36 @@ impl ScheduledPaymentsStorage {
         Ok(())
     }
 
+    /// Decode a stored schedule, accepting records written before USD pegging or run
+    /// notes. Newest layout first: older shapes are prefixes of it.
+    pub fn decode_schedule(bytes: &[u8]) -> Option<ScheduledPaymentRecord> {
+        let config = bincode::config::standard();
+        bincode::decode_from_slice::<ScheduledPaymentRecord, _>(bytes, config)
+            .map(|(v, _)| v)
+            .or_else(|_| {
+                bincode::decode_from_slice::<NotedScheduledPaymentRecord, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .or_else(|_| {
+                bincode::decode_from_slice::<LegacyScheduledPaymentRecord, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .ok()
+    }
+
     pub fn get_schedule(&self, id: &str) -> Option<ScheduledPaymentRecord> {
         self.scheduled
             .get(id.as_bytes())
             .ok()
             .flatten()
-            .and_then(|ivec: IVec| {
-                bincode::decode_from_slice::<ScheduledPaymentRecord, _>(
-                    &ivec,
-                    bincode::config::standard(),
-                )
-                .ok()
-                .map(|(v, _)| v)
-            })
+            .and_then(|ivec: IVec| Self::decode_schedule(&ivec))
     }
 
     pub fn list_schedules_for_group(&self, group_id: i64) -> Vec<ScheduledPaymentRecord> {
         let mut out = Vec::new();
         for kv in self.scheduled.iter() {
             if let Ok((_k, ivec)) = kv {
-                if let Ok((rec, _)) = bincode::decode_from_slice::<ScheduledPaymentRecord, _>(
-                    &ivec,
-                    bincode::config::standard(),
-                ) {
+                if let Some(rec) = Self::decode_schedule(&ivec) {
                     if rec.group_id == group_id && rec.active {
                         out.push(rec);
                     }
@@ -56,20 +69,85 @@ impl ScheduledPaymentsStorage {
         out
     }
 
-    pub fn put_pending(&self, key: (&i64, &i64), state: &PendingPaymentWizardState) -> sled::Result<()> {
+    /// Active and paused schedules alike, oldest first
+    pub fn list_all_schedules_for_group(&self, group_id: i64) -> Vec<ScheduledPaymentRecord> {
+        let mut out: Vec<ScheduledPaymentRecord> = self
+            .scheduled
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter_map(|(_k, ivec)| Self::decode_schedule(&ivec))
+            .filter(|rec| rec.group_id == group_id)
+            .collect();
+        out.sort_by_key(|rec| rec.created_at);
+        out
+    }
+
+    pub fn delete_schedule(&self, id: &str) -> sled::Result<()> {
+        self.scheduled.remove(id.as_bytes())?;
+        Ok(())
+    }
+
+    /// Store the wizard state, stamping it as touched now
+    pub fn put_pending(
+        &self,
+        key: (&i64, &i64),
+        state: &PendingPaymentWizardState,
+    ) -> sled::Result<()> {
         let k = Self::pending_key_bytes(key);
-        let bytes = bincode::encode_to_vec(state, bincode::config::standard()).unwrap();
+        let mut state = state.clone();
+        state.updated_at = Utc::now().timestamp();
+        let bytes = bincode::encode_to_vec(&state, bincode::config::standard()).unwrap();
         self.pending.insert(k, bytes)?;
         Ok(())
     }
 
+    fn decode_pending(bytes: &[u8]) -> Option<PendingPaymentWizardState> {
+        let config = bincode::config::standard();
+        bincode::decode_from_slice::<PendingPaymentWizardState, _>(bytes, config)
+            .map(|(v, _)| v)
+            .or_else(|_| {
+                bincode::decode_from_slice::<LegacyPendingPaymentWizardState, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .ok()
+    }
+
+    fn is_stale(state: &PendingPaymentWizardState, now: i64) -> bool {
+        now - state.updated_at > PENDING_PAYMENT_WIZARD_TTL_SECS
+    }
+
+    /// The wizard state for this chat and user; an abandoned one is removed and not returned
     pub fn get_pending(&self, key: (&i64, &i64)) -> Option<PendingPaymentWizardState> {
         let k = Self::pending_key_bytes(key);
-        self.pending.get(k).ok().flatten().and_then(|ivec: IVec| {
-            bincode::decode_from_slice::<PendingPaymentWizardState, _>(&ivec, bincode::config::standard())
-                .ok()
-                .map(|(v, _)| v)
-        })
+        let state = self
+            .pending
+            .get(&k)
+            .ok()
+            .flatten()
+            .and_then(|ivec: IVec| Self::decode_pending(&ivec))?;
+        if Self::is_stale(&state, Utc::now().timestamp()) {
+            let _ = self.pending.remove(k);
+            return None;
+        }
+        Some(state)
+    }
+
+    /// Remove wizard states nobody has touched within the TTL, plus unreadable ones
+    pub fn sweep_stale_pending(&self) -> usize {
+        let now = Utc::now().timestamp();
+        let stale: Vec<IVec> = self
+            .pending
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(_, v)| Self::decode_pending(v).is_none_or(|st| Self::is_stale(&st, now)))
+            .map(|(k, _)| k)
+            .collect();
+        for k in &stale {
+            if let Err(e) = self.pending.remove(k) {
+                log::warn!("Failed to remove stale scheduled payment wizard: {}", e);
+            }
+        }
+        stale.len()
     }
 
     pub fn delete_pending(&self, key: (&i64, &i64)) -> sled::Result<()> {
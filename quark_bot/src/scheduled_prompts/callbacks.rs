@@ -1,11 +1,16 @@
 use anyhow::Result;
-use teloxide::{prelude::*, types::InlineKeyboardMarkup};
+use teloxide::prelude::*;
 
 use crate::{
     dependencies::BotDependencies,
     scheduled_prompts::dto::{PendingStep, RepeatPolicy},
     scheduled_prompts::handler::finalize_and_register,
-    scheduled_prompts::helpers::{build_minutes_keyboard, build_repeat_keyboard, summarize},
+    scheduled_prompts::helpers::{
+        MAX_SKIP_DATES, MAX_TEMPLATE_LEN, build_confirm_keyboard, build_hours_keyboard,
+        build_minutes_keyboard, build_repeat_keyboard, build_settings_keyboard,
+        build_skip_keyboard, parse_timezone, summarize, timezone_label,
+    },
+    user_model_preferences::dto::ChatModel,
 };
 
 pub async fn handle_scheduled_prompts_callback(
@@ -35,22 +40,64 @@ pub async fn handle_scheduled_prompts_callback(
     }
     let key = (&message.chat.id.0, &(user.id.0 as i64));
 
-    if data.starts_with("sched_hour:") {
+    if data.starts_with("sched_tz:") {
+        let Ok(tz) = parse_timezone(data.trim_start_matches("sched_tz:")) else {
+            bot.answer_callback_query(query.id)
+                .text("❌ Unknown timezone")
+                .await?;
+            return Ok(());
+        };
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.step = PendingStep::AwaitingHour;
+            st.timezone = (tz != chrono_tz::UTC).then(|| tz.name().to_string());
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format!(
+                    "Select start hour ({})",
+                    timezone_label(st.timezone.as_deref())
+                ),
+            )
+            .reply_markup(build_hours_keyboard())
+            .await?;
+        }
+    } else if data == "sched_tz_other" {
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.step = PendingStep::AwaitingTimezone;
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                "✏️ Send the timezone as an IANA name, e.g. Europe/Madrid, America/Denver or Asia/Jakarta.",
+            )
+            .await?;
+        }
+    } else if data.starts_with("sched_hour:") {
         let hour: u8 = data.split(':').nth(1).unwrap_or("0").parse().unwrap_or(0);
         if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
             st.step = PendingStep::AwaitingMinute;
-            st.hour_utc = Some(hour);
+            st.start_hour = Some(hour);
             bot_deps.scheduled_storage.put_pending(key, &st)?;
             bot.answer_callback_query(query.id).await?;
-            bot.edit_message_text(message.chat.id, message.id, "Select start minute (UTC)")
-                .reply_markup(build_minutes_keyboard())
-                .await?;
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format!(
+                    "Select start minute ({})",
+                    timezone_label(st.timezone.as_deref())
+                ),
+            )
+            .reply_markup(build_minutes_keyboard())
+            .await?;
         }
     } else if data.starts_with("sched_min:") {
         let minute: u8 = data.split(':').nth(1).unwrap_or("0").parse().unwrap_or(0);
         if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
             st.step = PendingStep::AwaitingRepeat;
-            st.minute_utc = Some(minute);
+            st.start_minute = Some(minute);
             bot_deps.scheduled_storage.put_pending(key, &st)?;
             bot.answer_callback_query(query.id).await?;
             bot.edit_message_text(message.chat.id, message.id, "Select repeat interval")
@@ -78,17 +125,117 @@ pub async fn handle_scheduled_prompts_callback(
             st.repeat = Some(repeat);
             bot_deps.scheduled_storage.put_pending(key, &st)?;
             let summary = summarize(&st);
-            let kb = InlineKeyboardMarkup::new(vec![vec![
-                teloxide::types::InlineKeyboardButton::callback(
-                    "✔️ Create schedule".to_string(),
-                    "sched_confirm".to_string(),
-                ),
-            ]]);
+            let kb = build_confirm_keyboard(&st);
             bot.answer_callback_query(query.id).await?;
             bot.edit_message_text(message.chat.id, message.id, summary)
                 .reply_markup(kb)
                 .await?;
         }
+    } else if data == "sched_template" {
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.step = PendingStep::AwaitingTemplate;
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format!(
+                    "🎨 Send the output template as your next message (max {} characters).\n\nPlaceholders:\n• {{output}} — the AI response (required)\n• {{date}}, {{time}}, {{datetime}} — run time in UTC\n\nExample:\n<b>📊 Daily Update</b>\n{{output}}\n— powered by Quark",
+                    MAX_TEMPLATE_LEN
+                ),
+            )
+            .await?;
+        }
+    } else if data == "sched_template_clear" {
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.output_template = None;
+            st.step = PendingStep::AwaitingConfirm;
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id)
+                .text("Template removed")
+                .await?;
+            bot.edit_message_text(message.chat.id, message.id, summarize(&st))
+                .reply_markup(build_confirm_keyboard(&st))
+                .await?;
+        }
+    } else if data == "sched_delivery" {
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.output_delivery = st.output_delivery.next();
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id)
+                .text(format!("Output as: {}", st.output_delivery.label()))
+                .await?;
+            bot.edit_message_text(message.chat.id, message.id, summarize(&st))
+                .reply_markup(build_confirm_keyboard(&st))
+                .await?;
+        }
+    } else if data == "sched_settings" {
+        if let Some(st) = bot_deps.scheduled_storage.get_pending(key) {
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_reply_markup(message.chat.id, message.id)
+                .reply_markup(build_settings_keyboard(&st))
+                .await?;
+        }
+    } else if data.starts_with("sched_model:") {
+        let choice = data.split(':').nth(1).unwrap_or("default");
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.model = ChatModel::from_name(choice).map(|m| m.canonical_name().to_string());
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(message.chat.id, message.id, summarize(&st))
+                .reply_markup(build_settings_keyboard(&st))
+                .await?;
+        }
+    } else if data == "sched_skip" {
+        if let Some(st) = bot_deps.scheduled_storage.get_pending(key) {
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_reply_markup(message.chat.id, message.id)
+                .reply_markup(build_skip_keyboard(&st))
+                .await?;
+        }
+    } else if data == "sched_skip_weekends" {
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.skip_weekends = !st.skip_weekends;
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(message.chat.id, message.id, summarize(&st))
+                .reply_markup(build_skip_keyboard(&st))
+                .await?;
+        }
+    } else if data == "sched_skip_dates" {
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.step = PendingStep::AwaitingSkipDates;
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(
+                message.chat.id,
+                message.id,
+                format!(
+                    "📅 Send the dates to skip as your next message, as YYYY-MM-DD separated by commas (max {}).\n\nExample: 2025-12-25, 2026-01-01\n\nDates are checked in the schedule's timezone (now {}) and replace any list set before.",
+                    MAX_SKIP_DATES,
+                    st.timezone.as_deref().unwrap_or("UTC")
+                ),
+            )
+            .await?;
+        }
+    } else if data == "sched_skip_dates_clear" {
+        if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+            st.skip_dates.clear();
+            bot_deps.scheduled_storage.put_pending(key, &st)?;
+            bot.answer_callback_query(query.id)
+                .text("Holiday dates removed")
+                .await?;
+            bot.edit_message_text(message.chat.id, message.id, summarize(&st))
+                .reply_markup(build_skip_keyboard(&st))
+                .await?;
+        }
+    } else if data == "sched_settings_done" {
+        if let Some(st) = bot_deps.scheduled_storage.get_pending(key) {
+            bot.answer_callback_query(query.id).await?;
+            bot.edit_message_text(message.chat.id, message.id, summarize(&st))
+                .reply_markup(build_confirm_keyboard(&st))
+                .await?;
+        }
     } else if data == "sched_confirm" {
         if let Some(st) = bot_deps.scheduled_storage.get_pending(key) {
             bot_deps.scheduled_storage.delete_pending(key)?;
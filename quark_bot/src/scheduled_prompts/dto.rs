@@ -17,6 +17,48 @@ pub enum RepeatPolicy {
     Monthly,
 }
 
+/// Outputs longer than this are attached as a file in `OutputDelivery::Auto`
+pub const AUTO_ATTACH_OVER_CHARS: usize = 8000;
+
+/// How a schedule's output reaches the group
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Encode, Decode)]
+pub enum OutputDelivery {
+    /// Post as messages, split across several when long
+    #[default]
+    Inline,
+    /// Post as messages, but attach long outputs as a file
+    Auto,
+    /// Always attach the output as a file
+    File,
+}
+
+impl OutputDelivery {
+    pub fn attaches(&self, output_chars: usize) -> bool {
+        match self {
+            OutputDelivery::Inline => false,
+            OutputDelivery::Auto => output_chars > AUTO_ATTACH_OVER_CHARS,
+            OutputDelivery::File => true,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputDelivery::Inline => "messages",
+            OutputDelivery::Auto => "file when long",
+            OutputDelivery::File => "file",
+        }
+    }
+
+    /// Next option for the wizard's cycling button
+    pub fn next(&self) -> Self {
+        match self {
+            OutputDelivery::Inline => OutputDelivery::Auto,
+            OutputDelivery::Auto => OutputDelivery::File,
+            OutputDelivery::File => OutputDelivery::Inline,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Encode, Decode)]
 pub struct ScheduledPromptRecord {
     pub id: String,
@@ -36,6 +78,308 @@ pub struct ScheduledPromptRecord {
     pub scheduler_job_id: Option<String>,
     pub conversation_response_id: Option<String>,
     pub thread_id: Option<i32>,
+    /// Optional wrapper for the AI output, e.g. "📊 Daily Update\n{output}"
+    pub output_template: Option<String>,
+    /// Canonical `ChatModel` name; the creator's preferred model when unset
+    pub model: Option<String>,
+    /// Unused: the schedulable models reject `temperature`. Kept so stored records decode
+    pub temperature: Option<f32>,
+    /// Don't run on Saturdays and Sundays
+    pub skip_weekends: bool,
+    /// Extra days to skip, as "YYYY-MM-DD"
+    pub skip_dates: Vec<String>,
+    pub output_delivery: OutputDelivery,
+    /// IANA timezone the schedule was set up in; UTC when unset
+    pub timezone: Option<String>,
+    /// Start hour and minute in `timezone`. `start_hour_utc`/`start_minute_utc` are
+    /// recomputed from this so the local time holds across DST changes.
+    pub local_start: Option<(u8, u8)>,
+}
+
+/// Record layout before timezone support was added
+#[derive(Clone, Debug, Decode)]
+pub struct DeliveringScheduledPromptRecord {
+    pub id: String,
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub prompt: String,
+    pub start_hour_utc: u8,
+    pub start_minute_utc: u8,
+    pub repeat: RepeatPolicy,
+    pub active: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub run_count: u64,
+    pub locked_until: Option<i64>,
+    pub scheduler_job_id: Option<String>,
+    pub conversation_response_id: Option<String>,
+    pub thread_id: Option<i32>,
+    pub output_template: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub skip_weekends: bool,
+    pub skip_dates: Vec<String>,
+    pub output_delivery: OutputDelivery,
+}
+
+impl From<DeliveringScheduledPromptRecord> for ScheduledPromptRecord {
+    fn from(legacy: DeliveringScheduledPromptRecord) -> Self {
+        Self {
+            id: legacy.id,
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            prompt: legacy.prompt,
+            start_hour_utc: legacy.start_hour_utc,
+            start_minute_utc: legacy.start_minute_utc,
+            repeat: legacy.repeat,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            last_run_at: legacy.last_run_at,
+            next_run_at: legacy.next_run_at,
+            run_count: legacy.run_count,
+            locked_until: legacy.locked_until,
+            scheduler_job_id: legacy.scheduler_job_id,
+            conversation_response_id: legacy.conversation_response_id,
+            thread_id: legacy.thread_id,
+            output_template: legacy.output_template,
+            model: legacy.model,
+            temperature: legacy.temperature,
+            skip_weekends: legacy.skip_weekends,
+            skip_dates: legacy.skip_dates,
+            output_delivery: legacy.output_delivery,
+            timezone: None,
+            local_start: None,
+        }
+    }
+}
+
+/// Record layout before output delivery settings were added
+#[derive(Clone, Debug, Decode)]
+pub struct SkippingScheduledPromptRecord {
+    pub id: String,
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub prompt: String,
+    pub start_hour_utc: u8,
+    pub start_minute_utc: u8,
+    pub repeat: RepeatPolicy,
+    pub active: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub run_count: u64,
+    pub locked_until: Option<i64>,
+    pub scheduler_job_id: Option<String>,
+    pub conversation_response_id: Option<String>,
+    pub thread_id: Option<i32>,
+    pub output_template: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub skip_weekends: bool,
+    pub skip_dates: Vec<String>,
+}
+
+impl From<SkippingScheduledPromptRecord> for ScheduledPromptRecord {
+    fn from(legacy: SkippingScheduledPromptRecord) -> Self {
+        Self {
+            id: legacy.id,
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            prompt: legacy.prompt,
+            start_hour_utc: legacy.start_hour_utc,
+            start_minute_utc: legacy.start_minute_utc,
+            repeat: legacy.repeat,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            last_run_at: legacy.last_run_at,
+            next_run_at: legacy.next_run_at,
+            run_count: legacy.run_count,
+            locked_until: legacy.locked_until,
+            scheduler_job_id: legacy.scheduler_job_id,
+            conversation_response_id: legacy.conversation_response_id,
+            thread_id: legacy.thread_id,
+            output_template: legacy.output_template,
+            model: legacy.model,
+            temperature: legacy.temperature,
+            skip_weekends: legacy.skip_weekends,
+            skip_dates: legacy.skip_dates,
+            output_delivery: OutputDelivery::Inline,
+            timezone: None,
+            local_start: None,
+        }
+    }
+}
+
+/// Record layout before weekend/holiday skip rules were added
+#[derive(Clone, Debug, Decode)]
+pub struct TunedScheduledPromptRecord {
+    pub id: String,
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub prompt: String,
+    pub start_hour_utc: u8,
+    pub start_minute_utc: u8,
+    pub repeat: RepeatPolicy,
+    pub active: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub run_count: u64,
+    pub locked_until: Option<i64>,
+    pub scheduler_job_id: Option<String>,
+    pub conversation_response_id: Option<String>,
+    pub thread_id: Option<i32>,
+    pub output_template: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+impl From<TunedScheduledPromptRecord> for ScheduledPromptRecord {
+    fn from(legacy: TunedScheduledPromptRecord) -> Self {
+        Self {
+            id: legacy.id,
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            prompt: legacy.prompt,
+            start_hour_utc: legacy.start_hour_utc,
+            start_minute_utc: legacy.start_minute_utc,
+            repeat: legacy.repeat,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            last_run_at: legacy.last_run_at,
+            next_run_at: legacy.next_run_at,
+            run_count: legacy.run_count,
+            locked_until: legacy.locked_until,
+            scheduler_job_id: legacy.scheduler_job_id,
+            conversation_response_id: legacy.conversation_response_id,
+            thread_id: legacy.thread_id,
+            output_template: legacy.output_template,
+            model: legacy.model,
+            temperature: legacy.temperature,
+            skip_weekends: false,
+            skip_dates: Vec::new(),
+            output_delivery: OutputDelivery::Inline,
+            timezone: None,
+            local_start: None,
+        }
+    }
+}
+
+/// Record layout before per-schedule model settings were added
+#[derive(Clone, Debug, Decode)]
+pub struct TemplatedScheduledPromptRecord {
+    pub id: String,
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub prompt: String,
+    pub start_hour_utc: u8,
+    pub start_minute_utc: u8,
+    pub repeat: RepeatPolicy,
+    pub active: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub run_count: u64,
+    pub locked_until: Option<i64>,
+    pub scheduler_job_id: Option<String>,
+    pub conversation_response_id: Option<String>,
+    pub thread_id: Option<i32>,
+    pub output_template: Option<String>,
+}
+
+impl From<TemplatedScheduledPromptRecord> for ScheduledPromptRecord {
+    fn from(legacy: TemplatedScheduledPromptRecord) -> Self {
+        Self {
+            id: legacy.id,
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            prompt: legacy.prompt,
+            start_hour_utc: legacy.start_hour_utc,
+            start_minute_utc: legacy.start_minute_utc,
+            repeat: legacy.repeat,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            last_run_at: legacy.last_run_at,
+            next_run_at: legacy.next_run_at,
+            run_count: legacy.run_count,
+            locked_until: legacy.locked_until,
+            scheduler_job_id: legacy.scheduler_job_id,
+            conversation_response_id: legacy.conversation_response_id,
+            thread_id: legacy.thread_id,
+            output_template: legacy.output_template,
+            model: None,
+            temperature: None,
+            skip_weekends: false,
+            skip_dates: Vec::new(),
+            output_delivery: OutputDelivery::Inline,
+            timezone: None,
+            local_start: None,
+        }
+    }
+}
+
+/// Record layout before output templates were added. bincode has no field
+/// defaults, so stored records are decoded with this shape as a fallback.
+#[derive(Clone, Debug, Decode)]
+pub struct LegacyScheduledPromptRecord {
+    pub id: String,
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub prompt: String,
+    pub start_hour_utc: u8,
+    pub start_minute_utc: u8,
+    pub repeat: RepeatPolicy,
+    pub active: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub run_count: u64,
+    pub locked_until: Option<i64>,
+    pub scheduler_job_id: Option<String>,
+    pub conversation_response_id: Option<String>,
+    pub thread_id: Option<i32>,
+}
+
+impl From<LegacyScheduledPromptRecord> for ScheduledPromptRecord {
+    fn from(legacy: LegacyScheduledPromptRecord) -> Self {
+        Self {
+            id: legacy.id,
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            prompt: legacy.prompt,
+            start_hour_utc: legacy.start_hour_utc,
+            start_minute_utc: legacy.start_minute_utc,
+            repeat: legacy.repeat,
+            active: legacy.active,
+            created_at: legacy.created_at,
+            last_run_at: legacy.last_run_at,
+            next_run_at: legacy.next_run_at,
+            run_count: legacy.run_count,
+            locked_until: legacy.locked_until,
+            scheduler_job_id: legacy.scheduler_job_id,
+            conversation_response_id: legacy.conversation_response_id,
+            thread_id: legacy.thread_id,
+            output_template: None,
+            model: None,
+            temperature: None,
+            skip_weekends: false,
+            skip_dates: Vec::new(),
+            output_delivery: OutputDelivery::Inline,
+            timezone: None,
+            local_start: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Encode, Decode)]
@@ -45,6 +389,12 @@ pub enum PendingStep {
     AwaitingMinute,
     AwaitingRepeat,
     AwaitingConfirm,
+    AwaitingTemplate,
+    /// Retired with the temperature setting; kept so stored wizard steps keep their index
+    #[allow(dead_code)]
+    AwaitingTemperature,
+    AwaitingSkipDates,
+    AwaitingTimezone,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Encode, Decode)]
@@ -54,8 +404,111 @@ pub struct PendingWizardState {
     pub creator_username: String,
     pub step: PendingStep,
     pub prompt: Option<String>,
-    pub hour_utc: Option<u8>,
-    pub minute_utc: Option<u8>,
+    /// Start hour in `timezone` (UTC when unset); converted to UTC when the schedule is saved
+    pub start_hour: Option<u8>,
+    /// Start minute in `timezone` (UTC when unset)
+    pub start_minute: Option<u8>,
     pub repeat: Option<RepeatPolicy>,
     pub thread_id: Option<i32>,
+    pub output_template: Option<String>,
+    pub model: Option<String>,
+    /// Unused, like the record's; kept for the stored layout
+    pub temperature: Option<f32>,
+    pub skip_weekends: bool,
+    pub skip_dates: Vec<String>,
+    pub output_delivery: OutputDelivery,
+    /// Unix seconds of the last wizard step; stale states are cleared
+    pub updated_at: i64,
+    /// IANA timezone picked in the wizard; UTC when unset
+    pub timezone: Option<String>,
+}
+
+/// Wizard state layout before the timezone step was added; decoded as a fallback
+#[derive(Clone, Debug, Decode)]
+pub struct StampedPendingWizardState {
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub step: PendingStep,
+    pub prompt: Option<String>,
+    pub start_hour: Option<u8>,
+    pub start_minute: Option<u8>,
+    pub repeat: Option<RepeatPolicy>,
+    pub thread_id: Option<i32>,
+    pub output_template: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub skip_weekends: bool,
+    pub skip_dates: Vec<String>,
+    pub output_delivery: OutputDelivery,
+    pub updated_at: i64,
+}
+
+impl From<StampedPendingWizardState> for PendingWizardState {
+    fn from(legacy: StampedPendingWizardState) -> Self {
+        Self {
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            step: legacy.step,
+            prompt: legacy.prompt,
+            start_hour: legacy.start_hour,
+            start_minute: legacy.start_minute,
+            repeat: legacy.repeat,
+            thread_id: legacy.thread_id,
+            output_template: legacy.output_template,
+            model: legacy.model,
+            temperature: legacy.temperature,
+            skip_weekends: legacy.skip_weekends,
+            skip_dates: legacy.skip_dates,
+            output_delivery: legacy.output_delivery,
+            updated_at: legacy.updated_at,
+            timezone: None,
+        }
+    }
+}
+
+/// Wizard state layout before `updated_at` was added; decoded as a fallback
+#[derive(Clone, Debug, Decode)]
+pub struct LegacyPendingWizardState {
+    pub group_id: i64,
+    pub creator_user_id: i64,
+    pub creator_username: String,
+    pub step: PendingStep,
+    pub prompt: Option<String>,
+    pub start_hour: Option<u8>,
+    pub start_minute: Option<u8>,
+    pub repeat: Option<RepeatPolicy>,
+    pub thread_id: Option<i32>,
+    pub output_template: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub skip_weekends: bool,
+    pub skip_dates: Vec<String>,
+    pub output_delivery: OutputDelivery,
+}
+
+impl From<LegacyPendingWizardState> for PendingWizardState {
+    fn from(legacy: LegacyPendingWizardState) -> Self {
+        Self {
+            group_id: legacy.group_id,
+            creator_user_id: legacy.creator_user_id,
+            creator_username: legacy.creator_username,
+            step: legacy.step,
+            prompt: legacy.prompt,
+            start_hour: legacy.start_hour,
+            start_minute: legacy.start_minute,
+            repeat: legacy.repeat,
+            thread_id: legacy.thread_id,
+            output_template: legacy.output_template,
+            model: legacy.model,
+            temperature: legacy.temperature,
+            skip_weekends: legacy.skip_weekends,
+            skip_dates: legacy.skip_dates,
+            output_delivery: legacy.output_delivery,
+            // Unknown age: treat as stale so the sweep clears it
+            updated_at: 0,
+            timezone: None,
+        }
+    }
 }
@@ -10,8 +10,15 @@ use uuid::Uuid;
 use crate::{
     dependencies::BotDependencies,
     scheduled_prompts::{
-        dto::{PendingStep, PendingWizardState, RepeatPolicy, ScheduledPromptRecord},
-        helpers::{build_hours_keyboard, summarize},
+        dto::{
+            OutputDelivery, PendingStep, PendingWizardState, RepeatPolicy, ScheduledPromptRecord,
+        },
+        helpers::{
+            build_confirm_keyboard, build_hours_keyboard, build_skip_keyboard,
+            build_timezone_keyboard, local_to_utc, model_settings_label, parse_skip_dates,
+            parse_timezone, skip_rules_label, start_time_label, summarize, timezone_label,
+            validate_template,
+        },
         runner::{register_all_schedules, register_schedule},
     },
     utils::{
@@ -76,14 +83,22 @@ pub async fn handle_scheduleprompt_command(
         creator_username: username,
         step: PendingStep::AwaitingPrompt,
         prompt: None,
-        hour_utc: None,
-        minute_utc: None,
+        start_hour: None,
+        start_minute: None,
         repeat: None,
         thread_id: if let Some(thread_id) = msg.thread_id {
             Some(thread_id.0.0)
         } else {
             None
         },
+        output_template: None,
+        model: None,
+        temperature: None,
+        skip_weekends: false,
+        skip_dates: Vec::new(),
+        output_delivery: OutputDelivery::Inline,
+        updated_at: Utc::now().timestamp(),
+        timezone: None,
     };
     bot_deps
         .scheduled_storage
@@ -95,7 +110,7 @@ pub async fn handle_scheduleprompt_command(
         msg,
         bot,
         format!(
-            "📝 Send the prompt you want to schedule — you can <b>reply to this message</b> or just <b>send it as your next message</b>.{}\n\nIf your prompt is rejected for using a forbidden action, <b>try again</b> with a safer prompt.",
+            "📝 Send the prompt you want to schedule — you can <b>reply to this message</b> or just <b>send it as your next message</b>.{}\n\nIf your prompt is rejected for using a forbidden action, <b>try again</b> with a safer prompt.\n\nSend /cancel at any step to stop; unfinished setups are cleared after 30 minutes.",
             note
         ),
     )
@@ -156,14 +171,27 @@ pub async fn handle_listscheduled_command(
             RepeatPolicy::Monthly => "Monthly".to_string(),
         };
         let title = format!(
-            "⏰ {:02}:{:02} UTC — {}\n\n{}",
-            rec.start_hour_utc,
-            rec.start_minute_utc,
+            "⏰ {} — {}\n\n{}\n\n🧠 {}{}{}{}",
+            start_time_label(&rec),
             repeat_label,
             if rec.prompt.len() > 180 {
                 format!("{}…", &rec.prompt[..180])
             } else {
                 rec.prompt.clone()
+            },
+            model_settings_label(rec.model.as_deref()),
+            skip_rules_label(rec.skip_weekends, &rec.skip_dates)
+                .map(|label| format!("\n📅 Skips {}", label))
+                .unwrap_or_default(),
+            if rec.output_template.is_some() {
+                "\n🎨 Custom output template"
+            } else {
+                ""
+            },
+            if rec.output_delivery == OutputDelivery::Inline {
+                String::new()
+            } else {
+                format!("\n📎 Output as {}", rec.output_delivery.label())
             }
         );
         let kb =
@@ -204,6 +232,23 @@ pub async fn finalize_and_register(
         return Ok(());
     }
 
+    // The wizard collects local time; the runner works in UTC and keeps it in step with DST
+    let local_hour = state.start_hour.unwrap_or(0);
+    let local_minute = state.start_minute.unwrap_or(0);
+    let timezone = state
+        .timezone
+        .as_deref()
+        .and_then(|name| parse_timezone(name).ok());
+    let (start_hour_utc, start_minute_utc) = match timezone {
+        Some(tz) => local_to_utc(
+            tz,
+            Utc::now().with_timezone(&tz).date_naive(),
+            local_hour,
+            local_minute,
+        ),
+        None => (local_hour, local_minute),
+    };
+
     let id = Uuid::new_v4().to_string();
     let mut rec = ScheduledPromptRecord {
         id: id.clone(),
@@ -211,8 +256,8 @@ pub async fn finalize_and_register(
         creator_user_id: state.creator_user_id,
         creator_username: state.creator_username.clone(),
         prompt: state.prompt.clone().unwrap_or_default(),
-        start_hour_utc: state.hour_utc.unwrap_or(0),
-        start_minute_utc: state.minute_utc.unwrap_or(0),
+        start_hour_utc,
+        start_minute_utc,
         repeat: state.repeat.clone().unwrap_or(RepeatPolicy::None),
         active: true,
         created_at: Utc::now().timestamp(),
@@ -223,6 +268,14 @@ pub async fn finalize_and_register(
         scheduler_job_id: None,
         conversation_response_id: None,
         thread_id: state.thread_id,
+        output_template: state.output_template.clone(),
+        model: state.model.clone(),
+        temperature: state.temperature,
+        skip_weekends: state.skip_weekends,
+        skip_dates: state.skip_dates.clone(),
+        output_delivery: state.output_delivery,
+        timezone: timezone.map(|tz| tz.name().to_string()),
+        local_start: timezone.map(|_| (local_hour, local_minute)),
     };
 
     bot_deps.scheduled_storage.put_schedule(&rec)?;
@@ -240,10 +293,18 @@ pub async fn finalize_and_register(
                 creator_username: rec.creator_username,
                 step: PendingStep::AwaitingConfirm,
                 prompt: Some(rec.prompt),
-                hour_utc: Some(rec.start_hour_utc),
-                minute_utc: Some(rec.start_minute_utc),
+                start_hour: Some(rec.local_start.map_or(rec.start_hour_utc, |(h, _)| h)),
+                start_minute: Some(rec.local_start.map_or(rec.start_minute_utc, |(_, m)| m)),
                 repeat: Some(rec.repeat),
                 thread_id: rec.thread_id,
+                output_template: rec.output_template,
+                model: rec.model,
+                temperature: rec.temperature,
+                skip_weekends: rec.skip_weekends,
+                skip_dates: rec.skip_dates,
+                output_delivery: rec.output_delivery,
+                updated_at: rec.created_at,
+                timezone: rec.timezone,
             })
         ),
     )
@@ -260,6 +321,135 @@ pub async fn handle_message_scheduled_prompts(
 ) -> Result<bool> {
     let key = (&msg.chat.id.0, &(user.id.0 as i64));
     if let Some(mut st) = bot_deps.scheduled_storage.get_pending(key) {
+        let text_cmd = msg.text().unwrap_or("").trim().to_lowercase();
+        if text_cmd == "/cancel" || text_cmd.starts_with("/cancel@") {
+            bot_deps.scheduled_storage.delete_pending(key)?;
+            send_message(
+                msg,
+                bot,
+                "✅ Cancelled scheduled prompt setup.".to_string(),
+            )
+            .await?;
+            return Ok(true);
+        }
+
+        if st.step == PendingStep::AwaitingTemplate {
+            let text_raw = msg.text().unwrap_or("");
+            if text_raw.trim().is_empty() || text_raw.trim_start().starts_with('/') {
+                return Ok(false);
+            }
+
+            if let Err(reason) = validate_template(text_raw.trim()) {
+                send_message(
+                    msg.clone(),
+                    bot,
+                    format!("❌ {}\n\nPlease send a corrected template.", reason),
+                )
+                .await?;
+                return Ok(true);
+            }
+
+            st.output_template = Some(text_raw.trim().to_string());
+            st.step = PendingStep::AwaitingConfirm;
+            if let Err(e) = bot_deps.scheduled_storage.put_pending(key, &st) {
+                log::error!("Failed to persist scheduled wizard state: {}", e);
+                send_message(
+                    msg.clone(),
+                    bot,
+                    "❌ Error saving schedule state. Please try /scheduleprompt again."
+                        .to_string(),
+                )
+                .await?;
+                return Ok(true);
+            }
+
+            bot.send_message(msg.chat.id, summarize(&st))
+                .reply_markup(build_confirm_keyboard(&st))
+                .await?;
+            return Ok(true);
+        }
+
+        if st.step == PendingStep::AwaitingSkipDates {
+            let text_raw = msg.text().unwrap_or("");
+            if text_raw.trim().is_empty() || text_raw.trim_start().starts_with('/') {
+                return Ok(false);
+            }
+
+            match parse_skip_dates(text_raw) {
+                Ok(dates) => st.skip_dates = dates,
+                Err(reason) => {
+                    send_message(
+                        msg.clone(),
+                        bot,
+                        format!("❌ {}\n\nPlease send the dates again.", reason),
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            }
+            st.step = PendingStep::AwaitingConfirm;
+            if let Err(e) = bot_deps.scheduled_storage.put_pending(key, &st) {
+                log::error!("Failed to persist scheduled wizard state: {}", e);
+                send_message(
+                    msg.clone(),
+                    bot,
+                    "❌ Error saving schedule state. Please try /scheduleprompt again."
+                        .to_string(),
+                )
+                .await?;
+                return Ok(true);
+            }
+
+            bot.send_message(msg.chat.id, summarize(&st))
+                .reply_markup(build_skip_keyboard(&st))
+                .await?;
+            return Ok(true);
+        }
+
+        if st.step == PendingStep::AwaitingTimezone {
+            let text_raw = msg.text().unwrap_or("");
+            if text_raw.trim().is_empty() || text_raw.trim_start().starts_with('/') {
+                return Ok(false);
+            }
+
+            let tz = match parse_timezone(text_raw) {
+                Ok(tz) => tz,
+                Err(reason) => {
+                    send_message(
+                        msg.clone(),
+                        bot,
+                        format!("❌ {}\n\nPlease send a valid timezone.", reason),
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+            };
+            st.timezone = (tz != chrono_tz::UTC).then(|| tz.name().to_string());
+            st.step = PendingStep::AwaitingHour;
+            if let Err(e) = bot_deps.scheduled_storage.put_pending(key, &st) {
+                log::error!("Failed to persist scheduled wizard state: {}", e);
+                send_message(
+                    msg.clone(),
+                    bot,
+                    "❌ Error saving schedule state. Please try /scheduleprompt again."
+                        .to_string(),
+                )
+                .await?;
+                return Ok(true);
+            }
+
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Select start hour ({})",
+                    timezone_label(st.timezone.as_deref())
+                ),
+            )
+            .reply_markup(build_hours_keyboard())
+            .await?;
+            return Ok(true);
+        }
+
         if st.step == PendingStep::AwaitingPrompt {
             // Accept prompt if message is a reply OR a regular follow-up (non-command) from the same user
             let is_reply = msg.reply_to_message().is_some();
@@ -313,7 +503,7 @@ pub async fn handle_message_scheduled_prompts(
                 }
 
                 st.prompt = Some(text);
-                st.step = PendingStep::AwaitingHour;
+                st.step = PendingStep::AwaitingTimezone;
                 if let Err(e) = bot_deps.scheduled_storage.put_pending(key, &st) {
                     log::error!("Failed to persist scheduled wizard state: {}", e);
                     send_message(
@@ -325,12 +515,12 @@ pub async fn handle_message_scheduled_prompts(
                     .await?;
                     return Ok(true);
                 }
-                let kb = build_hours_keyboard();
+                let kb = build_timezone_keyboard();
                 send_markdown_message_with_keyboard(
                     bot,
                     msg,
                     KeyboardMarkupType::InlineKeyboardType(kb),
-                    "Select start hour (UTC)",
+                    "Select the timezone for the start time",
                 )
                 .await?;
                 return Ok(true);
@@ -1,5 +1,349 @@
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use regex::Regex;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
-use crate::scheduled_prompts::dto::{PendingWizardState, RepeatPolicy};
+use teloxide::utils::html;
+use crate::scheduled_prompts::dto::{
+    OutputDelivery, PendingWizardState, RepeatPolicy, ScheduledPromptRecord,
+};
+use crate::user_model_preferences::dto::ChatModel;
+
+pub const MAX_TEMPLATE_LEN: usize = 500;
+pub const MAX_SKIP_DATES: usize = 50;
+/// Zones offered as buttons in the wizard; any other IANA name can be typed
+const TIMEZONE_PRESETS: &[&str] = &[
+    "UTC",
+    "Europe/London",
+    "Europe/Berlin",
+    "Europe/Moscow",
+    "America/New_York",
+    "America/Chicago",
+    "America/Los_Angeles",
+    "America/Sao_Paulo",
+    "Asia/Dubai",
+    "Asia/Kolkata",
+    "Asia/Singapore",
+    "Asia/Tokyo",
+    "Australia/Sydney",
+];
+pub const TEMPLATE_OUTPUT_PLACEHOLDER: &str = "{output}";
+/// Tags Telegram accepts in HTML parse mode
+const ALLOWED_TEMPLATE_TAGS: &[&str] = &[
+    "b", "strong", "i", "em", "u", "ins", "s", "strike", "del", "code", "pre", "a",
+    "blockquote", "tg-spoiler", "span",
+];
+
+/// Check a scheduled output template before it is saved
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.chars().count() > MAX_TEMPLATE_LEN {
+        return Err(format!(
+            "Template is too long (max {} characters).",
+            MAX_TEMPLATE_LEN
+        ));
+    }
+    if !template.contains(TEMPLATE_OUTPUT_PLACEHOLDER) {
+        return Err("Template must include the {output} placeholder.".to_string());
+    }
+
+    let tag_re = Regex::new(r"<(/?)([a-zA-Z-]+)(\s[^<>]*)?>").unwrap();
+    let mut open_tags: Vec<String> = Vec::new();
+    let mut last_end = 0;
+    for cap in tag_re.captures_iter(template) {
+        let whole = cap.get(0).unwrap();
+        if template[last_end..whole.start()].contains(['<', '>']) {
+            return Err("Use &lt; and &gt; for literal angle brackets.".to_string());
+        }
+        last_end = whole.end();
+
+        let name = cap[2].to_lowercase();
+        if !ALLOWED_TEMPLATE_TAGS.contains(&name.as_str()) {
+            return Err(format!("Tag <{}> is not supported by Telegram.", name));
+        }
+        if &cap[1] == "/" {
+            if open_tags.pop().as_deref() != Some(name.as_str()) {
+                return Err(format!("Closing tag </{}> does not match an open tag.", name));
+            }
+        } else {
+            open_tags.push(name);
+        }
+    }
+    if template[last_end..].contains(['<', '>']) {
+        return Err("Use &lt; and &gt; for literal angle brackets.".to_string());
+    }
+    if let Some(unclosed) = open_tags.pop() {
+        return Err(format!("Tag <{}> is never closed.", unclosed));
+    }
+
+    Ok(())
+}
+
+/// "GPT-5" style label, with the default spelled out
+pub fn model_settings_label(model: Option<&str>) -> String {
+    model
+        .and_then(ChatModel::from_name)
+        .map(|m| m.to_display_string().to_string())
+        .unwrap_or_else(|| "creator's default".to_string())
+}
+
+/// Parse holiday dates typed as "2025-12-25, 2026-01-01"; returns them sorted and deduplicated
+pub fn parse_skip_dates(raw: &str) -> Result<Vec<String>, String> {
+    let mut dates: Vec<NaiveDate> = Vec::new();
+    for part in raw.split(|c: char| c == ',' || c.is_whitespace()) {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        let date = NaiveDate::parse_from_str(part, "%Y-%m-%d")
+            .map_err(|_| format!("\"{}\" is not a date like 2025-12-25.", part))?;
+        dates.push(date);
+    }
+    if dates.is_empty() {
+        return Err("Send at least one date, e.g. 2025-12-25.".to_string());
+    }
+    dates.sort();
+    dates.dedup();
+    if dates.len() > MAX_SKIP_DATES {
+        return Err(format!("At most {} dates can be skipped.", MAX_SKIP_DATES));
+    }
+    Ok(dates
+        .iter()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .collect())
+}
+
+/// True when a run falling on `date` should be skipped
+pub fn is_skipped_day(date: NaiveDate, skip_weekends: bool, skip_dates: &[String]) -> bool {
+    if skip_weekends && matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        return true;
+    }
+    let date = date.format("%Y-%m-%d").to_string();
+    skip_dates.iter().any(|d| *d == date)
+}
+
+/// "weekends · 2025-12-25, 2026-01-01"; `None` when nothing is skipped
+pub fn skip_rules_label(skip_weekends: bool, skip_dates: &[String]) -> Option<String> {
+    let mut parts = Vec::new();
+    if skip_weekends {
+        parts.push("weekends".to_string());
+    }
+    if !skip_dates.is_empty() {
+        parts.push(skip_dates.join(", "));
+    }
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+/// Substitute `{output}`, `{date}`, `{time}` and `{datetime}` (UTC) into a template
+pub fn apply_template(template: &str, output: &str, now: DateTime<Utc>) -> String {
+    template
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M UTC").to_string())
+        .replace("{datetime}", &now.format("%Y-%m-%d %H:%M UTC").to_string())
+        .replace(TEMPLATE_OUTPUT_PLACEHOLDER, output)
+}
+
+/// Self-contained HTML document for an output attached as a file. The output is escaped
+/// into a `<pre>` block, so whatever the model wrote shows as text and never runs as markup
+/// or script; built in memory, so nothing touches disk.
+pub fn output_attachment(schedule_id: &str, output: &str, now: DateTime<Utc>) -> (String, Vec<u8>) {
+    let short_id: String = schedule_id.chars().take(8).collect();
+    let file_name = format!("report-{}-{}.html", short_id, now.format("%Y-%m-%d-%H%M"));
+    let document = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Scheduled report {}</title></head>\n<body>\n<pre style=\"white-space: pre-wrap; font-family: sans-serif;\">{}</pre>\n</body>\n</html>\n",
+        now.format("%Y-%m-%d %H:%M UTC"),
+        html::escape(output)
+    );
+    (file_name, document.into_bytes())
+}
+
+pub fn build_confirm_keyboard(state: &PendingWizardState) -> InlineKeyboardMarkup {
+    let has_template = state.output_template.is_some();
+    let mut rows = vec![vec![InlineKeyboardButton::callback(
+        if has_template {
+            "🎨 Change output template"
+        } else {
+            "🎨 Add output template"
+        },
+        "sched_template",
+    )]];
+    if has_template {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "🧹 Remove template",
+            "sched_template_clear",
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "🧠 Model",
+        "sched_settings",
+    )]);
+    rows.push(vec![InlineKeyboardButton::callback(
+        "📅 Skip weekends/holidays",
+        "sched_skip",
+    )]);
+    rows.push(vec![InlineKeyboardButton::callback(
+        format!("📎 Output as: {}", state.output_delivery.label()),
+        "sched_delivery",
+    )]);
+    rows.push(vec![InlineKeyboardButton::callback(
+        "✔️ Create schedule",
+        "sched_confirm",
+    )]);
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Model choices for the schedule; "default" clears the override
+pub fn build_settings_keyboard(state: &PendingWizardState) -> InlineKeyboardMarkup {
+    let mark = |selected: bool, label: &str| {
+        if selected {
+            format!("✅ {}", label)
+        } else {
+            label.to_string()
+        }
+    };
+
+    let mut model_row = vec![InlineKeyboardButton::callback(
+        mark(state.model.is_none(), "Default model"),
+        "sched_model:default",
+    )];
+    for model in ChatModel::ALL {
+        model_row.push(InlineKeyboardButton::callback(
+            mark(
+                state.model.as_deref() == Some(model.canonical_name()),
+                model.to_display_string(),
+            ),
+            format!("sched_model:{}", model.canonical_name()),
+        ));
+    }
+
+    InlineKeyboardMarkup::new(vec![
+        model_row,
+        vec![InlineKeyboardButton::callback("⬅️ Back", "sched_settings_done")],
+    ])
+}
+
+/// Weekend toggle and holiday list for the schedule
+pub fn build_skip_keyboard(state: &PendingWizardState) -> InlineKeyboardMarkup {
+    let mut rows = vec![
+        vec![InlineKeyboardButton::callback(
+            if state.skip_weekends {
+                "✅ Skip weekends"
+            } else {
+                "Skip weekends"
+            },
+            "sched_skip_weekends",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "✏️ Set holiday dates",
+            "sched_skip_dates",
+        )],
+    ];
+    if !state.skip_dates.is_empty() {
+        rows.push(vec![InlineKeyboardButton::callback(
+            "🧹 Clear holiday dates",
+            "sched_skip_dates_clear",
+        )]);
+    }
+    rows.push(vec![InlineKeyboardButton::callback(
+        "⬅️ Back",
+        "sched_settings_done",
+    )]);
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Parse an IANA timezone name such as "Europe/Berlin"
+pub fn parse_timezone(input: &str) -> Result<Tz, String> {
+    input.trim().parse::<Tz>().map_err(|_| {
+        format!(
+            "\"{}\" is not a known timezone. Use an IANA name like Europe/Berlin or America/New_York.",
+            input.trim()
+        )
+    })
+}
+
+/// The timezone name shown to users; UTC when none is set
+pub fn timezone_label(timezone: Option<&str>) -> &str {
+    timezone.unwrap_or("UTC")
+}
+
+/// UTC hour and minute of `hour:minute` local time in `tz` on `date`. A local time
+/// skipped by a DST jump runs an hour later; a repeated one runs at its first occurrence.
+pub fn local_to_utc(tz: Tz, date: NaiveDate, hour: u8, minute: u8) -> (u8, u8) {
+    let local = date
+        .and_hms_opt(hour as u32, minute as u32, 0)
+        .unwrap_or_default();
+    let resolved = tz.from_local_datetime(&local).earliest().or_else(|| {
+        tz.from_local_datetime(&(local + chrono::Duration::hours(1)))
+            .earliest()
+    });
+    match resolved {
+        Some(dt) => {
+            let utc = dt.with_timezone(&Utc);
+            (utc.hour() as u8, utc.minute() as u8)
+        }
+        None => (hour, minute),
+    }
+}
+
+/// Today's date where the schedule runs, for skip rules
+pub fn schedule_date(timezone: Option<&str>, now: DateTime<Utc>) -> NaiveDate {
+    match timezone.and_then(|name| name.parse::<Tz>().ok()) {
+        Some(tz) => now.with_timezone(&tz).date_naive(),
+        None => now.date_naive(),
+    }
+}
+
+/// Recompute the UTC start of a timezone-bound schedule for today's offset in its zone.
+/// Returns how many seconds the start moved, if it did, so pending runs can follow it.
+pub fn refresh_utc_start(rec: &mut ScheduledPromptRecord, now: DateTime<Utc>) -> Option<i64> {
+    let (hour, minute) = rec.local_start?;
+    let tz = rec.timezone.as_deref()?.parse::<Tz>().ok()?;
+    let (utc_hour, utc_minute) =
+        local_to_utc(tz, now.with_timezone(&tz).date_naive(), hour, minute);
+    if (utc_hour, utc_minute) == (rec.start_hour_utc, rec.start_minute_utc) {
+        return None;
+    }
+
+    let old = rec.start_hour_utc as i64 * 60 + rec.start_minute_utc as i64;
+    let new = utc_hour as i64 * 60 + utc_minute as i64;
+    // Take the short way round midnight: an offset change is never more than half a day
+    let mut shift = new - old;
+    if shift > 12 * 60 {
+        shift -= 24 * 60;
+    } else if shift < -12 * 60 {
+        shift += 24 * 60;
+    }
+    rec.start_hour_utc = utc_hour;
+    rec.start_minute_utc = utc_minute;
+    Some(shift * 60)
+}
+
+/// Start time as shown in lists, e.g. "09:00 Europe/Berlin (07:00 UTC)"
+pub fn start_time_label(rec: &ScheduledPromptRecord) -> String {
+    match (rec.timezone.as_deref(), rec.local_start) {
+        (Some(timezone), Some((hour, minute))) => format!(
+            "{:02}:{:02} {} ({:02}:{:02} UTC)",
+            hour, minute, timezone, rec.start_hour_utc, rec.start_minute_utc
+        ),
+        _ => format!("{:02}:{:02} UTC", rec.start_hour_utc, rec.start_minute_utc),
+    }
+}
+
+pub fn build_timezone_keyboard() -> InlineKeyboardMarkup {
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = TIMEZONE_PRESETS
+        .chunks(2)
+        .map(|pair| {
+            pair.iter()
+                .map(|tz| {
+                    InlineKeyboardButton::callback(tz.to_string(), format!("sched_tz:{}", tz))
+                })
+                .collect()
+        })
+        .collect();
+    rows.push(vec![InlineKeyboardButton::callback(
+        "✏️ Other timezone",
+        "sched_tz_other",
+    )]);
+    InlineKeyboardMarkup::new(rows)
+}
 
 pub fn build_hours_keyboard() -> InlineKeyboardMarkup {
     let mut rows: Vec<Vec<InlineKeyboardButton>> = Vec::new();
@@ -69,8 +413,8 @@ pub fn build_repeat_keyboard() -> InlineKeyboardMarkup {
 
 pub fn summarize(state: &PendingWizardState) -> String {
     let prompt = state.prompt.as_deref().unwrap_or("");
-    let hour = state.hour_utc.map(|h| format!("{:02}", h)).unwrap_or("--".into());
-    let minute = state.minute_utc.map(|m| format!("{:02}", m)).unwrap_or("--".into());
+    let hour = state.start_hour.map(|h| format!("{:02}", h)).unwrap_or("--".into());
+    let minute = state.start_minute.map(|m| format!("{:02}", m)).unwrap_or("--".into());
     let repeat = match state.repeat {
         Some(RepeatPolicy::None) => "No repeat".to_string(),
         Some(RepeatPolicy::Every5m) => "Every 5 min".to_string(),
@@ -86,10 +430,95 @@ pub fn summarize(state: &PendingWizardState) -> String {
         Some(RepeatPolicy::Monthly) => "Monthly".to_string(),
         None => "--".to_string(),
     };
+    let template = match &state.output_template {
+        Some(t) => format!("\nTemplate: \n{}", t),
+        None => String::new(),
+    };
+    let skips = skip_rules_label(state.skip_weekends, &state.skip_dates)
+        .map(|label| format!("\nSkips: {}", label))
+        .unwrap_or_default();
+    let delivery = if state.output_delivery == OutputDelivery::Inline {
+        String::new()
+    } else {
+        format!("\nOutput: {}", state.output_delivery.label())
+    };
     format!(
-        "🗓️ Schedule summary (UTC)\n\nPrompt: \n{}\n\nStart: {}:{} UTC\nRepeat: {}",
-        prompt, hour, minute, repeat
+        "🗓️ Schedule summary\n\nPrompt: \n{}\n\nStart: {}:{} {}\nRepeat: {}\nModel: {}{}{}{}",
+        prompt,
+        hour,
+        minute,
+        timezone_label(state.timezone.as_deref()),
+        repeat,
+        model_settings_label(state.model.as_deref()),
+        skips,
+        delivery,
+        template
     )
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduled_prompts::dto::AUTO_ATTACH_OVER_CHARS;
+
+    #[test]
+    fn test_parse_skip_dates() {
+        assert_eq!(
+            parse_skip_dates("2026-01-01, 2025-12-25 2025-12-25"),
+            Ok(vec!["2025-12-25".to_string(), "2026-01-01".to_string()])
+        );
+        assert!(parse_skip_dates("").is_err());
+        assert!(parse_skip_dates("25/12/2025").is_err());
+    }
+
+    #[test]
+    fn test_is_skipped_day() {
+        let saturday = NaiveDate::from_ymd_opt(2025, 12, 27).unwrap();
+        let christmas = NaiveDate::from_ymd_opt(2025, 12, 25).unwrap();
+        let holidays = vec!["2025-12-25".to_string()];
+        assert!(is_skipped_day(saturday, true, &[]));
+        assert!(!is_skipped_day(saturday, false, &holidays));
+        assert!(is_skipped_day(christmas, false, &holidays));
+        assert!(!is_skipped_day(christmas, true, &[]));
+    }
+
+    #[test]
+    fn test_model_settings_label() {
+        assert_eq!(model_settings_label(None), "creator's default");
+        assert_eq!(model_settings_label(Some("GPT5")), "GPT-5");
+    }
+
+    #[test]
+    fn test_local_to_utc_follows_dst() {
+        let berlin = parse_timezone("Europe/Berlin").unwrap();
+        let winter = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let summer = NaiveDate::from_ymd_opt(2026, 7, 15).unwrap();
+        assert_eq!(local_to_utc(berlin, winter, 9, 0), (8, 0));
+        assert_eq!(local_to_utc(berlin, summer, 9, 0), (7, 0));
+        // 02:30 doesn't exist on the spring-forward day
+        let spring = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
+        assert_eq!(local_to_utc(berlin, spring, 2, 30), (1, 30));
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_output_delivery_attaches() {
+        assert!(!OutputDelivery::Inline.attaches(100_000));
+        assert!(!OutputDelivery::Auto.attaches(AUTO_ATTACH_OVER_CHARS));
+        assert!(OutputDelivery::Auto.attaches(AUTO_ATTACH_OVER_CHARS + 1));
+        assert!(OutputDelivery::File.attaches(1));
+    }
+
+    #[test]
+    fn test_output_attachment_escapes_output() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 15, 9, 30, 0).unwrap();
+        let (file_name, bytes) =
+            output_attachment("abcdef123456", "<b>Hi</b><script>alert(1)</script>", now);
+        let document = String::from_utf8(bytes).unwrap();
+        assert_eq!(file_name, "report-abcdef12-2026-01-15-0930.html");
+        assert!(document.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!document.contains("<script>"));
+    }
+}
@@ -1,7 +1,8 @@
 use chrono::{Datelike, TimeZone, Timelike, Utc};
 use teloxide::{
     prelude::*,
-    types::{ChatId, ParseMode},
+    sugar::request::RequestReplyExt,
+    types::{ChatId, InputFile, MessageId, ParseMode},
 };
 use tokio_cron_scheduler::Job;
 
@@ -9,6 +10,9 @@ use crate::utils::{create_purchase_request, send_scheduled_message};
 use crate::{
     dependencies::BotDependencies,
     scheduled_prompts::dto::{RepeatPolicy, ScheduledPromptRecord},
+    scheduled_prompts::helpers::{
+        apply_template, is_skipped_day, output_attachment, refresh_utc_start, schedule_date,
+    },
     scheduled_prompts::storage::ScheduledStorage,
     user_model_preferences::dto::ChatModel,
 };
@@ -123,6 +127,56 @@ async fn send_long_message(
     parts.len()
 }
 
+/// Post the output as an HTML file (after the generated image, if any) instead of a
+/// run of messages. Everything stays in memory, so there is no temp file to clean up.
+async fn send_output_attachment(
+    bot: &Bot,
+    chat_id: ChatId,
+    rec: &ScheduledPromptRecord,
+    image_data: Option<Vec<u8>>,
+    text: &str,
+) {
+    if let Some(image_data) = image_data {
+        let mut request = bot.send_photo(chat_id, InputFile::memory(image_data));
+        if let Some(thread) = rec.thread_id {
+            request = request.reply_to(MessageId(thread));
+        }
+        if let Err(e) = request.await {
+            log::error!("[sched:{}] failed sending image to chat {}: {}", rec.id, chat_id.0, e);
+        }
+    }
+
+    let (file_name, bytes) = output_attachment(&rec.id, text, Utc::now());
+    let size = bytes.len();
+    let mut request = bot
+        .send_document(chat_id, InputFile::memory(bytes).file_name(file_name))
+        .caption(format!(
+            "📎 Scheduled report ({} characters) — open the attached file to read it.",
+            text.chars().count()
+        ));
+    if let Some(thread) = rec.thread_id {
+        request = request.reply_to(MessageId(thread));
+    }
+    match request.await {
+        Ok(msg) => log::info!(
+            "[sched:{}] sent output as file ({} bytes) to chat {} (msg_id={})",
+            rec.id,
+            size,
+            chat_id.0,
+            msg.id.0
+        ),
+        Err(e) => {
+            log::error!(
+                "[sched:{}] failed sending output file to chat {}, falling back to messages: {}",
+                rec.id,
+                chat_id.0,
+                e
+            );
+            send_long_message(bot, chat_id, text, rec.thread_id).await;
+        }
+    }
+}
+
 fn next_every_n_minutes_at(n: u32, start_minute: u8) -> i64 {
     let now = Utc::now();
     let m = now.minute();
@@ -220,10 +274,7 @@ pub async fn register_all_schedules(bot: Bot, bot_deps: BotDependencies) -> anyh
     let storage = ScheduledStorage::new(&bot_deps.db)?;
     for item in storage.scheduled.iter() {
         if let Ok((_, ivec)) = item {
-            if let Ok((mut rec, _)) = bincode::decode_from_slice::<ScheduledPromptRecord, _>(
-                &ivec,
-                bincode::config::standard(),
-            ) {
+            if let Some(mut rec) = ScheduledStorage::decode_schedule(&ivec) {
                 if rec.active {
                     if let Err(e) = register_schedule(bot.clone(), bot_deps.clone(), &mut rec).await
                     {
@@ -248,13 +299,18 @@ pub async fn register_schedule(
     bot_deps: BotDependencies,
     record: &mut ScheduledPromptRecord,
 ) -> anyhow::Result<()> {
+    // Pick up any DST change since the record was last saved
+    if let Some(shift) = refresh_utc_start(record, Utc::now()) {
+        record.next_run_at = record.next_run_at.map(|ts| ts + shift);
+    }
     log::info!(
-        "Registering schedule: id={}, group={}, repeat={:?}, start={:02}:{:02} UTC",
+        "Registering schedule: id={}, group={}, repeat={:?}, start={:02}:{:02} UTC, tz={:?}",
         record.id,
         record.group_id,
         record.repeat,
         record.start_hour_utc,
-        record.start_minute_utc
+        record.start_minute_utc,
+        record.timezone
     );
     let schedule_id = record.id.clone();
     let scheduler = bot_deps.scheduler.clone();
@@ -289,8 +345,25 @@ pub async fn register_schedule(
                 return;
             }
 
-            // Overlap throttle
+            // Timezone-bound schedules follow their zone's current offset, so DST changes
+            // move the UTC start (and any pending run) instead of shifting the local time
             let now_ts = Utc::now().timestamp();
+            if let Some(shift) = refresh_utc_start(&mut rec, Utc::now()) {
+                rec.next_run_at = rec.next_run_at.map(|ts| ts + shift);
+                log::info!(
+                    "[sched:{}] UTC start moved to {:02}:{:02} for {:?}; next_run_at={:?}",
+                    schedule_id,
+                    rec.start_hour_utc,
+                    rec.start_minute_utc,
+                    rec.timezone,
+                    rec.next_run_at
+                );
+                if let Err(e) = bot_deps.scheduled_storage.put_schedule(&rec) {
+                    log::warn!("Failed to persist UTC start for schedule {}: {}", schedule_id, e);
+                }
+            }
+
+            // Overlap throttle
             if let Some(locked) = rec.locked_until {
                 if now_ts < locked {
                     log::debug!(
@@ -306,7 +379,7 @@ pub async fn register_schedule(
             // Check timing conditions
             match rec.repeat {
                 RepeatPolicy::None | RepeatPolicy::Daily => {
-                    // Should run at specific hour:minute UTC
+                    // Should run at specific hour:minute UTC (refreshed above for local zones)
                     let now = Utc::now();
                     if now.minute() as u8 != rec.start_minute_utc {
                         log::trace!(
@@ -355,6 +428,27 @@ pub async fn register_schedule(
                 }
             }
 
+            // Weekend/holiday rules: move to the next slot without running or counting a run
+            let today = schedule_date(rec.timezone.as_deref(), Utc::now());
+            if is_skipped_day(today, rec.skip_weekends, &rec.skip_dates) {
+                rec.next_run_at = Some(add_interval_from(
+                    now_ts,
+                    &rec.repeat,
+                    rec.start_hour_utc,
+                    rec.start_minute_utc,
+                ));
+                log::info!(
+                    "[sched:{}] skipped for {} (skip rules); next_run_at={:?}",
+                    schedule_id,
+                    today,
+                    rec.next_run_at
+                );
+                if let Err(e) = bot_deps.scheduled_storage.put_schedule(&rec) {
+                    log::warn!("Failed to persist skip for schedule {}: {}", schedule_id, e);
+                }
+                return;
+            }
+
             // Lock for 120s
             rec.locked_until = Some(now_ts + 120);
             let storage = bot_deps.scheduled_storage.clone();
@@ -366,8 +460,13 @@ pub async fn register_schedule(
             let prefs = bot_deps
                 .user_model_prefs
                 .get_preferences(&rec.creator_username);
-            let _model = prefs.chat_model.to_openai_model();
-            let temperature: Option<f32> = match prefs.chat_model {
+            // Per-schedule settings win over the creator's preferences
+            let schedule_model = rec
+                .model
+                .as_deref()
+                .and_then(ChatModel::from_name)
+                .unwrap_or(prefs.chat_model.clone());
+            let temperature: Option<f32> = match schedule_model {
                 ChatModel::GPT5 | ChatModel::GPT5Mini => None,
             };
 
@@ -381,10 +480,7 @@ pub async fn register_schedule(
             );
 
             // Execute AI as group scheduled prompt
-            let chat_model: Model = match prefs.chat_model {
-                ChatModel::GPT5 => Model::GPT5,
-                ChatModel::GPT5Mini => Model::GPT5Mini,
-            };
+            let chat_model: Model = schedule_model.to_openai_model();
 
             let creator_user_id = rec.creator_user_id;
 
@@ -408,9 +504,25 @@ pub async fn register_schedule(
 
             match ai_call {
                 Ok((ai_response, new_resp_id)) => {
-                    // Send output
-                    let text_out = ai_response.text.clone();
-                    if let Some(image_data) = ai_response.image_data.clone() {
+                    // Send output, wrapped in the schedule's template when one is set
+                    let text_out = match &rec.output_template {
+                        Some(template) if !ai_response.text.trim().is_empty() => {
+                            apply_template(template, &ai_response.text, Utc::now())
+                        }
+                        _ => ai_response.text.clone(),
+                    };
+                    let attach = !text_out.trim().is_empty()
+                        && rec.output_delivery.attaches(text_out.chars().count());
+                    if attach {
+                        send_output_attachment(
+                            &bot,
+                            group_chat_id,
+                            &rec,
+                            ai_response.image_data.clone(),
+                            &text_out,
+                        )
+                        .await;
+                    } else if let Some(image_data) = ai_response.image_data.clone() {
                         let photo = teloxide::types::InputFile::memory(image_data);
                         if text_out.trim().is_empty() {
                             match bot.send_photo(group_chat_id, photo).await {
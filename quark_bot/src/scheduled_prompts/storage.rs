@@ -1,8 +1,15 @@
-use crate::scheduled_prompts::dto::{PendingWizardState, ScheduledPromptRecord};
+use crate::scheduled_prompts::dto::{
+    DeliveringScheduledPromptRecord, LegacyPendingWizardState, LegacyScheduledPromptRecord,
+    PendingWizardState, ScheduledPromptRecord, SkippingScheduledPromptRecord,
+    StampedPendingWizardState, TemplatedScheduledPromptRecord, TunedScheduledPromptRecord,
+};
+use chrono::Utc;
 use sled::{Db, IVec, Tree};
 
 const SCHEDULED_PROMPTS_TREE: &str = "scheduled_prompts";
 const SCHEDULED_PROMPT_PENDING_TREE: &str = "scheduled_prompt_pending";
+/// A wizard untouched for this long is abandoned and cleared
+pub const PENDING_WIZARD_TTL_SECS: i64 = 30 * 60;
 
 #[derive(Clone)]
 pub struct ScheduledStorage {
@@ -24,19 +31,42 @@ impl ScheduledStorage {
         Ok(())
     }
 
+    /// Decode a stored schedule, accepting records written before timezones, output
+    /// delivery, skip rules, model settings or output templates. Newest layout first: older shapes are
+    /// prefixes of it.
+    pub fn decode_schedule(bytes: &[u8]) -> Option<ScheduledPromptRecord> {
+        let config = bincode::config::standard();
+        bincode::decode_from_slice::<ScheduledPromptRecord, _>(bytes, config)
+            .map(|(v, _)| v)
+            .or_else(|_| {
+                bincode::decode_from_slice::<DeliveringScheduledPromptRecord, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .or_else(|_| {
+                bincode::decode_from_slice::<SkippingScheduledPromptRecord, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .or_else(|_| {
+                bincode::decode_from_slice::<TunedScheduledPromptRecord, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .or_else(|_| {
+                bincode::decode_from_slice::<TemplatedScheduledPromptRecord, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .or_else(|_| {
+                bincode::decode_from_slice::<LegacyScheduledPromptRecord, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .ok()
+    }
+
     pub fn get_schedule(&self, id: &str) -> Option<ScheduledPromptRecord> {
         self.scheduled
             .get(id.as_bytes())
             .ok()
             .flatten()
-            .and_then(|ivec: IVec| {
-                bincode::decode_from_slice::<ScheduledPromptRecord, _>(
-                    &ivec,
-                    bincode::config::standard(),
-                )
-                .ok()
-                .map(|(v, _)| v)
-            })
+            .and_then(|ivec: IVec| Self::decode_schedule(&ivec))
     }
 
     #[allow(dead_code)]
@@ -49,10 +79,7 @@ impl ScheduledStorage {
         let mut out = Vec::new();
         for kv in self.scheduled.iter() {
             if let Ok((_k, ivec)) = kv {
-                if let Ok((rec, _)) = bincode::decode_from_slice::<ScheduledPromptRecord, _>(
-                    &ivec,
-                    bincode::config::standard(),
-                ) {
+                if let Some(rec) = Self::decode_schedule(&ivec) {
                     if rec.group_id == group_id && rec.active {
                         out.push(rec);
                     }
@@ -62,20 +89,67 @@ impl ScheduledStorage {
         out
     }
 
+    /// Store the wizard state, stamping it as touched now
     pub fn put_pending(&self, key: (&i64, &i64), state: &PendingWizardState) -> sled::Result<()> {
         let k = Self::pending_key_bytes(key);
-        let bytes = bincode::encode_to_vec(state, bincode::config::standard()).unwrap();
+        let mut state = state.clone();
+        state.updated_at = Utc::now().timestamp();
+        let bytes = bincode::encode_to_vec(&state, bincode::config::standard()).unwrap();
         self.pending.insert(k, bytes)?;
         Ok(())
     }
 
+    fn decode_pending(bytes: &[u8]) -> Option<PendingWizardState> {
+        let config = bincode::config::standard();
+        bincode::decode_from_slice::<PendingWizardState, _>(bytes, config)
+            .map(|(v, _)| v)
+            .or_else(|_| {
+                bincode::decode_from_slice::<StampedPendingWizardState, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .or_else(|_| {
+                bincode::decode_from_slice::<LegacyPendingWizardState, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .ok()
+    }
+
+    fn is_stale(state: &PendingWizardState, now: i64) -> bool {
+        now - state.updated_at > PENDING_WIZARD_TTL_SECS
+    }
+
+    /// The wizard state for this chat and user; an abandoned one is removed and not returned
     pub fn get_pending(&self, key: (&i64, &i64)) -> Option<PendingWizardState> {
         let k = Self::pending_key_bytes(key);
-        self.pending.get(k).ok().flatten().and_then(|ivec: IVec| {
-            bincode::decode_from_slice::<PendingWizardState, _>(&ivec, bincode::config::standard())
-                .ok()
-                .map(|(v, _)| v)
-        })
+        let state = self
+            .pending
+            .get(&k)
+            .ok()
+            .flatten()
+            .and_then(|ivec: IVec| Self::decode_pending(&ivec))?;
+        if Self::is_stale(&state, Utc::now().timestamp()) {
+            let _ = self.pending.remove(k);
+            return None;
+        }
+        Some(state)
+    }
+
+    /// Remove wizard states nobody has touched within the TTL, plus unreadable ones
+    pub fn sweep_stale_pending(&self) -> usize {
+        let now = Utc::now().timestamp();
+        let stale: Vec<IVec> = self
+            .pending
+            .iter()
+            .filter_map(|kv| kv.ok())
+            .filter(|(_, v)| Self::decode_pending(v).is_none_or(|st| Self::is_stale(&st, now)))
+            .map(|(k, _)| k)
+            .collect();
+        for k in &stale {
+            if let Err(e) = self.pending.remove(k) {
+                log::warn!("Failed to remove stale scheduled prompt wizard: {}", e);
+            }
+        }
+        stale.len()
     }
 
     pub fn delete_pending(&self, key: (&i64, &i64)) -> sled::Result<()> {
@@ -91,3 +165,55 @@ impl ScheduledStorage {
         v
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduled_prompts::dto::{OutputDelivery, PendingStep};
+
+    fn state() -> PendingWizardState {
+        PendingWizardState {
+            group_id: -100,
+            creator_user_id: 7,
+            creator_username: "alice".to_string(),
+            step: PendingStep::AwaitingPrompt,
+            prompt: None,
+            start_hour: None,
+            start_minute: None,
+            repeat: None,
+            thread_id: None,
+            output_template: None,
+            model: None,
+            temperature: None,
+            skip_weekends: false,
+            skip_dates: Vec::new(),
+            output_delivery: OutputDelivery::Inline,
+            updated_at: 0,
+            timezone: None,
+        }
+    }
+
+    #[test]
+    fn test_stale_pending_wizards_are_cleared() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let storage = ScheduledStorage::new(&db).unwrap();
+
+        storage.put_pending((&-100, &7), &state()).unwrap();
+        assert!(storage.get_pending((&-100, &7)).is_some());
+        assert_eq!(storage.sweep_stale_pending(), 0);
+
+        // Written directly so put_pending doesn't refresh the timestamp
+        let mut stale = state();
+        stale.creator_user_id = 8;
+        stale.updated_at = Utc::now().timestamp() - PENDING_WIZARD_TTL_SECS - 1;
+        let bytes = bincode::encode_to_vec(&stale, bincode::config::standard()).unwrap();
+        storage
+            .pending
+            .insert(ScheduledStorage::pending_key_bytes((&-100, &8)), bytes)
+            .unwrap();
+
+        assert_eq!(storage.sweep_stale_pending(), 1);
+        assert!(storage.get_pending((&-100, &8)).is_none());
+        assert!(storage.get_pending((&-100, &7)).is_some());
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::Result as AnyResult;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, MaybeInaccessibleMessage, ParseMode},
+};
+
+use super::spam_guard::{LIMIT_CHOICES, SpamVerdict, WINDOW_CHOICES, is_emoji_or_sticker};
+use crate::{dependencies::BotDependencies, utils};
+
+/// Delete emoji-only/sticker messages past the group's limit and warn once.
+///
+/// Returns `true` when the message was removed.
+pub async fn enforce_spam_limit(
+    bot: &Bot,
+    msg: &Message,
+    bot_deps: &BotDependencies,
+) -> AnyResult<bool> {
+    let Some(user) = msg.from.as_ref() else {
+        return Ok(false);
+    };
+    let settings = bot_deps.spam_guard.get_settings(msg.chat.id);
+    if !settings.enabled() || user.is_bot {
+        return Ok(false);
+    }
+    let user_id = user.id.0 as i64;
+
+    if !is_emoji_or_sticker(msg) {
+        bot_deps.spam_guard.reset(msg.chat.id, user_id)?;
+        return Ok(false);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let verdict = bot_deps
+        .spam_guard
+        .record_emoji_message(msg.chat.id, user_id, &settings, now)?;
+    let SpamVerdict::Excess { first } = verdict else {
+        return Ok(false);
+    };
+
+    // Only look up admins once someone is over the limit
+    if utils::is_admin(bot, msg.chat.id, user.id).await {
+        return Ok(false);
+    }
+
+    if let Err(e) = bot.delete_message(msg.chat.id, msg.id).await {
+        log::warn!(
+            "Failed to delete emoji/sticker spam {} in chat {}: {}",
+            msg.id.0,
+            msg.chat.id.0,
+            e
+        );
+        return Ok(false);
+    }
+
+    if first {
+        let mention = match &user.username {
+            Some(username) => format!("@{}", username),
+            None => format!(
+                "<a href=\"tg://user?id={}\">{}</a>",
+                user.id.0,
+                teloxide::utils::html::escape(&user.first_name)
+            ),
+        };
+        let mut request = bot
+            .send_message(
+                msg.chat.id,
+                format!(
+                    "🧸 {} please slow down — only {} emoji or sticker messages in a row are allowed here. Extra ones are removed for the next {}s.",
+                    mention, settings.max_consecutive, settings.window_secs
+                ),
+            )
+            .parse_mode(ParseMode::Html);
+        if let Some(thread_id) = utils::topic_thread_id(msg) {
+            request = request.message_thread_id(thread_id);
+        }
+        if let Err(e) = request.await {
+            log::warn!("Failed to send spam limit warning: {}", e);
+        }
+    }
+
+    Ok(true)
+}
+
+/// `mod_spamlim_menu`, `mod_spamlim_set:<n>` and `mod_spamlim_window:<secs>` from the moderation menu
+pub async fn handle_spam_limit_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(MaybeInaccessibleMessage::Regular(m)) = &query.message else {
+        return Ok(());
+    };
+    let data = query.data.clone().unwrap_or_default();
+
+    if !utils::is_admin(&bot, m.chat.id, query.from.id).await {
+        bot.answer_callback_query(query.id)
+            .text("❌ Only administrators can manage moderation settings")
+            .await?;
+        return Ok(());
+    }
+
+    let mut settings = bot_deps.spam_guard.get_settings(m.chat.id);
+    if let Some(value) = data.strip_prefix("mod_spamlim_set:") {
+        let Some(limit) = value
+            .parse::<u32>()
+            .ok()
+            .filter(|v| *v == 0 || LIMIT_CHOICES.contains(v))
+        else {
+            bot.answer_callback_query(query.id)
+                .text("❌ Unknown limit")
+                .await?;
+            return Ok(());
+        };
+        settings.max_consecutive = limit;
+        bot_deps.spam_guard.set_settings(m.chat.id, &settings)?;
+        bot.answer_callback_query(query.id.clone())
+            .text(format!("✅ Emoji/sticker limit: {}", settings.label()))
+            .await?;
+    } else if let Some(value) = data.strip_prefix("mod_spamlim_window:") {
+        let Some(window) = value
+            .parse::<i64>()
+            .ok()
+            .filter(|v| WINDOW_CHOICES.contains(v))
+        else {
+            bot.answer_callback_query(query.id)
+                .text("❌ Unknown window")
+                .await?;
+            return Ok(());
+        };
+        settings.window_secs = window;
+        bot_deps.spam_guard.set_settings(m.chat.id, &settings)?;
+        bot.answer_callback_query(query.id.clone()).await?;
+    } else {
+        bot.answer_callback_query(query.id.clone()).await?;
+    }
+
+    let mark = |selected: bool, label: String| {
+        if selected {
+            format!("✅ {}", label)
+        } else {
+            label
+        }
+    };
+
+    let mut limit_row: Vec<InlineKeyboardButton> = LIMIT_CHOICES
+        .iter()
+        .map(|value| {
+            InlineKeyboardButton::callback(
+                mark(settings.max_consecutive == *value, format!("{} in a row", value)),
+                format!("mod_spamlim_set:{}", value),
+            )
+        })
+        .collect();
+    limit_row.push(InlineKeyboardButton::callback(
+        mark(!settings.enabled(), "🔕 Off".to_string()),
+        "mod_spamlim_set:0",
+    ));
+    let window_row: Vec<InlineKeyboardButton> = WINDOW_CHOICES
+        .iter()
+        .map(|value| {
+            InlineKeyboardButton::callback(
+                mark(settings.window_secs == *value, format!("{}s window", value)),
+                format!("mod_spamlim_window:{}", value),
+            )
+        })
+        .collect();
+
+    bot.edit_message_text(
+        m.chat.id,
+        m.id,
+        format!(
+            "🧸 <b>Emoji/Sticker Limit</b>\n\nStops floods of emoji-only and sticker messages that text moderation doesn't catch. Once a member goes over the limit within the window, further ones are deleted and they get a single warning. Any normal message resets the count. Admins are exempt.\n\nCurrent: <b>{}</b>",
+            settings.label()
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(InlineKeyboardMarkup::new(vec![
+        limit_row,
+        window_row,
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "open_moderation_settings",
+        )],
+    ]))
+    .await?;
+
+    Ok(())
+}
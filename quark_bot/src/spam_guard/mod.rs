@@ -0,0 +1,4 @@
+pub mod handler;
+pub mod spam_guard;
+
+pub use spam_guard::SpamGuard;
@@ -0,0 +1,183 @@
+//! Limits on back-to-back emoji-only and sticker messages, enforced without AI.
+
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use teloxide::types::{ChatId, Message};
+
+const TREE_NAME: &str = "spam_guard";
+pub const DEFAULT_WINDOW_SECS: i64 = 60;
+/// Messages allowed in a row before the rest are deleted; 0 turns the limit off
+pub const LIMIT_CHOICES: [u32; 3] = [3, 5, 10];
+pub const WINDOW_CHOICES: [i64; 3] = [30, 60, 300];
+
+fn settings_key(chat_id: ChatId) -> String {
+    format!("settings:{}", chat_id.0)
+}
+
+fn counter_key(chat_id: ChatId, user_id: i64) -> String {
+    format!("count:{}:{}", chat_id.0, user_id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpamLimitSettings {
+    /// Emoji-only/sticker messages a user may send in a row; 0 disables the limit
+    pub max_consecutive: u32,
+    pub window_secs: i64,
+}
+
+impl Default for SpamLimitSettings {
+    fn default() -> Self {
+        Self {
+            max_consecutive: 0,
+            window_secs: DEFAULT_WINDOW_SECS,
+        }
+    }
+}
+
+impl SpamLimitSettings {
+    pub fn enabled(&self) -> bool {
+        self.max_consecutive > 0
+    }
+
+    pub fn label(&self) -> String {
+        if self.enabled() {
+            format!("{} in a row per {}s", self.max_consecutive, self.window_secs)
+        } else {
+            "OFF".to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SpamCounter {
+    count: u32,
+    window_start: i64,
+}
+
+/// What to do with an emoji-only or sticker message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamVerdict {
+    Allow,
+    /// Over the limit; `first` is set on the first excess message so the warning is sent once
+    Excess { first: bool },
+}
+
+fn is_pictographic(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF
+            | 0x2600..=0x27BF
+            | 0x2300..=0x23FF
+            | 0x2B00..=0x2BFF
+            | 0x2190..=0x21FF
+            | 0x2934..=0x2935
+            | 0x3030 | 0x303D | 0x3297 | 0x3299
+            | 0x00A9 | 0x00AE | 0x2122 | 0x203C | 0x2049 | 0x20E3)
+}
+
+/// Joiners, variation selectors, skin tones and tag characters that only appear inside emoji
+fn is_emoji_component(c: char) -> bool {
+    matches!(c as u32, 0x200D | 0xFE0E | 0xFE0F | 0xE0020..=0xE007F)
+        || matches!(c, '#' | '*' | '0'..='9')
+}
+
+/// True for text made up only of emoji (and whitespace), e.g. "😂😂🔥"
+pub fn is_emoji_only(text: &str) -> bool {
+    let mut has_emoji = false;
+    for c in text.chars() {
+        if is_pictographic(c) {
+            has_emoji = true;
+        } else if !c.is_whitespace() && !is_emoji_component(c) {
+            return false;
+        }
+    }
+    has_emoji
+}
+
+/// Sticker messages and text messages with nothing but emoji
+pub fn is_emoji_or_sticker(msg: &Message) -> bool {
+    msg.sticker().is_some() || msg.text().is_some_and(is_emoji_only)
+}
+
+#[derive(Clone)]
+pub struct SpamGuard {
+    tree: Tree,
+}
+
+impl SpamGuard {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    pub fn get_settings(&self, chat_id: ChatId) -> SpamLimitSettings {
+        self.tree
+            .get(settings_key(chat_id))
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn set_settings(&self, chat_id: ChatId, settings: &SpamLimitSettings) -> sled::Result<()> {
+        self.tree.insert(
+            settings_key(chat_id),
+            serde_json::to_vec(settings).unwrap(),
+        )?;
+        Ok(())
+    }
+
+    /// Count an emoji-only/sticker message from the user
+    pub fn record_emoji_message(
+        &self,
+        chat_id: ChatId,
+        user_id: i64,
+        settings: &SpamLimitSettings,
+        now: i64,
+    ) -> sled::Result<SpamVerdict> {
+        let key = counter_key(chat_id, user_id);
+        let mut counter: SpamCounter = self
+            .tree
+            .get(&key)?
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default();
+
+        if now - counter.window_start > settings.window_secs {
+            counter = SpamCounter {
+                count: 0,
+                window_start: now,
+            };
+        }
+        counter.count += 1;
+        self.tree.insert(&key, serde_json::to_vec(&counter).unwrap())?;
+
+        Ok(if counter.count > settings.max_consecutive {
+            SpamVerdict::Excess {
+                first: counter.count == settings.max_consecutive + 1,
+            }
+        } else {
+            SpamVerdict::Allow
+        })
+    }
+
+    /// Any other message breaks the streak
+    pub fn reset(&self, chat_id: ChatId, user_id: i64) -> sled::Result<()> {
+        self.tree.remove(counter_key(chat_id, user_id))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_emoji_only() {
+        assert!(is_emoji_only("😂😂🔥"));
+        assert!(is_emoji_only(" 👍🏽 ❤️ "));
+        assert!(is_emoji_only("👨‍👩‍👧"));
+        assert!(is_emoji_only("1️⃣"));
+        assert!(!is_emoji_only("lol 😂"));
+        assert!(!is_emoji_only("123"));
+        assert!(!is_emoji_only(""));
+    }
+}
@@ -22,6 +22,9 @@ pub struct SponsorSettings {
     pub requests: u64,
     pub interval: SponsorInterval,
     pub cooldown: SponsorCooldown,
+    /// Append "Sponsored requests left: X/Y" to sponsored /g replies
+    #[serde(default)]
+    pub show_remaining: bool,
 }
 
 impl Default for SponsorSettings {
@@ -30,6 +33,7 @@ impl Default for SponsorSettings {
             requests: 0,
             interval: SponsorInterval::Hourly,
             cooldown: SponsorCooldown::WithoutCooldown,
+            show_remaining: false,
         }
     }
 }
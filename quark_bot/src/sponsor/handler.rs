@@ -398,6 +398,42 @@ pub async fn handle_sponsor_settings_callback(
                 show_sponsor_settings(&bot, m.chat.id, m.id, &bot_deps, &group_id).await?;
             }
         }
+    } else if data == "sponsor_toggle_remaining" {
+        if let Some(message) = &query.message {
+            if let teloxide::types::MaybeInaccessibleMessage::Regular(m) = message {
+                let is_admin = utils::is_admin(&bot, m.chat.id, query.from.id).await;
+                if !is_admin {
+                    bot.answer_callback_query(query.id)
+                        .text("❌ Only administrators can manage sponsor settings")
+                        .await?;
+                    return Ok(());
+                }
+
+                let group_id = m.chat.id.to_string();
+                let mut settings = bot_deps.sponsor.get_sponsor_settings(group_id.clone());
+                settings.show_remaining = !settings.show_remaining;
+
+                if let Err(e) = bot_deps
+                    .sponsor
+                    .set_or_update_sponsor_settings(group_id.clone(), settings.clone())
+                {
+                    bot.answer_callback_query(query.id)
+                        .text(&format!("❌ Failed to update settings: {}", e))
+                        .await?;
+                    return Ok(());
+                }
+
+                bot.answer_callback_query(query.id)
+                    .text(if settings.show_remaining {
+                        "✅ Requests left will be shown after sponsored replies"
+                    } else {
+                        "✅ Requests left will no longer be shown"
+                    })
+                    .await?;
+
+                show_sponsor_settings(&bot, m.chat.id, m.id, &bot_deps, &group_id).await?;
+            }
+        }
     } else if data == "sponsor_disable" {
         // Disable sponsor by setting requests to 0
         if let Some(message) = &query.message {
@@ -535,7 +571,8 @@ async fn show_sponsor_settings(
         • Total Requests: <b>{}</b>\n\
         • Requests Left: <b>{}</b>\n\
         • Interval: <b>{}</b>\n\
-        • Cooldown: <b>{}</b>\n\n\
+        • Cooldown: <b>{}</b>\n\
+        • Show Requests Left: <b>{}</b>\n\n\
         <b>How it works:</b>\n\
         • Users can use <code>/g</code> command\n\
         • No registration required\n\
@@ -543,7 +580,11 @@ async fn show_sponsor_settings(
         • Cooldown applies between user requests\n\
         • Only admins can change settings\n\n\
         Choose an action below:",
-        total_requests, requests_left, interval_text, cooldown_text
+        total_requests,
+        requests_left,
+        interval_text,
+        cooldown_text,
+        if settings.show_remaining { "ON" } else { "OFF" }
     );
 
     // Show different buttons based on whether sponsor is enabled or disabled
@@ -574,6 +615,14 @@ async fn show_sponsor_settings(
                 "⏳ Set Cooldown",
                 "sponsor_set_cooldown",
             )],
+            vec![InlineKeyboardButton::callback(
+                if settings.show_remaining {
+                    "🔢 Hide Requests Left"
+                } else {
+                    "🔢 Show Requests Left"
+                },
+                "sponsor_toggle_remaining",
+            )],
             vec![InlineKeyboardButton::callback(
                 "🚫 Disable Sponsor",
                 "sponsor_disable",
@@ -68,6 +68,7 @@ impl Sponsor {
                     existing.requests = settings.requests;
                     existing.interval = settings.interval.clone();
                     existing.cooldown = settings.cooldown.clone();
+                    existing.show_remaining = settings.show_remaining;
                     Some(serde_json::to_vec(&existing).unwrap())
                 } else {
                     Some(serde_json::to_vec(&settings).unwrap())
@@ -211,6 +212,29 @@ impl Sponsor {
         }
     }
 
+    /// Footer for a sponsored reply, read after the request was counted; `None` when the toggle is off
+    pub fn remaining_requests_notice(&self, group_id: String) -> Option<String> {
+        let settings = self.get_sponsor_settings(group_id.clone());
+        if !settings.show_remaining || settings.requests == 0 {
+            return None;
+        }
+
+        let requests = self.get_sponsor_requests(group_id)?;
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        // Don't write the reset back here; the next /g does that when it consumes a request
+        let requests_left =
+            if self.should_reset_interval(&settings, requests.last_request, current_time) {
+                settings.requests
+            } else {
+                requests.requests_left.min(settings.requests)
+            };
+
+        Some(format!(
+            "Sponsored requests left: {}/{}",
+            requests_left, settings.requests
+        ))
+    }
+
     /// Get sponsor state for a group
     pub fn get_sponsor_state(&self, group_id: String) -> Option<SponsorState> {
         let group_id = format!("{}-{}", group_id, self.account_seed);
@@ -134,6 +134,10 @@ async fn show_user_settings_menu(bot: Bot, query: CallbackQuery) -> Result<()> {
                 "🧾 Summarization Settings",
                 "open_summarization_settings",
             )],
+            vec![InlineKeyboardButton::callback(
+                "🖼️ Max Images per Request",
+                "open_user_max_images",
+            )],
             vec![InlineKeyboardButton::callback(
                 "↩️ Close",
                 "user_settings_close",
@@ -192,6 +196,10 @@ async fn show_group_settings_menu(bot: Bot, query: CallbackQuery) -> Result<()>
                 "📋 Summarization Settings",
                 "open_group_summarization_settings",
             )],
+            vec![InlineKeyboardButton::callback(
+                "🧠 History Settings",
+                "open_history_settings",
+            )],
             vec![InlineKeyboardButton::callback(
                 "🔄 Migrate Group ID",
                 "open_migrate_group_id",
@@ -0,0 +1,199 @@
+//! Render dense tabular data (pools, portfolios) as a PNG so it stays readable on mobile.
+//! Drawn in memory with a bitmap font; callers fall back to text when rendering fails.
+
+use anyhow::{Result, anyhow};
+use font8x8::{BASIC_FONTS, UnicodeFonts};
+use image::{ImageFormat, Rgb, RgbImage};
+use teloxide::{
+    Bot,
+    prelude::*,
+    types::{InputFile, Message},
+};
+
+use crate::utils;
+
+/// Glyphs are 8x8, drawn at this scale
+const SCALE: u32 = 2;
+const GLYPH: u32 = 8 * SCALE;
+const CELL_PAD_X: u32 = 12;
+const ROW_HEIGHT: u32 = GLYPH + 14;
+const MARGIN: u32 = 16;
+/// Longer cells are cut so one column can't push the image past Telegram's limits
+const MAX_CELL_CHARS: usize = 28;
+const MAX_ROWS: usize = 30;
+
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+const STRIPE: Rgb<u8> = Rgb([242, 244, 248]);
+const HEADER: Rgb<u8> = Rgb([33, 41, 60]);
+const HEADER_TEXT: Rgb<u8> = Rgb([255, 255, 255]);
+const TEXT: Rgb<u8> = Rgb([30, 30, 30]);
+const GRID: Rgb<u8> = Rgb([210, 214, 222]);
+
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    pub title: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Keep what the bitmap font can draw: emoji are dropped, other non-ASCII becomes '?'
+fn printable(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .filter_map(|c| match c {
+            ' '..='~' => Some(c),
+            c if c as u32 >= 0x1F000 || ('\u{2600}'..='\u{27BF}').contains(&c) => None,
+            c if c.is_whitespace() => Some(' '),
+            '\u{FE0F}' | '\u{200D}' => None,
+            _ => Some('?'),
+        })
+        .collect();
+    let cleaned = cleaned.trim().to_string();
+    if cleaned.chars().count() > MAX_CELL_CHARS {
+        let cut: String = cleaned.chars().take(MAX_CELL_CHARS - 2).collect();
+        format!("{}..", cut.trim_end())
+    } else {
+        cleaned
+    }
+}
+
+fn draw_text(image: &mut RgbImage, x: u32, y: u32, text: &str, color: Rgb<u8>) {
+    for (index, c) in text.chars().enumerate() {
+        let Some(glyph) = BASIC_FONTS.get(c) else {
+            continue;
+        };
+        let origin_x = x + index as u32 * GLYPH;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..8u32 {
+                if bits & (1 << col) == 0 {
+                    continue;
+                }
+                for dy in 0..SCALE {
+                    for dx in 0..SCALE {
+                        let px = origin_x + col * SCALE + dx;
+                        let py = y + row as u32 * SCALE + dy;
+                        if px < image.width() && py < image.height() {
+                            image.put_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn fill_rect(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// Draw the table and encode it as PNG bytes
+pub fn render_png(table: &Table) -> Result<Vec<u8>> {
+    if table.headers.is_empty() || table.rows.is_empty() {
+        return Err(anyhow!("Nothing to render"));
+    }
+
+    let title = printable(&table.title);
+    let headers: Vec<String> = table.headers.iter().map(|h| printable(h)).collect();
+    let rows: Vec<Vec<String>> = table
+        .rows
+        .iter()
+        .take(MAX_ROWS)
+        .map(|row| {
+            (0..headers.len())
+                .map(|i| row.get(i).map(|cell| printable(cell)).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<u32> = (0..headers.len())
+        .map(|i| {
+            let chars = rows
+                .iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(headers[i].chars().count()))
+                .max()
+                .unwrap_or(0) as u32;
+            chars * GLYPH + CELL_PAD_X * 2
+        })
+        .collect();
+
+    let table_width: u32 = widths.iter().sum();
+    let title_width = title.chars().count() as u32 * GLYPH;
+    let width = table_width.max(title_width) + MARGIN * 2;
+    let title_height = if title.is_empty() { 0 } else { ROW_HEIGHT };
+    let height = MARGIN * 2 + title_height + ROW_HEIGHT * (rows.len() as u32 + 1);
+
+    let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+    let text_offset = (ROW_HEIGHT - GLYPH) / 2;
+
+    if !title.is_empty() {
+        draw_text(&mut image, MARGIN, MARGIN + text_offset, &title, TEXT);
+    }
+
+    let top = MARGIN + title_height;
+    fill_rect(&mut image, MARGIN, top, table_width, ROW_HEIGHT, HEADER);
+    let mut x = MARGIN;
+    for (header, width) in headers.iter().zip(&widths) {
+        draw_text(&mut image, x + CELL_PAD_X, top + text_offset, header, HEADER_TEXT);
+        x += width;
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let y = top + ROW_HEIGHT * (index as u32 + 1);
+        if index % 2 == 1 {
+            fill_rect(&mut image, MARGIN, y, table_width, ROW_HEIGHT, STRIPE);
+        }
+        fill_rect(&mut image, MARGIN, y + ROW_HEIGHT - 1, table_width, 1, GRID);
+        let mut x = MARGIN;
+        for (cell, width) in row.iter().zip(&widths) {
+            draw_text(&mut image, x + CELL_PAD_X, y + text_offset, cell, TEXT);
+            x += width;
+        }
+    }
+
+    let mut png = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Render the table and send it as a photo to the message's chat and topic
+pub async fn send_table_image(bot: &Bot, msg: &Message, table: &Table) -> Result<()> {
+    let png = render_png(table)?;
+    let mut request = bot.send_photo(msg.chat.id, InputFile::memory(png));
+    if let Some(thread_id) = utils::topic_thread_id(msg) {
+        request = request.message_thread_id(thread_id);
+    }
+    request.await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_png_produces_png() {
+        let table = Table {
+            title: "🔥 Trending Pools".to_string(),
+            headers: vec!["Pool".to_string(), "24h".to_string()],
+            rows: vec![
+                vec!["APT / USDC".to_string(), "+4.2%".to_string()],
+                vec!["THL / APT".to_string()],
+            ],
+        };
+        let png = render_png(&table).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        assert!(render_png(&Table::default()).is_err());
+    }
+
+    #[test]
+    fn test_printable_strips_emoji_and_truncates() {
+        assert_eq!(printable("🔥 Pools"), "Pools");
+        assert_eq!(printable("café"), "caf?");
+        assert_eq!(printable(&"x".repeat(40)).len(), MAX_CELL_CHARS);
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::Result as AnyResult;
+use teloxide::prelude::*;
+
+use crate::{
+    dependencies::BotDependencies,
+    utils::{format_timestamp, send_html_message, send_message},
+};
+
+/// /stats — the caller's own AI usage and current settings (DM only)
+pub async fn handle_stats_command(
+    bot: Bot,
+    msg: Message,
+    bot_deps: BotDependencies,
+) -> AnyResult<()> {
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ Unable to identify user.".to_string()).await?;
+        return Ok(());
+    };
+    let user_id = user.id.0 as i64;
+
+    let usage = bot_deps.usage_stats.get(user_id);
+    let files_stored = bot_deps.user_convos.get_files(user_id).len();
+    let prefs = user
+        .username
+        .as_deref()
+        .map(|username| bot_deps.user_model_prefs.get_preferences(username))
+        .unwrap_or_default();
+
+    let mut text = format!(
+        "📊 <b>Your Usage</b>\n\n💬 Messages: {}\n🔢 Tokens: {}\n🖼️ Images generated: {}\n🌐 Web searches: {}\n📁 Files stored: {}\n\n🤖 Model: {}\n🧠 Reasoning: {}\n🗣️ Verbosity: {}\n🎭 Persona: {}",
+        usage.messages,
+        usage.tokens,
+        usage.images_generated,
+        usage.web_searches,
+        files_stored,
+        prefs.chat_model.to_display_string(),
+        if prefs.reasoning_enabled { "On" } else { "Off" },
+        prefs.verbosity.to_display_string(),
+        prefs.persona.to_display_string(),
+    );
+    match (usage.first_used_at, usage.last_used_at) {
+        (Some(first), Some(last)) => text.push_str(&format!(
+            "\n\n🗓️ Since {} · last used {}",
+            format_timestamp(first as u64),
+            format_timestamp(last as u64)
+        )),
+        _ => text.push_str("\n\n💡 No AI requests yet — try /c followed by a question."),
+    }
+
+    send_html_message(msg, bot, text).await?;
+    Ok(())
+}
@@ -0,0 +1,4 @@
+pub mod handler;
+pub mod usage_stats;
+
+pub use usage_stats::UsageStats;
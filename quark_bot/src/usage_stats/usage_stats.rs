@@ -0,0 +1,63 @@
+//! Per-user AI usage counters behind the DM /stats dashboard.
+
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+
+const TREE_NAME: &str = "user_usage_stats";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserUsage {
+    /// AI requests answered (/c, /g and plain DM prompts)
+    pub messages: u64,
+    pub tokens: u64,
+    pub images_generated: u64,
+    pub web_searches: u64,
+    pub first_used_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+}
+
+#[derive(Clone)]
+pub struct UsageStats {
+    tree: Tree,
+}
+
+impl UsageStats {
+    pub fn new(db: &Db) -> sled::Result<Self> {
+        let tree = db.open_tree(TREE_NAME)?;
+        Ok(Self { tree })
+    }
+
+    /// Zeroed usage for users who haven't made a request yet
+    pub fn get(&self, user_id: i64) -> UserUsage {
+        self.tree
+            .get(user_id.to_be_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn record(
+        &self,
+        user_id: i64,
+        tokens: u32,
+        images_generated: u32,
+        web_searches: u32,
+    ) -> sled::Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.tree
+            .update_and_fetch(user_id.to_be_bytes(), |old| {
+                let mut usage: UserUsage = old
+                    .and_then(|v| serde_json::from_slice(v).ok())
+                    .unwrap_or_default();
+                usage.messages += 1;
+                usage.tokens += tokens as u64;
+                usage.images_generated += images_generated as u64;
+                usage.web_searches += web_searches as u64;
+                usage.first_used_at.get_or_insert(now);
+                usage.last_used_at = Some(now);
+                Some(serde_json::to_vec(&usage).unwrap())
+            })?;
+        Ok(())
+    }
+}
@@ -5,3 +5,19 @@ pub struct FileInfo {
     pub id: String,
     pub name: String,
 }
+
+/// A library file as listed to the user, with its upload size when known
+#[derive(Debug, Clone)]
+pub struct LibraryFile {
+    pub id: String,
+    pub name: String,
+    pub bytes: Option<u64>,
+}
+
+/// A named document library with its own OpenAI vector store
+#[derive(Serialize, Deserialize, Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct KnowledgeBase {
+    pub name: String,
+    pub vector_store_id: Option<String>,
+    pub files: Vec<FileInfo>,
+}
@@ -1,27 +1,184 @@
-use super::dto::FileInfo;
+use super::dto::{FileInfo, KnowledgeBase};
 use serde::{Deserialize, Serialize};
 use sled::{Db, IVec};
 
 const TREE_NAME: &str = "user_conversations";
+/// Upload sizes by OpenAI file id, kept apart so existing conversation records still decode
+const FILE_SIZES_TREE: &str = "user_file_sizes";
+/// Generated image links kept per user for /lastimage
+const MAX_LAST_IMAGES: usize = 5;
+/// Name given to the library users had before knowledge bases existed
+pub const DEFAULT_KNOWLEDGE_BASE: &str = "default";
+pub const MAX_KNOWLEDGE_BASES: usize = 10;
+const MAX_KNOWLEDGE_BASE_NAME_LEN: usize = 32;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, bincode::Encode, bincode::Decode)]
 pub struct UserData {
     pub response_id: Option<String>,
+    /// Pre-knowledge-base library; moved into the "default" knowledge base when read
     pub vector_store_id: Option<String>,
     pub wallet_address: Option<String>,
     pub files: Vec<FileInfo>,
     pub last_image_urls: Vec<String>,
+    pub knowledge_bases: Vec<KnowledgeBase>,
+    pub active_knowledge_base: Option<String>,
+}
+
+/// Record layout before knowledge bases were added
+#[derive(Debug, bincode::Decode)]
+struct ImageUserData {
+    response_id: Option<String>,
+    vector_store_id: Option<String>,
+    wallet_address: Option<String>,
+    files: Vec<FileInfo>,
+    last_image_urls: Vec<String>,
+}
+
+impl From<ImageUserData> for UserData {
+    fn from(old: ImageUserData) -> Self {
+        Self {
+            response_id: old.response_id,
+            vector_store_id: old.vector_store_id,
+            wallet_address: old.wallet_address,
+            files: old.files,
+            last_image_urls: old.last_image_urls,
+            knowledge_bases: Vec::new(),
+            active_knowledge_base: None,
+        }
+    }
+}
+
+/// Record layout before /lastimage links were kept
+#[derive(Debug, bincode::Decode)]
+struct LegacyUserData {
+    response_id: Option<String>,
+    vector_store_id: Option<String>,
+    wallet_address: Option<String>,
+    files: Vec<FileInfo>,
+}
+
+impl From<LegacyUserData> for UserData {
+    fn from(old: LegacyUserData) -> Self {
+        ImageUserData {
+            response_id: old.response_id,
+            vector_store_id: old.vector_store_id,
+            wallet_address: old.wallet_address,
+            files: old.files,
+            last_image_urls: Vec::new(),
+        }
+        .into()
+    }
+}
+
+/// Why a knowledge base could not be created or selected
+#[derive(Debug, PartialEq)]
+pub enum KnowledgeBaseError {
+    InvalidName,
+    AlreadyExists,
+    LimitReached,
+    NotFound,
+}
+
+impl std::fmt::Display for KnowledgeBaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KnowledgeBaseError::InvalidName => write!(
+                f,
+                "Names can be up to {} letters, digits, spaces, '-' or '_'",
+                MAX_KNOWLEDGE_BASE_NAME_LEN
+            ),
+            KnowledgeBaseError::AlreadyExists => {
+                write!(f, "You already have a knowledge base with that name")
+            }
+            KnowledgeBaseError::LimitReached => {
+                write!(
+                    f,
+                    "You can have up to {} knowledge bases",
+                    MAX_KNOWLEDGE_BASES
+                )
+            }
+            KnowledgeBaseError::NotFound => write!(f, "No knowledge base with that name"),
+        }
+    }
+}
+
+fn valid_knowledge_base_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= MAX_KNOWLEDGE_BASE_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+}
+
+impl UserData {
+    /// Move a library from before knowledge bases into the "default" one, and make sure
+    /// the active name points at an existing knowledge base
+    fn migrate_legacy_library(&mut self) {
+        let legacy_store = self.vector_store_id.take().filter(|id| !id.is_empty());
+        if legacy_store.is_some() || !self.files.is_empty() {
+            let files = std::mem::take(&mut self.files);
+            match self
+                .knowledge_bases
+                .iter_mut()
+                .find(|kb| kb.name == DEFAULT_KNOWLEDGE_BASE)
+            {
+                Some(kb) => {
+                    kb.vector_store_id = kb.vector_store_id.take().or(legacy_store);
+                    kb.files.extend(files);
+                }
+                None => self.knowledge_bases.push(KnowledgeBase {
+                    name: DEFAULT_KNOWLEDGE_BASE.to_string(),
+                    vector_store_id: legacy_store,
+                    files,
+                }),
+            }
+        }
+
+        let active_exists = self
+            .active_knowledge_base
+            .as_ref()
+            .is_some_and(|name| self.knowledge_bases.iter().any(|kb| &kb.name == name));
+        if !active_exists {
+            self.active_knowledge_base = self.knowledge_bases.first().map(|kb| kb.name.clone());
+        }
+    }
+
+    fn active_kb(&self) -> Option<&KnowledgeBase> {
+        let name = self.active_knowledge_base.as_ref()?;
+        self.knowledge_bases.iter().find(|kb| &kb.name == name)
+    }
+
+    /// The active knowledge base, creating the default one for a user who has none
+    fn active_kb_mut(&mut self) -> &mut KnowledgeBase {
+        if self.active_kb().is_none() {
+            if self.knowledge_bases.is_empty() {
+                self.knowledge_bases.push(KnowledgeBase {
+                    name: DEFAULT_KNOWLEDGE_BASE.to_string(),
+                    vector_store_id: None,
+                    files: Vec::new(),
+                });
+            }
+            self.active_knowledge_base = Some(self.knowledge_bases[0].name.clone());
+        }
+        let name = self.active_knowledge_base.clone().unwrap_or_default();
+        self.knowledge_bases
+            .iter_mut()
+            .find(|kb| kb.name == name)
+            .expect("active knowledge base exists")
+    }
 }
 
 #[derive(Clone)]
 pub struct UserConversations {
     tree: sled::Tree,
+    file_sizes: sled::Tree,
 }
 
 impl UserConversations {
     pub fn new(db: &Db) -> sled::Result<Self> {
         let tree = db.open_tree(TREE_NAME)?;
-        Ok(Self { tree })
+        let file_sizes = db.open_tree(FILE_SIZES_TREE)?;
+        Ok(Self { tree, file_sizes })
     }
 
     pub fn set_user_data(&self, user_id: i64, data: &UserData) -> sled::Result<()> {
@@ -31,13 +188,31 @@ impl UserConversations {
         Ok(())
     }
 
+    fn decode_user_data(bytes: &[u8]) -> Option<UserData> {
+        let config = bincode::config::standard();
+        bincode::decode_from_slice::<UserData, _>(bytes, config)
+            .map(|(v, _)| v)
+            .or_else(|_| {
+                bincode::decode_from_slice::<ImageUserData, _>(bytes, config).map(|(v, _)| v.into())
+            })
+            .or_else(|_| {
+                bincode::decode_from_slice::<LegacyUserData, _>(bytes, config)
+                    .map(|(v, _)| v.into())
+            })
+            .ok()
+    }
+
     pub fn get_user_data(&self, user_id: i64) -> Option<UserData> {
         let key = user_id.to_be_bytes();
-        self.tree.get(key).ok().flatten().and_then(|ivec: IVec| {
-            bincode::decode_from_slice(&ivec, bincode::config::standard())
-                .ok()
-                .map(|(data, _)| data)
-        })
+        self.tree
+            .get(key)
+            .ok()
+            .flatten()
+            .and_then(|ivec: IVec| Self::decode_user_data(&ivec))
+            .map(|mut data| {
+                data.migrate_legacy_library();
+                data
+            })
     }
 
     pub fn set_response_id(&self, user_id: i64, response_id: &str) -> sled::Result<()> {
@@ -51,21 +226,24 @@ impl UserConversations {
             .and_then(|data| data.response_id)
     }
 
+    /// Vector store of the active knowledge base
     pub fn set_vector_store_id(&self, user_id: i64, vector_store_id: &str) -> sled::Result<()> {
         let mut data = self.get_user_data(user_id).unwrap_or_default();
-        data.vector_store_id = Some(vector_store_id.to_string());
+        data.active_kb_mut().vector_store_id = Some(vector_store_id.to_string());
         self.set_user_data(user_id, &data)
     }
 
+    /// Vector store of the active knowledge base
     pub fn get_vector_store_id(&self, user_id: i64) -> Option<String> {
         self.get_user_data(user_id)
-            .and_then(|data| data.vector_store_id)
+            .and_then(|data| data.active_kb().and_then(|kb| kb.vector_store_id.clone()))
     }
 
     pub fn add_file(&self, user_id: i64, file_id: &str, filename: &str) -> sled::Result<()> {
         let mut data = self.get_user_data(user_id).unwrap_or_default();
-        if !data.files.iter().any(|f| f.id == file_id) {
-            data.files.push(FileInfo {
+        let kb = data.active_kb_mut();
+        if !kb.files.iter().any(|f| f.id == file_id) {
+            kb.files.push(FileInfo {
                 id: file_id.to_string(),
                 name: filename.to_string(),
             });
@@ -73,35 +251,216 @@ impl UserConversations {
         self.set_user_data(user_id, &data)
     }
 
+    /// Files in the active knowledge base
     pub fn get_files(&self, user_id: i64) -> Vec<FileInfo> {
         self.get_user_data(user_id)
-            .map(|data| data.files)
+            .and_then(|data| data.active_kb().map(|kb| kb.files.clone()))
             .unwrap_or_else(Vec::new)
     }
 
     pub fn remove_file_id(&self, user_id: i64, file_id: &str) -> sled::Result<()> {
         let mut data = self.get_user_data(user_id).unwrap_or_default();
-        data.files.retain(|f| f.id != file_id);
+        data.active_kb_mut().files.retain(|f| f.id != file_id);
+        self.file_sizes.remove(file_id)?;
         self.set_user_data(user_id, &data)
     }
 
     pub fn clear_files(&self, user_id: i64) -> sled::Result<()> {
         let mut data = self.get_user_data(user_id).unwrap_or_default();
-        data.files.clear();
+        for file in data.active_kb_mut().files.drain(..) {
+            self.file_sizes.remove(file.id.as_str())?;
+        }
         self.set_user_data(user_id, &data)
     }
 
+    pub fn set_file_size(&self, file_id: &str, bytes: u64) -> sled::Result<()> {
+        self.file_sizes
+            .insert(file_id, bytes.to_be_bytes().to_vec())?;
+        Ok(())
+    }
+
+    /// Remember that OpenAI couldn't give a file's size, so it isn't asked again
+    pub fn set_file_size_unknown(&self, file_id: &str) -> sled::Result<()> {
+        self.file_sizes.insert(file_id, Vec::<u8>::new())?;
+        Ok(())
+    }
+
+    /// Size recorded at upload or looked up later. `None` when it was never looked up (files
+    /// uploaded before sizes were kept), `Some(None)` when the lookup failed.
+    pub fn get_file_size(&self, file_id: &str) -> Option<Option<u64>> {
+        self.file_sizes.get(file_id).ok().flatten().map(|ivec| {
+            <[u8; 8]>::try_from(ivec.as_ref())
+                .ok()
+                .map(u64::from_be_bytes)
+        })
+    }
+
+    /// The user's knowledge bases and the name of the active one
+    pub fn list_knowledge_bases(&self, user_id: i64) -> (Vec<KnowledgeBase>, Option<String>) {
+        self.get_user_data(user_id)
+            .map(|data| (data.knowledge_bases, data.active_knowledge_base))
+            .unwrap_or_default()
+    }
+
+    pub fn active_knowledge_base_name(&self, user_id: i64) -> String {
+        self.get_user_data(user_id)
+            .and_then(|data| data.active_knowledge_base)
+            .unwrap_or_else(|| DEFAULT_KNOWLEDGE_BASE.to_string())
+    }
+
+    /// Create an empty knowledge base and make it the active one
+    pub fn create_knowledge_base(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> anyhow::Result<Result<(), KnowledgeBaseError>> {
+        let name = name.trim();
+        if !valid_knowledge_base_name(name) {
+            return Ok(Err(KnowledgeBaseError::InvalidName));
+        }
+        let mut data = self.get_user_data(user_id).unwrap_or_default();
+        if data
+            .knowledge_bases
+            .iter()
+            .any(|kb| kb.name.eq_ignore_ascii_case(name))
+        {
+            return Ok(Err(KnowledgeBaseError::AlreadyExists));
+        }
+        if data.knowledge_bases.len() >= MAX_KNOWLEDGE_BASES {
+            return Ok(Err(KnowledgeBaseError::LimitReached));
+        }
+        data.knowledge_bases.push(KnowledgeBase {
+            name: name.to_string(),
+            vector_store_id: None,
+            files: Vec::new(),
+        });
+        data.active_knowledge_base = Some(name.to_string());
+        self.set_user_data(user_id, &data)?;
+        Ok(Ok(()))
+    }
+
+    /// Switch the active knowledge base; names match case-insensitively.
+    /// Returns the stored name of the one now active.
+    pub fn use_knowledge_base(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> anyhow::Result<Result<String, KnowledgeBaseError>> {
+        let mut data = self.get_user_data(user_id).unwrap_or_default();
+        let Some(kb) = data
+            .knowledge_bases
+            .iter()
+            .find(|kb| kb.name.eq_ignore_ascii_case(name.trim()))
+        else {
+            return Ok(Err(KnowledgeBaseError::NotFound));
+        };
+        let name = kb.name.clone();
+        data.active_knowledge_base = Some(name.clone());
+        self.set_user_data(user_id, &data)?;
+        Ok(Ok(name))
+    }
+
+    /// Remember a generated image's download link, keeping only the newest few
+    pub fn push_image_url(&self, user_id: i64, url: &str) -> sled::Result<()> {
+        let mut data = self.get_user_data(user_id).unwrap_or_default();
+        data.last_image_urls.push(url.to_string());
+        let excess = data.last_image_urls.len().saturating_sub(MAX_LAST_IMAGES);
+        data.last_image_urls.drain(..excess);
+        self.set_user_data(user_id, &data)
+    }
+
+    pub fn last_image_url(&self, user_id: i64) -> Option<String> {
+        self.get_user_data(user_id)
+            .and_then(|data| data.last_image_urls.last().cloned())
+    }
+
     pub fn clear_response_id(&self, user_id: i64) -> sled::Result<()> {
         let mut data = self.get_user_data(user_id).unwrap_or_default();
         data.response_id = None;
         self.set_user_data(user_id, &data)
     }
 
+    /// /forget: drop the thread and the cached image links, keeping files and wallet
+    pub fn forget_last_turn(&self, user_id: i64) -> sled::Result<()> {
+        let mut data = self.get_user_data(user_id).unwrap_or_default();
+        data.response_id = None;
+        data.last_image_urls.clear();
+        self.set_user_data(user_id, &data)
+    }
+
     /// Clean up orphaned vector store references when vector store is not found in OpenAI
     pub fn cleanup_orphaned_vector_store(&self, user_id: i64) -> sled::Result<()> {
         let mut data = self.get_user_data(user_id).unwrap_or_default();
-        data.vector_store_id = None;
-        data.files.clear();
+        let kb = data.active_kb_mut();
+        kb.vector_store_id = None;
+        kb.files.clear();
         self.set_user_data(user_id, &data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_library_becomes_default_knowledge_base() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let convos = UserConversations::new(&db).unwrap();
+
+        #[derive(bincode::Encode)]
+        struct OldUserData {
+            response_id: Option<String>,
+            vector_store_id: Option<String>,
+            wallet_address: Option<String>,
+            files: Vec<FileInfo>,
+        }
+        let old = OldUserData {
+            response_id: Some("resp_1".to_string()),
+            vector_store_id: Some("vs_old".to_string()),
+            wallet_address: None,
+            files: vec![FileInfo {
+                id: "file_1".to_string(),
+                name: "notes.md".to_string(),
+            }],
+        };
+        let bytes = bincode::encode_to_vec(&old, bincode::config::standard()).unwrap();
+        convos.tree.insert(7i64.to_be_bytes(), bytes).unwrap();
+
+        assert_eq!(convos.get_response_id(7).as_deref(), Some("resp_1"));
+        assert_eq!(convos.get_vector_store_id(7).as_deref(), Some("vs_old"));
+        assert_eq!(convos.active_knowledge_base_name(7), DEFAULT_KNOWLEDGE_BASE);
+
+        convos
+            .create_knowledge_base(7, "research")
+            .unwrap()
+            .unwrap();
+        assert_eq!(convos.get_vector_store_id(7), None);
+        assert!(convos.get_files(7).is_empty());
+        assert_eq!(
+            convos.create_knowledge_base(7, "Research").unwrap(),
+            Err(KnowledgeBaseError::AlreadyExists)
+        );
+
+        assert_eq!(
+            convos.use_knowledge_base(7, "DEFAULT").unwrap(),
+            Ok(DEFAULT_KNOWLEDGE_BASE.to_string())
+        );
+        assert_eq!(convos.get_files(7).len(), 1);
+    }
+
+    #[test]
+    fn test_forget_last_turn_keeps_library() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let convos = UserConversations::new(&db).unwrap();
+        convos.set_response_id(3, "resp_9").unwrap();
+        convos
+            .push_image_url(3, "https://example.com/a.png")
+            .unwrap();
+        convos.add_file(3, "file_1", "notes.md").unwrap();
+
+        convos.forget_last_turn(3).unwrap();
+        assert_eq!(convos.get_response_id(3), None);
+        assert_eq!(convos.last_image_url(3), None);
+        assert_eq!(convos.get_files(3).len(), 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! /newkb, /listkb and /usekb: separate document libraries per user. Uploads, the
+//! library menu and file search all use the active knowledge base.
+
+use anyhow::Result;
+use teloxide::{prelude::*, utils::html};
+
+use super::{dto::KnowledgeBase, handler::MAX_KNOWLEDGE_BASES};
+use crate::{
+    dependencies::BotDependencies,
+    utils::{send_html_message, send_message},
+};
+
+fn format_knowledge_bases(knowledge_bases: &[KnowledgeBase], active: Option<&str>) -> String {
+    if knowledge_bases.is_empty() {
+        return "📚 <b>Your Knowledge Bases</b>\n\n<i>None yet.</i> Your first upload creates the <b>default</b> one, or use /newkb &lt;name&gt;.".to_string();
+    }
+
+    let lines = knowledge_bases
+        .iter()
+        .map(|kb| {
+            let marker = if Some(kb.name.as_str()) == active {
+                "✅"
+            } else {
+                "•"
+            };
+            format!(
+                "{} <b>{}</b> ({} files)",
+                marker,
+                html::escape(&kb.name),
+                kb.files.len()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "📚 <b>Your Knowledge Bases</b> ({}/{})\n\n{}\n\n💡 Switch with /usekb &lt;name&gt;, create one with /newkb &lt;name&gt;",
+        knowledge_bases.len(),
+        MAX_KNOWLEDGE_BASES,
+        lines
+    )
+}
+
+/// /newkb <name> — create an empty knowledge base and make it active
+pub async fn handle_new_kb(
+    bot: Bot,
+    msg: Message,
+    name: &str,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ Unable to identify user.".to_string()).await?;
+        return Ok(());
+    };
+    if name.trim().is_empty() {
+        send_message(
+            msg,
+            bot,
+            "Usage: /newkb <name>, e.g. /newkb research".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let text = match bot_deps
+        .user_convos
+        .create_knowledge_base(user.id.0 as i64, name)?
+    {
+        Ok(()) => format!(
+            "✅ Created knowledge base <b>{}</b> and switched to it.\n\nFiles you upload now go here; use /usersettings → Document Library to add some.",
+            html::escape(name.trim())
+        ),
+        Err(e) => format!("❌ {}", html::escape(&e.to_string())),
+    };
+    send_html_message(msg, bot, text).await
+}
+
+/// /listkb — the user's knowledge bases with file counts
+pub async fn handle_list_kb(bot: Bot, msg: Message, bot_deps: BotDependencies) -> Result<()> {
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ Unable to identify user.".to_string()).await?;
+        return Ok(());
+    };
+    let (knowledge_bases, active) = bot_deps.user_convos.list_knowledge_bases(user.id.0 as i64);
+    send_html_message(
+        msg,
+        bot,
+        format_knowledge_bases(&knowledge_bases, active.as_deref()),
+    )
+    .await
+}
+
+/// /usekb <name> — switch which knowledge base uploads and answers use
+pub async fn handle_use_kb(
+    bot: Bot,
+    msg: Message,
+    name: &str,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(user) = msg.from.clone() else {
+        send_message(msg, bot, "❌ Unable to identify user.".to_string()).await?;
+        return Ok(());
+    };
+    if name.trim().is_empty() {
+        send_message(
+            msg,
+            bot,
+            "Usage: /usekb <name>. See /listkb for your knowledge bases.".to_string(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let text = match bot_deps
+        .user_convos
+        .use_knowledge_base(user.id.0 as i64, name)?
+    {
+        Ok(name) => format!(
+            "✅ Now using knowledge base <b>{}</b>.",
+            html::escape(&name)
+        ),
+        Err(e) => format!(
+            "❌ {}. See /listkb for your knowledge bases.",
+            html::escape(&e.to_string())
+        ),
+    };
+    send_html_message(msg, bot, text).await
+}
@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use teloxide::{prelude::*, types::Message, utils::html};
+
+use super::dto::ChatModel;
+use crate::dependencies::BotDependencies;
+use crate::utils::{send_html_message, send_message};
+
+/// Used when MODEL_ALIASES is unset
+const DEFAULT_GLOBAL_ALIASES: &str = "fast=GPT5Mini,smart=GPT5";
+pub const MAX_USER_ALIASES: usize = 10;
+const MAX_ALIAS_LEN: usize = 16;
+
+fn parse_alias_list(raw: &str) -> BTreeMap<String, ChatModel> {
+    let mut aliases = BTreeMap::new();
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once('=').and_then(|(alias, model)| {
+            let alias = normalize_alias(alias);
+            let model = ChatModel::from_name(model.trim())?;
+            (is_valid_alias_name(&alias) && ChatModel::from_name(&alias).is_none())
+                .then_some((alias, model))
+        });
+        match parsed {
+            Some((alias, model)) => {
+                aliases.insert(alias, model);
+            }
+            None => log::warn!("Ignoring invalid MODEL_ALIASES entry: {}", entry),
+        }
+    }
+    aliases
+}
+
+/// Operator-defined aliases shared by every user, read once from MODEL_ALIASES ("fast=GPT5Mini,smart=GPT5")
+pub fn global_aliases() -> &'static BTreeMap<String, ChatModel> {
+    static GLOBAL: OnceLock<BTreeMap<String, ChatModel>> = OnceLock::new();
+    GLOBAL.get_or_init(|| {
+        let raw = env::var("MODEL_ALIASES").unwrap_or_else(|_| DEFAULT_GLOBAL_ALIASES.to_string());
+        parse_alias_list(&raw)
+    })
+}
+
+pub fn normalize_alias(alias: &str) -> String {
+    alias.trim().to_lowercase()
+}
+
+fn is_valid_alias_name(alias: &str) -> bool {
+    !alias.is_empty()
+        && alias.chars().count() <= MAX_ALIAS_LEN
+        && alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Check a new user alias; returns the normalized name or a user-facing reason
+pub fn validate_alias(
+    alias: &str,
+    user_aliases: &BTreeMap<String, ChatModel>,
+    global: &BTreeMap<String, ChatModel>,
+) -> Result<String, String> {
+    let alias = normalize_alias(alias);
+
+    if !is_valid_alias_name(&alias) {
+        return Err(format!(
+            "Aliases must be 1-{} characters of letters, digits, '-' or '_'.",
+            MAX_ALIAS_LEN
+        ));
+    }
+    if ChatModel::from_name(&alias).is_some() {
+        return Err(format!("'{}' is already a model name.", alias));
+    }
+    if global.contains_key(&alias) {
+        return Err(format!("'{}' is already a built-in alias.", alias));
+    }
+    if !user_aliases.contains_key(&alias) && user_aliases.len() >= MAX_USER_ALIASES {
+        return Err(format!(
+            "You can have at most {} aliases. Remove one first.",
+            MAX_USER_ALIASES
+        ));
+    }
+
+    Ok(alias)
+}
+
+/// Resolve a model name or alias: canonical names first, then the user's aliases, then global ones
+pub fn resolve_model(
+    name: &str,
+    user_aliases: &BTreeMap<String, ChatModel>,
+    global: &BTreeMap<String, ChatModel>,
+) -> Option<ChatModel> {
+    if let Some(model) = ChatModel::from_name(name.trim()) {
+        return Some(model);
+    }
+    let alias = normalize_alias(name);
+    user_aliases
+        .get(&alias)
+        .or_else(|| global.get(&alias))
+        .cloned()
+}
+
+/// Split "/c:fast prompt" (optionally "/c:fast@BotName prompt") into ("fast", "prompt")
+pub fn parse_model_prefix(text: &str) -> Option<(String, String)> {
+    let rest = text.strip_prefix("/c:")?;
+    let (head, prompt) = match rest.split_once(char::is_whitespace) {
+        Some((head, prompt)) => (head, prompt.trim()),
+        None => (rest, ""),
+    };
+    let alias = head.split('@').next().unwrap_or_default();
+    if alias.is_empty() {
+        return None;
+    }
+    Some((alias.to_string(), prompt.to_string()))
+}
+
+fn format_alias_list(user_aliases: &BTreeMap<String, ChatModel>) -> String {
+    let mut text = String::from("🏷️ <b>Model aliases</b>\n\n");
+
+    let global = global_aliases();
+    if !global.is_empty() {
+        text.push_str("<b>Built-in</b>\n");
+        for (alias, model) in global {
+            text.push_str(&format!(
+                "• <code>{}</code> → {}\n",
+                html::escape(alias),
+                model.to_display_string()
+            ));
+        }
+        text.push('\n');
+    }
+
+    text.push_str("<b>Yours</b>\n");
+    if user_aliases.is_empty() {
+        text.push_str("<i>None yet</i>\n");
+    } else {
+        for (alias, model) in user_aliases {
+            text.push_str(&format!(
+                "• <code>{}</code> → {}\n",
+                html::escape(alias),
+                model.to_display_string()
+            ));
+        }
+    }
+
+    let models = ChatModel::ALL
+        .iter()
+        .map(|m| m.canonical_name())
+        .collect::<Vec<_>>()
+        .join(", ");
+    text.push_str(&format!(
+        "\n💡 Use <code>/c:alias your prompt</code> to pick a model for one message.\n\
+         Add: <code>/modelalias name model</code> (models: {})\n\
+         Remove: <code>/modelalias remove name</code>",
+        models
+    ));
+    text
+}
+
+/// /modelalias — list, add or remove the caller's model aliases
+pub async fn handle_model_alias_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    bot_deps: BotDependencies,
+) -> Result<()> {
+    let Some(username) = msg.from.as_ref().and_then(|u| u.username.clone()) else {
+        send_message(
+            msg,
+            bot,
+            "❌ Username not found, required for this feature".to_string(),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let prefs_store = bot_deps.user_model_prefs.clone();
+    let mut prefs = prefs_store.get_preferences(&username);
+    let parts: Vec<&str> = args.split_whitespace().collect();
+
+    match parts.as_slice() {
+        [] => {
+            send_html_message(msg, bot, format_alias_list(&prefs.model_aliases)).await?;
+        }
+        ["remove", alias] => {
+            let alias = normalize_alias(alias);
+            if prefs.model_aliases.remove(&alias).is_some() {
+                prefs_store.set_preferences(&username, &prefs)?;
+                send_message(msg, bot, format!("✅ Removed alias '{}'.", alias)).await?;
+            } else {
+                send_message(msg, bot, format!("❌ You have no alias '{}'.", alias)).await?;
+            }
+        }
+        [alias, model] => {
+            let Some(model) = ChatModel::from_name(model) else {
+                send_message(
+                    msg,
+                    bot,
+                    format!("❌ Unknown model '{}'. Send /modelalias to see the options.", model),
+                )
+                .await?;
+                return Ok(());
+            };
+            match validate_alias(alias, &prefs.model_aliases, global_aliases()) {
+                Ok(alias) => {
+                    prefs.model_aliases.insert(alias.clone(), model.clone());
+                    prefs_store.set_preferences(&username, &prefs)?;
+                    send_message(
+                        msg,
+                        bot,
+                        format!(
+                            "✅ '{}' now points to {}. Try /c:{} your prompt",
+                            alias,
+                            model.to_display_string(),
+                            alias
+                        ),
+                    )
+                    .await?;
+                }
+                Err(reason) => {
+                    send_message(msg, bot, format!("❌ {}", reason)).await?;
+                }
+            }
+        }
+        _ => {
+            send_message(
+                msg,
+                bot,
+                "Usage: /modelalias, /modelalias <name> <model> or /modelalias remove <name>"
+                    .to_string(),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefers_canonical_then_user_then_global() {
+        let global = parse_alias_list("fast=GPT5Mini,smart=GPT5");
+        let mut user = BTreeMap::new();
+        user.insert("quick".to_string(), ChatModel::GPT5Mini);
+
+        assert_eq!(resolve_model("gpt5", &user, &global), Some(ChatModel::GPT5));
+        assert_eq!(resolve_model("GPT-5-Mini", &user, &global), Some(ChatModel::GPT5Mini));
+        assert_eq!(resolve_model("Quick", &user, &global), Some(ChatModel::GPT5Mini));
+        assert_eq!(resolve_model("smart", &user, &global), Some(ChatModel::GPT5));
+        assert_eq!(resolve_model("unknown", &user, &global), None);
+    }
+
+    #[test]
+    fn test_validate_rejects_collisions() {
+        let global = parse_alias_list("fast=GPT5Mini");
+        let user = BTreeMap::new();
+
+        assert!(validate_alias("gpt5", &user, &global).is_err());
+        assert!(validate_alias("FAST", &user, &global).is_err());
+        assert!(validate_alias("has space", &user, &global).is_err());
+        assert_eq!(validate_alias("Cheap", &user, &global), Ok("cheap".to_string()));
+    }
+
+    #[test]
+    fn test_parse_model_prefix() {
+        assert_eq!(
+            parse_model_prefix("/c:fast what is aptos?"),
+            Some(("fast".to_string(), "what is aptos?".to_string()))
+        );
+        assert_eq!(
+            parse_model_prefix("/c:smart@NovaBot hi"),
+            Some(("smart".to_string(), "hi".to_string()))
+        );
+        assert_eq!(parse_model_prefix("/c hello"), None);
+        assert_eq!(parse_model_prefix("/c: hello"), None);
+    }
+
+    #[test]
+    fn test_global_list_skips_invalid_entries() {
+        let aliases = parse_alias_list("fast=GPT5Mini, gpt5=GPT5, broken, odd=GPT9");
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases.get("fast"), Some(&ChatModel::GPT5Mini));
+    }
+}
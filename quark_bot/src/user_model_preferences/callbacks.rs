@@ -1,4 +1,4 @@
-use super::dto::{ChatModel, VerbosityLevel};
+use super::dto::{ChatModel, PersonaPreset, VerbosityLevel};
 use super::handler::UserModelPreferences;
 use anyhow::Result;
 
@@ -269,6 +269,54 @@ pub async fn handle_model_preferences_callback(
         bot.answer_callback_query(query.id)
             .text("Back to reasoning settings")
             .await?;
+    } else if data == "open_persona" || data.starts_with("set_persona:") {
+        let mut prefs = user_model_prefs.get_preferences(username);
+
+        if let Some(key) = data.strip_prefix("set_persona:") {
+            let Some(persona) = PersonaPreset::from_key(key) else {
+                bot.answer_callback_query(query.id)
+                    .text("❌ Unknown persona")
+                    .await?;
+                return Ok(());
+            };
+            prefs.persona = persona;
+            user_model_prefs.set_preferences(username, &prefs)?;
+            bot.answer_callback_query(query.id.clone())
+                .text(format!("Persona set to {}", persona.to_display_string()))
+                .await?;
+        } else {
+            bot.answer_callback_query(query.id.clone()).await?;
+        }
+
+        if let Some(teloxide::types::MaybeInaccessibleMessage::Regular(msg)) = query.message {
+            let mut rows: Vec<Vec<InlineKeyboardButton>> = PersonaPreset::ALL
+                .iter()
+                .map(|persona| {
+                    let label = if *persona == prefs.persona {
+                        format!("✅ {}", persona.to_display_string())
+                    } else {
+                        persona.to_display_string().to_string()
+                    };
+                    vec![InlineKeyboardButton::callback(
+                        label,
+                        format!("set_persona:{}", persona.key()),
+                    )]
+                })
+                .collect();
+            rows.push(vec![InlineKeyboardButton::callback(
+                "↩️ Back",
+                "open_my_settings",
+            )]);
+
+            bot.edit_message_text(
+                msg.chat.id,
+                msg.id,
+                "🎭 <b>Persona</b>\n\nPick how the AI answers you:\n\n• <b>Default</b> — current behaviour, uses your verbosity setting\n• <b>Concise</b> — short, straight to the point\n• <b>Detailed</b> — thorough explanations with caveats\n• <b>Playful</b> — friendly, light-hearted tone\n• <b>Technical</b> — precise, engineer-to-engineer\n\n💡 Concise and Detailed override your verbosity setting.",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(InlineKeyboardMarkup::new(rows))
+            .await?;
+        }
     } else if data.starts_with("set_verbosity:") {
         let verbosity_str = data.strip_prefix("set_verbosity:").unwrap();
         let verbosity = match verbosity_str {
@@ -1,5 +1,6 @@
 use open_ai_rust_responses_by_sshift::Verbosity;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ModelPreferences {
@@ -9,14 +10,48 @@ pub struct ModelPreferences {
     // GPT-5 specific preferences (unified chat flow)
     pub reasoning_enabled: bool,
     pub verbosity: VerbosityLevel,
+
+    // Append cited web sources to AI replies
+    #[serde(default = "default_show_sources")]
+    pub show_sources: bool,
+
+    // Friendly names for models, e.g. "fast" -> GPT5Mini, usable as /c:fast
+    #[serde(default)]
+    pub model_aliases: BTreeMap<String, ChatModel>,
+
+    // Response style preset layered on top of the system prompt
+    #[serde(default)]
+    pub persona: PersonaPreset,
+
+    // Send dense tool data (e.g. trending pools) as a table image instead of text
+    #[serde(default)]
+    pub table_images: bool,
+
+    // Stream /c replies into one message as they are generated
+    #[serde(default)]
+    pub streaming: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+fn default_show_sources() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum ChatModel {
     GPT5,
     GPT5Mini,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PersonaPreset {
+    #[default]
+    Default,
+    Concise,
+    Detailed,
+    Playful,
+    Technical,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum VerbosityLevel {
     Normal,
@@ -29,11 +64,34 @@ impl Default for ModelPreferences {
             chat_model: ChatModel::GPT5Mini,
             reasoning_enabled: false,
             verbosity: VerbosityLevel::Normal,
+            show_sources: default_show_sources(),
+            model_aliases: BTreeMap::new(),
+            persona: PersonaPreset::Default,
+            table_images: false,
+            streaming: false,
         }
     }
 }
 
 impl ChatModel {
+    pub const ALL: [ChatModel; 2] = [ChatModel::GPT5, ChatModel::GPT5Mini];
+
+    /// Enum name as used in callbacks, e.g. "GPT5Mini"
+    pub fn canonical_name(&self) -> &'static str {
+        match self {
+            ChatModel::GPT5 => "GPT5",
+            ChatModel::GPT5Mini => "GPT5Mini",
+        }
+    }
+
+    /// Match a canonical or display name, ignoring case ("gpt5mini", "GPT-5-Mini")
+    pub fn from_name(name: &str) -> Option<ChatModel> {
+        Self::ALL.into_iter().find(|model| {
+            model.canonical_name().eq_ignore_ascii_case(name)
+                || model.to_display_string().eq_ignore_ascii_case(name)
+        })
+    }
+
     pub fn to_display_string(&self) -> &'static str {
         match self {
             ChatModel::GPT5 => "GPT-5",
@@ -49,6 +107,76 @@ impl ChatModel {
     }
 }
 
+impl PersonaPreset {
+    pub const ALL: [PersonaPreset; 5] = [
+        PersonaPreset::Default,
+        PersonaPreset::Concise,
+        PersonaPreset::Detailed,
+        PersonaPreset::Playful,
+        PersonaPreset::Technical,
+    ];
+
+    /// Enum name as used in callbacks, e.g. "Concise"
+    pub fn key(&self) -> &'static str {
+        match self {
+            PersonaPreset::Default => "Default",
+            PersonaPreset::Concise => "Concise",
+            PersonaPreset::Detailed => "Detailed",
+            PersonaPreset::Playful => "Playful",
+            PersonaPreset::Technical => "Technical",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<PersonaPreset> {
+        Self::ALL.into_iter().find(|p| p.key() == key)
+    }
+
+    pub fn to_display_string(&self) -> &'static str {
+        match self {
+            PersonaPreset::Default => "🙂 Default",
+            PersonaPreset::Concise => "✂️ Concise",
+            PersonaPreset::Detailed => "📖 Detailed",
+            PersonaPreset::Playful => "🎈 Playful",
+            PersonaPreset::Technical => "🛠️ Technical",
+        }
+    }
+
+    /// Style instruction appended to the system prompt; `None` keeps the prompt unchanged
+    pub fn instructions(&self) -> Option<&'static str> {
+        match self {
+            PersonaPreset::Default => None,
+            PersonaPreset::Concise => Some(
+                "Be brief and direct. Lead with the answer, skip preambles and recaps, and prefer a few short sentences or a tight list.",
+            ),
+            PersonaPreset::Detailed => Some(
+                "Be thorough. Explain the reasoning, cover relevant caveats and alternatives, and use headings or lists when the answer has several parts.",
+            ),
+            PersonaPreset::Playful => Some(
+                "Use a friendly, upbeat and lightly humorous tone with the occasional emoji, while keeping facts, numbers and transaction details exact.",
+            ),
+            PersonaPreset::Technical => Some(
+                "Answer as a senior engineer talking to a peer: precise terminology, concrete specifics such as addresses, function names and parameters, and code or commands where useful. Skip basic explanations.",
+            ),
+        }
+    }
+
+    /// Verbosity the preset implies; `None` keeps the user's own verbosity setting
+    pub fn verbosity(&self) -> Option<VerbosityLevel> {
+        match self {
+            PersonaPreset::Concise => Some(VerbosityLevel::Normal),
+            PersonaPreset::Detailed => Some(VerbosityLevel::Chatty),
+            PersonaPreset::Default | PersonaPreset::Playful | PersonaPreset::Technical => None,
+        }
+    }
+}
+
+impl ModelPreferences {
+    /// Verbosity sent to the model once the persona preset is taken into account
+    pub fn effective_verbosity(&self) -> VerbosityLevel {
+        self.persona.verbosity().unwrap_or_else(|| self.verbosity.clone())
+    }
+}
+
 impl VerbosityLevel {
     pub fn to_display_string(&self) -> &'static str {
         match self {
@@ -104,6 +104,11 @@ impl UserModelPreferences {
             chat_model,
             reasoning_enabled,
             verbosity,
+            show_sources: true,
+            model_aliases: Default::default(),
+            persona: Default::default(),
+            table_images: false,
+            streaming: false,
         }
     }
 }
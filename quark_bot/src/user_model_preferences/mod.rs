@@ -1,3 +1,4 @@
+pub mod aliases;
 pub mod dto;
 pub mod handler;
 pub mod callbacks; 
\ No newline at end of file
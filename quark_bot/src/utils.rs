@@ -9,10 +9,16 @@ use teloxide::{
     Bot, RequestError,
     prelude::*,
     sugar::request::RequestReplyExt,
-    types::{ChatId, InlineKeyboardMarkup, KeyboardMarkup, MessageId, ParseMode, UserId},
+    types::{
+        ChatId, InlineKeyboardMarkup, KeyboardMarkup, LinkPreviewOptions, MessageId, ParseMode,
+        ThreadId, UserId,
+    },
 };
 
-use crate::dependencies::BotDependencies;
+use crate::{
+    dependencies::BotDependencies, group_budget::pricing::estimate_usd, profile,
+    rate_limiter::SendLimiter,
+};
 
 pub enum KeyboardMarkupType {
     InlineKeyboardType(InlineKeyboardMarkup),
@@ -25,6 +31,40 @@ pub fn format_timestamp(timestamp: u64) -> String {
     datetime.format("%Y-%m-%d at %H:%M UTC").to_string()
 }
 
+/// Build a t.me link to a message in a supergroup, if the chat supports links
+pub fn group_message_link(chat_id: ChatId, message_id: i32) -> Option<reqwest::Url> {
+    // Supergroup ids are -100 followed by the internal channel id
+    let internal_id = chat_id.0.to_string();
+    let internal_id = internal_id.strip_prefix("-100")?;
+    reqwest::Url::parse(&format!("https://t.me/c/{}/{}", internal_id, message_id)).ok()
+}
+
+/// Render an address as a PNG QR code in memory, sized to stay sharp in Telegram previews
+pub fn wallet_qr_png(address: &str) -> anyhow::Result<Vec<u8>> {
+    let code = qrcode::QrCode::new(address.as_bytes())?;
+    let image = code
+        .render::<image::Luma<u8>>()
+        .min_dimensions(400, 400)
+        .build();
+
+    let mut png = Vec::new();
+    image::DynamicImage::ImageLuma8(image)
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Parse a message id from a raw id or a t.me message link
+pub fn parse_message_reference(reference: &str) -> Option<i32> {
+    let reference = reference.trim().trim_end_matches('/');
+    let last_segment = reference
+        .split('?')
+        .next()
+        .unwrap_or(reference)
+        .rsplit('/')
+        .next()?;
+    last_segment.parse::<i32>().ok().filter(|id| *id > 0)
+}
+
 /// Helper function to format time duration in a human-readable way
 pub fn format_time_duration(seconds: u64) -> String {
     let hours = seconds / 3600;
@@ -43,6 +83,20 @@ pub fn format_time_duration(seconds: u64) -> String {
     }
 }
 
+/// Byte count as B / KB / MB for file listings
+pub fn format_file_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    let size = bytes as f64;
+    if size >= MB {
+        format!("{:.1} MB", size / MB)
+    } else if size >= KB {
+        format!("{:.1} KB", size / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
 /// Get emoji icon based on file extension
 pub fn get_file_icon(filename: &str) -> &'static str {
     let extension = filename.split('.').last().unwrap_or("").to_lowercase();
@@ -209,6 +263,15 @@ pub async fn create_purchase_request(
     user_id: Option<String>,
     bot_deps: BotDependencies,
 ) -> Result<(), anyhow::Error> {
+    if !profile::current().billing_enabled() {
+        log::debug!(
+            "Billing disabled for profile {}; skipping purchase of {} tokens",
+            profile::current().as_str(),
+            total_tokens_used
+        );
+        return Ok(());
+    }
+
     // Resolve currency/version from user or group prefs; fallback to on-chain default
     let (currency, coin_version) = if let Some(gid) = &group_id {
         let key = gid.clone();
@@ -260,6 +323,14 @@ pub async fn create_purchase_request(
         });
     };
 
+    // Group spend counts toward the group's monthly AI budget once the purchase goes through
+    let budget_chat = group_id
+        .as_deref()
+        .and_then(|gid| gid.parse::<i64>().ok())
+        .map(ChatId);
+    let estimated_usd = budget_chat
+        .and_then(|_| estimate_usd(&model, total_tokens_used, &tools_used));
+
     if group_id.is_some() {
         let group_id_result = group_id.unwrap();
         let group_id_with_seed = format!("{}-{}", group_id_result, account_seed);
@@ -288,7 +359,14 @@ pub async fn create_purchase_request(
     };
 
     match response {
-        Ok(_) => Ok(()),
+        Ok(_) => {
+            if let (Some(chat_id), Some(usd)) = (budget_chat, estimated_usd) {
+                if let Err(e) = bot_deps.group_budgets.record_spend(chat_id, usd) {
+                    log::error!("Failed to record AI spend for group {}: {}", chat_id, e);
+                }
+            }
+            Ok(())
+        }
         Err(e) => {
             log::error!("Error purchasing tokens: {}", e);
             Err(e)
@@ -308,9 +386,23 @@ pub async fn is_admin(bot: &Bot, chat_id: ChatId, user_id: UserId) -> bool {
     is_admin
 }
 
+/// Forum topic a message was posted in. Regular groups also set `thread_id` on replies,
+/// but only forum topics accept it back as `message_thread_id`.
+pub fn topic_thread_id(msg: &Message) -> Option<ThreadId> {
+    if msg.is_topic_message {
+        msg.thread_id
+    } else {
+        None
+    }
+}
+
 pub async fn send_message(msg: Message, bot: Bot, text: String) -> Result<(), anyhow::Error> {
     if msg.chat.is_group() || msg.chat.is_supergroup() {
-        bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+        let mut request = bot.send_message(msg.chat.id, text).reply_to(msg.id);
+        if let Some(thread_id) = topic_thread_id(&msg) {
+            request = request.message_thread_id(thread_id);
+        }
+        request.await?;
     } else {
         bot.send_message(msg.chat.id, text).await?;
     }
@@ -319,30 +411,57 @@ pub async fn send_message(msg: Message, bot: Bot, text: String) -> Result<(), an
 }
 
 pub async fn send_html_message(msg: Message, bot: Bot, text: String) -> Result<(), anyhow::Error> {
+    send_html_message_with_previews(msg, bot, text, true).await
+}
+
+/// `send_html_message` with control over Telegram's link preview card
+pub async fn send_html_message_with_previews(
+    msg: Message,
+    bot: Bot,
+    text: String,
+    link_previews: bool,
+) -> Result<(), anyhow::Error> {
+    let mut request = bot
+        .send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html);
+    if !link_previews {
+        request = request.link_preview_options(disabled_link_preview());
+    }
     if msg.chat.is_group() || msg.chat.is_supergroup() {
-        bot.send_message(msg.chat.id, text)
-            .parse_mode(ParseMode::Html)
-            .reply_to(msg.id)
-            .await?;
-    } else {
-        bot.send_message(msg.chat.id, text)
-            .parse_mode(ParseMode::Html)
-            .await?;
+        request = request.reply_to(msg.id);
+        if let Some(thread_id) = topic_thread_id(&msg) {
+            request = request.message_thread_id(thread_id);
+        }
     }
+    request.await?;
 
     Ok(())
 }
 
+pub fn disabled_link_preview() -> LinkPreviewOptions {
+    LinkPreviewOptions {
+        is_disabled: true,
+        url: None,
+        prefer_small_media: false,
+        prefer_large_media: false,
+        show_above_text: false,
+    }
+}
+
 pub async fn send_markdown_message(
     msg: Message,
     bot: Bot,
     text: String,
 ) -> Result<(), anyhow::Error> {
     if msg.chat.is_group() || msg.chat.is_supergroup() {
-        bot.send_message(msg.chat.id, text)
+        let mut request = bot
+            .send_message(msg.chat.id, text)
             .parse_mode(ParseMode::MarkdownV2)
-            .reply_to(msg.id)
-            .await?;
+            .reply_to(msg.id);
+        if let Some(thread_id) = topic_thread_id(&msg) {
+            request = request.message_thread_id(thread_id);
+        }
+        request.await?;
     } else {
         bot.send_message(msg.chat.id, text)
             .parse_mode(ParseMode::MarkdownV2)
@@ -357,14 +476,19 @@ pub async fn send_scheduled_message(
     text: &str,
     thread_id: Option<i32>,
 ) -> Result<Message, RequestError> {
-    // For scheduled messages, send to thread if thread_id is available
-    let mut request = bot.send_message(chat_id, text).parse_mode(ParseMode::Html);
-
-    if let Some(thread) = thread_id {
-        request = request.reply_to(MessageId(thread));
-    }
+    // Scheduled jobs fan out across many groups, so pace them through the shared limiter
+    SendLimiter::global()
+        .send(|| {
+            // For scheduled messages, send to thread if thread_id is available
+            let mut request = bot.send_message(chat_id, text).parse_mode(ParseMode::Html);
+
+            if let Some(thread) = thread_id {
+                request = request.reply_to(MessageId(thread));
+            }
 
-    request.await
+            request.send()
+        })
+        .await
 }
 
 pub async fn send_markdown_message_with_keyboard(
@@ -391,11 +515,15 @@ pub async fn reply_markup(
     text: &str,
 ) -> Result<(), RequestError> {
     if msg.chat.is_group() || msg.chat.is_supergroup() {
-        bot.send_message(msg.chat.id, text)
+        let mut request = bot
+            .send_message(msg.chat.id, text)
             .parse_mode(ParseMode::Html)
             .reply_markup(keyboard_markup)
-            .reply_to(msg.id)
-            .await?;
+            .reply_to(msg.id);
+        if let Some(thread_id) = topic_thread_id(&msg) {
+            request = request.message_thread_id(thread_id);
+        }
+        request.await?;
     } else {
         bot.send_message(msg.chat.id, text)
             .parse_mode(ParseMode::Html)
@@ -412,11 +540,15 @@ pub async fn reply_inline_markup(
     text: &str,
 ) -> Result<(), RequestError> {
     if msg.chat.is_group() || msg.chat.is_supergroup() {
-        bot.send_message(msg.chat.id, text)
+        let mut request = bot
+            .send_message(msg.chat.id, text)
             .parse_mode(ParseMode::Html)
             .reply_markup(keyboard_markup)
-            .reply_to(msg.id)
-            .await?;
+            .reply_to(msg.id);
+        if let Some(thread_id) = topic_thread_id(&msg) {
+            request = request.message_thread_id(thread_id);
+        }
+        request.await?;
     } else {
         bot.send_message(msg.chat.id, text)
             .parse_mode(ParseMode::Html)
@@ -445,6 +577,9 @@ pub async fn send_markdown_message_with_keyboard_with_reply(
 
     if msg.chat.is_group() || msg.chat.is_supergroup() {
         request = request.reply_to(msg.id);
+        if let Some(thread_id) = topic_thread_id(&msg) {
+            request = request.message_thread_id(thread_id);
+        }
     }
 
     request.await.map_err(|e| e.into())
@@ -457,14 +592,54 @@ pub async fn send_scheduled_message_with_keyboard(
     thread_id: Option<i32>,
     keyboard: InlineKeyboardMarkup,
 ) -> Result<Message, RequestError> {
-    let mut request = bot
-        .send_message(chat_id, text)
-        .parse_mode(ParseMode::Html)
-        .reply_markup(keyboard);
+    SendLimiter::global()
+        .send(|| {
+            let mut request = bot
+                .send_message(chat_id, text)
+                .parse_mode(ParseMode::Html)
+                .reply_markup(keyboard.clone());
+
+            if let Some(thread) = thread_id {
+                request = request.reply_to(MessageId(thread));
+            }
+
+            request.send()
+        })
+        .await
+}
 
-    if let Some(thread) = thread_id {
-        request = request.reply_to(MessageId(thread));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn group_message(is_topic_message: bool) -> Message {
+        serde_json::from_value(json!({
+            "message_id": 100,
+            "message_thread_id": 42,
+            "is_topic_message": is_topic_message,
+            "date": 1_700_000_000,
+            "chat": {"id": -1001234567890i64, "type": "supergroup", "title": "Forum", "is_forum": true},
+            "from": {"id": 7, "is_bot": false, "first_name": "Ann"},
+            "text": "hello"
+        }))
+        .unwrap()
     }
 
-    request.await
+    #[test]
+    fn test_topic_thread_id() {
+        assert_eq!(
+            topic_thread_id(&group_message(true)),
+            Some(ThreadId(MessageId(42)))
+        );
+        // Reply chains in regular groups carry a thread id too, but it isn't a topic
+        assert_eq!(topic_thread_id(&group_message(false)), None);
+    }
+
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(5 * 1024 * 1024), "5.0 MB");
+    }
 }
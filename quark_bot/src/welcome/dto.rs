@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use teloxide::types::{ChatId, UserId};
 
+/// Choices for how long a verified member may rejoin without verifying again; 0 is off
+pub const REMEMBER_VERIFIED_DAYS_OPTIONS: [u32; 4] = [0, 7, 30, 90];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WelcomeSettings {
     pub enabled: bool,
@@ -9,6 +12,9 @@ pub struct WelcomeSettings {
     pub verification_success_count: u64,
     pub verification_failure_count: u64,
     pub last_updated: i64, // unix timestamp
+    /// Members who verified within this many days skip verification on rejoin; 0 is off
+    #[serde(default)]
+    pub remember_verified_days: u32,
 }
 
 impl Default for WelcomeSettings {
@@ -20,6 +26,7 @@ impl Default for WelcomeSettings {
             verification_success_count: 0,
             verification_failure_count: 0,
             last_updated: chrono::Utc::now().timestamp(),
+            remember_verified_days: 0,
         }
     }
 }
@@ -8,7 +8,11 @@ use teloxide::{
 use crate::{
     dependencies::BotDependencies,
     utils::{self, send_html_message, send_message},
-    welcome::{helpers::format_timeout_display, welcome_service::WelcomeService},
+    welcome::{
+        dto::REMEMBER_VERIFIED_DAYS_OPTIONS,
+        helpers::{format_remember_verified_display, format_timeout_display},
+        welcome_service::WelcomeService,
+    },
 };
 
 pub async fn handle_welcome_settings_callback(
@@ -61,6 +65,27 @@ pub async fn handle_welcome_settings_callback(
         "welcome_set_custom_message" => {
             start_custom_message_input(bot.clone(), msg, welcome_service).await?;
         }
+        "welcome_remember" => {
+            show_remember_verified_menu(bot.clone(), msg, welcome_service).await?;
+        }
+        "welcome_forget_verified" => {
+            let cleared = welcome_service.clear_verified_members(msg.chat.id)?;
+            bot.answer_callback_query(query.id)
+                .text(format!(
+                    "🧹 Forgot {} verified member{}",
+                    cleared,
+                    if cleared == 1 { "" } else { "s" }
+                ))
+                .await?;
+            show_remember_verified_menu(bot.clone(), msg, welcome_service).await?;
+            return Ok(());
+        }
+        _ if data.starts_with("welcome_remember_set_") => {
+            let days = data.strip_prefix("welcome_remember_set_").unwrap();
+            if let Ok(days) = days.parse::<u32>() {
+                set_remember_verified_days(bot.clone(), msg, welcome_service, days).await?;
+            }
+        }
         _ if data.starts_with("welcome_timeout_set_") => {
             let timeout = data.strip_prefix("welcome_timeout_set_").unwrap();
             if let Ok(timeout_seconds) = timeout.parse::<u64>() {
@@ -104,12 +129,14 @@ async fn show_welcome_settings_menu(
         "👋 <b>Welcome Settings</b>\n\n\
         📊 Status: {}\n\
         ⏰ Verification Timeout: {}\n\
+        🔁 Returning Members: {}\n\
         📈 Success Rate: {:.1}%\n\
         ✅ Total Verifications: {}\n\
         ❌ Failed Verifications: {}\n\n\
         Configure anti-spam protection for new group members.",
         status_text,
         timeout_text,
+        format_remember_verified_display(settings.remember_verified_days),
         stats.success_rate,
         stats.total_verifications,
         stats.failed_verifications
@@ -132,6 +159,10 @@ async fn show_welcome_settings_menu(
             "⏰ Set Timeout",
             "welcome_timeout",
         )],
+        vec![InlineKeyboardButton::callback(
+            "🔁 Returning Members",
+            "welcome_remember",
+        )],
         vec![InlineKeyboardButton::callback(
             "📊 View Statistics",
             "welcome_stats",
@@ -289,6 +320,83 @@ async fn set_welcome_timeout(
     Ok(())
 }
 
+async fn show_remember_verified_menu(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+) -> Result<()> {
+    let settings = welcome_service.get_settings(msg.chat.id);
+    let remembered = welcome_service.verified_member_count(msg.chat.id);
+
+    let text = format!(
+        "🔁 <b>Returning Members</b>\n\n\
+        Members who passed verification can rejoin without verifying again for a while.\n\n\
+        Current window: {}\n\
+        Remembered members: {}\n\n\
+        Select how long to remember verified members:",
+        format_remember_verified_display(settings.remember_verified_days),
+        remembered
+    );
+
+    let options = REMEMBER_VERIFIED_DAYS_OPTIONS
+        .iter()
+        .map(|days| {
+            let label = format_remember_verified_display(*days);
+            InlineKeyboardButton::callback(
+                if *days == settings.remember_verified_days {
+                    format!("✅ {}", label)
+                } else {
+                    label
+                },
+                format!("welcome_remember_set_{}", days),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        options,
+        vec![InlineKeyboardButton::callback(
+            "🧹 Forget Verified Members",
+            "welcome_forget_verified",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "↩️ Back",
+            "welcome_back_to_main",
+        )],
+    ]);
+
+    match bot
+        .edit_message_text(msg.chat.id, msg.id, text)
+        .parse_mode(ParseMode::Html)
+        .reply_markup(keyboard)
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if e.to_string().contains("message is not modified") => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to edit message: {}", e)),
+    }
+
+    Ok(())
+}
+
+async fn set_remember_verified_days(
+    bot: Bot,
+    msg: &Message,
+    welcome_service: WelcomeService,
+    days: u32,
+) -> Result<()> {
+    let mut settings = welcome_service.get_settings(msg.chat.id);
+    settings.remember_verified_days = days;
+    settings.last_updated = chrono::Utc::now().timestamp();
+
+    welcome_service.save_settings(msg.chat.id, settings)?;
+
+    // Refresh the menu
+    show_remember_verified_menu(bot, msg, welcome_service).await?;
+
+    Ok(())
+}
+
 async fn show_welcome_stats(
     bot: Bot,
     msg: &Message,
@@ -452,6 +560,10 @@ async fn show_main_group_settings(bot: Bot, msg: &Message) -> Result<()> {
             "📋 Summarization Settings",
             "open_group_summarization_settings",
         )],
+        vec![InlineKeyboardButton::callback(
+            "🧠 History Settings",
+            "open_history_settings",
+        )],
         vec![InlineKeyboardButton::callback(
             "🔄 Migrate Group ID",
             "open_migrate_group_id",
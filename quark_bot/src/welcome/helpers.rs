@@ -48,6 +48,14 @@ pub fn format_timeout_display(seconds: u64) -> String {
     }
 }
 
+pub fn format_remember_verified_display(days: u32) -> String {
+    match days {
+        0 => "Off".to_string(),
+        1 => "1 day".to_string(),
+        _ => format!("{} days", days),
+    }
+}
+
 pub fn is_verification_expired(timestamp: i64) -> bool {
     chrono::Utc::now().timestamp() > timestamp
 }
@@ -4,7 +4,7 @@ use anyhow::Result;
 use sled::Tree;
 use teloxide::{
     prelude::*,
-    types::{ChatId, ChatPermissions, InlineKeyboardButton, InlineKeyboardMarkup, UserId},
+    types::{ChatId, ChatPermissions, InlineKeyboardButton, InlineKeyboardMarkup, ThreadId, UserId},
 };
 
 use crate::welcome::{
@@ -20,6 +20,8 @@ pub struct WelcomeService {
     settings_db: Tree,
     verifications_db: Tree,
     stats_db: Tree,
+    /// When each member last passed verification, for the rejoin bypass
+    verified_members_db: Tree,
     account_seed: String,
 }
 
@@ -34,6 +36,9 @@ impl WelcomeService {
         let stats_db = db
             .open_tree("welcome_stats")
             .expect("Failed to open welcome stats tree");
+        let verified_members_db = db
+            .open_tree("welcome_verified_members")
+            .expect("Failed to open welcome verified members tree");
 
         let account_seed: String =
             env::var("ACCOUNT_SEED").expect("ACCOUNT_SEED environment variable not found");
@@ -42,6 +47,7 @@ impl WelcomeService {
             settings_db,
             verifications_db,
             stats_db,
+            verified_members_db,
             account_seed,
         }
     }
@@ -70,6 +76,59 @@ impl WelcomeService {
         self.get_settings(chat_id).enabled
     }
 
+    fn verified_members_prefix(&self, chat_id: ChatId) -> String {
+        format!("{}-{}:", chat_id.0, self.account_seed)
+    }
+
+    fn remember_verified(&self, chat_id: ChatId, user_id: UserId) -> Result<()> {
+        let key = format!("{}{}", self.verified_members_prefix(chat_id), user_id.0);
+        let now = chrono::Utc::now().timestamp();
+        self.verified_members_db
+            .insert(key.as_bytes(), serde_json::to_vec(&now)?)?;
+        Ok(())
+    }
+
+    /// True when the member verified recently enough to skip verification; stale records are dropped
+    fn recently_verified(&self, chat_id: ChatId, user_id: UserId, remember_days: u32) -> bool {
+        let key = format!("{}{}", self.verified_members_prefix(chat_id), user_id.0);
+        let Some(verified_at) = self
+            .verified_members_db
+            .get(key.as_bytes())
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_slice::<i64>(&v).ok())
+        else {
+            return false;
+        };
+        let window = remember_days as i64 * 24 * 60 * 60;
+        if chrono::Utc::now().timestamp() - verified_at <= window {
+            return true;
+        }
+        let _ = self.verified_members_db.remove(key.as_bytes());
+        false
+    }
+
+    /// How many members are remembered for this group; the members themselves are never listed
+    pub fn verified_member_count(&self, chat_id: ChatId) -> usize {
+        self.verified_members_db
+            .scan_prefix(self.verified_members_prefix(chat_id).as_bytes())
+            .count()
+    }
+
+    /// Forget every remembered member of this group, so all of them verify on their next join
+    pub fn clear_verified_members(&self, chat_id: ChatId) -> Result<usize> {
+        let keys: Vec<_> = self
+            .verified_members_db
+            .scan_prefix(self.verified_members_prefix(chat_id).as_bytes())
+            .keys()
+            .filter_map(|k| k.ok())
+            .collect();
+        for key in &keys {
+            self.verified_members_db.remove(key)?;
+        }
+        Ok(keys.len())
+    }
+
     pub async fn handle_new_member(
         &self,
         bot: &Bot,
@@ -77,6 +136,7 @@ impl WelcomeService {
         user_id: UserId,
         username: Option<String>,
         first_name: String,
+        thread_id: Option<ThreadId>,
     ) -> Result<()> {
         if !self.is_enabled(chat_id) {
             return Ok(());
@@ -100,6 +160,18 @@ impl WelcomeService {
 
         let settings = self.get_settings(chat_id);
 
+        if settings.remember_verified_days > 0
+            && self.recently_verified(chat_id, user_id, settings.remember_verified_days)
+        {
+            log::info!(
+                "User {} verified in chat {} within the last {} days, skipping verification",
+                user_id,
+                chat_id,
+                settings.remember_verified_days
+            );
+            return Ok(());
+        }
+
         // Mute the new member immediately
         let restricted_permissions = ChatPermissions::empty();
         bot.restrict_chat_member(chat_id, user_id, restricted_permissions)
@@ -123,11 +195,15 @@ impl WelcomeService {
         // Prefer the user's actual @username for a clickable mention; fall back to first name
         let username_for_message = username.as_deref().unwrap_or(&first_name);
         let welcome_text = get_custom_welcome_message(&settings, username_for_message, &group_name);
-        let message = bot
+        let mut request = bot
             .send_message(chat_id, welcome_text)
             .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-            .reply_markup(keyboard)
-            .await?;
+            .reply_markup(keyboard);
+        // Keep the prompt in the forum topic the join was announced in
+        if let Some(thread_id) = thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        let message = request.await?;
 
         // Store pending verification
         let verification = PendingVerification {
@@ -304,6 +380,17 @@ impl WelcomeService {
             ));
         }
 
+        if self.get_settings(chat_id).remember_verified_days > 0 {
+            if let Err(e) = self.remember_verified(chat_id, user_id) {
+                log::warn!(
+                    "Failed to remember verified user {} in chat {}: {}",
+                    user_id,
+                    chat_id,
+                    e
+                );
+            }
+        }
+
         log::info!(
             "Updating statistics for user {} in chat {}",
             user_id.to_string(),
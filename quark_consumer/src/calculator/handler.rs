@@ -1,18 +1,10 @@
-use super::dto::{Price, ToolName};
 use crate::error::ConsumerError;
-use quark_core::helpers::dto::{AITool, PriceCoin, ToolUsage};
+use quark_core::helpers::dto::{PriceCoin, ToolUsage};
+use quark_core::helpers::prices::{Price, ToolName};
 use reqwest::Client;
 use ron::de::from_str;
 use std::fs;
 
-fn ai_tool_to_tool_name(ai_tool: &AITool) -> ToolName {
-    match ai_tool {
-        AITool::FileSearch => ToolName::FileSearch,
-        AITool::ImageGeneration => ToolName::ImageGeneration,
-        AITool::WebSearchPreview => ToolName::WebSearchPreview,
-    }
-}
-
 pub async fn get_price(
     path: &str,
     panora_url: &str,
@@ -83,7 +75,7 @@ pub async fn get_price(
     let price_tools: f64 = tool_usage
         .iter()
         .filter_map(|tool| {
-            let tool_name = ai_tool_to_tool_name(&tool.tool);
+            let tool_name = ToolName::from(&tool.tool);
             price
                 .tool
                 .iter()
@@ -2,23 +2,28 @@ mod admin;
 mod calculator;
 mod error;
 mod purchase;
+mod retry;
 
 use aptos_rust_sdk::client::builder::AptosClientBuilder;
 use aptos_rust_sdk::client::config::AptosNetwork;
 use aptos_rust_sdk_types::api_types::address::AccountAddress;
 use aptos_rust_sdk_types::api_types::chain_id::ChainId;
 use error::{ConsumerError, ConsumerResult};
-use quark_core::helpers::dto::PurchaseMessage;
+use quark_core::helpers::{dto::PurchaseMessage, queues::PURCHASE_QUEUE};
 use redis::{AsyncCommands, Client};
 use reqwest::Client as ReqClient;
 use serde_json;
 use std::env;
+use std::future::Future;
 use std::str::FromStr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::calculator::handler::get_price;
 use crate::purchase::dto::{Purchase, PurchaseType};
 use crate::purchase::handler::purchase_ai;
+use crate::retry::handler::{
+    PROMOTE_INTERVAL, now_ms, park, pending_retry_at, promote_due, requeue_or_dead_letter,
+};
 
 async fn connect_to_redis_with_retry(redis_url: &str) -> redis::aio::MultiplexedConnection {
     let mut retry_count = 0;
@@ -68,6 +73,28 @@ async fn connect_to_redis_with_retry(redis_url: &str) -> redis::aio::Multiplexed
     }
 }
 
+/// `DRY_RUN` turns the consumer into a validator: anything but empty, `0` or `false` enables it
+fn dry_run_enabled(value: Option<&str>) -> bool {
+    match value.map(|v| v.trim().to_ascii_lowercase()) {
+        Some(v) => !v.is_empty() && v != "0" && v != "false",
+        None => false,
+    }
+}
+
+/// Hand `purchase` to `submit`, or only log it in dry-run mode.
+/// Returns `None` when nothing was submitted.
+async fn submit_purchase<F, Fut, T>(purchase: Purchase, dry_run: bool, submit: F) -> Option<T>
+where
+    F: FnOnce(Purchase) -> Fut,
+    Fut: Future<Output = T>,
+{
+    if dry_run {
+        println!("[dry-run] Would purchase: {:?}", purchase);
+        return None;
+    }
+    Some(submit(purchase).await)
+}
+
 async fn process_message_with_retry(
     redis_connection: &mut redis::aio::MultiplexedConnection,
     message: String,
@@ -77,13 +104,19 @@ async fn process_message_with_retry(
     path: &str,
     panora_url: &str,
     panora_api_key: &str,
+    dry_run: bool,
 ) -> ConsumerResult<()> {
     let purchase: PurchaseMessage = serde_json::from_str(&message)
         .map_err(|e| ConsumerError::InvalidMessage(format!("Failed to parse message: {}", e)))?;
 
+    // Still backing off after an earlier failure; wait in the delayed set instead
+    if let Some(retry_at) = pending_retry_at(&purchase, now_ms()) {
+        return park(redis_connection, message, retry_at).await;
+    }
+
     let model_name = purchase.model.to_string();
     let total_tokens = purchase.tokens_used;
-    let tool_usage = purchase.tools_used;
+    let tool_usage = purchase.tools_used.clone();
     let client = ReqClient::builder()
         .user_agent("quark-consumer/1.0")
         .build()
@@ -103,59 +136,50 @@ async fn process_message_with_retry(
     )
     .await;
 
-    if price.is_err() {
-        eprintln!("Error getting price: {:?}", price.err());
-
-        // Try to requeue the message
-        let _: () = redis_connection
-            .lpush("purchase", message)
-            .await
-            .map_err(|e| {
-                ConsumerError::InvalidMessage(format!("Failed to push message to Redis: {}", e))
-            })?;
+    let (amount, token_address) = match price {
+        Ok(price) => price,
+        Err(e) => {
+            eprintln!("Error getting price: {:?}", e);
+            requeue_or_dead_letter(redis_connection, purchase, &format!("Price: {}", e)).await?;
 
-        return Err(ConsumerError::InvalidMessage(
-            "Failed to get price".to_string(),
-        ));
-    }
-
-    let purchase_type = if purchase.group_id.is_some() {
-        PurchaseType::Group(purchase.group_id.unwrap())
-    } else {
-        PurchaseType::User(purchase.account_address)
+            return Err(ConsumerError::InvalidMessage(
+                "Failed to get price".to_string(),
+            ));
+        }
     };
 
-    let (amount, token_address) = price.unwrap();
+    let purchase_type = match &purchase.group_id {
+        Some(group_id) => PurchaseType::Group(group_id.clone()),
+        None => PurchaseType::User(purchase.account_address.clone()),
+    };
 
     let purchase_query = Purchase::from((
         purchase_type,
         contract_address,
-        purchase.coin_version,
+        purchase.coin_version.clone(),
         amount,
         token_address,
         node.clone(),
         chain_id,
     ));
 
-    let transaction_response = purchase_ai(purchase_query).await;
-
-    if transaction_response.is_err() {
-        eprintln!("Error purchasing: {:?}", transaction_response.err());
-
-        // Try to requeue the message
-        let _: () = redis_connection
-            .lpush("purchase", message)
-            .await
-            .map_err(|e| {
-                ConsumerError::InvalidMessage(format!("Failed to push message to Redis: {}", e))
-            })?;
+    // In dry-run the message has already been popped, so returning here acknowledges it
+    let Some(transaction_response) = submit_purchase(purchase_query, dry_run, purchase_ai).await
+    else {
+        return Ok(());
+    };
 
-        return Err(ConsumerError::InvalidMessage(
-            "Failed to purchase".to_string(),
-        ));
-    }
+    let transaction_response = match transaction_response {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("Error purchasing: {:?}", e);
+            requeue_or_dead_letter(redis_connection, purchase, &format!("Purchase: {}", e)).await?;
 
-    let transaction_response = transaction_response.unwrap();
+            return Err(ConsumerError::InvalidMessage(
+                "Failed to purchase".to_string(),
+            ));
+        }
+    };
 
     println!("Purchased successfully: {:?}", transaction_response);
 
@@ -175,6 +199,7 @@ async fn main() -> ConsumerResult<()> {
         env::var("PANORA_URL").unwrap_or_else(|_| "https://api.panora.exchange".to_string());
     let panora_api_key = env::var("PANORA_API_KEY").unwrap_or_else(|_| "".to_string());
     let aptos_api_key = env::var("APTOS_API_KEY").unwrap_or_else(|_| "".to_string());
+    let dry_run = dry_run_enabled(env::var("DRY_RUN").ok().as_deref());
 
     let (builder, chain_id) = match network.as_str() {
         "mainnet" => (
@@ -205,6 +230,9 @@ async fn main() -> ConsumerResult<()> {
         .expect("CONTRACT_ADDRESS is not a valid account address");
 
     println!("Starting Quark Consumer...");
+    if dry_run {
+        println!("DRY_RUN is set: purchases will be priced and logged, not submitted");
+    }
     println!("Connecting to Redis");
 
     // Initial connection with retry
@@ -215,10 +243,20 @@ async fn main() -> ConsumerResult<()> {
 
     let mut consecutive_errors = 0;
     let max_consecutive_errors = 5;
+    let mut next_promotion = Instant::now();
 
     loop {
+        if Instant::now() >= next_promotion {
+            match promote_due(&mut redis_connection, now_ms()).await {
+                Ok(0) => {}
+                Ok(moved) => println!("Moved {} delayed purchases back to the queue", moved),
+                Err(e) => eprintln!("Failed to move delayed purchases: {}", e),
+            }
+            next_promotion = Instant::now() + PROMOTE_INTERVAL;
+        }
+
         match redis_connection
-            .rpop::<_, Option<String>>("purchase", None)
+            .rpop::<_, Option<String>>(PURCHASE_QUEUE, None)
             .await
         {
             Ok(outcome) => {
@@ -236,6 +274,7 @@ async fn main() -> ConsumerResult<()> {
                             &path,
                             &panora_url,
                             &panora_api_key,
+                            dry_run,
                         )
                         .await
                         {
@@ -273,3 +312,38 @@ async fn main() -> ConsumerResult<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quark_core::helpers::dto::CoinVersion;
+
+    #[test]
+    fn test_dry_run_env_values() {
+        assert!(!dry_run_enabled(None));
+        assert!(!dry_run_enabled(Some("")));
+        assert!(!dry_run_enabled(Some("false")));
+        assert!(!dry_run_enabled(Some("0")));
+        assert!(dry_run_enabled(Some("1")));
+        assert!(dry_run_enabled(Some("TRUE")));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_skips_transaction() {
+        let purchase = Purchase::from((
+            PurchaseType::User("0x1".to_string()),
+            AccountAddress::from_str("0x1").unwrap(),
+            CoinVersion::V1,
+            1_000,
+            "0x1::aptos_coin::AptosCoin".to_string(),
+            AptosClientBuilder::new(AptosNetwork::testnet()).build(),
+            ChainId::Testnet,
+        ));
+
+        let submitted = submit_purchase(purchase, true, |_| async {
+            panic!("dry-run must not submit a transaction")
+        })
+        .await;
+        assert!(submitted.is_none());
+    }
+}
@@ -1,7 +1,9 @@
 use aptos_rust_sdk::client::rest_api::AptosFullnodeClient;
 use aptos_rust_sdk_types::api_types::{address::AccountAddress, chain_id::ChainId};
 use quark_core::helpers::dto::CoinVersion;
+use std::fmt;
 
+#[derive(Debug)]
 pub enum PurchaseType {
     User(String),
     Group(String),
@@ -17,6 +19,18 @@ pub struct Purchase {
     pub chain_id: ChainId,
 }
 
+impl fmt::Debug for Purchase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Purchase")
+            .field("purchase_type", &self.purchase_type)
+            .field("contract_address", &self.contract_address)
+            .field("token_address", &self.token_address)
+            .field("coin_version", &self.coin_version)
+            .field("amount", &self.amount)
+            .finish_non_exhaustive()
+    }
+}
+
 impl
     From<(
         PurchaseType,
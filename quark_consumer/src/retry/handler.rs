@@ -0,0 +1,152 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use quark_core::helpers::{
+    dto::PurchaseMessage,
+    queues::{DEAD_LETTER_QUEUE, DELAYED_QUEUE, PURCHASE_QUEUE},
+};
+use rand::Rng;
+use redis::AsyncCommands;
+
+use crate::error::{ConsumerError, ConsumerResult};
+
+/// How often the consumer moves due purchases out of the delayed set
+pub const PROMOTE_INTERVAL: Duration = Duration::from_secs(1);
+/// Most due purchases moved back per check
+const PROMOTE_BATCH: isize = 100;
+/// Failed attempts before a purchase is moved to the dead-letter list
+pub const MAX_ATTEMPTS: u32 = 5;
+const BASE_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Exponential step for the `attempts`-th failure, scaled into its upper half by
+/// `jitter` (0.0..=1.0) so consumers retrying the same outage spread out
+pub fn backoff_delay(attempts: u32, jitter: f64) -> Duration {
+    let step = BASE_DELAY
+        .saturating_mul(2_u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_DELAY);
+    step.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+}
+
+/// When a requeued purchase may be retried, if it isn't due yet
+pub fn pending_retry_at(purchase: &PurchaseMessage, now_ms: i64) -> Option<i64> {
+    purchase.retry_at.filter(|retry_at| *retry_at > now_ms)
+}
+
+/// Park a purchase that isn't due yet in the delayed set, so the queue keeps flowing
+/// while it backs off
+pub async fn park(
+    redis_connection: &mut redis::aio::MultiplexedConnection,
+    message: String,
+    retry_at: i64,
+) -> ConsumerResult<()> {
+    redis_connection
+        .zadd::<_, _, _, ()>(DELAYED_QUEUE, message, retry_at)
+        .await
+        .map_err(|e| {
+            ConsumerError::InvalidMessage(format!("Failed to park message in Redis: {}", e))
+        })
+}
+
+/// Move delayed purchases that are due back onto the consumer's end of the queue.
+///
+/// Only the consumer whose `ZREM` removed a member pushes it, so concurrent consumers
+/// never requeue the same purchase twice. Returns how many were moved.
+pub async fn promote_due(
+    redis_connection: &mut redis::aio::MultiplexedConnection,
+    now_ms: i64,
+) -> ConsumerResult<usize> {
+    let redis_error =
+        |e: redis::RedisError| ConsumerError::InvalidMessage(format!("Redis error: {}", e));
+
+    let due: Vec<String> = redis_connection
+        .zrangebyscore_limit(DELAYED_QUEUE, "-inf", now_ms, 0, PROMOTE_BATCH)
+        .await
+        .map_err(redis_error)?;
+
+    let mut moved = 0;
+    for message in due {
+        let removed: i64 = redis_connection
+            .zrem(DELAYED_QUEUE, &message)
+            .await
+            .map_err(redis_error)?;
+        if removed == 1 {
+            redis_connection
+                .rpush::<_, _, ()>(PURCHASE_QUEUE, message)
+                .await
+                .map_err(redis_error)?;
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+/// Requeue a failed purchase with backoff, or dead-letter it once it is out of attempts.
+///
+/// Requeued messages wait in the delayed set until their backoff ends, then go back on the
+/// consumer's end of the list so they stay ahead of purchases that arrived later.
+pub async fn requeue_or_dead_letter(
+    redis_connection: &mut redis::aio::MultiplexedConnection,
+    mut purchase: PurchaseMessage,
+    reason: &str,
+) -> ConsumerResult<()> {
+    purchase.attempts += 1;
+    purchase.last_error = Some(reason.to_string());
+
+    if purchase.attempts >= MAX_ATTEMPTS {
+        purchase.retry_at = None;
+        eprintln!(
+            "Purchase for {} failed {} times, moving to {}: {}",
+            purchase
+                .group_id
+                .as_ref()
+                .unwrap_or(&purchase.account_address),
+            purchase.attempts,
+            DEAD_LETTER_QUEUE,
+            reason
+        );
+        let payload = serde_json::to_string(&purchase)?;
+        return redis_connection
+            .lpush::<_, _, ()>(DEAD_LETTER_QUEUE, payload)
+            .await
+            .map_err(|e| {
+                ConsumerError::InvalidMessage(format!("Failed to dead-letter message: {}", e))
+            });
+    }
+
+    let delay = backoff_delay(purchase.attempts, rand::rng().random());
+    let retry_at = now_ms() + delay.as_millis() as i64;
+    purchase.retry_at = Some(retry_at);
+    println!(
+        "Retrying purchase in {:?} (attempt {} of {})",
+        delay,
+        purchase.attempts + 1,
+        MAX_ATTEMPTS
+    );
+
+    park(
+        redis_connection,
+        serde_json::to_string(&purchase)?,
+        retry_at,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        assert_eq!(backoff_delay(1, 1.0), Duration::from_secs(2));
+        assert_eq!(backoff_delay(3, 1.0), Duration::from_secs(8));
+        assert_eq!(backoff_delay(3, 0.0), Duration::from_secs(4));
+        assert_eq!(backoff_delay(30, 1.0), MAX_DELAY);
+    }
+}
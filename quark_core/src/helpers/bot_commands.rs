@@ -12,40 +12,127 @@ pub enum Command {
     LoginUser,
     #[command(description = "Login as a group admin.", parse_with = "split")]
     LoginGroup,
+    #[command(
+        description = "Re-issue this group's credentials when commands keep failing (admins only).",
+        rename = "refreshgroup"
+    )]
+    RefreshGroup,
     #[command(description = "Display this text.")]
     Help,
     #[command(description = "Start a new conversation thread.")]
     NewChat,
+    #[command(description = "Drop your last /c message from the bot's context.")]
+    Forget,
     #[command(description = "Send a prompt to the bot.")]
     C(String),
+    #[command(
+        description = "List or edit your model aliases, e.g. /modelalias fast GPT5Mini, then /c:fast <prompt>.",
+        rename = "modelalias"
+    )]
+    ModelAlias(String),
     #[command(description = "Send a prompt to the bot in a group.")]
     G(String),
+    #[command(
+        description = "Resend your most recent AI-generated image.",
+        rename = "lastimage"
+    )]
+    LastImage,
     #[command(description = "Show example prompts.")]
     PromptExamples,
     #[command(description = "Open user settings menu (DM only).")]
     Usersettings,
+    #[command(description = "List open DAO proposals across your groups (DM only).")]
+    MyVotes,
+    #[command(description = "Show your personal AI usage and settings (DM only).")]
+    Stats,
+    #[command(description = "Choose which notifications the bot sends you (DM only).")]
+    Notifications,
+    #[command(description = "Create a knowledge base and switch to it, e.g. /newkb research (DM only).")]
+    NewKb(String),
+    #[command(description = "List your knowledge bases (DM only).")]
+    ListKb,
+    #[command(description = "Switch the knowledge base used for uploads and answers (DM only).")]
+    UseKb(String),
     // Sentinel control moved into Group Settings → Moderation
     #[command(
         description = "Moderate content (reply to message) and send a report to the admin if content is found to be inappropriate, muting the user in this case."
     )]
     Report,
+    #[command(
+        description = "Preview how moderation would judge some text, or a replied message, without acting on it (admins only).",
+        rename = "testmod"
+    )]
+    TestMod(String),
+    #[command(
+        description = "Pause sentinel for a while and resume it automatically, e.g. /snoozesentinel 30m (admins only).",
+        rename = "snoozesentinel"
+    )]
+    SnoozeSentinel(String),
+    #[command(
+        description = "Review recently flagged messages and what was done about them (admins only)."
+    )]
+    Flagged,
+    #[command(
+        description = "Show or set this group's monthly AI budget, e.g. /aibudget 25 or /aibudget off (admins only).",
+        rename = "aibudget"
+    )]
+    AiBudget(String),
     #[command(description = "Show core and custom rules for this group.")]
     Rules,
     #[command(description = "Get your wallet address.")]
     WalletAddress,
     #[command(description = "Get your balance of a token.")]
     Balance(String),
+    #[command(description = "List all of your non-zero token balances.")]
+    Balances,
+    #[command(description = "Send tokens described in plain words, e.g. /send 10 USDC to @alice.")]
+    Send(String),
+    #[command(
+        description = "Send your entire balance of a token, e.g. /sendmax @alice USDC.",
+        rename = "sendmax"
+    )]
+    SendMax(String),
+    #[command(description = "Show or cancel a payment that is waiting for your confirmation.")]
+    Pending,
+    #[command(
+        description = "Explain an Aptos transaction in plain English, e.g. /explaintx 0x…",
+        rename = "explaintx"
+    )]
+    ExplainTx(String),
     #[command(description = "Get the group's wallet address.")]
     GroupWalletAddress,
     #[command(description = "Get the group's balance of a token.")]
     GroupBalance(String),
+    #[command(
+        description = "List all of the group's non-zero token balances.",
+        rename = "groupbalances"
+    )]
+    GroupBalances,
+    #[command(description = "Show which group members are registered with Quark (admins only).")]
+    Members,
+    #[command(description = "Get a token's USD price, e.g. /price APT.")]
+    Price(String),
+    #[command(description = "Search DEX pools by token, e.g. /pools APT aptos.")]
+    Pools(String),
     #[command(description = "Display model pricing information.")]
     Prices,
+    #[command(description = "Display model pricing in this chat's payment token.")]
+    Rates,
     #[command(
         description = "Send a global announcement (authorized only).",
         rename = "globalannouncement"
     )]
     Announcement(String),
+    #[command(
+        description = "List, grant or revoke global announcement rights (super-admins only).",
+        rename = "announcers"
+    )]
+    Announcers(String),
+    #[command(
+        description = "Re-fetch the Panora token list and AI fees (authorized only).",
+        rename = "refreshtokens"
+    )]
+    RefreshTokens,
     #[command(description = "Schedule a recurring or one-shot group prompt (admins only).")]
     SchedulePrompt,
     #[command(description = "List active scheduled prompts (admins only).")]
@@ -54,8 +141,67 @@ pub enum Command {
     SchedulePayment,
     #[command(description = "List your scheduled token payments (group admins only).")]
     ListScheduledPayments,
+    #[command(
+        description = "Export this group's scheduled payments as CSV (group admins only).",
+        rename = "exportpayments"
+    )]
+    ExportScheduledPayments,
+    #[command(
+        description = "Show or set an extra command prefix for this group, e.g. /commandprefix ! (admins only).",
+        rename = "commandprefix"
+    )]
+    CommandPrefix(String),
     #[command(description = "Open group settings menu (admins only).")]
     Groupsettings,
+    #[command(description = "Inspect bot state for this group (admins only).")]
+    Debug,
+    #[command(
+        description = "Make the bot ignore a user in this group, or everywhere with \"global\" (admins/operators only)."
+    )]
+    Block(String),
+    #[command(description = "Remove a user from the blocklist (admins/operators only).")]
+    Unblock(String),
+    #[command(description = "List blocked users (admins/operators only).")]
+    Blocklist(String),
+    #[command(description = "Show how long the bot has been running.")]
+    Uptime,
+    #[command(description = "Show the bot's build version and commit (authorized only).")]
+    Version,
+    #[command(description = "Show the bot's non-secret configuration (authorized only).")]
+    Config,
+}
+
+/// Where a command can be used; drives the context-aware /help listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandContext {
+    Any,
+    Private,
+    Group,
+}
+
+impl CommandContext {
+    /// Context for a command name as listed by `Command::bot_commands()` (with or without `/`)
+    pub fn of(command: &str) -> Self {
+        match command.trim_start_matches('/') {
+            "loginuser" | "usersettings" | "myvotes" | "stats" | "notifications" | "newkb" | "listkb"
+            | "usekb" => CommandContext::Private,
+            "logingroup" | "refreshgroup" | "g" | "report" | "testmod" | "snoozesentinel" | "flagged" | "aibudget" | "rules" | "groupwalletaddress" | "groupbalance" | "groupbalances"
+            | "members"
+            | "scheduleprompt" | "listscheduled" | "schedulepayment"
+            | "listscheduledpayments" | "exportpayments" | "commandprefix" | "groupsettings" | "debug" => {
+                CommandContext::Group
+            }
+            _ => CommandContext::Any,
+        }
+    }
+
+    pub fn applies_to(&self, is_private: bool) -> bool {
+        match self {
+            CommandContext::Any => true,
+            CommandContext::Private => is_private,
+            CommandContext::Group => !is_private,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
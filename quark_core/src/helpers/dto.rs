@@ -61,15 +61,24 @@ pub struct PurchaseMessage {
     pub tools_used: Vec<ToolUsage>,
     pub account_address: String,
     pub group_id: Option<String>,
-}
-
-#[derive(Deserialize, Serialize, Debug, ToSchema)]
+    /// Failed processing attempts so far, set by the consumer when it requeues
+    #[serde(default)]
+    pub attempts: u32,
+    /// Unix time in milliseconds before which the consumer should not retry
+    #[serde(default)]
+    pub retry_at: Option<i64>,
+    /// Why the last attempt failed
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub struct ToolUsage {
     pub tool: AITool,
     pub calls: u32,
 }
 
-#[derive(Deserialize, Serialize, Debug, ToSchema)]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
 pub enum AITool {
     FileSearch,
     ImageGeneration,
@@ -200,6 +209,9 @@ impl From<(PurchaseRequest, String)> for PurchaseMessage {
             group_id,
             currency,
             coin_version,
+            attempts: 0,
+            retry_at: None,
+            last_error: None,
         }
     }
 }
@@ -2,4 +2,6 @@ pub mod bot_commands;
 pub mod dto;
 pub mod gpg;
 pub mod jwt;
+pub mod prices;
+pub mod queues;
 pub mod utils;
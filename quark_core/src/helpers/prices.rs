@@ -1,6 +1,11 @@
+//! AI price table shared by the consumer, which bills with it, and the bot, which
+//! estimates USD spend for group budgets.
+
 use serde::Deserialize;
 use std::fmt;
 
+use super::dto::AITool;
+
 #[derive(Debug, Deserialize)]
 pub struct Price {
     pub model: Vec<ModelEntry>,
@@ -62,3 +67,13 @@ impl fmt::Display for ToolName {
         }
     }
 }
+
+impl From<&AITool> for ToolName {
+    fn from(tool: &AITool) -> Self {
+        match tool {
+            AITool::FileSearch => ToolName::FileSearch,
+            AITool::ImageGeneration => ToolName::ImageGeneration,
+            AITool::WebSearchPreview => ToolName::WebSearchPreview,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+//! Redis keys shared by the purchase producer (quark_server), the consumer and the admin API.
+
+/// List of pending purchases: producers LPUSH, the consumer RPOPs
+pub const PURCHASE_QUEUE: &str = "purchase";
+/// List of purchases that ran out of retries, kept for inspection and manual replay
+pub const DEAD_LETTER_QUEUE: &str = "purchase_dead";
+/// Sorted set of purchases backing off, scored by their `retry_at` (unix ms)
+pub const DELAYED_QUEUE: &str = "purchase_delayed";
@@ -0,0 +1,32 @@
+use quark_core::helpers::dto::PurchaseMessage;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct DeadLetter {
+    /// Position in `purchase_dead`, 0 being the most recent failure
+    pub index: usize,
+    /// Last error recorded by the consumer, or why the entry could not be read
+    pub failure_reason: Option<String>,
+    /// `None` when the stored entry is not a valid purchase message
+    pub purchase: Option<PurchaseMessage>,
+}
+
+impl From<(usize, String)> for DeadLetter {
+    fn from(value: (usize, String)) -> Self {
+        let (index, raw) = value;
+
+        match serde_json::from_str::<PurchaseMessage>(&raw) {
+            Ok(purchase) => Self {
+                index,
+                failure_reason: purchase.last_error.clone(),
+                purchase: Some(purchase),
+            },
+            Err(e) => Self {
+                index,
+                failure_reason: Some(format!("Unreadable entry: {}", e)),
+                purchase: None,
+            },
+        }
+    }
+}
@@ -1,12 +1,22 @@
-use std::env;
+use std::{env, sync::Arc};
 
 use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
 use aptos_rust_sdk_types::api_types::{
     address::AccountAddress, transaction_authenticator::AuthenticationKey,
 };
-use quark_core::helpers::gpg::decrypt_private_key_in_memory;
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use quark_core::helpers::{
+    dto::PurchaseMessage,
+    gpg::decrypt_private_key_in_memory,
+    queues::{DEAD_LETTER_QUEUE, PURCHASE_QUEUE},
+};
+use redis::AsyncCommands;
 
-use crate::error::ErrorServer;
+use super::dto::DeadLetter;
+use crate::{error::ErrorServer, state::ServerState};
 
 pub fn get_admin() -> Result<(AccountAddress, Ed25519PrivateKey), ErrorServer> {
     let private_key = env::var("PRIVATE_KEY").expect("PRIVATE_KEY environment variable not set");
@@ -44,3 +54,100 @@ pub fn get_reviewer_priv_acc() -> Result<(AccountAddress, Ed25519PrivateKey), Er
 
     Ok((reviewer, private_key))
 }
+
+fn redis_error(e: redis::RedisError) -> ErrorServer {
+    ErrorServer {
+        status: StatusCode::INTERNAL_SERVER_ERROR.into(),
+        message: e.to_string(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/dead-letters",
+    description = "Purchases the consumer gave up on, most recent first",
+    responses(
+        (status = 200, description = "Success", body = [DeadLetter]),
+        (status = 401, description = "Unauthorized"),
+    )
+)]
+#[axum::debug_handler]
+pub async fn dead_letters(
+    State(server_state): State<Arc<ServerState>>,
+) -> Result<Json<Vec<DeadLetter>>, ErrorServer> {
+    let mut redis_client = server_state.redis_client().clone();
+
+    let entries: Vec<String> = redis_client
+        .lrange(DEAD_LETTER_QUEUE, 0, -1)
+        .await
+        .map_err(redis_error)?;
+
+    Ok(Json(
+        entries
+            .into_iter()
+            .enumerate()
+            .map(DeadLetter::from)
+            .collect(),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/dead-letters/requeue/{index}",
+    description = "Move one dead-lettered purchase back onto the purchase queue with a fresh retry budget",
+    params(("index" = usize, Path, description = "Index from GET /admin/dead-letters")),
+    responses(
+        (status = 200, description = "Success"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No entry at this index"),
+        (status = 409, description = "The entry was moved by another request"),
+        (status = 422, description = "The entry is not a valid purchase message"),
+    )
+)]
+#[axum::debug_handler]
+pub async fn requeue_dead_letter(
+    State(server_state): State<Arc<ServerState>>,
+    Path(index): Path<usize>,
+) -> Result<Json<()>, ErrorServer> {
+    let mut redis_client = server_state.redis_client().clone();
+
+    let raw: Option<String> = redis_client
+        .lindex(DEAD_LETTER_QUEUE, index as isize)
+        .await
+        .map_err(redis_error)?;
+    let raw = raw.ok_or_else(|| ErrorServer {
+        status: StatusCode::NOT_FOUND.into(),
+        message: format!("No dead letter at index {}", index),
+    })?;
+
+    let mut purchase: PurchaseMessage = serde_json::from_str(&raw).map_err(|e| ErrorServer {
+        status: StatusCode::UNPROCESSABLE_ENTITY.into(),
+        message: format!("Dead letter is not a purchase message: {}", e),
+    })?;
+
+    // Removing by value means two operators requeueing at once can't both succeed
+    let removed: usize = redis_client
+        .lrem(DEAD_LETTER_QUEUE, 1, &raw)
+        .await
+        .map_err(redis_error)?;
+    if removed == 0 {
+        return Err(ErrorServer {
+            status: StatusCode::CONFLICT.into(),
+            message: "Dead letter was already moved, list it again".to_string(),
+        });
+    }
+
+    purchase.attempts = 0;
+    purchase.retry_at = None;
+    purchase.last_error = None;
+    let message = serde_json::to_string(&purchase).unwrap();
+
+    println!("Requeueing dead letter: {}", message);
+
+    let _: () = redis_client
+        .lpush(PURCHASE_QUEUE, message)
+        .await
+        .map_err(redis_error)?;
+
+    Ok(Json(()))
+}
@@ -6,9 +6,12 @@ use crate::{
     state::ServerState,
     util::execute_transaction,
 };
+use aptos_rust_sdk::client::rest_api::AptosFullnodeClient;
 use aptos_rust_sdk_types::api_types::{
+    address::AccountAddress,
     module_id::ModuleId,
     transaction::{EntryFunction, TransactionPayload},
+    view::ViewRequest,
 };
 use axum::{
     extract::{Json, State},
@@ -21,7 +24,7 @@ use quark_core::helpers::dto::CreateGroupRequest;
     path = "/create-group",
     description = "Create group",
     responses(
-        (status = 200, description = "Success"),
+        (status = 200, description = "Success, including when the group already exists"),
         (status = 400, description = "Bad Request"),
     )
 )]
@@ -46,6 +49,12 @@ pub async fn create_group(
     let node = server_state.node();
     let chain_id = server_state.chain_id();
     let contract_address = server_state.contract_address();
+
+    if group_exists(node, contract_address, &group_id).await {
+        println!("Group already exists, skipping creation: {}", group_id);
+        return Ok(Json(()));
+    }
+
     let state = node.get_state().await.map_err(|e| ErrorServer {
         status: StatusCode::INTERNAL_SERVER_ERROR.into(),
         message: e.to_string(),
@@ -81,3 +90,26 @@ pub async fn create_group(
 
     Ok(Json(()))
 }
+
+/// Lookup failures count as "not found" so creation is still attempted
+async fn group_exists(
+    node: &AptosFullnodeClient,
+    contract_address: AccountAddress,
+    group_id: &str,
+) -> bool {
+    let response = node
+        .view_function(ViewRequest {
+            function: format!("{}::group::exist_group_id", contract_address),
+            type_arguments: vec![],
+            arguments: vec![serde_json::Value::String(group_id.to_string())],
+        })
+        .await;
+
+    match response {
+        Ok(response) => serde_json::from_value::<Vec<bool>>(response.into_inner())
+            .ok()
+            .and_then(|values| values.first().copied())
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
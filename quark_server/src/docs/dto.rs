@@ -1,10 +1,20 @@
-use crate::{info, pay_users};
+use crate::{admin, info, pay_users};
 use quark_core::helpers::dto::{PayUsersRequest, PurchaseRequest};
 use utoipa::OpenApi;
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(info::handler::info, pay_users::handler::pay_users,),
-    components(schemas(info::dto::Info, PayUsersRequest, PurchaseRequest))
+    paths(
+        info::handler::info,
+        pay_users::handler::pay_users,
+        admin::handler::dead_letters,
+        admin::handler::requeue_dead_letter,
+    ),
+    components(schemas(
+        info::dto::Info,
+        PayUsersRequest,
+        PurchaseRequest,
+        admin::dto::DeadLetter
+    ))
 )]
 pub struct ApiDoc;
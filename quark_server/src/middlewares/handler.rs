@@ -1,3 +1,5 @@
+use std::env;
+
 use axum::{extract::Request, middleware::Next, response::Response};
 use quark_core::helpers::dto::{GroupPayload, UserPayload};
 use quark_core::helpers::jwt::JwtManager;
@@ -59,3 +61,34 @@ pub async fn auth_group(mut req: Request, next: Next) -> Result<Response, ErrorS
 
     Ok(next.run(req).await)
 }
+
+/// Operator endpoints: the bearer token must equal `ADMIN_API_TOKEN`. With the variable
+/// unset every request is refused, so the admin routes are off by default.
+pub async fn auth_admin(req: Request, next: Next) -> Result<Response, ErrorServer> {
+    let expected = env::var("ADMIN_API_TOKEN").unwrap_or_default();
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .map(|token| token.replace("Bearer ", ""));
+
+    match token {
+        Some(token) if !expected.is_empty() && tokens_match(&token, &expected) => {
+            Ok(next.run(req).await)
+        }
+        _ => Err(ErrorServer {
+            message: "Unauthorized".to_string(),
+            status: 401,
+        }),
+    }
+}
+
+/// Compare without returning early on the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -5,7 +5,10 @@ use axum::{
     extract::{Json, State},
     http::StatusCode,
 };
-use quark_core::helpers::dto::{GroupPayload, PurchaseMessage, PurchaseRequest, UserPayload};
+use quark_core::helpers::{
+    dto::{GroupPayload, PurchaseMessage, PurchaseRequest, UserPayload},
+    queues::PURCHASE_QUEUE,
+};
 use redis::AsyncCommands;
 
 use crate::{error::ErrorServer, state::ServerState};
@@ -35,7 +38,7 @@ pub async fn purchase(
     println!("Purchase message: {}", message);
 
     let _: () = redis_client
-        .lpush("purchase", message)
+        .lpush(PURCHASE_QUEUE, message)
         .await
         .map_err(|e| ErrorServer {
             status: StatusCode::INTERNAL_SERVER_ERROR.into(),
@@ -67,7 +70,7 @@ pub async fn group_purchase(
     let mut redis_client = server_state.redis_client().clone();
 
     let _: () = redis_client
-        .lpush("purchase", message)
+        .lpush(PURCHASE_QUEUE, message)
         .await
         .map_err(|e| ErrorServer {
             status: StatusCode::INTERNAL_SERVER_ERROR.into(),
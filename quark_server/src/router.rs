@@ -11,11 +11,12 @@ use utoipa::OpenApi;
 use utoipa_redoc::{Redoc, Servable};
 
 use crate::{
+    admin::handler::{dead_letters, requeue_dead_letter},
     create_group::handler::create_group,
     dao::handler::create_proposal,
     docs::{dto::ApiDoc, handler::api_docs},
     info::handler::info,
-    middlewares::handler::{auth, auth_group},
+    middlewares::handler::{auth, auth_admin, auth_group},
     migration::handler::migrate_group_id,
     pay_members::handler::pay_members,
     pay_users::handler::pay_users,
@@ -127,11 +128,20 @@ pub async fn router() -> Router {
         .route("/migrate-group-id", post(migrate_group_id))
         .route_layer(middleware::from_fn(auth_group));
 
+    let auth_admin_router = Router::new()
+        .route("/admin/dead-letters", get(dead_letters))
+        .route(
+            "/admin/dead-letters/requeue/{index}",
+            post(requeue_dead_letter),
+        )
+        .route_layer(middleware::from_fn(auth_admin));
+
     Router::new()
         .merge(Redoc::with_url("/redoc", doc))
         .merge(auth_router)
         .route("/create-group", post(create_group))
         .merge(auth_group_router)
+        .merge(auth_admin_router)
         .route("/", get(info))
         .route("/docs", get(api_docs))
         .layer(TraceLayer::new_for_http())
@@ -15,6 +15,6 @@ cargo build --release
 
 # Run the download script
 echo "📥 Starting download process..."
-./target/release/quark-scripts --download
+./target/release/quark-scripts --download "$@"
 
 echo "✅ Download script completed!"
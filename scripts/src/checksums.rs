@@ -0,0 +1,36 @@
+// Manifest of expected checksums for the AI files, kept next to the scripts
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use google_cloud_storage::http::objects::Object;
+
+/// Relative to the scripts directory, like the paths in `TARGET_FILES`
+pub const MANIFEST_PATH: &str = "checksums.json";
+
+/// Filename -> base64 MD5, the same encoding GCS uses for `md5Hash`
+pub type Manifest = BTreeMap<String, String>;
+
+pub fn load_manifest() -> Result<Manifest> {
+    if !Path::new(MANIFEST_PATH).exists() {
+        return Ok(Manifest::new());
+    }
+    let content = fs::read_to_string(MANIFEST_PATH)?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", MANIFEST_PATH, e))
+}
+
+pub fn save_manifest(manifest: &Manifest) -> Result<()> {
+    fs::write(MANIFEST_PATH, serde_json::to_string_pretty(manifest)? + "\n")?;
+    Ok(())
+}
+
+/// The manifest entry wins; otherwise fall back to the checksum GCS stored for the object
+pub fn expected_checksum(manifest: &Manifest, filename: &str, remote: &Object) -> Option<String> {
+    manifest
+        .get(filename)
+        .cloned()
+        .or_else(|| remote.md5_hash.clone())
+}
@@ -1,5 +1,14 @@
 // Common constants and types shared between download and upload modules
 
+use std::env;
+
+use anyhow::Result;
+use base64::Engine;
+use google_cloud_storage::client::Client;
+use google_cloud_storage::http::objects::list::ListObjectsRequest;
+use google_cloud_storage::http::objects::Object;
+use md5::{Digest, Md5};
+
 // Define target files with flexible matching
 pub const TARGET_FILES: &[(&str, &str, &[&str])] = &[
     (
@@ -40,3 +49,38 @@ pub const TARGET_FILES: &[(&str, &str, &[&str])] = &[
 ];
 
 pub type TargetFile = (&'static str, &'static str, &'static [&'static str]);
+
+/// Files transferred at the same time unless TRANSFER_CONCURRENCY says otherwise
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+pub fn transfer_concurrency() -> usize {
+    env::var("TRANSFER_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_CONCURRENCY)
+}
+
+pub async fn list_bucket_objects(client: &Client, bucket_name: &str) -> Result<Vec<Object>> {
+    println!("🔍 Listing objects in bucket: {}", bucket_name);
+
+    let request = ListObjectsRequest {
+        bucket: bucket_name.to_string(),
+        ..Default::default()
+    };
+
+    let response = client.list_objects(&request).await?;
+
+    if let Some(items) = response.items {
+        println!("📊 Found {} objects in bucket", items.len());
+        Ok(items)
+    } else {
+        println!("📊 No objects found in bucket");
+        Ok(Vec::new())
+    }
+}
+
+/// MD5 of the content, base64-encoded the way GCS reports `md5Hash`
+pub fn md5_base64(content: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(Md5::digest(content))
+}
@@ -3,15 +3,17 @@ use std::fs;
 use std::path::Path;
 
 use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
 use google_cloud_storage::client::{Client, ClientConfig};
 use google_cloud_storage::http::objects::download::Range;
 use google_cloud_storage::http::objects::get::GetObjectRequest;
-use google_cloud_storage::http::objects::list::ListObjectsRequest;
 use google_cloud_storage::http::objects::Object;
 
-use crate::common::TargetFile;
+use crate::checksums::{expected_checksum, load_manifest, Manifest, MANIFEST_PATH};
+use crate::common::{list_bucket_objects, md5_base64, transfer_concurrency, TargetFile};
 
-pub async fn download_files(target_files: &[TargetFile]) -> Result<()> {
+/// With `verify` a checksum mismatch fails the run; otherwise it only warns
+pub async fn download_files(target_files: &[TargetFile], verify: bool) -> Result<()> {
     println!("🚀 Starting AI files download and replacement from Google Cloud Storage...");
 
     // Required environment variables for bucket and naming
@@ -49,6 +51,14 @@ pub async fn download_files(target_files: &[TargetFile]) -> Result<()> {
         println!("   - {} (Size: {} bytes)", obj.name, obj.size);
     }
 
+    // Load expected checksums before leaving the scripts directory
+    let manifest = load_manifest()?;
+    println!(
+        "🔐 Loaded {} expected checksums from {}",
+        manifest.len(),
+        MANIFEST_PATH
+    );
+
     // Create temporary directory for downloads
     let temp_dir = "temp_downloads";
     if Path::new(temp_dir).exists() {
@@ -59,30 +69,38 @@ pub async fn download_files(target_files: &[TargetFile]) -> Result<()> {
     // Change to temp directory
     env::set_current_dir(temp_dir)?;
 
-    // Try to download and map files
+    // Try to download and map files, a few at a time
+    let concurrency = transfer_concurrency();
+    println!("⚙️  Downloading with concurrency {}", concurrency);
+
+    let outcomes: Vec<(&TargetFile, DownloadOutcome)> = stream::iter(target_files)
+        .map(|target| {
+            let (client, bucket_name, bucket_objects, manifest) =
+                (&client, &bucket_name, &bucket_objects, &manifest);
+            async move {
+                let outcome =
+                    download_target(client, bucket_name, bucket_objects, manifest, target, verify)
+                        .await;
+                (target, outcome)
+            }
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+
     let mut downloaded_files = Vec::new();
-    
-    for (target_path, default_name, possible_names) in target_files {
-        println!("📥 Looking for file to match: {}...", default_name);
-        
-        // Try to find a matching file
-        let matching_object = find_matching_object(&bucket_objects, possible_names);
-        
-        if let Some(obj) = matching_object {
-            println!("🎯 Found matching file: {} -> {}", obj.name, default_name);
-            
-            match download_file_from_storage(&client, &bucket_name, &obj.name, default_name).await {
-                Ok(_) => {
-                    println!("✅ Successfully downloaded {}", default_name);
-                    downloaded_files.push((default_name.to_string(), target_path.to_string()));
-                }
-                Err(e) => {
-                    println!("❌ Failed to download {}: {}", default_name, e);
-                }
+    let mut mismatched_files = Vec::new();
+    let mut failed_files = Vec::new();
+    for ((target_path, default_name, _), outcome) in &outcomes {
+        match outcome {
+            DownloadOutcome::Downloaded => {
+                downloaded_files.push((default_name.to_string(), target_path.to_string()))
             }
-        } else {
-            println!("⚠️  No matching file found for {}", default_name);
-            println!("   Looking for files containing: {:?}", possible_names);
+            DownloadOutcome::Mismatched => mismatched_files.push(default_name.to_string()),
+            DownloadOutcome::NotFound => {
+                failed_files.push(format!("{} (no matching object)", default_name))
+            }
+            DownloadOutcome::Failed(e) => failed_files.push(format!("{} ({})", default_name, e)),
         }
     }
 
@@ -90,6 +108,7 @@ pub async fn download_files(target_files: &[TargetFile]) -> Result<()> {
     env::set_current_dir("..")?;
 
     // Replace files in their locations
+    let downloaded_files_count = downloaded_files.len();
     for (filename, target_path) in downloaded_files {
         let source_path = format!("{}/{}", temp_dir, filename);
         let target_path = Path::new(&target_path);
@@ -122,30 +141,79 @@ pub async fn download_files(target_files: &[TargetFile]) -> Result<()> {
     fs::remove_dir_all(temp_dir)?;
     println!("🧹 Cleaned up temporary files");
 
+    println!("📊 Download Summary:");
+    println!("   Total files: {}", target_files.len());
+    println!("   Downloaded: {}", downloaded_files_count);
+    println!("   Checksum mismatches: {}", mismatched_files.len());
+    println!("   Failed: {}", failed_files.len());
+    for failure in &failed_files {
+        println!("   ❌ {}", failure);
+    }
+
+    if !mismatched_files.is_empty() {
+        return Err(anyhow!(
+            "Checksum verification failed for {}; those files were not replaced",
+            mismatched_files.join(", ")
+        ));
+    }
+
     println!("🎉 AI files download and replacement completed!");
     Ok(())
 }
 
-async fn list_bucket_objects(
+enum DownloadOutcome {
+    Downloaded,
+    /// Only reported when verification is enforced; otherwise a mismatch just warns
+    Mismatched,
+    NotFound,
+    Failed(String),
+}
+
+/// Find, download and check one target into the current (temporary) directory
+async fn download_target(
     client: &Client,
     bucket_name: &str,
-) -> Result<Vec<Object>> {
-    println!("🔍 Listing objects in bucket: {}", bucket_name);
-    
-    let request = ListObjectsRequest {
-        bucket: bucket_name.to_string(),
-        ..Default::default()
+    bucket_objects: &[Object],
+    manifest: &Manifest,
+    target: &TargetFile,
+    verify: bool,
+) -> DownloadOutcome {
+    let (_, default_name, possible_names) = target;
+    println!("📥 Looking for file to match: {}...", default_name);
+
+    let Some(obj) = find_matching_object(bucket_objects, possible_names) else {
+        println!("⚠️  No matching file found for {}", default_name);
+        println!("   Looking for files containing: {:?}", possible_names);
+        return DownloadOutcome::NotFound;
     };
+    println!("🎯 Found matching file: {} -> {}", obj.name, default_name);
 
-    let response = client.list_objects(&request).await?;
-    
-    if let Some(items) = response.items {
-        println!("📊 Found {} objects in bucket", items.len());
-        Ok(items)
-    } else {
-        println!("📊 No objects found in bucket");
-        Ok(Vec::new())
+    let checksum = match download_file_from_storage(client, bucket_name, &obj.name, default_name).await {
+        Ok(checksum) => checksum,
+        Err(e) => {
+            println!("❌ Failed to download {}: {}", default_name, e);
+            return DownloadOutcome::Failed(e.to_string());
+        }
+    };
+    println!("✅ Successfully downloaded {}", default_name);
+
+    match expected_checksum(manifest, default_name, obj) {
+        Some(expected) if expected != checksum => {
+            println!(
+                "{} Checksum mismatch for {}: expected {}, got {}",
+                if verify { "❌" } else { "⚠️ " },
+                default_name,
+                expected,
+                checksum
+            );
+            if verify {
+                return DownloadOutcome::Mismatched;
+            }
+        }
+        Some(_) => println!("🔐 Checksum verified for {}", default_name),
+        None => println!("⚠️  No checksum available for {}", default_name),
     }
+    DownloadOutcome::Downloaded
 }
 
 fn find_matching_object<'a>(
@@ -182,7 +250,7 @@ async fn download_file_from_storage(
     bucket_name: &str,
     object_name: &str,
     filename: &str,
-) -> Result<()> {
+) -> Result<String> {
     println!("📥 Downloading {} from bucket {}...", object_name, bucket_name);
     
     let request = GetObjectRequest {
@@ -197,5 +265,5 @@ async fn download_file_from_storage(
     fs::write(filename, &response)?;
     
     println!("💾 Downloaded {} bytes for {}", response.len(), filename);
-    Ok(())
+    Ok(md5_base64(&response))
 }
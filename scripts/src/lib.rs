@@ -1,3 +1,4 @@
+pub mod checksums;
 pub mod common;
 pub mod download;
 pub mod upload;
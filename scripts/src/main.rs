@@ -13,12 +13,15 @@ fn print_usage(program: &str, opts: Options) {
     println!("\nOptions:");
     println!("  -d, --download    Download AI files from Google Cloud Storage");
     println!("  -u, --upload      Upload/update AI files to Google Cloud Storage");
+    println!("  -c, --verify      With --download, fail on checksum mismatches instead of warning");
+    println!("  -n, --dry-run     With --upload, show what would change without writing");
     println!("  -h, --help        Show this help message");
     println!("\nEnvironment variables:");
     println!("  BUCKET            Google Cloud Storage bucket name (required)");
     println!("  PROJECT_ID        Google Cloud project ID (required)");
     println!("  GOOGLE_ACCOUNT    Google Cloud account (required)");
     println!("  CLOUD_ID          Google Cloud ID (required)");
+    println!("  TRANSFER_CONCURRENCY  Files transferred at once (optional, default 4)");
 }
 
 #[tokio::main]
@@ -40,6 +43,16 @@ async fn main() -> Result<()> {
         "upload",
         "Upload/update AI files to Google Cloud Storage",
     );
+    opts.optflag(
+        "c",
+        "verify",
+        "With --download, fail on checksum mismatches instead of warning",
+    );
+    opts.optflag(
+        "n",
+        "dry-run",
+        "With --upload, show what would change without writing",
+    );
     opts.optflag("h", "help", "Show this help message");
 
     let matches = match opts.parse(&args[1..]) {
@@ -58,6 +71,8 @@ async fn main() -> Result<()> {
 
     let download_flag = matches.opt_present("d") || matches.opt_present("download");
     let upload_flag = matches.opt_present("u") || matches.opt_present("upload");
+    let verify = matches.opt_present("c") || matches.opt_present("verify");
+    let dry_run = matches.opt_present("n") || matches.opt_present("dry-run");
 
     if download_flag && upload_flag {
         eprintln!("Error: Cannot specify both download and upload options");
@@ -70,12 +85,22 @@ async fn main() -> Result<()> {
         process::exit(1);
     }
 
+    if dry_run && !upload_flag {
+        eprintln!("Error: --dry-run can only be used with the upload (-u) option");
+        process::exit(1);
+    }
+
+    if verify && !download_flag {
+        eprintln!("Error: --verify can only be used with the download (-d) option");
+        process::exit(1);
+    }
+
     if download_flag {
         println!("📥 Download mode selected");
-        download::download_files(TARGET_FILES).await?;
+        download::download_files(TARGET_FILES, verify).await?;
     } else if upload_flag {
         println!("📤 Upload/update mode selected");
-        upload::upload_files(TARGET_FILES).await?;
+        upload::upload_files(TARGET_FILES, dry_run).await?;
     }
 
     Ok(())
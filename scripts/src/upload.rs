@@ -3,14 +3,20 @@ use std::fs;
 use std::path::Path;
 
 use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
 use google_cloud_storage::client::{Client, ClientConfig};
 use google_cloud_storage::http::objects::upload::Media;
 use google_cloud_storage::http::objects::upload::UploadType;
 
-use crate::common::TargetFile;
+use crate::checksums::{load_manifest, save_manifest, MANIFEST_PATH};
+use crate::common::{list_bucket_objects, md5_base64, transfer_concurrency, TargetFile};
 
-pub async fn upload_files(target_files: &[TargetFile]) -> Result<()> {
-    println!("🚀 Starting AI files upload/update to Google Cloud Storage...");
+pub async fn upload_files(target_files: &[TargetFile], dry_run: bool) -> Result<()> {
+    if dry_run {
+        println!("🔎 Previewing AI files upload/update to Google Cloud Storage (dry run)...");
+    } else {
+        println!("🚀 Starting AI files upload/update to Google Cloud Storage...");
+    }
 
     // Required environment variables for bucket and naming
     let project_id = env::var("PROJECT_ID").unwrap_or_default();
@@ -29,27 +35,55 @@ pub async fn upload_files(target_files: &[TargetFile]) -> Result<()> {
     let client = Client::new(config);
     println!("🔗 Google Cloud Storage client created with ADC");
 
-    // Upload/update files to Google Cloud Storage
-    let mut uploaded_files = Vec::new();
-
-    for (source_path, filename, _) in target_files {
-        println!("📤 Processing file: {}...", source_path);
+    if dry_run {
+        return preview_upload(&client, &bucket_name, target_files).await;
+    }
 
-        if Path::new(source_path).exists() {
-            match upload_file_to_storage(&client, &bucket_name, source_path, filename).await {
-                Ok(_) => {
-                    println!("✅ Successfully processed {} in bucket", filename);
-                    uploaded_files.push((filename.to_string(), source_path.to_string()));
-                }
-                Err(e) => {
-                    println!("❌ Failed to process {}: {}", filename, e);
+    // Upload/update files to Google Cloud Storage, a few at a time
+    let concurrency = transfer_concurrency();
+    println!("⚙️  Uploading with concurrency {}", concurrency);
+
+    let outcomes: Vec<(&TargetFile, Result<String>)> = stream::iter(target_files)
+        .map(|target| {
+            let (client, bucket_name) = (&client, &bucket_name);
+            async move {
+                let (source_path, filename, _) = target;
+                println!("📤 Processing file: {}...", source_path);
+
+                let result = if Path::new(source_path).exists() {
+                    upload_file_to_storage(client, bucket_name, source_path, filename).await
+                } else {
+                    Err(anyhow!("source file not found: {}", source_path))
+                };
+                match &result {
+                    Ok(_) => println!("✅ Successfully processed {} in bucket", filename),
+                    Err(e) => println!("❌ Failed to process {}: {}", filename, e),
                 }
+                (target, result)
             }
-        } else {
-            println!("⚠️  Source file not found: {}", source_path);
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+
+    let mut uploaded_files = Vec::new();
+    let mut failed_files = Vec::new();
+    let mut manifest = load_manifest()?;
+    for ((source_path, filename, _), result) in outcomes {
+        match result {
+            Ok(checksum) => {
+                manifest.insert(filename.to_string(), checksum);
+                uploaded_files.push((filename.to_string(), source_path.to_string()));
+            }
+            Err(e) => failed_files.push(format!("{} ({})", filename, e)),
         }
     }
 
+    if !uploaded_files.is_empty() {
+        save_manifest(&manifest)?;
+        println!("🔐 Updated checksums in {}", MANIFEST_PATH);
+    }
+
     println!("📊 Upload/Update Summary:");
     println!("   Total files processed: {}", target_files.len());
     println!("   Successfully processed: {}", uploaded_files.len());
@@ -58,6 +92,10 @@ pub async fn upload_files(target_files: &[TargetFile]) -> Result<()> {
         target_files.len() - uploaded_files.len()
     );
 
+    for failure in &failed_files {
+        println!("   ❌ {}", failure);
+    }
+
     if !uploaded_files.is_empty() {
         println!("✅ Successfully processed files:");
         for (filename, source_path) in uploaded_files {
@@ -69,12 +107,100 @@ pub async fn upload_files(target_files: &[TargetFile]) -> Result<()> {
     Ok(())
 }
 
+/// What an upload would do to a single target
+enum PlannedChange {
+    New { size: usize },
+    Changed { local_size: usize, remote_size: i64 },
+    Unchanged,
+    MissingLocally,
+}
+
+/// Compare local files with the bucket and report what `--upload` would write
+async fn preview_upload(
+    client: &Client,
+    bucket_name: &str,
+    target_files: &[TargetFile],
+) -> Result<()> {
+    let bucket_objects = list_bucket_objects(client, bucket_name).await?;
+
+    let mut planned = Vec::new();
+    for (source_path, filename, _) in target_files {
+        let change = match fs::read(source_path) {
+            Err(_) if !Path::new(source_path).exists() => PlannedChange::MissingLocally,
+            Err(e) => return Err(anyhow!("Failed to read {}: {}", source_path, e)),
+            Ok(content) => match bucket_objects.iter().find(|obj| obj.name == *filename) {
+                None => PlannedChange::New {
+                    size: content.len(),
+                },
+                Some(remote) => {
+                    // Prefer the MD5 GCS keeps for simple uploads; fall back to size
+                    let same = match &remote.md5_hash {
+                        Some(remote_md5) => *remote_md5 == md5_base64(&content),
+                        None => remote.size == content.len() as i64,
+                    };
+                    if same {
+                        PlannedChange::Unchanged
+                    } else {
+                        PlannedChange::Changed {
+                            local_size: content.len(),
+                            remote_size: remote.size,
+                        }
+                    }
+                }
+            },
+        };
+        planned.push((*filename, *source_path, change));
+    }
+
+    println!("📋 Dry run: nothing will be written to bucket {}", bucket_name);
+    for (filename, source_path, change) in &planned {
+        match change {
+            PlannedChange::New { size } => {
+                println!("   + {} (new, {} bytes from {})", filename, size, source_path)
+            }
+            PlannedChange::Changed {
+                local_size,
+                remote_size,
+            } => println!(
+                "   ~ {} (would overwrite: {} bytes remote -> {} bytes local)",
+                filename, remote_size, local_size
+            ),
+            PlannedChange::Unchanged => println!("   = {} (unchanged)", filename),
+            PlannedChange::MissingLocally => {
+                println!("   ! {} (source file not found: {})", filename, source_path)
+            }
+        }
+    }
+
+    let count = |f: fn(&PlannedChange) -> bool| planned.iter().filter(|(_, _, c)| f(c)).count();
+    println!("📊 Dry Run Summary:");
+    println!(
+        "   New: {}",
+        count(|c| matches!(c, PlannedChange::New { .. }))
+    );
+    println!(
+        "   Would overwrite: {}",
+        count(|c| matches!(c, PlannedChange::Changed { .. }))
+    );
+    println!(
+        "   Unchanged: {}",
+        count(|c| matches!(c, PlannedChange::Unchanged))
+    );
+    println!(
+        "   Missing locally: {}",
+        count(|c| matches!(c, PlannedChange::MissingLocally))
+    );
+
+    println!("✅ Dry run completed. Run with --upload alone to apply these changes.");
+    Ok(())
+}
+
 async fn upload_file_to_storage(
     client: &Client,
     bucket_name: &str,
     source_path: &str,
     object_name: &str,
-) -> Result<()> {
+) -> Result<String> {
     println!(
         "📤 Uploading {} to bucket {} as {}...",
         source_path, bucket_name, object_name
@@ -84,6 +210,7 @@ async fn upload_file_to_storage(
     let file_content = fs::read(source_path)?;
     println!("📖 Read {} bytes from {}", file_content.len(), source_path);
 
+    let checksum = md5_base64(&file_content);
     let media = Media::new(object_name.to_string());
 
     // Upload the file using google-cloud-storage crate
@@ -103,5 +230,5 @@ async fn upload_file_to_storage(
         "💾 Successfully uploaded/updated {} in bucket {} (Object: {})",
         source_path, bucket_name, object_name
     );
-    Ok(())
+    Ok(checksum)
 }
@@ -15,6 +15,6 @@ cargo build --release
 
 # Run the upload/update script
 echo "📤 Starting upload/update process..."
-./target/release/quark-scripts --upload
+./target/release/quark-scripts --upload "$@"
 
 echo "✅ Upload/update script completed!"